@@ -0,0 +1,68 @@
+use calldata_decoder::corpus;
+use calldata_decoder::heuristics::Heuristics;
+use calldata_decoder::layout::LayoutTable;
+use calldata_decoder::limits::Limits;
+use calldata_decoder::{decode_hex, guess_param_type, guess_word_type, Calldata};
+use criterion::{criterion_group, criterion_main, BenchmarkId, Criterion, Throughput};
+
+/// Parsing, layout and type guessing, then the decode tree, on each calldata of the corpus.
+fn bench_decode(c: &mut Criterion) {
+    let mut group = c.benchmark_group("decode");
+    for (name, hex) in corpus::ALL {
+        let bytes = decode_hex(hex);
+        group.throughput(Throughput::Bytes(bytes.len() as u64));
+        group.bench_with_input(BenchmarkId::new("from_bytes", name), &bytes, |b, bytes| {
+            b.iter(|| Calldata::from_bytes(bytes))
+        });
+        group.bench_with_input(BenchmarkId::new("layout", name), &bytes, |b, bytes| {
+            let (heuristics, limits) = (Heuristics::default(), Limits::default());
+            // Whole words are counted from the selector, see `Calldata::parse_selector`.
+            let first = match bytes.len() % 32 {
+                0 => 28,
+                _ => 32,
+            };
+            b.iter(|| LayoutTable::build(&bytes[4..], first, &heuristics, &limits))
+        });
+        let calldata = Calldata::from_bytes(&bytes);
+        group.bench_with_input(BenchmarkId::new("tree", name), &calldata, |b, calldata| {
+            b.iter(|| calldata.decode())
+        });
+    }
+    group.finish();
+}
+
+/// Type guessing of every word of the corpus, from hex strings and from bytes.
+fn bench_heuristics(c: &mut Criterion) {
+    let words = corpus::ALL
+        .iter()
+        .flat_map(|(_, hex)| {
+            decode_hex(hex)[4..]
+                .chunks_exact(32)
+                .map(|w| w.to_vec())
+                .collect::<Vec<_>>()
+        })
+        .collect::<Vec<Vec<u8>>>();
+    let hex = words
+        .iter()
+        .map(|w| faster_hex::hex_string(w))
+        .collect::<Vec<String>>();
+    let heuristics = Heuristics::default();
+
+    let mut group = c.benchmark_group("heuristics");
+    group.throughput(Throughput::Elements(words.len() as u64));
+    group.bench_function("hex_words", |b| {
+        b.iter(|| hex.iter().map(|w| guess_param_type(w)).collect::<Vec<_>>())
+    });
+    group.bench_function("byte_words", |b| {
+        b.iter(|| {
+            words
+                .iter()
+                .map(|w| guess_word_type(w, &heuristics))
+                .collect::<Vec<_>>()
+        })
+    });
+    group.finish();
+}
+
+criterion_group!(benches, bench_decode, bench_heuristics);
+criterion_main!(benches);
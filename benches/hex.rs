@@ -0,0 +1,51 @@
+use calldata_decoder::{decode_hex, Calldata};
+use criterion::{criterion_group, criterion_main, BenchmarkId, Criterion, Throughput};
+
+/// A multicall-shaped payload of `words` words: offsets, lengths and nested calls.
+fn payload(words: usize) -> String {
+    let nested = "88316456000000000000000000000000c011a73ee8576fb46f5e1c5751ca3b9f";
+    let mut hex = String::from("0xac9650d8");
+    for i in 0..words {
+        match i % 4 {
+            0 => hex.push_str(&format!("{:064x}", 32 * (i % 16))),
+            1 => hex.push_str(nested),
+            2 => hex.push_str(&"ff".repeat(32)),
+            _ => hex.push_str(&format!("{:064x}", i)),
+        }
+    }
+    hex
+}
+
+/// Per-pair decoding, what `decode_hex` falls back to on malformed input.
+fn decode_pairs(calldata: &str) -> Vec<u8> {
+    let hex = calldata.trim_start_matches("0x").as_bytes();
+    hex.chunks_exact(2)
+        .map_while(|pair| {
+            let hi = (pair[0] as char).to_digit(16)?;
+            let lo = (pair[1] as char).to_digit(16)?;
+            Some((hi << 4 | lo) as u8)
+        })
+        .collect()
+}
+
+fn bench_hex(c: &mut Criterion) {
+    let mut group = c.benchmark_group("hex");
+    // A single transfer up to a multi-hundred-KB multicall.
+    for words in [2, 1_000, 10_000] {
+        let hex = payload(words);
+        group.throughput(Throughput::Bytes(hex.len() as u64));
+        group.bench_with_input(BenchmarkId::new("decode_hex", words), &hex, |b, hex| {
+            b.iter(|| decode_hex(hex))
+        });
+        group.bench_with_input(BenchmarkId::new("per_pair", words), &hex, |b, hex| {
+            b.iter(|| decode_pairs(hex))
+        });
+        group.bench_with_input(BenchmarkId::new("calldata_new", words), &hex, |b, hex| {
+            b.iter(|| Calldata::new(hex))
+        });
+    }
+    group.finish();
+}
+
+criterion_group!(benches, bench_hex);
+criterion_main!(benches);
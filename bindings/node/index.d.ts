@@ -0,0 +1,36 @@
+/** A decoded call: its selector, params and the calls nested in them. */
+export interface DecodedCalldata {
+  selector: string
+  signatureGuess: string
+  params: DecodedParam[]
+  calls: DecodedCalldata[]
+  span: Span
+  [key: string]: unknown
+}
+
+export interface DecodedParam {
+  index: number
+  raw: string
+  value: string
+  types: { kind: string; solidity: string; confidence: number }[]
+  span: Span
+  [key: string]: unknown
+}
+
+export interface Span {
+  start: number
+  end: number
+}
+
+/** The JSON output of the CLI, versioned by `schemaVersion()`. */
+export interface JsonOutput {
+  version: number
+  calldata: string
+  root: DecodedCalldata
+}
+
+/** Decodes hex calldata, with or without `0x`; throws when it isn't calldata. */
+export function decode(hex: string): JsonOutput
+
+/** Version of the objects returned by `decode`. */
+export function schemaVersion(): number
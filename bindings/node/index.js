@@ -0,0 +1,8 @@
+// Loads the addon built by `npm run build` for this platform.
+const { existsSync } = require('fs')
+const { join } = require('path')
+
+const triple = `${process.platform}-${process.arch}`
+const local = join(__dirname, `calldata-decoder.${triple}.node`)
+
+module.exports = require(existsSync(local) ? local : `calldata-decoder-${triple}`)
@@ -0,0 +1,18 @@
+const { test } = require('node:test')
+const assert = require('node:assert')
+const { decode, schemaVersion } = require('./index.js')
+
+const TRANSFER =
+  '0xa9059cbb000000000000000000000000c02aaa39b223fe8d0a0e5c4f27ead9083c756cc20000000000000000000000000000000000000000000000000de0b6b3a7640000'
+
+test('decode', () => {
+  const output = decode(TRANSFER)
+  assert.strictEqual(output.version, schemaVersion())
+  assert.strictEqual(output.root.selector, 'a9059cbb')
+  assert.strictEqual(output.root.params[0].value, '0xC02aaA39b223FE8D0A0e5C4F27eAD9083C756Cc2')
+  assert.strictEqual(output.root.params[1].value, '1000000000000000000')
+})
+
+test('invalid hex throws', () => {
+  assert.throws(() => decode('0xa9059cbz'), /'z'/)
+})
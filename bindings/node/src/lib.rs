@@ -0,0 +1,24 @@
+use calldata_decoder::schema::JsonOutput;
+use napi::{Error, Result};
+use napi_derive::napi;
+
+// ------------------------------------------------------------
+//  Node.js bindings
+// ------------------------------------------------------------
+
+/// Decodes hex calldata into the JSON output as a plain object, the shape
+/// documented on `calldata_decoder::schema::JsonOutput`.
+///
+/// ## Returns
+/// 1. The decode, or an error when the input isn't calldata or is over the default limits.
+#[napi]
+pub fn decode(hex: String) -> Result<serde_json::Value> {
+    let output = JsonOutput::from_hex(&hex).map_err(Error::from_reason)?;
+    serde_json::to_value(output).map_err(|e| Error::from_reason(e.to_string()))
+}
+
+/// Version of the JSON returned by `decode`.
+#[napi]
+pub fn schema_version() -> u32 {
+    calldata_decoder::schema::SCHEMA_VERSION
+}
@@ -0,0 +1,226 @@
+use proc_macro::TokenStream;
+use proc_macro2::TokenStream as TokenStream2;
+use quote::quote;
+use syn::{parse_macro_input, Data, DeriveInput, Error, Fields, LitInt, LitStr};
+
+// ------------------------------------------------------------
+//  #[derive(FromCalldata)]
+// ------------------------------------------------------------
+
+/// Implements `calldata_decoder::bind::FromCalldata` for a struct with named
+/// fields, each bound to a param of the call by its declared Solidity type.
+///
+/// ```ignore
+/// #[derive(FromCalldata)]
+/// #[calldata(signature = "transfer(address,uint256)")]
+/// struct Transfer {
+///     to: H160,
+///     amount: U256,
+/// }
+/// ```
+///
+/// On the struct, `signature` gives the selector calls must have and the
+/// types of the fields in order; `selector = "0x..."` only gives the selector.
+/// On a field, `ty = "uint24"` declares (or overrides) its type and
+/// `index = 2` the param it's read from, the field's position by default.
+/// Only static types (`address`, `bool`, `uintN`, `intN`, `bytesN`) bind, as
+/// they're the ones a word holds.
+#[proc_macro_derive(FromCalldata, attributes(calldata))]
+pub fn derive_from_calldata(input: TokenStream) -> TokenStream {
+    let input = parse_macro_input!(input as DeriveInput);
+    expand(input)
+        .unwrap_or_else(Error::into_compile_error)
+        .into()
+}
+
+fn expand(input: DeriveInput) -> Result<TokenStream2, Error> {
+    let Data::Struct(data) = &input.data else {
+        return Err(Error::new_spanned(
+            &input.ident,
+            "FromCalldata only derives for structs",
+        ));
+    };
+    let Fields::Named(fields) = &data.fields else {
+        return Err(Error::new_spanned(
+            &input.ident,
+            "FromCalldata needs named fields",
+        ));
+    };
+
+    // Struct attributes.
+    let mut selector = None;
+    let mut signature_types = None;
+    for attr in input.attrs.iter().filter(|a| a.path().is_ident("calldata")) {
+        attr.parse_nested_meta(|meta| {
+            let value: LitStr = meta.value()?.parse()?;
+            if meta.path.is_ident("signature") {
+                let (canonical, types) =
+                    parse_signature(&value.value()).map_err(|e| Error::new(value.span(), e))?;
+                selector = Some(selector_of(&canonical));
+                signature_types = Some((types, value.span()));
+            } else if meta.path.is_ident("selector") {
+                let hex = value.value();
+                let hex = hex.strip_prefix("0x").unwrap_or(&hex).to_lowercase();
+                if hex.len() != 8 || !hex.bytes().all(|b| b.is_ascii_hexdigit()) {
+                    return Err(Error::new(
+                        value.span(),
+                        "expected 4 bytes of hex, e.g. `0xa9059cbb`",
+                    ));
+                }
+                selector = Some(hex);
+            } else {
+                return Err(meta.error("expected `signature` or `selector`"));
+            }
+            Ok(())
+        })?;
+    }
+    if let Some((types, span)) = &signature_types {
+        if types.len() != fields.named.len() {
+            return Err(Error::new(
+                *span,
+                format!(
+                    "the signature has {} params for {} fields",
+                    types.len(),
+                    fields.named.len()
+                ),
+            ));
+        }
+    }
+
+    // Field attributes.
+    let mut inits = vec![];
+    for (position, field) in fields.named.iter().enumerate() {
+        let ident = field.ident.as_ref().expect("named field");
+        let mut ty = signature_types
+            .as_ref()
+            .map(|(types, span)| (types[position].clone(), *span));
+        let mut index = position;
+        for attr in field.attrs.iter().filter(|a| a.path().is_ident("calldata")) {
+            attr.parse_nested_meta(|meta| {
+                if meta.path.is_ident("ty") {
+                    let value: LitStr = meta.value()?.parse()?;
+                    ty = Some((value.value(), value.span()));
+                } else if meta.path.is_ident("index") {
+                    let value: LitInt = meta.value()?.parse()?;
+                    index = value.base10_parse()?;
+                } else {
+                    return Err(meta.error("expected `ty` or `index`"));
+                }
+                Ok(())
+            })?;
+        }
+        let Some((ty, span)) = ty else {
+            return Err(Error::new_spanned(
+                ident,
+                "no Solidity type: add `#[calldata(ty = \"...\")]` or a struct `signature`",
+            ));
+        };
+        let sol_type = sol_type(&ty).map_err(|e| Error::new(span, e))?;
+        let name = ident.to_string();
+        inits.push(quote! {
+            #ident: ::calldata_decoder::bind::field(call, #name, #index, #sol_type)?
+        });
+    }
+
+    let selector = match selector {
+        Some(selector) => quote! { ::core::option::Option::Some(#selector) },
+        None => quote! { ::core::option::Option::None },
+    };
+    let name = &input.ident;
+    let (impl_generics, ty_generics, where_clause) = input.generics.split_for_impl();
+    Ok(quote! {
+        impl #impl_generics ::calldata_decoder::bind::FromCalldata for #name #ty_generics #where_clause {
+            const SELECTOR: ::core::option::Option<&'static str> = #selector;
+
+            fn from_call(
+                call: &::calldata_decoder::decoded::DecodedCalldata,
+            ) -> ::core::result::Result<Self, ::calldata_decoder::bind::BindError> {
+                ::calldata_decoder::bind::check_selector(call, Self::SELECTOR)?;
+                ::core::result::Result::Ok(Self { #(#inits,)* })
+            }
+        }
+    })
+}
+
+/// The canonical form of `signature` and its param types, e.g. `uint` read as `uint256`.
+fn parse_signature(signature: &str) -> Result<(String, Vec<String>), String> {
+    let signature = signature.trim();
+    let (name, params) = signature
+        .strip_suffix(')')
+        .and_then(|s| s.split_once('('))
+        .ok_or_else(|| {
+            format!(
+                "`{}` isn't a signature like `transfer(address,uint256)`",
+                signature
+            )
+        })?;
+    if params.contains(['(', ')']) {
+        return Err("tuple params don't bind to a field".to_string());
+    }
+    let types = match params.trim() {
+        "" => vec![],
+        params => params.split(',').map(|t| canonical(t.trim())).collect(),
+    };
+    Ok((format!("{}({})", name.trim(), types.join(",")), types))
+}
+
+fn canonical(ty: &str) -> String {
+    match ty {
+        "uint" => "uint256".to_string(),
+        "int" => "int256".to_string(),
+        ty => ty.to_string(),
+    }
+}
+
+/// First 4 bytes of the keccak of `signature`, as 8 hex chars.
+fn selector_of(signature: &str) -> String {
+    use tiny_keccak::{Hasher, Keccak};
+    let mut hasher = Keccak::v256();
+    let mut out = [0u8; 32];
+    hasher.update(signature.as_bytes());
+    hasher.finalize(&mut out);
+    out[..4].iter().map(|b| format!("{:02x}", b)).collect()
+}
+
+/// The `SolType` of a static Solidity type.
+fn sol_type(ty: &str) -> Result<TokenStream2, String> {
+    let ty = canonical(ty.trim());
+    let bits = |digits: &str, max: u16, step: u16| {
+        digits
+            .parse::<u16>()
+            .ok()
+            .filter(|n| *n >= step && *n <= max && n % step == 0)
+    };
+    let dynamic = || format!("`{}` is dynamic: only static types bind to a word", ty);
+    let tokens = match ty.as_str() {
+        "address" => quote! { ::calldata_decoder::bind::SolType::Address },
+        "bool" => quote! { ::calldata_decoder::bind::SolType::Bool },
+        "bytes" | "string" => return Err(dynamic()),
+        t if t.ends_with(']') => return Err(dynamic()),
+        t => {
+            let (kind, digits) = match t {
+                t if t.starts_with("uint") => ("uint", &t[4..]),
+                t if t.starts_with("int") => ("int", &t[3..]),
+                t if t.starts_with("bytes") => ("bytes", &t[5..]),
+                _ => return Err(format!("unknown Solidity type `{}`", t)),
+            };
+            match kind {
+                "bytes" => match bits(digits, 32, 1) {
+                    Some(n) => {
+                        let n = n as u8;
+                        quote! { ::calldata_decoder::bind::SolType::FixedBytes(#n) }
+                    }
+                    None => return Err(format!("`{}` isn't bytes1 to bytes32", t)),
+                },
+                _ => match bits(digits, 256, 8) {
+                    Some(n) if kind == "uint" => {
+                        quote! { ::calldata_decoder::bind::SolType::Uint(#n) }
+                    }
+                    Some(n) => quote! { ::calldata_decoder::bind::SolType::Int(#n) },
+                    None => return Err(format!("`{}` isn't a multiple of 8 bits up to 256", t)),
+                },
+            }
+        }
+    };
+    Ok(tokens)
+}
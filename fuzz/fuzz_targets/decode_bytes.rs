@@ -0,0 +1,35 @@
+#![no_main]
+
+use calldata_decoder::arena::DecodeArena;
+use calldata_decoder::heuristics::Heuristics;
+use calldata_decoder::lazy::LazyCall;
+use calldata_decoder::limits::Limits;
+use calldata_decoder::render::tree::render_tree;
+use calldata_decoder::schema::to_json;
+use calldata_decoder::Calldata;
+use libfuzzer_sys::fuzz_target;
+
+// Raw calldata through every decoder, under the default and the tightest limits.
+fuzz_target!(|data: &[u8]| {
+    let calldata = Calldata::from_bytes(data);
+    let root = calldata.decode();
+    render_tree(&root);
+    to_json(&calldata);
+
+    let mut arena = DecodeArena::new();
+    let id = arena.decode(&calldata);
+    assert_eq!(arena.call(id).to_decoded(), root);
+
+    LazyCall::from_bytes(data.to_vec()).decode();
+
+    let tight = Limits {
+        max_depth: 1,
+        max_calls: 1,
+        max_input: 256,
+        max_region: 64,
+        max_nodes: 16,
+    };
+    if let Ok(calldata) = Calldata::from_bytes_with_limits(data.to_vec(), Heuristics::default(), tight) {
+        calldata.decode();
+    }
+});
@@ -0,0 +1,13 @@
+#![no_main]
+
+use calldata_decoder::decoder::Decoder;
+use calldata_decoder::{check_hex, Calldata};
+use libfuzzer_sys::fuzz_target;
+
+// Untrusted text, as a web decoder would get it.
+fuzz_target!(|text: &str| {
+    let checked = check_hex(text);
+    let decoded = Decoder::new().decode(text);
+    assert_eq!(checked.is_ok(), decoded.is_ok());
+    Calldata::new(text).decode();
+});
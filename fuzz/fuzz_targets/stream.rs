@@ -0,0 +1,10 @@
+#![no_main]
+
+use calldata_decoder::stream::StreamDecoder;
+use libfuzzer_sys::fuzz_target;
+
+// Both stream inputs, ending at the first error.
+fuzz_target!(|data: &[u8]| {
+    StreamDecoder::hex(data).for_each(drop);
+    StreamDecoder::binary(data).for_each(drop);
+});
@@ -0,0 +1,41 @@
+/*
+ * C API of calldata-decoder, built with `cargo build --release --features capi`
+ * as `libcalldata_decoder.so` / `.dylib` / `.dll`.
+ */
+#ifndef CALLDATA_DECODER_H
+#define CALLDATA_DECODER_H
+
+#include <stdint.h>
+
+#ifdef __cplusplus
+extern "C" {
+#endif
+
+/*
+ * Decodes NUL-terminated calldata hex (with or without `0x`) into the JSON
+ * output, stored in `*json_out`, or the error message when it fails.
+ * `*json_out` must be released with `cd_free`.
+ *
+ * Returns 0 when decoded, 2 when partially decoded, 3 for invalid input.
+ */
+int cd_decode(const char *hex, char **json_out);
+
+/*
+ * Decodes a NUL-terminated JSON request, e.g.
+ * `{"calldata": "0x...", "options": {"addressCase": "lowercase"}}`, into a
+ * JSON response: `{"version", "status", "output"}` or `{"version", "status",
+ * "error"}`. Never returns NULL; the response must be released with `cd_free`.
+ */
+char *cd_decode_json(const char *request);
+
+/* Releases a string returned by `cd_decode` or `cd_decode_json`; NULL is ignored. */
+void cd_free(char *s);
+
+/* Version of the JSON written by `cd_decode`. */
+uint32_t cd_schema_version(void);
+
+#ifdef __cplusplus
+}
+#endif
+
+#endif /* CALLDATA_DECODER_H */
@@ -0,0 +1,123 @@
+use crate::decoded::{DecodedCalldata, DecodedParam};
+use crate::differential::{find_function, signature};
+use crate::Calldata;
+use ethers::abi::{Abi, ParamType};
+use primitive_types::U256;
+
+// ------------------------------------------------------------
+//  ABI-guided decoding
+// ------------------------------------------------------------
+
+impl Calldata {
+    /// Decodes the calls `abi` has a function for exactly, as its inputs:
+    /// the main call when its selector is in the ABI, and any nested call
+    /// whose selector is. The other calls, e.g. payloads for contracts the ABI
+    /// isn't of, keep their heuristic types (see `apply_abi`).
+    pub fn with_abi(mut self, abi: &Abi) -> Self {
+        self.abi = Some(abi.clone());
+        self
+    }
+
+    /// The ABI given with `with_abi`.
+    pub fn abi(&self) -> Option<&Abi> {
+        self.abi.as_ref()
+    }
+}
+
+/// Types each call of the tree `abi` has a function for as its inputs: the
+/// head words, the offsets and lengths of the dynamic inputs and the
+/// elements of their arrays rendered as the declared types, and the
+/// function's signature written as the call's guess.
+///
+/// A call is only typed when its bytes decode as the function's inputs;
+/// otherwise, or when the ABI doesn't have its selector, it keeps its
+/// heuristic types. Words inside `bytes` and `string` params are left to
+/// the heuristics, so calls nested in them are typed by their own function.
+///
+/// ## Params
+/// 1. root - the decode of `calldata`.
+/// 2. calldata - the whole calldata, which the spans of the tree are in.
+/// 3. abi - the functions to type the calls as.
+pub fn apply_abi(root: &mut DecodedCalldata, calldata: &[u8], abi: &Abi) {
+    for call in root.calls.iter_mut() {
+        apply_abi(call, calldata, abi);
+    }
+    let Some(bytes) = calldata.get(root.span.start..root.span.end) else {
+        return;
+    };
+    let Some(function) = find_function(abi, bytes) else {
+        return;
+    };
+    if function.decode_input(&bytes[4..]).is_err() {
+        return;
+    }
+    let kinds = function.inputs.iter().map(|input| &input.kind);
+    type_tuple(&mut root.params, kinds, 0);
+    root.signature_guess = signature(function);
+}
+
+/// Types the words of a tuple of `kinds` encoded from word `base`, offsets
+/// of its dynamic members counted from there.
+fn type_tuple<'a>(
+    params: &mut [DecodedParam],
+    kinds: impl IntoIterator<Item = &'a ParamType>,
+    base: usize,
+) {
+    let mut at = base;
+    for kind in kinds {
+        if at >= params.len() {
+            return;
+        }
+        type_member(params, kind, at, base);
+        at += head_words(kind);
+    }
+}
+
+/// Types the member of a tuple whose head is at word `at`.
+fn type_member(params: &mut [DecodedParam], kind: &ParamType, at: usize, base: usize) {
+    if !kind.is_dynamic() {
+        match kind {
+            ParamType::FixedArray(inner, len) => type_tuple(params, vec![&**inner; *len], at),
+            ParamType::Tuple(inner) => type_tuple(params, inner, at),
+            _ => params[at].prefer(&kind.to_string()),
+        }
+        return;
+    }
+    params[at].prefer(&kind.to_string());
+    let Some(start) = word(&params[at])
+        .filter(|offset| offset % 32 == 0)
+        .map(|offset| base + offset / 32)
+        .filter(|start| *start < params.len())
+    else {
+        return;
+    };
+    match kind {
+        ParamType::Bytes | ParamType::String => params[start].prefer("uint256"),
+        ParamType::Array(inner) => {
+            params[start].prefer("uint256");
+            // Bounded by the words there are, whatever the length word says.
+            let len = word(&params[start]).unwrap_or_default();
+            let len = len.min(params.len());
+            type_tuple(params, std::iter::repeat_n(&**inner, len), start + 1);
+        }
+        ParamType::FixedArray(inner, len) => type_tuple(params, vec![&**inner; *len], start),
+        ParamType::Tuple(inner) => type_tuple(params, inner, start),
+        _ => {}
+    }
+}
+
+/// Words a type takes in the head of the tuple it's in.
+fn head_words(kind: &ParamType) -> usize {
+    match kind {
+        _ if kind.is_dynamic() => 1,
+        ParamType::FixedArray(inner, len) => len * head_words(inner),
+        ParamType::Tuple(inner) => inner.iter().map(head_words).sum(),
+        _ => 1,
+    }
+}
+
+/// A word as an offset or length, `None` past `usize`.
+fn word(param: &DecodedParam) -> Option<usize> {
+    let value = U256::from_str_radix(&param.raw, 16).ok()?;
+    (value <= U256::from(usize::MAX)).then(|| value.as_usize())
+}
@@ -0,0 +1,66 @@
+use crate::decoded::{DecodeWarning, DecodedCalldata, DecodedParam};
+use crate::heuristics::Heuristics;
+use crate::limits::Limits;
+use crate::Calldata;
+use serde::{Deserialize, Serialize};
+
+// ------------------------------------------------------------
+//  ABI-encoded bytes
+// ------------------------------------------------------------
+
+/// Decode tree of ABI-encoded bytes with no selector in front, e.g. the
+/// result of `abi.encode` kept in storage or sent over a bridge.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DecodedAbi {
+    /// The values as a tuple, e.g. `(address,uint256)`, built from each
+    /// param's most likely type.
+    pub types_guess: String,
+    /// Params of the bytes, spans being byte offsets in them.
+    pub params: Vec<DecodedParam>,
+    /// Method calls embedded in the params.
+    pub calls: Vec<DecodedCalldata>,
+    /// Irregularities found in the params.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub warnings: Vec<DecodeWarning>,
+}
+
+/// Decodes ABI-encoded bytes that don't start with a selector (storage values,
+/// oracle answers, bridge payloads, ...) with the default heuristics and limits.
+///
+/// ## Returns
+/// 1. The decode, or an error when the bytes are over the limits.
+pub fn decode_abi_blob(bytes: &[u8]) -> Result<DecodedAbi, String> {
+    decode_abi_blob_with(bytes, Heuristics::default(), Limits::default())
+}
+
+/// Same as `decode_abi_blob`, under the given heuristics and limits.
+pub fn decode_abi_blob_with(
+    bytes: &[u8],
+    heuristics: Heuristics,
+    limits: Limits,
+) -> Result<DecodedAbi, String> {
+    // The bytes are laid out as a call's params, behind a blank selector.
+    let mut calldata = vec![0; 4];
+    calldata.extend_from_slice(bytes);
+    let mut data = Calldata::from_bytes_with_limits(calldata, heuristics, limits)?.decode();
+    data.unshift(4);
+    Ok(DecodedAbi {
+        types_guess: tuple_guess(&data.params),
+        params: data.params,
+        calls: data.calls,
+        warnings: data.warnings,
+    })
+}
+
+/// `(t0,t1,...)` from each param's most likely type.
+fn tuple_guess(params: &[DecodedParam]) -> String {
+    let types = params
+        .iter()
+        .map(|p| match p.types.first() {
+            Some(t) => t.solidity.clone(),
+            None => "bytes32".to_string(),
+        })
+        .collect::<Vec<_>>();
+    format!("({})", types.join(","))
+}
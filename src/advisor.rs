@@ -0,0 +1,209 @@
+use crate::constants::Types;
+use crate::decoded::{DecodedCalldata, DecodedParam, Span};
+use crate::l1fee::calldata_gas;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+
+// ------------------------------------------------------------
+//  Compression advice
+// ------------------------------------------------------------
+
+/// Shortest run of zero bytes worth reporting: two words, so the padding of a
+/// single small param doesn't count.
+pub const MIN_ZERO_RUN: usize = 64;
+
+/// A way to encode part of the calldata in fewer bytes.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "kind", rename_all = "camelCase")]
+pub enum Advice {
+    /// A param whose value fits a narrower type, dropping its padding when
+    /// packed (e.g. `abi.encodePacked` or a custom decoder).
+    #[serde(rename_all = "camelCase")]
+    NarrowerType {
+        path: String,
+        /// The param's most likely type.
+        guessed: String,
+        /// Narrowest type holding the value, e.g. `uint40`.
+        fits: String,
+    },
+    /// Zero bytes that a zero byte and a one-byte count could stand for.
+    #[serde(rename_all = "camelCase")]
+    ZeroRun { len: usize },
+    /// An address already sent in an earlier param, which a one-byte index
+    /// into the addresses sent could stand for.
+    #[serde(rename_all = "camelCase")]
+    RepeatedAddress {
+        path: String,
+        address: String,
+        /// Path of the param first holding the address.
+        first: String,
+    },
+}
+
+/// One suggestion and what it would save.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Suggestion {
+    #[serde(flatten)]
+    pub advice: Advice,
+    /// Bytes of the calldata the suggestion is about.
+    pub span: Span,
+    pub saved_bytes: usize,
+    /// Calldata gas saved, at 4 gas a zero byte and 16 a non-zero one.
+    pub saved_gas: u64,
+}
+
+/// Encoding optimizations found in a calldata.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CompressionReport {
+    pub bytes: usize,
+    pub calldata_gas: u64,
+    /// In calldata order.
+    pub suggestions: Vec<Suggestion>,
+    /// Savings of the suggestions together, overlapping ones counting once
+    /// (the one saving the most gas).
+    pub saved_bytes: usize,
+    pub saved_gas: u64,
+}
+
+/// Suggests encoding optimizations for `calldata`, given `root`, its decode:
+/// params that fit narrower types, long runs of zero bytes and repeated
+/// addresses, each with the bytes and calldata gas it would save.
+///
+/// The params of nested calls are looked at in place of the outer params
+/// holding them.
+pub fn advise(calldata: &[u8], root: &DecodedCalldata) -> CompressionReport {
+    let params = root.leaf_params();
+    let mut suggestions = vec![];
+    let mut addresses = BTreeMap::new();
+    for (path, param) in params.iter() {
+        let Some(word) = calldata.get(param.span.start..param.span.end) else {
+            continue;
+        };
+        if word.len() != 32 {
+            continue;
+        }
+        if let Some(suggestion) = narrower_type(path, param, word) {
+            suggestions.push(suggestion);
+        }
+        if param.types.first().map(|t| &t.kind) != Some(&Types::Address) {
+            continue;
+        }
+        match addresses.get(&param.raw) {
+            None => {
+                addresses.insert(param.raw.clone(), path.clone());
+            }
+            Some(first) => suggestions.push(Suggestion {
+                advice: Advice::RepeatedAddress {
+                    path: path.clone(),
+                    address: format!("0x{}", &param.raw[24..]),
+                    first: first.clone(),
+                },
+                span: param.span,
+                saved_bytes: 31,
+                saved_gas: calldata_gas(word) - 16,
+            }),
+        }
+    }
+    suggestions.extend(zero_runs(calldata));
+    suggestions.sort_by_key(|s| (s.span.start, s.span.end));
+
+    // Greedily keep the suggestions saving the most, skipping overlaps.
+    let mut ranked = suggestions.iter().collect::<Vec<_>>();
+    ranked.sort_by_key(|s| std::cmp::Reverse(s.saved_gas));
+    let mut taken: Vec<Span> = vec![];
+    let (mut saved_bytes, mut saved_gas) = (0, 0);
+    for suggestion in ranked {
+        let span = suggestion.span;
+        if taken
+            .iter()
+            .any(|t| t.start < span.end && span.start < t.end)
+        {
+            continue;
+        }
+        taken.push(span);
+        saved_bytes += suggestion.saved_bytes;
+        saved_gas += suggestion.saved_gas;
+    }
+    CompressionReport {
+        bytes: calldata.len(),
+        calldata_gas: calldata_gas(calldata),
+        suggestions,
+        saved_bytes,
+        saved_gas,
+    }
+}
+
+/// How much packing the param in its narrowest type would save.
+fn narrower_type(path: &str, param: &DecodedParam, word: &[u8]) -> Option<Suggestion> {
+    let guessed = param.types.first()?;
+    let leading = |byte: u8| word.iter().take_while(|b| **b == byte).count();
+    // Bytes the value needs, and whether they're the low-order ones.
+    let (needed, fits, low) = match guessed.kind {
+        Types::Uint | Types::Uint8 | Types::ZeroUint | Types::MaxUint128 | Types::AnyZero => {
+            let needed = (32 - leading(0)).max(1);
+            (needed, format!("uint{}", needed * 8), true)
+        }
+        Types::Int | Types::Int128 => {
+            let sign = match word[0] & 0x80 {
+                0 => 0,
+                _ => 0xff,
+            };
+            let mut needed = (32 - leading(sign)).max(1);
+            // Keep the sign bit when the first significant byte lost it.
+            if (word[32 - needed] & 0x80 != 0) != (sign == 0xff) {
+                needed += 1;
+            }
+            let needed = needed.min(32);
+            (needed, format!("int{}", needed * 8), true)
+        }
+        Types::Address | Types::Address0 => (20, "address".to_string(), true),
+        Types::Bool => (1, "bool".to_string(), true),
+        Types::Bytes | Types::Bytes1 | Types::Bytes20 | Types::Selector => {
+            let trailing = word.iter().rev().take_while(|b| **b == 0).count();
+            let needed = (32 - trailing).max(1);
+            (needed, format!("bytes{}", needed), false)
+        }
+        Types::AnyMax | Types::String => return None,
+    };
+    if needed >= 32 {
+        return None;
+    }
+    let removed = match low {
+        true => &word[..32 - needed],
+        false => &word[needed..],
+    };
+    Some(Suggestion {
+        advice: Advice::NarrowerType {
+            path: path.to_string(),
+            guessed: guessed.solidity.clone(),
+            fits,
+        },
+        span: param.span,
+        saved_bytes: 32 - needed,
+        saved_gas: calldata_gas(removed),
+    })
+}
+
+/// Runs of at least `MIN_ZERO_RUN` zero bytes.
+fn zero_runs(calldata: &[u8]) -> Vec<Suggestion> {
+    let mut runs = vec![];
+    let mut start = 0;
+    for (i, byte) in calldata.iter().chain([&1u8]).enumerate() {
+        if *byte != 0 {
+            if i - start >= MIN_ZERO_RUN {
+                let len = i - start;
+                runs.push(Suggestion {
+                    advice: Advice::ZeroRun { len },
+                    span: Span { start, end: i },
+                    saved_bytes: len - 2,
+                    // A zero byte and a non-zero count are left.
+                    saved_gas: len as u64 * 4 - 20,
+                });
+            }
+            start = i + 1;
+        }
+    }
+    runs
+}
@@ -0,0 +1,217 @@
+use crate::query::Query;
+use crate::rpc::DecodedTransaction;
+use crate::watch::TxFilter;
+use serde::{Deserialize, Serialize};
+use std::io::Write;
+use std::path::Path;
+use std::process::{Child, Command, Stdio};
+
+// ------------------------------------------------------------
+//  Alert rules
+// ------------------------------------------------------------
+
+/// Rules checked against watched transactions, read from a TOML file.
+///
+/// ```toml
+/// [[rule]]
+/// name = "unlimited approval"
+/// selectors = ["0x095ea7b3"]
+/// nested = true
+/// query = "selector == 0x095ea7b3 and params[1] > 1e30"
+/// webhook = "https://hooks.example/alerts"
+///
+/// [[rule]]
+/// name = "router multicall"
+/// to = ["0x68b3465833fb72A70ecDF485E0e4C7bD8665Fc45"]
+/// exec = ["notify-send", "multicall"]
+/// ```
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct Rules {
+    pub rule: Vec<Rule>,
+}
+
+/// What a rule matches and what it does when it does.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct Rule {
+    pub name: String,
+    /// Selectors the transaction must call; any when empty.
+    pub selectors: Vec<String>,
+    /// Addresses the transaction must be sent to; any when empty.
+    pub to: Vec<String>,
+    /// Also match `selectors` in nested calls.
+    pub nested: bool,
+    /// Condition on the decoded calls (see `query::Query`), e.g. on param values.
+    pub query: Option<String>,
+    /// URL the alert is POSTed to as JSON.
+    pub webhook: Option<String>,
+    /// Command run with the alert as JSON on its stdin, and the rule's name in
+    /// `CALLDATA_DECODER_RULE`.
+    pub exec: Vec<String>,
+}
+
+impl Rules {
+    pub fn from_toml(text: &str) -> Result<Self, String> {
+        toml::from_str(text).map_err(|e| e.to_string())
+    }
+
+    /// Reads the rules at `path`.
+    pub fn load(path: &Path) -> Result<Self, String> {
+        let text =
+            std::fs::read_to_string(path).map_err(|e| format!("{}: {}", path.display(), e))?;
+        Self::from_toml(&text).map_err(|e| format!("{}: {}", path.display(), e))
+    }
+}
+
+/// A transaction a rule matched.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Alert {
+    pub rule: String,
+    /// Paths of the calls matching the rule's query, e.g. `calls[1]`; empty
+    /// without a query.
+    pub calls: Vec<String>,
+    pub transaction: DecodedTransaction,
+}
+
+/// A rule ready to be checked.
+#[derive(Debug, Clone)]
+struct Compiled {
+    rule: Rule,
+    filter: TxFilter,
+    query: Option<Query>,
+}
+
+/// Checks decoded transactions against rules and runs their actions.
+#[derive(Debug, Clone)]
+pub struct Alerter {
+    rules: Vec<Compiled>,
+}
+
+impl Alerter {
+    /// Parses the rules' addresses and queries.
+    pub fn new(rules: Rules) -> Result<Self, String> {
+        let rules = rules
+            .rule
+            .into_iter()
+            .map(|rule| {
+                let error = |e: String| format!("rule `{}`: {}", rule.name, e);
+                let to = rule
+                    .to
+                    .iter()
+                    .map(|a| a.parse().map_err(|e| error(format!("{}: {}", a, e))))
+                    .collect::<Result<Vec<_>, String>>()?;
+                let query = match &rule.query {
+                    Some(query) => Some(Query::parse(query).map_err(error)?),
+                    None => None,
+                };
+                Ok(Compiled {
+                    filter: TxFilter {
+                        selectors: rule.selectors.clone(),
+                        to,
+                        nested: rule.nested,
+                    },
+                    query,
+                    rule,
+                })
+            })
+            .collect::<Result<Vec<_>, String>>()?;
+        Ok(Self { rules })
+    }
+
+    /// The transactions any rule could match, to skip decoding the others.
+    pub fn prefilter(&self) -> TxFilter {
+        let any = |f: fn(&TxFilter) -> bool| self.rules.iter().any(|r| f(&r.filter));
+        let mut filter = TxFilter {
+            nested: any(|f| f.nested),
+            ..Default::default()
+        };
+        if !any(|f| f.selectors.is_empty()) {
+            filter.selectors = self
+                .rules
+                .iter()
+                .flat_map(|r| r.filter.selectors.iter().cloned())
+                .collect();
+        }
+        if !any(|f| f.to.is_empty()) {
+            filter.to = self
+                .rules
+                .iter()
+                .flat_map(|r| r.filter.to.iter().copied())
+                .collect();
+        }
+        filter
+    }
+
+    /// An alert for each rule `tx` matches, in the rules' order.
+    pub fn check(&self, tx: &DecodedTransaction) -> Vec<Alert> {
+        self.rules
+            .iter()
+            .filter(|r| r.filter.matches(tx))
+            .filter_map(|r| {
+                let calls = match &r.query {
+                    Some(query) => {
+                        let found = query.find(&tx.decoded);
+                        if found.is_empty() {
+                            return None;
+                        }
+                        found.into_iter().map(|(path, _)| path).collect()
+                    }
+                    None => vec![],
+                };
+                Some(Alert {
+                    rule: r.rule.name.clone(),
+                    calls,
+                    transaction: tx.clone(),
+                })
+            })
+            .collect()
+    }
+
+    /// The rule `alert` was raised by.
+    pub fn rule(&self, alert: &Alert) -> Option<&Rule> {
+        self.rules
+            .iter()
+            .map(|r| &r.rule)
+            .find(|r| r.name == alert.rule)
+    }
+}
+
+/// POSTs `alert` to `url` as JSON.
+pub async fn post_webhook(
+    client: &reqwest::Client,
+    url: &str,
+    alert: &Alert,
+) -> Result<(), String> {
+    let body = serde_json::to_string(alert).map_err(|e| e.to_string())?;
+    client
+        .post(url)
+        .header(reqwest::header::CONTENT_TYPE, "application/json")
+        .body(body)
+        .send()
+        .await
+        .and_then(|response| response.error_for_status())
+        .map(|_| ())
+        .map_err(|e| format!("{}: {}", url, e))
+}
+
+/// Starts `command` with `alert` as JSON on its stdin.
+///
+/// ## Returns
+/// 1. The running command, for the caller to wait on.
+pub fn run_command(command: &[String], alert: &Alert) -> Result<Child, String> {
+    let (program, args) = command.split_first().ok_or("empty command")?;
+    let mut child = Command::new(program)
+        .args(args)
+        .env("CALLDATA_DECODER_RULE", &alert.rule)
+        .stdin(Stdio::piped())
+        .spawn()
+        .map_err(|e| format!("{}: {}", program, e))?;
+    let json = serde_json::to_string(alert).map_err(|e| e.to_string())?;
+    if let Some(mut stdin) = child.stdin.take() {
+        // A command that doesn't read its input isn't an error.
+        let _ = writeln!(stdin, "{}", json);
+    }
+    Ok(child)
+}
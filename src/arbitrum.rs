@@ -0,0 +1,387 @@
+use crate::batcher::read_capped;
+use crate::constants::*;
+use crate::decoded::DecodedCalldata;
+use crate::heuristics::Heuristics;
+use crate::limits::Limits;
+use crate::rawtx::{serialize_hex, DecodedRawTransaction, RawTransaction};
+use crate::render::address::serialize_checksum_opt;
+use crate::rlp::Rlp;
+use crate::signatures::selector_bytes;
+use crate::{word_usize, Calldata};
+use primitive_types::{H160, U256};
+use serde::Serialize;
+
+// ------------------------------------------------------------
+//  Sequencer inboxes
+// ------------------------------------------------------------
+
+/// Sequencer inboxes of the Arbitrum chains, by name.
+pub const SEQUENCER_INBOXES: &[(&str, &str)] = &[
+    ("Arbitrum One", "1c479675ad559dc151f6ec7ed3fbf8cee79582b6"),
+    ("Arbitrum Nova", "211e1c4c7f1bf5351ac850ed10fd68cffcf6c21b"),
+];
+
+/// Name of the chain whose sequencer inbox `to` is, among [`SEQUENCER_INBOXES`].
+pub fn sequencer_inbox_name(to: &H160) -> Option<&'static str> {
+    let hex = faster_hex::hex_string(to.as_bytes());
+    SEQUENCER_INBOXES
+        .iter()
+        .find(|(_, inbox)| *inbox == hex)
+        .map(|(name, _)| *name)
+}
+
+/// The sequencer inbox functions posting a batch, its sequence number then
+/// its data being their first two params.
+pub const BATCH_SIGNATURES: &[&str] = &[
+    "addSequencerL2BatchFromOrigin(uint256,bytes,uint256,address)",
+    "addSequencerL2BatchFromOrigin(uint256,bytes,uint256,address,uint256,uint256)",
+    "addSequencerL2Batch(uint256,bytes,uint256,address,uint256,uint256)",
+];
+
+/// Most bytes a batch, or a compressed L2 message, may decompress to.
+pub const MAX_DECOMPRESSED: usize = 16 << 20;
+
+/// Largest L2 message nested in a batch message.
+pub const MAX_L2_MESSAGE: usize = 256 << 10;
+
+/// The sequence number and data of a call posting a batch, `None` when
+/// `calldata` isn't one.
+pub fn batch_data(calldata: &[u8]) -> Option<(U256, &[u8])> {
+    let selector = calldata.get(..SELECTOR)?;
+    if !BATCH_SIGNATURES
+        .iter()
+        .any(|s| selector_bytes(s)[..] == *selector)
+    {
+        return None;
+    }
+    let params = &calldata[SELECTOR..];
+    let sequence_number = U256::from_big_endian(params.get(..WORD)?);
+    let offset = word_usize(params.get(WORD..2 * WORD)?)?;
+    let len = word_usize(params.get(offset..offset.checked_add(WORD)?)?)?;
+    let start = offset + WORD;
+    Some((sequence_number, params.get(start..start.checked_add(len)?)?))
+}
+
+// ------------------------------------------------------------
+//  Batches
+// ------------------------------------------------------------
+
+/// How a batch's data is carried, from its header byte.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub enum BatchFormat {
+    /// `0x00`: brotli-compressed segments, in the calldata.
+    Brotli,
+    /// `0x80` or `0x88`: a certificate of data held by a data availability committee.
+    Das,
+    /// `0x50`: blobs, the data being their versioned hashes.
+    Blobs,
+    /// Any other header, or none.
+    Unknown,
+}
+
+/// A sequencer batch and the segments of its data.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SequencerBatch {
+    pub sequence_number: U256,
+    pub format: BatchFormat,
+    /// Empty unless the data is in the calldata.
+    pub segments: Vec<Segment>,
+    /// Why the data, or its segments past the decoded ones, couldn't be read.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+}
+
+impl SequencerBatch {
+    /// Decodes of the calldata of every transaction in the batch, in order.
+    pub fn calls(&self) -> Vec<&DecodedCalldata> {
+        let mut calls = vec![];
+        for segment in self.segments.iter() {
+            if let Segment::L2Message { message, .. } = segment {
+                message.collect_calls(&mut calls);
+            }
+        }
+        calls
+    }
+
+    /// Same as `calls`, to annotate the decodes.
+    pub fn calls_mut(&mut self) -> Vec<&mut DecodedCalldata> {
+        let mut calls = vec![];
+        for segment in self.segments.iter_mut() {
+            if let Segment::L2Message { message, .. } = segment {
+                message.collect_calls_mut(&mut calls);
+            }
+        }
+        calls
+    }
+}
+
+/// A segment of a batch's decompressed data.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(tag = "kind", rename_all = "camelCase")]
+pub enum Segment {
+    /// An L2 message from the sequencer, brotli-compressed on its own when `compressed`.
+    L2Message {
+        compressed: bool,
+        message: L2Message,
+    },
+    /// The next delayed message, sent through the L1 bridge, is included here.
+    DelayedMessages,
+    /// The following messages' timestamp moves on by `by` seconds.
+    AdvanceTimestamp { by: u64 },
+    /// The following messages' L1 block number moves on by `by`.
+    AdvanceL1BlockNumber { by: u64 },
+}
+
+/// An L2 message, by its kind byte.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(tag = "kind", rename_all = "camelCase")]
+pub enum L2Message {
+    /// `3`: messages each behind an 8-byte length.
+    Batch { messages: Vec<L2Message> },
+    /// `4`: a signed transaction in its raw encoding.
+    SignedTx {
+        transaction: Box<DecodedRawTransaction>,
+    },
+    /// `0` or `1`: a transaction without signature, from a delayed message.
+    UnsignedTx {
+        transaction: Box<UnsignedTransaction>,
+    },
+    /// Any other kind, left as bytes.
+    Unknown { byte: u8, len: usize },
+}
+
+impl L2Message {
+    fn collect_calls<'a>(&'a self, calls: &mut Vec<&'a DecodedCalldata>) {
+        match self {
+            L2Message::Batch { messages } => {
+                messages.iter().for_each(|m| m.collect_calls(calls));
+            }
+            L2Message::SignedTx { transaction } => calls.extend(transaction.decoded.as_ref()),
+            L2Message::UnsignedTx { transaction } => calls.extend(transaction.decoded.as_ref()),
+            L2Message::Unknown { .. } => {}
+        }
+    }
+
+    fn collect_calls_mut<'a>(&'a mut self, calls: &mut Vec<&'a mut DecodedCalldata>) {
+        match self {
+            L2Message::Batch { messages } => {
+                messages.iter_mut().for_each(|m| m.collect_calls_mut(calls));
+            }
+            L2Message::SignedTx { transaction } => calls.extend(transaction.decoded.as_mut()),
+            L2Message::UnsignedTx { transaction } => calls.extend(transaction.decoded.as_mut()),
+            L2Message::Unknown { .. } => {}
+        }
+    }
+}
+
+/// An unsigned user transaction (kind `0`, with a nonce) or a contract
+/// transaction (kind `1`, without).
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct UnsignedTransaction {
+    pub gas_limit: U256,
+    pub max_fee_per_gas: U256,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub nonce: Option<U256>,
+    /// `None` for contract creations.
+    #[serde(serialize_with = "serialize_checksum_opt")]
+    pub to: Option<H160>,
+    pub value: U256,
+    #[serde(serialize_with = "serialize_hex")]
+    pub input: Vec<u8>,
+    pub decoded: Option<DecodedCalldata>,
+}
+
+/// Decodes Arbitrum sequencer batches under one set of heuristics and limits.
+///
+/// Messages nested in batch messages are read down to `max_depth`.
+#[derive(Debug, Clone, Default)]
+pub struct SequencerDecoder {
+    heuristics: Heuristics,
+    limits: Limits,
+}
+
+impl SequencerDecoder {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn with_heuristics(mut self, heuristics: Heuristics) -> Self {
+        self.heuristics = heuristics;
+        self
+    }
+
+    pub fn with_limits(mut self, limits: Limits) -> Self {
+        self.limits = limits;
+        self
+    }
+
+    /// Decodes the batch posted by `calldata`, a call to the sequencer inbox.
+    pub fn decode_calldata(&self, calldata: &[u8]) -> Result<SequencerBatch, String> {
+        let (sequence_number, data) =
+            batch_data(calldata).ok_or("calldata doesn't post a sequencer batch")?;
+        Ok(self.decode(sequence_number, data))
+    }
+
+    /// Decodes a batch's data: a header byte then, for a brotli batch, its
+    /// compressed segments.
+    pub fn decode(&self, sequence_number: U256, data: &[u8]) -> SequencerBatch {
+        let mut batch = SequencerBatch {
+            sequence_number,
+            format: BatchFormat::Unknown,
+            segments: vec![],
+            error: None,
+        };
+        let body = match data.split_first() {
+            Some((0x00, body)) => body,
+            Some((header, _)) => {
+                batch.format = match header {
+                    0x80 | 0x88 => BatchFormat::Das,
+                    0x50 => BatchFormat::Blobs,
+                    _ => BatchFormat::Unknown,
+                };
+                if batch.format == BatchFormat::Unknown {
+                    batch.error = Some(format!("unknown batch header 0x{:02x}", header));
+                }
+                return batch;
+            }
+            None => {
+                batch.error = Some("empty batch".to_string());
+                return batch;
+            }
+        };
+        batch.format = BatchFormat::Brotli;
+        let segments = match unbrotli(body) {
+            Ok(segments) => segments,
+            Err(e) => {
+                batch.error = Some(e);
+                return batch;
+            }
+        };
+        // Each segment is an RLP string: kind byte then content.
+        let mut rest = segments.as_slice();
+        while !rest.is_empty() {
+            let segment = Rlp::decode_prefix(rest)
+                .and_then(|(item, after)| Ok((self.segment(item.as_bytes()?)?, after)));
+            match segment {
+                Ok((segment, after)) => {
+                    batch.segments.push(segment);
+                    rest = after;
+                }
+                Err(e) => {
+                    batch.error = Some(format!("segment {}: {}", batch.segments.len(), e));
+                    break;
+                }
+            }
+        }
+        batch
+    }
+
+    fn segment(&self, bytes: &[u8]) -> Result<Segment, String> {
+        let advance = |content: &[u8]| Rlp::decode(content)?.as_u64();
+        match bytes.split_first() {
+            Some((0, message)) => Ok(Segment::L2Message {
+                compressed: false,
+                message: self.message(message, 0)?,
+            }),
+            Some((1, compressed)) => Ok(Segment::L2Message {
+                compressed: true,
+                message: self.message(&unbrotli(compressed)?, 0)?,
+            }),
+            Some((2, _)) => Ok(Segment::DelayedMessages),
+            Some((3, content)) => Ok(Segment::AdvanceTimestamp {
+                by: advance(content)?,
+            }),
+            Some((4, content)) => Ok(Segment::AdvanceL1BlockNumber {
+                by: advance(content)?,
+            }),
+            Some((kind, _)) => Err(format!("unknown segment kind {}", kind)),
+            None => Err("empty segment".to_string()),
+        }
+    }
+
+    fn message(&self, bytes: &[u8], depth: usize) -> Result<L2Message, String> {
+        match bytes.split_first() {
+            Some((3, mut rest)) => {
+                if depth >= self.limits.max_depth {
+                    return Err(format!("batch messages nested past {}", depth));
+                }
+                let mut messages = vec![];
+                while !rest.is_empty() {
+                    let len = rest
+                        .get(..8)
+                        .map(|l| u64::from_be_bytes(l.try_into().unwrap()) as usize)
+                        .filter(|len| *len <= MAX_L2_MESSAGE && *len <= rest.len() - 8)
+                        .ok_or("nested message length out of bounds")?;
+                    messages.push(self.message(&rest[8..8 + len], depth + 1)?);
+                    rest = &rest[8 + len..];
+                }
+                Ok(L2Message::Batch { messages })
+            }
+            Some((4, raw)) => {
+                let tx = RawTransaction::from_bytes(raw)?;
+                Ok(L2Message::SignedTx {
+                    transaction: Box::new(DecodedRawTransaction::new(
+                        tx,
+                        self.heuristics,
+                        self.limits,
+                    )?),
+                })
+            }
+            Some((kind @ (0 | 1), fields)) => Ok(L2Message::UnsignedTx {
+                transaction: Box::new(self.unsigned(*kind == 0, fields)?),
+            }),
+            Some((byte, rest)) => Ok(L2Message::Unknown {
+                byte: *byte,
+                len: rest.len(),
+            }),
+            None => Err("empty L2 message".to_string()),
+        }
+    }
+
+    /// Words of gas limit, max fee, nonce when `with_nonce`, recipient and
+    /// value, then the input.
+    fn unsigned(&self, with_nonce: bool, fields: &[u8]) -> Result<UnsignedTransaction, String> {
+        let words = 4 + with_nonce as usize;
+        if fields.len() < words * WORD {
+            return Err(format!("unsigned transaction shorter than {} words", words));
+        }
+        let word = |i: usize| U256::from_big_endian(&fields[i * WORD..(i + 1) * WORD]);
+        let at = with_nonce as usize;
+        let to = word(2 + at);
+        let to = match to.is_zero() {
+            true => None,
+            false => Some(H160::from_slice(
+                &fields[(3 + at) * WORD - 20..(3 + at) * WORD],
+            )),
+        };
+        let input = fields[words * WORD..].to_vec();
+        let decoded = match to.is_some() && input.len() >= SELECTOR {
+            true => {
+                let calldata =
+                    Calldata::from_bytes_with_limits(input.clone(), self.heuristics, self.limits)?;
+                Some(calldata.decode())
+            }
+            false => None,
+        };
+        Ok(UnsignedTransaction {
+            gas_limit: word(0),
+            max_fee_per_gas: word(1),
+            nonce: with_nonce.then(|| word(2)),
+            to,
+            value: word(3 + at),
+            input,
+            decoded,
+        })
+    }
+}
+
+fn unbrotli(data: &[u8]) -> Result<Vec<u8>, String> {
+    read_capped(
+        brotli_decompressor::Decompressor::new(data, 4096),
+        MAX_DECOMPRESSED,
+    )
+    .map_err(|e| format!("brotli: {}", e))
+}
@@ -0,0 +1,606 @@
+use crate::constants::{Types, SELECTOR, WORD};
+use crate::decoded::{
+    candidates, hex_u128, render_value, DecodeWarning, DecodedCalldata, DecodedParam,
+    DecodedTuple, Span, TypeCandidate,
+};
+use crate::prototypes::{layout_tuples, top_signature};
+use crate::text::attach_texts;
+use crate::type_guesser::{ParamTypes, Params};
+use crate::walletsig::attach_signatures;
+use crate::{guess_param_type, Calldata};
+use std::fmt::Write;
+use std::ops::Range;
+
+// ------------------------------------------------------------
+//  Arena-backed decode tree
+// ------------------------------------------------------------
+
+/// Holds decode trees in a few flat buffers instead of a `String`/`Vec` per field.
+///
+/// For hot batch loops: decode into the arena, read the tree through `CallRef`,
+/// then `clear` it for the next calldata, keeping its memory. Use `to_decoded`
+/// on a call to get an owned `DecodedCalldata` out.
+#[derive(Debug, Default)]
+pub struct DecodeArena {
+    /// Every selector, signature guess, raw word and value, back to back.
+    text: String,
+    calls: Vec<Call>,
+    params: Vec<Param>,
+    types: Vec<(Types, f64)>,
+    /// Solidity type of each of `types`, when it isn't the kind's own name.
+    solidity: Vec<Option<Range<usize>>>,
+    warnings: Vec<DecodeWarning>,
+    /// Nested calls of each call, each call's next to each other.
+    children: Vec<usize>,
+    /// Dynamic tuples of each call, each call's next to each other.
+    tuples: Vec<DecodedTuple>,
+}
+
+/// A call of the tree; ranges index the arena's buffers.
+#[derive(Debug, Clone)]
+struct Call {
+    selector: Range<usize>,
+    signature_guess: Range<usize>,
+    params: Range<usize>,
+    calls: Range<usize>,
+    warnings: Range<usize>,
+    span: Span,
+    layer: Option<Range<usize>>,
+    tuples: Range<usize>,
+}
+
+#[derive(Debug, Clone)]
+struct Param {
+    index: usize,
+    raw: Range<usize>,
+    value: Range<usize>,
+    types: Range<usize>,
+    span: Span,
+}
+
+/// Root of a tree decoded into a `DecodeArena`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct CallId(usize);
+
+impl CallId {
+    pub(crate) fn of(id: usize) -> Self {
+        Self(id)
+    }
+}
+
+/// Builds a call the layout pass found in place of its tree, given its depth
+/// below the root, its span and what its tree counts; see `DecodeArena::push_heuristics`.
+pub(crate) type Claim<'a> = dyn FnMut(&mut DecodeArena, usize, Span, (usize, usize)) -> Option<CallId> + 'a;
+
+/// Lengths of the arena's buffers, to drop what was built after them.
+#[derive(Debug, Clone, Copy)]
+pub(crate) struct Mark {
+    text: usize,
+    calls: usize,
+    params: usize,
+    types: usize,
+    warnings: usize,
+    children: usize,
+    tuples: usize,
+}
+
+impl DecodeArena {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Forgets every tree, keeping the memory for the next ones.
+    pub fn clear(&mut self) {
+        self.text.clear();
+        self.calls.clear();
+        self.params.clear();
+        self.types.clear();
+        self.solidity.clear();
+        self.warnings.clear();
+        self.children.clear();
+        self.tuples.clear();
+    }
+
+    /// Number of calls held, over every tree.
+    pub fn len(&self) -> usize {
+        self.calls.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.calls.is_empty()
+    }
+
+    /// Builds the decode tree of `calldata` in the arena, same as `Calldata::decode`.
+    ///
+    /// The heuristics' guesses and the trees of the protocol decoders that
+    /// claim calls go straight in (see `ProtocolDecoder::decode_into`); only
+    /// a decode typed by an ABI is copied in from `Calldata::decode`.
+    pub fn decode(&mut self, calldata: &Calldata) -> CallId {
+        #[cfg(feature = "ethers")]
+        if calldata.abi().is_some() {
+            return CallId(self.push_decoded(&calldata.decode()));
+        }
+        let mark = self.mark();
+        match calldata.protocols().decode_into(calldata, self) {
+            Ok(root) => root,
+            Err(_) => {
+                self.truncate(mark);
+                CallId(self.push_heuristics(calldata, &mut |_, _, _, _| None))
+            }
+        }
+    }
+
+    /// Adds `node`, leaving out its nested calls, with `calls` already in the
+    /// arena as its nested calls: for a `ProtocolDecoder::decode_into`.
+    pub fn push_node(&mut self, node: &DecodedCalldata, calls: &[CallId]) -> CallId {
+        let id = self.push_node_only(node);
+        let first = self.children.len();
+        self.children.extend(calls.iter().map(|call| call.0));
+        self.calls[id].calls = first..self.children.len();
+        CallId(id)
+    }
+
+    /// Builds the tree the layout pass found in `calldata`, the same as
+    /// `Calldata::decode_heuristics`, with the spans counted from its selector.
+    ///
+    /// Each nested call, before it's built, is offered to `claim` with its
+    /// depth below the root, its span and what its tree counts (see
+    /// `DecodeArena::size`); a call `claim` builds takes the place of the
+    /// tree the heuristics found.
+    pub(crate) fn push_heuristics(
+        &mut self,
+        calldata: &Calldata,
+        claim: &mut Claim,
+    ) -> usize {
+        let found = Found::new(calldata);
+        self.push_found(calldata, &found, 0, 0, claim)
+    }
+
+    /// `calls` and `params` of the tree the layout pass found in `calldata`,
+    /// counted as `DecodeArena::size` does.
+    pub(crate) fn found_size(calldata: &Calldata) -> (usize, usize) {
+        Found::new(calldata).sizes[0]
+    }
+
+    /// Builds call `i` of `found` (0 for the root, `n + 1` for the `n`th
+    /// nested call) and the calls under it, `depth` levels below the root.
+    fn push_found(
+        &mut self,
+        calldata: &Calldata,
+        found: &Found,
+        i: usize,
+        depth: usize,
+        claim: &mut Claim,
+    ) -> usize {
+        let empty = Params::new(&calldata.selector, vec![]);
+        let (details, start) = match i {
+            0 => (calldata.main_details.first().unwrap_or(&empty), 0),
+            _ => (&calldata.nested_details[i - 1], found.starts[i - 1]),
+        };
+        let mut calls = vec![];
+        for &child in found.children[i].iter() {
+            let (nested, at) = (&calldata.nested_details[child - 1], found.starts[child - 1]);
+            match claim(self, depth + 1, span_of(nested, at), found.sizes[child]) {
+                Some(claimed) => calls.push(claimed.0),
+                None => calls.push(self.push_found(calldata, found, child, depth + 1, claim)),
+            }
+        }
+        let id = self.push_call(details, start);
+        let first = self.children.len();
+        self.children.extend(calls);
+        self.calls[id].calls = first..self.children.len();
+
+        let first = self.warnings.len();
+        let warnings = calldata.layout().warnings();
+        let own = warnings.filter(|(parent, _)| parent.map(|p| p + 1).unwrap_or(0) == i);
+        self.warnings.extend(own.map(|(_, warning)| warning.clone()));
+        self.calls[id].warnings = first..self.warnings.len();
+
+        // A signature reconstructed from the layout needs the children attached.
+        self.name_call(id);
+        id
+    }
+
+    /// Replaces the per-word signature guess of a call whose words may lay
+    /// out dynamic params by `prototypes::top_signature`, and adds the
+    /// tuples they lay out (see `prototypes::layout_tuples`); the params are
+    /// only copied out for those.
+    fn name_call(&mut self, id: usize) {
+        let call = &self.calls[id];
+        let n = call.params.len() as u128;
+        // As `layout_guess` takes a head word for an offset.
+        let offset = self.params[call.params.clone()]
+            .iter()
+            .enumerate()
+            .filter_map(|(i, p)| Some((i as u128, hex_u128(&self.text[p.raw.clone()])?)))
+            .any(|(i, v)| v.is_multiple_of(32) && v / 32 > i && v / 32 < n);
+        if call.calls.is_empty() && !offset {
+            return;
+        }
+        let calls = call.calls.len();
+        let call = CallRef { arena: self, call };
+        let mut params = call.params().map(|p| p.to_decoded()).collect::<Vec<_>>();
+        attach_texts(&mut params);
+        let signature = top_signature(call.selector(), &params, calls);
+        if signature != call.signature_guess() {
+            self.calls[id].signature_guess = self.push_text(&signature);
+        }
+        if offset {
+            let first = self.tuples.len();
+            self.tuples.extend(layout_tuples(&params));
+            self.calls[id].tuples = first..self.tuples.len();
+        }
+    }
+
+    /// Moves the spans of `id`, its params, warnings and nested calls `by`
+    /// bytes further, as `DecodedCalldata::shift`.
+    pub(crate) fn shift(&mut self, id: CallId, by: usize) {
+        let call = &mut self.calls[id.0];
+        call.span.start += by;
+        call.span.end += by;
+        let (params, warnings, calls) = (
+            call.params.clone(),
+            call.warnings.clone(),
+            call.calls.clone(),
+        );
+        for param in self.params[params].iter_mut() {
+            param.span.start += by;
+            param.span.end += by;
+        }
+        for warning in self.warnings[warnings].iter_mut() {
+            *warning = warning.clone().shifted(by);
+        }
+        for i in calls {
+            self.shift(CallId(self.children[i]), by);
+        }
+    }
+
+    /// Calls under `id` and its calls and params, as `protocol::size` counts
+    /// a `DecodedCalldata`.
+    pub(crate) fn size(&self, id: CallId) -> (usize, usize) {
+        let call = &self.calls[id.0];
+        let below = self.children[call.calls.clone()]
+            .iter()
+            .map(|&nested| self.size(CallId(nested)));
+        below.fold((0, 1 + call.params.len()), |(calls, nodes), (c, n)| {
+            (calls + 1 + c, nodes + n)
+        })
+    }
+
+    pub(crate) fn mark(&self) -> Mark {
+        Mark {
+            text: self.text.len(),
+            calls: self.calls.len(),
+            params: self.params.len(),
+            types: self.types.len(),
+            warnings: self.warnings.len(),
+            children: self.children.len(),
+            tuples: self.tuples.len(),
+        }
+    }
+
+    /// Drops what was built since `mark`.
+    pub(crate) fn truncate(&mut self, mark: Mark) {
+        self.text.truncate(mark.text);
+        self.calls.truncate(mark.calls);
+        self.params.truncate(mark.params);
+        self.types.truncate(mark.types);
+        self.solidity.truncate(mark.types);
+        self.warnings.truncate(mark.warnings);
+        self.children.truncate(mark.children);
+        self.tuples.truncate(mark.tuples);
+    }
+
+    pub fn call(&self, id: CallId) -> CallRef<'_> {
+        CallRef {
+            arena: self,
+            call: &self.calls[id.0],
+        }
+    }
+
+    /// Copies `call` and its nested calls in.
+    pub(crate) fn push_decoded(&mut self, call: &DecodedCalldata) -> usize {
+        let calls = call
+            .calls
+            .iter()
+            .map(|nested| CallId(self.push_decoded(nested)))
+            .collect::<Vec<_>>();
+        self.push_node(call, &calls).0
+    }
+
+    /// Same as `push_call` for a call already decoded, without children.
+    fn push_node_only(&mut self, call: &DecodedCalldata) -> usize {
+        let selector = self.push_text(&call.selector);
+        let signature_guess = self.push_text(&call.signature_guess);
+        let first = self.params.len();
+        for param in call.params.iter() {
+            let raw = self.push_text(&param.raw);
+            let value = self.push_text(&param.value);
+            let types = self.types.len();
+            for candidate in param.types.iter() {
+                let solidity = match candidate.solidity == candidate.kind.solidity_name() {
+                    true => None,
+                    false => Some(self.push_text(&candidate.solidity)),
+                };
+                self.types
+                    .push((candidate.kind.clone(), candidate.confidence));
+                self.solidity.push(solidity);
+            }
+            self.params.push(Param {
+                index: param.index,
+                raw,
+                value,
+                types: types..self.types.len(),
+                span: param.span,
+            });
+        }
+        let warnings = self.warnings.len();
+        self.warnings.extend(call.warnings.iter().cloned());
+        let layer = call.layer.as_deref().map(|layer| self.push_text(layer));
+        let tuples = self.tuples.len();
+        self.tuples.extend(call.tuples.iter().cloned());
+        self.calls.push(Call {
+            selector,
+            signature_guess,
+            params: first..self.params.len(),
+            calls: 0..0,
+            warnings: warnings..self.warnings.len(),
+            span: call.span,
+            layer,
+            tuples: tuples..self.tuples.len(),
+        });
+        self.calls.len() - 1
+    }
+
+    fn push_text(&mut self, text: &str) -> Range<usize> {
+        let start = self.text.len();
+        self.text.push_str(text);
+        start..self.text.len()
+    }
+
+    /// Same as `DecodedCalldata::from_params`, without children.
+    fn push_call(&mut self, details: &Params, start: usize) -> usize {
+        let selector = self.push_text(&details.selector);
+        let first = self.params.len();
+        for (index, raw) in details.params.iter().enumerate() {
+            let guessed;
+            let types = match details.types.get(index) {
+                Some(t) => t,
+                None => {
+                    guessed = guess_param_type(raw);
+                    &guessed
+                }
+            };
+            self.push_param(index, raw, types, start);
+        }
+        let params = first..self.params.len();
+
+        // `unknown_<selector>(t0,t1,...)`, written straight into the buffer.
+        let guess_start = self.text.len();
+        let _ = write!(self.text, "unknown_{}(", details.selector);
+        for (i, param) in self.params[params.clone()].iter().enumerate() {
+            let solidity = match self.types.get(param.types.start) {
+                Some((kind, _)) if !param.types.is_empty() => kind.solidity_name(),
+                _ => "bytes32",
+            };
+            if i > 0 {
+                self.text.push(',');
+            }
+            self.text.push_str(solidity);
+        }
+        self.text.push(')');
+        let signature_guess = guess_start..self.text.len();
+
+        self.calls.push(Call {
+            selector,
+            signature_guess,
+            params,
+            calls: 0..0,
+            warnings: 0..0,
+            span: span_of(details, start),
+            layer: None,
+            tuples: 0..0,
+        });
+        self.calls.len() - 1
+    }
+
+    fn push_param(&mut self, index: usize, raw: &str, types: &ParamTypes, start: usize) {
+        let raw_range = self.push_text(raw);
+        let value = self.push_text(&render_value(types, raw));
+        let first = self.types.len();
+        for candidate in candidates(types, raw) {
+            self.types.push((candidate.kind, candidate.confidence));
+            self.solidity.push(None);
+        }
+        let word_start = start + SELECTOR + index * WORD;
+        self.params.push(Param {
+            index,
+            raw: raw_range,
+            value,
+            types: first..self.types.len(),
+            span: Span {
+                start: word_start,
+                end: word_start + raw.len() / 2,
+            },
+        });
+    }
+}
+
+/// Span of a call the layout pass found `start` bytes in, as
+/// `DecodedCalldata::from_params` has it.
+fn span_of(details: &Params, start: usize) -> Span {
+    let end = details
+        .params
+        .last()
+        .map(|raw| start + SELECTOR + (details.params.len() - 1) * WORD + raw.len() / 2)
+        .unwrap_or(start + SELECTOR);
+    Span { start, end }
+}
+
+/// The tree of the calls the layout pass found in a calldata, the root at 0
+/// and its `n`th nested call at `n + 1`, as `Calldata::decode_heuristics`
+/// attaches them.
+struct Found {
+    children: Vec<Vec<usize>>,
+    starts: Vec<usize>,
+    /// Calls under each call and its calls and params, as `DecodeArena::size`.
+    sizes: Vec<(usize, usize)>,
+}
+
+impl Found {
+    fn new(calldata: &Calldata) -> Self {
+        let n = calldata.nested_details.len();
+        let mut children = vec![vec![]; n + 1];
+        for i in 0..n {
+            // Parents come before their children; anything else hangs off the root.
+            let parent = calldata.nested_parent(i).filter(|p| *p < i).map(|p| p + 1);
+            children[parent.unwrap_or(0)].push(i + 1);
+        }
+        let root = calldata.main_details.first().map(|m| m.params.len());
+        let mut sizes = vec![(0, 1 + root.unwrap_or(0))];
+        sizes.extend(calldata.nested_details.iter().map(|c| (0, 1 + c.params.len())));
+        for i in (1..=n).rev() {
+            let parent = calldata.nested_parent(i - 1).filter(|p| *p < i - 1).map(|p| p + 1);
+            let (calls, nodes) = sizes[i];
+            let above = &mut sizes[parent.unwrap_or(0)];
+            *above = (above.0 + 1 + calls, above.1 + nodes);
+        }
+        Self {
+            children,
+            starts: calldata.nested_starts(),
+            sizes,
+        }
+    }
+}
+
+/// A call read from a `DecodeArena`.
+#[derive(Debug, Clone, Copy)]
+pub struct CallRef<'a> {
+    arena: &'a DecodeArena,
+    call: &'a Call,
+}
+
+impl<'a> CallRef<'a> {
+    /// 4-byte method selector (8 chars, no prefix).
+    pub fn selector(&self) -> &'a str {
+        &self.arena.text[self.call.selector.clone()]
+    }
+
+    pub fn signature_guess(&self) -> &'a str {
+        &self.arena.text[self.call.signature_guess.clone()]
+    }
+
+    pub fn span(&self) -> Span {
+        self.call.span
+    }
+
+    pub fn params(&self) -> impl Iterator<Item = ParamRef<'a>> + 'a {
+        let arena = self.arena;
+        arena.params[self.call.params.clone()]
+            .iter()
+            .map(move |param| ParamRef { arena, param })
+    }
+
+    /// Method calls embedded in this call's params.
+    pub fn calls(&self) -> impl Iterator<Item = CallRef<'a>> + 'a {
+        let arena = self.arena;
+        arena.children[self.call.calls.clone()]
+            .iter()
+            .map(move |&id| CallRef {
+                arena,
+                call: &arena.calls[id],
+            })
+    }
+
+    /// Irregularities found in this call's params.
+    pub fn warnings(&self) -> &'a [DecodeWarning] {
+        &self.arena.warnings[self.call.warnings.clone()]
+    }
+
+    /// How the wrapper holding this call makes it, see `DecodedCalldata::layer`.
+    pub fn layer(&self) -> Option<&'a str> {
+        let layer = self.call.layer.clone()?;
+        Some(&self.arena.text[layer])
+    }
+
+    /// Dynamic tuples among the call's params, see `DecodedCalldata::tuples`.
+    pub fn tuples(&self) -> &'a [DecodedTuple] {
+        &self.arena.tuples[self.call.tuples.clone()]
+    }
+
+    /// Copies the call and its nested calls out of the arena.
+    pub fn to_decoded(&self) -> DecodedCalldata {
+        let mut params = self.params().map(|p| p.to_decoded()).collect::<Vec<_>>();
+        attach_texts(&mut params);
+        attach_signatures(&mut params);
+        DecodedCalldata {
+            selector: self.selector().to_string(),
+            signature_guess: self.signature_guess().to_string(),
+            params,
+            calls: self.calls().map(|c| c.to_decoded()).collect(),
+            span: self.span(),
+            warnings: self.warnings().to_vec(),
+            layer: self.layer().map(str::to_string),
+            tuples: self.tuples().to_vec(),
+        }
+    }
+}
+
+/// A param read from a `DecodeArena`.
+#[derive(Debug, Clone, Copy)]
+pub struct ParamRef<'a> {
+    arena: &'a DecodeArena,
+    param: &'a Param,
+}
+
+impl<'a> ParamRef<'a> {
+    /// Position of the word in the method's body.
+    pub fn index(&self) -> usize {
+        self.param.index
+    }
+
+    /// Word as hex (64 chars, no prefix).
+    pub fn raw(&self) -> &'a str {
+        &self.arena.text[self.param.raw.clone()]
+    }
+
+    /// The word rendered as its most likely type.
+    pub fn value(&self) -> &'a str {
+        &self.arena.text[self.param.value.clone()]
+    }
+
+    /// Candidate types with their confidence, most likely first.
+    pub fn types(&self) -> &'a [(Types, f64)] {
+        &self.arena.types[self.param.types.clone()]
+    }
+
+    pub fn span(&self) -> Span {
+        self.param.span
+    }
+
+    pub fn to_decoded(&self) -> DecodedParam {
+        DecodedParam {
+            index: self.index(),
+            raw: self.raw().to_string(),
+            value: self.value().to_string(),
+            types: self
+                .types()
+                .iter()
+                .zip(&self.arena.solidity[self.param.types.clone()])
+                .map(|((kind, confidence), solidity)| TypeCandidate {
+                    kind: kind.clone(),
+                    solidity: match solidity {
+                        Some(solidity) => self.arena.text[solidity.clone()].to_string(),
+                        None => kind.solidity_name().to_string(),
+                    },
+                    confidence: *confidence,
+                })
+                .collect(),
+            span: self.span(),
+            text: None,
+            signature: None,
+            label: None,
+            amount: None,
+        }
+    }
+}
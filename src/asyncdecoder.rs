@@ -0,0 +1,292 @@
+use crate::decoded::DecodedCalldata;
+use crate::decoder::Decoder;
+use crate::proxy::ProxyResolution;
+use crate::rpc::resolve_proxy;
+use crate::signatures::{
+    apply_signatures, find_selectors, selector_of, SelectorHit, SignatureMap, Signatures,
+};
+use ethers::providers::{Http, Provider};
+use ethers::types::Address;
+use futures::future::{join, join_all};
+use serde::Serialize;
+use serde_json::Value;
+use std::collections::BTreeMap;
+use std::sync::Mutex;
+use std::time::Duration;
+
+// ------------------------------------------------------------
+//  Signature directories
+// ------------------------------------------------------------
+
+/// Lookups taking longer than this fail, by default.
+pub const DEFAULT_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// A public directory of function signatures queried over HTTP, e.g.
+/// openchain or 4byte.
+#[derive(Debug, Clone)]
+pub struct SignatureDirectory {
+    client: reqwest::Client,
+    /// With `{selector}` standing for the `0x` prefixed selector.
+    url: String,
+    timeout: Duration,
+}
+
+impl SignatureDirectory {
+    /// A directory answering like openchain's or 4byte's API at `url`.
+    pub fn new(url: &str) -> Self {
+        Self {
+            client: reqwest::Client::new(),
+            url: url.to_string(),
+            timeout: DEFAULT_TIMEOUT,
+        }
+    }
+
+    pub fn openchain() -> Self {
+        Self::new("https://api.openchain.xyz/signature-database/v1/lookup?function={selector}")
+    }
+
+    pub fn fourbyte() -> Self {
+        Self::new("https://www.4byte.directory/api/v1/signatures/?hex_signature={selector}")
+    }
+
+    pub fn with_timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = timeout;
+        self
+    }
+
+    /// Signatures the directory has for `selector` (8 hex chars, `0x` optional),
+    /// those that don't hash to it left out.
+    pub async fn lookup(&self, selector: &str) -> Result<Vec<String>, String> {
+        let selector = format!("0x{}", selector.trim_start_matches("0x").to_lowercase());
+        let url = self.url.replace("{selector}", &selector);
+        let body = self
+            .client
+            .get(&url)
+            .timeout(self.timeout)
+            .send()
+            .await
+            .and_then(|response| response.error_for_status())
+            .map_err(|e| e.to_string())?
+            .text()
+            .await
+            .map_err(|e| e.to_string())?;
+        let json = serde_json::from_str::<Value>(&body).map_err(|e| e.to_string())?;
+        Ok(directory_signatures(&json)
+            .into_iter()
+            .filter(|s| format!("0x{}", selector_of(s)) == selector)
+            .collect())
+    }
+}
+
+/// The signatures of an openchain (`result.function.<selector>[].name`) or
+/// 4byte (`results[].text_signature`, newest first) response.
+pub fn directory_signatures(json: &Value) -> Vec<String> {
+    let openchain = json["result"]["function"]
+        .as_object()
+        .into_iter()
+        .flat_map(|functions| functions.values())
+        .filter_map(|entries| entries.as_array())
+        .flatten()
+        .filter_map(|entry| entry["name"].as_str());
+    // 4byte lists the oldest submission last, and it's usually the real one.
+    let fourbyte = json["results"]
+        .as_array()
+        .into_iter()
+        .flat_map(|results| results.iter().rev())
+        .filter_map(|entry| entry["text_signature"].as_str());
+    let mut out: Vec<String> = vec![];
+    for signature in openchain.chain(fourbyte) {
+        if !out.iter().any(|s| s == signature) {
+            out.push(signature.to_string());
+        }
+    }
+    out
+}
+
+// ------------------------------------------------------------
+//  Async decoder
+// ------------------------------------------------------------
+
+/// A decode with what the network added to it.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct EnrichedDecode {
+    pub decoded: DecodedCalldata,
+    /// Every selector of the tree with its signatures, the local ones first.
+    pub selectors: Vec<SelectorHit>,
+    /// The implementation the target delegates to, when it's a proxy.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub proxy: Option<ProxyResolution>,
+    /// Lookups that failed; the decode is complete without them.
+    pub errors: Vec<String>,
+}
+
+/// A `Decoder` whose decodes are enriched over the network: unknown selectors
+/// looked up in signature directories, and proxies resolved to their
+/// implementation over RPC.
+///
+/// The decode itself stays synchronous and offline (`decoder()`); only the
+/// enrichment is async. Its lookups run concurrently, and dropping the future
+/// cancels those still in flight.
+pub struct AsyncDecoder {
+    decoder: Decoder,
+    directories: Vec<SignatureDirectory>,
+    provider: Option<Provider<Http>>,
+    /// Directory answers by selector, failures left out to be retried.
+    found: Mutex<BTreeMap<String, Vec<String>>>,
+}
+
+impl AsyncDecoder {
+    /// Enriches the decodes of `decoder`, with no directory or provider yet.
+    pub fn new(decoder: Decoder) -> Self {
+        Self {
+            decoder,
+            directories: vec![],
+            provider: None,
+            found: Mutex::new(BTreeMap::new()),
+        }
+    }
+
+    /// Looks up the selectors `decoder`'s signatures don't know in `directory`
+    /// too, after the directories added before.
+    pub fn with_directory(mut self, directory: SignatureDirectory) -> Self {
+        self.directories.push(directory);
+        self
+    }
+
+    /// Resolves proxies with `provider`, at the latest block.
+    pub fn with_provider(mut self, provider: Provider<Http>) -> Self {
+        self.provider = Some(provider);
+        self
+    }
+
+    /// The synchronous decoder, for decodes that don't touch the network.
+    pub fn decoder(&self) -> &Decoder {
+        &self.decoder
+    }
+
+    /// Decodes `calldata` hex and enriches it.
+    pub async fn decode(&self, calldata: &str) -> Result<EnrichedDecode, String> {
+        let decoded = self.decoder.decode(calldata)?;
+        Ok(self.enrich(decoded, None).await)
+    }
+
+    /// Same as `decode` for a call to `to`: when `to` is a proxy, nested calls
+    /// are limited to its implementation's selectors (see `rpc::resolve_proxy`).
+    pub async fn decode_call(&self, to: Address, calldata: &str) -> Result<EnrichedDecode, String> {
+        let decoded = self.decoder.decode(calldata)?;
+        let mut enriched = self.enrich(decoded, Some(to)).await;
+        if let Some(proxy) = enriched.proxy.as_ref().filter(|p| !p.selectors.is_empty()) {
+            enriched.decoded = self
+                .decoder
+                .decode_for_target(&crate::decode_hex(calldata), proxy.selectors.clone())?;
+            // The nested calls changed, their selectors with them.
+            let known = enriched
+                .selectors
+                .iter()
+                .map(|h| (h.selector.clone(), h.signatures.clone()))
+                .collect::<BTreeMap<_, _>>();
+            enriched.selectors = find_selectors(&enriched.decoded, self.decoder.signatures());
+            for hit in enriched
+                .selectors
+                .iter_mut()
+                .filter(|h| h.signatures.is_empty())
+            {
+                hit.signatures = known.get(&hit.selector).cloned().unwrap_or_default();
+            }
+            if self.decoder.signature_types() {
+                type_as_found(&mut enriched.decoded, &enriched.selectors);
+            }
+        }
+        Ok(enriched)
+    }
+
+    /// Runs the directory lookups and proxy resolution concurrently.
+    async fn enrich(&self, mut decoded: DecodedCalldata, to: Option<Address>) -> EnrichedDecode {
+        let mut selectors = self.decoder.selectors(&decoded);
+        let mut unknown = selectors
+            .iter()
+            .filter(|h| h.signatures.is_empty())
+            .map(|h| h.selector.clone())
+            .collect::<Vec<_>>();
+        unknown.sort();
+        unknown.dedup();
+
+        let lookups = join_all(unknown.iter().map(|selector| self.lookup(selector)));
+        let proxy = async {
+            match (&self.provider, to) {
+                (Some(provider), Some(to)) => resolve_proxy(provider, to, None).await,
+                _ => Ok(None),
+            }
+        };
+        let (lookups, proxy) = join(lookups, proxy).await;
+
+        let mut errors = vec![];
+        let mut found = BTreeMap::new();
+        for (selector, (signatures, failures)) in unknown.into_iter().zip(lookups) {
+            errors.extend(failures);
+            found.insert(selector, signatures);
+        }
+        for hit in selectors.iter_mut().filter(|h| h.signatures.is_empty()) {
+            hit.signatures = found.get(&hit.selector).cloned().unwrap_or_default();
+        }
+        if self.decoder.signature_types() {
+            type_as_found(&mut decoded, &selectors);
+        }
+        let proxy = proxy.unwrap_or_else(|e| {
+            errors.push(format!("proxy: {}", e));
+            None
+        });
+        EnrichedDecode {
+            decoded,
+            selectors,
+            proxy,
+            errors,
+        }
+    }
+
+    /// Signatures of every directory for `selector`, and the lookups that failed.
+    async fn lookup(&self, selector: &str) -> (Vec<String>, Vec<String>) {
+        if let Some(hit) = self
+            .found
+            .lock()
+            .ok()
+            .and_then(|f| f.get(selector).cloned())
+        {
+            return (hit, vec![]);
+        }
+        let answers = join_all(self.directories.iter().map(|d| d.lookup(selector))).await;
+        let mut signatures: Vec<String> = vec![];
+        let mut errors = vec![];
+        for answer in answers {
+            match answer {
+                Ok(found) => {
+                    for signature in found {
+                        if !signatures.contains(&signature) {
+                            signatures.push(signature);
+                        }
+                    }
+                }
+                Err(e) => errors.push(format!("{}: {}", selector, e)),
+            }
+        }
+        if errors.is_empty() {
+            if let Ok(mut found) = self.found.lock() {
+                found.insert(selector.to_string(), signatures.clone());
+            }
+        }
+        (signatures, errors)
+    }
+}
+
+/// Types the calls of `root` as the signatures of `selectors`, the local and
+/// directory answers alike.
+fn type_as_found(root: &mut DecodedCalldata, selectors: &[SelectorHit]) {
+    let mut map = SignatureMap::new();
+    for hit in selectors {
+        for signature in hit.signatures.iter() {
+            map.insert_with_selector(&hit.selector, signature);
+        }
+    }
+    apply_signatures(root, &Signatures::new().with_source(map));
+}
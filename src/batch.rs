@@ -0,0 +1,273 @@
+use crate::decoded::DecodedCalldata;
+use crate::heuristics::Heuristics;
+use crate::limits::Limits;
+use crate::{check_hex, Calldata};
+use memmap2::Mmap;
+use rayon::prelude::*;
+use std::fs::File;
+use std::path::Path;
+
+// ------------------------------------------------------------
+//  Batch decoding
+// ------------------------------------------------------------
+
+/// Calldatas decoded in parallel at a time when reading a file.
+pub const DEFAULT_WINDOW: usize = 4096;
+
+/// Decodes one calldata, turning bad input and decoder panics into errors.
+pub fn decode_one(calldata: &str) -> Result<DecodedCalldata, String> {
+    check_hex(calldata)?;
+    std::panic::catch_unwind(|| Calldata::new(calldata).decode())
+        .map_err(|_| "decoder panicked".to_string())
+}
+
+/// Decodes `calldatas` in parallel on rayon's thread pool.
+///
+/// ## Returns
+/// 1. One result per calldata, in input order.
+pub fn decode_batch<S: AsRef<str> + Sync>(calldatas: &[S]) -> Vec<Result<DecodedCalldata, String>> {
+    calldatas
+        .par_iter()
+        .map(|c| decode_one(c.as_ref()))
+        .collect()
+}
+
+/// Same as `decode_batch` for inputs too large to hold at once, e.g. a stream of
+/// historical transactions: at most `window` calldatas (and their decodes) are
+/// in memory at a time.
+///
+/// ## Params
+/// 1. calldatas - pulled lazily, `window` at a time.
+/// 2. window - how many calldatas are decoded in parallel before handing them on.
+/// 3. sink - called with each input's index and result, in input order.
+pub fn decode_batch_bounded<S, I, F>(calldatas: I, window: usize, mut sink: F)
+where
+    I: IntoIterator<Item = S>,
+    S: AsRef<str> + Sync,
+    F: FnMut(usize, Result<DecodedCalldata, String>),
+{
+    let mut calldatas = calldatas.into_iter();
+    let mut index = 0;
+    loop {
+        let chunk = calldatas.by_ref().take(window.max(1)).collect::<Vec<S>>();
+        if chunk.is_empty() {
+            break;
+        }
+        for result in decode_batch(&chunk) {
+            sink(index, result);
+            index += 1;
+        }
+    }
+}
+
+// ------------------------------------------------------------
+//  Memory-mapped files
+// ------------------------------------------------------------
+
+/// How the calldatas of a file are laid out.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RecordFormat {
+    /// One hex calldata per line; blank lines and `#` comments are skipped.
+    HexLines,
+    /// Raw calldatas, each preceded by its byte length as a 4-byte big-endian integer.
+    LengthPrefixed,
+}
+
+/// A calldata of a `CalldataFile`, borrowed from the mapping.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Record<'a> {
+    Hex(&'a str),
+    Bytes(&'a [u8]),
+}
+
+impl Record<'_> {
+    /// Same as `decode_one`, applying only the enabled `heuristics` within `limits`.
+    pub fn decode(
+        &self,
+        heuristics: Heuristics,
+        limits: Limits,
+    ) -> Result<DecodedCalldata, String> {
+        let bytes = match self {
+            Record::Hex(hex) => {
+                check_hex(hex)?;
+                crate::decode_hex(hex)
+            }
+            Record::Bytes(bytes) if bytes.len() < 4 => {
+                return Err(format!("{} bytes is shorter than a selector", bytes.len()))
+            }
+            Record::Bytes(bytes) => bytes.to_vec(),
+        };
+        std::panic::catch_unwind(|| {
+            Calldata::from_bytes_with_limits(bytes, heuristics, limits).map(|c| c.decode())
+        })
+        .map_err(|_| "decoder panicked".to_string())?
+    }
+
+    /// The calldata as `0x` prefixed hex.
+    pub fn to_hex(&self) -> String {
+        match self {
+            Record::Hex(hex) => format!("0x{}", faster_hex::hex_string(&crate::decode_hex(hex))),
+            Record::Bytes(bytes) => format!("0x{}", faster_hex::hex_string(bytes)),
+        }
+    }
+}
+
+/// A file of calldatas read through a memory map, so corpora larger than
+/// memory can be decoded: records borrow from the mapping and only a window
+/// of decodes is held at a time.
+///
+/// The file must not be modified while it's open.
+pub struct CalldataFile {
+    /// `None` for an empty file, which can't be mapped.
+    map: Option<Mmap>,
+    format: RecordFormat,
+    heuristics: Heuristics,
+    limits: Limits,
+}
+
+impl CalldataFile {
+    pub fn open(path: &Path, format: RecordFormat) -> Result<Self, String> {
+        let file = File::open(path).map_err(|e| format!("{}: {}", path.display(), e))?;
+        let len = file
+            .metadata()
+            .map_err(|e| format!("{}: {}", path.display(), e))?
+            .len();
+        let map = match len {
+            0 => None,
+            // SAFETY: the mapping is only read, and the file is documented to stay unmodified.
+            _ => Some(
+                unsafe { Mmap::map(&file) }.map_err(|e| format!("{}: {}", path.display(), e))?,
+            ),
+        };
+        #[cfg(unix)]
+        if let Some(map) = &map {
+            // Records are read front to back; a failed hint only costs speed.
+            let _ = map.advise(memmap2::Advice::Sequential);
+        }
+        Ok(Self {
+            map,
+            format,
+            heuristics: Heuristics::default(),
+            limits: Limits::default(),
+        })
+    }
+
+    /// Decodes records applying only the enabled `heuristics`.
+    pub fn with_heuristics(mut self, heuristics: Heuristics) -> Self {
+        self.heuristics = heuristics;
+        self
+    }
+
+    /// Decodes records within `limits`.
+    pub fn with_limits(mut self, limits: Limits) -> Self {
+        self.limits = limits;
+        self
+    }
+
+    pub fn as_bytes(&self) -> &[u8] {
+        self.map.as_deref().unwrap_or_default()
+    }
+
+    /// The records of the file, in order. A record that can't be read is an error;
+    /// a truncated length-prefixed record ends the iteration.
+    pub fn records(&self) -> Records<'_> {
+        Records {
+            bytes: self.as_bytes(),
+            format: self.format,
+            pos: 0,
+            line: 0,
+        }
+    }
+
+    /// Decodes every record in parallel, `window` at a time.
+    ///
+    /// ## Params
+    /// 1. window - how many records are decoded in parallel before handing them on.
+    /// 2. sink - called with each record's index and decode, in file order.
+    pub fn decode<F>(&self, window: usize, mut sink: F)
+    where
+        F: FnMut(usize, Result<(Record<'_>, DecodedCalldata), String>),
+    {
+        let mut records = self.records();
+        let mut index = 0;
+        loop {
+            let chunk = records.by_ref().take(window.max(1)).collect::<Vec<_>>();
+            if chunk.is_empty() {
+                break;
+            }
+            let results = chunk
+                .into_par_iter()
+                .map(|record| {
+                    let record = record?;
+                    let decoded = record.decode(self.heuristics, self.limits)?;
+                    Ok((record, decoded))
+                })
+                .collect::<Vec<_>>();
+            for result in results {
+                sink(index, result);
+                index += 1;
+            }
+        }
+    }
+}
+
+/// Iterator over the records of a `CalldataFile`.
+pub struct Records<'a> {
+    bytes: &'a [u8],
+    format: RecordFormat,
+    pos: usize,
+    /// Lines read so far, for error messages.
+    line: usize,
+}
+
+impl<'a> Iterator for Records<'a> {
+    type Item = Result<Record<'a>, String>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        match self.format {
+            RecordFormat::HexLines => self.next_line(),
+            RecordFormat::LengthPrefixed => self.next_prefixed(),
+        }
+    }
+}
+
+impl<'a> Records<'a> {
+    fn next_line(&mut self) -> Option<Result<Record<'a>, String>> {
+        while self.pos < self.bytes.len() {
+            let rest = &self.bytes[self.pos..];
+            let end = rest.iter().position(|b| *b == b'\n').unwrap_or(rest.len());
+            self.pos += end + 1;
+            self.line += 1;
+
+            let line = &rest[..end];
+            let line = &line[..line.iter().position(|b| *b == b'#').unwrap_or(line.len())];
+            let line = match std::str::from_utf8(line) {
+                Ok(line) => line.trim(),
+                Err(e) => return Some(Err(format!("line {}: {}", self.line, e))),
+            };
+            if !line.is_empty() {
+                return Some(Ok(Record::Hex(line)));
+            }
+        }
+        None
+    }
+
+    fn next_prefixed(&mut self) -> Option<Result<Record<'a>, String>> {
+        let rest = self.bytes.get(self.pos..).filter(|rest| !rest.is_empty())?;
+        let at = self.pos;
+        let record = rest
+            .get(..4)
+            .map(|len| u32::from_be_bytes([len[0], len[1], len[2], len[3]]) as usize)
+            .and_then(|len| rest.get(4..4usize.checked_add(len)?));
+        match record {
+            Some(record) => {
+                self.pos += 4 + record.len();
+                Some(Ok(Record::Bytes(record)))
+            }
+            None => {
+                self.pos = self.bytes.len();
+                Some(Err(format!("record at byte {} is truncated", at)))
+            }
+        }
+    }
+}
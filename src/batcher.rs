@@ -0,0 +1,593 @@
+use crate::blob::parse_frames;
+use crate::decoded::DecodedCalldata;
+use crate::heuristics::Heuristics;
+use crate::limits::Limits;
+use crate::rawtx::{
+    parse_access_list, parse_h256, serialize_hex, AccessListItem, DecodedRawTransaction,
+    RawTransaction,
+};
+use crate::render::address::serialize_checksum_opt;
+use crate::rlp::Rlp;
+use crate::Calldata;
+use primitive_types::{H160, H256, U256};
+use serde::Serialize;
+use std::io::Read;
+
+// ------------------------------------------------------------
+//  Batch inboxes
+// ------------------------------------------------------------
+
+/// Batch inboxes of the main OP Stack chains, by name.
+pub const BATCH_INBOXES: &[(&str, &str)] = &[
+    ("OP Mainnet", "ff00000000000000000000000000000000000010"),
+    ("Base", "ff00000000000000000000000000000000008453"),
+    ("OP Sepolia", "ff00000000000000000000000000000011155420"),
+    ("Base Sepolia", "ff00000000000000000000000000000000084532"),
+];
+
+/// Name of the chain whose batch inbox `to` is, among [`BATCH_INBOXES`].
+pub fn batch_inbox_name(to: &H160) -> Option<&'static str> {
+    let hex = faster_hex::hex_string(to.as_bytes());
+    BATCH_INBOXES
+        .iter()
+        .find(|(_, inbox)| *inbox == hex)
+        .map(|(name, _)| *name)
+}
+
+/// Whether `to` is a batch inbox: a known one, or one following the
+/// `0xff00..00<chain id>` convention, the chain id written in decimal digits.
+pub fn is_batch_inbox(to: &H160) -> bool {
+    let hex = faster_hex::hex_string(to.as_bytes());
+    let Some(chain) = hex.strip_prefix("ff") else {
+        return false;
+    };
+    let digits = chain.trim_start_matches('0');
+    batch_inbox_name(to).is_some()
+        || (!digits.is_empty() && digits.len() <= 20 && digits.bytes().all(|b| b.is_ascii_digit()))
+}
+
+// ------------------------------------------------------------
+//  Channels
+// ------------------------------------------------------------
+
+/// Most bytes a channel may decompress to, as of Fjord.
+pub const MAX_CHANNEL_BYTES: usize = 100_000_000;
+
+/// Most blocks, or transactions, in a span batch.
+pub const MAX_SPAN_ELEMENTS: u64 = 10_000_000;
+
+/// How a channel's data was compressed.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub enum Compression {
+    /// RFC 1950 zlib, recognised by its deflate method nibble.
+    Zlib,
+    /// Brotli behind a `0x01` version byte, from Fjord.
+    Brotli,
+}
+
+/// Decompresses a channel's data, zlib or brotli as its first byte says.
+pub fn decompress(data: &[u8]) -> Result<(Compression, Vec<u8>), String> {
+    let (compression, reader): (_, Box<dyn Read + '_>) = match data.first() {
+        Some(b) if b & 0x0f == 8 || b & 0x0f == 15 => (
+            Compression::Zlib,
+            Box::new(flate2::read::ZlibDecoder::new(data)),
+        ),
+        Some(1) => (
+            Compression::Brotli,
+            Box::new(brotli_decompressor::Decompressor::new(&data[1..], 4096)),
+        ),
+        Some(b) => return Err(format!("unknown channel compression 0x{:02x}", b)),
+        None => return Err("empty channel".to_string()),
+    };
+    let out = read_capped(reader, MAX_CHANNEL_BYTES)
+        .map_err(|e| format!("{:?} channel: {}", compression, e))?;
+    Ok((compression, out))
+}
+
+/// Reads `reader` to the end, failing past `max` bytes rather than
+/// inflating a compression bomb.
+pub(crate) fn read_capped(reader: impl Read, max: usize) -> Result<Vec<u8>, String> {
+    let mut out = vec![];
+    reader
+        .take(max as u64 + 1)
+        .read_to_end(&mut out)
+        .map_err(|e| e.to_string())?;
+    match out.len() > max {
+        true => Err(format!("decompresses past {} bytes", max)),
+        false => Ok(out),
+    }
+}
+
+/// A channel reassembled from its frames, with the batches it holds.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DecodedChannel {
+    /// 16-byte channel id (32 hex chars, no prefix).
+    pub id: String,
+    /// Frames received for the channel.
+    pub frames: usize,
+    /// Whether every frame up to the last one was received.
+    pub complete: bool,
+    /// `None` until the channel is complete.
+    pub compression: Option<Compression>,
+    pub batches: Vec<Batch>,
+    /// Why the channel, or its batches past the decoded ones, couldn't be read.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+}
+
+/// A batch of L2 blocks from a channel.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(tag = "kind", rename_all = "camelCase")]
+pub enum Batch {
+    /// Version 0: one block.
+    Singular(SingularBatch),
+    /// Version 1, from Delta: consecutive blocks, fields grouped across them.
+    Span(SpanBatch),
+}
+
+impl Batch {
+    /// Decodes of the transactions' calldata, in order.
+    pub fn calls(&self) -> Vec<&DecodedCalldata> {
+        match self {
+            Batch::Singular(batch) => batch
+                .transactions
+                .iter()
+                .filter_map(|tx| tx.decoded.as_ref())
+                .collect(),
+            Batch::Span(batch) => batch
+                .transactions
+                .iter()
+                .filter_map(|tx| tx.decoded.as_ref())
+                .collect(),
+        }
+    }
+
+    /// Same as `calls`, to annotate the decodes.
+    pub fn calls_mut(&mut self) -> Vec<&mut DecodedCalldata> {
+        match self {
+            Batch::Singular(batch) => batch
+                .transactions
+                .iter_mut()
+                .filter_map(|tx| tx.decoded.as_mut())
+                .collect(),
+            Batch::Span(batch) => batch
+                .transactions
+                .iter_mut()
+                .filter_map(|tx| tx.decoded.as_mut())
+                .collect(),
+        }
+    }
+}
+
+/// `rlp([parent_hash, epoch_number, epoch_hash, timestamp, transaction_list])`
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SingularBatch {
+    pub parent_hash: H256,
+    /// L1 origin of the block.
+    pub epoch_number: u64,
+    pub epoch_hash: H256,
+    pub timestamp: u64,
+    pub transactions: Vec<DecodedRawTransaction>,
+}
+
+/// A span batch. Its transactions have no chain id, hence no hash, and
+/// their signatures are left out.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SpanBatch {
+    /// Timestamp of the first block, relative to genesis.
+    pub rel_timestamp: u64,
+    /// L1 origin of the last block.
+    pub l1_origin_num: u64,
+    /// First 20 bytes of the parent hash (40 hex chars, no prefix).
+    pub parent_check: String,
+    /// First 20 bytes of the last block's L1 origin hash.
+    pub l1_origin_check: String,
+    /// Per block, whether its L1 origin moved on from the previous block's.
+    pub origin_bits: Vec<bool>,
+    /// Per block, its number of transactions.
+    pub block_tx_counts: Vec<u64>,
+    pub transactions: Vec<SpanTransaction>,
+}
+
+/// A transaction of a span batch.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SpanTransaction {
+    /// Index of its block in the span.
+    pub block: usize,
+    #[serde(rename = "type")]
+    pub tx_type: u8,
+    pub nonce: u64,
+    pub gas_limit: u64,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub gas_price: Option<U256>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub max_priority_fee_per_gas: Option<U256>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub max_fee_per_gas: Option<U256>,
+    /// `None` for contract creations.
+    #[serde(serialize_with = "serialize_checksum_opt")]
+    pub to: Option<H160>,
+    pub value: U256,
+    #[serde(serialize_with = "serialize_hex")]
+    pub input: Vec<u8>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub access_list: Vec<AccessListItem>,
+    /// `None` when the input isn't calldata.
+    pub decoded: Option<DecodedCalldata>,
+}
+
+/// A frame waiting for the rest of its channel.
+#[derive(Debug, Clone)]
+struct Frame {
+    number: u16,
+    is_last: bool,
+    data: Vec<u8>,
+}
+
+/// Reassembles OP Stack channels from batcher payloads, the calldata sent
+/// to a batch inbox or the payload of a blob, and decodes the batches in
+/// them.
+///
+/// Payloads are pushed in the order they landed on L1. Frames of a channel
+/// may span payloads; a frame number seen twice keeps its first frame.
+#[derive(Debug, Clone, Default)]
+pub struct BatcherDecoder {
+    heuristics: Heuristics,
+    limits: Limits,
+    /// Channels by first frame seen.
+    channels: Vec<([u8; 16], Vec<Frame>)>,
+}
+
+impl BatcherDecoder {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn with_heuristics(mut self, heuristics: Heuristics) -> Self {
+        self.heuristics = heuristics;
+        self
+    }
+
+    pub fn with_limits(mut self, limits: Limits) -> Self {
+        self.limits = limits;
+        self
+    }
+
+    /// Adds the frames of a payload: a derivation version `0` then frames.
+    ///
+    /// ## Returns
+    /// 1. The number of frames in the payload.
+    pub fn push(&mut self, payload: &[u8]) -> Result<usize, String> {
+        let headers = parse_frames(payload).ok_or("payload isn't batcher frames")?;
+        for header in headers.iter() {
+            let start = header.start + 22;
+            let frame = Frame {
+                number: header.frame_number,
+                is_last: header.is_last,
+                data: payload[start..start + header.data_len as usize].to_vec(),
+            };
+            let mut id = [0u8; 16];
+            faster_hex::hex_decode(header.channel_id.as_bytes(), &mut id)
+                .map_err(|e| e.to_string())?;
+            match self.channels.iter_mut().find(|(c, _)| *c == id) {
+                Some((_, frames)) if frames.iter().any(|f| f.number == frame.number) => {}
+                Some((_, frames)) => frames.push(frame),
+                None => self.channels.push((id, vec![frame])),
+            }
+        }
+        Ok(headers.len())
+    }
+
+    /// Every channel seen, decoded when complete.
+    pub fn channels(&self) -> Vec<DecodedChannel> {
+        self.channels
+            .iter()
+            .map(|(id, frames)| self.channel(id, frames))
+            .collect()
+    }
+
+    fn channel(&self, id: &[u8; 16], frames: &[Frame]) -> DecodedChannel {
+        let mut channel = DecodedChannel {
+            id: faster_hex::hex_string(id),
+            frames: frames.len(),
+            complete: false,
+            compression: None,
+            batches: vec![],
+            error: None,
+        };
+        let Some(last) = frames.iter().find(|f| f.is_last).map(|f| f.number) else {
+            channel.error = Some("no last frame yet".to_string());
+            return channel;
+        };
+        let ordered = (0..=last)
+            .map(|n| frames.iter().find(|f| f.number == n))
+            .collect::<Option<Vec<_>>>();
+        let Some(ordered) = ordered else {
+            channel.error = Some(format!(
+                "{} of {} frames received",
+                frames.iter().filter(|f| f.number <= last).count(),
+                last as usize + 1
+            ));
+            return channel;
+        };
+        channel.complete = true;
+        let data = ordered.iter().flat_map(|f| f.data.iter().copied());
+        let (compression, out) = match decompress(&data.collect::<Vec<_>>()) {
+            Ok((compression, out)) => (compression, out),
+            Err(e) => {
+                channel.error = Some(e);
+                return channel;
+            }
+        };
+        channel.compression = Some(compression);
+        // A batch is an RLP string: version byte then content. Whatever
+        // doesn't read as one ends the channel.
+        let mut rest = out.as_slice();
+        while !rest.is_empty() {
+            let batch = Rlp::decode_prefix(rest)
+                .and_then(|(item, after)| Ok((self.batch(item.as_bytes()?)?, after)));
+            match batch {
+                Ok((batch, after)) => {
+                    channel.batches.push(batch);
+                    rest = after;
+                }
+                Err(e) => {
+                    channel.error = Some(format!("batch {}: {}", channel.batches.len(), e));
+                    break;
+                }
+            }
+        }
+        channel
+    }
+
+    fn batch(&self, bytes: &[u8]) -> Result<Batch, String> {
+        match bytes.split_first() {
+            Some((0, content)) => self.singular(content).map(Batch::Singular),
+            Some((1, content)) => self.span(content).map(Batch::Span),
+            Some((version, _)) => Err(format!("unknown batch version {}", version)),
+            None => Err("empty batch".to_string()),
+        }
+    }
+
+    fn singular(&self, content: &[u8]) -> Result<SingularBatch, String> {
+        let item = Rlp::decode(content)?;
+        let [parent_hash, epoch_number, epoch_hash, timestamp, transactions] = item.as_list()?
+        else {
+            return Err("singular batch has 5 fields".to_string());
+        };
+        let transactions = transactions
+            .as_list()?
+            .iter()
+            .map(|tx| {
+                let tx = RawTransaction::from_bytes(tx.as_bytes()?)?;
+                DecodedRawTransaction::new(tx, self.heuristics, self.limits)
+            })
+            .collect::<Result<_, _>>()?;
+        Ok(SingularBatch {
+            parent_hash: parse_h256(parent_hash)?,
+            epoch_number: epoch_number.as_u64()?,
+            epoch_hash: parse_h256(epoch_hash)?,
+            timestamp: timestamp.as_u64()?,
+            transactions,
+        })
+    }
+
+    /// `prefix ++ payload`, see the Delta span batch format.
+    fn span(&self, content: &[u8]) -> Result<SpanBatch, String> {
+        let mut r = Reader(content);
+        let rel_timestamp = r.uvarint()?;
+        let l1_origin_num = r.uvarint()?;
+        let parent_check = faster_hex::hex_string(r.take(20)?);
+        let l1_origin_check = faster_hex::hex_string(r.take(20)?);
+        let block_count = r.count(1)?;
+        let origin_bits = r.bits(block_count)?;
+        let block_tx_counts = (0..block_count)
+            .map(|_| r.uvarint())
+            .collect::<Result<Vec<_>, _>>()?;
+        let total = block_tx_counts
+            .iter()
+            .try_fold(0u64, |sum, n| sum.checked_add(*n))
+            .filter(|sum| *sum <= MAX_SPAN_ELEMENTS)
+            .ok_or("span batch has too many transactions")?;
+        let total = usize::try_from(total).map_err(|e| e.to_string())?;
+        // Each transaction takes at least its 64-byte signature.
+        if total > r.0.len() / 64 {
+            return Err(format!(
+                "{} transactions don't fit in {} bytes",
+                total,
+                r.0.len()
+            ));
+        }
+
+        let creations = r.bits(total)?;
+        let _y_parities = r.bits(total)?;
+        r.take(total * 64)?;
+        let tos = creations
+            .iter()
+            .map(|creation| match creation {
+                true => Ok(None),
+                false => r.take(20).map(|to| Some(H160::from_slice(to))),
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+        let datas = (0..total)
+            .map(|_| r.tx_data())
+            .collect::<Result<Vec<_>, _>>()?;
+        let nonces = (0..total)
+            .map(|_| r.uvarint())
+            .collect::<Result<Vec<_>, _>>()?;
+        let gases = (0..total)
+            .map(|_| r.uvarint())
+            .collect::<Result<Vec<_>, _>>()?;
+        let legacy = datas.iter().filter(|d| d.tx_type == 0).count();
+        r.bits(legacy)?;
+        if !r.0.is_empty() {
+            return Err(format!("{} bytes after the span batch", r.0.len()));
+        }
+
+        let blocks = block_tx_counts
+            .iter()
+            .enumerate()
+            .flat_map(|(block, n)| std::iter::repeat_n(block, *n as usize));
+        let transactions = datas
+            .into_iter()
+            .zip(blocks)
+            .zip(tos.into_iter().zip(nonces.into_iter().zip(gases)))
+            .map(|((data, block), (to, (nonce, gas_limit)))| {
+                let decoded = match to.is_some() && data.input.len() >= 4 {
+                    true => {
+                        let input = data.input.clone();
+                        Some(
+                            Calldata::from_bytes_with_limits(input, self.heuristics, self.limits)?
+                                .decode(),
+                        )
+                    }
+                    false => None,
+                };
+                Ok(SpanTransaction {
+                    block,
+                    tx_type: data.tx_type,
+                    nonce,
+                    gas_limit,
+                    gas_price: data.gas_price,
+                    max_priority_fee_per_gas: data.max_priority_fee_per_gas,
+                    max_fee_per_gas: data.max_fee_per_gas,
+                    to,
+                    value: data.value,
+                    input: data.input,
+                    access_list: data.access_list,
+                    decoded,
+                })
+            })
+            .collect::<Result<_, String>>()?;
+        Ok(SpanBatch {
+            rel_timestamp,
+            l1_origin_num,
+            parent_check,
+            l1_origin_check,
+            origin_bits,
+            block_tx_counts,
+            transactions,
+        })
+    }
+}
+
+/// The fields a span batch keeps in a transaction's `tx_data`.
+struct TxData {
+    tx_type: u8,
+    value: U256,
+    gas_price: Option<U256>,
+    max_priority_fee_per_gas: Option<U256>,
+    max_fee_per_gas: Option<U256>,
+    input: Vec<u8>,
+    access_list: Vec<AccessListItem>,
+}
+
+/// Reads a span batch from the front.
+struct Reader<'a>(&'a [u8]);
+
+impl<'a> Reader<'a> {
+    fn take(&mut self, n: usize) -> Result<&'a [u8], String> {
+        match self.0.len() >= n {
+            true => {
+                let (taken, rest) = self.0.split_at(n);
+                self.0 = rest;
+                Ok(taken)
+            }
+            false => Err(format!("span batch ends {} bytes short", n - self.0.len())),
+        }
+    }
+
+    /// An unsigned LEB128 integer.
+    fn uvarint(&mut self) -> Result<u64, String> {
+        let mut value = 0u64;
+        for i in 0..10 {
+            let byte = self.take(1)?[0];
+            value |= ((byte & 0x7f) as u64) << (7 * i);
+            if byte & 0x80 == 0 {
+                return Ok(value);
+            }
+        }
+        Err("varint over 64 bits".to_string())
+    }
+
+    /// A count of elements of at least `size` bytes each, bounded.
+    fn count(&mut self, size: usize) -> Result<usize, String> {
+        let n = self.uvarint()?;
+        match n > MAX_SPAN_ELEMENTS || n as usize > self.0.len() / size {
+            true => Err(format!("span batch count {} is out of bounds", n)),
+            false => Ok(n as usize),
+        }
+    }
+
+    /// `n` bits, a big-endian integer padded to whole bytes, bit `i` being `1 << i`.
+    fn bits(&mut self, n: usize) -> Result<Vec<bool>, String> {
+        let bytes = self.take(n.div_ceil(8))?;
+        Ok((0..n)
+            .map(|i| bytes[bytes.len() - 1 - i / 8] >> (i % 8) & 1 == 1)
+            .collect())
+    }
+
+    /// `rlp([value, gasPrice, data])` for a legacy transaction, else its
+    /// type then the list of its fee fields, `data` and access list.
+    fn tx_data(&mut self) -> Result<TxData, String> {
+        let tx_type = match self.0.first() {
+            Some(0xc0..) => 0,
+            Some(t @ (1 | 2)) => *t,
+            Some(t) => return Err(format!("unknown span transaction type {}", t)),
+            None => return Err("span batch ends before its transactions".to_string()),
+        };
+        if tx_type != 0 {
+            self.take(1)?;
+        }
+        let (item, rest) = Rlp::decode_prefix(self.0)?;
+        self.0 = rest;
+        let fields = item.as_list()?;
+        let mut data = TxData {
+            tx_type,
+            value: U256::zero(),
+            gas_price: None,
+            max_priority_fee_per_gas: None,
+            max_fee_per_gas: None,
+            input: vec![],
+            access_list: vec![],
+        };
+        match (tx_type, fields) {
+            (0, [value, gas_price, input]) => {
+                data.gas_price = Some(gas_price.as_u256()?);
+                (data.value, data.input) = (value.as_u256()?, input.as_bytes()?.to_vec());
+            }
+            (1, [value, gas_price, input, access_list]) => {
+                data.gas_price = Some(gas_price.as_u256()?);
+                (data.value, data.input) = (value.as_u256()?, input.as_bytes()?.to_vec());
+                data.access_list = parse_access_list(access_list)?;
+            }
+            (2, [value, priority, max_fee, input, access_list]) => {
+                data.max_priority_fee_per_gas = Some(priority.as_u256()?);
+                data.max_fee_per_gas = Some(max_fee.as_u256()?);
+                (data.value, data.input) = (value.as_u256()?, input.as_bytes()?.to_vec());
+                data.access_list = parse_access_list(access_list)?;
+            }
+            (t, fields) => {
+                return Err(format!(
+                    "span transaction of type {} with {} fields",
+                    t,
+                    fields.len()
+                ))
+            }
+        }
+        Ok(data)
+    }
+}
+
+/// Decodes the channels of one batcher payload with the default heuristics
+/// and limits. Channels spanning several payloads need a [`BatcherDecoder`].
+pub fn decode_batcher_payload(payload: &[u8]) -> Result<Vec<DecodedChannel>, String> {
+    let mut decoder = BatcherDecoder::new();
+    decoder.push(payload)?;
+    Ok(decoder.channels())
+}
@@ -0,0 +1,351 @@
+use crate::decode_hex;
+use crate::decoded::DecodedCalldata;
+use primitive_types::{H160, H256, U256};
+
+#[cfg(feature = "derive")]
+pub use calldata_decoder_derive::FromCalldata;
+
+// ------------------------------------------------------------
+//  Binding calls to structs
+// ------------------------------------------------------------
+
+/// A static Solidity type, the kind a single word holds.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SolType {
+    Address,
+    Bool,
+    /// `uintN`, with N bits.
+    Uint(u16),
+    /// `intN`, with N bits.
+    Int(u16),
+    /// `bytesN`, with N bytes.
+    FixedBytes(u8),
+}
+
+impl std::fmt::Display for SolType {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            SolType::Address => write!(f, "address"),
+            SolType::Bool => write!(f, "bool"),
+            SolType::Uint(bits) => write!(f, "uint{}", bits),
+            SolType::Int(bits) => write!(f, "int{}", bits),
+            SolType::FixedBytes(len) => write!(f, "bytes{}", len),
+        }
+    }
+}
+
+impl std::str::FromStr for SolType {
+    type Err = String;
+
+    /// Reads a static Solidity type, `uint` and `int` as their 256-bit forms.
+    fn from_str(ty: &str) -> Result<Self, Self::Err> {
+        let bits = |digits: &str, max: u16, step: u16| {
+            digits
+                .parse::<u16>()
+                .ok()
+                .filter(|n| *n >= step && *n <= max && n % step == 0)
+        };
+        let invalid = || format!("`{}` isn't a static Solidity type", ty);
+        match ty.trim() {
+            "address" => Ok(SolType::Address),
+            "bool" => Ok(SolType::Bool),
+            "uint" => Ok(SolType::Uint(256)),
+            "int" => Ok(SolType::Int(256)),
+            t if t.starts_with("uint") => bits(&t[4..], 256, 8).map(SolType::Uint).ok_or_else(invalid),
+            t if t.starts_with("int") => bits(&t[3..], 256, 8).map(SolType::Int).ok_or_else(invalid),
+            t if t.starts_with("bytes") => bits(&t[5..], 32, 1)
+                .map(|n| SolType::FixedBytes(n as u8))
+                .ok_or_else(invalid),
+            _ => Err(invalid()),
+        }
+    }
+}
+
+impl SolType {
+    /// Checks that `word` is how an encoder writes a value of the type.
+    pub fn check(&self, word: &[u8; 32]) -> Result<(), String> {
+        let clean = |from: usize, fill: u8| word[..from].iter().all(|b| *b == fill);
+        match *self {
+            SolType::Address if !clean(12, 0) => {
+                Err("bits are set above the 20 bytes of an address".to_string())
+            }
+            SolType::Bool if !clean(31, 0) || word[31] > 1 => Err("neither 0 nor 1".to_string()),
+            SolType::Uint(bits) if !clean(32 - bits as usize / 8, 0) => {
+                Err(format!("the value doesn't fit in {} bits", bits))
+            }
+            SolType::Int(bits) => {
+                let from = 32 - bits as usize / 8;
+                // Sign extended from the type's top bit.
+                let fill = match word[from] & 0x80 {
+                    0 => 0,
+                    _ => 0xff,
+                };
+                match clean(from, fill) {
+                    true => Ok(()),
+                    false => Err(format!("the value doesn't fit in {} bits", bits)),
+                }
+            }
+            SolType::FixedBytes(len) if word[len as usize..].iter().any(|b| *b != 0) => {
+                Err(format!("non-zero bytes after the first {}", len))
+            }
+            _ => Ok(()),
+        }
+    }
+}
+
+/// Why a call didn't bind to a struct.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum BindError {
+    /// The call isn't to the struct's method.
+    Selector {
+        expected: &'static str,
+        found: String,
+    },
+    /// The call has no param for a field.
+    MissingParam {
+        field: &'static str,
+        index: usize,
+        params: usize,
+    },
+    /// A param doesn't hold a value of its field's type.
+    Mismatch {
+        field: &'static str,
+        index: usize,
+        /// The field's Solidity type.
+        expected: SolType,
+        raw: String,
+        reason: String,
+    },
+}
+
+impl std::fmt::Display for BindError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            BindError::Selector { expected, found } => {
+                write!(f, "selector is 0x{}, expected 0x{}", found, expected)
+            }
+            BindError::MissingParam {
+                field,
+                index,
+                params,
+            } => write!(
+                f,
+                "field `{}`: no params[{}], the call has {} params",
+                field, index, params
+            ),
+            BindError::Mismatch {
+                field,
+                index,
+                expected,
+                raw,
+                reason,
+            } => write!(
+                f,
+                "field `{}`: params[{}] ({}) isn't a {}: {}",
+                field, index, raw, expected, reason
+            ),
+        }
+    }
+}
+
+impl std::error::Error for BindError {}
+
+impl From<BindError> for String {
+    fn from(e: BindError) -> Self {
+        e.to_string()
+    }
+}
+
+/// A struct the params of a call bind to, with `#[derive(FromCalldata)]`
+/// (`derive` feature):
+///
+/// ```ignore
+/// #[derive(FromCalldata)]
+/// #[calldata(signature = "transfer(address,uint256)")]
+/// struct Transfer {
+///     to: H160,
+///     amount: U256,
+/// }
+///
+/// let transfer = Transfer::from_call(&decoder.decode(calldata)?)?;
+/// ```
+///
+/// Each field is read from a param under the Solidity type it's declared
+/// with, whatever types the heuristics guessed for it.
+pub trait FromCalldata: Sized {
+    /// Selector (8 hex chars, no prefix) a call must have to bind, `None` for any.
+    const SELECTOR: Option<&'static str>;
+
+    /// Binds the params of `call`, its nested calls aside.
+    fn from_call(call: &DecodedCalldata) -> Result<Self, BindError>;
+
+    /// Every call of the tree that binds, parents first: e.g. the swaps of a multicall.
+    fn find(root: &DecodedCalldata) -> Vec<Self> {
+        root.walk()
+            .into_iter()
+            .filter_map(|call| Self::from_call(call).ok())
+            .collect()
+    }
+}
+
+/// A Rust type a field can have, read from a word of a Solidity type.
+pub trait FromWord: Sized {
+    /// Reads `word`, already checked to hold a `ty`.
+    fn from_word(word: &[u8; 32], ty: SolType) -> Result<Self, String>;
+}
+
+/// Fails unless `call` has `selector`, or `selector` is `None`.
+pub fn check_selector(
+    call: &DecodedCalldata,
+    selector: Option<&'static str>,
+) -> Result<(), BindError> {
+    match selector {
+        Some(expected) if !call.selector.eq_ignore_ascii_case(expected) => {
+            Err(BindError::Selector {
+                expected,
+                found: call.selector.clone(),
+            })
+        }
+        _ => Ok(()),
+    }
+}
+
+/// Reads `params[index]` of `call` as a `ty`, for the field called `field`.
+pub fn field<T: FromWord>(
+    call: &DecodedCalldata,
+    field: &'static str,
+    index: usize,
+    ty: SolType,
+) -> Result<T, BindError> {
+    let param = call.params.get(index).ok_or(BindError::MissingParam {
+        field,
+        index,
+        params: call.params.len(),
+    })?;
+    let mismatch = |reason: String| BindError::Mismatch {
+        field,
+        index,
+        expected: ty,
+        raw: param.raw.clone(),
+        reason,
+    };
+    let word: [u8; 32] = decode_hex(&param.raw)
+        .try_into()
+        .map_err(|_| mismatch("not a full word".to_string()))?;
+    ty.check(&word).map_err(mismatch)?;
+    T::from_word(&word, ty).map_err(mismatch)
+}
+
+/// The error of a field whose Rust type can't hold a `ty`.
+fn unsupported<T>(ty: SolType) -> Result<T, String> {
+    Err(format!(
+        "a {} field can't hold a {}",
+        std::any::type_name::<T>(),
+        ty
+    ))
+}
+
+impl FromWord for U256 {
+    fn from_word(word: &[u8; 32], ty: SolType) -> Result<Self, String> {
+        match ty {
+            SolType::Uint(_) => Ok(U256::from_big_endian(word)),
+            ty => unsupported(ty),
+        }
+    }
+}
+
+impl FromWord for H160 {
+    fn from_word(word: &[u8; 32], ty: SolType) -> Result<Self, String> {
+        match ty {
+            SolType::Address => Ok(H160::from_slice(&word[12..])),
+            ty => unsupported(ty),
+        }
+    }
+}
+
+impl FromWord for H256 {
+    fn from_word(word: &[u8; 32], ty: SolType) -> Result<Self, String> {
+        match ty {
+            SolType::FixedBytes(32) => Ok(H256(*word)),
+            ty => unsupported(ty),
+        }
+    }
+}
+
+impl FromWord for bool {
+    fn from_word(word: &[u8; 32], ty: SolType) -> Result<Self, String> {
+        match ty {
+            SolType::Bool => Ok(word[31] == 1),
+            ty => unsupported(ty),
+        }
+    }
+}
+
+impl<const N: usize> FromWord for [u8; N] {
+    fn from_word(word: &[u8; 32], ty: SolType) -> Result<Self, String> {
+        match ty {
+            SolType::FixedBytes(len) if len as usize == N => {
+                let mut out = [0; N];
+                out.copy_from_slice(&word[..N]);
+                Ok(out)
+            }
+            ty => unsupported(ty),
+        }
+    }
+}
+
+impl FromWord for Vec<u8> {
+    fn from_word(word: &[u8; 32], ty: SolType) -> Result<Self, String> {
+        match ty {
+            SolType::FixedBytes(len) => Ok(word[..len as usize].to_vec()),
+            ty => unsupported(ty),
+        }
+    }
+}
+
+/// Unsigned integers read from any `uintN` whose value fits.
+macro_rules! from_uint {
+    ($($t:ty),*) => {$(
+        impl FromWord for $t {
+            fn from_word(word: &[u8; 32], ty: SolType) -> Result<Self, String> {
+                match ty {
+                    SolType::Uint(_) => crate::word_u128(word)
+                        .and_then(|v| <$t>::try_from(v).ok())
+                        .ok_or_else(|| format!("the value doesn't fit in a {}", stringify!($t))),
+                    ty => unsupported(ty),
+                }
+            }
+        }
+    )*};
+}
+
+/// Signed integers read from any `intN` whose value fits.
+macro_rules! from_int {
+    ($($t:ty),*) => {$(
+        impl FromWord for $t {
+            fn from_word(word: &[u8; 32], ty: SolType) -> Result<Self, String> {
+                match ty {
+                    SolType::Int(_) => int_of(word)
+                        .and_then(|v| <$t>::try_from(v).ok())
+                        .ok_or_else(|| format!("the value doesn't fit in a {}", stringify!($t))),
+                    ty => unsupported(ty),
+                }
+            }
+        }
+    )*};
+}
+
+from_uint!(u8, u16, u32, u64, u128, usize);
+from_int!(i8, i16, i32, i64, i128, isize);
+
+/// The value of a two's complement word if it fits in an `i128`.
+fn int_of(word: &[u8; 32]) -> Option<i128> {
+    let fill = match word[16] & 0x80 {
+        0 => 0,
+        _ => 0xff,
+    };
+    match word[..16].iter().all(|b| *b == fill) {
+        true => Some(i128::from_be_bytes(word[16..].try_into().ok()?)),
+        false => None,
+    }
+}
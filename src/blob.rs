@@ -0,0 +1,423 @@
+use crate::decoded::DecodedCalldata;
+use crate::heuristics::Heuristics;
+use crate::limits::Limits;
+use crate::rawtx::{DecodedRawTransaction, RawTransaction};
+use crate::rlp::Rlp;
+use crate::signatures::is_builtin_selector;
+use crate::{decode_hex_checked, Calldata};
+use serde::{Deserialize, Serialize};
+
+// ------------------------------------------------------------
+//  Blob encodings
+// ------------------------------------------------------------
+
+/// Field elements in a blob.
+pub const FIELD_ELEMENTS: usize = 4096;
+/// Bytes in a blob: 4096 field elements of 32 bytes.
+pub const BLOB_SIZE: usize = FIELD_ELEMENTS * 32;
+/// Most payload bytes the OP Stack encoding fits in a blob: 127 bytes every
+/// 4 field elements, less the version and length header.
+pub const MAX_OP_PAYLOAD: usize = (4 * 31 + 3) * 1024 - 4;
+
+/// How a payload was packed into the field elements of a blob.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum BlobEncoding {
+    /// OP Stack's: a version byte and 3-byte length, then 127 bytes every 4
+    /// field elements, their high bytes holding 6 bits each.
+    OpStack,
+    /// 31 bytes per field element behind a zero byte, trailing zeros trimmed.
+    Padded,
+    /// Bytes that aren't a blob, taken as an already extracted payload.
+    Raw,
+}
+
+/// Extracts the payload of a blob in the OP Stack encoding.
+pub fn decode_op_blob(blob: &[u8]) -> Result<Vec<u8>, String> {
+    if blob.len() != BLOB_SIZE {
+        return Err(format!(
+            "{} bytes isn't a blob of {}",
+            blob.len(),
+            BLOB_SIZE
+        ));
+    }
+    if blob[1] != 0 {
+        return Err(format!("unknown blob encoding version {}", blob[1]));
+    }
+    let len = (blob[2] as usize) << 16 | (blob[3] as usize) << 8 | blob[4] as usize;
+    if len > MAX_OP_PAYLOAD {
+        return Err(format!("payload length {} is over {}", len, MAX_OP_PAYLOAD));
+    }
+    let mut output = vec![0; MAX_OP_PAYLOAD];
+    // Round 0 has 27 bytes in its first element, after the version and length.
+    output[..27].copy_from_slice(&blob[5..32]);
+    let mut high = [blob[0], 0, 0, 0];
+    let (mut opos, mut ipos) = (28, 32);
+    for byte in high.iter_mut().skip(1) {
+        *byte = op_element(blob, ipos, &mut output[opos..])?;
+        opos += 32;
+        ipos += 32;
+    }
+    check_high(blob[0], 0)?;
+    opos = reassemble(opos, high, &mut output);
+    for _ in 1..FIELD_ELEMENTS / 4 {
+        if opos >= len {
+            break;
+        }
+        for byte in high.iter_mut() {
+            *byte = op_element(blob, ipos, &mut output[opos..])?;
+            opos += 32;
+            ipos += 32;
+        }
+        opos = reassemble(opos, high, &mut output);
+    }
+    if let Some(i) = output[len..].iter().position(|b| *b != 0) {
+        return Err(format!("non-zero byte at {} past the payload", len + i));
+    }
+    if let Some(i) = blob[ipos..].iter().position(|b| *b != 0) {
+        return Err(format!(
+            "non-zero byte at {} past the encoded payload",
+            ipos + i
+        ));
+    }
+    output.truncate(len);
+    Ok(output)
+}
+
+/// Packs `data` in the OP Stack encoding, the reverse of [`decode_op_blob`].
+pub fn encode_op_blob(data: &[u8]) -> Result<Vec<u8>, String> {
+    if data.len() > MAX_OP_PAYLOAD {
+        return Err(format!("{} bytes is over {}", data.len(), MAX_OP_PAYLOAD));
+    }
+    let mut input = data.to_vec();
+    input.resize(MAX_OP_PAYLOAD, 0);
+    let mut blob = vec![0; BLOB_SIZE];
+    let len = data.len();
+    blob[2..5].copy_from_slice(&[(len >> 16) as u8, (len >> 8) as u8, len as u8]);
+    // Round 0 carries the version and length, the others 127 payload bytes.
+    let mut ipos = 0;
+    for round in 0..FIELD_ELEMENTS / 4 {
+        let fe = round * 128;
+        let head = match round {
+            0 => 4,
+            _ => 0,
+        };
+        let chunk = |i: usize, n: usize| &input[ipos + i..ipos + i + n];
+        blob[fe + 1 + head..fe + 32].copy_from_slice(chunk(0, 31 - head));
+        let x = input[ipos + 31 - head];
+        blob[fe + 33..fe + 64].copy_from_slice(chunk(32 - head, 31));
+        let y = input[ipos + 63 - head];
+        blob[fe + 65..fe + 96].copy_from_slice(chunk(64 - head, 31));
+        let z = input[ipos + 95 - head];
+        blob[fe + 97..fe + 128].copy_from_slice(chunk(96 - head, 31));
+        blob[fe] = x & 0b0011_1111;
+        blob[fe + 32] = (y & 0b0000_1111) | ((x & 0b1100_0000) >> 2);
+        blob[fe + 64] = z & 0b0011_1111;
+        blob[fe + 96] = ((z & 0b1100_0000) >> 2) | ((y & 0b1111_0000) >> 4);
+        ipos += 127 - head;
+        if ipos >= len {
+            break;
+        }
+    }
+    Ok(blob)
+}
+
+/// Copies the low 31 bytes of the element at `ipos` to `output`.
+///
+/// ## Returns
+/// 1. The element's high byte, holding 6 bits of the round's 3 extra bytes.
+fn op_element(blob: &[u8], ipos: usize, output: &mut [u8]) -> Result<u8, String> {
+    let high = blob[ipos];
+    check_high(high, ipos / 32)?;
+    output[..31].copy_from_slice(&blob[ipos + 1..ipos + 32]);
+    Ok(high)
+}
+
+fn check_high(high: u8, element: usize) -> Result<(), String> {
+    match high & 0b1100_0000 {
+        0 => Ok(()),
+        _ => Err(format!("field element {} has its top bits set", element)),
+    }
+}
+
+/// Writes the 3 bytes spread over the round's 4 high bytes before `opos`,
+/// the round's output being a byte short of its 4 elements.
+fn reassemble(opos: usize, high: [u8; 4], output: &mut [u8]) -> usize {
+    let opos = opos - 1;
+    let x = (high[0] & 0b0011_1111) | ((high[1] & 0b0011_0000) << 2);
+    let y = (high[1] & 0b0000_1111) | ((high[3] & 0b0000_1111) << 4);
+    let z = (high[2] & 0b0011_1111) | ((high[3] & 0b0011_0000) << 2);
+    output[opos - 32] = z;
+    output[opos - 64] = y;
+    output[opos - 96] = x;
+    opos
+}
+
+/// Extracts the payload of a blob whose elements each hold 31 bytes behind a
+/// zero byte, trimming the trailing zeros.
+pub fn decode_padded_blob(blob: &[u8]) -> Result<Vec<u8>, String> {
+    if blob.len() != BLOB_SIZE {
+        return Err(format!(
+            "{} bytes isn't a blob of {}",
+            blob.len(),
+            BLOB_SIZE
+        ));
+    }
+    let mut output = Vec::with_capacity(FIELD_ELEMENTS * 31);
+    for (i, element) in blob.chunks(32).enumerate() {
+        if element[0] != 0 {
+            return Err(format!("field element {} has a non-zero high byte", i));
+        }
+        output.extend_from_slice(&element[1..]);
+    }
+    let end = output.iter().rposition(|b| *b != 0).map_or(0, |i| i + 1);
+    output.truncate(end);
+    Ok(output)
+}
+
+/// The payload of `blob`, in the first encoding it fits: OP Stack, then
+/// padded, else the bytes as given when they aren't a blob.
+pub fn blob_payload(blob: &[u8]) -> Result<(BlobEncoding, Vec<u8>), String> {
+    if blob.len() != BLOB_SIZE {
+        return Ok((BlobEncoding::Raw, blob.to_vec()));
+    }
+    if let Ok(payload) = decode_op_blob(blob) {
+        return Ok((BlobEncoding::OpStack, payload));
+    }
+    match decode_padded_blob(blob) {
+        Ok(payload) => Ok((BlobEncoding::Padded, payload)),
+        Err(e) => Err(format!("blob in no known encoding: {}", e)),
+    }
+}
+
+// ------------------------------------------------------------
+//  Sidecars
+// ------------------------------------------------------------
+
+/// The blobs of a blob transaction in its network form,
+/// `0x03 || rlp([tx, blobs, commitments, proofs])`.
+pub fn transaction_blobs(raw: &[u8]) -> Result<Vec<Vec<u8>>, String> {
+    if raw.first() != Some(&0x03) {
+        return Err("not a blob transaction".to_string());
+    }
+    let item = Rlp::decode(&raw[1..])?;
+    let [Rlp::List(_), blobs, _, _] = item.as_list()? else {
+        return Err("blob transaction without its sidecar".to_string());
+    };
+    blobs
+        .as_list()?
+        .iter()
+        .map(|blob| blob.as_bytes().map(<[u8]>::to_vec))
+        .collect()
+}
+
+/// A sidecar as served by the beacon API's `blob_sidecars` endpoint.
+#[derive(Debug, Clone, Deserialize)]
+struct BeaconSidecar {
+    blob: String,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+struct BeaconSidecars {
+    data: Vec<BeaconSidecar>,
+}
+
+/// The blobs of a beacon API `/eth/v1/beacon/blob_sidecars/{block}` response.
+pub fn sidecar_blobs(json: &str) -> Result<Vec<Vec<u8>>, String> {
+    let sidecars: BeaconSidecars = serde_json::from_str(json).map_err(|e| e.to_string())?;
+    sidecars
+        .data
+        .iter()
+        .map(|s| decode_hex_checked(&s.blob).map_err(String::from))
+        .collect()
+}
+
+/// Blobs in `input`, whichever of these it holds: a beacon API sidecars
+/// response, the hex of a blob transaction in its network form, or the hex
+/// of a single blob or payload.
+pub fn read_blobs(input: &str) -> Result<Vec<Vec<u8>>, String> {
+    if input.trim_start().starts_with('{') {
+        return sidecar_blobs(input);
+    }
+    let bytes = decode_hex_checked(input)?;
+    match bytes.first() {
+        Some(0x03) if bytes.len() != BLOB_SIZE => transaction_blobs(&bytes),
+        _ => Ok(vec![bytes]),
+    }
+}
+
+// ------------------------------------------------------------
+//  Blob contents
+// ------------------------------------------------------------
+
+/// Header of an OP Stack batcher frame, a piece of a compressed channel.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct FrameHeader {
+    /// 16-byte channel id (32 hex chars, no prefix).
+    pub channel_id: String,
+    pub frame_number: u16,
+    /// Bytes of channel data in the frame.
+    pub data_len: u32,
+    /// Whether the frame closes its channel.
+    pub is_last: bool,
+    /// Byte offset of the frame in the payload.
+    pub start: usize,
+}
+
+/// What a blob's payload was recognised as.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(tag = "kind", rename_all = "camelCase")]
+pub enum BlobContent {
+    /// Rollup batch data: a derivation version byte then batcher frames.
+    Frames { frames: Vec<FrameHeader> },
+    /// An RLP list of raw signed transactions, each decoded.
+    Transactions {
+        transactions: Vec<DecodedRawTransaction>,
+    },
+    /// Calldatas back to back, split at the known selectors.
+    Calldatas { calls: Vec<DecodedCalldata> },
+    /// Nothing recognisable.
+    Unknown,
+}
+
+/// A blob's payload and what it holds.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DecodedBlob {
+    pub encoding: BlobEncoding,
+    /// Bytes of payload extracted from the field elements.
+    pub payload_len: usize,
+    pub content: BlobContent,
+}
+
+/// Decodes blobs under one set of heuristics and limits.
+#[derive(Debug, Clone, Default)]
+pub struct BlobDecoder {
+    heuristics: Heuristics,
+    limits: Limits,
+}
+
+impl BlobDecoder {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn with_heuristics(mut self, heuristics: Heuristics) -> Self {
+        self.heuristics = heuristics;
+        self
+    }
+
+    pub fn with_limits(mut self, limits: Limits) -> Self {
+        self.limits = limits;
+        self
+    }
+
+    /// Extracts the payload of `blob` and tries it as batcher frames, an RLP
+    /// list of transactions, then back-to-back calldatas.
+    ///
+    /// ## Returns
+    /// 1. The decode, or an error when the blob is in no known encoding.
+    pub fn decode(&self, blob: &[u8]) -> Result<DecodedBlob, String> {
+        let (encoding, payload) = blob_payload(blob)?;
+        let content = match parse_frames(&payload) {
+            Some(frames) => BlobContent::Frames { frames },
+            None => match self.transactions(&payload) {
+                Some(transactions) => BlobContent::Transactions { transactions },
+                None => match self.calldatas(&payload, encoding == BlobEncoding::Padded) {
+                    calls if calls.is_empty() => BlobContent::Unknown,
+                    calls => BlobContent::Calldatas { calls },
+                },
+            },
+        };
+        Ok(DecodedBlob {
+            encoding,
+            payload_len: payload.len(),
+            content,
+        })
+    }
+
+    /// Every item of an RLP list read as a raw transaction: a list for a
+    /// legacy one, a string holding a typed envelope otherwise.
+    fn transactions(&self, payload: &[u8]) -> Option<Vec<DecodedRawTransaction>> {
+        let items = Rlp::raw_items(payload).ok()?;
+        if items.is_empty() {
+            return None;
+        }
+        items
+            .into_iter()
+            .map(|raw| {
+                let tx = match Rlp::decode(raw).ok()? {
+                    Rlp::List(_) => RawTransaction::from_bytes(raw),
+                    Rlp::Bytes(envelope) => RawTransaction::from_bytes(envelope),
+                };
+                DecodedRawTransaction::new(tx.ok()?, self.heuristics, self.limits).ok()
+            })
+            .collect()
+    }
+
+    /// Splits `payload` before each known selector and decodes the pieces
+    /// that start with one; `trimmed` when the payload's trailing zeros were
+    /// cut, the last piece getting them back up to a whole word.
+    fn calldatas(&self, payload: &[u8], trimmed: bool) -> Vec<DecodedCalldata> {
+        let starts = (0..payload.len().saturating_sub(3))
+            .filter(|&i| is_builtin_selector(&payload[i..i + 4]))
+            .collect::<Vec<_>>();
+        let ends = starts.iter().skip(1).copied().chain([payload.len()]);
+        starts
+            .iter()
+            .zip(ends)
+            .filter_map(|(&start, end)| {
+                let mut bytes = payload[start..end].to_vec();
+                if trimmed && end == payload.len() {
+                    let words = (bytes.len() - 4).div_ceil(32);
+                    bytes.resize(4 + words * 32, 0);
+                }
+                let calldata =
+                    Calldata::from_bytes_with_limits(bytes, self.heuristics, self.limits).ok()?;
+                Some(calldata.decode())
+            })
+            .collect()
+    }
+}
+
+/// Frames of a derivation version 0 payload, `None` unless the whole
+/// payload is frames: `channel_id (16) | frame_number (2) | data_len (4) |
+/// data | is_last (1)`.
+pub(crate) fn parse_frames(payload: &[u8]) -> Option<Vec<FrameHeader>> {
+    let (&version, mut rest) = payload.split_first()?;
+    if version != 0 || rest.is_empty() {
+        return None;
+    }
+    let mut frames = vec![];
+    while !rest.is_empty() {
+        let start = payload.len() - rest.len();
+        let header = rest.get(..22)?;
+        let data_len = u32::from_be_bytes(header[18..22].try_into().ok()?);
+        let end = 22usize.checked_add(data_len as usize)?;
+        let is_last = match rest.get(end)? {
+            0 => false,
+            1 => true,
+            _ => return None,
+        };
+        frames.push(FrameHeader {
+            channel_id: faster_hex::hex_string(&header[..16]),
+            frame_number: u16::from_be_bytes([header[16], header[17]]),
+            data_len,
+            is_last,
+            start,
+        });
+        rest = &rest[end + 1..];
+    }
+    Some(frames)
+}
+
+/// Decodes every blob in `input` (see [`read_blobs`]) with the default
+/// heuristics and limits.
+pub fn decode_blobs(input: &str) -> Result<Vec<DecodedBlob>, String> {
+    let decoder = BlobDecoder::new();
+    read_blobs(input)?
+        .iter()
+        .map(|blob| decoder.decode(blob))
+        .collect()
+}
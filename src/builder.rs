@@ -0,0 +1,357 @@
+use crate::signatures::{selector_bytes, Layout, SolType};
+use primitive_types::{H160, U256};
+
+// ------------------------------------------------------------
+//  ABI values
+// ------------------------------------------------------------
+
+/// A value to ABI encode.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum AbiValue {
+    Address(H160),
+    Uint(U256),
+    /// Two's complement, see [`AbiValue::int`].
+    Int(U256),
+    Bool(bool),
+    /// `bytes1` to `bytes32`.
+    FixedBytes(Vec<u8>),
+    Bytes(Vec<u8>),
+    String(String),
+    /// `T[]`.
+    Array(Vec<AbiValue>),
+    /// `T[k]`.
+    FixedArray(Vec<AbiValue>),
+    Tuple(Vec<AbiValue>),
+}
+
+impl AbiValue {
+    /// A signed integer in two's complement.
+    pub fn int(value: i128) -> Self {
+        let magnitude = U256::from(value.unsigned_abs());
+        match value < 0 {
+            true => AbiValue::Int((!magnitude).overflowing_add(U256::one()).0),
+            false => AbiValue::Int(magnitude),
+        }
+    }
+
+    /// Whether the value is encoded after the head, behind an offset.
+    pub fn is_dynamic(&self) -> bool {
+        match self {
+            AbiValue::Bytes(_) | AbiValue::String(_) | AbiValue::Array(_) => true,
+            AbiValue::FixedArray(values) | AbiValue::Tuple(values) => {
+                values.iter().any(AbiValue::is_dynamic)
+            }
+            _ => false,
+        }
+    }
+
+    /// Bytes the value takes in the head of the tuple it's in.
+    fn head_len(&self) -> usize {
+        match self {
+            _ if self.is_dynamic() => 32,
+            AbiValue::FixedArray(values) | AbiValue::Tuple(values) => {
+                values.iter().map(AbiValue::head_len).sum()
+            }
+            _ => 32,
+        }
+    }
+
+    /// The value's encoding: in place for a static value, the tail for a dynamic one.
+    pub fn encode(&self) -> Vec<u8> {
+        let word = |value: U256| {
+            let mut word = [0u8; 32];
+            value.to_big_endian(&mut word);
+            word.to_vec()
+        };
+        match self {
+            AbiValue::Address(address) => {
+                let mut out = vec![0; 12];
+                out.extend_from_slice(address.as_bytes());
+                out
+            }
+            AbiValue::Uint(value) | AbiValue::Int(value) => word(*value),
+            AbiValue::Bool(value) => word(U256::from(*value as u8)),
+            AbiValue::FixedBytes(bytes) => padded(bytes),
+            AbiValue::Bytes(bytes) => [word(bytes.len().into()), padded(bytes)].concat(),
+            AbiValue::String(text) => [word(text.len().into()), padded(text.as_bytes())].concat(),
+            AbiValue::Array(values) => [word(values.len().into()), encode_tuple(values)].concat(),
+            AbiValue::FixedArray(values) | AbiValue::Tuple(values) => encode_tuple(values),
+        }
+    }
+
+    /// Whether the value can be encoded as the Solidity type `ty`.
+    pub fn matches(&self, ty: &str) -> bool {
+        SolType::parse(ty).is_some_and(|ty| self.matches_type(&ty))
+    }
+
+    fn matches_type(&self, ty: &SolType) -> bool {
+        let bits = |name: &str, prefix: &str| name[prefix.len()..].parse::<usize>().ok();
+        match (self, ty) {
+            (AbiValue::Address(_), SolType::Elementary(name)) => name == "address",
+            (AbiValue::Bool(_), SolType::Elementary(name)) => name == "bool",
+            (AbiValue::Bytes(_), SolType::Elementary(name)) => name == "bytes",
+            (AbiValue::String(_), SolType::Elementary(name)) => name == "string",
+            (AbiValue::FixedBytes(bytes), SolType::Elementary(name)) => match name.as_str() {
+                "function" => bytes.len() == 24,
+                _ if name.starts_with("bytes") => bits(name, "bytes") == Some(bytes.len()),
+                _ => false,
+            },
+            (AbiValue::Uint(value), SolType::Elementary(name)) if name.starts_with("uint") => {
+                bits(name, "uint").is_some_and(|n| value.bits() <= n)
+            }
+            (AbiValue::Int(value), SolType::Elementary(name)) if name.starts_with("int") => {
+                // The value or its complement must leave the sign bit free.
+                let magnitude = match value.bit(255) {
+                    true => !*value,
+                    false => *value,
+                };
+                bits(name, "int").is_some_and(|n| magnitude.bits() < n)
+            }
+            (AbiValue::Array(values), SolType::Array(inner)) => {
+                values.iter().all(|v| v.matches_type(inner))
+            }
+            (AbiValue::FixedArray(values), SolType::FixedArray(inner, len)) => {
+                values.len() == *len && values.iter().all(|v| v.matches_type(inner))
+            }
+            (AbiValue::Tuple(values), SolType::Tuple(types)) => {
+                values.len() == types.len()
+                    && values.iter().zip(types).all(|(v, t)| v.matches_type(t))
+            }
+            _ => false,
+        }
+    }
+}
+
+/// The bytes right padded with zeros to whole words.
+fn padded(bytes: &[u8]) -> Vec<u8> {
+    let mut out = bytes.to_vec();
+    out.resize(bytes.len().div_ceil(32) * 32, 0);
+    out
+}
+
+/// Encodes values one after the other, as a call's params or a tuple: static
+/// values in the head, dynamic ones in the tail behind an offset from the
+/// start of the head.
+pub fn encode_tuple(values: &[AbiValue]) -> Vec<u8> {
+    let head_len = values.iter().map(AbiValue::head_len).sum::<usize>();
+    let (mut head, mut tail) = (Vec::with_capacity(head_len), vec![]);
+    for value in values {
+        match value.is_dynamic() {
+            true => {
+                head.extend_from_slice(&AbiValue::Uint((head_len + tail.len()).into()).encode());
+                tail.extend(value.encode());
+            }
+            false => head.extend(value.encode()),
+        }
+    }
+    head.extend(tail);
+    head
+}
+
+// ------------------------------------------------------------
+//  Calldata builder
+// ------------------------------------------------------------
+
+/// Builds canonical ABI calldata from typed values, the inverse of the decoder.
+///
+/// ```
+/// use calldata_decoder::builder::CalldataBuilder;
+/// use primitive_types::H160;
+///
+/// let calldata = CalldataBuilder::new("transfer(address,uint256)")
+///     .address(H160::repeat_byte(0x11))
+///     .uint(1_000u64)
+///     .build()
+///     .unwrap();
+/// assert_eq!(calldata.len(), 4 + 32 * 2);
+/// ```
+///
+/// A builder made from a signature checks the values against its inputs when
+/// built; the first error met while building, e.g. an invalid signature or a
+/// nested call that doesn't build, is returned then.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CalldataBuilder {
+    selector: [u8; 4],
+    inputs: Option<Vec<String>>,
+    values: Vec<AbiValue>,
+    error: Option<String>,
+}
+
+impl CalldataBuilder {
+    /// A call to the function `signature`, e.g. `transfer(address,uint256)`.
+    pub fn new(signature: &str) -> Self {
+        let layout = Layout::parse(signature);
+        let name = signature.split('(').next().unwrap_or_default().trim();
+        let inputs = layout.as_ref().map(|l| l.inputs.clone());
+        Self {
+            selector: match &inputs {
+                Some(inputs) => selector_bytes(&format!("{}({})", name, inputs.join(","))),
+                None => [0; 4],
+            },
+            error: match inputs {
+                Some(_) => None,
+                None => Some(format!("not a function signature: {}", signature)),
+            },
+            inputs,
+            values: vec![],
+        }
+    }
+
+    /// A call to an unknown function, whose values aren't checked.
+    pub fn from_selector(selector: [u8; 4]) -> Self {
+        Self {
+            selector,
+            inputs: None,
+            values: vec![],
+            error: None,
+        }
+    }
+
+    pub fn push(mut self, value: AbiValue) -> Self {
+        self.values.push(value);
+        self
+    }
+
+    pub fn address(self, address: H160) -> Self {
+        self.push(AbiValue::Address(address))
+    }
+
+    pub fn uint(self, value: impl Into<U256>) -> Self {
+        self.push(AbiValue::Uint(value.into()))
+    }
+
+    pub fn int(self, value: i128) -> Self {
+        self.push(AbiValue::int(value))
+    }
+
+    pub fn bool(self, value: bool) -> Self {
+        self.push(AbiValue::Bool(value))
+    }
+
+    /// A `bytesN` value, N being the length of `bytes`.
+    pub fn fixed_bytes(self, bytes: &[u8]) -> Self {
+        self.push(AbiValue::FixedBytes(bytes.to_vec()))
+    }
+
+    pub fn bytes(self, bytes: &[u8]) -> Self {
+        self.push(AbiValue::Bytes(bytes.to_vec()))
+    }
+
+    pub fn string(self, text: &str) -> Self {
+        self.push(AbiValue::String(text.to_string()))
+    }
+
+    /// A `T[]` value.
+    pub fn array(self, values: Vec<AbiValue>) -> Self {
+        self.push(AbiValue::Array(values))
+    }
+
+    pub fn tuple(self, values: Vec<AbiValue>) -> Self {
+        self.push(AbiValue::Tuple(values))
+    }
+
+    /// A nested call, as a `bytes` value.
+    pub fn call(self, call: &CalldataBuilder) -> Self {
+        let value = call.value();
+        self.push_built(value)
+    }
+
+    /// `multicall(bytes[])`, as on Uniswap's routers.
+    pub fn multicall(calls: &[CalldataBuilder]) -> Self {
+        let calls = calls
+            .iter()
+            .map(CalldataBuilder::value)
+            .collect::<Result<_, _>>();
+        Self::new("multicall(bytes[])").push_built(calls.map(AbiValue::Array))
+    }
+
+    /// `multicall(uint256,bytes[])`, the calls reverting after `deadline`.
+    pub fn multicall_with_deadline(deadline: impl Into<U256>, calls: &[CalldataBuilder]) -> Self {
+        let calls = calls
+            .iter()
+            .map(CalldataBuilder::value)
+            .collect::<Result<_, _>>();
+        Self::new("multicall(uint256,bytes[])")
+            .uint(deadline)
+            .push_built(calls.map(AbiValue::Array))
+    }
+
+    /// Multicall's `aggregate((address,bytes)[])`, each call made to its target.
+    pub fn aggregate(calls: &[(H160, CalldataBuilder)]) -> Self {
+        let calls = calls
+            .iter()
+            .map(|(target, call)| {
+                let call = call.value()?;
+                Ok(AbiValue::Tuple(vec![AbiValue::Address(*target), call]))
+            })
+            .collect::<Result<_, String>>();
+        Self::new("aggregate((address,bytes)[])").push_built(calls.map(AbiValue::Array))
+    }
+
+    /// Multicall3's `aggregate3((address,bool,bytes)[])`, each call with
+    /// whether it may fail.
+    pub fn aggregate3(calls: &[(H160, bool, CalldataBuilder)]) -> Self {
+        let calls = calls
+            .iter()
+            .map(|(target, allow_failure, call)| {
+                let call = call.value()?;
+                Ok(AbiValue::Tuple(vec![
+                    AbiValue::Address(*target),
+                    AbiValue::Bool(*allow_failure),
+                    call,
+                ]))
+            })
+            .collect::<Result<_, String>>();
+        Self::new("aggregate3((address,bool,bytes)[])").push_built(calls.map(AbiValue::Array))
+    }
+
+    /// The calldata as a `bytes` value.
+    fn value(&self) -> Result<AbiValue, String> {
+        self.build().map(AbiValue::Bytes)
+    }
+
+    /// Pushes a value that may have failed to build, keeping the first error.
+    fn push_built(mut self, value: Result<AbiValue, String>) -> Self {
+        match value {
+            Ok(value) => self.push(value),
+            Err(e) => {
+                self.error.get_or_insert(e);
+                self
+            }
+        }
+    }
+
+    /// The selector followed by the encoded values.
+    ///
+    /// ## Returns
+    /// 1. The calldata, or the first error met while building and, for a
+    ///    builder made from a signature, the first value not matching its input.
+    pub fn build(&self) -> Result<Vec<u8>, String> {
+        if let Some(error) = &self.error {
+            return Err(error.clone());
+        }
+        if let Some(inputs) = &self.inputs {
+            if inputs.len() != self.values.len() {
+                return Err(format!(
+                    "{} inputs, got {} values",
+                    inputs.len(),
+                    self.values.len()
+                ));
+            }
+            if let Some((i, ty)) = inputs
+                .iter()
+                .enumerate()
+                .find(|(i, ty)| !self.values[*i].matches(ty))
+            {
+                return Err(format!("value {} isn't a {}: {:?}", i, ty, self.values[i]));
+            }
+        }
+        Ok([self.selector.to_vec(), encode_tuple(&self.values)].concat())
+    }
+
+    /// [`Self::build`] as `0x` prefixed hex.
+    pub fn build_hex(&self) -> Result<String, String> {
+        self.build()
+            .map(|calldata| format!("0x{}", faster_hex::hex_string(&calldata)))
+    }
+}
@@ -0,0 +1,212 @@
+use crate::decode_hex;
+use crate::heuristics::Heuristics;
+use crate::limits::Limits;
+use crate::rawtx::{DecodedRawTransaction, RawTransaction};
+use crate::render::address::{serialize_checksum, serialize_checksum_opt};
+use primitive_types::H160;
+use serde::Serialize;
+use serde_json::Value;
+use std::collections::BTreeMap;
+
+// ------------------------------------------------------------
+//  MEV bundles
+// ------------------------------------------------------------
+
+/// A transaction of a bundle.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BundleTransaction {
+    /// Position in the bundle, the order it executes in.
+    pub index: usize,
+    /// The signer, recovered from the signature with the `ethers` feature;
+    /// `None` without it and for blob transactions.
+    #[serde(serialize_with = "serialize_checksum_opt")]
+    pub from: Option<H160>,
+    #[serde(flatten)]
+    pub decoded: DecodedRawTransaction,
+}
+
+/// How a transaction touches an address.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub enum AddressRole {
+    Sender,
+    Recipient,
+    /// An address param of the calldata, nested calls included.
+    Param,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AddressUse {
+    pub transaction: usize,
+    pub role: AddressRole,
+    /// The param, e.g. `calls[0].params[9]`, for `Param`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub path: Option<String>,
+}
+
+/// An address touched by more than one transaction of the bundle, e.g. the
+/// pool a sandwich trades against or the searcher's contract.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SharedAddress {
+    #[serde(serialize_with = "serialize_checksum")]
+    pub address: H160,
+    /// In bundle order.
+    pub uses: Vec<AddressUse>,
+}
+
+/// A bundle's transactions decoded, in order, and the addresses they share.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DecodedBundle {
+    pub transactions: Vec<BundleTransaction>,
+    /// Those touched by the most transactions first, then by first use.
+    pub shared: Vec<SharedAddress>,
+}
+
+impl DecodedBundle {
+    /// Decodes each raw transaction and correlates their addresses.
+    ///
+    /// ## Params
+    /// 1. raws - the bundle's raw signed transactions, in order.
+    /// 2. heuristics - to decode the calldatas under.
+    /// 3. limits - to decode the calldatas under.
+    ///
+    /// ## Returns
+    /// 1. The bundle, or the first transaction that doesn't parse.
+    pub fn new(raws: &[Vec<u8>], heuristics: Heuristics, limits: Limits) -> Result<Self, String> {
+        let transactions = raws
+            .iter()
+            .enumerate()
+            .map(|(index, raw)| {
+                let transaction = RawTransaction::from_bytes(raw)
+                    .map_err(|e| format!("transaction {}: {}", index, e))?;
+                Ok(BundleTransaction {
+                    index,
+                    from: recover_sender(raw),
+                    decoded: DecodedRawTransaction::new(transaction, heuristics, limits)
+                        .map_err(|e| format!("transaction {}: {}", index, e))?,
+                })
+            })
+            .collect::<Result<Vec<_>, String>>()?;
+        let shared = shared_addresses(&transactions);
+        Ok(Self {
+            transactions,
+            shared,
+        })
+    }
+}
+
+/// Parses a bundle and decodes it with the default heuristics and limits.
+pub fn decode_bundle(text: &str) -> Result<DecodedBundle, String> {
+    DecodedBundle::new(
+        &parse_bundle(text)?,
+        Heuristics::default(),
+        Limits::default(),
+    )
+}
+
+/// Reads the raw transactions of a bundle, given as a JSON array of hex
+/// strings, as `eth_sendBundle`'s params object (`{"txs": [...]}`) or its whole
+/// request, or one hex transaction per line.
+pub fn parse_bundle(text: &str) -> Result<Vec<Vec<u8>>, String> {
+    let text = text.trim();
+    let raws = match text.starts_with(['[', '{']) {
+        true => {
+            let value = serde_json::from_str::<Value>(text).map_err(|e| e.to_string())?;
+            let txs = match &value {
+                Value::Object(request) if request.contains_key("params") => request["params"]
+                    .get(0)
+                    .and_then(|params| params.get("txs")),
+                Value::Object(params) => params.get("txs"),
+                Value::Array(_) => Some(&value),
+                _ => None,
+            };
+            txs.and_then(|txs| txs.as_array())
+                .ok_or("expected an array of raw transactions, or an object with `txs`")?
+                .iter()
+                .map(|tx| tx.as_str().map(str::to_string))
+                .collect::<Option<Vec<_>>>()
+                .ok_or("raw transactions are hex strings")?
+        }
+        false => text
+            .lines()
+            .map(str::trim)
+            .filter(|l| !l.is_empty())
+            .map(str::to_string)
+            .collect(),
+    };
+    if raws.is_empty() {
+        return Err("empty bundle".to_string());
+    }
+    Ok(raws.iter().map(|raw| decode_hex(raw)).collect())
+}
+
+/// Addresses touched by at least two transactions.
+fn shared_addresses(transactions: &[BundleTransaction]) -> Vec<SharedAddress> {
+    let mut uses = BTreeMap::<H160, Vec<AddressUse>>::new();
+    let mut order = vec![];
+    let mut add = |address: H160, transaction, role, path: Option<String>| {
+        if address.is_zero() {
+            return;
+        }
+        let entry = uses.entry(address).or_insert_with(|| {
+            order.push(address);
+            vec![]
+        });
+        entry.push(AddressUse {
+            transaction,
+            role,
+            path,
+        });
+    };
+    for tx in transactions {
+        if let Some(from) = tx.from {
+            add(from, tx.index, AddressRole::Sender, None);
+        }
+        if let Some(to) = tx.decoded.transaction.to {
+            add(to, tx.index, AddressRole::Recipient, None);
+        }
+        let Some(root) = tx.decoded.decoded.as_ref() else {
+            continue;
+        };
+        for (path, param) in root.leaf_params() {
+            let is_address = param.types.first().is_some_and(|t| t.solidity == "address");
+            if is_address && param.raw.len() == 64 {
+                let address = H160::from_slice(&decode_hex(&param.raw[24..]));
+                add(address, tx.index, AddressRole::Param, Some(path));
+            }
+        }
+    }
+    let transactions_of = |uses: &[AddressUse]| {
+        let mut txs = uses.iter().map(|u| u.transaction).collect::<Vec<_>>();
+        txs.dedup();
+        txs.len()
+    };
+    let mut shared = order
+        .into_iter()
+        .filter_map(|address| {
+            let uses = uses.remove(&address)?;
+            (transactions_of(&uses) > 1).then_some(SharedAddress { address, uses })
+        })
+        .collect::<Vec<_>>();
+    // Ties stay in order of first use, the sort being stable.
+    shared.sort_by_key(|s| std::cmp::Reverse(transactions_of(&s.uses)));
+    shared
+}
+
+/// The signer of a raw legacy, access list or dynamic fee transaction.
+#[cfg(feature = "ethers")]
+fn recover_sender(raw: &[u8]) -> Option<H160> {
+    use ethers::types::transaction::eip2718::TypedTransaction;
+    use ethers::utils::rlp::Rlp;
+    let (tx, signature) = TypedTransaction::decode_signed(&Rlp::new(raw)).ok()?;
+    signature.recover(tx.sighash()).ok()
+}
+
+#[cfg(not(feature = "ethers"))]
+fn recover_sender(_raw: &[u8]) -> Option<H160> {
+    None
+}
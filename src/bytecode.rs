@@ -0,0 +1,101 @@
+use crate::heuristics::Profile;
+
+// ------------------------------------------------------------
+//  Bytecode selectors
+// ------------------------------------------------------------
+
+const PUSH1: u8 = 0x60;
+const PUSH4: u8 = 0x63;
+const PUSH32: u8 = 0x7f;
+const DUP1: u8 = 0x80;
+const DUP16: u8 = 0x8f;
+const EQ: u8 = 0x14;
+const XOR: u8 = 0x18;
+
+/// Selectors of the functions a contract's runtime bytecode dispatches to,
+/// sorted and without duplicates.
+///
+/// Read from its dispatch table: a `PUSH4` compared to the calldata's selector
+/// right after, directly or through a `DUP`, with `EQ` (Solidity) or `XOR`
+/// (Vyper). Push data is skipped rather than read as opcodes. Proxies dispatch
+/// in the code they delegate to, so theirs has no selectors.
+pub fn dispatch_selectors(code: &[u8]) -> Vec<[u8; 4]> {
+    let mut selectors = dispatch(code)
+        .into_iter()
+        .map(|(selector, _)| selector)
+        .collect::<Vec<_>>();
+    selectors.sort_unstable();
+    selectors.dedup();
+    selectors
+}
+
+/// The compiler whose patterns a contract's runtime bytecode shows, `None`
+/// when it shows none, e.g. for a proxy.
+///
+/// Told by the CBOR metadata at the end of the code, its `vyper` (Vyper 0.3.4
+/// to 0.3.9) or `solc` key, else by how the dispatch table compares selectors:
+/// with `XOR` for Vyper, `EQ` for Solidity.
+pub fn compiler_profile(code: &[u8]) -> Option<Profile> {
+    if let Some(metadata) = metadata(code) {
+        let has = |key: &[u8]| metadata.windows(key.len()).any(|w| w == key);
+        if has(b"vyper") {
+            return Some(Profile::Vyper);
+        }
+        if has(b"solc") {
+            return Some(Profile::Solidity);
+        }
+    }
+    let compares = dispatch(code);
+    let xor = compares.iter().filter(|(_, op)| *op == XOR).count();
+    match (compares.is_empty(), xor * 2 > compares.len()) {
+        (true, _) => None,
+        (false, true) => Some(Profile::Vyper),
+        (false, false) => Some(Profile::Solidity),
+    }
+}
+
+/// Each selector of the dispatch table with the opcode comparing it.
+fn dispatch(code: &[u8]) -> Vec<([u8; 4], u8)> {
+    let mut compares = vec![];
+    let mut pc = 0;
+    while pc < code.len() {
+        let op = code[pc];
+        let next = pc + 1 + push_len(op);
+        if op == PUSH4 {
+            if let Some(selector) = code.get(pc + 1..next) {
+                let mut after = next;
+                if code
+                    .get(after)
+                    .is_some_and(|op| (DUP1..=DUP16).contains(op))
+                {
+                    after += 1;
+                }
+                if let Some(&compare @ (EQ | XOR)) = code.get(after) {
+                    compares.push((
+                        [selector[0], selector[1], selector[2], selector[3]],
+                        compare,
+                    ));
+                }
+            }
+        }
+        pc = next;
+    }
+    compares
+}
+
+/// The CBOR metadata the code ends with, its length in the last 2 bytes.
+fn metadata(code: &[u8]) -> Option<&[u8]> {
+    let (rest, len) = code.split_last_chunk::<2>()?;
+    let len = u16::from_be_bytes(*len) as usize;
+    // A CBOR map of a few entries.
+    let metadata = rest.get(rest.len().checked_sub(len)?..)?;
+    matches!(metadata.first(), Some(0xa1..=0xa5)).then_some(metadata)
+}
+
+/// Bytes of push data following `op`.
+fn push_len(op: u8) -> usize {
+    match (PUSH1..=PUSH32).contains(&op) {
+        true => (op - PUSH1 + 1) as usize,
+        false => 0,
+    }
+}
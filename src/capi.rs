@@ -0,0 +1,108 @@
+use crate::decoded::DecodeStatus;
+use crate::schema::{decode_json, DecodeResponse, JsonOutput, SCHEMA_VERSION};
+use std::ffi::{c_char, c_int, CStr, CString};
+use std::panic::catch_unwind;
+use std::ptr;
+
+// ------------------------------------------------------------
+//  C API
+// ------------------------------------------------------------
+//
+// Declared in `include/calldata_decoder.h`. Every string handed out is owned
+// by the library and must be released with `cd_free`.
+
+/// Decodes hex calldata into the JSON output.
+///
+/// ## Params
+/// 1. hex - NUL-terminated calldata hex, with or without the `0x` prefix.
+/// 2. json_out - set to the JSON decode, or to the error message when decoding fails.
+///
+/// ## Returns
+/// 1. The exit code of the decode's status as the CLI gives it: 0 decoded,
+///    2 partial, 3 invalid input (`json_out` then holding the error).
+///
+/// # Safety
+/// `hex` must be NULL or a valid NUL-terminated string, and `json_out` NULL or
+/// valid for a pointer write.
+#[no_mangle]
+pub unsafe extern "C" fn cd_decode(hex: *const c_char, json_out: *mut *mut c_char) -> c_int {
+    if json_out.is_null() {
+        return DecodeStatus::Invalid.exit_code();
+    }
+    let (status, text) = match hex.is_null() {
+        true => (DecodeStatus::Invalid, "calldata is NULL".to_string()),
+        false => decode(CStr::from_ptr(hex)),
+    };
+    *json_out = into_c_string(text);
+    status.exit_code()
+}
+
+/// Decodes a JSON request (`{"calldata": "0x...", "options": {...}}`) into a
+/// JSON response holding the versioned output or the error.
+///
+/// ## Returns
+/// 1. The response, never NULL, to release with `cd_free`.
+///
+/// # Safety
+/// `request` must be NULL or a valid NUL-terminated string.
+#[no_mangle]
+pub unsafe extern "C" fn cd_decode_json(request: *const c_char) -> *mut c_char {
+    let response = match request.is_null() {
+        true => error_json("request is NULL".to_string()),
+        false => {
+            let request = CStr::from_ptr(request);
+            let response = catch_unwind(|| match request.to_str() {
+                Ok(request) => decode_json(request),
+                Err(e) => error_json(format!("invalid request: {}", e)),
+            });
+            response.unwrap_or_else(|_| error_json("decoder panicked".to_string()))
+        }
+    };
+    into_c_string(response)
+}
+
+/// Releases a string returned by the library; NULL is ignored.
+///
+/// # Safety
+/// `s` must be NULL or a string returned by `cd_decode` or `cd_decode_json`
+/// that wasn't freed yet.
+#[no_mangle]
+pub unsafe extern "C" fn cd_free(s: *mut c_char) {
+    if !s.is_null() {
+        drop(CString::from_raw(s));
+    }
+}
+
+/// Version of the JSON written by `cd_decode`.
+#[no_mangle]
+pub extern "C" fn cd_schema_version() -> u32 {
+    SCHEMA_VERSION
+}
+
+/// The status and JSON of a decode, or the error; a panic never crosses the boundary.
+fn decode(hex: &CStr) -> (DecodeStatus, String) {
+    let decoded = catch_unwind(|| {
+        let hex = hex.to_str().map_err(|e| e.to_string())?;
+        let output = JsonOutput::from_hex(hex)?;
+        let json = serde_json::to_string(&output).map_err(|e| e.to_string())?;
+        Ok::<_, String>((output.root.status(), json))
+    });
+    match decoded {
+        Ok(Ok(decoded)) => decoded,
+        Ok(Err(e)) => (DecodeStatus::Invalid, e),
+        Err(_) => (DecodeStatus::Invalid, "decoder panicked".to_string()),
+    }
+}
+
+fn error_json(error: String) -> String {
+    serde_json::to_string(&DecodeResponse::error(error)).expect("decode response is serializable")
+}
+
+/// Hands a string over to the caller, NUL bytes (never in JSON) cutting it short.
+fn into_c_string(text: String) -> *mut c_char {
+    let mut bytes = text.into_bytes();
+    if let Some(nul) = bytes.iter().position(|b| *b == 0) {
+        bytes.truncate(nul);
+    }
+    CString::new(bytes).map_or(ptr::null_mut(), CString::into_raw)
+}
@@ -0,0 +1,524 @@
+use crate::bind::SolType;
+use crate::constants::{Types, WORD};
+use crate::decode_hex;
+use crate::decoded::{DecodedCalldata, TypeCandidate};
+use crate::render::solidity::function_name;
+use crate::word_usize;
+use std::collections::BTreeMap;
+
+// ------------------------------------------------------------
+//  Signature inference
+// ------------------------------------------------------------
+
+/// A param of an inferred signature.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct InferredParam {
+    /// Position of the param's word in the head.
+    pub index: usize,
+    /// Solidity type every observed call holds: a static type, `bytes` or `string`.
+    pub solidity: String,
+}
+
+/// The signature a set of observed calls to one selector agree on.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct InferredSignature {
+    /// 4-byte method selector (8 chars, no prefix).
+    pub selector: String,
+    /// Function name, `unknown_<selector>` when the signature isn't known.
+    pub name: String,
+    pub params: Vec<InferredParam>,
+    /// How many calls the signature was inferred from.
+    pub samples: usize,
+}
+
+impl InferredSignature {
+    /// `name(t0,t1,...)`.
+    pub fn signature(&self) -> String {
+        let types = self
+            .params
+            .iter()
+            .map(|p| p.solidity.as_str())
+            .collect::<Vec<&str>>();
+        format!("{}({})", self.name, types.join(","))
+    }
+}
+
+/// Infers the signature of the calls to `selector` in `roots`, nested calls
+/// included, from what every one of them holds.
+///
+/// A head word pointing to a length and its data in every call is a dynamic
+/// param: `bytes` when the data is right padded with zeros (`string` when every
+/// call has text there), `bytes[]` when it's offsets to such bytes, and an
+/// array of a static type otherwise. Any other word, and the elements of
+/// arrays, get the type most calls guessed for them among those every word is
+/// valid for, so an amount that happens to be small in one call stays a
+/// `uint256`.
+///
+/// ## Params
+/// 1. selector - 8 hex chars, `0x` optional.
+/// 2. roots - the observed decodes.
+pub fn infer_signature(
+    selector: &str,
+    roots: &[DecodedCalldata],
+) -> Result<InferredSignature, String> {
+    let selector = selector.trim_start_matches("0x").to_lowercase();
+    let calls = roots
+        .iter()
+        .flat_map(|root| root.walk())
+        .filter(|call| call.selector == selector)
+        .collect::<Vec<_>>();
+    let Some(first) = calls.first() else {
+        return Err(format!("no call to 0x{} among the decodes", selector));
+    };
+
+    let mut words = vec![];
+    for (n, call) in calls.iter().enumerate() {
+        let mut body = vec![];
+        for param in call.params.iter() {
+            let word: [u8; WORD] = decode_hex(&param.raw)
+                .try_into()
+                .map_err(|_| format!("call {} to 0x{} ends with a partial word", n, selector))?;
+            body.push(word);
+        }
+        words.push(body);
+    }
+
+    // Words holding an offset to the same kind of data in every call, then the
+    // head they all end at.
+    let shortest = words.iter().map(Vec::len).min().unwrap_or(0);
+    let dynamic = (0..shortest)
+        .filter_map(|i| {
+            [Data::Bytes, Data::BytesArray, Data::Array]
+                .into_iter()
+                .find(|data| words.iter().all(|body| data.fits(body, i)))
+                .map(|data| (i, data))
+        })
+        .collect::<BTreeMap<usize, Data>>();
+    let mut head = None;
+    for (n, body) in words.iter().enumerate() {
+        let len = dynamic
+            .keys()
+            .filter_map(|&i| length_at(body, i))
+            .min()
+            .unwrap_or(body.len());
+        match head {
+            Some(head) if head != len => return Err(format!(
+                "calls to 0x{} don't share a layout: a head of {} words in call 0, {} in call {}",
+                selector, head, len, n
+            )),
+            _ => head = Some(len),
+        }
+    }
+
+    let samples = calls.iter().zip(&words).collect::<Vec<_>>();
+    let params = (0..head.unwrap_or(0))
+        .map(|index| {
+            let solidity = match dynamic.get(&index) {
+                Some(data) => data.solidity(&samples, index),
+                None => static_type(
+                    samples
+                        .iter()
+                        .map(|(call, body)| (&call.params[index].types[..], &body[index])),
+                ),
+            };
+            InferredParam { index, solidity }
+        })
+        .collect();
+    Ok(InferredSignature {
+        selector,
+        name: function_name(first),
+        params,
+        samples: calls.len(),
+    })
+}
+
+/// What a dynamic param's data is laid out as.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Data {
+    /// Bytes right padded to a word.
+    Bytes,
+    /// Offsets, from the word after the length, to bytes.
+    BytesArray,
+    /// A word per element.
+    Array,
+}
+
+impl Data {
+    /// Whether `body[index]` is an offset to data laid out this way.
+    fn fits(&self, body: &[[u8; WORD]], index: usize) -> bool {
+        let Some(at) = length_at(body, index) else {
+            return false;
+        };
+        let (len, data) = (word_usize(&body[at]).unwrap_or(usize::MAX), &body[at + 1..]);
+        match self {
+            Data::Bytes => data
+                .concat()
+                .get(len..len.div_ceil(WORD) * WORD)
+                .is_some_and(|padding| padding.iter().all(|b| *b == 0)),
+            Data::BytesArray => len <= data.len() && (0..len).all(|i| Data::Bytes.fits(data, i)),
+            Data::Array => len <= data.len(),
+        }
+    }
+
+    /// The Solidity type of the param at `index`, laid out this way in every sample.
+    fn solidity(&self, samples: &[(&&DecodedCalldata, &Vec<[u8; WORD]>)], index: usize) -> String {
+        match self {
+            Data::Bytes => {
+                let text = samples.iter().all(|(call, body)| {
+                    length_at(body, index)
+                        .and_then(|at| call.params.get(at))
+                        .is_some_and(|length| length.text.is_some())
+                });
+                match text {
+                    true => "string".to_string(),
+                    false => "bytes".to_string(),
+                }
+            }
+            Data::BytesArray => "bytes[]".to_string(),
+            Data::Array => {
+                let elements = samples.iter().flat_map(|(call, body)| {
+                    let at = length_at(body, index).unwrap_or(body.len());
+                    let len = word_usize(&body[at]).unwrap_or(0);
+                    (at + 1..at + 1 + len).map(|i| (&call.params[i].types[..], &body[i]))
+                });
+                format!("{}[]", static_type(elements))
+            }
+        }
+    }
+}
+
+/// The word of the length `body[index]` is the offset of, when it's an
+/// aligned offset past `index` within the body.
+fn length_at(body: &[[u8; WORD]], index: usize) -> Option<usize> {
+    let offset = word_usize(&body[index])?;
+    let at = offset / WORD;
+    (offset % WORD == 0 && at > index && at < body.len() && word_usize(&body[at]).is_some())
+        .then_some(at)
+}
+
+/// The static type of words, from the types guessed for each.
+pub(crate) fn static_type<'a>(words: impl Iterator<Item = (&'a [TypeCandidate], &'a [u8; WORD])>) -> String {
+    // Votes of the words saying something about their type, ranked by count
+    // then by first vote.
+    let mut votes: BTreeMap<String, (usize, usize)> = BTreeMap::new();
+    let mut all = vec![];
+    for (n, (types, word)) in words.enumerate() {
+        all.push(word);
+        match types.first() {
+            Some(t) if !matches!(t.kind, Types::AnyZero | Types::AnyMax) => {
+                votes.entry(t.solidity.clone()).or_insert((0, n)).0 += 1;
+            }
+            _ => {}
+        }
+    }
+    let mut ranked = votes.into_iter().collect::<Vec<_>>();
+    ranked.sort_by_key(|(_, (count, first))| (usize::MAX - count, *first));
+    let valid = |ty: &SolType| all.iter().all(|word| ty.check(word).is_ok());
+    match ranked.is_empty() {
+        // Zeros and max values only: a number is all they can be told apart as.
+        true => "uint256".to_string(),
+        false => ranked
+            .into_iter()
+            .find(|(ty, _)| ty.parse::<SolType>().is_ok_and(|ty| valid(&ty)))
+            .map_or("bytes32".to_string(), |(ty, _)| ty),
+    }
+}
+
+// ------------------------------------------------------------
+//  Rust decoders
+// ------------------------------------------------------------
+
+/// Emits a Rust module decoding calls to `signature` exactly, no heuristics
+/// involved: a struct with a field per param and a `decode` reading the
+/// calldata (selector included) into it, failing on any word its type doesn't
+/// allow. The module only depends on `primitive_types`.
+pub fn render_decoder(signature: &InferredSignature) -> String {
+    let module = snake_case(&signature.name);
+    let name = camel_case(&signature.name);
+    let fields = signature
+        .params
+        .iter()
+        .map(|p| (p, field_of(&p.solidity, p.index)))
+        .collect::<Vec<_>>();
+    let imports = ["H160", "U256"]
+        .into_iter()
+        .filter(|import| fields.iter().any(|(_, (ty, _))| ty.contains(import)))
+        .collect::<Vec<&str>>();
+    let selector = (0..signature.selector.len())
+        .step_by(2)
+        .map(|i| {
+            format!(
+                "0x{}",
+                &signature.selector[i..(i + 2).min(signature.selector.len())]
+            )
+        })
+        .collect::<Vec<String>>();
+
+    let mut out = format!(
+        "// Decoder for `{}` (0x{}), inferred from {} observed call{} by `calldata-decoder codegen`.\n",
+        signature.signature(),
+        signature.selector,
+        signature.samples,
+        match signature.samples {
+            1 => "",
+            _ => "s",
+        }
+    );
+    out.push_str(&format!(
+        "pub mod {} {{\n    #![allow(dead_code)]\n\n",
+        module
+    ));
+    match imports.as_slice() {
+        [] => {}
+        [one] => out.push_str(&format!("    use primitive_types::{};\n\n", one)),
+        many => out.push_str(&format!(
+            "    use primitive_types::{{{}}};\n\n",
+            many.join(", ")
+        )),
+    }
+    out.push_str(&format!(
+        "    pub const SELECTOR: [u8; 4] = [{}];\n",
+        selector.join(", ")
+    ));
+    out.push_str(&format!(
+        "    pub const SIGNATURE: &str = \"{}\";\n\n",
+        signature.signature()
+    ));
+    out.push_str(&format!(
+        "    #[derive(Debug, Clone, PartialEq, Eq)]\n    pub struct {} {{\n",
+        name
+    ));
+    for (param, (ty, _)) in fields.iter() {
+        out.push_str(&format!(
+            "        /// `{}`{}\n        pub arg{}: {},\n",
+            param.solidity,
+            match param.solidity.starts_with("int") && ty.contains("U256") {
+                true => ", two's complement",
+                false => "",
+            },
+            param.index,
+            ty
+        ));
+    }
+    out.push_str("    }\n\n");
+    out.push_str(&format!("    impl {} {{\n", name));
+    out.push_str(
+        "        /// Decodes calldata, selector included, failing on any word of the\n        \
+         /// wrong type.\n        \
+         pub fn decode(calldata: &[u8]) -> Result<Self, String> {\n",
+    );
+    out.push_str(&format!(
+        "            let body = calldata\n                \
+         .strip_prefix(&SELECTOR[..])\n                \
+         .ok_or(\"not a call to 0x{}\")?;\n",
+        signature.selector
+    ));
+    out.push_str("            Ok(Self {\n");
+    for (param, (_, read)) in fields.iter() {
+        out.push_str(&format!("                arg{}: {},\n", param.index, read));
+    }
+    out.push_str("            })\n        }\n    }\n");
+    out.push_str(HELPERS);
+    out.push_str("}\n");
+    out
+}
+
+/// The Rust type of a param and the expression reading it from `body`.
+fn field_of(solidity: &str, index: usize) -> (String, String) {
+    match solidity {
+        "bytes" => ("Vec<u8>".to_string(), format!("dynamic(body, {})?", index)),
+        "string" => (
+            "String".to_string(),
+            format!(
+                "String::from_utf8(dynamic(body, {})?).map_err(|e| e.to_string())?",
+                index
+            ),
+        ),
+        "bytes[]" => (
+            "Vec<Vec<u8>>".to_string(),
+            format!("dynamic_array(body, {})?", index),
+        ),
+        ty => match ty.strip_suffix("[]") {
+            Some(element) => {
+                let (rust, read) = static_field(element, "word(body, at)?");
+                let read = format!(
+                    "elements(body, {})?\n                    \
+                     .map(|at| Ok({}))\n                    \
+                     .collect::<Result<_, String>>()?",
+                    index, read
+                );
+                (format!("Vec<{}>", rust), read)
+            }
+            None => static_field(ty, &format!("word(body, {})?", index)),
+        },
+    }
+}
+
+/// The Rust type of a static param and the expression reading it from `word`.
+fn static_field(solidity: &str, word: &str) -> (String, String) {
+    match solidity
+        .parse::<SolType>()
+        .unwrap_or(SolType::FixedBytes(32))
+    {
+        SolType::Address => (
+            "H160".to_string(),
+            format!("H160(last(uint({}, 20)?))", word),
+        ),
+        SolType::Bool => ("bool".to_string(), format!("boolean({})?", word)),
+        SolType::Uint(bits) if bits <= 128 => {
+            let rust = format!("u{}", bits.next_power_of_two().max(8));
+            let read = format!(
+                "{}::from_be_bytes(last(uint({}, {})?))",
+                rust,
+                word,
+                bits / 8
+            );
+            (rust, read)
+        }
+        SolType::Int(bits) if bits <= 128 => {
+            let rust = format!("i{}", bits.next_power_of_two().max(8));
+            let read = format!(
+                "{}::from_be_bytes(last(int({}, {})?))",
+                rust,
+                word,
+                bits / 8
+            );
+            (rust, read)
+        }
+        SolType::Uint(bits) => (
+            "U256".to_string(),
+            format!("U256::from_big_endian(&uint({}, {})?)", word, bits / 8),
+        ),
+        SolType::Int(bits) => (
+            "U256".to_string(),
+            format!("U256::from_big_endian(&int({}, {})?)", word, bits / 8),
+        ),
+        SolType::FixedBytes(len) => (
+            format!("[u8; {}]", len),
+            format!("first(fixed({}, {})?)", word, len),
+        ),
+    }
+}
+
+/// `exactInputSingle` as `exact_input_single`.
+fn snake_case(name: &str) -> String {
+    let mut out = String::new();
+    for (i, c) in name.chars().enumerate() {
+        if c.is_ascii_uppercase() && i > 0 && !out.ends_with('_') {
+            out.push('_');
+        }
+        out.push(c.to_ascii_lowercase());
+    }
+    out
+}
+
+/// `exactInputSingle` as `ExactInputSingle`, `unknown_a9059cbb` as `UnknownA9059cbb`.
+fn camel_case(name: &str) -> String {
+    name.split('_')
+        .filter(|part| !part.is_empty())
+        .map(|part| {
+            let mut chars = part.chars();
+            chars
+                .next()
+                .map(|c| c.to_ascii_uppercase().to_string() + chars.as_str())
+                .unwrap_or_default()
+        })
+        .collect()
+}
+
+/// Word readers of the generated modules.
+const HELPERS: &str = r#"
+    /// Word `index` of the body.
+    fn word(body: &[u8], index: usize) -> Result<[u8; 32], String> {
+        body.get(index * 32..index * 32 + 32)
+            .and_then(|w| w.try_into().ok())
+            .ok_or_else(|| format!("no word {}", index))
+    }
+
+    /// `word` if it holds an unsigned value of `bytes` bytes.
+    fn uint(word: [u8; 32], bytes: usize) -> Result<[u8; 32], String> {
+        match word[..32 - bytes].iter().all(|b| *b == 0) {
+            true => Ok(word),
+            false => Err(format!("the value doesn't fit in {} bits", bytes * 8)),
+        }
+    }
+
+    /// `word` if it holds a signed value of `bytes` bytes, sign extended.
+    fn int(word: [u8; 32], bytes: usize) -> Result<[u8; 32], String> {
+        let fill = match word[32 - bytes] & 0x80 {
+            0 => 0,
+            _ => 0xff,
+        };
+        match word[..32 - bytes].iter().all(|b| *b == fill) {
+            true => Ok(word),
+            false => Err(format!("the value doesn't fit in {} bits", bytes * 8)),
+        }
+    }
+
+    /// `word` if it holds `len` bytes, left aligned.
+    fn fixed(word: [u8; 32], len: usize) -> Result<[u8; 32], String> {
+        match word[len..].iter().all(|b| *b == 0) {
+            true => Ok(word),
+            false => Err(format!("non-zero bytes after the first {}", len)),
+        }
+    }
+
+    fn boolean(word: [u8; 32]) -> Result<bool, String> {
+        match uint(word, 1)?[31] {
+            0 => Ok(false),
+            1 => Ok(true),
+            _ => Err("neither 0 nor 1".to_string()),
+        }
+    }
+
+    fn last<const N: usize>(word: [u8; 32]) -> [u8; N] {
+        let mut out = [0; N];
+        out.copy_from_slice(&word[32 - N..]);
+        out
+    }
+
+    fn first<const N: usize>(word: [u8; 32]) -> [u8; N] {
+        let mut out = [0; N];
+        out.copy_from_slice(&word[..N]);
+        out
+    }
+
+    /// A length or an offset.
+    fn size(word: [u8; 32]) -> Result<usize, String> {
+        uint(word, 8).map(|w| u64::from_be_bytes(last(w)) as usize)
+    }
+
+    /// The word `index` points to, and the length it holds.
+    fn length(body: &[u8], index: usize) -> Result<(usize, usize), String> {
+        let offset = size(word(body, index)?)?;
+        if offset % 32 != 0 {
+            return Err(format!("word {} isn't an offset", index));
+        }
+        Ok((offset / 32, size(word(body, offset / 32)?)?))
+    }
+
+    /// The bytes word `index` is the offset of.
+    fn dynamic(body: &[u8], index: usize) -> Result<Vec<u8>, String> {
+        let (at, len) = length(body, index)?;
+        body[(at + 1) * 32..]
+            .get(..len)
+            .map(<[u8]>::to_vec)
+            .ok_or_else(|| format!("the data of word {} overruns the calldata", index))
+    }
+
+    /// The words of the elements of the array word `index` is the offset of.
+    fn elements(body: &[u8], index: usize) -> Result<std::ops::Range<usize>, String> {
+        let (at, len) = length(body, index)?;
+        match len <= body.len() / 32 - at - 1 {
+            true => Ok(at + 1..at + 1 + len),
+            false => Err(format!("the data of word {} overruns the calldata", index)),
+        }
+    }
+
+    /// The bytes of the array word `index` is the offset of.
+    fn dynamic_array(body: &[u8], index: usize) -> Result<Vec<Vec<u8>>, String> {
+        let elements = elements(body, index)?;
+        let data = &body[elements.start * 32..];
+        (0..elements.len()).map(|i| dynamic(data, i)).collect()
+    }
+"#;
@@ -0,0 +1,166 @@
+use crate::heuristics::Heuristics;
+use crate::limits::Limits;
+use crate::protocol::ProtocolRegistry;
+use crate::render::address::AddressCase;
+use crate::render::color::ColorMode;
+use crate::render::labels::Labels;
+use crate::render::output::OutputFormat;
+use crate::signatures::{SignatureMap, Signatures};
+use crate::tokens::Tokens;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+
+// ------------------------------------------------------------
+//  Config file
+// ------------------------------------------------------------
+
+/// Defaults for the CLI, read from `~/.config/calldata-decoder/config.toml`.
+///
+/// ```toml
+/// chain = "mainnet"
+/// color = "never"
+/// format = "json"
+/// address_case = "lowercase"
+/// signatures = ["~/sigs.txt"]
+/// labels = ["~/labels.txt"]
+/// tokens = ["~/tokens.txt"]
+/// plugins = ["~/.config/calldata-decoder/plugins"]
+///
+/// [rpc]
+/// mainnet = "https://eth.llamarpc.com"
+/// base = "wss://base.example"
+///
+/// [api_keys]
+/// etherscan = "..."
+///
+/// [heuristics]
+/// nested = false
+///
+/// [limits]
+/// max_depth = 2
+/// ```
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct Config {
+    /// Chain used when none is given, a key of `rpc`.
+    pub chain: Option<String>,
+    /// JSON-RPC endpoint per chain name.
+    pub rpc: BTreeMap<String, String>,
+    /// API keys per service, e.g. `etherscan`.
+    pub api_keys: BTreeMap<String, String>,
+    pub heuristics: Heuristics,
+    pub limits: Limits,
+    /// Signature files, one signature per line, or `.json` / `.csv` tables.
+    pub signatures: Vec<PathBuf>,
+    /// Address label files, one `0xaddress name` per line.
+    pub labels: Vec<PathBuf>,
+    /// Token files extending the built-in ones, one `chain 0xaddress symbol decimals` per line.
+    pub tokens: Vec<PathBuf>,
+    /// WASM protocol decoder plugins, `.wasm` files or directories of them.
+    pub plugins: Vec<PathBuf>,
+    pub color: Option<ColorMode>,
+    pub format: Option<OutputFormat>,
+    /// How address params are written, checksummed by default.
+    pub address_case: Option<AddressCase>,
+}
+
+impl Config {
+    /// `$XDG_CONFIG_HOME/calldata-decoder/config.toml`, falling back to `~/.config`.
+    pub fn default_path() -> Option<PathBuf> {
+        let base = match std::env::var_os("XDG_CONFIG_HOME") {
+            Some(dir) if !dir.is_empty() => PathBuf::from(dir),
+            _ => PathBuf::from(std::env::var_os("HOME")?).join(".config"),
+        };
+        Some(base.join("calldata-decoder").join("config.toml"))
+    }
+
+    pub fn from_toml(text: &str) -> Result<Self, String> {
+        toml::from_str(text).map_err(|e| e.to_string())
+    }
+
+    /// Reads the config at `path`.
+    pub fn load(path: &Path) -> Result<Self, String> {
+        let text =
+            std::fs::read_to_string(path).map_err(|e| format!("{}: {}", path.display(), e))?;
+        Self::from_toml(&text).map_err(|e| format!("{}: {}", path.display(), e))
+    }
+
+    /// Reads the config at the default path, an absent file giving the defaults.
+    pub fn load_default() -> Result<Self, String> {
+        match Self::default_path() {
+            Some(path) if path.exists() => Self::load(&path),
+            _ => Ok(Self::default()),
+        }
+    }
+
+    /// Endpoint of `chain`, or of the default chain when `None`.
+    pub fn rpc_url(&self, chain: Option<&str>) -> Option<String> {
+        let chain = chain.or(self.chain.as_deref())?;
+        self.rpc.get(chain).cloned()
+    }
+
+    /// API key of `service`, `<SERVICE>_API_KEY` in the environment taking precedence.
+    pub fn api_key(&self, service: &str) -> Option<String> {
+        let var = format!("{}_API_KEY", service.to_uppercase());
+        match std::env::var(var) {
+            Ok(key) if !key.is_empty() => Some(key),
+            _ => self.api_keys.get(service).cloned(),
+        }
+    }
+
+    /// Built-in signatures plus the ones in the configured files.
+    pub fn signature_sources(&self) -> Result<Signatures, String> {
+        let mut map = SignatureMap::new();
+        for path in self.signatures.iter() {
+            for (selector, signatures) in SignatureMap::parse(path, &read(path)?)?.iter() {
+                for signature in signatures {
+                    map.insert_with_selector(selector, signature);
+                }
+            }
+        }
+        Ok(Signatures::builtin().with_source(map))
+    }
+
+    /// Labels from every configured label file, later files overriding earlier ones.
+    pub fn labels(&self) -> Result<Labels, String> {
+        let mut labels = Labels::new();
+        for path in self.labels.iter() {
+            labels.extend(Labels::from_text(&read(path)?));
+        }
+        Ok(labels)
+    }
+
+    /// Built-in tokens plus the ones in the configured token files.
+    pub fn tokens(&self) -> Result<Tokens, String> {
+        let mut tokens = Tokens::builtin();
+        for path in self.tokens.iter() {
+            let extra = Tokens::from_text(&read(path)?)
+                .map_err(|e| format!("{}: {}", expand_home(path).display(), e))?;
+            tokens.extend(extra);
+        }
+        Ok(tokens)
+    }
+
+    /// Built-in protocol decoders followed by the configured plugins.
+    pub fn protocols(&self) -> Result<ProtocolRegistry, String> {
+        let mut registry = ProtocolRegistry::builtin();
+        for path in self.plugins.iter() {
+            registry = registry.with_plugins(&expand_home(path))?;
+        }
+        Ok(registry)
+    }
+}
+
+/// `~/` expanded to the home directory.
+pub fn expand_home(path: &Path) -> PathBuf {
+    match (path.strip_prefix("~"), std::env::var_os("HOME")) {
+        (Ok(rest), Some(home)) => PathBuf::from(home).join(rest),
+        _ => path.to_path_buf(),
+    }
+}
+
+fn read(path: &Path) -> Result<String, String> {
+    let path = expand_home(path);
+    std::fs::read_to_string(&path).map_err(|e| format!("{}: {}", path.display(), e))
+}
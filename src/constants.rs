@@ -1,19 +1,30 @@
-pub const MASK_4: &str = "FFFFFFFF";
+use serde::{Deserialize, Serialize};
+
+/// Bytes in an ABI word.
+pub const WORD: usize = 32;
+
+/// Bytes in a method selector.
+pub const SELECTOR: usize = 4;
+
+pub const MASK_4: [u8; 4] = [0xff; 4];
 
 // PUSH20 followed by AND is used to "mask" the 32-byte address into its correct type.
-pub const MASK_20: &str = "ffffffffffffffffffffffffffffffffffffffff";
+pub const MASK_20: [u8; 20] = [0xff; 20];
 
-pub const EMPTY_4: &str = "00000000";
-pub const EMPTY_32: &str = "0000000000000000000000000000000000000000000000000000000000000000";
+pub const EMPTY_4: [u8; 4] = [0; 4];
+pub const EMPTY_32: [u8; WORD] = [0; WORD];
 
 //
-pub const MAX_U256: &str = "ffffffffffffffffffffffffffffffffffffffffffffffffffffffffffffffff";
+pub const MAX_U256: [u8; WORD] = [0xff; WORD];
 
 //
-pub const MAX_U128: &str = "00000000000000000000000000000000ffffffffffffffffffffffffffffffff";
+pub const MAX_U128: [u8; WORD] = [
+    0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0,
+    0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff,
+];
 
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
 pub enum Types {
     AnyZero,
     AnyMax,
@@ -30,4 +41,56 @@ pub enum Types {
     Address0,
     ZeroUint,
     MaxUint128,
+    /// A small value in a Vyper contract, e.g. a Curve coin index.
+    Int128,
+}
+
+impl Types {
+    /// The canonical Solidity type used when writing out a signature.
+    pub fn solidity_name(&self) -> &'static str {
+        match self {
+            Types::AnyZero | Types::AnyMax | Types::Uint | Types::ZeroUint => "uint256",
+            Types::MaxUint128 => "uint128",
+            Types::Int => "int256",
+            Types::Int128 => "int128",
+            Types::Bytes => "bytes32",
+            Types::Bool => "bool",
+            Types::Uint8 => "uint8",
+            Types::Bytes1 => "bytes1",
+            Types::Bytes20 => "bytes20",
+            Types::Address | Types::Address0 => "address",
+            Types::Selector => "bytes4",
+            Types::String => "string",
+        }
+    }
+
+    /// Whether a Solidity type (e.g. `uint256`, `address`, `bytes32[]`) fits this guess.
+    ///
+    /// Dynamic types (`string`, `bytes`, `T[]`) are represented in the head by
+    /// their offset word, which is guessed as a number, so any number guess
+    /// fits them; telling whether the word is one takes following the offset
+    /// (see `crosscheck::cross_check`).
+    pub fn matches_solidity(&self, ty: &str) -> bool {
+        let ty = ty.trim();
+        if ty.ends_with(']') || ty == "string" || ty == "bytes" {
+            return matches!(
+                self,
+                Types::Uint | Types::Uint8 | Types::String | Types::Bytes
+            );
+        }
+        match self {
+            Types::AnyZero => true,
+            Types::AnyMax => ty.starts_with("uint") || ty.starts_with("int") || ty.starts_with("bytes"),
+            Types::Uint | Types::ZeroUint | Types::MaxUint128 => ty.starts_with("uint"),
+            Types::Uint8 => ty.starts_with("uint"),
+            Types::Int | Types::Int128 => ty.starts_with("int"),
+            Types::Bytes => ty.starts_with("bytes"),
+            Types::Bytes1 => ty.starts_with("bytes"),
+            Types::Bytes20 => ty == "bytes20",
+            Types::Bool => ty == "bool",
+            Types::Address | Types::Address0 => ty == "address",
+            Types::Selector => ty == "bytes4",
+            Types::String => ty == "string",
+        }
+    }
 }
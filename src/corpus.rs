@@ -0,0 +1,26 @@
+// ------------------------------------------------------------
+//  Corpus
+// ------------------------------------------------------------
+
+/// `transfer(address,uint256)` of 1000 USDT: a selector and two static words.
+pub const TRANSFER: &str = "0xa9059cbb000000000000000000000000dac17f958d2ee523a2206206994597c13d831ec7000000000000000000000000000000000000000000000000000000003b9aca00";
+
+/// Uniswap V3 `multicall(bytes[])` of a `mint` and a `refundETH`, from
+/// tx 0x1fe71e209bfed2990ac72e88a640b09008be10579ae1405a8c86ce2ced5767d1.
+pub const MULTICALL: &str = "0xac9650d800000000000000000000000000000000000000000000000000000000000000200000000000000000000000000000000000000000000000000000000000000002000000000000000000000000000000000000000000000000000000000000004000000000000000000000000000000000000000000000000000000000000001e0000000000000000000000000000000000000000000000000000000000000016488316456000000000000000000000000c011a73ee8576fb46f5e1c5751ca3b9fe0af2a6f000000000000000000000000c02aaa39b223fe8d0a0e5c4f27ead9083c756cc20000000000000000000000000000000000000000000000000000000000002710fffffffffffffffffffffffffffffffffffffffffffffffffffffffffffee530ffffffffffffffffffffffffffffffffffffffffffffffffffffffffffff1b18000000000000000000000000000000000000000000000000016345785d89fd6800000000000000000000000000000000000000000000000000007f73eca3063a000000000000000000000000000000000000000000000000016042b530ddaec600000000000000000000000000000000000000000000000000007e59f044bada000000000000000000000000f847e9d51989033b691b8be943f8e9e268f99b9e000000000000000000000000000000000000000000000000000000006377347700000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000412210e8a00000000000000000000000000000000000000000000000000000000";
+
+/// Seaport `fulfillBasicOrder` of an ERC721 for ETH with one additional recipient:
+/// a struct of 18 fields behind an offset, a nested array and a signature.
+pub const SEAPORT: &str = "0xfb0f3ee100000000000000000000000000000000000000000000000000000000000000200000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000ae153d89fe80000000000000000000000000006e1ac5b2ea2ab5d7a0b1d8a8f7b7e1c7e0f4d2c1000000000000000000000000004c00500000ad104d7dbd00e3ae0a5c00560c00000000000000000000000000bd3531da5cf5857e7cfaa92426877b022e612cf80000000000000000000000000000000000000000000000000000000000001e8400000000000000000000000000000000000000000000000000000000000000010000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000006526c68000000000000000000000000000000000000000000000000000000000654e53800000000000000000000000000000000000000000000000000000000000000000360c6ebe0000000000000000000000000000000000000000f7a6eb6700e4c4180000007b02230091a7ed01230072f7006a004d60a8d4e71d599b8104250f00000000007b02230091a7ed01230072f7006a004d60a8d4e71d599b8104250f00000000000000000000000000000000000000000000000000000000000000000001000000000000000000000000000000000000000000000000000000000000024000000000000000000000000000000000000000000000000000000000000002a0000000000000000000000000000000000000000000000000000000000000000100000000000000000000000000000000000000000000000000045a2189d990000000000000000000000000000000a26b00c1f0df003000390027140000faa71900000000000000000000000000000000000000000000000000000000000000413f3f3f3f3f3f3f3f3f3f3f3f3f3f3f3f3f3f3f3f3f3f3f3f3f3f3f3f3f3f3f3f7d7d7d7d7d7d7d7d7d7d7d7d7d7d7d7d7d7d7d7d7d7d7d7d7d7d7d7d7d7d7d7d1b00000000000000000000000000000000000000000000000000000000000000";
+
+/// Safe `execTransaction` of a USDC transfer: a nested call in a `bytes` param
+/// and a packed 65-byte signature.
+pub const SAFE: &str = "0x6a761202000000000000000000000000a0b86991c6218b36c1d19d4a2e9eb0ce3606eb480000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000014000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000001c00000000000000000000000000000000000000000000000000000000000000044a9059cbb000000000000000000000000dac17f958d2ee523a2206206994597c13d831ec7000000000000000000000000000000000000000000000000000000003b9aca00000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000041a1a1a1a1a1a1a1a1a1a1a1a1a1a1a1a1a1a1a1a1a1a1a1a1a1a1a1a1a1a1a1a15b5b5b5b5b5b5b5b5b5b5b5b5b5b5b5b5b5b5b5b5b5b5b5b5b5b5b5b5b5b5b5b1c00000000000000000000000000000000000000000000000000000000000000";
+
+/// Every calldata of the corpus with its name, from simplest to most nested.
+pub const ALL: [(&str, &str); 4] = [
+    ("transfer", TRANSFER),
+    ("multicall", MULTICALL),
+    ("seaport", SEAPORT),
+    ("safe", SAFE),
+];
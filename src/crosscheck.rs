@@ -0,0 +1,221 @@
+use crate::constants::{SELECTOR, WORD};
+use crate::{word_usize, Calldata};
+use std::process::Command;
+
+// ------------------------------------------------------------
+//  Heimdall cross-check
+// ------------------------------------------------------------
+
+/// Whether heimdall's decoded type for a param fits our guesses.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Agreement {
+    /// One of our candidate types matches heimdall's type, or for a dynamic
+    /// type, the word is an offset to a length whose data fits the calldata.
+    Agree,
+    /// None of our candidate types match heimdall's type, or the word isn't
+    /// an offset to data of that type.
+    Disagree,
+    /// Heimdall's type isn't checked against the words, e.g. a tuple or a
+    /// fixed-size array.
+    Unchecked,
+    /// Heimdall decoded this param but our decode has no word for it.
+    Missing,
+}
+
+#[derive(Debug, Clone)]
+pub struct ParamCheck {
+    /// Index of the param in the main method's body.
+    pub index: usize,
+    /// The Solidity type heimdall decoded, if it reached this param.
+    pub heimdall: Option<String>,
+    /// Solidity names of our candidate types, empty when we have no word there.
+    pub guessed: Vec<String>,
+    pub agreement: Agreement,
+}
+
+#[derive(Debug, Clone)]
+pub struct CrossCheck {
+    /// Signature heimdall resolved or reconstructed, e.g. `transfer(address,uint256)`.
+    pub signature: Option<String>,
+    pub params: Vec<ParamCheck>,
+}
+
+impl CrossCheck {
+    /// Amount of params both decoders agree on.
+    pub fn agreed(&self) -> usize {
+        self.params
+            .iter()
+            .filter(|p| p.agreement == Agreement::Agree)
+            .count()
+    }
+
+    /// Ratio of agreements over compared params, leaving out the unchecked
+    /// ones (0 when nothing was compared).
+    pub fn confidence(&self) -> f64 {
+        let compared = self
+            .params
+            .iter()
+            .filter(|p| p.agreement != Agreement::Unchecked)
+            .count();
+        match compared {
+            0 => 0.0,
+            _ => self.agreed() as f64 / compared as f64,
+        }
+    }
+}
+
+/// Runs `heimdall decode` on the calldata and returns its stdout.
+///
+/// ## Params
+/// 1. calldata - raw calldata, with or without the `0x` prefix.
+pub fn run_heimdall(calldata: &str) -> std::io::Result<String> {
+    let output = Command::new("heimdall")
+        .args(["decode", calldata, "--default"])
+        .output()?;
+    if !output.status.success() {
+        return Err(std::io::Error::other(
+            String::from_utf8_lossy(&output.stderr).to_string(),
+        ));
+    }
+    Ok(String::from_utf8_lossy(&output.stdout).to_string())
+}
+
+/// Finds the first function signature in heimdall's output.
+///
+/// ## Returns
+/// 1. The full signature.
+/// 2. The Solidity types of its top-level params.
+pub fn parse_heimdall_signature(output: &str) -> Option<(String, Vec<String>)> {
+    for token in output.split_whitespace() {
+        let Some(open) = token.find('(') else { continue };
+        let name = &token[..open];
+        if name.is_empty() || !name.chars().all(|c| c.is_alphanumeric() || c == '_') {
+            continue;
+        }
+        let Some(close) = token.rfind(')') else { continue };
+        if close < open {
+            continue;
+        }
+        let signature = token[..=close].to_string();
+        return Some((signature, split_types(&token[open + 1..close])));
+    }
+    None
+}
+
+/// Splits a comma-separated type list, keeping tuples `(a,b)` intact.
+pub fn split_types(types: &str) -> Vec<String> {
+    let mut out = vec![];
+    let mut depth = 0;
+    let mut current = String::new();
+    for c in types.chars() {
+        match c {
+            '(' => depth += 1,
+            ')' => depth -= 1,
+            ',' if depth == 0 => {
+                out.push(std::mem::take(&mut current));
+                continue;
+            }
+            _ => {}
+        }
+        current.push(c);
+    }
+    if !current.is_empty() {
+        out.push(current);
+    }
+    out
+}
+
+/// Compares our decode of the main method against heimdall's output.
+///
+/// ## Params
+/// 1. calldata - our decode.
+/// 2. heimdall_output - stdout of `heimdall decode` on the same input.
+pub fn cross_check(calldata: &Calldata, heimdall_output: &str) -> CrossCheck {
+    let (signature, types) = match parse_heimdall_signature(heimdall_output) {
+        Some((s, t)) => (Some(s), t),
+        None => (None, vec![]),
+    };
+    let ours = calldata
+        .main_details
+        .first()
+        .map(|p| p.types.clone())
+        .unwrap_or_default();
+    let body = calldata.bytes().get(SELECTOR..).unwrap_or_default();
+
+    // Our body also holds the tail words of dynamic types, so only the
+    // head params heimdall decoded are compared.
+    let params = types
+        .iter()
+        .enumerate()
+        .map(|(index, ty)| {
+            let ty = ty.trim();
+            let guessed = ours.get(index);
+            let agreement = match guessed {
+                None => Agreement::Missing,
+                Some(_) if ty.starts_with('(') || fixed_array(ty) => Agreement::Unchecked,
+                // The head word of a dynamic param is an offset, which every
+                // number guess would fit.
+                Some(_) if dynamic(ty) => match points_at_data(body, index, ty) {
+                    true => Agreement::Agree,
+                    false => Agreement::Disagree,
+                },
+                Some(g) if g.types().iter().any(|t| t.matches_solidity(ty)) => Agreement::Agree,
+                Some(_) => Agreement::Disagree,
+            };
+            let mut names = vec![];
+            for kind in guessed.map(|g| g.types()).unwrap_or_default() {
+                let name = kind.solidity_name().to_string();
+                if !names.contains(&name) {
+                    names.push(name);
+                }
+            }
+            ParamCheck {
+                index,
+                heimdall: Some(ty.to_string()),
+                guessed: names,
+                agreement,
+            }
+        })
+        .collect();
+
+    CrossCheck { signature, params }
+}
+
+/// `bytes`, `string` and `T[]`, encoded behind an offset.
+fn dynamic(ty: &str) -> bool {
+    ty == "bytes" || ty == "string" || ty.ends_with("[]")
+}
+
+/// `T[N]`, encoded in place when `T` is static.
+fn fixed_array(ty: &str) -> bool {
+    ty.ends_with(']') && !ty.ends_with("[]")
+}
+
+/// Whether the word at `index` is an offset, past it, to a length whose data
+/// fits in `body`: that many words for an array, that many bytes otherwise.
+fn points_at_data(body: &[u8], index: usize, ty: &str) -> bool {
+    let word = |at: usize| body.get(at..at + WORD).and_then(word_usize);
+    let Some(offset) = word(index * WORD) else {
+        return false;
+    };
+    if offset % WORD != 0 || offset <= index * WORD {
+        return false;
+    }
+    let Some(len) = word(offset) else {
+        return false;
+    };
+    let words = match ty.ends_with("[]") {
+        true => len,
+        false => len.div_ceil(WORD),
+    };
+    words
+        .checked_mul(WORD)
+        .and_then(|data| data.checked_add(offset + WORD))
+        .is_some_and(|end| end <= body.len())
+}
+
+/// Decodes `calldata` with both decoders and compares them.
+pub fn cross_check_with_heimdall(calldata: &str) -> std::io::Result<CrossCheck> {
+    let output = run_heimdall(calldata)?;
+    Ok(cross_check(&Calldata::new(calldata), &output))
+}
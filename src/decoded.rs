@@ -0,0 +1,647 @@
+use crate::constants::*;
+use crate::prototypes::{layout_tuples, top_signature};
+use crate::render::address::checksum_hex;
+use crate::signatures::is_value_type;
+use crate::type_guesser::*;
+use crate::text::{attach_texts, DecodedText};
+use crate::walletsig::{attach_signatures, DecodedSignature};
+use crate::{decode_hex, guess_param_type, Calldata, DecodeError};
+use primitive_types::U256;
+use serde::{Deserialize, Serialize};
+
+// ------------------------------------------------------------
+//  Decode tree
+// ------------------------------------------------------------
+
+/// Byte range `[start, end)` a value occupies in the original calldata.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Span {
+    pub start: usize,
+    pub end: usize,
+}
+
+/// A potential type of a param and how much we trust it.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TypeCandidate {
+    /// Heuristic type that was guessed.
+    pub kind: Types,
+    /// Solidity type the guess maps to.
+    pub solidity: String,
+    /// 0..=1, candidates of a param sum to 1.
+    pub confidence: f64,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DecodedParam {
+    /// Position of the word in the method's body.
+    pub index: usize,
+    /// 32-byte word as hex (64 chars, no prefix).
+    pub raw: String,
+    /// The word rendered as its most likely type.
+    pub value: String,
+    /// Candidates ordered from most to least likely.
+    pub types: Vec<TypeCandidate>,
+    pub span: Span,
+    /// The string this word is the length of, when text follows it.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub text: Option<DecodedText>,
+    /// The signature in the `bytes` this word is the length of, when it holds one.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub signature: Option<DecodedSignature>,
+    /// Name of the address this word holds, e.g. a token's symbol (see
+    /// `render::labels::apply_labels`).
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub label: Option<String>,
+    /// The amount in the units of the token the call is sent to, e.g. `1.5 USDC`
+    /// (see `tokens::apply_token_amount`).
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub amount: Option<String>,
+}
+
+/// Decode tree of a call and the calls nested in it.
+///
+/// Deterministic: the same calldata under the same settings always gives the same
+/// tree, field for field and in the same order, so its serialized form can be
+/// snapshotted or used as a cache key.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DecodedCalldata {
+    /// 4-byte method selector (8 chars, no prefix).
+    pub selector: String,
+    /// Signature built from each param's most likely type.
+    pub signature_guess: String,
+    pub params: Vec<DecodedParam>,
+    /// Method calls embedded in this call's params.
+    pub calls: Vec<DecodedCalldata>,
+    /// Where the selector and its params sit in the original calldata.
+    pub span: Span,
+    /// Irregularities found in this call's params.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub warnings: Vec<DecodeWarning>,
+    /// How the wrapper holding this call makes it, e.g. `Safe transaction to
+    /// 0x… value 1` (see `wrappers`).
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub layer: Option<String>,
+    /// Dynamic tuples among the params, bounded by the offsets pointing at
+    /// them (see `prototypes::layout_tuples`). A static struct among the
+    /// params isn't here: with no offset around it, it reads the same as its
+    /// fields passed one by one.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub tuples: Vec<DecodedTuple>,
+}
+
+/// Words of a call grouped into a dynamic tuple, e.g. the `(address,bytes)`
+/// an offset in the head points at.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DecodedTuple {
+    /// Index of the param holding the tuple's offset.
+    pub offset: usize,
+    /// Index of the tuple's first param.
+    pub start: usize,
+    /// Index of the param after its last.
+    pub end: usize,
+    /// Type of each field as the words suggest it, e.g. `["address", "bytes"]`.
+    pub types: Vec<String>,
+    /// Dynamic tuples among its fields, or elements of its arrays.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub tuples: Vec<DecodedTuple>,
+}
+
+/// Something irregular about the calldata, and where it is.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "kind", rename_all = "camelCase")]
+pub enum DecodeWarning {
+    /// A length word declaring more bytes than are left; what follows is left as words.
+    #[serde(rename_all = "camelCase")]
+    LengthOutOfBounds {
+        /// Byte offset of the length word.
+        at: usize,
+        declared: usize,
+        /// Bytes left from the end of the length word.
+        remaining: usize,
+    },
+    /// A word taken for an offset that points past the params it's in: the
+    /// first word of the params, an element of an array of offsets or a word
+    /// in a head of corroborated offsets. It isn't followed.
+    #[serde(rename_all = "camelCase")]
+    OffsetOutOfBounds {
+        /// Byte offset of the word.
+        at: usize,
+        offset: usize,
+        /// Bytes of params the offset is counted in.
+        len: usize,
+    },
+    /// The first offset of a call's params pointing at or before its own word,
+    /// at a length taken for a call, where an encoder would have pointed past
+    /// the head.
+    #[serde(rename_all = "camelCase")]
+    NonCanonicalOffset {
+        /// Byte offset of the word.
+        at: usize,
+        offset: usize,
+    },
+    /// Bytes after the last full word, left as bytes rather than read as a param.
+    #[serde(rename_all = "camelCase")]
+    UnparsedTail {
+        /// Byte offset of the first left over byte.
+        at: usize,
+        len: usize,
+    },
+    /// Non-zero bytes where a nested call should be padded to a full word.
+    #[serde(rename_all = "camelCase")]
+    PaddingMismatch {
+        /// Byte offset of the padding.
+        at: usize,
+        len: usize,
+    },
+}
+
+impl DecodeWarning {
+    /// Byte offset in the calldata the warning is about.
+    pub fn at(&self) -> usize {
+        match self {
+            DecodeWarning::LengthOutOfBounds { at, .. }
+            | DecodeWarning::OffsetOutOfBounds { at, .. }
+            | DecodeWarning::NonCanonicalOffset { at, .. }
+            | DecodeWarning::UnparsedTail { at, .. }
+            | DecodeWarning::PaddingMismatch { at, .. } => *at,
+        }
+    }
+
+    /// The same warning `by` bytes further into the calldata.
+    pub(crate) fn shifted(mut self, by: usize) -> Self {
+        match &mut self {
+            DecodeWarning::LengthOutOfBounds { at, .. }
+            | DecodeWarning::OffsetOutOfBounds { at, .. }
+            | DecodeWarning::NonCanonicalOffset { at, .. }
+            | DecodeWarning::UnparsedTail { at, .. }
+            | DecodeWarning::PaddingMismatch { at, .. } => *at += by,
+        }
+        self
+    }
+
+    /// The same warning `by` bytes closer to the start of the calldata.
+    pub(crate) fn unshifted(mut self, by: usize) -> Self {
+        match &mut self {
+            DecodeWarning::LengthOutOfBounds { at, .. }
+            | DecodeWarning::OffsetOutOfBounds { at, .. }
+            | DecodeWarning::NonCanonicalOffset { at, .. }
+            | DecodeWarning::UnparsedTail { at, .. }
+            | DecodeWarning::PaddingMismatch { at, .. } => *at = at.saturating_sub(by),
+        }
+        self
+    }
+}
+
+impl std::fmt::Display for DecodeWarning {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            DecodeWarning::LengthOutOfBounds {
+                at,
+                declared,
+                remaining,
+            } => write!(
+                f,
+                "length at byte {} declares {} bytes, only {} left",
+                at, declared, remaining
+            ),
+            DecodeWarning::OffsetOutOfBounds { at, offset, len } => write!(
+                f,
+                "offset {} at byte {} points past the {} bytes of params",
+                offset, at, len
+            ),
+            DecodeWarning::NonCanonicalOffset { at, offset } => write!(
+                f,
+                "offset {} at byte {} points back into the words before it",
+                offset, at
+            ),
+            DecodeWarning::UnparsedTail { at, len } => {
+                write!(f, "{} bytes at byte {} don't fill a word", len, at)
+            }
+            DecodeWarning::PaddingMismatch { at, len } => {
+                write!(f, "{} bytes of padding at byte {} aren't zero", len, at)
+            }
+        }
+    }
+}
+
+/// How much of an input could be decoded, ordered from best to worst.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum DecodeStatus {
+    /// Every param is a full word.
+    Decoded,
+    /// Decoded, but some bytes don't fit the word layout.
+    Partial,
+    /// Not calldata at all (not hex, or shorter than a selector).
+    Invalid,
+}
+
+impl DecodeStatus {
+    /// Process exit code for the CLI: 0 decoded, 2 partial, 3 invalid input.
+    pub fn exit_code(&self) -> i32 {
+        match self {
+            DecodeStatus::Decoded => 0,
+            DecodeStatus::Partial => 2,
+            DecodeStatus::Invalid => 3,
+        }
+    }
+}
+
+/// The annotated words of the call then of its nested calls, see
+/// `render::hexdump::render_hexdump`.
+impl std::fmt::Display for DecodedCalldata {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(&crate::render::hexdump::render_hexdump(self))
+    }
+}
+
+impl DecodedCalldata {
+    /// Builds a node from a method's parsed params.
+    ///
+    /// ## Params
+    /// 1. params - the selector, raw words and guessed types of the method.
+    /// 2. start - byte offset of the selector in the original calldata.
+    pub fn from_params(params: &Params, start: usize) -> Self {
+        let mut decoded = params
+            .params
+            .iter()
+            .enumerate()
+            .map(|(index, raw)| {
+                let types = match params.types.get(index) {
+                    Some(t) => t.clone(),
+                    None => guess_param_type(raw),
+                };
+                let word_start = start + 4 + index * 32;
+                DecodedParam {
+                    index,
+                    raw: raw.clone(),
+                    value: render_value(&types, raw),
+                    types: candidates(&types, raw),
+                    span: Span {
+                        start: word_start,
+                        end: word_start + raw.len() / 2,
+                    },
+                    text: None,
+                    signature: None,
+                    label: None,
+                    amount: None,
+                }
+            })
+            .collect::<Vec<DecodedParam>>();
+        attach_texts(&mut decoded);
+        attach_signatures(&mut decoded);
+
+        let end = decoded.last().map(|p| p.span.end).unwrap_or(start + 4);
+        Self {
+            selector: params.selector.clone(),
+            signature_guess: signature_guess(&params.selector, &decoded),
+            tuples: layout_tuples(&decoded),
+            params: decoded,
+            calls: vec![],
+            span: Span { start, end },
+            warnings: vec![],
+            layer: None,
+        }
+    }
+
+    /// `Partial` when any call has a param that isn't a full 32-byte word or
+    /// a warning, `Invalid` when there isn't even a selector.
+    pub fn status(&self) -> DecodeStatus {
+        if self.selector.len() < 8 {
+            return DecodeStatus::Invalid;
+        }
+        let complete = self
+            .walk()
+            .iter()
+            .all(|call| call.warnings.is_empty() && call.params.iter().all(|p| p.raw.len() == 64));
+        match complete {
+            true => DecodeStatus::Decoded,
+            false => DecodeStatus::Partial,
+        }
+    }
+
+    /// Iterates over this node and all nested nodes, depth-first.
+    pub fn walk(&self) -> Vec<&DecodedCalldata> {
+        let mut out = vec![self];
+        for call in self.calls.iter() {
+            out.extend(call.walk());
+        }
+        out
+    }
+
+    /// This node and all nested nodes, parents first, each with the prefix of
+    /// its paths: `""` for this node, `calls[0].`, `calls[0].calls[1].`, ...
+    pub(crate) fn walk_paths(&self) -> Vec<(String, &DecodedCalldata)> {
+        let mut out = vec![];
+        let mut stack = vec![(String::new(), self)];
+        while let Some((prefix, call)) = stack.pop() {
+            for (i, nested) in call.calls.iter().enumerate().rev() {
+                stack.push((format!("{}calls[{}].", prefix, i), nested));
+            }
+            out.push((prefix, call));
+        }
+        out
+    }
+
+    /// Params of every call with their path (e.g. `calls[0].params[2]`), in
+    /// calldata order, leaving out the outer params that hold a nested call.
+    pub(crate) fn leaf_params(&self) -> Vec<(String, &DecodedParam)> {
+        let mut out = vec![];
+        for (prefix, call) in self.walk_paths() {
+            for (i, param) in call.params.iter().enumerate() {
+                let holds_call = call
+                    .calls
+                    .iter()
+                    .any(|c| c.span.start < param.span.end && param.span.start < c.span.end);
+                if !holds_call {
+                    out.push((format!("{}params[{}]", prefix, i), param));
+                }
+            }
+        }
+        out.sort_by_key(|(_, p)| p.span.start);
+        out
+    }
+
+    /// Moves the spans of this node, its params, warnings and nested calls
+    /// `by` bytes forward, for a call decoded apart from the calldata it's in.
+    pub(crate) fn shift(&mut self, by: usize) {
+        self.span.start += by;
+        self.span.end += by;
+        for param in self.params.iter_mut() {
+            param.span.start += by;
+            param.span.end += by;
+        }
+        self.warnings = std::mem::take(&mut self.warnings)
+            .into_iter()
+            .map(|w| w.shifted(by))
+            .collect();
+        for nested in self.calls.iter_mut() {
+            nested.shift(by);
+        }
+    }
+
+    /// Moves the spans of this node, its params, warnings and nested calls
+    /// `by` bytes back, for a decode behind a made-up selector.
+    pub(crate) fn unshift(&mut self, by: usize) {
+        self.span.start = self.span.start.saturating_sub(by);
+        self.span.end = self.span.end.saturating_sub(by);
+        for param in self.params.iter_mut() {
+            param.span.start = param.span.start.saturating_sub(by);
+            param.span.end = param.span.end.saturating_sub(by);
+        }
+        self.warnings = std::mem::take(&mut self.warnings)
+            .into_iter()
+            .map(|w| w.unshifted(by))
+            .collect();
+        for nested in self.calls.iter_mut() {
+            nested.unshift(by);
+        }
+    }
+}
+
+impl DecodedParam {
+    /// ` (label)` and ` (amount)` for the ones the param has, as the
+    /// renderers read by people write them after its value.
+    pub fn annotations(&self) -> String {
+        let label = self.label.iter().chain(self.amount.iter());
+        label.map(|s| format!(" ({})", s)).collect()
+    }
+
+    /// The value followed by its `annotations`, e.g. `0x… (WETH)` or
+    /// `1500000 (1.5 USDC)`.
+    pub fn labelled_value(&self) -> String {
+        format!("{}{}", self.value, self.annotations())
+    }
+
+    /// Puts the candidate fitting the declared type first, adding one when none
+    /// does, and writes the value as that type.
+    pub(crate) fn prefer(&mut self, expected: &str) {
+        let mut kinds = self
+            .types
+            .iter()
+            .map(|t| t.kind.clone())
+            .collect::<Vec<_>>();
+        // A zero word fits any type, so it doesn't say how to render the value.
+        let fits = |kind: &Types| *kind != Types::AnyZero && kind.matches_solidity(expected);
+        match kinds.iter().position(fits) {
+            Some(i) => {
+                let kind = kinds.remove(i);
+                kinds.insert(0, kind);
+            }
+            None => kinds.insert(0, fitting(expected)),
+        }
+        let types = ParamTypes::new(kinds);
+        self.value = render_value(&types, &self.raw);
+        self.types = candidates(&types, &self.raw);
+        self.types[0].solidity = expected.to_string();
+    }
+}
+
+/// The heuristic type a declared Solidity type is rendered as.
+pub(crate) fn fitting(ty: &str) -> Types {
+    match ty {
+        "address" => Types::Address,
+        "bool" => Types::Bool,
+        "uint8" => Types::Uint8,
+        "bytes1" => Types::Bytes1,
+        "bytes4" => Types::Selector,
+        "bytes20" => Types::Bytes20,
+        "int128" => Types::Int128,
+        "string" => Types::String,
+        _ if !is_value_type(ty) => Types::Uint,
+        _ if ty.starts_with("uint") => Types::Uint,
+        _ if ty.starts_with("int") => Types::Int,
+        _ => Types::Bytes,
+    }
+}
+
+/// Spreads confidence over the candidates of the word `raw`, favouring the
+/// earlier guesses and the types the word's shape fits (see `type_guesser::fit`).
+/// A candidate never gets more confidence than the ones guessed before it.
+pub fn candidates(types: &ParamTypes, raw: &str) -> Vec<TypeCandidate> {
+    let word = decode_hex(raw);
+    let n = types.types().len();
+    let mut weights = types
+        .types()
+        .iter()
+        .enumerate()
+        .map(|(rank, kind)| (n - rank) as f64 * fit(kind, &word))
+        .collect::<Vec<f64>>();
+    for i in 1..n {
+        weights[i] = weights[i].min(weights[i - 1]);
+    }
+    let total = weights.iter().sum::<f64>();
+    types
+        .types()
+        .iter()
+        .zip(weights)
+        .map(|(kind, weight)| TypeCandidate {
+            kind: kind.clone(),
+            solidity: kind.solidity_name().to_string(),
+            confidence: weight / total,
+        })
+        .collect()
+}
+
+/// Confidence of the candidate at `rank` out of `n`, linearly decreasing and summing to 1.
+pub fn confidence(rank: usize, n: usize) -> f64 {
+    let total = (n * (n + 1) / 2) as f64;
+    (n - rank) as f64 / total
+}
+
+/// `unknown_<selector>(t0,t1,...)` from each param's most likely type.
+pub fn signature_guess(selector: &str, params: &[DecodedParam]) -> String {
+    let types = params
+        .iter()
+        .map(|p| match p.types.first() {
+            Some(t) => t.solidity.clone(),
+            None => "bytes32".to_string(),
+        })
+        .collect::<Vec<String>>();
+    format!("unknown_{}({})", selector, types.join(","))
+}
+
+/// Names each call of the tree by its top reconstructed signature (see
+/// `prototypes::top_signature`), once its nested calls are attached.
+fn guess_signatures(call: &mut DecodedCalldata) {
+    call.calls.iter_mut().for_each(guess_signatures);
+    call.signature_guess = top_signature(&call.selector, &call.params, call.calls.len());
+}
+
+/// Renders a 32-byte word as the first of its guessed types.
+///
+/// ## Params
+/// 1. types - the guessed types of the word.
+/// 2. raw - 32 byte str representation of the word.
+pub fn render_value(types: &ParamTypes, raw: &str) -> String {
+    let Some(ty) = types.types().first() else {
+        return format!("0x{}", raw);
+    };
+    if raw.len() > 64 || !raw.bytes().all(|c| c.is_ascii_hexdigit()) {
+        return format!("0x{}", raw);
+    }
+    match ty {
+        Types::Address | Types::Address0 if raw.len() >= 40 => {
+            checksum_hex(&raw[raw.len() - 40..]).unwrap_or_else(|| format!("0x{}", raw))
+        }
+        Types::Bytes20 if raw.len() >= 40 => format!("0x{}", &raw[raw.len() - 40..]),
+        Types::Bool => raw.bytes().any(|c| c != b'0').to_string(),
+        Types::Selector if raw.len() >= 8 => format!("0x{}", &raw[..8]),
+        Types::Int | Types::Int128 => match raw.len() == 64 && raw.as_bytes()[0] >= b'8' {
+            true => render_negative(raw),
+            false => render_decimal(raw),
+        },
+        Types::AnyMax | Types::Bytes | Types::Bytes1 | Types::String | Types::Selector
+        | Types::Address | Types::Address0 | Types::Bytes20 => format!("0x{}", raw),
+        Types::AnyZero
+        | Types::Uint
+        | Types::Uint8
+        | Types::ZeroUint
+        | Types::MaxUint128 => render_decimal(raw),
+    }
+}
+
+/// The value of a hex word, `None` when it needs more than 128 bits.
+pub fn hex_u128(raw: &str) -> Option<u128> {
+    let digits = raw.trim_start_matches('0');
+    match digits.len() {
+        0 => Some(0),
+        1..=32 => u128::from_str_radix(digits, 16).ok(),
+        _ => None,
+    }
+}
+
+/// Unsigned decimal of a valid hex word, only parsing a `U256` when it doesn't fit a `u128`.
+fn render_decimal(raw: &str) -> String {
+    match hex_u128(raw) {
+        Some(v) => v.to_string(),
+        None => U256::from_str_radix(raw, 16).unwrap_or_default().to_string(),
+    }
+}
+
+/// Two's complement decimal of a 64 char hex word with its sign bit set.
+fn render_negative(raw: &str) -> String {
+    // Small negatives are all `f`s down to the low 128 bits.
+    let (high, low) = raw.split_at(32);
+    if high.bytes().all(|c| c == b'f' || c == b'F') && low.as_bytes()[0] >= b'8' {
+        if let Some(v) = hex_u128(low) {
+            return (v as i128).to_string();
+        }
+    }
+    let v = U256::from_str_radix(raw, 16).unwrap_or_default();
+    format!("-{}", (!v).overflowing_add(U256::one()).0)
+}
+
+impl Calldata {
+    /// Builds the decode tree: the main method with its nested calls as
+    /// children, the calls a protocol decoder claims decoded by it (see
+    /// `with_protocols`). A tree over `max_nodes` through the decoders is left
+    /// to the heuristics alone, bounded when parsing.
+    pub fn decode(&self) -> DecodedCalldata {
+        self.protocols()
+            .decode(self)
+            .unwrap_or_else(|_| self.decode_heuristics())
+    }
+
+    /// Same as `decode` with the heuristics alone, the tree the layout pass found.
+    pub(crate) fn decode_heuristics(&self) -> DecodedCalldata {
+        let empty = Params::new(&self.selector, vec![]);
+        let main = self.main_details.first().unwrap_or(&empty);
+        let mut root = DecodedCalldata::from_params(main, 0);
+        let mut nested = self
+            .nested_details
+            .iter()
+            .zip(self.nested_starts())
+            .map(|(nested, start)| DecodedCalldata::from_params(nested, start))
+            .collect::<Vec<_>>();
+
+        for (parent, warning) in self.layout().warnings() {
+            match parent.and_then(|p| nested.get_mut(p)) {
+                Some(call) => call.warnings.push(warning.clone()),
+                None => root.warnings.push(warning.clone()),
+            }
+        }
+
+        // Parents come before their children, so attach from the back.
+        while let Some(call) = nested.pop() {
+            let parent = self.nested_parent(nested.len());
+            match parent.and_then(|p| nested.get_mut(p)) {
+                Some(parent) => parent.calls.insert(0, call),
+                None => root.calls.insert(0, call),
+            }
+        }
+        guess_signatures(&mut root);
+        #[cfg(feature = "ethers")]
+        if let Some(abi) = &self.abi {
+            crate::abi::apply_abi(&mut root, &self.bytes, abi);
+        }
+        root
+    }
+
+    /// Same as `decode`, failing on the first irregularity that leaves part of
+    /// the calldata undecoded (see `DecodeError::from_warning`), for callers
+    /// that would rather reject calldata from untrusted sources than show a
+    /// partial decode. Only structure the layout pass confirmed can fail it:
+    /// a word that merely looks like an offset is read as a value.
+    pub fn try_decode(&self) -> Result<DecodedCalldata, DecodeError> {
+        let root = self.protocols().decode(self).map_err(DecodeError::OverLimit)?;
+        let error = root
+            .walk()
+            .iter()
+            .flat_map(|call| call.warnings.iter())
+            .find_map(DecodeError::from_warning);
+        match error {
+            Some(e) => Err(e),
+            None => Ok(root),
+        }
+    }
+
+    /// Byte offset of each nested call in the calldata, as recorded by the layout pass.
+    pub fn nested_starts(&self) -> Vec<usize> {
+        self.layout().calls().map(|call| call.at).collect()
+    }
+}
@@ -0,0 +1,350 @@
+use crate::cache::{Lru, DEFAULT_CAPACITY};
+#[cfg(feature = "cli")]
+use crate::config::Config;
+use crate::constants::SELECTOR;
+use crate::decoded::DecodedCalldata;
+use crate::explain::Explanation;
+use crate::heuristics::{Heuristic, HeuristicChain, Heuristics};
+use crate::limits::Limits;
+use crate::protocol::{LayoutDecoder, ProtocolRegistry};
+use crate::render::labels::{apply_labels, Labels};
+use crate::signatures::{apply_signatures, find_selectors, Layout, SelectorHit, Signatures};
+use crate::summary::summarize;
+use crate::{check_hex, decode_hex, hex_len, Calldata, DecodeError};
+#[cfg(feature = "batch")]
+use rayon::prelude::*;
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+
+// ------------------------------------------------------------
+//  Decoder
+// ------------------------------------------------------------
+
+/// Everything a decode needs, built once and shared.
+///
+/// `Decoder` is `Send + Sync`: a service can keep one behind an `Arc` and
+/// decode from every worker thread, the signature answers and the decodes
+/// themselves being cached across calls.
+pub struct Decoder {
+    heuristics: Heuristics,
+    /// Heuristics typing the words, the built-in ones unless changed.
+    chain: HeuristicChain,
+    limits: Limits,
+    signatures: Arc<Signatures>,
+    labels: Labels,
+    /// Decoders of the protocols decoded exactly.
+    protocols: ProtocolRegistry,
+    /// The protocols, then with signature types the layouts of the selectors
+    /// the signatures know, so their calls skip the heuristics' layout pass.
+    registry: ProtocolRegistry,
+    /// Whether the calls are typed as their resolved signatures.
+    typed: bool,
+    /// Labelled decodes by calldata.
+    decodes: Mutex<Lru<Vec<u8>, DecodedCalldata>>,
+}
+
+impl Default for Decoder {
+    fn default() -> Self {
+        Self {
+            heuristics: Heuristics::default(),
+            chain: HeuristicChain::builtin(),
+            limits: Limits::default(),
+            signatures: Arc::new(Signatures::builtin()),
+            labels: Labels::new(),
+            protocols: ProtocolRegistry::builtin(),
+            registry: ProtocolRegistry::builtin(),
+            typed: false,
+            decodes: Mutex::new(Lru::new(DEFAULT_CAPACITY)),
+        }
+    }
+}
+
+impl Decoder {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Heuristics, limits, signatures, labels and plugins of `config`.
+    #[cfg(feature = "cli")]
+    pub fn from_config(config: &Config) -> Result<Self, String> {
+        Ok(Self::new()
+            .with_heuristics(config.heuristics)
+            .with_limits(config.limits)
+            .with_signatures(config.signature_sources()?)
+            .with_labels(config.labels()?)
+            .with_protocols(config.protocols()?))
+    }
+
+    pub fn with_heuristics(mut self, heuristics: Heuristics) -> Self {
+        self.heuristics = heuristics;
+        self.cleared()
+    }
+
+    /// Types the words through `chain`, e.g. the built-in heuristics
+    /// reordered or some taken out.
+    pub fn with_heuristic_chain(mut self, chain: HeuristicChain) -> Self {
+        self.chain = chain;
+        self.cleared()
+    }
+
+    /// Consults `heuristic` before the heuristics already there, e.g. one
+    /// typing the token addresses of a registry.
+    pub fn with_heuristic(mut self, heuristic: impl Heuristic + 'static) -> Self {
+        self.chain = self.chain.with_first(heuristic);
+        self.cleared()
+    }
+
+    pub fn with_limits(mut self, limits: Limits) -> Self {
+        self.limits = limits;
+        self.cleared()
+    }
+
+    pub fn with_signatures(mut self, signatures: Signatures) -> Self {
+        self.signatures = Arc::new(signatures);
+        self.cleared()
+    }
+
+    /// Types each call as its first resolved signature fitting its params,
+    /// rather than by heuristics alone (see `signatures::apply_signatures`).
+    /// The calls to a selector with a known signature are then decoded by its
+    /// layout (see `LayoutDecoder::from_signatures`), where it fits them.
+    pub fn with_signature_types(mut self, typed: bool) -> Self {
+        self.typed = typed;
+        self.cleared()
+    }
+
+    pub fn with_labels(mut self, labels: Labels) -> Self {
+        self.labels = labels;
+        self.cleared()
+    }
+
+    /// Decodes the calls `protocols` claim with them, `ProtocolRegistry::builtin()` by default.
+    pub fn with_protocols(mut self, protocols: ProtocolRegistry) -> Self {
+        self.protocols = protocols;
+        self.cleared()
+    }
+
+    /// Keeps at most `capacity` decodes, least recently used evicted first.
+    pub fn with_cache_capacity(mut self, capacity: usize) -> Self {
+        self.decodes = Mutex::new(Lru::new(capacity));
+        self
+    }
+
+    /// Drops the cached decodes, made under other settings.
+    fn cleared(mut self) -> Self {
+        self.registry = match self.typed {
+            true => {
+                let layouts = LayoutDecoder::from_signatures(self.signatures.clone());
+                self.protocols.clone().with(layouts)
+            }
+            false => self.protocols.clone(),
+        };
+        if let Ok(cache) = self.decodes.get_mut() {
+            cache.clear();
+        }
+        self
+    }
+
+    pub fn heuristics(&self) -> &Heuristics {
+        &self.heuristics
+    }
+
+    pub fn heuristic_chain(&self) -> &HeuristicChain {
+        &self.chain
+    }
+
+    pub fn limits(&self) -> &Limits {
+        &self.limits
+    }
+
+    pub fn signatures(&self) -> &Signatures {
+        &self.signatures
+    }
+
+    pub fn protocols(&self) -> &ProtocolRegistry {
+        &self.protocols
+    }
+
+    pub fn signature_types(&self) -> bool {
+        self.typed
+    }
+
+    /// Decode tree of `calldata` hex, with address labels applied.
+    pub fn decode(&self, calldata: &str) -> Result<DecodedCalldata, String> {
+        check_hex(calldata)?;
+        // Checked before decoding so an oversized input is never allocated.
+        self.limits.check_input(hex_len(calldata))?;
+        self.decode_bytes(&decode_hex(calldata))
+    }
+
+    /// Same as `decode`, from raw bytes.
+    pub fn decode_bytes(&self, calldata: &[u8]) -> Result<DecodedCalldata, String> {
+        if calldata.len() < 4 {
+            return Err(format!(
+                "{} bytes is shorter than a selector",
+                calldata.len()
+            ));
+        }
+        let key = calldata.to_vec();
+        if let Some(hit) = self.decodes.lock().ok().and_then(|mut c| c.get(&key)) {
+            return Ok(hit);
+        }
+        // Decoded without holding the cache, so threads don't wait on each other.
+        let calldata = self.parse(key.clone())?;
+        let mut root = self.registry.decode(&calldata)?;
+        self.annotate(&mut root);
+        if let Ok(mut cache) = self.decodes.lock() {
+            cache.insert(key, root.clone());
+        }
+        Ok(root)
+    }
+
+    /// Decodes many calldatas at once, e.g. the inputs of a mempool's pending
+    /// transactions.
+    ///
+    /// Each distinct calldata is decoded once, repeats getting a copy of its
+    /// decode. The first calldata of each selector is decoded as `decode`
+    /// would; the layout guessed for it, its signature guess or the signature
+    /// it was typed as, is then applied to the others with that selector (see
+    /// `LayoutDecoder`), so calls sharing a selector are typed alike and their
+    /// nested calls read off that layout. With the `batch` feature the
+    /// calldatas are decoded in parallel on rayon's thread pool.
+    ///
+    /// ## Returns
+    /// 1. One result per calldata, in input order: its decode, or why it isn't
+    ///    calldata (`InvalidHex`, `TooShort`) or is over the limits (`OverLimit`).
+    pub fn decode_batch<S: AsRef<str> + Sync>(
+        &self,
+        calldatas: &[S],
+    ) -> Vec<Result<DecodedCalldata, DecodeError>> {
+        let mut unique: Vec<Vec<u8>> = vec![];
+        let mut seen: HashMap<Vec<u8>, usize> = HashMap::new();
+        let slots = calldatas
+            .iter()
+            .map(|calldata| {
+                let calldata = calldata.as_ref();
+                check_hex(calldata)?;
+                self.limits
+                    .check_input(hex_len(calldata))
+                    .map_err(DecodeError::OverLimit)?;
+                let bytes = decode_hex(calldata);
+                let next = unique.len();
+                let slot = *seen.entry(bytes.clone()).or_insert(next);
+                if slot == next {
+                    unique.push(bytes);
+                }
+                Ok(slot)
+            })
+            .collect::<Vec<Result<usize, DecodeError>>>();
+
+        // The first calldata of each selector is decoded as `decode` has it,
+        // the others through the layouts guessed for the firsts.
+        let mut selectors: HashMap<&[u8], usize> = HashMap::new();
+        let (firsts, others): (Vec<usize>, Vec<usize>) = (0..unique.len()).partition(|&i| {
+            let selector = &unique[i][..SELECTOR.min(unique[i].len())];
+            *selectors.entry(selector).or_insert(i) == i
+        });
+        let mut decodes = vec![Err(String::new()); unique.len()];
+        let mut layouts = LayoutDecoder::new();
+        let decoded = map_each(&firsts, |i| self.decode_bytes(&unique[i]));
+        for (&i, decode) in firsts.iter().zip(decoded) {
+            let selector = unique[i].get(..SELECTOR).and_then(|s| <[u8; 4]>::try_from(s).ok());
+            let layout = decode.as_ref().ok().and_then(|root| Layout::parse(&root.signature_guess));
+            if let (Some(selector), Some(layout)) = (selector, layout) {
+                layouts = layouts.with_layout(selector, layout);
+            }
+            decodes[i] = decode;
+        }
+        let protocols = self.registry.clone().with(layouts);
+        let decoded = map_each(&others, |i| self.decode_through(&unique[i], &protocols));
+        for (&i, decode) in others.iter().zip(decoded) {
+            decodes[i] = decode;
+        }
+        slots
+            .into_iter()
+            .map(|slot| match &decodes[slot?] {
+                Ok(root) => Ok(root.clone()),
+                Err(e) => Err(DecodeError::OverLimit(e.clone())),
+            })
+            .collect()
+    }
+
+    /// Same as `decode_bytes` for a target implementing only `selectors`, e.g.
+    /// a proxy's implementation (see `Calldata::with_target_selectors`). Not cached.
+    pub fn decode_for_target(
+        &self,
+        calldata: &[u8],
+        selectors: Vec<[u8; 4]>,
+    ) -> Result<DecodedCalldata, String> {
+        let calldata = self.parse(calldata.to_vec())?;
+        let calldata = calldata.with_target_selectors(selectors);
+        let mut root = self.registry.decode(&calldata)?;
+        self.annotate(&mut root);
+        Ok(root)
+    }
+
+    /// Same as `decode_bytes` through `protocols` rather than the decoder's own. Not cached.
+    fn decode_through(
+        &self,
+        calldata: &[u8],
+        protocols: &ProtocolRegistry,
+    ) -> Result<DecodedCalldata, String> {
+        let calldata = self.parse(calldata.to_vec())?;
+        let calldata = calldata.with_protocols(protocols.clone());
+        let mut root = protocols.decode(&calldata)?;
+        self.annotate(&mut root);
+        Ok(root)
+    }
+
+    /// `calldata` parsed under the decoder's settings.
+    fn parse(&self, calldata: Vec<u8>) -> Result<Calldata, String> {
+        let chain = self.chain.clone();
+        Calldata::from_bytes_with_chain(calldata, self.heuristics, self.limits, chain)
+            .map(|calldata| calldata.with_protocols(self.registry.clone()))
+    }
+
+    /// Types the calls as their signatures, if asked to, and labels the addresses.
+    fn annotate(&self, root: &mut DecodedCalldata) {
+        if self.typed {
+            apply_signatures(root, &self.signatures);
+        }
+        apply_labels(root, &self.labels);
+    }
+
+    /// Why `calldata` hex decodes as `decode` has it, heuristic by heuristic
+    /// (see `Calldata::explain`). Not cached.
+    pub fn explain(&self, calldata: &str) -> Result<Explanation, String> {
+        check_hex(calldata)?;
+        self.limits.check_input(hex_len(calldata))?;
+        Ok(self.parse(decode_hex(calldata))?.explain())
+    }
+
+    /// Signatures known for the selector.
+    pub fn resolve(&self, selector: &str) -> Vec<String> {
+        self.signatures.resolve(selector)
+    }
+
+    /// Every selector of the tree, depth-first, resolved against the signatures.
+    pub fn selectors(&self, root: &DecodedCalldata) -> Vec<SelectorHit> {
+        find_selectors(root, &self.signatures)
+    }
+
+    /// One-line summary of a decode, named with the signatures and labels.
+    pub fn summarize(&self, root: &DecodedCalldata) -> String {
+        summarize(root, &self.signatures, &self.labels)
+    }
+
+    /// `(hits, misses)` of the decode cache so far.
+    pub fn cache_stats(&self) -> (u64, u64) {
+        self.decodes.lock().map(|c| c.stats()).unwrap_or_default()
+    }
+}
+
+/// `f` of each index, in order; in parallel on rayon's thread pool with the
+/// `batch` feature.
+fn map_each<T: Send>(indices: &[usize], f: impl Fn(usize) -> T + Send + Sync) -> Vec<T> {
+    #[cfg(feature = "batch")]
+    return indices.par_iter().map(|&i| f(i)).collect();
+    #[cfg(not(feature = "batch"))]
+    indices.iter().map(|&i| f(i)).collect()
+}
@@ -0,0 +1,93 @@
+use crate::decoded::{DecodedCalldata, DecodedParam};
+
+// ------------------------------------------------------------
+//  Decode diff
+// ------------------------------------------------------------
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Change {
+    Same,
+    Changed,
+    /// Only present on the right.
+    Added,
+    /// Only present on the left.
+    Removed,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct ParamDiff {
+    pub index: usize,
+    pub left: Option<DecodedParam>,
+    pub right: Option<DecodedParam>,
+    pub change: Change,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct CallDiff {
+    pub left_selector: Option<String>,
+    pub right_selector: Option<String>,
+    pub params: Vec<ParamDiff>,
+    pub calls: Vec<CallDiff>,
+}
+
+impl CallDiff {
+    /// Whether both sides decoded to the same calls and param words.
+    pub fn is_identical(&self) -> bool {
+        self.left_selector == self.right_selector
+            && self.params.iter().all(|p| p.change == Change::Same)
+            && self.calls.iter().all(|c| c.is_identical())
+    }
+
+    /// Amount of params that differ, nested calls included.
+    pub fn changes(&self) -> usize {
+        self.params.iter().filter(|p| p.change != Change::Same).count()
+            + self.calls.iter().map(|c| c.changes()).sum::<usize>()
+    }
+}
+
+/// Compares two decodes param by param and nested call by nested call.
+pub fn diff(left: &DecodedCalldata, right: &DecodedCalldata) -> CallDiff {
+    diff_nodes(Some(left), Some(right))
+}
+
+fn diff_nodes(left: Option<&DecodedCalldata>, right: Option<&DecodedCalldata>) -> CallDiff {
+    let empty = vec![];
+    let left_params = left.map(|c| &c.params).unwrap_or(&empty);
+    let right_params = right.map(|c| &c.params).unwrap_or(&empty);
+    let params = (0..left_params.len().max(right_params.len()))
+        .map(|index| {
+            let l = left_params.get(index);
+            let r = right_params.get(index);
+            let change = match (l, r) {
+                (Some(l), Some(r)) if l.raw == r.raw => Change::Same,
+                (Some(_), Some(_)) => Change::Changed,
+                (Some(_), None) => Change::Removed,
+                _ => Change::Added,
+            };
+            ParamDiff {
+                index,
+                left: l.cloned(),
+                right: r.cloned(),
+                change,
+            }
+        })
+        .collect();
+
+    let left_calls = left.map(|c| c.calls.len()).unwrap_or(0);
+    let right_calls = right.map(|c| c.calls.len()).unwrap_or(0);
+    let calls = (0..left_calls.max(right_calls))
+        .map(|i| {
+            diff_nodes(
+                left.and_then(|c| c.calls.get(i)),
+                right.and_then(|c| c.calls.get(i)),
+            )
+        })
+        .collect();
+
+    CallDiff {
+        left_selector: left.map(|c| c.selector.clone()),
+        right_selector: right.map(|c| c.selector.clone()),
+        params,
+        calls,
+    }
+}
@@ -0,0 +1,354 @@
+use crate::decoded::{DecodedCalldata, TypeCandidate};
+use crate::Calldata;
+use ethers::abi::{Abi, Function, ParamType, Token};
+use ethers::utils::hex;
+use serde::Serialize;
+use std::collections::BTreeMap;
+use std::path::Path;
+
+// ------------------------------------------------------------
+//  Differential decoding
+// ------------------------------------------------------------
+
+/// How the heuristics guessed a word the ABI gives the type of.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub enum Verdict {
+    /// The most likely candidate fits the ABI type.
+    Top,
+    /// A lesser candidate fits it.
+    Candidate,
+    /// No candidate fits it.
+    Wrong,
+    /// The heuristics have no param at this index.
+    Missing,
+}
+
+/// A head word of the exact decode next to our guesses for it.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct WordCheck {
+    /// Index of the word in the call's params.
+    pub index: usize,
+    /// The Solidity type the ABI gives the word.
+    pub expected: String,
+    /// Our candidate types, most likely first.
+    pub guessed: Vec<String>,
+    pub verdict: Verdict,
+}
+
+/// The exact decode of a call, by its ABI, compared against the heuristic one.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Differential {
+    /// Signature of the function the ABI matched, e.g. `transfer(address,uint256)`.
+    pub signature: String,
+    pub words: Vec<WordCheck>,
+    /// `bytes` params of the exact decode shaped like a call (a selector and whole words).
+    pub expected_calls: usize,
+    /// Of those, the ones the heuristics extracted as nested calls.
+    pub found_calls: usize,
+    /// Nested calls found by both whose selector is in the ABI too.
+    pub calls: Vec<Differential>,
+}
+
+/// Word and call counts of one or more differentials.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Score {
+    pub words: usize,
+    pub top: usize,
+    /// Words with a fitting candidate, the top one included.
+    pub candidate: usize,
+    pub expected_calls: usize,
+    pub found_calls: usize,
+}
+
+impl Score {
+    fn add(&mut self, other: Score) {
+        self.words += other.words;
+        self.top += other.top;
+        self.candidate += other.candidate;
+        self.expected_calls += other.expected_calls;
+        self.found_calls += other.found_calls;
+    }
+
+    /// Ratio of words whose most likely candidate fits (1 when there are none).
+    pub fn top_accuracy(&self) -> f64 {
+        ratio(self.top, self.words)
+    }
+
+    /// Ratio of words with any fitting candidate (1 when there are none).
+    pub fn accuracy(&self) -> f64 {
+        ratio(self.candidate, self.words)
+    }
+
+    /// Ratio of call-shaped `bytes` extracted as nested calls (1 when there are none).
+    pub fn call_recall(&self) -> f64 {
+        ratio(self.found_calls, self.expected_calls)
+    }
+}
+
+fn ratio(n: usize, total: usize) -> f64 {
+    match total {
+        0 => 1.0,
+        total => n as f64 / total as f64,
+    }
+}
+
+impl Differential {
+    /// Counts of this call and the calls compared in it.
+    pub fn score(&self) -> Score {
+        let mut score = Score {
+            words: self.words.len(),
+            top: self.count(&[Verdict::Top]),
+            candidate: self.count(&[Verdict::Top, Verdict::Candidate]),
+            expected_calls: self.expected_calls,
+            found_calls: self.found_calls,
+        };
+        for call in self.calls.iter() {
+            score.add(call.score());
+        }
+        score
+    }
+
+    /// The words not guessed as the top candidate, nested calls' included.
+    pub fn disagreements(&self) -> Vec<&WordCheck> {
+        let mut out = self
+            .words
+            .iter()
+            .filter(|w| w.verdict != Verdict::Top)
+            .collect::<Vec<_>>();
+        for call in self.calls.iter() {
+            out.extend(call.disagreements());
+        }
+        out
+    }
+
+    fn count(&self, verdicts: &[Verdict]) -> usize {
+        let words = self.words.iter();
+        words.filter(|w| verdicts.contains(&w.verdict)).count()
+    }
+}
+
+/// Reads a JSON ABI, either the bare array or a build artifact with an `abi` field.
+pub fn load_abi(path: &Path) -> Result<Abi, String> {
+    let text = std::fs::read_to_string(path).map_err(|e| format!("{}: {}", path.display(), e))?;
+    let json = serde_json::from_str::<serde_json::Value>(&text)
+        .map_err(|e| format!("{}: {}", path.display(), e))?;
+    let abi = match json.get("abi") {
+        Some(abi) => abi.clone(),
+        None => json,
+    };
+    serde_json::from_value(abi).map_err(|e| format!("{}: {}", path.display(), e))
+}
+
+/// Decodes the calldata by the ABI and compares it with the heuristic decode.
+///
+/// ## Returns
+/// 1. The comparison, or an error when the ABI has no function for the
+///    selector or the calldata doesn't decode as its inputs.
+pub fn differential(calldata: &Calldata, abi: &Abi) -> Result<Differential, String> {
+    let bytes = calldata.bytes();
+    compare(bytes, bytes, &calldata.decode(), abi)
+}
+
+/// Compares one call, then the nested calls both decodes found.
+///
+/// ## Params
+/// 1. calldata - the whole calldata, which the spans of `ours` are in.
+/// 2. bytes - the call's selector and params.
+fn compare(
+    calldata: &[u8],
+    bytes: &[u8],
+    ours: &DecodedCalldata,
+    abi: &Abi,
+) -> Result<Differential, String> {
+    let function = find_function(abi, bytes).ok_or_else(|| {
+        let selector = bytes.get(..4).map(hex::encode).unwrap_or_default();
+        format!("no function with selector 0x{} in the ABI", selector)
+    })?;
+    let tokens = function
+        .decode_input(&bytes[4..])
+        .map_err(|e| format!("{}: {}", signature(function), e))?;
+
+    let words = function
+        .inputs
+        .iter()
+        .flat_map(|input| head_types(&input.kind))
+        .enumerate()
+        .map(|(index, expected)| check_word(index, expected, ours))
+        .collect();
+
+    let mut expected = vec![];
+    for token in tokens.iter() {
+        call_bytes(token, &mut expected);
+    }
+    let mut found_calls = 0;
+    let mut calls = vec![];
+    for call in expected.iter() {
+        let Some(node) = ours
+            .calls
+            .iter()
+            .find(|c| calldata.get(c.span.start..c.span.end) == Some(&call[..]))
+        else {
+            continue;
+        };
+        found_calls += 1;
+        if find_function(abi, call).is_some() {
+            calls.push(compare(calldata, call, node, abi)?);
+        }
+    }
+
+    Ok(Differential {
+        signature: signature(function),
+        words,
+        expected_calls: expected.len(),
+        found_calls,
+        calls,
+    })
+}
+
+fn check_word(index: usize, expected: String, ours: &DecodedCalldata) -> WordCheck {
+    let candidates = ours.params.get(index).map(|p| p.types.as_slice());
+    let fits = |c: &TypeCandidate| c.kind.matches_solidity(&expected);
+    let verdict = match candidates {
+        None => Verdict::Missing,
+        Some(c) if c.first().is_some_and(fits) => Verdict::Top,
+        Some(c) if c.iter().any(fits) => Verdict::Candidate,
+        Some(_) => Verdict::Wrong,
+    };
+    WordCheck {
+        index,
+        guessed: candidates
+            .unwrap_or_default()
+            .iter()
+            .map(|c| c.solidity.clone())
+            .collect(),
+        expected,
+        verdict,
+    }
+}
+
+pub(crate) fn find_function<'a>(abi: &'a Abi, bytes: &[u8]) -> Option<&'a Function> {
+    let selector = bytes.get(..4)?;
+    abi.functions().find(|f| f.short_signature() == selector)
+}
+
+pub(crate) fn signature(function: &Function) -> String {
+    let inputs = function.inputs.iter().map(|p| p.kind.to_string());
+    format!(
+        "{}({})",
+        function.name,
+        inputs.collect::<Vec<_>>().join(",")
+    )
+}
+
+/// Solidity type of each head word of a type: static arrays and tuples are
+/// inlined, dynamic types are an offset.
+fn head_types(kind: &ParamType) -> Vec<String> {
+    match kind {
+        _ if kind.is_dynamic() => vec![kind.to_string()],
+        ParamType::FixedArray(inner, len) => (0..*len).flat_map(|_| head_types(inner)).collect(),
+        ParamType::Tuple(inner) => inner.iter().flat_map(head_types).collect(),
+        _ => vec![kind.to_string()],
+    }
+}
+
+/// Collects the `bytes` shaped like a call: a selector followed by whole words.
+fn call_bytes(token: &Token, out: &mut Vec<Vec<u8>>) {
+    match token {
+        Token::Bytes(bytes) if bytes.len() >= 4 && bytes.len() % 32 == 4 => out.push(bytes.clone()),
+        Token::Array(tokens) | Token::FixedArray(tokens) | Token::Tuple(tokens) => {
+            for token in tokens.iter() {
+                call_bytes(token, out);
+            }
+        }
+        _ => {}
+    }
+}
+
+// ------------------------------------------------------------
+//  Accuracy report
+// ------------------------------------------------------------
+
+/// Scores of many differentials, overall and by ABI type.
+#[derive(Debug, Clone, Default, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AccuracyReport {
+    /// Calldatas compared.
+    pub compared: usize,
+    /// Calldatas the ABI couldn't decode, with the reason.
+    pub skipped: Vec<String>,
+    pub total: Score,
+    /// Word counts by the type the ABI gives them.
+    pub by_type: BTreeMap<String, Score>,
+}
+
+impl AccuracyReport {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Adds the comparison of one calldata.
+    pub fn add(&mut self, differential: &Differential) {
+        self.compared += 1;
+        self.total.add(differential.score());
+        self.add_words(differential);
+    }
+
+    /// Records a calldata the ABI couldn't decode.
+    pub fn skip(&mut self, reason: String) {
+        self.skipped.push(reason);
+    }
+
+    fn add_words(&mut self, differential: &Differential) {
+        for word in differential.words.iter() {
+            let score = self.by_type.entry(word.expected.clone()).or_default();
+            score.words += 1;
+            if word.verdict == Verdict::Top {
+                score.top += 1;
+            }
+            if matches!(word.verdict, Verdict::Top | Verdict::Candidate) {
+                score.candidate += 1;
+            }
+        }
+        for call in differential.calls.iter() {
+            self.add_words(call);
+        }
+    }
+}
+
+impl std::fmt::Display for AccuracyReport {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        writeln!(
+            f,
+            "{} compared, {} skipped",
+            self.compared,
+            self.skipped.len()
+        )?;
+        writeln!(
+            f,
+            "words: {:.1}% top, {:.1}% any candidate of {}",
+            self.total.top_accuracy() * 100.0,
+            self.total.accuracy() * 100.0,
+            self.total.words
+        )?;
+        writeln!(
+            f,
+            "nested calls: {} of {} found",
+            self.total.found_calls, self.total.expected_calls
+        )?;
+        for (ty, score) in self.by_type.iter() {
+            writeln!(
+                f,
+                "  {:<24} {:>6.1}% top {:>6.1}% any  ({})",
+                ty,
+                score.top_accuracy() * 100.0,
+                score.accuracy() * 100.0,
+                score.words
+            )?;
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,125 @@
+use crate::decoded::{DecodeWarning, DecodedCalldata, DecodedParam, TypeCandidate};
+use serde::{Deserialize, Serialize};
+
+// ------------------------------------------------------------
+//  Plain-data decode tree
+// ------------------------------------------------------------
+//
+// Owned primitives and strings only, so a decode can be stored in another
+// service's schema without its types changing with this crate's.
+
+/// A [`TypeCandidate`] with its heuristic type as a string.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TypeCandidateDto {
+    /// Heuristic type that was guessed, e.g. `Address` or `AnyZero`.
+    pub kind: String,
+    /// Solidity type the guess maps to.
+    pub solidity: String,
+    pub confidence: f64,
+}
+
+/// A [`DecodedParam`] with its span and text flattened.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DecodedParamDto {
+    pub index: u64,
+    /// 32-byte word as hex (64 chars, no prefix).
+    pub raw: String,
+    pub value: String,
+    /// Candidates ordered from most to least likely.
+    pub types: Vec<TypeCandidateDto>,
+    pub span_start: u64,
+    pub span_end: u64,
+    /// The escaped string this word is the length of, when text follows it.
+    pub text: Option<String>,
+    /// The text wasn't valid UTF-8.
+    pub text_lossy: bool,
+}
+
+/// A [`DecodeWarning`] as its kind, offset and message.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DecodeWarningDto {
+    /// The warning's `kind` tag in the JSON output, e.g. `lengthOutOfBounds`.
+    pub kind: String,
+    /// Byte offset in the calldata the warning is about.
+    pub at: u64,
+    pub message: String,
+}
+
+/// A [`DecodedCalldata`] and its nested calls as plain data.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DecodedCalldataDto {
+    /// 4-byte method selector (8 chars, no prefix).
+    pub selector: String,
+    pub signature_guess: String,
+    pub params: Vec<DecodedParamDto>,
+    pub calls: Vec<DecodedCalldataDto>,
+    pub span_start: u64,
+    pub span_end: u64,
+    pub warnings: Vec<DecodeWarningDto>,
+}
+
+impl From<&TypeCandidate> for TypeCandidateDto {
+    fn from(candidate: &TypeCandidate) -> Self {
+        Self {
+            kind: format!("{:?}", candidate.kind),
+            solidity: candidate.solidity.clone(),
+            confidence: candidate.confidence,
+        }
+    }
+}
+
+impl From<&DecodedParam> for DecodedParamDto {
+    fn from(param: &DecodedParam) -> Self {
+        Self {
+            index: param.index as u64,
+            raw: param.raw.clone(),
+            value: param.value.clone(),
+            types: param.types.iter().map(TypeCandidateDto::from).collect(),
+            span_start: param.span.start as u64,
+            span_end: param.span.end as u64,
+            text: param.text.as_ref().map(|t| t.text.clone()),
+            text_lossy: param.text.as_ref().is_some_and(|t| t.lossy),
+        }
+    }
+}
+
+impl From<&DecodeWarning> for DecodeWarningDto {
+    fn from(warning: &DecodeWarning) -> Self {
+        let kind = match warning {
+            DecodeWarning::LengthOutOfBounds { .. } => "lengthOutOfBounds",
+            DecodeWarning::OffsetOutOfBounds { .. } => "offsetOutOfBounds",
+            DecodeWarning::NonCanonicalOffset { .. } => "nonCanonicalOffset",
+            DecodeWarning::UnparsedTail { .. } => "unparsedTail",
+            DecodeWarning::PaddingMismatch { .. } => "paddingMismatch",
+        };
+        Self {
+            kind: kind.to_string(),
+            at: warning.at() as u64,
+            message: warning.to_string(),
+        }
+    }
+}
+
+impl From<&DecodedCalldata> for DecodedCalldataDto {
+    fn from(call: &DecodedCalldata) -> Self {
+        Self {
+            selector: call.selector.clone(),
+            signature_guess: call.signature_guess.clone(),
+            params: call.params.iter().map(DecodedParamDto::from).collect(),
+            calls: call.calls.iter().map(DecodedCalldataDto::from).collect(),
+            span_start: call.span.start as u64,
+            span_end: call.span.end as u64,
+            warnings: call.warnings.iter().map(DecodeWarningDto::from).collect(),
+        }
+    }
+}
+
+impl From<DecodedCalldata> for DecodedCalldataDto {
+    fn from(call: DecodedCalldata) -> Self {
+        Self::from(&call)
+    }
+}
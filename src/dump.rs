@@ -0,0 +1,289 @@
+use crate::batch::Record;
+use crate::decoded::DecodedCalldata;
+use crate::heuristics::Heuristics;
+use crate::limits::Limits;
+use rayon::prelude::*;
+use std::path::Path;
+
+// ------------------------------------------------------------
+//  Transaction dumps
+// ------------------------------------------------------------
+
+/// Accepted names of the `hash`, `to` and `input` columns, covering the
+/// exports of the common warehouses and indexers.
+const HASH_COLUMNS: [&str; 3] = ["hash", "transaction_hash", "tx_hash"];
+const TO_COLUMNS: [&str; 2] = ["to", "to_address"];
+const INPUT_COLUMNS: [&str; 3] = ["input", "data", "calldata"];
+
+/// How a dump is stored.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DumpFormat {
+    /// Comma separated, with a header row.
+    Csv,
+    /// Read with the `parquet` feature.
+    Parquet,
+}
+
+impl DumpFormat {
+    /// The format of `path`, from its extension.
+    pub fn from_path(path: &Path) -> Result<Self, String> {
+        let extension = path
+            .extension()
+            .and_then(|e| e.to_str())
+            .unwrap_or_default();
+        match extension.to_lowercase().as_str() {
+            "csv" => Ok(DumpFormat::Csv),
+            "parquet" | "pq" => Ok(DumpFormat::Parquet),
+            _ => Err(format!(
+                "{}: unknown dump format, expected a .csv or .parquet file",
+                path.display()
+            )),
+        }
+    }
+}
+
+/// A transaction of a dump.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DumpRow {
+    pub hash: String,
+    /// `None` for contract creations.
+    pub to: Option<String>,
+    /// The calldata as hex.
+    pub input: String,
+}
+
+/// The transactions of a data-warehouse export, with a `hash`, `to` and
+/// `input` column, to be decoded in parallel.
+///
+/// Columns are matched by name, other columns being ignored; `transaction_hash`,
+/// `to_address` and `data` are read as well. Transactions without calldata,
+/// plain transfers, are left out.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Dump {
+    rows: Vec<DumpRow>,
+    heuristics: Heuristics,
+    limits: Limits,
+}
+
+impl Dump {
+    pub fn new(rows: Vec<DumpRow>) -> Self {
+        Self {
+            rows: rows
+                .into_iter()
+                .filter(|r| has_calldata(&r.input))
+                .collect(),
+            heuristics: Heuristics::default(),
+            limits: Limits::default(),
+        }
+    }
+
+    /// Reads the dump at `path` in `format`.
+    pub fn open(path: &Path, format: DumpFormat) -> Result<Self, String> {
+        let error = |e: String| format!("{}: {}", path.display(), e);
+        let rows = match format {
+            DumpFormat::Csv => {
+                let text = std::fs::read_to_string(path).map_err(|e| error(e.to_string()))?;
+                read_csv(&text).map_err(error)?
+            }
+            DumpFormat::Parquet => {
+                let file = std::fs::File::open(path).map_err(|e| error(e.to_string()))?;
+                read_parquet(file).map_err(error)?
+            }
+        };
+        Ok(Self::new(rows))
+    }
+
+    /// Decodes the calldatas applying only the enabled `heuristics`.
+    pub fn with_heuristics(mut self, heuristics: Heuristics) -> Self {
+        self.heuristics = heuristics;
+        self
+    }
+
+    /// Decodes the calldatas within `limits`.
+    pub fn with_limits(mut self, limits: Limits) -> Self {
+        self.limits = limits;
+        self
+    }
+
+    /// Only keeps the transactions sent to `to`, compared case insensitively.
+    pub fn with_to(mut self, to: &str) -> Self {
+        self.rows
+            .retain(|r| r.to.as_deref().is_some_and(|t| t.eq_ignore_ascii_case(to)));
+        self
+    }
+
+    pub fn rows(&self) -> &[DumpRow] {
+        &self.rows
+    }
+
+    /// Decodes every transaction in parallel, `window` at a time.
+    ///
+    /// ## Params
+    /// 1. window - how many transactions are decoded in parallel before handing them on.
+    /// 2. sink - called with each transaction and its decode, in dump order.
+    pub fn decode<F>(&self, window: usize, mut sink: F)
+    where
+        F: FnMut(&DumpRow, Result<DecodedCalldata, String>),
+    {
+        for chunk in self.rows.chunks(window.max(1)) {
+            let results = chunk
+                .par_iter()
+                .map(|row| Record::Hex(&row.input).decode(self.heuristics, self.limits))
+                .collect::<Vec<_>>();
+            for (row, result) in chunk.iter().zip(results) {
+                sink(row, result);
+            }
+        }
+    }
+}
+
+fn has_calldata(input: &str) -> bool {
+    !matches!(input.trim(), "" | "0x" | "0X")
+}
+
+/// Index of the first of `names` in `header`.
+fn position(header: &[String], names: &[&str]) -> Option<usize> {
+    names
+        .iter()
+        .find_map(|name| header.iter().position(|h| h == name))
+}
+
+/// Reads the transactions of a CSV dump, its first row being the header.
+pub fn read_csv(text: &str) -> Result<Vec<DumpRow>, String> {
+    let mut lines = text
+        .lines()
+        .enumerate()
+        .filter(|(_, l)| !l.trim().is_empty());
+    let (_, header) = lines.next().ok_or("empty dump")?;
+    let header = split_csv(header)
+        .iter()
+        .map(|h| h.trim().to_lowercase())
+        .collect::<Vec<_>>();
+    let hash = position(&header, &HASH_COLUMNS).ok_or("no `hash` column")?;
+    let input = position(&header, &INPUT_COLUMNS).ok_or("no `input` column")?;
+    let to = position(&header, &TO_COLUMNS);
+    lines
+        .map(|(i, line)| {
+            let fields = split_csv(line);
+            let field = |index: usize| {
+                fields
+                    .get(index)
+                    .map(|f| f.trim().to_string())
+                    .ok_or_else(|| {
+                        format!(
+                            "line {}: {} fields, expected {}",
+                            i + 1,
+                            fields.len(),
+                            header.len()
+                        )
+                    })
+            };
+            Ok(DumpRow {
+                hash: field(hash)?,
+                to: to.map(field).transpose()?.filter(|t| !t.is_empty()),
+                input: field(input)?,
+            })
+        })
+        .collect()
+}
+
+/// The fields of a CSV line, `"` quoted ones unquoted.
+fn split_csv(line: &str) -> Vec<String> {
+    let mut fields = vec![];
+    let mut field = String::new();
+    let mut quoted = false;
+    let mut chars = line.chars().peekable();
+    while let Some(c) = chars.next() {
+        match (c, quoted) {
+            ('"', true) if chars.peek() == Some(&'"') => {
+                field.push('"');
+                chars.next();
+            }
+            ('"', _) => quoted = !quoted,
+            (',', false) => fields.push(std::mem::take(&mut field)),
+            (c, _) => field.push(c),
+        }
+    }
+    fields.push(field);
+    fields
+}
+
+/// Reads the transactions of a Parquet dump. Columns are strings, or binary
+/// (e.g. cryo's exports) read as `0x` prefixed hex.
+#[cfg(feature = "parquet")]
+pub fn read_parquet(file: std::fs::File) -> Result<Vec<DumpRow>, String> {
+    use parquet::arrow::arrow_reader::ParquetRecordBatchReaderBuilder;
+
+    let reader = ParquetRecordBatchReaderBuilder::try_new(file)
+        .and_then(|b| b.build())
+        .map_err(|e| e.to_string())?;
+    let mut rows = vec![];
+    for batch in reader {
+        let batch = batch.map_err(|e| e.to_string())?;
+        let hashes = column(&batch, &HASH_COLUMNS)?.ok_or("no `hash` column")?;
+        let inputs = column(&batch, &INPUT_COLUMNS)?.ok_or("no `input` column")?;
+        let tos = column(&batch, &TO_COLUMNS)?;
+        for (i, (hash, input)) in hashes.into_iter().zip(inputs).enumerate() {
+            rows.push(DumpRow {
+                hash: hash.unwrap_or_default(),
+                to: tos.as_ref().and_then(|t| t[i].clone()),
+                input: input.unwrap_or_default(),
+            });
+        }
+    }
+    Ok(rows)
+}
+
+#[cfg(not(feature = "parquet"))]
+pub fn read_parquet(_file: std::fs::File) -> Result<Vec<DumpRow>, String> {
+    Err("reading Parquet needs the `parquet` feature".to_string())
+}
+
+/// The values of the first of `names` in `batch`, nulls as `None`.
+#[cfg(feature = "parquet")]
+fn column(
+    batch: &arrow_array::RecordBatch,
+    names: &[&str],
+) -> Result<Option<Vec<Option<String>>>, String> {
+    use arrow_array::cast::AsArray;
+    use arrow_schema::DataType;
+
+    let Some(array) = names.iter().find_map(|n| batch.column_by_name(n)) else {
+        return Ok(None);
+    };
+    let hex = |bytes: &[u8]| format!("0x{}", faster_hex::hex_string(bytes));
+    let values = match array.data_type() {
+        DataType::Utf8 => array
+            .as_string::<i32>()
+            .iter()
+            .map(|v| v.map(str::to_string))
+            .collect(),
+        DataType::LargeUtf8 => array
+            .as_string::<i64>()
+            .iter()
+            .map(|v| v.map(str::to_string))
+            .collect(),
+        DataType::Binary => array
+            .as_binary::<i32>()
+            .iter()
+            .map(|v| v.map(hex))
+            .collect(),
+        DataType::LargeBinary => array
+            .as_binary::<i64>()
+            .iter()
+            .map(|v| v.map(hex))
+            .collect(),
+        DataType::FixedSizeBinary(_) => array
+            .as_fixed_size_binary()
+            .iter()
+            .map(|v| v.map(hex))
+            .collect(),
+        other => {
+            return Err(format!(
+                "column `{}` is {}, expected strings or binary",
+                names[0], other
+            ))
+        }
+    };
+    Ok(Some(values))
+}
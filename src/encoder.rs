@@ -0,0 +1,90 @@
+use ethers::abi::token::{LenientTokenizer, Tokenizer};
+use ethers::abi::{Function, HumanReadableParser, ParamType, Token};
+use ethers::types::U256;
+
+// ------------------------------------------------------------
+//  Encoder
+// ------------------------------------------------------------
+
+/// Encodes a call from its signature and string values, returning `0x` prefixed calldata.
+///
+/// ## Params
+/// 1. signature - e.g. `transfer(address,uint256)`, a leading `function ` is optional.
+/// 2. values - one per input; numbers accept decimals, `0x` hex, `1e18` and `1.5 ether`.
+///
+/// ## Returns
+/// 1. The calldata hex, or an error naming the value that could not be parsed.
+pub fn encode_call(signature: &str, values: &[&str]) -> Result<String, String> {
+    let function = parse_function(signature)?;
+    if function.inputs.len() != values.len() {
+        return Err(format!(
+            "{} expects {} values, got {}",
+            function.name,
+            function.inputs.len(),
+            values.len()
+        ));
+    }
+
+    let tokens = function
+        .inputs
+        .iter()
+        .zip(values.iter())
+        .map(|(input, value)| parse_value(&input.kind, value))
+        .collect::<Result<Vec<Token>, String>>()?;
+    let data = function.encode_input(&tokens).map_err(|e| e.to_string())?;
+    Ok(format!("0x{}", ethers::utils::hex::encode(data)))
+}
+
+/// Parses a signature such as `transfer(address,uint256)`, a leading `function ` optional.
+pub(crate) fn parse_function(signature: &str) -> Result<Function, String> {
+    let signature = signature.trim();
+    let signature = match signature.starts_with("function ") {
+        true => signature.to_string(),
+        false => format!("function {}", signature),
+    };
+    HumanReadableParser::parse_function(&signature).map_err(|e| e.to_string())
+}
+
+/// Parses a string into a token of the given ABI type.
+pub fn parse_value(kind: &ParamType, value: &str) -> Result<Token, String> {
+    let value = value.trim();
+    let value = match kind {
+        ParamType::Uint(_) | ParamType::Int(_) => expand_scientific(value)?,
+        _ => value.to_string(),
+    };
+    LenientTokenizer::tokenize(kind, &value)
+        .map_err(|e| format!("invalid {} `{}`: {}", kind, value, e))
+}
+
+/// `1e18` / `-2.5e3` / `0xff` to their plain decimal form, other inputs are returned as-is.
+pub fn expand_scientific(value: &str) -> Result<String, String> {
+    if let Some(hex) = value.strip_prefix("0x") {
+        let v = U256::from_str_radix(hex, 16)
+            .map_err(|e| format!("invalid number `{}`: {}", value, e))?;
+        return Ok(v.to_string());
+    }
+    let Some((mantissa, exponent)) = value
+        .to_lowercase()
+        .split_once('e')
+        .map(|(m, e)| (m.to_string(), e.to_string()))
+    else {
+        return Ok(value.to_string());
+    };
+    // Unit suffixes like `ether` contain an `e` too.
+    if exponent.parse::<u32>().is_err() {
+        return Ok(value.to_string());
+    }
+    let exponent = exponent.parse::<usize>().map_err(|e| e.to_string())?;
+    let (sign, mantissa) = match mantissa.strip_prefix('-') {
+        Some(m) => ("-", m.to_string()),
+        None => ("", mantissa),
+    };
+    let (int, frac) = mantissa.split_once('.').unwrap_or((&mantissa, ""));
+    if frac.len() > exponent {
+        return Err(format!("`{}` is not an integer", value));
+    }
+    let digits = format!("{}{}{}", int, frac, "0".repeat(exponent - frac.len()));
+    let v =
+        U256::from_dec_str(&digits).map_err(|e| format!("invalid number `{}`: {}", value, e))?;
+    Ok(format!("{}{}", sign, v))
+}
@@ -0,0 +1,265 @@
+use crate::constants::*;
+use crate::decoded::{candidates, TypeCandidate};
+use crate::heuristics::WordContext;
+use crate::layout::LayoutTable;
+use crate::stride::{apply_votes, column_votes};
+use crate::{guess_tail_traced, Calldata};
+use serde::Serialize;
+
+// ------------------------------------------------------------
+//  Explanations
+// ------------------------------------------------------------
+
+/// What became of a heuristic check.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub enum Outcome {
+    /// The pattern matched and decided the outcome.
+    Fired,
+    /// The pattern didn't match, or the decision went against it.
+    Rejected,
+    /// The pattern wasn't checked, its heuristic being disabled.
+    Disabled,
+}
+
+impl Outcome {
+    pub fn name(&self) -> &'static str {
+        match self {
+            Outcome::Fired => "fired",
+            Outcome::Rejected => "rejected",
+            Outcome::Disabled => "disabled",
+        }
+    }
+}
+
+/// One check made while typing a word, in the order the guesser makes them.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Check {
+    /// The heuristic (see `HeuristicChain::names`) or the rule (`tail`,
+    /// `fallback`, `strides`) checked.
+    pub rule: &'static str,
+    pub outcome: Outcome,
+    /// What the word looked like to the rule.
+    pub reason: String,
+}
+
+impl Check {
+    /// The check of `rule`, `matched` saying whether the word has its pattern.
+    pub fn new(rule: &'static str, enabled: bool, matched: bool, reason: String) -> Self {
+        let outcome = match (enabled, matched) {
+            (false, _) => Outcome::Disabled,
+            (true, true) => Outcome::Fired,
+            (true, false) => Outcome::Rejected,
+        };
+        Self {
+            rule,
+            outcome,
+            reason,
+        }
+    }
+}
+
+/// How a word of the calldata was typed.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ParamTrace {
+    /// Position of the word in the method's body.
+    pub index: usize,
+    pub raw: String,
+    pub checks: Vec<Check>,
+    /// The types the checks led to, as in the decode.
+    pub types: Vec<TypeCandidate>,
+}
+
+/// How the params of a call were typed.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CallTrace {
+    /// Path of the call in the decode tree: `""` for the top-level call,
+    /// `calls[0]`, `calls[0].calls[1]`, ...
+    pub path: String,
+    /// Byte offset of the selector in the calldata.
+    pub at: usize,
+    /// 4-byte method selector (8 chars, no prefix).
+    pub selector: String,
+    pub params: Vec<ParamTrace>,
+}
+
+/// What a word of the layout pass was taken, or not taken, as.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub enum Role {
+    /// A zero word re-aligning the words after it.
+    Padding,
+    /// The offset of a dynamic param.
+    Offset,
+    /// The start of a nested call, after its length.
+    Call,
+}
+
+impl Role {
+    pub fn name(&self) -> &'static str {
+        match self {
+            Role::Padding => "padding",
+            Role::Offset => "offset",
+            Role::Call => "call",
+        }
+    }
+}
+
+/// A decision of the layout pass on a word.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct LayoutDecision {
+    /// Byte offset of the word in the calldata, selector included.
+    pub at: usize,
+    /// Index, among the nested calls, of the call the word is in; `None` for
+    /// the top-level call.
+    pub parent: Option<usize>,
+    pub role: Role,
+    pub outcome: Outcome,
+    pub reason: String,
+}
+
+/// Why a calldata decoded the way it did: what the layout pass took each
+/// word for, and how each param was typed, alternatives included.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Explanation {
+    /// In the order the words were looked at, nested calls after their parent.
+    pub layout: Vec<LayoutDecision>,
+    /// The top-level call then the nested calls, parents first.
+    pub calls: Vec<CallTrace>,
+}
+
+impl Explanation {
+    /// Path of the call a layout decision was made in, `""` for the top-level call.
+    pub fn path_of(&self, decision: &LayoutDecision) -> &str {
+        decision
+            .parent
+            .and_then(|p| self.calls.get(p + 1))
+            .map_or("", |call| call.path.as_str())
+    }
+}
+
+/// Collects trace entries while explaining, and does nothing otherwise.
+///
+/// Entries are built by a closure, so decoding without a trace doesn't pay for
+/// formatting the reasons.
+pub(crate) struct Recorder<'a, T>(Option<&'a mut Vec<T>>);
+
+impl<'a, T> Recorder<'a, T> {
+    pub(crate) fn off() -> Self {
+        Self(None)
+    }
+
+    pub(crate) fn on(entries: &'a mut Vec<T>) -> Self {
+        Self(Some(entries))
+    }
+
+    pub(crate) fn record(&mut self, entry: impl FnOnce() -> T) {
+        if let Some(entries) = self.0.as_deref_mut() {
+            entries.push(entry());
+        }
+    }
+
+    /// Same as `record`, for entries that may not be worth keeping.
+    pub(crate) fn record_some(&mut self, entry: impl FnOnce() -> Option<T>) {
+        if let Some(entries) = self.0.as_deref_mut() {
+            entries.extend(entry());
+        }
+    }
+}
+
+impl Calldata {
+    /// Runs the heuristics again, recording each of their decisions.
+    ///
+    /// The decode isn't changed: the trace describes `decode()`'s tree.
+    pub fn explain(&self) -> Explanation {
+        let mut layout = vec![];
+        let (body, first) = (self.raw_params.as_bytes(), self.raw_params.first);
+        LayoutTable::build_traced(
+            body,
+            first,
+            &self.heuristics,
+            &self.limits,
+            self.targets.as_deref(),
+            &mut Recorder::on(&mut layout),
+        );
+
+        let selector = &self.bytes[..SELECTOR.min(self.bytes.len())];
+        let mut calls = vec![self.explain_call(String::new(), 0, selector, self.raw_params.iter())];
+        // Children of each call so far, to number the next one.
+        let mut children = vec![0; self.layout.calls().count() + 1];
+        for call in self.layout.calls() {
+            let parent = call.parent.map_or(0, |p| p + 1);
+            let path = match calls[parent].path.as_str() {
+                "" => format!("calls[{}]", children[parent]),
+                prefix => format!("{}.calls[{}]", prefix, children[parent]),
+            };
+            children[parent] += 1;
+            calls.push(self.explain_call(path, call.at, call.selector, call.body.chunks(WORD)));
+        }
+        Explanation { layout, calls }
+    }
+
+    /// Types the words of a call as `word_details` does, with their checks.
+    fn explain_call<'a>(
+        &self,
+        path: String,
+        at: usize,
+        selector: &[u8],
+        words: impl Iterator<Item = &'a [u8]>,
+    ) -> CallTrace {
+        let words = words.collect::<Vec<_>>();
+        let mut checks = vec![vec![]; words.len()];
+        let mut types = words
+            .iter()
+            .zip(checks.iter_mut())
+            .enumerate()
+            .map(|(index, (word, checks))| {
+                let mut trace = Recorder::on(checks);
+                let heuristics = &self.heuristics;
+                let cx = WordContext {
+                    selector,
+                    index,
+                    heuristics,
+                };
+                match index + 1 == words.len() {
+                    true => guess_tail_traced(word, &self.chain, &cx, &mut trace),
+                    false => self.chain.guess_traced(word, &cx, &mut trace),
+                }
+            })
+            .collect::<Vec<_>>();
+
+        // Struct array fields voted on, after every word was typed alone.
+        let votes = column_votes(&words, &types);
+        for vote in votes.iter() {
+            let strides = self.heuristics.strides;
+            checks[vote.index].push(Check::new("strides", strides, true, vote.reason.clone()));
+        }
+        if self.heuristics.strides {
+            apply_votes(&mut types, &votes);
+        }
+
+        let params = words
+            .iter()
+            .zip(checks)
+            .zip(types.iter())
+            .enumerate()
+            .map(|(index, ((word, checks), types))| ParamTrace {
+                index,
+                raw: faster_hex::hex_string(word),
+                checks,
+                types: candidates(types, &faster_hex::hex_string(word)),
+            })
+            .collect();
+        CallTrace {
+            path,
+            at,
+            selector: faster_hex::hex_string(selector),
+            params,
+        }
+    }
+}
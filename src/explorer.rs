@@ -0,0 +1,267 @@
+use crate::decoded::{DecodedCalldata, Span};
+use crate::Calldata;
+use ratatui::crossterm::event::KeyCode;
+use ratatui::layout::{Constraint, Direction, Layout};
+use ratatui::style::{Color, Modifier, Style};
+use ratatui::text::{Line, Span as Text};
+use ratatui::widgets::{Block, Borders, List, ListItem, ListState, Paragraph};
+use ratatui::Frame;
+use std::collections::BTreeSet;
+
+// ------------------------------------------------------------
+//  Explorer
+// ------------------------------------------------------------
+
+pub const KEYS: &str = "↑/↓ move  ←/→ collapse/expand  enter toggle  c copy  q quit";
+
+/// A visible line of the call tree.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Row {
+    pub depth: usize,
+    /// Indices of the nested calls leading to the row's call, empty for the root.
+    pub path: Vec<usize>,
+    /// Param index, `None` for the call line itself.
+    pub param: Option<usize>,
+    pub text: String,
+    /// What `c` copies.
+    pub value: String,
+    pub span: Span,
+}
+
+/// What the caller should do after a key press.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Action {
+    None,
+    /// Put this text on the clipboard.
+    Copy(String),
+    Quit,
+}
+
+/// State of the terminal explorer: the decode tree, which calls are collapsed and the cursor.
+pub struct Explorer {
+    /// Calldata hex, no prefix.
+    hex: String,
+    root: DecodedCalldata,
+    collapsed: BTreeSet<Vec<usize>>,
+    selected: usize,
+    status: String,
+}
+
+impl Explorer {
+    pub fn new(calldata: &Calldata) -> Self {
+        Self {
+            hex: calldata.calldata.clone(),
+            root: calldata.decode(),
+            collapsed: BTreeSet::new(),
+            selected: 0,
+            status: KEYS.to_string(),
+        }
+    }
+
+    /// The tree lines currently visible, collapsed calls hiding their children.
+    pub fn rows(&self) -> Vec<Row> {
+        let mut rows = vec![];
+        self.push_rows(&mut rows, &self.root, vec![]);
+        rows
+    }
+
+    fn push_rows(&self, rows: &mut Vec<Row>, call: &DecodedCalldata, path: Vec<usize>) {
+        let collapsed = self.collapsed.contains(&path);
+        let has_children = !call.params.is_empty() || !call.calls.is_empty();
+        let marker = match (has_children, collapsed) {
+            (false, _) => "  ",
+            (true, true) => "▸ ",
+            (true, false) => "▾ ",
+        };
+        rows.push(Row {
+            depth: path.len(),
+            path: path.clone(),
+            param: None,
+            text: format!("{}0x{} {}", marker, call.selector, call.signature_guess),
+            value: format!("0x{}", call.selector),
+            span: call.span,
+        });
+        if collapsed {
+            return;
+        }
+        for param in call.params.iter() {
+            let ty = param
+                .types
+                .first()
+                .map(|t| t.solidity.as_str())
+                .unwrap_or("bytes32");
+            rows.push(Row {
+                depth: path.len() + 1,
+                path: path.clone(),
+                param: Some(param.index),
+                text: format!("[{}] {}: {}", param.index, ty, param.value),
+                value: param.value.clone(),
+                span: param.span,
+            });
+        }
+        for (i, nested) in call.calls.iter().enumerate() {
+            let mut nested_path = path.clone();
+            nested_path.push(i);
+            self.push_rows(rows, nested, nested_path);
+        }
+    }
+
+    /// The row under the cursor.
+    pub fn selected(&self) -> Option<Row> {
+        self.rows().get(self.selected).cloned()
+    }
+
+    /// Collapses or expands the call of the selected row.
+    ///
+    /// ## Params
+    /// 1. expand - `Some(true)` expands, `Some(false)` collapses, `None` flips.
+    pub fn toggle(&mut self, expand: Option<bool>) {
+        let Some(row) = self.selected() else {
+            return;
+        };
+        let expand = expand.unwrap_or(self.collapsed.contains(&row.path));
+        match expand {
+            true => {
+                self.collapsed.remove(&row.path);
+            }
+            false => {
+                self.collapsed.insert(row.path.clone());
+                // Keep the cursor on the call when its params fold away.
+                let rows = self.rows();
+                if let Some(i) = rows
+                    .iter()
+                    .position(|r| r.path == row.path && r.param.is_none())
+                {
+                    self.selected = i;
+                }
+            }
+        }
+    }
+
+    pub fn handle_key(&mut self, key: KeyCode) -> Action {
+        let count = self.rows().len();
+        match key {
+            KeyCode::Char('q') | KeyCode::Esc => return Action::Quit,
+            KeyCode::Up | KeyCode::Char('k') => self.selected = self.selected.saturating_sub(1),
+            KeyCode::Down | KeyCode::Char('j') => {
+                self.selected = (self.selected + 1).min(count.saturating_sub(1))
+            }
+            KeyCode::Left | KeyCode::Char('h') => self.toggle(Some(false)),
+            KeyCode::Right | KeyCode::Char('l') => self.toggle(Some(true)),
+            KeyCode::Enter | KeyCode::Char(' ') => self.toggle(None),
+            KeyCode::Char('c') => {
+                if let Some(row) = self.selected() {
+                    self.status = format!("copied {}", row.value);
+                    return Action::Copy(row.value);
+                }
+            }
+            _ => {}
+        }
+        Action::None
+    }
+
+    /// Hex pane lines: the selector, then one 32-byte word per line, as `(byte offset, hex)`.
+    pub fn hex_lines(&self) -> Vec<(usize, &str)> {
+        let mut lines = vec![];
+        let mut start = 0;
+        while start * 2 < self.hex.len() {
+            let len = match start == 0 {
+                true => 4,
+                false => 32,
+            };
+            let end = ((start + len) * 2).min(self.hex.len());
+            lines.push((start, &self.hex[start * 2..end]));
+            start += len;
+        }
+        lines
+    }
+
+    pub fn draw(&self, frame: &mut Frame) {
+        let [main, status] = Layout::default()
+            .direction(Direction::Vertical)
+            .constraints([Constraint::Min(3), Constraint::Length(1)])
+            .areas(frame.area());
+        let [tree, hex] = Layout::default()
+            .direction(Direction::Horizontal)
+            .constraints([Constraint::Percentage(55), Constraint::Percentage(45)])
+            .areas(main);
+
+        let rows = self.rows();
+        let items = rows
+            .iter()
+            .map(|r| ListItem::new(format!("{}{}", "  ".repeat(r.depth), r.text)))
+            .collect::<Vec<ListItem>>();
+        let list = List::new(items)
+            .block(Block::default().borders(Borders::ALL).title(" calls "))
+            .highlight_style(Style::default().add_modifier(Modifier::REVERSED));
+        let mut state = ListState::default().with_selected(Some(self.selected));
+        frame.render_stateful_widget(list, tree, &mut state);
+
+        let span = rows.get(self.selected).map(|r| r.span);
+        let highlight = Style::default().fg(Color::Black).bg(Color::Yellow);
+        let mut first_highlighted = 0;
+        let lines = self
+            .hex_lines()
+            .into_iter()
+            .enumerate()
+            .map(|(n, (offset, hex))| {
+                let mut parts = vec![Text::styled(
+                    format!("{:06x} ", offset),
+                    Style::default().fg(Color::DarkGray),
+                )];
+                let end = offset + hex.len() / 2;
+                match span {
+                    Some(s) if s.start < end && s.end > offset => {
+                        if first_highlighted == 0 {
+                            first_highlighted = n;
+                        }
+                        let from = (s.start.max(offset) - offset) * 2;
+                        let to = (s.end.min(end) - offset) * 2;
+                        parts.push(Text::raw(hex[..from].to_string()));
+                        parts.push(Text::styled(hex[from..to].to_string(), highlight));
+                        parts.push(Text::raw(hex[to..].to_string()));
+                    }
+                    _ => parts.push(Text::raw(hex.to_string())),
+                }
+                Line::from(parts)
+            })
+            .collect::<Vec<Line>>();
+        // Scroll so the highlighted bytes stay in view.
+        let height = hex.height.saturating_sub(2) as usize;
+        let scroll = first_highlighted.saturating_sub(height / 2) as u16;
+        let paragraph = Paragraph::new(lines)
+            .block(Block::default().borders(Borders::ALL).title(" calldata "))
+            .scroll((scroll, 0));
+        frame.render_widget(paragraph, hex);
+
+        frame.render_widget(
+            Paragraph::new(self.status.as_str()).style(Style::default().fg(Color::DarkGray)),
+            status,
+        );
+    }
+}
+
+/// OSC 52 escape sequence asking the terminal to put `text` on the clipboard.
+pub fn osc52(text: &str) -> String {
+    format!("\x1b]52;c;{}\x07", base64(text.as_bytes()))
+}
+
+fn base64(bytes: &[u8]) -> String {
+    const TABLE: &[u8; 64] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789+/";
+    let mut out = String::new();
+    for chunk in bytes.chunks(3) {
+        let b = [
+            chunk[0],
+            *chunk.get(1).unwrap_or(&0),
+            *chunk.get(2).unwrap_or(&0),
+        ];
+        let n = (b[0] as u32) << 16 | (b[1] as u32) << 8 | b[2] as u32;
+        for i in 0..4 {
+            match i <= chunk.len() {
+                true => out.push(TABLE[(n >> (18 - 6 * i) & 63) as usize] as char),
+                false => out.push('='),
+            }
+        }
+    }
+    out
+}
@@ -0,0 +1,74 @@
+use crate::decoded::DecodedCalldata;
+use crate::render::{flatten, FlatRow};
+use arrow_array::{ArrayRef, RecordBatch, StringArray, UInt32Array};
+use arrow_schema::{ArrowError, DataType, Field, Schema};
+use parquet::arrow::ArrowWriter;
+use parquet::errors::ParquetError;
+use std::io::Write;
+use std::sync::Arc;
+
+// ------------------------------------------------------------
+//  Arrow / Parquet export
+// ------------------------------------------------------------
+
+/// Columns of the exported table: one row per param of every decoded call.
+pub fn schema() -> Schema {
+    Schema::new(vec![
+        Field::new("tx_id", DataType::Utf8, false),
+        Field::new("path", DataType::Utf8, false),
+        Field::new("depth", DataType::UInt32, false),
+        Field::new("selector", DataType::Utf8, false),
+        Field::new("signature_guess", DataType::Utf8, false),
+        Field::new("param_index", DataType::UInt32, false),
+        Field::new("type", DataType::Utf8, false),
+        Field::new("candidates", DataType::Utf8, false),
+        Field::new("raw", DataType::Utf8, false),
+        Field::new("value", DataType::Utf8, false),
+    ])
+}
+
+/// Flattens a batch of decodes into one Arrow record batch.
+///
+/// ## Params
+/// 1. batch - `(tx id, decode tree)` pairs.
+pub fn to_record_batch<'a>(
+    batch: impl IntoIterator<Item = (&'a str, &'a DecodedCalldata)>,
+) -> Result<RecordBatch, ArrowError> {
+    let rows = batch
+        .into_iter()
+        .flat_map(|(id, root)| flatten(id, root))
+        .collect::<Vec<FlatRow>>();
+    let strings = |f: fn(&FlatRow) -> &str| {
+        Arc::new(StringArray::from_iter_values(rows.iter().map(f))) as ArrayRef
+    };
+    let numbers = |f: fn(&FlatRow) -> u32| {
+        Arc::new(UInt32Array::from_iter_values(rows.iter().map(f))) as ArrayRef
+    };
+    RecordBatch::try_new(
+        Arc::new(schema()),
+        vec![
+            strings(|r| &r.tx_id),
+            strings(|r| &r.path),
+            numbers(|r| r.depth),
+            strings(|r| &r.selector),
+            strings(|r| &r.signature_guess),
+            numbers(|r| r.param_index),
+            strings(|r| &r.ty),
+            strings(|r| &r.candidates),
+            strings(|r| &r.raw),
+            strings(|r| &r.value),
+        ],
+    )
+}
+
+/// Writes a batch of decodes to `writer` as a Parquet file.
+pub fn write_parquet<'a, W: Write + Send>(
+    writer: W,
+    batch: impl IntoIterator<Item = (&'a str, &'a DecodedCalldata)>,
+) -> Result<(), ParquetError> {
+    let record_batch = to_record_batch(batch)?;
+    let mut writer = ArrowWriter::try_new(writer, record_batch.schema(), None)?;
+    writer.write(&record_batch)?;
+    writer.close()?;
+    Ok(())
+}
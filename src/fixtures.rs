@@ -0,0 +1,279 @@
+use crate::decoded::DecodedCalldata;
+use crate::schema::JsonOutput;
+use crate::Calldata;
+use serde_json::{Map, Value};
+use std::path::{Path, PathBuf};
+
+// ------------------------------------------------------------
+//  Fixtures
+// ------------------------------------------------------------
+
+/// A calldata and the decode it must keep giving.
+///
+/// Stored as `<name>.json`, the JSON output of `calldata-decoder --format json decode`,
+/// which holds the calldata hex along with its decode tree.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Fixture {
+    pub name: String,
+    pub expected: JsonOutput,
+}
+
+impl Fixture {
+    /// Fixture of the current decode of `calldata`.
+    pub fn new(name: &str, calldata: &str) -> Self {
+        Self {
+            name: name.to_string(),
+            expected: JsonOutput::new(&Calldata::new(calldata)),
+        }
+    }
+
+    /// Reads the fixture at `path`, named after the file. The labels and
+    /// token amounts the CLI adds to its output are dropped, not being part
+    /// of the decode.
+    pub fn load(path: &Path) -> Result<Self, String> {
+        let text =
+            std::fs::read_to_string(path).map_err(|e| format!("{}: {}", path.display(), e))?;
+        let mut expected: JsonOutput =
+            serde_json::from_str(&text).map_err(|e| format!("{}: {}", path.display(), e))?;
+        unlabel(&mut expected.root);
+        let name = path.file_stem().unwrap_or_default().to_string_lossy();
+        Ok(Self {
+            name: name.to_string(),
+            expected,
+        })
+    }
+
+    /// Writes the fixture as `<name>.json` in `dir`, returning its path.
+    pub fn save(&self, dir: &Path) -> Result<PathBuf, String> {
+        let path = dir.join(format!("{}.json", self.name));
+        let json = serde_json::to_string_pretty(&self.expected).map_err(|e| e.to_string())?;
+        std::fs::write(&path, json + "\n").map_err(|e| format!("{}: {}", path.display(), e))?;
+        Ok(path)
+    }
+
+    /// Decodes the calldata again, describing the first difference from the expected decode.
+    pub fn check(&self) -> Result<(), String> {
+        let actual = JsonOutput::new(&Calldata::new(&self.expected.calldata));
+        if actual == self.expected {
+            return Ok(());
+        }
+        let expected = serde_json::to_value(&self.expected).map_err(|e| e.to_string())?;
+        let actual = serde_json::to_value(&actual).map_err(|e| e.to_string())?;
+        let difference = first_difference(&expected, &actual, "")
+            .unwrap_or_else(|| "decodes differ".to_string());
+        Err(format!("{}: {}", self.name, difference))
+    }
+}
+
+/// Clears the `label` and `amount` of every param of the tree.
+fn unlabel(call: &mut DecodedCalldata) {
+    for param in call.params.iter_mut() {
+        param.label = None;
+        param.amount = None;
+    }
+    call.calls.iter_mut().for_each(unlabel);
+}
+
+/// Every `*.json` fixture in `dir`, ordered by name.
+pub fn load_dir(dir: &Path) -> Result<Vec<Fixture>, String> {
+    let entries = std::fs::read_dir(dir).map_err(|e| format!("{}: {}", dir.display(), e))?;
+    let mut paths = entries
+        .filter_map(|entry| entry.ok().map(|e| e.path()))
+        .filter(|path| path.extension().is_some_and(|ext| ext == "json"))
+        .collect::<Vec<_>>();
+    paths.sort();
+    paths.iter().map(|path| Fixture::load(path)).collect()
+}
+
+/// Checks every fixture in `dir`, returning how many passed or every failure, one per line.
+pub fn check_dir(dir: &Path) -> Result<usize, String> {
+    let fixtures = load_dir(dir)?;
+    let failures = fixtures
+        .iter()
+        .filter_map(|fixture| fixture.check().err())
+        .collect::<Vec<_>>();
+    match failures.is_empty() {
+        true => Ok(fixtures.len()),
+        false => Err(failures.join("\n")),
+    }
+}
+
+// ------------------------------------------------------------
+//  Golden files
+// ------------------------------------------------------------
+
+/// Set to anything but empty to have golden files written rather than checked.
+pub const UPDATE_GOLDEN: &str = "UPDATE_GOLDEN";
+
+/// The JSON output indented, its keys sorted at every level and a newline at
+/// the end: the same bytes for the same decode, whatever the serde_json
+/// features of the build.
+pub fn canonical_json(output: &JsonOutput) -> String {
+    let value = serde_json::to_value(output).expect("decode tree is serializable");
+    let json = serde_json::to_string_pretty(&sorted(value)).expect("values are serializable");
+    json + "\n"
+}
+
+/// The decode one line per call, param and warning, each led by its path:
+///
+/// ```text
+/// calldata 0xa9059cbb...
+/// root a9059cbb transfer(address,uint256) 0..68
+/// root.params[0] address 0x...
+/// root.params[1] uint256 1000000000
+/// ```
+///
+/// Terser than `canonical_json`, leaving out the raw words, spans of params and
+/// the runner-up types, so a golden file only changes with what a reader sees.
+pub fn canonical_text(output: &JsonOutput) -> String {
+    let mut out = format!("calldata {}\n", output.calldata);
+    for (prefix, call) in output.root.walk_paths() {
+        let path = format!("root.{}", prefix);
+        let path = path.trim_end_matches('.');
+        out += &format!(
+            "{} {} {} {}..{}\n",
+            path, call.selector, call.signature_guess, call.span.start, call.span.end
+        );
+        for (i, param) in call.params.iter().enumerate() {
+            let ty = param
+                .types
+                .first()
+                .map_or("bytes32", |t| t.solidity.as_str());
+            out += &format!("{}.params[{}] {} {}\n", path, i, ty, param.value);
+        }
+        for warning in call.warnings.iter() {
+            out += &format!("{} warning {}\n", path, warning);
+        }
+    }
+    out
+}
+
+/// Checks `output` against the golden file at `path`: `canonical_json` when it
+/// ends in `.json`, `canonical_text` otherwise. With `UPDATE_GOLDEN` set the
+/// file is written instead.
+///
+/// ## Returns
+/// 1. Where the decode first differs from the file, or that there's no file.
+pub fn check_golden(output: &JsonOutput, path: &Path) -> Result<(), String> {
+    let json = path.extension().is_some_and(|ext| ext == "json");
+    let actual = match json {
+        true => canonical_json(output),
+        false => canonical_text(output),
+    };
+    if std::env::var_os(UPDATE_GOLDEN).is_some_and(|v| !v.is_empty()) {
+        return std::fs::write(path, actual).map_err(|e| format!("{}: {}", path.display(), e));
+    }
+    let expected = match std::fs::read_to_string(path) {
+        Ok(expected) => expected,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => {
+            return Err(format!(
+                "{}: no golden file, run with {}=1 to write it",
+                path.display(),
+                UPDATE_GOLDEN
+            ))
+        }
+        Err(e) => return Err(format!("{}: {}", path.display(), e)),
+    };
+    if expected == actual {
+        return Ok(());
+    }
+    let difference = match json {
+        true => {
+            let expected = serde_json::from_str::<Value>(&expected)
+                .map_err(|e| format!("{}: {}", path.display(), e))?;
+            let actual = serde_json::from_str::<Value>(&actual).map_err(|e| e.to_string())?;
+            first_difference(&expected, &actual, "")
+        }
+        false => first_line_difference(&expected, &actual),
+    };
+    Err(format!(
+        "{}: {}",
+        path.display(),
+        difference.unwrap_or_else(|| "formatted differently".to_string())
+    ))
+}
+
+/// Panics unless the default decode of `calldata` (hex) matches the golden
+/// file at `golden`, for snapshot tests pinning the decodes a crate relies on.
+///
+/// ```no_run
+/// calldata_decoder::fixtures::assert_decodes_to("0xa9059cbb...", "tests/golden/transfer.txt");
+/// ```
+///
+/// Run with `UPDATE_GOLDEN=1` to write the files, and again after an upgrade
+/// to accept the decodes it changed.
+pub fn assert_decodes_to(calldata: &str, golden: impl AsRef<Path>) {
+    let output = JsonOutput::new(&Calldata::new(calldata));
+    if let Err(e) = check_golden(&output, golden.as_ref()) {
+        panic!("{}", e);
+    }
+}
+
+/// `value` with the keys of every object in order.
+fn sorted(value: Value) -> Value {
+    match value {
+        Value::Object(object) => {
+            let mut entries = object.into_iter().collect::<Vec<_>>();
+            entries.sort_by(|a, b| a.0.cmp(&b.0));
+            let sorted = entries.into_iter().map(|(k, v)| (k, sorted(v)));
+            Value::Object(sorted.collect::<Map<_, _>>())
+        }
+        Value::Array(items) => Value::Array(items.into_iter().map(sorted).collect()),
+        value => value,
+    }
+}
+
+/// Number and content of the first line of `actual` differing from `expected`.
+fn first_line_difference(expected: &str, actual: &str) -> Option<String> {
+    let expected = expected.lines().collect::<Vec<_>>();
+    let actual = actual.lines().collect::<Vec<_>>();
+    (0..expected.len().max(actual.len())).find_map(|i| {
+        let (e, a) = (expected.get(i), actual.get(i));
+        (e != a).then(|| {
+            format!(
+                "line {}: expected {:?}, got {:?}",
+                i + 1,
+                e.copied().unwrap_or_default(),
+                a.copied().unwrap_or_default()
+            )
+        })
+    })
+}
+
+/// Path and values of the first place `actual` differs from `expected`, e.g.
+/// `root.calls[0].params[3].value: expected "1", got "0x01"`.
+fn first_difference(expected: &Value, actual: &Value, path: &str) -> Option<String> {
+    match (expected, actual) {
+        (Value::Object(e), Value::Object(a)) => {
+            let keys = e.keys().chain(a.keys().filter(|k| !e.contains_key(*k)));
+            keys.into_iter().find_map(|key| {
+                let path = match path.is_empty() {
+                    true => key.clone(),
+                    false => format!("{}.{}", path, key),
+                };
+                match (e.get(key), a.get(key)) {
+                    (Some(e), Some(a)) => first_difference(e, a, &path),
+                    (Some(e), None) => Some(format!("{}: expected {}, got nothing", path, e)),
+                    (None, a) => Some(format!(
+                        "{}: unexpected {}",
+                        path,
+                        a.unwrap_or(&Value::Null)
+                    )),
+                }
+            })
+        }
+        (Value::Array(e), Value::Array(a)) if e.len() == a.len() => e
+            .iter()
+            .zip(a.iter())
+            .enumerate()
+            .find_map(|(i, (e, a))| first_difference(e, a, &format!("{}[{}]", path, i))),
+        (Value::Array(e), Value::Array(a)) => Some(format!(
+            "{}: expected {} items, got {}",
+            path,
+            e.len(),
+            a.len()
+        )),
+        (e, a) if e != a => Some(format!("{}: expected {}, got {}", path, e, a)),
+        _ => None,
+    }
+}
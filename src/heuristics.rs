@@ -0,0 +1,542 @@
+use crate::constants::*;
+use crate::explain::{Check, Recorder};
+use crate::type_guesser::ParamTypes;
+use crate::word_u64;
+use serde::{Deserialize, Serialize};
+use std::fmt;
+use std::sync::{Arc, OnceLock};
+
+// ------------------------------------------------------------
+//  Heuristics
+// ------------------------------------------------------------
+
+/// Which guessing rules the decoder applies. All are enabled by default.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Heuristics {
+    /// Extract method calls embedded in `bytes` params.
+    pub nested: bool,
+    /// Type words starting with 4 non-zero bytes followed by zeros as selectors.
+    pub selectors: bool,
+    /// Type words starting with `ffffffff` as negative ints.
+    pub ints: bool,
+    /// Type words with exactly 20 significant bytes as addresses.
+    pub addresses: bool,
+    /// Type words up to 8 as `uint8` / `bool`.
+    pub small_values: bool,
+    /// Split arrays of structs into their elements and vote on each field's
+    /// type across the elements.
+    pub strides: bool,
+    /// Compiler whose patterns the rules expect.
+    pub profile: Profile,
+}
+
+/// The patterns of a compiler the heuristics can be tuned to.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Profile {
+    #[default]
+    Solidity,
+    /// Contracts compiled by Vyper, e.g. Curve's and Yearn's. Small values are
+    /// typed as `int128` (Curve's coin indices) or `bool` rather than `uint8`,
+    /// and zero words aren't taken as padding re-aligning the words after them:
+    /// they're the unused entries of the bounded arrays Vyper code favors.
+    Vyper,
+}
+
+impl Profile {
+    pub fn name(&self) -> &'static str {
+        match self {
+            Profile::Solidity => "solidity",
+            Profile::Vyper => "vyper",
+        }
+    }
+}
+
+impl Default for Heuristics {
+    fn default() -> Self {
+        Self::all()
+    }
+}
+
+impl Heuristics {
+    /// Names accepted by `get` / `set`, in declaration order.
+    pub const NAMES: [&'static str; 6] = [
+        "nested",
+        "selectors",
+        "ints",
+        "addresses",
+        "small_values",
+        "strides",
+    ];
+
+    pub fn all() -> Self {
+        Self {
+            nested: true,
+            selectors: true,
+            ints: true,
+            addresses: true,
+            small_values: true,
+            strides: true,
+            profile: Profile::Solidity,
+        }
+    }
+
+    pub fn none() -> Self {
+        Self {
+            nested: false,
+            selectors: false,
+            ints: false,
+            addresses: false,
+            small_values: false,
+            strides: false,
+            profile: Profile::Solidity,
+        }
+    }
+
+    /// The same rules, tuned to `profile`'s patterns.
+    pub fn with_profile(mut self, profile: Profile) -> Self {
+        self.profile = profile;
+        self
+    }
+
+    fn flag(&mut self, name: &str) -> Option<&mut bool> {
+        match name {
+            "nested" => Some(&mut self.nested),
+            "selectors" => Some(&mut self.selectors),
+            "ints" => Some(&mut self.ints),
+            "addresses" => Some(&mut self.addresses),
+            "small_values" => Some(&mut self.small_values),
+            "strides" => Some(&mut self.strides),
+            _ => None,
+        }
+    }
+
+    /// Whether the heuristic called `name` is enabled.
+    pub fn get(&self, name: &str) -> Option<bool> {
+        let mut copy = *self;
+        copy.flag(name).map(|f| *f)
+    }
+
+    /// Enables or disables the heuristic called `name`.
+    pub fn set(&mut self, name: &str, enabled: bool) -> Result<(), String> {
+        match self.flag(name) {
+            Some(f) => {
+                *f = enabled;
+                Ok(())
+            }
+            None => Err(format!(
+                "unknown heuristic `{}`, expected one of: {}",
+                name,
+                Self::NAMES.join(", ")
+            )),
+        }
+    }
+}
+
+// ------------------------------------------------------------
+//  Heuristic chain
+// ------------------------------------------------------------
+
+/// What a heuristic knows of the word it inspects, besides its bytes.
+#[derive(Debug, Clone, Copy)]
+pub struct WordContext<'a> {
+    /// Selector of the method the word is a param of, empty when the word
+    /// isn't a call's, e.g. a log topic.
+    pub selector: &'a [u8],
+    /// Index of the word in the method's params.
+    pub index: usize,
+    /// Toggles and profile of the decode.
+    pub heuristics: &'a Heuristics,
+}
+
+impl<'a> WordContext<'a> {
+    /// A word on its own, not a param of a known method.
+    pub fn new(heuristics: &'a Heuristics) -> Self {
+        Self {
+            selector: &[],
+            index: 0,
+            heuristics,
+        }
+    }
+}
+
+/// Candidate types a heuristic gives a word, most likely first.
+#[derive(Clone)]
+pub struct Guess {
+    pub types: ParamTypes,
+    /// How sure the heuristic is. A guess scoring `Guess::CERTAIN` ends the
+    /// chain; a lower one is kept unless a later heuristic scores higher.
+    pub score: f64,
+}
+
+impl Guess {
+    pub const CERTAIN: f64 = 1.0;
+
+    pub fn new(types: Vec<Types>, score: f64) -> Self {
+        Self {
+            types: ParamTypes::new(types),
+            score,
+        }
+    }
+
+    pub fn certain(types: Vec<Types>) -> Self {
+        Self::new(types, Self::CERTAIN)
+    }
+}
+
+/// A rule typing words by their pattern, e.g. "12 zero bytes then 20
+/// significant bytes is an address".
+///
+/// The built-in rules are heuristics too (see `HeuristicChain::builtin`), so
+/// a crate's own, e.g. the token addresses of its registry or timestamps
+/// near the current time, are consulted among them, and the built-in ones
+/// reordered or taken out.
+pub trait Heuristic: Send + Sync {
+    /// Short name, e.g. `addresses`. A name in `Heuristics::NAMES` is
+    /// toggled by that flag, others are always enabled.
+    fn name(&self) -> &'static str;
+
+    /// Candidate types of `word` when it has the heuristic's pattern.
+    fn inspect(&self, word: &[u8], cx: &WordContext) -> Option<Guess>;
+
+    /// What `word` looks like to the heuristic, shown by `Calldata::explain`.
+    /// Without one, only the words the heuristic types are shown.
+    fn reason(&self, _word: &[u8], _cx: &WordContext) -> Option<String> {
+        None
+    }
+}
+
+/// Heuristics consulted, in order, to type a word.
+///
+/// The first guess scoring `Guess::CERTAIN` types the word, else the best
+/// scoring one, the earlier winning ties. Words no heuristic types are
+/// `uint256`, `int256` or `bytes32`.
+#[derive(Clone, Default)]
+pub struct HeuristicChain {
+    heuristics: Vec<Arc<dyn Heuristic>>,
+}
+
+impl fmt::Debug for HeuristicChain {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_list().entries(self.names()).finish()
+    }
+}
+
+impl HeuristicChain {
+    /// No heuristics: every word falls back to `uint256`, `int256` or `bytes32`.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// The rules shipped with the crate: `ZeroWord`, `MaxWord`,
+    /// `SelectorWord`, `NegativeInt`, `AddressWord` and `SmallValue`.
+    pub fn builtin() -> Self {
+        Self::shared().clone()
+    }
+
+    /// The built-in chain, built once.
+    pub(crate) fn shared() -> &'static Self {
+        static BUILTIN: OnceLock<HeuristicChain> = OnceLock::new();
+        BUILTIN.get_or_init(|| {
+            Self::new()
+                .with(ZeroWord)
+                .with(MaxWord)
+                .with(SelectorWord)
+                .with(NegativeInt)
+                .with(AddressWord)
+                .with(SmallValue)
+        })
+    }
+
+    /// Adds `heuristic`, consulted after the ones already there.
+    pub fn with(mut self, heuristic: impl Heuristic + 'static) -> Self {
+        self.heuristics.push(Arc::new(heuristic));
+        self
+    }
+
+    /// Adds `heuristic`, consulted before the ones already there.
+    pub fn with_first(mut self, heuristic: impl Heuristic + 'static) -> Self {
+        self.heuristics.insert(0, Arc::new(heuristic));
+        self
+    }
+
+    /// Takes out the heuristics called `name`.
+    pub fn without(mut self, name: &str) -> Self {
+        self.heuristics.retain(|h| h.name() != name);
+        self
+    }
+
+    /// Consults the heuristics called `names` first, in that order, then the
+    /// others as they were.
+    pub fn ordered(mut self, names: &[&str]) -> Result<Self, String> {
+        let mut first = vec![];
+        for name in names {
+            match self.heuristics.iter().position(|h| h.name() == *name) {
+                Some(i) => first.push(self.heuristics.remove(i)),
+                None => {
+                    return Err(format!(
+                        "unknown heuristic `{}`, expected one of: {}",
+                        name,
+                        self.names().join(", ")
+                    ))
+                }
+            }
+        }
+        first.append(&mut self.heuristics);
+        self.heuristics = first;
+        Ok(self)
+    }
+
+    /// Names of the heuristics, in the order they're consulted.
+    pub fn names(&self) -> Vec<&'static str> {
+        self.heuristics.iter().map(|h| h.name()).collect()
+    }
+
+    /// Potential types of `word`.
+    pub fn guess(&self, word: &[u8], cx: &WordContext) -> ParamTypes {
+        self.guess_traced(word, cx, &mut Recorder::off())
+    }
+
+    /// Same as `guess`, recording each check made in `trace`.
+    pub(crate) fn guess_traced(
+        &self,
+        word: &[u8],
+        cx: &WordContext,
+        trace: &mut Recorder<Check>,
+    ) -> ParamTypes {
+        let mut best: Option<Guess> = None;
+        for heuristic in self.heuristics.iter() {
+            let enabled = cx.heuristics.get(heuristic.name()).unwrap_or(true);
+            let guess = match enabled {
+                true => heuristic.inspect(word, cx),
+                false => None,
+            };
+            let matched = guess.is_some();
+            trace.record_some(|| {
+                let reason = heuristic.reason(word, cx);
+                match (reason, matched) {
+                    (Some(reason), _) => {
+                        Some(Check::new(heuristic.name(), enabled, matched, reason))
+                    }
+                    (None, true) => Some(Check::new(
+                        heuristic.name(),
+                        true,
+                        true,
+                        "matched".to_string(),
+                    )),
+                    (None, false) => None,
+                }
+            });
+            if let Some(guess) = guess {
+                if guess.score >= Guess::CERTAIN {
+                    return guess.types;
+                }
+                if best.as_ref().is_none_or(|b| guess.score > b.score) {
+                    best = Some(guess);
+                }
+            }
+        }
+        match best {
+            Some(guess) => guess.types,
+            None => {
+                // Eliminated some patterns; now we can conclude it can be one of these.
+                trace.record(|| {
+                    Check::new("fallback", true, true, "no pattern matched".to_string())
+                });
+                ParamTypes::new(vec![Types::Uint, Types::Int, Types::Bytes])
+            }
+        }
+    }
+}
+
+// ------------------------------------------------------------
+//  Built-in heuristics
+// ------------------------------------------------------------
+
+/// Words of zero bytes, any type's zero.
+pub struct ZeroWord;
+
+impl Heuristic for ZeroWord {
+    fn name(&self) -> &'static str {
+        "zero"
+    }
+
+    fn inspect(&self, word: &[u8], _cx: &WordContext) -> Option<Guess> {
+        match word == EMPTY_32 {
+            true => Some(Guess::certain(vec![Types::AnyZero])),
+            false => None,
+        }
+    }
+
+    fn reason(&self, word: &[u8], _cx: &WordContext) -> Option<String> {
+        match word == EMPTY_32 {
+            true => Some("every byte is zero".to_string()),
+            false => None,
+        }
+    }
+}
+
+/// Maxed out words: the max of a `uint128`, or every byte `ff`.
+pub struct MaxWord;
+
+impl Heuristic for MaxWord {
+    fn name(&self) -> &'static str {
+        "max"
+    }
+
+    fn inspect(&self, word: &[u8], _cx: &WordContext) -> Option<Guess> {
+        match word {
+            w if w == MAX_U128 => Some(Guess::certain(vec![Types::MaxUint128])),
+            w if w == MAX_U256 => Some(Guess::certain(vec![Types::AnyMax])),
+            _ => None,
+        }
+    }
+
+    fn reason(&self, word: &[u8], _cx: &WordContext) -> Option<String> {
+        match word {
+            w if w == MAX_U128 => Some("the max of a uint128".to_string()),
+            w if w == MAX_U256 => Some("every byte is ff".to_string()),
+            _ => None,
+        }
+    }
+}
+
+/// Selectors: 4 non-zero bytes followed by zeros, e.g. a `bytes4` interface id.
+pub struct SelectorWord;
+
+impl Heuristic for SelectorWord {
+    fn name(&self) -> &'static str {
+        "selectors"
+    }
+
+    fn inspect(&self, word: &[u8], _cx: &WordContext) -> Option<Guess> {
+        // if: !00000000... && !FFFFFFFF... && ________00000000
+        let head = word.get(..SELECTOR);
+        let selector = head.is_some_and(|h| h != EMPTY_4 && h != MASK_4)
+            && word.get(SELECTOR..8) == Some(&EMPTY_4[..]);
+        match selector {
+            true => Some(Guess::certain(vec![
+                Types::Selector,
+                Types::String,
+                Types::Bytes,
+            ])),
+            false => None,
+        }
+    }
+
+    fn reason(&self, word: &[u8], cx: &WordContext) -> Option<String> {
+        let reason = match (word.get(..SELECTOR), self.inspect(word, cx).is_some()) {
+            (_, true) => "4 non-zero bytes then 4 zero bytes",
+            (Some(h), false) if h == EMPTY_4 => "starts with 4 zero bytes",
+            (Some(h), false) if h == MASK_4 => "starts with ffffffff",
+            _ => "bytes 4 to 8 aren't zero",
+        };
+        Some(reason.to_string())
+    }
+}
+
+/// Negative ints, whose leading bytes are `ff`.
+pub struct NegativeInt;
+
+impl Heuristic for NegativeInt {
+    fn name(&self) -> &'static str {
+        "ints"
+    }
+
+    fn inspect(&self, word: &[u8], _cx: &WordContext) -> Option<Guess> {
+        // Ints replace 0s with 1s in bitwise.
+        if word.get(..SELECTOR) != Some(&MASK_4[..]) {
+            return None;
+        }
+        // if: FFFFFFFFFFFFFFFF we can assume it's an Int
+        match word.get(SELECTOR..8) == Some(&MASK_4[..]) {
+            true => Some(Guess::certain(vec![Types::Int])),
+            false => Some(Guess::certain(vec![
+                Types::Int,
+                Types::String,
+                Types::Bytes,
+            ])),
+        }
+    }
+
+    fn reason(&self, word: &[u8], _cx: &WordContext) -> Option<String> {
+        let int = word.get(..SELECTOR) == Some(&MASK_4[..]);
+        let reason = match (int, word.get(SELECTOR..8) == Some(&MASK_4[..])) {
+            (true, true) => "starts with 8 ff bytes, a negative int",
+            (true, false) => "starts with ffffffff, a negative int or text",
+            (false, _) => "doesn't start with ffffffff",
+        };
+        Some(reason.to_string())
+    }
+}
+
+/// Addresses: 12 zero bytes then a full 20 bytes.
+pub struct AddressWord;
+
+impl Heuristic for AddressWord {
+    fn name(&self) -> &'static str {
+        "addresses"
+    }
+
+    fn inspect(&self, word: &[u8], _cx: &WordContext) -> Option<Guess> {
+        // Todo:
+        // - Check for optimised addresses via heuristics
+        let zeros = word.iter().take_while(|b| **b == 0).count();
+        match word.len() == WORD && zeros == 12 && word[12] >= 0x10 {
+            true => Some(Guess::certain(vec![
+                Types::Address,
+                Types::Bytes20,
+                Types::Uint,
+            ])),
+            false => None,
+        }
+    }
+
+    fn reason(&self, word: &[u8], cx: &WordContext) -> Option<String> {
+        let zeros = word.iter().take_while(|b| **b == 0).count();
+        let reason = match (self.inspect(word, cx).is_some(), zeros) {
+            (true, _) => "12 zero bytes then 20 significant bytes".to_string(),
+            (false, 12) if word.len() == WORD => {
+                "12 zero bytes, but the 13th is below 0x10".to_string()
+            }
+            (false, zeros) => format!("{} leading zero bytes, an address has 12", zeros),
+        };
+        Some(reason)
+    }
+}
+
+/// Values up to 8, a `uint8` or a `bool`, or under the Vyper profile an
+/// `int128` index.
+pub struct SmallValue;
+
+impl Heuristic for SmallValue {
+    fn name(&self) -> &'static str {
+        "small_values"
+    }
+
+    fn inspect(&self, word: &[u8], cx: &WordContext) -> Option<Guess> {
+        // Anything with high bytes set is bigger than a `uint8` anyway.
+        let v = word_u64(word).filter(|v| *v <= 8)?;
+        let types = match (cx.heuristics.profile, v <= 1) {
+            // Vyper code indexes with `int128` (Curve's coins) and rarely uses `uint8`.
+            (Profile::Vyper, true) => vec![Types::Int128, Types::Bool, Types::Uint],
+            (Profile::Vyper, false) => vec![Types::Int128, Types::Uint],
+            (Profile::Solidity, true) => vec![Types::Uint8, Types::Bytes1, Types::Bool],
+            (Profile::Solidity, false) => vec![Types::Uint8, Types::Bytes1],
+        };
+        Some(Guess::certain(types))
+    }
+
+    fn reason(&self, word: &[u8], _cx: &WordContext) -> Option<String> {
+        if word.len() > WORD {
+            return None;
+        }
+        let reason = match word_u64(word) {
+            Some(0 | 1) => "the value is 0 or 1, a bool too".to_string(),
+            Some(v) => format!("the value is {}, small values are at most 8", v),
+            None => "the value doesn't fit in 8 bytes".to_string(),
+        };
+        Some(reason)
+    }
+}
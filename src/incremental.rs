@@ -0,0 +1,209 @@
+use crate::constants::*;
+use crate::decoded::{DecodeWarning, DecodedCalldata};
+use crate::heuristics::Heuristics;
+use crate::limits::Limits;
+use crate::{Calldata, HexError};
+use serde::{Deserialize, Serialize};
+
+// ------------------------------------------------------------
+//  Incremental decoder
+// ------------------------------------------------------------
+
+/// What could be decoded from the bytes received so far.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PartialDecode {
+    /// The call read from the selector and the full words received, `None`
+    /// until the selector is in.
+    pub root: Option<DecodedCalldata>,
+    /// Bytes received.
+    pub received: usize,
+    /// Bytes `root` was decoded from, the rest waiting on the next word.
+    pub decoded: usize,
+    /// Whether every byte of the calldata is in and decoded.
+    pub complete: bool,
+}
+
+/// Decodes calldata as it arrives in chunks, e.g. streamed from a peer or a
+/// partial mempool fetch, refining the decode each time a word completes.
+///
+/// Until the calldata is complete only full words after the selector are
+/// decoded, and lengths or offsets pointing past the bytes received aren't
+/// warned about, since the bytes may be on their way. A nested call is split
+/// out once all of it is in.
+#[derive(Debug, Clone, Default)]
+pub struct IncrementalDecoder {
+    bytes: Vec<u8>,
+    heuristics: Heuristics,
+    limits: Limits,
+    /// Length of the whole calldata, when known up front.
+    expected: Option<usize>,
+    /// Hex digit left over from the last hex chunk.
+    nibble: Option<u8>,
+    /// Hex digits received, for error positions.
+    digits: usize,
+    /// Characters of hex text received, for error positions.
+    text: usize,
+    current: PartialDecode,
+}
+
+impl IncrementalDecoder {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Decodes applying only the enabled `heuristics`.
+    pub fn with_heuristics(mut self, heuristics: Heuristics) -> Self {
+        self.heuristics = heuristics;
+        self
+    }
+
+    /// Fails a chunk taking the calldata or its decode over `limits`.
+    pub fn with_limits(mut self, limits: Limits) -> Self {
+        self.limits = limits;
+        self
+    }
+
+    /// The calldata is `len` bytes, e.g. from a transaction's header: it is
+    /// decoded in full as soon as its last byte arrives.
+    pub fn with_expected_len(mut self, len: usize) -> Self {
+        self.expected = Some(len);
+        self
+    }
+
+    /// The decode of the bytes received so far.
+    pub fn current(&self) -> &PartialDecode {
+        &self.current
+    }
+
+    /// Bytes received so far.
+    pub fn received(&self) -> &[u8] {
+        &self.bytes
+    }
+
+    /// Appends raw bytes, decoding again when they complete a word.
+    pub fn push(&mut self, chunk: &[u8]) -> Result<&PartialDecode, String> {
+        let received = self.bytes.len() + chunk.len();
+        self.limits.check_input(received)?;
+        if let Some(expected) = self.expected.filter(|e| received > *e) {
+            return Err(format!(
+                "received {} bytes, past the {} expected",
+                received, expected
+            ));
+        }
+        self.bytes.extend_from_slice(chunk);
+        self.refresh()?;
+        Ok(&self.current)
+    }
+
+    /// Appends hex text, optionally `0x` prefixed at the very start; whitespace
+    /// is skipped and a digit split from its pair waits for the next chunk.
+    pub fn push_hex(&mut self, text: &str) -> Result<&PartialDecode, String> {
+        let mut bytes = vec![];
+        for c in text.chars() {
+            self.text += 1;
+            if c.is_ascii_whitespace() {
+                continue;
+            }
+            // `0x` prefix: a lone leading zero followed by `x`.
+            let prefix = self.digits == 1 && self.bytes.is_empty() && bytes.is_empty();
+            match (c.to_digit(16), self.nibble) {
+                (Some(lo), Some(hi)) => {
+                    bytes.push(hi << 4 | lo as u8);
+                    self.nibble = None;
+                }
+                (Some(hi), None) => self.nibble = Some(hi as u8),
+                (None, Some(0)) if prefix && (c == 'x' || c == 'X') => {
+                    self.nibble = None;
+                    self.digits = 0;
+                    continue;
+                }
+                (None, _) => {
+                    let e = HexError::InvalidChar {
+                        found: c,
+                        at: self.text - 1,
+                        byte: self.digits / 2,
+                    };
+                    return Err(e.to_string());
+                }
+            }
+            self.digits += 1;
+        }
+        self.push(&bytes)
+    }
+
+    /// Decodes all the bytes received as the whole calldata.
+    pub fn finish(self) -> Result<DecodedCalldata, String> {
+        if self.nibble.is_some() {
+            return Err(HexError::OddLength {
+                digits: self.digits,
+            }
+            .to_string());
+        }
+        if let Some(expected) = self.expected.filter(|e| *e != self.bytes.len()) {
+            return Err(format!(
+                "received {} bytes of the {} expected",
+                self.bytes.len(),
+                expected
+            ));
+        }
+        match &self.current.root {
+            Some(root) if self.current.complete => Ok(root.clone()),
+            _ => self.decode(self.bytes.len()),
+        }
+    }
+
+    /// Bytes that can be decoded: all of them once complete, else the
+    /// selector and the full words after it.
+    fn resolvable(&self) -> usize {
+        let received = self.bytes.len();
+        match (self.expected == Some(received), received < SELECTOR) {
+            (true, _) => received,
+            (false, true) => 0,
+            (false, false) => received - (received - SELECTOR) % WORD,
+        }
+    }
+
+    fn refresh(&mut self) -> Result<(), String> {
+        let complete = self.expected == Some(self.bytes.len());
+        let decoded = self.resolvable();
+        if decoded != self.current.decoded || complete != self.current.complete {
+            let root = match decoded < SELECTOR {
+                true => None,
+                false => Some(self.decode(decoded)?),
+            };
+            self.current.root = match complete {
+                true => root,
+                false => root.map(|mut root| {
+                    forget_truncation(&mut root);
+                    root
+                }),
+            };
+        }
+        self.current.received = self.bytes.len();
+        self.current.decoded = decoded;
+        self.current.complete = complete;
+        Ok(())
+    }
+
+    fn decode(&self, len: usize) -> Result<DecodedCalldata, String> {
+        if len < SELECTOR {
+            return Err(format!("{} bytes is shorter than a selector", len));
+        }
+        let bytes = self.bytes[..len].to_vec();
+        Calldata::from_bytes_with_limits(bytes, self.heuristics, self.limits).map(|c| c.decode())
+    }
+}
+
+/// Drops the warnings about bytes that may not have arrived yet.
+fn forget_truncation(call: &mut DecodedCalldata) {
+    call.warnings.retain(|w| {
+        !matches!(
+            w,
+            DecodeWarning::LengthOutOfBounds { .. } | DecodeWarning::OffsetOutOfBounds { .. }
+        )
+    });
+    for nested in call.calls.iter_mut() {
+        forget_truncation(nested);
+    }
+}
@@ -0,0 +1,184 @@
+use crate::decoded::DecodedCalldata;
+use crate::heuristics::Heuristics;
+use crate::limits::Limits;
+use crate::Calldata;
+use revm::interpreter::{CallInputs, CallOutcome, CallScheme, CreateInputs, CreateOutcome};
+use revm::primitives::{Address, CreateScheme};
+use revm::{Database, EvmContext, Inspector};
+use serde::Serialize;
+
+// ------------------------------------------------------------
+//  revm inspector
+// ------------------------------------------------------------
+
+/// The opcode, or transaction, a frame of execution was started by.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub enum FrameKind {
+    Call,
+    CallCode,
+    DelegateCall,
+    StaticCall,
+    Create,
+    Create2,
+}
+
+/// A CALL or CREATE observed during a simulation, with the frames it started.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ExecutionFrame {
+    pub kind: FrameKind,
+    /// EIP-55 checksummed address of the caller.
+    pub caller: String,
+    /// EIP-55 checksummed address whose code runs; the created contract's for
+    /// creates, `None` when the creation failed.
+    pub target: Option<String>,
+    /// Wei sent along, or passed on by a `DELEGATECALL`, in decimal.
+    pub value: String,
+    /// Decode of the calldata; `None` for creates, whose input is init code,
+    /// and calls without a selector.
+    pub decoded: Option<DecodedCalldata>,
+    pub success: bool,
+    pub calls: Vec<ExecutionFrame>,
+}
+
+/// Inspector decoding the calldata of every call of a simulation into a tree of frames.
+///
+/// ```ignore
+/// let mut evm = Evm::builder()
+///     .with_db(db)
+///     .with_external_context(DecodingInspector::new())
+///     .append_handler_register(inspector_handle_register)
+///     .build();
+/// evm.transact()?;
+/// let frames = evm.into_context().external.into_frames();
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct DecodingInspector {
+    heuristics: Heuristics,
+    limits: Limits,
+    /// Frames started and not ended yet, innermost last.
+    stack: Vec<ExecutionFrame>,
+    /// Frames ended at the top level, one per transaction inspected.
+    frames: Vec<ExecutionFrame>,
+}
+
+impl DecodingInspector {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Decodes with only the enabled `heuristics`.
+    pub fn with_heuristics(mut self, heuristics: Heuristics) -> Self {
+        self.heuristics = heuristics;
+        self
+    }
+
+    /// Leaves calldatas over `limits` undecoded.
+    pub fn with_limits(mut self, limits: Limits) -> Self {
+        self.limits = limits;
+        self
+    }
+
+    /// Top-level frames of the transactions inspected so far.
+    pub fn frames(&self) -> &[ExecutionFrame] {
+        &self.frames
+    }
+
+    pub fn into_frames(self) -> Vec<ExecutionFrame> {
+        self.frames
+    }
+
+    fn decode(&self, input: &[u8]) -> Option<DecodedCalldata> {
+        if input.len() < 4 {
+            return None;
+        }
+        Calldata::from_bytes_with_limits(input.to_vec(), self.heuristics, self.limits)
+            .ok()
+            .map(|calldata| calldata.decode())
+    }
+
+    fn start(&mut self, frame: ExecutionFrame) {
+        self.stack.push(frame);
+    }
+
+    /// Closes the innermost frame, attaching it to the one that started it.
+    fn end(&mut self, success: bool, target: Option<Address>) {
+        let Some(mut frame) = self.stack.pop() else {
+            return;
+        };
+        frame.success = success;
+        if let Some(target) = target {
+            frame.target = Some(target.to_checksum(None));
+        }
+        match self.stack.last_mut() {
+            Some(parent) => parent.calls.push(frame),
+            None => self.frames.push(frame),
+        }
+    }
+}
+
+impl<DB: Database> Inspector<DB> for DecodingInspector {
+    fn call(&mut self, _: &mut EvmContext<DB>, inputs: &mut CallInputs) -> Option<CallOutcome> {
+        let kind = match inputs.scheme {
+            CallScheme::Call => FrameKind::Call,
+            CallScheme::CallCode => FrameKind::CallCode,
+            CallScheme::DelegateCall => FrameKind::DelegateCall,
+            CallScheme::StaticCall => FrameKind::StaticCall,
+        };
+        let frame = ExecutionFrame {
+            kind,
+            caller: inputs.caller.to_checksum(None),
+            target: Some(inputs.bytecode_address.to_checksum(None)),
+            value: inputs.value.get().to_string(),
+            decoded: self.decode(&inputs.input),
+            success: false,
+            calls: vec![],
+        };
+        self.start(frame);
+        None
+    }
+
+    fn call_end(
+        &mut self,
+        _: &mut EvmContext<DB>,
+        _: &CallInputs,
+        outcome: CallOutcome,
+    ) -> CallOutcome {
+        self.end(outcome.result.is_ok(), None);
+        outcome
+    }
+
+    fn create(
+        &mut self,
+        _: &mut EvmContext<DB>,
+        inputs: &mut CreateInputs,
+    ) -> Option<CreateOutcome> {
+        let kind = match inputs.scheme {
+            CreateScheme::Create => FrameKind::Create,
+            CreateScheme::Create2 { .. } => FrameKind::Create2,
+        };
+        let frame = ExecutionFrame {
+            kind,
+            caller: inputs.caller.to_checksum(None),
+            target: None,
+            value: inputs.value.to_string(),
+            decoded: None,
+            success: false,
+            calls: vec![],
+        };
+        self.start(frame);
+        None
+    }
+
+    fn create_end(
+        &mut self,
+        _: &mut EvmContext<DB>,
+        _: &CreateInputs,
+        outcome: CreateOutcome,
+    ) -> CreateOutcome {
+        let success = outcome.result.is_ok();
+        self.end(success, outcome.address.filter(|_| success));
+        outcome
+    }
+}
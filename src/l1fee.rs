@@ -0,0 +1,302 @@
+use crate::decoded::{DecodedCalldata, Span};
+use primitive_types::U256;
+use serde::{Deserialize, Serialize};
+
+// ------------------------------------------------------------
+//  L1 data fee
+// ------------------------------------------------------------
+
+/// Which OP Stack upgrade's formula prices the L1 data.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum FeeFormula {
+    /// `(calldata gas + overhead) * l1 base fee * scalar / 1e6`.
+    Bedrock,
+    /// Calldata gas priced at a mix of the L1 base fee and blob base fee.
+    Ecotone,
+    /// The FastLZ compressed size, through a linear regression, priced as in Ecotone.
+    #[default]
+    Fjord,
+}
+
+/// Smallest size Fjord charges for, in bytes.
+pub const FJORD_MIN_SIZE: u64 = 100;
+/// Intercept of Fjord's size regression, scaled by 1e6.
+pub const FJORD_INTERCEPT: i64 = -42_585_600;
+/// FastLZ size coefficient of Fjord's size regression, scaled by 1e6.
+pub const FJORD_FASTLZ_COEF: u64 = 836_500;
+
+/// L1 prices and the chain's fee scalars.
+///
+/// The defaults are OP Mainnet's scalars with placeholder L1 prices (1 gwei
+/// base fee, 1 wei blob base fee), to be set from the chain's `GasPriceOracle`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct L1FeeParams {
+    pub formula: FeeFormula,
+    /// L1 base fee, in wei.
+    pub l1_base_fee: u128,
+    /// L1 blob base fee, in wei (Ecotone and later).
+    pub blob_base_fee: u128,
+    /// Weight of the L1 base fee, scaled by 1e6 (Ecotone and later).
+    pub base_fee_scalar: u32,
+    /// Weight of the blob base fee, scaled by 1e6 (Ecotone and later).
+    pub blob_base_fee_scalar: u32,
+    /// Fixed gas added to every transaction (Bedrock).
+    pub overhead: u64,
+    /// Multiplier of the fee, scaled by 1e6 (Bedrock).
+    pub scalar: u64,
+    /// Bytes of the signed transaction around the calldata, counted as
+    /// incompressible non-zero bytes; the oracle's `getL1Fee` adds 68 for the
+    /// signature of the unsigned transaction it's given.
+    pub envelope_bytes: u64,
+}
+
+impl Default for L1FeeParams {
+    fn default() -> Self {
+        Self {
+            formula: FeeFormula::Fjord,
+            l1_base_fee: 1_000_000_000,
+            blob_base_fee: 1,
+            base_fee_scalar: 5_227,
+            blob_base_fee_scalar: 1_014_213,
+            overhead: 188,
+            scalar: 684_000,
+            envelope_bytes: 68,
+        }
+    }
+}
+
+/// The share of the fee paid for the bytes of one part of the calldata.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PartFee {
+    /// `selector`, `params[1]`, `calls[0].params[2]`, ... as for `--select`,
+    /// or `envelope` and `unattributed` for the bytes outside any param.
+    pub path: String,
+    pub span: Span,
+    /// Bytes of the part that aren't covered by a nested call's parts.
+    pub bytes: usize,
+    pub zero_bytes: usize,
+    /// 4 gas a zero byte, 16 a non-zero one.
+    pub calldata_gas: u64,
+    /// Fee share in wei, in proportion to the calldata gas.
+    pub fee: U256,
+}
+
+/// L1 data fee of a calldata, with its breakdown.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct L1FeeEstimate {
+    pub formula: FeeFormula,
+    /// Bytes priced, the envelope included.
+    pub bytes: usize,
+    pub zero_bytes: usize,
+    pub calldata_gas: u64,
+    /// FastLZ compressed size of the calldata plus the envelope.
+    pub fastlz_size: u64,
+    /// Size Fjord charges for, in bytes (scaled down from its regression).
+    pub estimated_size: u64,
+    /// Fee in wei.
+    pub fee: U256,
+    /// Shares of the fee per part, in calldata order.
+    pub parts: Vec<PartFee>,
+}
+
+/// 4 gas a zero byte, 16 a non-zero one.
+pub fn calldata_gas(bytes: &[u8]) -> u64 {
+    bytes.iter().map(|b| if *b == 0 { 4 } else { 16 }).sum()
+}
+
+/// Estimates the L1 data fee of `calldata` under `params`, with its share per
+/// selector and param of `root`, the decode of `calldata`.
+///
+/// The bytes of a nested call are counted in its own params rather than in
+/// the outer param holding them.
+pub fn estimate_l1_fee(
+    calldata: &[u8],
+    root: &DecodedCalldata,
+    params: &L1FeeParams,
+) -> L1FeeEstimate {
+    let zero_bytes = calldata.iter().filter(|b| **b == 0).count();
+    let gas = calldata_gas(calldata) + params.envelope_bytes * 16;
+    let fastlz_size = fastlz_compressed_len(calldata) as u64 + params.envelope_bytes;
+    let scaled_size = (FJORD_INTERCEPT as i128 + FJORD_FASTLZ_COEF as i128 * fastlz_size as i128)
+        .max(FJORD_MIN_SIZE as i128 * 1_000_000) as u128;
+
+    let l1_base_fee = U256::from(params.l1_base_fee);
+    let blob_fee = U256::from(params.blob_base_fee) * params.blob_base_fee_scalar;
+    let fee_scaled = l1_base_fee * params.base_fee_scalar * 16 + blob_fee;
+    let fee = match params.formula {
+        FeeFormula::Bedrock => {
+            U256::from(gas + params.overhead) * l1_base_fee * params.scalar / 1_000_000
+        }
+        FeeFormula::Ecotone => U256::from(gas) * fee_scaled / 16_000_000,
+        FeeFormula::Fjord => U256::from(scaled_size) * fee_scaled / 1_000_000_000_000u64,
+    };
+
+    let mut parts = attribute(calldata, root);
+    if params.envelope_bytes > 0 {
+        parts.push(PartFee {
+            path: "envelope".to_string(),
+            span: Span {
+                start: calldata.len(),
+                end: calldata.len(),
+            },
+            bytes: params.envelope_bytes as usize,
+            zero_bytes: 0,
+            calldata_gas: params.envelope_bytes * 16,
+            fee: U256::zero(),
+        });
+    }
+    for part in parts.iter_mut() {
+        part.fee = fee * part.calldata_gas / gas.max(1);
+    }
+    L1FeeEstimate {
+        formula: params.formula,
+        bytes: calldata.len() + params.envelope_bytes as usize,
+        zero_bytes,
+        calldata_gas: gas,
+        fastlz_size,
+        estimated_size: (scaled_size / 1_000_000) as u64,
+        fee,
+        parts,
+    }
+}
+
+/// Parts of the calldata, each byte going to the innermost selector or param
+/// covering it, and the bytes covered by none to `unattributed`.
+fn attribute(calldata: &[u8], root: &DecodedCalldata) -> Vec<PartFee> {
+    let mut parts = vec![];
+    let mut owner = vec![usize::MAX; calldata.len()];
+    let mut claim = |path: String, span: Span, parts: &mut Vec<PartFee>| {
+        let end = span.end.min(calldata.len());
+        for byte in owner.iter_mut().take(end).skip(span.start) {
+            *byte = parts.len();
+        }
+        parts.push(PartFee {
+            path,
+            span,
+            bytes: 0,
+            zero_bytes: 0,
+            calldata_gas: 0,
+            fee: U256::zero(),
+        });
+    };
+    // Parents claim before their children, so the nested bytes are the children's.
+    for (prefix, call) in root.walk_paths() {
+        let selector = Span {
+            start: call.span.start,
+            end: call.span.start + 4,
+        };
+        claim(format!("{}selector", prefix), selector, &mut parts);
+        for (i, param) in call.params.iter().enumerate() {
+            claim(format!("{}params[{}]", prefix, i), param.span, &mut parts);
+        }
+    }
+    let unattributed = parts.len();
+    parts.push(PartFee {
+        path: "unattributed".to_string(),
+        span: Span {
+            start: 0,
+            end: calldata.len(),
+        },
+        bytes: 0,
+        zero_bytes: 0,
+        calldata_gas: 0,
+        fee: U256::zero(),
+    });
+    for (byte, owner) in calldata.iter().zip(owner) {
+        let part = &mut parts[owner.min(unattributed)];
+        part.bytes += 1;
+        part.zero_bytes += (*byte == 0) as usize;
+        part.calldata_gas += calldata_gas(&[*byte]);
+    }
+    let mut parts = parts
+        .into_iter()
+        .filter(|p| p.bytes > 0)
+        .collect::<Vec<_>>();
+    parts.sort_by_key(|p| (p.path == "unattributed", p.span.start));
+    parts
+}
+
+// ------------------------------------------------------------
+//  FastLZ
+// ------------------------------------------------------------
+
+/// Length of `input` compressed with FastLZ level 1, as computed by Fjord's
+/// `GasPriceOracle` (Solady's `LibZip.flzCompress`), without building the output.
+pub fn fastlz_compressed_len(input: &[u8]) -> u32 {
+    let mut n = 0u32;
+    let mut table = vec![0u32; 8192];
+    let u24 = |i: u32| {
+        let i = i as usize;
+        input[i] as u32 | (input[i + 1] as u32) << 8 | (input[i + 2] as u32) << 16
+    };
+    let hash = |v: u32| (2_654_435_769u32.wrapping_mul(v) >> 19) & 0x1fff;
+    let literals = |r: u32, n: &mut u32| {
+        *n += 0x21 * (r / 0x20);
+        if !r.is_multiple_of(0x20) {
+            *n += r % 0x20 + 1;
+        }
+    };
+    let matched = |l: u32, n: &mut u32| {
+        let l = l - 1;
+        *n += 3 * (l / 262);
+        *n += match l % 262 >= 6 {
+            true => 3,
+            false => 2,
+        };
+    };
+    // Length of the match at `q` against `p`, checking up to `e`.
+    let compare = |p: u32, q: u32, e: u32| {
+        let mut l = 0;
+        let mut e = e - q;
+        while l < e {
+            if input[(p + l) as usize] != input[(q + l) as usize] {
+                e = 0;
+            }
+            l += 1;
+        }
+        l
+    };
+
+    let len = input.len() as u32;
+    let ip_limit = len.saturating_sub(13);
+    let mut anchor = 0;
+    let mut ip = 2;
+    while ip < ip_limit {
+        let mut r;
+        loop {
+            let s = u24(ip);
+            let h = hash(s) as usize;
+            r = table[h];
+            table[h] = ip;
+            let distance = ip - r;
+            if ip >= ip_limit {
+                break;
+            }
+            ip += 1;
+            if distance <= 0x1fff && s == u24(r) {
+                break;
+            }
+        }
+        if ip >= ip_limit {
+            break;
+        }
+        ip -= 1;
+        if ip > anchor {
+            literals(ip - anchor, &mut n);
+        }
+        let l = compare(r + 3, ip + 3, ip_limit + 9);
+        matched(l, &mut n);
+        ip += l;
+        table[hash(u24(ip)) as usize] = ip;
+        ip += 1;
+        table[hash(u24(ip)) as usize] = ip;
+        ip += 1;
+        anchor = ip;
+    }
+    literals(len - anchor, &mut n);
+    n
+}
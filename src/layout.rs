@@ -0,0 +1,613 @@
+use crate::constants::*;
+use crate::decoded::DecodeWarning;
+use crate::explain::{LayoutDecision, Outcome, Recorder, Role};
+use crate::heuristics::{Heuristics, Profile};
+use crate::limits::Limits;
+use crate::signatures::is_builtin_selector;
+use crate::{try_parse_selector, word_usize};
+use std::borrow::Cow;
+use std::collections::VecDeque;
+
+// ------------------------------------------------------------
+//  Layout table
+// ------------------------------------------------------------
+
+/// Score a selector-looking word after a length needs to be taken as a nested call.
+pub const MIN_CALL_SCORE: i32 = 0;
+
+/// How much more a selector-looking word after a length looks like the start
+/// of a nested call than like data, e.g. a 4-letter string.
+///
+/// ## Params
+/// 1. selector - the word's first 4 bytes.
+/// 2. len - the length declared by the word before.
+/// 3. padding - the bytes after the call, up to a full word.
+pub fn call_score(selector: &[u8], len: usize, padding: &[u8]) -> i32 {
+    call_score_parts(selector, len, padding)
+        .iter()
+        .map(|(points, _)| points)
+        .sum()
+}
+
+/// The points `call_score` adds up, each with what earned it; 0 for the
+/// criteria that didn't apply.
+pub fn call_score_parts(selector: &[u8], len: usize, padding: &[u8]) -> [(i32, &'static str); 4] {
+    let mut parts = [(0, ""); 4];
+    if is_builtin_selector(selector) {
+        parts[0] = (2, "known selector");
+    }
+    // A `bytes4` or 4-letter string has the length of a lone selector, never more.
+    if len > SELECTOR {
+        parts[1] = (1, "longer than a lone selector");
+    }
+    // Text is more often a string than a selector.
+    if selector.iter().all(|b| b.is_ascii_graphic() || *b == b' ') {
+        parts[2] = (-2, "selector is printable text");
+    }
+    // Encoders pad with zeros.
+    parts[3] = match padding.iter().all(|b| *b == 0) {
+        true => (1, "zero padded"),
+        false => (-1, "padded with non-zero bytes"),
+    };
+    parts
+}
+
+/// Something the layout pass found in the calldata.
+///
+/// `at` is the byte offset in the calldata, selector included.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Fact {
+    /// A word small and aligned enough to be the offset of a dynamic param.
+    Offset { at: usize, value: usize },
+    /// A zero word; the words after it are re-aligned by a selector's width.
+    Padding { at: usize },
+    /// The byte length of the call that follows.
+    Length { at: usize, value: usize },
+    /// A method call embedded in a `bytes` param.
+    Call {
+        at: usize,
+        /// Index, among the calls, of the one it was found in; `None` for the main method.
+        parent: Option<usize>,
+        /// Selector followed by the params.
+        bytes: Vec<u8>,
+    },
+    /// Something irregular found in a call's params, `parent` being that call as for `Call`.
+    Warning {
+        parent: Option<usize>,
+        warning: DecodeWarning,
+    },
+}
+
+/// The facts found in one pass over the calldata, with nested calls scanned in turn.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct LayoutTable {
+    pub facts: Vec<Fact>,
+}
+
+/// A nested call of a `LayoutTable`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct CallFact<'a> {
+    pub at: usize,
+    pub parent: Option<usize>,
+    pub selector: &'a [u8],
+    pub body: &'a [u8],
+    /// Selector followed by the params.
+    pub bytes: &'a [u8],
+}
+
+impl LayoutTable {
+    /// Builds the table of the params after the selector.
+    ///
+    /// Nested calls are scanned for calls of their own through a queue rather than
+    /// recursion, within `limits`.
+    ///
+    /// ## Params
+    /// 1. body - the calldata after the selector.
+    /// 2. first - length of the first word, shorter when it shares a word with the selector.
+    pub fn build(body: &[u8], first: usize, heuristics: &Heuristics, limits: &Limits) -> Self {
+        Self::build_for(body, first, heuristics, limits, None)
+    }
+
+    /// Same as `build`, for calldata sent to a contract implementing only
+    /// `targets`: a call embedded in the main method's params is taken only
+    /// when its selector is one of them, as a contract calling itself (e.g.
+    /// `multicall`) can only dispatch those. Calls nested deeper go to other
+    /// contracts and aren't checked.
+    ///
+    /// ## Params
+    /// 4. targets - sorted selectors, e.g. from `bytecode::dispatch_selectors`.
+    pub fn build_for(
+        body: &[u8],
+        first: usize,
+        heuristics: &Heuristics,
+        limits: &Limits,
+        targets: Option<&[[u8; 4]]>,
+    ) -> Self {
+        Self::build_traced(
+            body,
+            first,
+            heuristics,
+            limits,
+            targets,
+            &mut Recorder::off(),
+        )
+    }
+
+    /// Same as `build_for`, recording in `trace` what each word was taken for
+    /// and why the alternatives weren't.
+    pub(crate) fn build_traced(
+        body: &[u8],
+        first: usize,
+        heuristics: &Heuristics,
+        limits: &Limits,
+        targets: Option<&[[u8; 4]]>,
+        trace: &mut Recorder<LayoutDecision>,
+    ) -> Self {
+        let mut table = Self::default();
+
+        // Region to scan, where it starts in the calldata, the call it belongs to and its depth.
+        let mut queue = VecDeque::from([(body.to_vec(), first, SELECTOR, None, 0)]);
+        while let Some((region, first, base, parent, depth)) = queue.pop_front() {
+            let found = table.calls().count();
+            let view = View::new(&region, first, heuristics.profile);
+            // Calls found here are at `depth + 1`.
+            let nested = match (heuristics.nested, depth < limits.max_depth) {
+                (false, _) => Err((Outcome::Disabled, "the nested heuristic is disabled")),
+                (true, false) => Err((Outcome::Rejected, "calls this deep are over max_depth")),
+                (true, true) => Ok(targets.filter(|_| depth == 0)),
+            };
+            table.scan(view, base, parent, nested, limits, trace);
+
+            // Calls found here are scanned one level deeper.
+            if depth + 1 < limits.max_depth {
+                let calls = table.calls().enumerate().skip(found);
+                let bodies = calls
+                    .map(|(i, call)| (call.body.to_vec(), WORD, call.at + SELECTOR, Some(i)))
+                    .collect::<Vec<_>>();
+                for (body, first, base, parent) in bodies {
+                    queue.push_back((body, first, base, parent, depth + 1));
+                }
+            }
+        }
+        table
+    }
+
+    /// The nested calls, parents before their children.
+    pub fn calls(&self) -> impl Iterator<Item = CallFact<'_>> {
+        self.facts.iter().filter_map(|fact| match fact {
+            Fact::Call { at, parent, bytes } => Some(CallFact {
+                at: *at,
+                parent: *parent,
+                selector: &bytes[..SELECTOR],
+                body: &bytes[SELECTOR..],
+                bytes,
+            }),
+            _ => None,
+        })
+    }
+
+    /// The warnings, each with the call it was found in.
+    pub fn warnings(&self) -> impl Iterator<Item = (Option<usize>, &DecodeWarning)> {
+        self.facts.iter().filter_map(|fact| match fact {
+            Fact::Warning { parent, warning } => Some((*parent, warning)),
+            _ => None,
+        })
+    }
+
+    /// Records the facts of one region, taking the calls in it when `nested` is
+    /// `Ok` and, given targets, only theirs; `Err` says why they aren't taken.
+    fn scan(
+        &mut self,
+        mut view: View,
+        base: usize,
+        parent: Option<usize>,
+        nested: Result<Option<&[[u8; 4]]>, (Outcome, &'static str)>,
+        limits: &Limits,
+        trace: &mut Recorder<LayoutDecision>,
+    ) {
+        let decision = |at: usize, role: Role, outcome: Outcome, reason: String| LayoutDecision {
+            at,
+            parent,
+            role,
+            outcome,
+            reason,
+        };
+        let len = view.count();
+        let mut calls = self.calls().count();
+        let mut offsets = 0;
+        let mut i = 0;
+        // Facts of this region start here; irregular offsets are kept aside
+        // until the region's offsets are known.
+        let from = self.facts.len();
+        let mut past = vec![];
+        let mut back = vec![];
+        // Offsets taken so far, and the words of the arrays of offsets they lead to.
+        let mut pointed = vec![];
+        let mut arrays: Vec<(usize, usize)> = vec![];
+
+        // A last word shorter than it should be is left as bytes.
+        if let Some(last) = len.checked_sub(1) {
+            let (start, end) = (view.start(last), view.start(len));
+            let full = match last {
+                0 => view.first,
+                _ => WORD,
+            };
+            if end - start < full {
+                self.facts.push(Fact::Warning {
+                    parent,
+                    warning: DecodeWarning::UnparsedTail {
+                        at: base + start,
+                        len: end - start,
+                    },
+                });
+            }
+        }
+
+        while i < len {
+            if view.word(i).is_some_and(|w| *w == EMPTY_32) {
+                let at = base + view.position(view.start(i));
+                match view.profile {
+                    Profile::Solidity => {
+                        self.facts.push(Fact::Padding { at });
+                        trace.record(|| {
+                            let reason = "zero word, the words after it re-aligned by 4 bytes";
+                            decision(at, Role::Padding, Outcome::Fired, reason.to_string())
+                        });
+                        view.pad(i);
+                    }
+                    Profile::Vyper => trace.record(|| {
+                        let reason = "zero word, left in place: an unused entry of a Vyper array";
+                        decision(at, Role::Padding, Outcome::Rejected, reason.to_string())
+                    }),
+                }
+                i += 1;
+            }
+
+            let Some(word) = view.word(i) else {
+                break;
+            };
+
+            // An offset leading to a count followed by its first element
+            // pointing just past the count's elements confirms an array of
+            // offsets, e.g. a `bytes[]`.
+            if pointed.contains(&view.start(i)) {
+                let first = view.word(i + 1).as_deref().and_then(word_usize);
+                let count = word_usize(&word).filter(|n| (1..len - i).contains(n));
+                if let Some(count) = count.filter(|n| first == Some(n * WORD)) {
+                    arrays.push((i + 1, i + count));
+                }
+            }
+
+            // Check if param has selector in it.
+            let parsed = try_parse_selector(&word);
+            let selector = parsed.0 != EMPTY_4 && parsed.0 != MASK_4;
+            let at = base + view.position(view.start(i));
+            let targets = match (nested, calls < limits.max_calls) {
+                (Ok(targets), true) => Ok(targets),
+                (Ok(_), false) => Err((Outcome::Rejected, "max_calls calls were taken already")),
+                (Err(why), _) => Err(why),
+            };
+
+            // If selector found.
+            if let (Ok(targets), true) = (targets, selector) {
+                // Check if last param was a length type.
+                // They indicate the start of a dynamic type (string, bytes, or array).
+                let last = match i {
+                    0 => None,
+                    i => view.word(i - 1).as_deref().and_then(word_usize),
+                };
+                let reject = |reason: String| decision(at, Role::Call, Outcome::Rejected, reason);
+
+                // If remainder is a selector we know its a function.
+                let start = view.start(i);
+                // Declared lengths are checked against what's left before reading anything,
+                // `read` failing when `start + len` is past the view.
+                let read = last
+                    .filter(|len| len % WORD == SELECTOR)
+                    .map(|len| (len, view.read(start, len)));
+                match read {
+                    None => trace.record(|| {
+                        reject(match last {
+                            Some(len) => {
+                                format!("the word before is {}, not a length of 32n+4 bytes", len)
+                            }
+                            None => "the word before isn't a length of 32n+4 bytes".to_string(),
+                        })
+                    }),
+                    Some((len, None)) => {
+                        let remaining = view.remaining(start);
+                        self.facts.push(Fact::Warning {
+                            parent,
+                            warning: DecodeWarning::LengthOutOfBounds {
+                                at: base + view.position(view.start(i - 1)),
+                                declared: len,
+                                remaining,
+                            },
+                        });
+                        trace.record(|| {
+                            reject(format!(
+                                "length {} is past the end, {} bytes left",
+                                len, remaining
+                            ))
+                        });
+                    }
+                    Some((len, _)) if len > limits.max_region => {
+                        trace.record(|| reject(format!("length {} is over max_region", len)))
+                    }
+                    Some((len, Some(bytes))) => {
+                        let padding = view.padding(start + len, len);
+                        let parts = call_score_parts(&bytes[..SELECTOR], len, &padding);
+                        let score = parts.iter().map(|(points, _)| points).sum::<i32>();
+                        let reason = |verdict: &str| {
+                            let parts = parts
+                                .iter()
+                                .filter(|(points, _)| *points != 0)
+                                .map(|(points, why)| format!("{} {:+}", why, points))
+                                .collect::<Vec<_>>();
+                            format!(
+                                "{} bytes after their length, score {} ({}){}",
+                                len,
+                                score,
+                                parts.join(", "),
+                                verdict
+                            )
+                        };
+                        if score < MIN_CALL_SCORE {
+                            trace.record(|| reject(reason(", more likely data")));
+                        } else if targets.is_some_and(|t| t.binary_search(&parsed.0).is_err()) {
+                            trace
+                                .record(|| reject(reason(", but the target doesn't implement it")));
+                        } else {
+                            trace.record(|| decision(at, Role::Call, Outcome::Fired, reason("")));
+                            self.facts.push(Fact::Length {
+                                at: base + view.position(view.start(i - 1)),
+                                value: len,
+                            });
+                            self.facts.push(Fact::Call {
+                                at,
+                                parent,
+                                bytes: bytes.into_owned(),
+                            });
+                            calls += 1;
+                            if padding.iter().any(|b| *b != 0) {
+                                self.facts.push(Fact::Warning {
+                                    parent,
+                                    warning: DecodeWarning::PaddingMismatch {
+                                        at: base + view.position(start + len),
+                                        len: padding.len(),
+                                    },
+                                });
+                            }
+
+                            // A lone selector has no words to step over.
+                            let words = (len - SELECTOR) / WORD;
+                            if words > 0 {
+                                view.cut(i);
+                                i += words - 1;
+                            }
+                        }
+                    }
+                }
+            }
+            // A call the region can't take.
+            else if let (Err((outcome, why)), true) = (targets, selector) {
+                trace.record(|| decision(at, Role::Call, outcome, why.to_string()));
+            }
+            // Offsets/lengths never have selectors
+            // Therefore, we check common offset/length sizes.
+            else if let Some(v) = word_usize(&word).filter(|v| *v <= 0xffff) {
+                // Check if value is for dynamic type by checking if
+                // - below safety net length, since they probably wont go that high.
+                // - divisible by 32 bytes (0x20).
+                let near = v < i.saturating_mul(WORD).saturating_add(960);
+                if near && v.is_multiple_of(WORD) {
+                    // Whatever it's counted from, an offset can't point past the params.
+                    match v < view.len() {
+                        true => {
+                            self.facts.push(Fact::Offset { at, value: v });
+                            pointed.push(v);
+                            trace.record(|| {
+                                let reason = format!("{}, a multiple of 32 within the params", v);
+                                decision(at, Role::Offset, Outcome::Fired, reason)
+                            });
+                        }
+                        false => {
+                            let warning = DecodeWarning::OffsetOutOfBounds {
+                                at,
+                                offset: v,
+                                len: view.len(),
+                            };
+                            // The first word of the head and the elements of an
+                            // array of offsets are offsets wherever they point;
+                            // elsewhere it waits for the head to be known.
+                            match i == 0 || arrays.iter().any(|(a, b)| (*a..=*b).contains(&i)) {
+                                true => self.facts.push(Fact::Warning { parent, warning }),
+                                false => past.push(warning),
+                            }
+                            trace.record(|| {
+                                let reason =
+                                    format!("{} points past the {} bytes of params", v, view.len());
+                                decision(at, Role::Offset, Outcome::Rejected, reason)
+                            });
+                        }
+                    }
+                    // The first offset is counted from the start of the params,
+                    // and an encoder puts what it points at after the head.
+                    if v > 0 {
+                        if offsets == 0 && v <= view.start(i) {
+                            back.push((at, v));
+                        }
+                        offsets += 1;
+                    }
+                } else {
+                    trace.record(|| {
+                        let reason = match near {
+                            true => format!("{} isn't a multiple of 32", v),
+                            false => format!("{} is too far past the word to be an offset", v),
+                        };
+                        decision(at, Role::Offset, Outcome::Rejected, reason)
+                    });
+                }
+            }
+
+            i += 1;
+        }
+
+        // Any other word past the params is only reported when it sits in a
+        // head whose offsets are corroborated, i.e. point at a length taken
+        // for a call; elsewhere it's more likely an amount or an id.
+        let lengths = self.facts[from..]
+            .iter()
+            .filter_map(|fact| match fact {
+                Fact::Length { at, .. } => Some(*at),
+                _ => None,
+            })
+            .collect::<Vec<_>>();
+        let head = self.facts[from..]
+            .iter()
+            .filter_map(|fact| match fact {
+                Fact::Offset { value, .. } => Some(base + value),
+                _ => None,
+            })
+            .filter(|target| lengths.contains(target))
+            .min();
+        if let Some(head) = head {
+            for warning in past.into_iter().filter(|w| w.at() < head) {
+                self.facts.push(Fact::Warning { parent, warning });
+            }
+        }
+        // Likewise an offset pointing back is only one when a length was taken there.
+        for (at, offset) in back {
+            if lengths.contains(&(base + offset)) {
+                self.facts.push(Fact::Warning {
+                    parent,
+                    warning: DecodeWarning::NonCanonicalOffset { at, offset },
+                });
+            }
+        }
+    }
+}
+
+// ------------------------------------------------------------
+//  View
+// ------------------------------------------------------------
+
+/// The words of a region as the heuristics see them, without moving any bytes.
+///
+/// Padding a zero word or cutting a selector out shifts every later byte by 4,
+/// the region keeping its length. Instead of shifting, the view remembers
+/// which region byte its current position maps to: from `at` on, it reads
+/// `region[pos..stop]` followed by zeros.
+struct View<'a> {
+    region: &'a [u8],
+    /// Length of the first word; every word is full once the view has shifted.
+    first: usize,
+    /// Position in the view from which bytes map back to the region.
+    at: usize,
+    /// Region byte at `at`.
+    pos: usize,
+    /// Region bytes from here on were pushed off the end.
+    stop: usize,
+    /// Vyper's leaves zero words in place, Solidity's pads them.
+    profile: Profile,
+}
+
+impl<'a> View<'a> {
+    fn new(region: &'a [u8], first: usize, profile: Profile) -> Self {
+        Self {
+            region,
+            first: first.min(WORD),
+            at: 0,
+            pos: 0,
+            stop: region.len(),
+            profile,
+        }
+    }
+
+    /// Number of words, counting a trailing partial one.
+    fn count(&self) -> usize {
+        match self.region.len() {
+            0 => 0,
+            n if n <= self.first => 1,
+            n => 1 + (n - self.first).div_ceil(WORD),
+        }
+    }
+
+    /// Position of word `i` in the view.
+    fn start(&self, i: usize) -> usize {
+        match i {
+            0 => 0,
+            i => self
+                .first
+                .saturating_add((i - 1).saturating_mul(WORD))
+                .min(self.region.len()),
+        }
+    }
+
+    /// Bytes of the view.
+    fn len(&self) -> usize {
+        self.region.len()
+    }
+
+    /// Bytes of the view from position `x` on.
+    fn remaining(&self, x: usize) -> usize {
+        self.region.len().saturating_sub(x)
+    }
+
+    /// Region byte a view position maps to.
+    fn position(&self, x: usize) -> usize {
+        (self.pos + x).saturating_sub(self.at)
+    }
+
+    /// `len` bytes from position `x`, `None` past the end of the view.
+    fn read(&self, x: usize, len: usize) -> Option<Cow<'a, [u8]>> {
+        if x.checked_add(len).is_none_or(|end| end > self.region.len()) {
+            return None;
+        }
+        let from = self.position(x);
+        if x >= self.at && from + len <= self.stop {
+            return Some(Cow::Borrowed(&self.region[from..from + len]));
+        }
+        let bytes = (x..x + len)
+            .map(|x| match x >= self.at && self.position(x) < self.stop {
+                true => self.region[self.position(x)],
+                false => 0,
+            })
+            .collect();
+        Some(Cow::Owned(bytes))
+    }
+
+    /// The zeros a `len` byte call ending at position `x` is padded with up to
+    /// a full word, or as many bytes as there are before the end.
+    fn padding(&self, x: usize, len: usize) -> Cow<'a, [u8]> {
+        let pad = (WORD - len % WORD) % WORD;
+        self.read(x, pad.min(self.remaining(x))).unwrap_or_default()
+    }
+
+    fn word(&self, i: usize) -> Option<Cow<'a, [u8]>> {
+        match i < self.count() {
+            true => self.read(self.start(i), self.start(i + 1) - self.start(i)),
+            false => None,
+        }
+    }
+
+    /// Pads word `i` with 4 zero bytes at the front, dropping as many off the end.
+    fn pad(&mut self, i: usize) {
+        let at = self.start(i);
+        let pad = SELECTOR.min(self.region.len() - at);
+        let pos = self.position(at);
+        self.stop = self.stop.min(pos + self.region.len() - at - pad);
+        self.at = at + pad;
+        self.pos = pos;
+        self.first = WORD;
+    }
+
+    /// Cuts the selector out of word `i`, pulling the bytes after it forward.
+    fn cut(&mut self, i: usize) {
+        let at = self.start(i);
+        let cut = SELECTOR.min(self.region.len() - at);
+        self.pos = self.position(at) + cut;
+        self.at = at;
+        self.first = WORD;
+    }
+}
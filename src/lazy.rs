@@ -0,0 +1,170 @@
+use crate::constants::*;
+use crate::decoded::{DecodeWarning, DecodedCalldata};
+use crate::heuristics::Heuristics;
+use crate::layout::LayoutTable;
+use crate::limits::Limits;
+use crate::protocol::ProtocolRegistry;
+use crate::prototypes::top_signature;
+use crate::{decode_hex, first_word, word_details, Calldata, Words};
+use std::sync::OnceLock;
+
+// ------------------------------------------------------------
+//  Lazy decoding
+// ------------------------------------------------------------
+
+/// A method call whose params and nested calls are only parsed when first asked for.
+///
+/// For monitoring, where most transactions are dropped after a look at their
+/// selector: `selector` is free, `node` guesses this call's params, `calls`
+/// scans them for nested calls, each nested call being lazy in turn.
+///
+/// `limits.max_calls` applies to each call's own children rather than the whole tree.
+#[derive(Debug)]
+pub struct LazyCall {
+    /// Selector followed by the params.
+    bytes: Vec<u8>,
+    /// Byte offset of the selector in the original calldata.
+    start: usize,
+    /// Length of the first word of the params.
+    first: usize,
+    heuristics: Heuristics,
+    limits: Limits,
+    node: OnceLock<DecodedCalldata>,
+    /// Nested calls and the warnings found looking for them.
+    calls: OnceLock<(Vec<LazyCall>, Vec<DecodeWarning>)>,
+}
+
+impl LazyCall {
+    pub fn new(calldata: &str) -> Self {
+        Self::from_bytes(decode_hex(calldata))
+    }
+
+    pub fn from_bytes(calldata: Vec<u8>) -> Self {
+        Self::with_limits(calldata, Heuristics::default(), Limits::default())
+    }
+
+    /// Same as `from_bytes`, applying only the enabled `heuristics` within `limits`.
+    pub fn with_limits(calldata: Vec<u8>, heuristics: Heuristics, limits: Limits) -> Self {
+        let first = first_word(calldata.len());
+        Self::nested(calldata, 0, first, heuristics, limits)
+    }
+
+    fn nested(
+        bytes: Vec<u8>,
+        start: usize,
+        first: usize,
+        heuristics: Heuristics,
+        limits: Limits,
+    ) -> Self {
+        Self {
+            bytes,
+            start,
+            first,
+            heuristics,
+            limits,
+            node: OnceLock::new(),
+            calls: OnceLock::new(),
+        }
+    }
+
+    /// The 4-byte selector, shorter when the calldata is.
+    pub fn selector(&self) -> &[u8] {
+        &self.bytes[..SELECTOR.min(self.bytes.len())]
+    }
+
+    /// Byte offset of the selector in the original calldata.
+    pub fn start(&self) -> usize {
+        self.start
+    }
+
+    /// Whether `calls` has been parsed yet.
+    pub fn is_parsed(&self) -> bool {
+        self.calls.get().is_some()
+    }
+
+    /// This call with its params, without its nested calls.
+    pub fn node(&self) -> &DecodedCalldata {
+        self.node.get_or_init(|| {
+            let words = Words::new(self.body().to_vec(), self.first);
+            let details = word_details(self.selector(), words.iter(), &self.heuristics);
+            DecodedCalldata::from_params(&details, self.start)
+        })
+    }
+
+    /// The calls embedded in this call's params, parsed on first access.
+    pub fn calls(&self) -> &[LazyCall] {
+        &self.parsed().0
+    }
+
+    /// Irregularities found in this call's params, parsed along with `calls`.
+    pub fn warnings(&self) -> &[DecodeWarning] {
+        &self.parsed().1
+    }
+
+    fn parsed(&self) -> &(Vec<LazyCall>, Vec<DecodeWarning>) {
+        self.calls.get_or_init(|| {
+            // Only this call's own children; theirs are found when asked for.
+            let limits = Limits {
+                max_depth: self.limits.max_depth.min(1),
+                ..self.limits
+            };
+            let remaining = Limits {
+                max_depth: self.limits.max_depth.saturating_sub(1),
+                ..self.limits
+            };
+            let layout = LayoutTable::build(self.body(), self.first, &self.heuristics, &limits);
+            let calls = layout
+                .calls()
+                .map(|call| {
+                    let bytes = [call.selector, call.body].concat();
+                    let start = call.at + self.start;
+                    Self::nested(bytes, start, WORD, self.heuristics, remaining)
+                })
+                .collect();
+            let warnings = layout
+                .warnings()
+                .map(|(_, warning)| warning.clone().shifted(self.start))
+                .collect();
+            (calls, warnings)
+        })
+    }
+
+    /// Every selector of the tree, depth-first, parsing only as far as needed.
+    pub fn any_selector(&self, mut f: impl FnMut(&[u8]) -> bool) -> bool {
+        self.any_selector_dyn(&mut f)
+    }
+
+    fn any_selector_dyn(&self, f: &mut dyn FnMut(&[u8]) -> bool) -> bool {
+        f(self.selector()) || self.calls().iter().any(|call| call.any_selector_dyn(f))
+    }
+
+    /// The full tree, same as `Calldata::decode`: when a protocol decoder
+    /// claims a call of the tree, it's decoded again through the registry.
+    pub fn decode(&self) -> DecodedCalldata {
+        let protocols = ProtocolRegistry::builtin();
+        if self.claimed(&protocols) {
+            let bytes = self.bytes.clone();
+            let calldata = Calldata::from_bytes_with_limits(bytes, self.heuristics, self.limits);
+            if let Ok(calldata) = calldata {
+                let mut root = calldata.decode();
+                root.shift(self.start);
+                return root;
+            }
+        }
+        let mut root = self.node().clone();
+        root.calls = self.calls().iter().map(|call| call.decode()).collect();
+        root.warnings = self.warnings().to_vec();
+        root.signature_guess = top_signature(&root.selector, &root.params, root.calls.len());
+        root
+    }
+
+    /// Whether a decoder of `protocols` takes this call or one in it.
+    fn claimed(&self, protocols: &ProtocolRegistry) -> bool {
+        protocols.claimer(&self.bytes).is_some()
+            || self.calls().iter().any(|call| call.claimed(protocols))
+    }
+
+    fn body(&self) -> &[u8] {
+        &self.bytes[self.selector().len()..]
+    }
+}
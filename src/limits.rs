@@ -0,0 +1,58 @@
+use serde::{Deserialize, Serialize};
+
+// ------------------------------------------------------------
+//  Limits
+// ------------------------------------------------------------
+
+/// Bounds on how much work the decoder does for one calldata, so adversarial
+/// payloads can't make it run away.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Limits {
+    /// Deepest level of nested calls extracted, 1 being calls inside the main method.
+    pub max_depth: usize,
+    /// Most nested calls extracted over the whole calldata.
+    pub max_calls: usize,
+    /// Largest calldata decoded, in bytes.
+    pub max_input: usize,
+    /// Largest declared length honored for a nested call, in bytes; longer ones are left as words.
+    pub max_region: usize,
+    /// Most calls and params in a decode tree.
+    pub max_nodes: usize,
+}
+
+impl Default for Limits {
+    fn default() -> Self {
+        Self {
+            max_depth: 8,
+            max_calls: 1024,
+            max_input: 16 << 20,
+            max_region: 1 << 20,
+            max_nodes: 1 << 20,
+        }
+    }
+}
+
+impl Limits {
+    /// Fails when a calldata of `len` bytes is over `max_input`.
+    pub fn check_input(&self, len: usize) -> Result<(), String> {
+        match len > self.max_input {
+            true => Err(format!(
+                "calldata is {} bytes, over the limit of {} (`max_input`)",
+                len, self.max_input
+            )),
+            false => Ok(()),
+        }
+    }
+
+    /// Fails when a decode tree of `nodes` calls and params is over `max_nodes`.
+    pub fn check_nodes(&self, nodes: usize) -> Result<(), String> {
+        match nodes > self.max_nodes {
+            true => Err(format!(
+                "decode has {} calls and params, over the limit of {} (`max_nodes`)",
+                nodes, self.max_nodes
+            )),
+            false => Ok(()),
+        }
+    }
+}
@@ -0,0 +1,418 @@
+use crate::abiblob::decode_abi_blob_with;
+use crate::constants::Types;
+use crate::decoded::{
+    candidates, render_value, DecodeWarning, DecodedCalldata, DecodedParam, Span,
+};
+use crate::heuristics::Heuristics;
+use crate::limits::Limits;
+use crate::signatures::{head_types, is_value_type, Layout};
+use crate::type_guesser::ParamTypes;
+use crate::{decode_hex_checked, guess_word_type, keccak256};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+
+// ------------------------------------------------------------
+//  Event signatures
+// ------------------------------------------------------------
+
+/// Events known out of the box, `indexed` marking the params in the topics.
+pub const BUILTIN_EVENTS: [&str; 15] = [
+    "Transfer(address indexed,address indexed,uint256)",
+    "Transfer(address indexed,address indexed,uint256 indexed)",
+    "Approval(address indexed,address indexed,uint256)",
+    "Approval(address indexed,address indexed,uint256 indexed)",
+    "ApprovalForAll(address indexed,address indexed,bool)",
+    "TransferSingle(address indexed,address indexed,address indexed,uint256,uint256)",
+    "TransferBatch(address indexed,address indexed,address indexed,uint256[],uint256[])",
+    "Deposit(address indexed,uint256)",
+    "Withdrawal(address indexed,uint256)",
+    "OwnershipTransferred(address indexed,address indexed)",
+    "Upgraded(address indexed)",
+    "Sync(uint112,uint112)",
+    "Swap(address indexed,uint256,uint256,uint256,uint256,address indexed)",
+    "Swap(address indexed,address indexed,int256,int256,uint160,uint128,int24)",
+    "PairCreated(address indexed,address indexed,address,uint256)",
+];
+
+/// An event declaration: its canonical signature and which inputs are indexed.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct EventSignature {
+    /// Canonical signature, the preimage of topic0, e.g. `Transfer(address,address,uint256)`.
+    pub signature: String,
+    /// Solidity type of each input.
+    pub inputs: Vec<String>,
+    /// Whether each input is in the topics rather than the data.
+    pub indexed: Vec<bool>,
+}
+
+impl EventSignature {
+    /// Parses a declaration such as `Transfer(address indexed from,address indexed to,uint256)`,
+    /// `None` if it isn't one.
+    pub fn parse(declaration: &str) -> Option<Self> {
+        let (name, rest) = declaration.trim().split_once('(')?;
+        let args = rest.strip_suffix(')')?;
+        let (mut types, mut indexed) = (vec![], vec![]);
+        if !args.trim().is_empty() {
+            for arg in args.split(',') {
+                let mut words = arg.split_whitespace();
+                types.push(words.next()?);
+                indexed.push(words.any(|w| w == "indexed"));
+            }
+        }
+        let layout = Layout::parse(&format!("{}({})", name, types.join(",")))?;
+        Some(Self {
+            signature: format!("{}({})", name.trim(), layout.inputs.join(",")),
+            inputs: layout.inputs,
+            indexed,
+        })
+    }
+
+    /// Hash of the signature (64 hex chars, no prefix), the topic0 of its logs.
+    pub fn topic0(&self) -> String {
+        faster_hex::hex_string(&keccak256(self.signature.as_bytes()))
+    }
+
+    /// Type of each indexed input as its topic holds it: values as is, the
+    /// rest (strings, bytes, arrays and tuples) as the `bytes32` of their hash.
+    pub fn topic_types(&self) -> Vec<String> {
+        let inputs = self.inputs.iter().zip(self.indexed.iter());
+        inputs
+            .filter(|(_, indexed)| **indexed)
+            .map(|(ty, _)| match is_value_type(ty) {
+                true => ty.clone(),
+                false => "bytes32".to_string(),
+            })
+            .collect()
+    }
+
+    /// Type of each head word of the data.
+    pub fn data_head_types(&self) -> Vec<String> {
+        let inputs = self.inputs.iter().zip(self.indexed.iter());
+        inputs
+            .filter(|(_, indexed)| !**indexed)
+            .flat_map(|(ty, _)| head_types(ty).unwrap_or_default())
+            .collect()
+    }
+
+    fn indexed_count(&self) -> usize {
+        self.indexed.iter().filter(|i| **i).count()
+    }
+}
+
+/// Known events by topic0.
+#[derive(Debug, Clone, Default)]
+pub struct EventSignatures(BTreeMap<String, Vec<EventSignature>>);
+
+impl EventSignatures {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Table of the `BUILTIN_EVENTS`.
+    pub fn builtin() -> Self {
+        let mut events = Self::new();
+        for declaration in BUILTIN_EVENTS {
+            let _ = events.insert(declaration);
+        }
+        events
+    }
+
+    /// Adds an event declaration under its topic0.
+    pub fn insert(&mut self, declaration: &str) -> Result<(), String> {
+        let event = EventSignature::parse(declaration)
+            .ok_or_else(|| format!("not an event declaration: {}", declaration))?;
+        let entry = self.0.entry(event.topic0()).or_default();
+        if !entry.contains(&event) {
+            entry.push(event);
+        }
+        Ok(())
+    }
+
+    /// Parses one declaration per line, `#` starting a comment:
+    ///
+    /// ```text
+    /// Transfer(address indexed,address indexed,uint256)
+    /// ```
+    pub fn from_text(text: &str) -> Result<Self, String> {
+        let mut events = Self::new();
+        events.insert_text(text)?;
+        Ok(events)
+    }
+
+    /// Adds the declarations of `text`, in the format of [`Self::from_text`].
+    pub fn insert_text(&mut self, text: &str) -> Result<(), String> {
+        for line in text.lines() {
+            let line = line.split('#').next().unwrap_or_default().trim();
+            if !line.is_empty() {
+                self.insert(line)?;
+            }
+        }
+        Ok(())
+    }
+
+    /// Events whose topic0 is `topic0` (64 hex chars, with or without `0x`).
+    pub fn resolve(&self, topic0: &str) -> &[EventSignature] {
+        let topic0 = topic0.trim_start_matches("0x").to_lowercase();
+        self.0
+            .get(&topic0)
+            .map(|e| e.as_slice())
+            .unwrap_or_default()
+    }
+
+    pub fn len(&self) -> usize {
+        self.0.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+}
+
+// ------------------------------------------------------------
+//  Log decoding
+// ------------------------------------------------------------
+
+/// An event log as emitted: up to 4 topics and the data.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct EventLog {
+    pub topics: Vec<[u8; 32]>,
+    pub data: Vec<u8>,
+}
+
+impl EventLog {
+    /// Parses hex topics of 32 bytes each and hex data, with or without the `0x` prefix.
+    pub fn from_hex(topics: &[impl AsRef<str>], data: &str) -> Result<Self, String> {
+        if topics.len() > 4 {
+            return Err(format!("a log has at most 4 topics, got {}", topics.len()));
+        }
+        let topics = topics
+            .iter()
+            .enumerate()
+            .map(|(i, topic)| {
+                let bytes = parse_hex(&format!("topic {}", i), topic.as_ref())?;
+                let len = bytes.len();
+                <[u8; 32]>::try_from(bytes)
+                    .map_err(|_| format!("topic {} is {} bytes, not 32", i, len))
+            })
+            .collect::<Result<_, String>>()?;
+        Ok(Self {
+            topics,
+            data: parse_hex("data", data)?,
+        })
+    }
+}
+
+/// Hex of any length, empty included.
+fn parse_hex(field: &str, hex: &str) -> Result<Vec<u8>, String> {
+    decode_hex_checked(hex).map_err(|e| format!("{}: {}", field, e))
+}
+
+/// Decode tree of a log: the indexed params in the topics, and the data
+/// decoded as the params of a call, nested calls included.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DecodedLog {
+    /// Event id (64 hex chars, no prefix), `None` for a log without topics.
+    pub topic0: Option<String>,
+    /// Known events matching topic0 and the number of topics.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub events: Vec<String>,
+    /// The first known event, else a signature built from each param's most
+    /// likely type: `event_<topic0>(address indexed,uint256)`.
+    pub signature_guess: String,
+    /// Params in the topics after topic0, `index` being the topic's and
+    /// `span` the bytes within it.
+    pub topics: Vec<DecodedParam>,
+    /// Params of the data, spans being byte offsets in the data.
+    pub params: Vec<DecodedParam>,
+    /// Method calls embedded in the data's params.
+    pub calls: Vec<DecodedCalldata>,
+    /// Irregularities found in the data.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub warnings: Vec<DecodeWarning>,
+}
+
+/// Decodes logs under one set of heuristics, limits and known events.
+#[derive(Debug, Clone)]
+pub struct LogDecoder {
+    heuristics: Heuristics,
+    limits: Limits,
+    events: EventSignatures,
+}
+
+impl Default for LogDecoder {
+    fn default() -> Self {
+        Self {
+            heuristics: Heuristics::default(),
+            limits: Limits::default(),
+            events: EventSignatures::builtin(),
+        }
+    }
+}
+
+impl LogDecoder {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn with_heuristics(mut self, heuristics: Heuristics) -> Self {
+        self.heuristics = heuristics;
+        self
+    }
+
+    pub fn with_limits(mut self, limits: Limits) -> Self {
+        self.limits = limits;
+        self
+    }
+
+    /// Replaces the builtin events.
+    pub fn with_events(mut self, events: EventSignatures) -> Self {
+        self.events = events;
+        self
+    }
+
+    /// Decodes `log`, typing its params by a known event when topic0 is one
+    /// and with the calldata heuristics otherwise.
+    ///
+    /// ## Returns
+    /// 1. The decode, or an error when the data is over the limits.
+    pub fn decode(&self, log: &EventLog) -> Result<DecodedLog, String> {
+        let topic0 = log.topics.first().map(|t| faster_hex::hex_string(t));
+        let indexed = log.topics.len().saturating_sub(1);
+        let events = match topic0.as_deref() {
+            Some(topic0) => self.events.resolve(topic0),
+            None => &[],
+        };
+        let event = events.iter().find(|e| e.indexed_count() == indexed);
+
+        let mut topics = log
+            .topics
+            .iter()
+            .enumerate()
+            .skip(1)
+            .map(|(index, topic)| self.topic_param(index, topic))
+            .collect::<Vec<_>>();
+
+        let mut data = decode_abi_blob_with(&log.data, self.heuristics, self.limits)?;
+
+        if let Some(event) = event {
+            for (param, ty) in topics.iter_mut().zip(event.topic_types()) {
+                param.prefer(&ty);
+            }
+            for (param, ty) in data.params.iter_mut().zip(event.data_head_types()) {
+                param.prefer(&ty);
+            }
+        }
+
+        let signature_guess = match event {
+            Some(event) => event.signature.clone(),
+            None => guess_signature(topic0.as_deref(), &topics, &data.params),
+        };
+        Ok(DecodedLog {
+            events: events
+                .iter()
+                .filter(|e| e.indexed_count() == indexed)
+                .map(|e| e.signature.clone())
+                .collect(),
+            topic0,
+            signature_guess,
+            topics,
+            params: data.params,
+            calls: data.calls,
+            warnings: data.warnings,
+        })
+    }
+
+    fn topic_param(&self, index: usize, topic: &[u8; 32]) -> DecodedParam {
+        let types = topic_types(topic, &self.heuristics);
+        let raw = faster_hex::hex_string(topic);
+        DecodedParam {
+            index,
+            value: render_value(&types, &raw),
+            types: candidates(&types, &raw),
+            raw,
+            span: Span { start: 0, end: 32 },
+            text: None,
+            signature: None,
+            label: None,
+            amount: None,
+        }
+    }
+}
+
+/// Candidate types of an indexed topic, as the calldata heuristics type a
+/// word, but a hash first for a word they can't place that fills all 32
+/// bytes: indexed strings, bytes and arrays are kept as their keccak256.
+fn topic_types(topic: &[u8; 32], heuristics: &Heuristics) -> ParamTypes {
+    let types = guess_word_type(topic, heuristics);
+    match topic[0] != 0 && types.types() == [Types::Uint, Types::Int, Types::Bytes] {
+        true => ParamTypes::new(vec![Types::Bytes, Types::Uint]),
+        false => types,
+    }
+}
+
+// ------------------------------------------------------------
+//  Log data
+// ------------------------------------------------------------
+
+/// The topics and data of a log, e.g. an entry of `eth_getLogs`: the
+/// counterpart of `Calldata` for logs, the data decoded as params with no
+/// selector in front and the topics as single words.
+#[derive(Debug, Clone)]
+pub struct LogData {
+    log: EventLog,
+    decoder: LogDecoder,
+}
+
+impl LogData {
+    /// Parses hex topics of 32 bytes each and hex data, with or without the
+    /// `0x` prefix, decoded with the default heuristics, limits and known events.
+    pub fn new(topics: &[impl AsRef<str>], data: &str) -> Result<Self, String> {
+        Ok(Self::from_log(EventLog::from_hex(topics, data)?))
+    }
+
+    /// Same as `new`, for a log that's already parsed.
+    pub fn from_log(log: EventLog) -> Self {
+        Self {
+            log,
+            decoder: LogDecoder::new(),
+        }
+    }
+
+    /// Decodes under `decoder`'s heuristics, limits and known events.
+    pub fn with_decoder(mut self, decoder: LogDecoder) -> Self {
+        self.decoder = decoder;
+        self
+    }
+
+    pub fn log(&self) -> &EventLog {
+        &self.log
+    }
+
+    /// Decode tree of the log (see `LogDecoder::decode`).
+    pub fn decode(&self) -> Result<DecodedLog, String> {
+        self.decoder.decode(&self.log)
+    }
+}
+
+/// `event_<topic0 prefix>(t0 indexed,...,d0,...)` from each param's most likely type.
+fn guess_signature(
+    topic0: Option<&str>,
+    topics: &[DecodedParam],
+    params: &[DecodedParam],
+) -> String {
+    let solidity = |p: &DecodedParam| match p.types.first() {
+        Some(t) => t.solidity.clone(),
+        None => "bytes32".to_string(),
+    };
+    let types = topics
+        .iter()
+        .map(|p| format!("{} indexed", solidity(p)))
+        .chain(params.iter().map(solidity))
+        .collect::<Vec<_>>();
+    match topic0 {
+        Some(topic0) => format!("event_{}({})", &topic0[..8], types.join(",")),
+        None => format!("anonymous({})", types.join(",")),
+    }
+}
@@ -0,0 +1,1909 @@
+use calldata_decoder::abiblob::{decode_abi_blob_with, DecodedAbi};
+use calldata_decoder::advisor::advise;
+use calldata_decoder::alert::{post_webhook, run_command, Alert, Alerter, Rules};
+use calldata_decoder::arbitrum::{
+    batch_data, sequencer_inbox_name, SequencerBatch, SequencerDecoder,
+};
+use calldata_decoder::batcher::{is_batch_inbox, BatcherDecoder, DecodedChannel};
+use calldata_decoder::blob::{blob_payload, read_blobs, BlobContent, BlobDecoder};
+use calldata_decoder::bundle::{parse_bundle, DecodedBundle};
+use calldata_decoder::bytecode::{compiler_profile, dispatch_selectors};
+use calldata_decoder::codegen::{infer_signature, render_decoder};
+use calldata_decoder::config::Config;
+use calldata_decoder::decoded::{DecodeStatus, DecodedCalldata, DecodedParam, Span};
+use calldata_decoder::differential::{differential, load_abi, AccuracyReport};
+use calldata_decoder::encoder::encode_call;
+use calldata_decoder::heuristics::Profile;
+use calldata_decoder::l1fee::{estimate_l1_fee, FeeFormula, L1FeeParams};
+use calldata_decoder::logs::{DecodedLog, EventLog, EventSignatures, LogDecoder};
+use calldata_decoder::mutate::mutations;
+use calldata_decoder::protocol::ProtocolRegistry;
+use calldata_decoder::prototypes::rank_prototypes;
+use calldata_decoder::query::Query;
+use calldata_decoder::rawtx::{DecodedRawTransaction, RawTransaction};
+use calldata_decoder::redact::{RedactKind, RedactMode, Redactor};
+use calldata_decoder::render::address::{apply_address_case, AddressCase};
+use calldata_decoder::render::color::{ColorMode, Style};
+use calldata_decoder::render::csv;
+use calldata_decoder::render::labels::{apply_labels, Labels};
+use calldata_decoder::render::output::{
+    render_abi_output, render_advice, render_blob, render_block, render_bundle, render_channels,
+    render_disambiguation, render_explanation, render_l1_fee, render_log_output, render_mutations,
+    render_output, render_raw_transaction, render_redaction, render_return_output,
+    render_sequencer_batch, render_stats, render_transaction, OutputFormat,
+};
+use calldata_decoder::repl::{Reply, Session};
+use calldata_decoder::returndata::{DecodedReturn, ReturnDecoder, ReturnSignatures};
+use calldata_decoder::rpc::{
+    decode_block, decode_transaction_resolved, disambiguate, fetch_transaction, http_provider,
+    ws_provider, DecodedTransaction,
+};
+use calldata_decoder::schema::JsonOutput;
+use calldata_decoder::select::{parse_path, project, projection_header, render_projection};
+use calldata_decoder::signatures::{find_selectors, SignatureMap, Signatures};
+use calldata_decoder::stats::BatchStats;
+use calldata_decoder::stream::{render_region, Region, StreamDecoder};
+use calldata_decoder::summary::summarize;
+use calldata_decoder::tokens::{apply_token_amount, chain_id, Tokens};
+use calldata_decoder::watch::{watch, TxFilter, WatchMode};
+use calldata_decoder::{check_hex, decode_hex, decode_hex_checked, Calldata};
+use clap::{Parser, Subcommand};
+use ethers::abi::Abi;
+use std::cell::Cell;
+use std::hash::BuildHasher;
+use std::io::{Read, Write};
+use std::path::PathBuf;
+
+/// A black-box raw calldata decoder using only calldata.
+#[derive(Parser)]
+#[command(
+    name = "calldata-decoder",
+    version,
+    about,
+    after_help = "Exit codes: 0 decoded, 1 error, 2 partially decoded, 3 invalid input."
+)]
+struct Cli {
+    /// Config file, `~/.config/calldata-decoder/config.toml` by default.
+    #[arg(long, global = true, env = "CALLDATA_DECODER_CONFIG")]
+    config: Option<PathBuf>,
+    /// Output format, `pretty` by default.
+    #[arg(long, global = true, value_enum)]
+    format: Option<Format>,
+    /// Shorthand for `--format json`.
+    #[arg(long, global = true, conflicts_with_all = ["format", "pretty"])]
+    json: bool,
+    /// Shorthand for `--format pretty`.
+    #[arg(long, global = true, conflicts_with = "format")]
+    pretty: bool,
+    /// Only print these fields of each decode, e.g. `selector,params[0].value`.
+    #[arg(long, global = true, value_delimiter = ',')]
+    select: Vec<String>,
+    /// Write address params in lowercase instead of EIP-55 checksummed.
+    #[arg(long, global = true)]
+    lowercase_addresses: bool,
+    /// Tune the heuristics to a compiler's patterns, over the config's profile and
+    /// the one `--bytecode` shows.
+    #[arg(long, global = true, value_enum)]
+    profile: Option<Compiler>,
+    /// WASM protocol decoder plugin, a `.wasm` file or a directory of them, on top of the
+    /// config's `plugins`. Repeatable.
+    #[arg(long = "plugin", global = true)]
+    plugins: Vec<PathBuf>,
+    #[command(subcommand)]
+    command: Command,
+}
+
+#[derive(Subcommand)]
+enum Command {
+    /// Decode raw calldata and print it as a tree.
+    Decode {
+        /// Calldata hex, with or without the `0x` prefix. `-` reads one calldata per line from stdin.
+        #[arg(required_unless_present = "file")]
+        calldata: Option<String>,
+        /// Decode one calldata per line of this file.
+        #[arg(long, visible_alias = "input-file", conflicts_with = "calldata")]
+        file: Option<PathBuf>,
+        /// Read the file (or stdin with `-`) as one raw-bytes calldata instead of hex lines.
+        #[arg(long)]
+        binary: bool,
+        /// Decode the file (or stdin with `-`) as one calldata, printing each word as it's read.
+        /// For payloads too large to hold; nested calls aren't split out.
+        #[arg(long)]
+        stream: bool,
+        /// Memory-map the file and decode its calldatas in parallel, for corpora larger than memory.
+        /// With `--binary`, the file holds raw calldatas each preceded by a 4-byte big-endian length.
+        #[cfg(feature = "batch")]
+        #[arg(long, requires = "file", conflicts_with = "stream")]
+        mmap: bool,
+        /// File holding the target's runtime bytecode as hex (e.g. from `cast code`). Calls nested
+        /// in the params are then only taken when the target implements their selector.
+        #[arg(long, conflicts_with = "stream")]
+        bytecode: Option<PathBuf>,
+        /// JSON ABI, or a build artifact with an `abi` field. Calls with a function in it are
+        /// decoded exactly, the others with the heuristics.
+        #[arg(long, conflicts_with = "stream")]
+        abi: Option<PathBuf>,
+        /// Only print the decodes with a call matching this query, e.g.
+        /// `select calls where selector == 0x095ea7b3 and params[1] > 1e30`.
+        #[arg(long, conflicts_with = "stream")]
+        query: Option<String>,
+        /// When to color the output.
+        #[arg(long, value_enum)]
+        color: Option<Color>,
+    },
+    /// Fetch a transaction from an RPC endpoint and decode its input.
+    #[command(visible_alias = "tx")]
+    DecodeTx {
+        /// HTTP JSON-RPC endpoint, defaults to the config's endpoint for `--chain`.
+        #[arg(long, visible_alias = "rpc-url", env = "ETH_RPC_URL")]
+        rpc: Option<String>,
+        /// Chain whose configured endpoint to use.
+        #[arg(long)]
+        chain: Option<String>,
+        /// Transaction hash.
+        hash: String,
+        /// When the transaction is sent to a proxy (EIP-1967, beacon or EIP-1167), resolve its
+        /// implementation at the transaction's block and limit nested calls to its selectors.
+        #[arg(long)]
+        resolve_proxy: bool,
+        /// When to color the output.
+        #[arg(long, value_enum)]
+        color: Option<Color>,
+    },
+    /// Fetch every transaction of a block, decode them in parallel and group them by selector.
+    Block {
+        /// HTTP JSON-RPC endpoint, defaults to the config's endpoint for `--chain`.
+        #[arg(long, visible_alias = "rpc-url", env = "ETH_RPC_URL")]
+        rpc: Option<String>,
+        /// Chain whose configured endpoint to use.
+        #[arg(long)]
+        chain: Option<String>,
+        /// Block number.
+        number: u64,
+        /// Extra signatures, one per line (`signature` or `0xselector signature`) or
+        /// a `.json` / `.csv` table.
+        #[arg(long)]
+        signatures: Option<PathBuf>,
+        /// Only print the per-selector summary.
+        #[arg(long)]
+        summary: bool,
+        /// When to color the output.
+        #[arg(long, value_enum)]
+        color: Option<Color>,
+    },
+    /// Stream new transactions matching the filters and print their decodes.
+    Watch {
+        /// HTTP or WebSocket JSON-RPC endpoint, defaults to the config's endpoint for `--chain`.
+        #[arg(long, visible_alias = "rpc-url", env = "ETH_RPC_URL")]
+        rpc: Option<String>,
+        /// Chain whose configured endpoint to use.
+        #[arg(long)]
+        chain: Option<String>,
+        /// Only transactions calling this selector (repeatable).
+        #[arg(long)]
+        selector: Vec<String>,
+        /// Only transactions sent to this address (repeatable).
+        #[arg(long)]
+        to: Vec<String>,
+        /// Also match selectors of nested calls.
+        #[arg(long)]
+        nested: bool,
+        /// Watch the mempool instead of new blocks.
+        #[arg(long)]
+        pending: bool,
+        /// Alert rules (TOML): only print the transactions a rule matches, and run its
+        /// webhook or command.
+        #[arg(long, conflicts_with_all = ["selector", "to", "nested"])]
+        rules: Option<PathBuf>,
+        /// When to color the output.
+        #[arg(long, value_enum)]
+        color: Option<Color>,
+    },
+    /// Decode an event log from its topics and data.
+    DecodeLog {
+        /// Topic hex, topic0 first (repeatable, at most 4).
+        #[arg(long = "topic")]
+        topics: Vec<String>,
+        /// Data hex, with or without the `0x` prefix.
+        #[arg(long, default_value = "")]
+        data: String,
+        /// Extra event declarations, one per line, e.g. `Transfer(address indexed,address indexed,uint256)`.
+        #[arg(long)]
+        events: Option<PathBuf>,
+        /// When to color the output.
+        #[arg(long, value_enum)]
+        color: Option<Color>,
+    },
+    /// Decode a raw signed transaction: its envelope and its input.
+    DecodeRaw {
+        /// Raw transaction hex, e.g. from `eth_getRawTransactionByHash`.
+        raw: String,
+        /// When to color the output.
+        #[arg(long, value_enum)]
+        color: Option<Color>,
+    },
+    /// Decode the transactions of an MEV bundle in order and list the addresses they share.
+    DecodeBundle {
+        /// File holding the bundle's raw transactions: a JSON array, `eth_sendBundle`'s
+        /// params or request, or one per line. `-` reads stdin.
+        file: PathBuf,
+        /// When to color the output.
+        #[arg(long, value_enum)]
+        color: Option<Color>,
+    },
+    /// Decode the transactions of a CSV or Parquet dump (columns `hash`, `to`, `input`) in parallel.
+    #[cfg(feature = "batch")]
+    DecodeDump {
+        /// `.csv` file with a header row, or `.parquet` file with the `parquet` feature.
+        file: PathBuf,
+        /// Only decode the transactions sent to this address.
+        #[arg(long)]
+        to: Option<String>,
+        /// Only print the decodes with a call matching this query (see `decode --query`).
+        #[arg(long)]
+        query: Option<String>,
+        /// Write the decodes to this Parquet file, one row per param, instead of printing them.
+        #[cfg(feature = "parquet")]
+        #[arg(long)]
+        parquet: Option<PathBuf>,
+        /// When to color the output.
+        #[arg(long, value_enum)]
+        color: Option<Color>,
+    },
+    /// Extract the payload of EIP-4844 blobs and decode the batches, transactions or calldatas in it.
+    DecodeBlob {
+        /// File holding a beacon API `blob_sidecars` response, or the hex of a blob, of a
+        /// payload, or of a blob transaction with its sidecar. `-` reads stdin.
+        file: PathBuf,
+        /// When to color the output.
+        #[arg(long, value_enum)]
+        color: Option<Color>,
+    },
+    /// Decode rollup batches: OP Stack batcher channels, reassembled, and Arbitrum sequencer
+    /// batches, with the L2 transactions in them.
+    DecodeBatch {
+        /// File holding the batcher payloads in the order they landed on L1, one hex per line:
+        /// the calldata sent to an OP Stack batch inbox or to the Arbitrum sequencer inbox, or
+        /// a blob. `-` reads stdin.
+        file: PathBuf,
+        /// When to color the output.
+        #[arg(long, value_enum)]
+        color: Option<Color>,
+    },
+    /// Decode the data a call returned, typed by the request's function when known.
+    DecodeReturn {
+        /// Return data hex, with or without the `0x` prefix.
+        data: String,
+        /// Calldata of the request that returned the data.
+        #[arg(long)]
+        calldata: Option<String>,
+        /// Extra return types, one per line, e.g. `balanceOf(address)(uint256)`.
+        #[arg(long)]
+        returns: Option<PathBuf>,
+        /// When to color the output.
+        #[arg(long, value_enum)]
+        color: Option<Color>,
+    },
+    /// Decode ABI-encoded bytes with no selector, e.g. a storage value, an oracle answer or a
+    /// bridge payload.
+    DecodeAbi {
+        /// ABI-encoded hex, with or without the `0x` prefix.
+        data: String,
+        /// When to color the output.
+        #[arg(long, value_enum)]
+        color: Option<Color>,
+    },
+    /// Suggest encoding optimizations for the calldata and the bytes and gas they'd save.
+    Advise {
+        /// Calldata hex, with or without the `0x` prefix.
+        calldata: String,
+        /// When to color the output.
+        #[arg(long, value_enum)]
+        color: Option<Color>,
+    },
+    /// Generate variants of the calldata for fuzzing: boundary values, changed array lengths and flipped addresses.
+    Mutate {
+        /// Calldata hex, with or without the `0x` prefix.
+        calldata: String,
+        /// When to color the output.
+        #[arg(long, value_enum)]
+        color: Option<Color>,
+    },
+    /// Replace the addresses, amounts and salts of the calldata and its decode,
+    /// keeping its structure, e.g. to share it before disclosure.
+    Redact {
+        /// Calldata hex, with or without the `0x` prefix.
+        calldata: String,
+        /// Show placeholders such as `[address 1]` in the decode instead of pseudonyms.
+        #[arg(long)]
+        mask: bool,
+        /// Only redact these kinds of values (repeatable).
+        #[arg(long, value_enum)]
+        only: Vec<Sensitive>,
+        /// Secret the pseudonyms are derived from, to get the same ones across
+        /// runs. A random one by default.
+        #[arg(long, env = "CALLDATA_DECODER_REDACT_KEY")]
+        key: Option<String>,
+        /// When to color the output.
+        #[arg(long, value_enum)]
+        color: Option<Color>,
+    },
+    /// Explain the decode: what each word was taken for, which heuristics typed
+    /// each param and why the alternatives were rejected.
+    Explain {
+        /// Calldata hex, with or without the `0x` prefix.
+        calldata: String,
+        /// When to color the output.
+        #[arg(long, value_enum)]
+        color: Option<Color>,
+    },
+    /// Describe each calldata in one line, e.g. `multicall: create WETH/USDC pool @fee 0.05%, then ...`.
+    Summarize {
+        /// Calldata hex, with or without the `0x` prefix. `-` reads one calldata per line from stdin.
+        #[arg(required_unless_present = "file")]
+        calldata: Option<String>,
+        /// Read one calldata per line of this file.
+        #[arg(long, conflicts_with = "calldata")]
+        file: Option<PathBuf>,
+        /// Extra signatures, one per line (`signature` or `0xselector signature`) or
+        /// a `.json` / `.csv` table.
+        #[arg(long)]
+        signatures: Option<PathBuf>,
+    },
+    /// Summarize a batch of calldatas: selector frequencies, param types per selector,
+    /// nesting depth and the most called targets.
+    Stats {
+        /// Calldata hex, with or without the `0x` prefix. `-` reads one calldata per line from stdin.
+        /// A line may start with the target address, e.g. `0xdac1... 0xa9059cbb...`.
+        #[arg(required_unless_present = "file")]
+        calldata: Option<String>,
+        /// Read one calldata per line of this file.
+        #[arg(long, conflicts_with = "calldata")]
+        file: Option<PathBuf>,
+        /// How many selectors and targets to list.
+        #[arg(long, default_value_t = 20)]
+        top: usize,
+        /// Extra signatures, one per line (`signature` or `0xselector signature`) or
+        /// a `.json` / `.csv` table.
+        #[arg(long)]
+        signatures: Option<PathBuf>,
+        /// When to color the output.
+        #[arg(long, value_enum)]
+        color: Option<Color>,
+    },
+    /// Infer a stable signature from observed calls to one selector and emit a Rust
+    /// decoder for it, reading each param exactly instead of through the heuristics.
+    Codegen {
+        /// Calldata hex, with or without the `0x` prefix. `-` reads one calldata per line from stdin.
+        #[arg(required_unless_present = "file")]
+        calldata: Option<String>,
+        /// Read one calldata per line of this file.
+        #[arg(long, conflicts_with = "calldata")]
+        file: Option<PathBuf>,
+        /// Selector to generate a decoder for, nested calls included.
+        /// Defaults to the most frequent top-level selector.
+        #[arg(long)]
+        selector: Option<String>,
+        /// Extra signatures, one per line (`signature` or `0xselector signature`) or
+        /// a `.json` / `.csv` table, to name the function.
+        #[arg(long)]
+        signatures: Option<PathBuf>,
+    },
+    /// Estimate the L1 data fee an OP Stack rollup charges for the calldata, per param.
+    L1Fee {
+        /// Calldata hex, with or without the `0x` prefix.
+        calldata: String,
+        /// Fee formula of the chain's current upgrade.
+        #[arg(long, value_enum, default_value = "fjord")]
+        formula: Formula,
+        /// L1 base fee, in wei.
+        #[arg(long, default_value_t = L1FeeParams::default().l1_base_fee)]
+        l1_base_fee: u128,
+        /// L1 blob base fee, in wei.
+        #[arg(long, default_value_t = L1FeeParams::default().blob_base_fee)]
+        blob_base_fee: u128,
+        /// Weight of the L1 base fee, scaled by 1e6 (Ecotone and Fjord).
+        #[arg(long, default_value_t = L1FeeParams::default().base_fee_scalar)]
+        base_fee_scalar: u32,
+        /// Weight of the blob base fee, scaled by 1e6 (Ecotone and Fjord).
+        #[arg(long, default_value_t = L1FeeParams::default().blob_base_fee_scalar)]
+        blob_base_fee_scalar: u32,
+        /// Fixed gas added to every transaction (Bedrock).
+        #[arg(long, default_value_t = L1FeeParams::default().overhead)]
+        overhead: u64,
+        /// Fee multiplier, scaled by 1e6 (Bedrock).
+        #[arg(long, default_value_t = L1FeeParams::default().scalar)]
+        scalar: u64,
+        /// Bytes of the signed transaction around the calldata.
+        #[arg(long, default_value_t = L1FeeParams::default().envelope_bytes)]
+        envelope_bytes: u64,
+        /// When to color the output.
+        #[arg(long, value_enum)]
+        color: Option<Color>,
+    },
+    /// List the selectors found in the calldata (top-level and nested) with their signatures.
+    Selector {
+        /// Calldata hex, with or without the `0x` prefix.
+        calldata: String,
+        /// Extra signatures, one per line (`signature` or `0xselector signature`) or
+        /// a `.json` / `.csv` table.
+        #[arg(long)]
+        signatures: Option<PathBuf>,
+    },
+    /// Print the ranked candidate signatures of the top-level call, without the params.
+    GuessSignature {
+        /// Calldata hex, with or without the `0x` prefix.
+        calldata: String,
+        /// Extra signatures, one per line (`signature` or `0xselector signature`) or
+        /// a `.json` / `.csv` table.
+        #[arg(long)]
+        signatures: Option<PathBuf>,
+        /// How many candidates to print at most.
+        #[arg(long, default_value_t = 5)]
+        limit: usize,
+        /// Contract to run a probe of each candidate against with `eth_call`,
+        /// picking the widest one it decodes.
+        #[arg(long, value_name = "ADDRESS")]
+        simulate: Option<String>,
+        /// Sender of the simulated calls.
+        #[arg(long, requires = "simulate")]
+        from: Option<String>,
+        /// HTTP JSON-RPC endpoint, defaults to the config's endpoint for `--chain`.
+        #[arg(long, visible_alias = "rpc-url", env = "ETH_RPC_URL")]
+        rpc: Option<String>,
+        /// Chain whose configured endpoint to use.
+        #[arg(long)]
+        chain: Option<String>,
+        /// When to color the output.
+        #[arg(long, value_enum)]
+        color: Option<Color>,
+    },
+    /// Encode a call from its signature and values, the inverse of `decode`.
+    Encode {
+        /// Function signature, e.g. `transfer(address,uint256)`.
+        signature: String,
+        /// One value per input; numbers accept `1e18` and `1.5 ether`.
+        #[arg(allow_hyphen_values = true)]
+        values: Vec<String>,
+    },
+    /// Decode by an ABI and with the heuristics, scoring where the heuristics disagree.
+    #[command(hide = true)]
+    Accuracy {
+        /// JSON ABI, or a build artifact with an `abi` field.
+        #[arg(long)]
+        abi: PathBuf,
+        /// Calldata hex, with or without the `0x` prefix. `-` reads one calldata per line from stdin.
+        #[arg(required_unless_present = "file")]
+        calldata: Option<String>,
+        /// Compare one calldata per line of this file.
+        #[arg(long, conflicts_with = "calldata")]
+        file: Option<PathBuf>,
+        /// Also print each word the heuristics didn't guess first.
+        #[arg(long)]
+        verbose: bool,
+    },
+    /// Paste calldata and re-decode it interactively, see `:help` inside.
+    Repl {
+        /// Extra signatures, one per line (`signature` or `0xselector signature`) or
+        /// a `.json` / `.csv` table.
+        #[arg(long)]
+        signatures: Option<PathBuf>,
+        #[arg(long, value_enum)]
+        color: Option<Color>,
+    },
+    /// Serve `POST /decode` and `POST /decode-batch`, answering in the JSON schema.
+    #[cfg(feature = "server")]
+    Serve {
+        /// Address to listen on.
+        #[arg(long, default_value = "127.0.0.1:8080")]
+        listen: String,
+    },
+    /// Explore the decode tree in the terminal, highlighting each param in the raw hex.
+    #[cfg(feature = "tui")]
+    Tui {
+        /// Calldata hex, with or without the `0x` prefix.
+        calldata: String,
+    },
+}
+
+#[derive(Clone, Copy, clap::ValueEnum)]
+enum Color {
+    Auto,
+    Always,
+    Never,
+}
+
+#[derive(Clone, Copy, clap::ValueEnum)]
+enum Format {
+    Pretty,
+    Tree,
+    Json,
+    Yaml,
+    Etherscan,
+    Csv,
+    Words,
+}
+
+#[derive(Clone, Copy, clap::ValueEnum)]
+enum Compiler {
+    Solidity,
+    Vyper,
+}
+
+impl From<Compiler> for Profile {
+    fn from(c: Compiler) -> Self {
+        match c {
+            Compiler::Solidity => Profile::Solidity,
+            Compiler::Vyper => Profile::Vyper,
+        }
+    }
+}
+
+#[derive(Clone, Copy, clap::ValueEnum)]
+enum Formula {
+    Bedrock,
+    Ecotone,
+    Fjord,
+}
+
+impl From<Formula> for FeeFormula {
+    fn from(f: Formula) -> Self {
+        match f {
+            Formula::Bedrock => FeeFormula::Bedrock,
+            Formula::Ecotone => FeeFormula::Ecotone,
+            Formula::Fjord => FeeFormula::Fjord,
+        }
+    }
+}
+
+impl From<Format> for OutputFormat {
+    fn from(f: Format) -> Self {
+        match f {
+            Format::Pretty => OutputFormat::Pretty,
+            Format::Tree => OutputFormat::Tree,
+            Format::Json => OutputFormat::Json,
+            Format::Yaml => OutputFormat::Yaml,
+            Format::Etherscan => OutputFormat::Etherscan,
+            Format::Csv => OutputFormat::Csv,
+            Format::Words => OutputFormat::Words,
+        }
+    }
+}
+
+#[derive(Clone, Copy, clap::ValueEnum)]
+enum Sensitive {
+    Address,
+    Amount,
+    Salt,
+}
+
+impl From<Sensitive> for RedactKind {
+    fn from(s: Sensitive) -> Self {
+        match s {
+            Sensitive::Address => RedactKind::Address,
+            Sensitive::Amount => RedactKind::Amount,
+            Sensitive::Salt => RedactKind::Salt,
+        }
+    }
+}
+
+impl From<Color> for ColorMode {
+    fn from(c: Color) -> Self {
+        match c {
+            Color::Auto => ColorMode::Auto,
+            Color::Always => ColorMode::Always,
+            Color::Never => ColorMode::Never,
+        }
+    }
+}
+
+/// A key for one run, from the randomly seeded hasher of the standard library.
+fn random_key() -> Vec<u8> {
+    let state = std::collections::hash_map::RandomState::new();
+    (0..4u8)
+        .flat_map(|i| state.hash_one(i).to_be_bytes())
+        .collect()
+}
+
+/// Splits text into calldatas, one per non-empty line (`#` starts a comment).
+fn lines(text: &str) -> Vec<String> {
+    text.lines()
+        .map(|l| l.split('#').next().unwrap_or_default().trim())
+        .filter(|l| !l.is_empty())
+        .map(|l| l.to_string())
+        .collect()
+}
+
+/// Collects the calldatas to decode from the argument, stdin or a file.
+///
+/// With `binary` the file or stdin holds a single calldata as raw bytes, returned hex encoded.
+fn inputs(
+    calldata: Option<String>,
+    file: Option<PathBuf>,
+    binary: bool,
+) -> std::io::Result<Vec<String>> {
+    let mut bytes = vec![];
+    match (file, calldata.as_deref()) {
+        (Some(file), _) => bytes = std::fs::read(file)?,
+        (None, Some("-")) => {
+            std::io::stdin().read_to_end(&mut bytes)?;
+        }
+        (None, Some(calldata)) => return Ok(vec![calldata.trim().to_string()]),
+        (None, None) => return Ok(vec![]),
+    }
+    match binary {
+        true => Ok(vec![faster_hex::hex_string(&bytes)]),
+        false => Ok(lines(&String::from_utf8_lossy(&bytes))),
+    }
+}
+
+/// The config file merged with what it points to and the global flags.
+struct Settings {
+    config: Config,
+    /// Symbols of the tokens of `chain`, then the configured labels.
+    labels: Labels,
+    tokens: Tokens,
+    /// Chain id of the configured chain, mainnet when none is.
+    chain: u64,
+    /// Built-in protocol decoders and plugins, consulted before the heuristics.
+    protocols: ProtocolRegistry,
+    format: OutputFormat,
+    address_case: AddressCase,
+    select: Vec<String>,
+    /// Selectors of the target's bytecode, nested calls being limited to them.
+    targets: Option<Vec<[u8; 4]>>,
+    /// ABI of the calls decoded exactly.
+    abi: Option<Abi>,
+    /// Worst status of the decodes so far, the exit code.
+    status: Cell<DecodeStatus>,
+}
+
+impl Settings {
+    fn load(
+        path: Option<PathBuf>,
+        format: Option<Format>,
+        select: Vec<String>,
+        lowercase_addresses: bool,
+        plugins: Vec<PathBuf>,
+    ) -> Result<Self, String> {
+        let mut config = match path {
+            Some(path) => Config::load(&path)?,
+            None => Config::load_default()?,
+        };
+        config.plugins.extend(plugins);
+        let tokens = config.tokens()?;
+        let chain = config.chain.as_deref().and_then(chain_id).unwrap_or(1);
+        let mut labels = tokens.labels(chain);
+        labels.extend(config.labels()?);
+        let protocols = config.protocols()?;
+        let format = match format {
+            Some(format) => format.into(),
+            None => config.format.unwrap_or_default(),
+        };
+        let address_case = match lowercase_addresses {
+            true => AddressCase::Lowercase,
+            false => config.address_case.unwrap_or_default(),
+        };
+        for path in select.iter() {
+            parse_path(path)?;
+        }
+        Ok(Self {
+            config,
+            labels,
+            tokens,
+            chain,
+            protocols,
+            format,
+            address_case,
+            select,
+            targets: None,
+            abi: None,
+            status: Cell::new(DecodeStatus::Decoded),
+        })
+    }
+
+    /// `--color`, else the configured color mode.
+    fn style(&self, color: Option<Color>) -> Style {
+        let mode = match color {
+            Some(color) => color.into(),
+            None => self.config.color.unwrap_or_default(),
+        };
+        Style::new(mode)
+    }
+
+    /// `--rpc`, else the configured endpoint of `--chain` or the default chain.
+    fn rpc(&self, rpc: Option<String>, chain: Option<String>) -> String {
+        match rpc {
+            Some(rpc) => rpc,
+            None => self.config.rpc_url(chain.as_deref()).unwrap_or_else(|| {
+                fail("no RPC endpoint: pass --rpc, set ETH_RPC_URL or configure [rpc]")
+            }),
+        }
+    }
+
+    /// Configured signatures plus the ones in `file`, if given.
+    fn signatures(&self, file: Option<PathBuf>) -> Signatures {
+        let sources = self.config.signature_sources().unwrap_or_else(|e| fail(e));
+        match file {
+            Some(path) => {
+                let text = std::fs::read_to_string(&path).unwrap_or_else(|e| fail(e));
+                sources.with_source(SignatureMap::parse(&path, &text).unwrap_or_else(|e| fail(e)))
+            }
+            None => sources,
+        }
+    }
+
+    fn record(&self, status: DecodeStatus) {
+        self.status.set(self.status.get().max(status));
+    }
+
+    /// Parses `input`, recording it as invalid when it isn't calldata.
+    fn calldata(&self, input: &str) -> Result<Calldata, String> {
+        if let Err(e) = check_hex(input) {
+            self.record(DecodeStatus::Invalid);
+            return Err(e.into());
+        }
+        let calldata =
+            Calldata::with_limits(input.trim(), self.config.heuristics, self.config.limits)
+                .inspect_err(|_| self.record(DecodeStatus::Invalid))?
+                .with_protocols(self.protocols.clone());
+        let calldata = match &self.targets {
+            Some(targets) => calldata.with_target_selectors(targets.clone()),
+            None => calldata,
+        };
+        Ok(match &self.abi {
+            Some(abi) => calldata.with_abi(abi),
+            None => calldata,
+        })
+    }
+
+    /// Writes the addresses of a decode in the configured case and labels them.
+    fn annotate(&self, root: &mut DecodedCalldata) {
+        apply_address_case(root, self.address_case);
+        apply_labels(root, &self.labels);
+    }
+
+    /// Same as `annotate` for a call sent to `to` on `chain`, its amount also
+    /// written in the token's units when `to` is a known token.
+    fn annotate_sent(&self, root: &mut DecodedCalldata, chain: Option<u64>, to: &str) {
+        self.annotate(root);
+        if let Some(token) = self.tokens.get(chain.unwrap_or(self.chain), to) {
+            apply_token_amount(root, token);
+        }
+    }
+
+    /// Same as `annotate` for the topics and data of a log.
+    fn annotate_log(&self, log: &mut DecodedLog) {
+        self.annotate_params(&mut log.topics);
+        self.annotate_params(&mut log.params);
+        for call in log.calls.iter_mut() {
+            self.annotate(call);
+        }
+    }
+
+    /// Same as `annotate` for return data.
+    fn annotate_return(&self, ret: &mut DecodedReturn) {
+        self.annotate_params(&mut ret.params);
+        for call in ret.calls.iter_mut() {
+            self.annotate(call);
+        }
+    }
+
+    /// Same as `annotate` for ABI-encoded bytes.
+    fn annotate_abi(&self, abi: &mut DecodedAbi) {
+        self.annotate_params(&mut abi.params);
+        for call in abi.calls.iter_mut() {
+            self.annotate(call);
+        }
+    }
+
+    /// Same as `annotate` for params outside of a call.
+    fn annotate_params(&self, params: &mut Vec<DecodedParam>) {
+        let mut call = DecodedCalldata {
+            selector: String::new(),
+            signature_guess: String::new(),
+            params: std::mem::take(params),
+            calls: vec![],
+            span: Span { start: 0, end: 0 },
+            warnings: vec![],
+            layer: None,
+            tuples: vec![],
+        };
+        self.annotate(&mut call);
+        *params = call.params;
+    }
+
+    /// The channels of batcher `payloads`, pushed in order, with their calls annotated.
+    fn channels(&self, payloads: &[Vec<u8>]) -> Result<Vec<DecodedChannel>, String> {
+        let mut decoder = BatcherDecoder::new()
+            .with_heuristics(self.config.heuristics)
+            .with_limits(self.config.limits);
+        for payload in payloads.iter() {
+            decoder.push(payload)?;
+        }
+        let mut channels = decoder.channels();
+        for batch in channels.iter_mut().flat_map(|c| c.batches.iter_mut()) {
+            batch
+                .calls_mut()
+                .into_iter()
+                .for_each(|call| self.annotate(call));
+        }
+        Ok(channels)
+    }
+
+    /// The sequencer batch `calldata` posts, with its calls annotated.
+    fn sequencer_batch(&self, calldata: &[u8]) -> Result<SequencerBatch, String> {
+        let decoder = SequencerDecoder::new()
+            .with_heuristics(self.config.heuristics)
+            .with_limits(self.config.limits);
+        let mut batch = decoder.decode_calldata(calldata)?;
+        batch
+            .calls_mut()
+            .into_iter()
+            .for_each(|call| self.annotate(call));
+        Ok(batch)
+    }
+
+    /// Decode of `input` with address labels applied.
+    fn decode(&self, input: &str) -> Result<JsonOutput, String> {
+        let calldata = self.calldata(input)?;
+        let mut root = self.protocols.decode(&calldata)?;
+        self.annotate(&mut root);
+        self.record(root.status());
+        Ok(JsonOutput::with_root(&calldata, root))
+    }
+
+    /// `--select`ed fields of the decode, or the whole decode in the output format.
+    fn render(&self, id: &str, output: &JsonOutput, style: &Style) -> String {
+        match self.select.is_empty() {
+            true => render_output(self.format, id, output, style),
+            false => self.project(&output.root),
+        }
+    }
+
+    /// Same as `render` for a fetched transaction, `--select` paths starting at the transaction.
+    fn render_transaction(&self, tx: &DecodedTransaction, style: &Style) -> String {
+        self.record(tx.decoded.status());
+        match self.select.is_empty() {
+            true => render_transaction(self.format, tx, style),
+            false => self.project(tx),
+        }
+    }
+
+    fn project(&self, value: &impl serde::Serialize) -> String {
+        let value = serde_json::to_value(value).unwrap_or_else(|e| fail(e));
+        let values = project(&value, &self.select).unwrap_or_else(|e| fail(e));
+        render_projection(self.format, &self.select, &values)
+    }
+
+    /// Prints the format's header before the first item and a blank line between human-readable ones.
+    fn print(&self, i: usize, rendered: &str) {
+        let header = match (self.select.is_empty(), self.format) {
+            (true, format) => format.header().map(|h| h.to_string()),
+            (false, OutputFormat::Csv) => Some(projection_header(&self.select)),
+            (false, _) => None,
+        };
+        match (i, header) {
+            (0, Some(header)) => println!("{}", header),
+            (0, None) => {}
+            _ if self.select.is_empty() && !self.format.is_machine() => println!(),
+            _ => {}
+        }
+        print!("{}", rendered);
+    }
+}
+
+/// Runs the explorer until `q` is pressed, restoring the terminal afterwards.
+#[cfg(feature = "tui")]
+fn explore(calldata: &Calldata) -> std::io::Result<()> {
+    use calldata_decoder::explorer::{osc52, Action, Explorer};
+    use ratatui::crossterm::event::{self, Event, KeyEventKind};
+
+    let mut explorer = Explorer::new(calldata);
+    let mut terminal = ratatui::init();
+    let result = loop {
+        if let Err(e) = terminal.draw(|frame| explorer.draw(frame)) {
+            break Err(e);
+        }
+        let key = match event::read() {
+            Ok(Event::Key(key)) if key.kind == KeyEventKind::Press => key,
+            Ok(_) => continue,
+            Err(e) => break Err(e),
+        };
+        match explorer.handle_key(key.code) {
+            Action::None => {}
+            Action::Copy(text) => {
+                print!("{}", osc52(&text));
+                std::io::stdout().flush()?;
+            }
+            Action::Quit => break Ok(()),
+        }
+    };
+    ratatui::restore();
+    result
+}
+
+fn fail(e: impl std::fmt::Display) -> ! {
+    eprintln!("error: {}", e);
+    std::process::exit(1);
+}
+
+/// Exits on input that isn't calldata.
+fn invalid(e: impl std::fmt::Display) -> ! {
+    eprintln!("error: {}", e);
+    std::process::exit(DecodeStatus::Invalid.exit_code());
+}
+
+/// Runs the actions of the rule that raised `alert` in the background,
+/// reporting on stderr.
+fn fire(alerter: &Alerter, alert: Alert, client: &reqwest::Client) {
+    let Some(rule) = alerter.rule(&alert) else {
+        return;
+    };
+    eprintln!("alert: {} matched {:?}", alert.rule, alert.transaction.hash);
+    if !rule.exec.is_empty() {
+        match run_command(&rule.exec, &alert) {
+            Ok(mut child) => {
+                std::thread::spawn(move || child.wait());
+            }
+            Err(e) => eprintln!("error: rule `{}`: {}", alert.rule, e),
+        }
+    }
+    if let Some(url) = rule.webhook.clone() {
+        let client = client.clone();
+        tokio::spawn(async move {
+            if let Err(e) = post_webhook(&client, &url, &alert).await {
+                eprintln!("error: rule `{}`: {}", alert.rule, e);
+            }
+        });
+    }
+}
+
+/// Parses `--query`, exiting when it doesn't parse.
+fn parse_query(query: Option<String>) -> Option<Query> {
+    query.map(|q| Query::parse(&q).unwrap_or_else(|e| fail(format!("--query: {}", e))))
+}
+
+/// `root` has a call matching the query, or there's no query.
+fn wanted(query: &Option<Query>, root: &DecodedCalldata) -> bool {
+    query.as_ref().is_none_or(|q| q.matches(root))
+}
+
+#[tokio::main]
+async fn main() {
+    let cli = Cli::parse();
+    let format = match (cli.json, cli.pretty) {
+        (true, _) => Some(Format::Json),
+        (_, true) => Some(Format::Pretty),
+        _ => cli.format,
+    };
+    let mut settings = Settings::load(
+        cli.config,
+        format,
+        cli.select,
+        cli.lowercase_addresses,
+        cli.plugins,
+    )
+    .unwrap_or_else(|e| fail(e));
+    if let Some(profile) = cli.profile {
+        settings.config.heuristics.profile = profile.into();
+    }
+    match cli.command {
+        Command::Decode {
+            calldata,
+            file,
+            binary,
+            stream,
+            color,
+            ..
+        } if stream => {
+            let reader: Box<dyn Read> = match (file, calldata.as_deref()) {
+                (Some(file), _) => Box::new(std::fs::File::open(file).unwrap_or_else(|e| fail(e))),
+                (None, Some("-")) => Box::new(std::io::stdin()),
+                _ => fail("--stream reads a file or stdin (`-`)"),
+            };
+            let decoder = match binary {
+                true => StreamDecoder::binary(reader),
+                false => StreamDecoder::hex(reader),
+            };
+            let style = settings.style(color);
+            let mut out = std::io::BufWriter::new(std::io::stdout().lock());
+            for region in decoder.with_heuristics(settings.config.heuristics) {
+                let region = region.unwrap_or_else(|e| {
+                    let _ = out.flush();
+                    invalid(e)
+                });
+                if matches!(&region, Region::Param(p) if p.raw.len() != 64) {
+                    settings.record(DecodeStatus::Partial);
+                }
+                write!(out, "{}", render_region(settings.format, &region, &style))
+                    .unwrap_or_else(|e| fail(e));
+            }
+            out.flush().unwrap_or_else(|e| fail(e));
+        }
+        #[cfg(feature = "batch")]
+        Command::Decode {
+            file: Some(file),
+            binary,
+            mmap: true,
+            bytecode: None,
+            abi: None,
+            query,
+            color,
+            ..
+        } => {
+            use calldata_decoder::batch::{CalldataFile, RecordFormat, DEFAULT_WINDOW};
+            use calldata_decoder::schema::SCHEMA_VERSION;
+
+            let format = match binary {
+                true => RecordFormat::LengthPrefixed,
+                false => RecordFormat::HexLines,
+            };
+            let file = CalldataFile::open(&file, format)
+                .unwrap_or_else(|e| fail(e))
+                .with_heuristics(settings.config.heuristics)
+                .with_limits(settings.config.limits);
+            let query = parse_query(query);
+            let style = settings.style(color);
+            let mut printed = 0;
+            file.decode(DEFAULT_WINDOW, |i, result| match result {
+                Ok((record, mut root)) => {
+                    settings.record(root.status());
+                    if !wanted(&query, &root) {
+                        return;
+                    }
+                    settings.annotate(&mut root);
+                    let output = JsonOutput {
+                        version: SCHEMA_VERSION,
+                        calldata: record.to_hex(),
+                        root,
+                    };
+                    settings.print(printed, &settings.render(&i.to_string(), &output, &style));
+                    printed += 1;
+                }
+                Err(e) => {
+                    settings.record(DecodeStatus::Invalid);
+                    eprintln!("error: input {}: {}", i, e);
+                }
+            });
+        }
+        Command::Decode {
+            calldata,
+            file,
+            binary,
+            bytecode,
+            abi,
+            query,
+            color,
+            ..
+        } => {
+            if let Some(path) = abi {
+                settings.abi = Some(load_abi(&path).unwrap_or_else(|e| fail(e)));
+            }
+            if let Some(path) = bytecode {
+                let code = std::fs::read_to_string(path).unwrap_or_else(|e| fail(e));
+                let code = decode_hex_checked(code.trim()).unwrap_or_else(|e| fail(e));
+                let targets = dispatch_selectors(&code);
+                if targets.is_empty() {
+                    eprintln!("warning: no selectors in the bytecode, nested calls are left out");
+                }
+                if let (None, Some(profile)) = (cli.profile, compiler_profile(&code)) {
+                    settings.config.heuristics.profile = profile;
+                }
+                settings.targets = Some(targets);
+            }
+            let query = parse_query(query);
+            let inputs = inputs(calldata, file, binary).unwrap_or_else(|e| fail(e));
+            let style = settings.style(color);
+            let mut printed = 0;
+            for (i, input) in inputs.iter().enumerate() {
+                match settings.decode(input) {
+                    Ok(output) if !wanted(&query, &output.root) => {}
+                    Ok(output) => {
+                        settings.print(printed, &settings.render(&i.to_string(), &output, &style));
+                        printed += 1;
+                    }
+                    Err(e) => eprintln!("error: input {}: {}", i, e),
+                }
+            }
+        }
+        Command::DecodeTx {
+            rpc,
+            chain,
+            hash,
+            resolve_proxy,
+            color,
+        } => {
+            let hash = hash.parse().unwrap_or_else(|e| fail(e));
+            let chain_id = chain.as_deref().and_then(chain_id);
+            let rpc = settings.rpc(rpc, chain);
+            let provider = http_provider(&rpc).unwrap_or_else(|e| fail(e));
+            let tx = fetch_transaction(&provider, hash)
+                .await
+                .unwrap_or_else(|e| fail(e));
+            let mut tx = match resolve_proxy {
+                true => decode_transaction_resolved(&provider, &tx)
+                    .await
+                    .unwrap_or_else(|e| fail(e)),
+                false => DecodedTransaction::from_transaction(&tx).unwrap_or_else(|e| invalid(e)),
+            };
+            let to = tx.to.map(|to| format!("{:#x}", to)).unwrap_or_default();
+            settings.annotate_sent(&mut tx.decoded, chain_id, &to);
+            let style = settings.style(color);
+            settings.print(0, &settings.render_transaction(&tx, &style));
+        }
+        Command::Block {
+            rpc,
+            chain,
+            number,
+            signatures,
+            summary,
+            color,
+        } => {
+            let rpc = settings.rpc(rpc, chain);
+            let provider = http_provider(&rpc).unwrap_or_else(|e| fail(e));
+            let sources = settings.signatures(signatures);
+            let mut report = decode_block(&provider, number, &sources)
+                .await
+                .unwrap_or_else(|e| fail(e));
+            for tx in report.transactions.iter_mut() {
+                settings.annotate(&mut tx.decoded);
+            }
+            let style = settings.style(color);
+            match settings.select.is_empty() {
+                true => {
+                    for tx in report.transactions.iter() {
+                        settings.record(tx.decoded.status());
+                    }
+                    settings.print(0, &render_block(settings.format, &report, summary, &style));
+                }
+                false => {
+                    for (i, tx) in report.transactions.iter().enumerate() {
+                        settings.print(i, &settings.render_transaction(tx, &style));
+                    }
+                }
+            }
+        }
+        Command::Watch {
+            rpc,
+            chain,
+            selector,
+            to,
+            nested,
+            pending,
+            rules,
+            color,
+        } => {
+            let alerter = rules.map(|path| {
+                Rules::load(&path)
+                    .and_then(Alerter::new)
+                    .unwrap_or_else(|e| fail(e))
+            });
+            let filter = match &alerter {
+                Some(alerter) => alerter.prefilter(),
+                None => TxFilter {
+                    selectors: selector,
+                    to: to
+                        .iter()
+                        .map(|a| a.parse().unwrap_or_else(|e| fail(e)))
+                        .collect(),
+                    nested,
+                },
+            };
+            let client = reqwest::Client::new();
+            let mode = match pending {
+                true => WatchMode::Pending,
+                false => WatchMode::Confirmed,
+            };
+            let rpc = settings.rpc(rpc, chain);
+            let style = settings.style(color);
+            let seen = std::cell::Cell::new(0);
+            let on_tx = |mut tx: DecodedTransaction| {
+                if let Some(alerter) = &alerter {
+                    let alerts = alerter.check(&tx);
+                    if alerts.is_empty() {
+                        return;
+                    }
+                    for alert in alerts {
+                        fire(alerter, alert, &client);
+                    }
+                }
+                settings.annotate(&mut tx.decoded);
+                settings.print(seen.get(), &settings.render_transaction(&tx, &style));
+                seen.set(seen.get() + 1);
+            };
+            let result = match rpc.starts_with("ws") {
+                true => {
+                    let provider = ws_provider(&rpc).await.unwrap_or_else(|e| fail(e));
+                    watch(&provider, &filter, mode, on_tx).await
+                }
+                false => {
+                    let provider = http_provider(&rpc).unwrap_or_else(|e| fail(e));
+                    watch(&provider, &filter, mode, on_tx).await
+                }
+            };
+            result.unwrap_or_else(|e| fail(e));
+        }
+        Command::DecodeLog {
+            topics,
+            data,
+            events,
+            color,
+        } => {
+            let mut known = EventSignatures::builtin();
+            if let Some(path) = events {
+                let text = std::fs::read_to_string(path).unwrap_or_else(|e| fail(e));
+                known.insert_text(&text).unwrap_or_else(|e| fail(e));
+            }
+            let log = EventLog::from_hex(&topics, &data).unwrap_or_else(|e| invalid(e));
+            let mut decoded = LogDecoder::new()
+                .with_heuristics(settings.config.heuristics)
+                .with_limits(settings.config.limits)
+                .with_events(known)
+                .decode(&log)
+                .unwrap_or_else(|e| invalid(e));
+            settings.annotate_log(&mut decoded);
+            print!(
+                "{}",
+                render_log_output(settings.format, &decoded, &settings.style(color))
+            );
+        }
+        Command::DecodeRaw { raw, color } => {
+            let transaction = RawTransaction::from_hex(&raw).unwrap_or_else(|e| invalid(e));
+            let config = &settings.config;
+            let mut decoded =
+                DecodedRawTransaction::new(transaction, config.heuristics, config.limits)
+                    .unwrap_or_else(|e| invalid(e));
+            // Batches aren't decoded as calldata but as the L2 transactions they hold.
+            let input = &decoded.transaction.input;
+            let channels = decoded
+                .transaction
+                .to
+                .filter(is_batch_inbox)
+                .and_then(|_| settings.channels(std::slice::from_ref(input)).ok());
+            let sequencer = decoded
+                .transaction
+                .to
+                .filter(|to| sequencer_inbox_name(to).is_some())
+                .and_then(|_| settings.sequencer_batch(input).ok());
+            if channels.is_some() || sequencer.is_some() {
+                decoded.decoded = None;
+            }
+            let (chain, to) = (decoded.transaction.chain_id, decoded.transaction.to);
+            if let Some(call) = decoded.decoded.as_mut() {
+                let to = to.map(|to| format!("{:#x}", to)).unwrap_or_default();
+                settings.annotate_sent(call, chain, &to);
+                settings.record(call.status());
+            }
+            let style = settings.style(color);
+            print!(
+                "{}",
+                render_raw_transaction(settings.format, &decoded, &style)
+            );
+            if let Some(channels) = channels {
+                print!("{}", render_channels(settings.format, &channels, &style));
+            }
+            if let Some(batch) = sequencer {
+                print!(
+                    "{}",
+                    render_sequencer_batch(settings.format, &batch, &style)
+                );
+            }
+        }
+        #[cfg(feature = "batch")]
+        Command::DecodeDump {
+            file,
+            to,
+            query,
+            #[cfg(feature = "parquet")]
+            parquet,
+            color,
+        } => {
+            use calldata_decoder::batch::DEFAULT_WINDOW;
+            use calldata_decoder::dump::{Dump, DumpFormat};
+            use calldata_decoder::schema::SCHEMA_VERSION;
+
+            let format = DumpFormat::from_path(&file).unwrap_or_else(|e| invalid(e));
+            let mut dump = Dump::open(&file, format)
+                .unwrap_or_else(|e| invalid(e))
+                .with_heuristics(settings.config.heuristics)
+                .with_limits(settings.config.limits);
+            if let Some(to) = to {
+                dump = dump.with_to(&to);
+            }
+            let query = parse_query(query);
+            let style = settings.style(color);
+            #[cfg(feature = "parquet")]
+            let mut decodes = vec![];
+            let mut i = 0;
+            dump.decode(DEFAULT_WINDOW, |row, result| match result {
+                Ok(mut root) => {
+                    settings.record(root.status());
+                    if !wanted(&query, &root) {
+                        return;
+                    }
+                    settings.annotate(&mut root);
+                    #[cfg(feature = "parquet")]
+                    if parquet.is_some() {
+                        decodes.push((row.hash.clone(), root));
+                        return;
+                    }
+                    let output = JsonOutput {
+                        version: SCHEMA_VERSION,
+                        calldata: row.input.clone(),
+                        root,
+                    };
+                    settings.print(i, &settings.render(&row.hash, &output, &style));
+                    i += 1;
+                }
+                Err(e) => {
+                    settings.record(DecodeStatus::Invalid);
+                    eprintln!("error: {}: {}", row.hash, e);
+                }
+            });
+            #[cfg(feature = "parquet")]
+            if let Some(path) = parquet {
+                use calldata_decoder::export::write_parquet;
+
+                let out = std::fs::File::create(&path).unwrap_or_else(|e| fail(e));
+                write_parquet(
+                    out,
+                    decodes.iter().map(|(hash, root)| (hash.as_str(), root)),
+                )
+                .unwrap_or_else(|e| fail(e));
+            }
+        }
+        Command::DecodeBundle { file, color } => {
+            let input = match file.to_str() {
+                Some("-") => std::io::read_to_string(std::io::stdin()),
+                _ => std::fs::read_to_string(&file),
+            }
+            .unwrap_or_else(|e| fail(e));
+            let raws = parse_bundle(&input).unwrap_or_else(|e| invalid(e));
+            let config = &settings.config;
+            let mut bundle = DecodedBundle::new(&raws, config.heuristics, config.limits)
+                .unwrap_or_else(|e| invalid(e));
+            for call in bundle
+                .transactions
+                .iter_mut()
+                .filter_map(|tx| tx.decoded.decoded.as_mut())
+            {
+                settings.annotate(call);
+                settings.record(call.status());
+            }
+            print!(
+                "{}",
+                render_bundle(settings.format, &bundle, &settings.style(color))
+            );
+        }
+        Command::DecodeBlob { file, color } => {
+            let input = match file.to_str() {
+                Some("-") => std::io::read_to_string(std::io::stdin()),
+                _ => std::fs::read_to_string(&file),
+            }
+            .unwrap_or_else(|e| fail(e));
+            let blobs = read_blobs(&input).unwrap_or_else(|e| invalid(e));
+            let decoder = BlobDecoder::new()
+                .with_heuristics(settings.config.heuristics)
+                .with_limits(settings.config.limits);
+            let style = settings.style(color);
+            for (index, blob) in blobs.iter().enumerate() {
+                let mut decoded = decoder.decode(blob).unwrap_or_else(|e| invalid(e));
+                match &mut decoded.content {
+                    BlobContent::Calldatas { calls } => {
+                        calls.iter_mut().for_each(|c| settings.annotate(c))
+                    }
+                    BlobContent::Transactions { transactions } => transactions
+                        .iter_mut()
+                        .filter_map(|tx| tx.decoded.as_mut())
+                        .for_each(|c| settings.annotate(c)),
+                    _ => {}
+                }
+                print!("{}", render_blob(settings.format, index, &decoded, &style));
+            }
+        }
+        Command::DecodeBatch { file, color } => {
+            let input = match file.to_str() {
+                Some("-") => std::io::read_to_string(std::io::stdin()),
+                _ => std::fs::read_to_string(&file),
+            }
+            .unwrap_or_else(|e| fail(e));
+            let style = settings.style(color);
+            let mut payloads = vec![];
+            for line in input.lines().map(str::trim).filter(|l| !l.is_empty()) {
+                for blob in read_blobs(line).unwrap_or_else(|e| invalid(e)) {
+                    if batch_data(&blob).is_some() {
+                        let batch = settings
+                            .sequencer_batch(&blob)
+                            .unwrap_or_else(|e| invalid(e));
+                        print!(
+                            "{}",
+                            render_sequencer_batch(settings.format, &batch, &style)
+                        );
+                        continue;
+                    }
+                    let (_, payload) = blob_payload(&blob).unwrap_or_else(|e| invalid(e));
+                    payloads.push(payload);
+                }
+            }
+            if !payloads.is_empty() {
+                let channels = settings.channels(&payloads).unwrap_or_else(|e| invalid(e));
+                print!("{}", render_channels(settings.format, &channels, &style));
+            }
+        }
+        Command::DecodeReturn {
+            data,
+            calldata,
+            returns,
+            color,
+        } => {
+            let mut known = ReturnSignatures::builtin();
+            if let Some(path) = returns {
+                let text = std::fs::read_to_string(path).unwrap_or_else(|e| fail(e));
+                known.insert_text(&text).unwrap_or_else(|e| fail(e));
+            }
+            let returndata = decode_hex_checked(&data).unwrap_or_else(|e| invalid(e));
+            let decoder = ReturnDecoder::new()
+                .with_heuristics(settings.config.heuristics)
+                .with_limits(settings.config.limits)
+                .with_returns(known);
+            let mut decoded = match calldata {
+                Some(calldata) => {
+                    let call = settings
+                        .calldata(&calldata)
+                        .unwrap_or_else(|e| invalid(e))
+                        .decode();
+                    decoder.decode_for(&call, &returndata)
+                }
+                None => decoder.decode(&returndata),
+            }
+            .unwrap_or_else(|e| invalid(e));
+            settings.annotate_return(&mut decoded);
+            print!(
+                "{}",
+                render_return_output(settings.format, &decoded, &settings.style(color))
+            );
+        }
+        Command::DecodeAbi { data, color } => {
+            let bytes = decode_hex_checked(&data).unwrap_or_else(|e| invalid(e));
+            let mut decoded =
+                decode_abi_blob_with(&bytes, settings.config.heuristics, settings.config.limits)
+                    .unwrap_or_else(|e| invalid(e));
+            settings.annotate_abi(&mut decoded);
+            print!(
+                "{}",
+                render_abi_output(settings.format, &decoded, &settings.style(color))
+            );
+        }
+        Command::Advise { calldata, color } => {
+            let calldata = settings.calldata(&calldata).unwrap_or_else(|e| invalid(e));
+            let decoded = calldata.decode();
+            settings.record(decoded.status());
+            let report = advise(calldata.bytes(), &decoded);
+            print!(
+                "{}",
+                render_advice(settings.format, &report, &settings.style(color))
+            );
+        }
+        Command::Mutate { calldata, color } => {
+            let calldata = settings.calldata(&calldata).unwrap_or_else(|e| invalid(e));
+            let decoded = calldata.decode();
+            settings.record(decoded.status());
+            let mutations = mutations(calldata.bytes(), &decoded);
+            print!(
+                "{}",
+                render_mutations(settings.format, &mutations, &settings.style(color))
+            );
+        }
+        Command::Redact {
+            calldata,
+            mask,
+            only,
+            key,
+            color,
+        } => {
+            let calldata = settings.calldata(&calldata).unwrap_or_else(|e| invalid(e));
+            let decoded = calldata.decode();
+            settings.record(decoded.status());
+            let key = match key {
+                Some(key) => key.into_bytes(),
+                None => random_key(),
+            };
+            let mut redactor = Redactor::new(&key).with_mode(match mask {
+                true => RedactMode::Mask,
+                false => RedactMode::Pseudonymize,
+            });
+            if !only.is_empty() {
+                redactor = redactor.with_kinds(only.into_iter().map(RedactKind::from).collect());
+            }
+            let mut redaction = redactor.redact(calldata.bytes(), &decoded);
+            apply_address_case(&mut redaction.decoded, settings.address_case);
+            print!(
+                "{}",
+                render_redaction(settings.format, &redaction, &settings.style(color))
+            );
+        }
+        Command::Explain { calldata, color } => {
+            let calldata = settings.calldata(&calldata).unwrap_or_else(|e| invalid(e));
+            settings.record(calldata.decode().status());
+            print!(
+                "{}",
+                render_explanation(settings.format, &calldata.explain(), &settings.style(color))
+            );
+        }
+        Command::Summarize {
+            calldata,
+            file,
+            signatures,
+        } => {
+            let sources = settings.signatures(signatures);
+            let mut summaries = vec![];
+            for (i, line) in inputs(calldata, file, false)
+                .unwrap_or_else(|e| fail(e))
+                .iter()
+                .enumerate()
+            {
+                match settings.calldata(line) {
+                    Ok(calldata) => {
+                        let decoded = calldata.decode();
+                        settings.record(decoded.status());
+                        summaries.push(summarize(&decoded, &sources, &settings.labels));
+                    }
+                    Err(e) => eprintln!("error: input {}: {}", i, e),
+                }
+            }
+            match settings.format {
+                OutputFormat::Json => println!(
+                    "{}",
+                    serde_json::to_string(&summaries).unwrap_or_else(|e| fail(e))
+                ),
+                OutputFormat::Yaml => print!(
+                    "{}",
+                    serde_yaml::to_string(&summaries).unwrap_or_else(|e| fail(e))
+                ),
+                OutputFormat::Csv => {
+                    println!("summary");
+                    for summary in summaries.iter() {
+                        println!("{}", csv::escape(summary));
+                    }
+                }
+                OutputFormat::Pretty
+                | OutputFormat::Tree
+                | OutputFormat::Etherscan
+                | OutputFormat::Words => {
+                    for summary in summaries.iter() {
+                        println!("{}", summary);
+                    }
+                }
+            }
+        }
+        Command::Stats {
+            calldata,
+            file,
+            top,
+            signatures,
+            color,
+        } => {
+            let mut stats = BatchStats::new();
+            for (i, line) in inputs(calldata, file, false)
+                .unwrap_or_else(|e| fail(e))
+                .iter()
+                .enumerate()
+            {
+                let (target, hex) = match line.split_once(char::is_whitespace) {
+                    Some((target, hex)) => (Some(target), hex.trim()),
+                    None => (None, line.as_str()),
+                };
+                match settings.calldata(hex) {
+                    Ok(calldata) => stats.add(&calldata.decode(), target),
+                    Err(e) => {
+                        stats.add_failure();
+                        settings.record(DecodeStatus::Invalid);
+                        eprintln!("error: input {}: {}", i, e);
+                    }
+                }
+            }
+            let summary = stats.summary(top, &settings.signatures(signatures));
+            print!(
+                "{}",
+                render_stats(settings.format, &summary, &settings.style(color))
+            );
+        }
+        Command::Codegen {
+            calldata,
+            file,
+            selector,
+            signatures,
+        } => {
+            let mut roots = vec![];
+            for (i, line) in inputs(calldata, file, false)
+                .unwrap_or_else(|e| fail(e))
+                .iter()
+                .enumerate()
+            {
+                match settings.calldata(line) {
+                    Ok(calldata) => roots.push(calldata.decode()),
+                    Err(e) => {
+                        settings.record(DecodeStatus::Invalid);
+                        eprintln!("error: input {}: {}", i, e);
+                    }
+                }
+            }
+            let selector = selector.unwrap_or_else(|| {
+                let mut counts = std::collections::BTreeMap::<&str, usize>::new();
+                for root in roots.iter() {
+                    *counts.entry(root.selector.as_str()).or_default() += 1;
+                }
+                counts
+                    .into_iter()
+                    .max_by_key(|(selector, count)| (*count, std::cmp::Reverse(*selector)))
+                    .map(|(selector, _)| selector.to_string())
+                    .unwrap_or_else(|| fail("no calldata decoded"))
+            });
+            let mut inferred = infer_signature(&selector, &roots).unwrap_or_else(|e| fail(e));
+            if inferred.name.starts_with("unknown_") {
+                let known = settings.signatures(signatures).resolve(&inferred.selector);
+                if let Some(name) = known.first().and_then(|s| s.split('(').next()) {
+                    inferred.name = name.to_string();
+                }
+            }
+            print!("{}", render_decoder(&inferred));
+        }
+        Command::L1Fee {
+            calldata,
+            formula,
+            l1_base_fee,
+            blob_base_fee,
+            base_fee_scalar,
+            blob_base_fee_scalar,
+            overhead,
+            scalar,
+            envelope_bytes,
+            color,
+        } => {
+            let params = L1FeeParams {
+                formula: formula.into(),
+                l1_base_fee,
+                blob_base_fee,
+                base_fee_scalar,
+                blob_base_fee_scalar,
+                overhead,
+                scalar,
+                envelope_bytes,
+            };
+            let calldata = settings.calldata(&calldata).unwrap_or_else(|e| invalid(e));
+            let decoded = calldata.decode();
+            settings.record(decoded.status());
+            let estimate = estimate_l1_fee(calldata.bytes(), &decoded, &params);
+            print!(
+                "{}",
+                render_l1_fee(settings.format, &estimate, &settings.style(color))
+            );
+        }
+        Command::Selector {
+            calldata,
+            signatures,
+        } => {
+            let sources = settings.signatures(signatures);
+            let decoded = settings
+                .calldata(&calldata)
+                .unwrap_or_else(|e| invalid(e))
+                .decode();
+            let hits = find_selectors(&decoded, &sources);
+            match settings.format {
+                OutputFormat::Json => println!(
+                    "{}",
+                    serde_json::to_string(&hits).unwrap_or_else(|e| fail(e))
+                ),
+                OutputFormat::Yaml => print!(
+                    "{}",
+                    serde_yaml::to_string(&hits).unwrap_or_else(|e| fail(e))
+                ),
+                OutputFormat::Csv => {
+                    println!("depth,selector,offset,signatures");
+                    for hit in hits.iter() {
+                        println!(
+                            "{},{},{},{}",
+                            hit.depth,
+                            hit.selector,
+                            hit.offset,
+                            csv::escape(&hit.signatures.join("|"))
+                        );
+                    }
+                }
+                OutputFormat::Pretty
+                | OutputFormat::Tree
+                | OutputFormat::Etherscan
+                | OutputFormat::Words => {
+                    for hit in hits.iter() {
+                        println!(
+                            "{}{} @ 0x{:04x}  {}",
+                            "  ".repeat(hit.depth),
+                            hit.selector,
+                            hit.offset,
+                            match hit.signatures.is_empty() {
+                                true => "?".to_string(),
+                                false => hit.signatures.join(" | "),
+                            }
+                        );
+                    }
+                }
+            }
+        }
+        Command::GuessSignature {
+            calldata,
+            signatures,
+            limit,
+            simulate,
+            from,
+            rpc,
+            chain,
+            color,
+        } => {
+            let sources = settings.signatures(signatures);
+            let decoded = settings
+                .calldata(&calldata)
+                .unwrap_or_else(|e| invalid(e))
+                .decode();
+            let prototypes = rank_prototypes(&decoded, &sources, limit);
+            if let Some(to) = simulate {
+                let provider = http_provider(&settings.rpc(rpc, chain)).unwrap_or_else(|e| fail(e));
+                let result = disambiguate(
+                    &provider,
+                    to.parse().unwrap_or_else(|e| fail(e)),
+                    from.map(|from| from.parse().unwrap_or_else(|e| fail(e))),
+                    &decode_hex(&calldata),
+                    &decoded,
+                    &prototypes,
+                )
+                .await
+                .unwrap_or_else(|e| fail(e));
+                print!(
+                    "{}",
+                    render_disambiguation(settings.format, &result, &settings.style(color))
+                );
+            } else {
+                match settings.format {
+                    OutputFormat::Json => println!(
+                        "{}",
+                        serde_json::to_string(&prototypes).unwrap_or_else(|e| fail(e))
+                    ),
+                    OutputFormat::Yaml => print!(
+                        "{}",
+                        serde_yaml::to_string(&prototypes).unwrap_or_else(|e| fail(e))
+                    ),
+                    OutputFormat::Csv => {
+                        println!("rank,signature,confidence,source");
+                        for (i, p) in prototypes.iter().enumerate() {
+                            println!(
+                                "{},{},{:.4},{:?}",
+                                i,
+                                csv::escape(&p.signature),
+                                p.confidence,
+                                p.source
+                            );
+                        }
+                    }
+                    OutputFormat::Pretty
+                    | OutputFormat::Tree
+                    | OutputFormat::Etherscan
+                    | OutputFormat::Words => {
+                        for p in prototypes.iter() {
+                            println!("{:.3}  {}", p.confidence, p.signature);
+                        }
+                    }
+                }
+            }
+        }
+        Command::Encode { signature, values } => {
+            let values = values.iter().map(|v| v.as_str()).collect::<Vec<&str>>();
+            let encoded = encode_call(&signature, &values).unwrap_or_else(|e| fail(e));
+            // Other formats show the decode of what was just encoded.
+            match (settings.format, settings.select.is_empty()) {
+                (OutputFormat::Pretty, true) => println!("{}", encoded),
+                _ => {
+                    let output = settings.decode(&encoded).unwrap_or_else(|e| fail(e));
+                    settings.print(0, &settings.render("0", &output, &settings.style(None)));
+                }
+            }
+        }
+        Command::Accuracy {
+            abi,
+            calldata,
+            file,
+            verbose,
+        } => {
+            let abi = load_abi(&abi).unwrap_or_else(|e| fail(e));
+            let mut report = AccuracyReport::new();
+            for (i, hex) in inputs(calldata, file, false)
+                .unwrap_or_else(|e| fail(e))
+                .iter()
+                .enumerate()
+            {
+                let compared = settings
+                    .calldata(hex)
+                    .and_then(|calldata| differential(&calldata, &abi));
+                match compared {
+                    Ok(compared) => {
+                        if verbose && settings.format == OutputFormat::Pretty {
+                            for word in compared.disagreements() {
+                                println!(
+                                    "{} {}  [{}] {} guessed {}",
+                                    i,
+                                    compared.signature,
+                                    word.index,
+                                    word.expected,
+                                    word.guessed.join("|")
+                                );
+                            }
+                        }
+                        report.add(&compared);
+                    }
+                    Err(e) => report.skip(format!("input {}: {}", i, e)),
+                }
+            }
+            match settings.format {
+                OutputFormat::Json => println!(
+                    "{}",
+                    serde_json::to_string(&report).unwrap_or_else(|e| fail(e))
+                ),
+                OutputFormat::Yaml => print!(
+                    "{}",
+                    serde_yaml::to_string(&report).unwrap_or_else(|e| fail(e))
+                ),
+                OutputFormat::Csv => {
+                    println!("type,words,top,candidate");
+                    for (ty, score) in report.by_type.iter() {
+                        println!(
+                            "{},{},{},{}",
+                            csv::escape(ty),
+                            score.words,
+                            score.top,
+                            score.candidate
+                        );
+                    }
+                }
+                OutputFormat::Pretty
+                | OutputFormat::Tree
+                | OutputFormat::Etherscan
+                | OutputFormat::Words => {
+                    print!("{}", report)
+                }
+            }
+        }
+        Command::Repl { signatures, color } => {
+            let mut session = Session::new(settings.signatures(signatures), settings.style(color));
+            session.heuristics = settings.config.heuristics;
+            session.format = settings.format;
+            let stdin = std::io::stdin();
+            loop {
+                print!("> ");
+                std::io::stdout().flush().unwrap_or_else(|e| fail(e));
+                let mut line = String::new();
+                if stdin.read_line(&mut line).unwrap_or_else(|e| fail(e)) == 0 {
+                    break;
+                }
+                match session.handle(&line) {
+                    Reply::Output(out) if out.is_empty() => {}
+                    Reply::Output(out) => println!("{}", out),
+                    Reply::Quit => break,
+                }
+            }
+        }
+        #[cfg(feature = "server")]
+        Command::Serve { listen } => {
+            use calldata_decoder::decoder::Decoder;
+            let decoder = Decoder::from_config(&settings.config).unwrap_or_else(|e| fail(e));
+            eprintln!("listening on {}", listen);
+            calldata_decoder::server::serve(&listen, std::sync::Arc::new(decoder))
+                .await
+                .unwrap_or_else(|e| fail(e));
+        }
+        #[cfg(feature = "tui")]
+        Command::Tui { calldata } => {
+            let calldata = settings.calldata(&calldata).unwrap_or_else(|e| invalid(e));
+            explore(&calldata).unwrap_or_else(|e| fail(e));
+        }
+    }
+    std::process::exit(settings.status.get().exit_code());
+}
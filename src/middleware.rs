@@ -0,0 +1,187 @@
+use crate::decoded::DecodedCalldata;
+use crate::heuristics::Heuristics;
+use crate::limits::Limits;
+use crate::Calldata;
+use async_trait::async_trait;
+use ethers::providers::{FromErr, Middleware, PendingTransaction};
+use ethers::types::transaction::eip2718::TypedTransaction;
+use ethers::types::{Block, BlockId, OtherFields, Transaction, TxHash};
+use std::sync::Arc;
+
+// ------------------------------------------------------------
+//  Decoding middleware
+// ------------------------------------------------------------
+
+/// Field of `Transaction::other` the decode of the input is stored under.
+pub const DECODED_FIELD: &str = "decodedInput";
+
+/// Called with the hash and decode of every transaction sent through the middleware.
+pub type OnSend = Arc<dyn Fn(TxHash, &DecodedCalldata) + Send + Sync>;
+
+/// Middleware decoding the input of the transactions going through it.
+///
+/// Fetched transactions (`get_transaction`, `get_block_with_txs`) carry their
+/// decode in `other`, read back with [`decoded`]; sent ones are handed to the
+/// `on_send` hook.
+///
+/// ```ignore
+/// let provider = DecodingMiddleware::new(Provider::<Http>::try_from(url)?);
+/// let tx = provider.get_transaction(hash).await?.unwrap();
+/// let call = decoded(&tx).unwrap();
+/// ```
+#[derive(Clone)]
+pub struct DecodingMiddleware<M> {
+    inner: M,
+    heuristics: Heuristics,
+    limits: Limits,
+    on_send: Option<OnSend>,
+}
+
+impl<M> DecodingMiddleware<M> {
+    pub fn new(inner: M) -> Self {
+        Self {
+            inner,
+            heuristics: Heuristics::default(),
+            limits: Limits::default(),
+            on_send: None,
+        }
+    }
+
+    /// Decodes with only the enabled `heuristics`.
+    pub fn with_heuristics(mut self, heuristics: Heuristics) -> Self {
+        self.heuristics = heuristics;
+        self
+    }
+
+    /// Leaves inputs over `limits` undecoded.
+    pub fn with_limits(mut self, limits: Limits) -> Self {
+        self.limits = limits;
+        self
+    }
+
+    /// Calls `f` with the decode of every transaction sent.
+    pub fn on_send(mut self, f: impl Fn(TxHash, &DecodedCalldata) + Send + Sync + 'static) -> Self {
+        self.on_send = Some(Arc::new(f));
+        self
+    }
+
+    /// Decode of a transaction's input, `None` for empty inputs and ones over the limits.
+    pub fn decode(&self, input: &[u8]) -> Option<DecodedCalldata> {
+        if input.len() < 4 {
+            return None;
+        }
+        Calldata::from_bytes_with_limits(input.to_vec(), self.heuristics, self.limits)
+            .ok()
+            .map(|calldata| calldata.decode())
+    }
+
+    /// Stores the decode of `tx`'s input in its `other` fields.
+    fn attach(&self, tx: &mut Transaction) {
+        let Some(decoded) = self.decode(&tx.input) else {
+            return;
+        };
+        let mut fields = std::mem::take(&mut tx.other)
+            .into_iter()
+            .collect::<serde_json::Map<_, _>>();
+        if let Ok(value) = serde_json::to_value(decoded) {
+            fields.insert(DECODED_FIELD.to_string(), value);
+        }
+        tx.other = serde_json::from_value::<OtherFields>(fields.into()).unwrap_or_default();
+    }
+}
+
+/// The decode a [`DecodingMiddleware`] attached to a fetched transaction.
+pub fn decoded(tx: &Transaction) -> Option<DecodedCalldata> {
+    tx.other.get_deserialized(DECODED_FIELD)?.ok()
+}
+
+impl<M: std::fmt::Debug> std::fmt::Debug for DecodingMiddleware<M> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("DecodingMiddleware")
+            .field("inner", &self.inner)
+            .field("heuristics", &self.heuristics)
+            .field("limits", &self.limits)
+            .field("on_send", &self.on_send.is_some())
+            .finish()
+    }
+}
+
+/// Error of the wrapped middleware; decoding itself never fails a request.
+#[derive(Debug)]
+pub struct DecodingMiddlewareError<M: Middleware>(pub M::Error);
+
+impl<M: Middleware> std::fmt::Display for DecodingMiddlewareError<M> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        self.0.fmt(f)
+    }
+}
+
+impl<M: Middleware> std::error::Error for DecodingMiddlewareError<M> {}
+
+impl<M: Middleware> FromErr<M::Error> for DecodingMiddlewareError<M> {
+    fn from(src: M::Error) -> Self {
+        Self(src)
+    }
+}
+
+#[async_trait]
+impl<M: Middleware> Middleware for DecodingMiddleware<M> {
+    type Error = DecodingMiddlewareError<M>;
+    type Provider = M::Provider;
+    type Inner = M;
+
+    fn inner(&self) -> &M {
+        &self.inner
+    }
+
+    async fn get_transaction<T: Send + Sync + Into<TxHash>>(
+        &self,
+        transaction_hash: T,
+    ) -> Result<Option<Transaction>, Self::Error> {
+        let mut tx = self
+            .inner
+            .get_transaction(transaction_hash)
+            .await
+            .map_err(FromErr::from)?;
+        if let Some(tx) = tx.as_mut() {
+            self.attach(tx);
+        }
+        Ok(tx)
+    }
+
+    async fn get_block_with_txs<T: Into<BlockId> + Send + Sync>(
+        &self,
+        block_hash_or_number: T,
+    ) -> Result<Option<Block<Transaction>>, Self::Error> {
+        let mut block = self
+            .inner
+            .get_block_with_txs(block_hash_or_number)
+            .await
+            .map_err(FromErr::from)?;
+        for tx in block.iter_mut().flat_map(|b| b.transactions.iter_mut()) {
+            self.attach(tx);
+        }
+        Ok(block)
+    }
+
+    async fn send_transaction<T: Into<TypedTransaction> + Send + Sync>(
+        &self,
+        tx: T,
+        block: Option<BlockId>,
+    ) -> Result<PendingTransaction<'_, Self::Provider>, Self::Error> {
+        let tx = tx.into();
+        let decoded = match self.on_send {
+            Some(_) => tx.data().and_then(|data| self.decode(data)),
+            None => None,
+        };
+        let pending = self
+            .inner
+            .send_transaction(tx, block)
+            .await
+            .map_err(FromErr::from)?;
+        if let (Some(on_send), Some(decoded)) = (self.on_send.as_ref(), decoded) {
+            on_send(pending.tx_hash(), &decoded);
+        }
+        Ok(pending)
+    }
+}
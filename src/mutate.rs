@@ -0,0 +1,223 @@
+use crate::decoded::{DecodedCalldata, DecodedParam, Span};
+use primitive_types::U256;
+use serde::{Deserialize, Serialize};
+
+// ------------------------------------------------------------
+//  Mutations
+// ------------------------------------------------------------
+
+/// What a mutation changed.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum MutationKind {
+    /// A param set to an edge of its guessed type.
+    Boundary,
+    /// The length of an array, `bytes` or `string` changed, its items left as they are.
+    ArrayLength,
+    /// An address param replaced by a near or far one.
+    FlippedAddress,
+}
+
+/// A variant of the calldata, one word different from it.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Mutation {
+    pub kind: MutationKind,
+    /// The param changed, e.g. `calls[0].params[2]`.
+    pub path: String,
+    /// What it was set to, e.g. `max uint8` or `length 2 -> 0`.
+    pub label: String,
+    /// The word replaced.
+    pub span: Span,
+    /// The variant, `0x` prefixed.
+    pub calldata: String,
+}
+
+/// Generates variants of `calldata` for fuzzing the contract it calls, with
+/// `root`, its decode, as the grammar: each param set to the boundaries of its
+/// guessed type, the length words of arrays shortened and lengthened, and
+/// address params flipped.
+///
+/// Each variant replaces one word, so the layout stays valid. The variants are
+/// the same for the same calldata and decode, in calldata order.
+pub fn mutations(calldata: &[u8], root: &DecodedCalldata) -> Vec<Mutation> {
+    let mut out = vec![];
+    let mut variant = |kind, path: &str, label: String, span: Span, word: [u8; 32]| {
+        if calldata.get(span.start..span.end) == Some(&word[..]) {
+            return;
+        }
+        let mut bytes = calldata.to_vec();
+        bytes[span.start..span.end].copy_from_slice(&word);
+        out.push(Mutation {
+            kind,
+            path: path.to_string(),
+            label,
+            span,
+            calldata: format!("0x{}", faster_hex::hex_string(&bytes)),
+        });
+    };
+
+    let (offsets, lengths) = offsets_and_lengths(root);
+    for (path, param) in root.leaf_params() {
+        let Some(word) = word_at(calldata, param) else {
+            continue;
+        };
+        // Moving an offset would break the layout rather than the values.
+        if offsets.contains(&param.span.start) {
+            continue;
+        }
+        if lengths.contains(&param.span.start) {
+            let len = U256::from_big_endian(&word);
+            for to in alternative_lengths(len) {
+                let label = format!("length {} -> {}", len, to);
+                variant(
+                    MutationKind::ArrayLength,
+                    &path,
+                    label,
+                    param.span,
+                    to_word(to),
+                );
+            }
+            continue;
+        }
+        let Some(guessed) = param.types.first() else {
+            continue;
+        };
+        for (label, value) in boundaries(&guessed.solidity) {
+            variant(MutationKind::Boundary, &path, label, param.span, value);
+        }
+        if guessed.solidity == "address" {
+            let (mut flipped, mut neighbour) = ([0u8; 32], word);
+            flipped[12..]
+                .iter_mut()
+                .zip(&word[12..])
+                .for_each(|(f, w)| *f = !w);
+            neighbour[31] ^= 1;
+            let kind = MutationKind::FlippedAddress;
+            variant(kind, &path, "flipped".to_string(), param.span, flipped);
+            variant(
+                kind,
+                &path,
+                "last bit flipped".to_string(),
+                param.span,
+                neighbour,
+            );
+        }
+    }
+    out
+}
+
+/// The param's word, `None` for a partial one.
+fn word_at(calldata: &[u8], param: &DecodedParam) -> Option<[u8; 32]> {
+    calldata
+        .get(param.span.start..param.span.end)?
+        .try_into()
+        .ok()
+}
+
+fn to_word(value: U256) -> [u8; 32] {
+    let mut word = [0; 32];
+    value.to_big_endian(&mut word);
+    word
+}
+
+/// Byte offsets of the offset words of each call, and of the words they point
+/// at: the lengths of its arrays, `bytes` and `string`s.
+fn offsets_and_lengths(root: &DecodedCalldata) -> (Vec<usize>, Vec<usize>) {
+    let (mut offsets, mut lengths) = (vec![], vec![]);
+    for (_, call) in root.walk_paths() {
+        let body = call.span.start + 4;
+        for param in call.params.iter().filter(|p| p.raw.len() == 64) {
+            let Ok(offset) = usize::from_str_radix(&param.raw, 16) else {
+                continue;
+            };
+            let target = body + offset;
+            let points_at_param = call.params.iter().any(|p| p.span.start == target);
+            if offset > 0
+                && offset.is_multiple_of(32)
+                && target > param.span.start
+                && points_at_param
+            {
+                offsets.push(param.span.start);
+                lengths.push(target);
+            }
+        }
+    }
+    lengths.sort_unstable();
+    lengths.dedup();
+    (offsets, lengths)
+}
+
+/// Lengths to try in place of `len`: empty, one item less and more, and far
+/// more than the data holds.
+fn alternative_lengths(len: U256) -> Vec<U256> {
+    let mut out = vec![U256::zero(), len.saturating_add(U256::one())];
+    if !len.is_zero() {
+        out.insert(1, len - 1);
+    }
+    out.push(U256::from(u32::MAX));
+    out.dedup();
+    out.retain(|to| *to != len);
+    out
+}
+
+/// Edge values of a Solidity value type, with their label.
+fn boundaries(ty: &str) -> Vec<(String, [u8; 32])> {
+    let ones = |bytes: usize| {
+        let mut word = [0; 32];
+        word[32 - bytes..].fill(0xff);
+        word
+    };
+    let bits = |prefix: &str| ty[prefix.len()..].parse::<usize>().ok();
+    match ty {
+        "address" => vec![
+            ("zero".to_string(), [0; 32]),
+            ("precompile 0x01".to_string(), to_word(U256::one())),
+            ("max".to_string(), ones(20)),
+        ],
+        "bool" => vec![
+            ("false".to_string(), [0; 32]),
+            ("true".to_string(), to_word(U256::one())),
+            ("out of range 2".to_string(), to_word(U256::from(2))),
+        ],
+        _ if ty.starts_with("uint") => {
+            let Some(n) = bits("uint") else {
+                return vec![];
+            };
+            let mut out = vec![
+                ("zero".to_string(), [0; 32]),
+                ("one".to_string(), to_word(U256::one())),
+                (format!("max {}", ty), ones(n / 8)),
+            ];
+            if n < 256 {
+                let overflow = to_word(U256::one() << n);
+                out.push((format!("max {} + 1", ty), overflow));
+            }
+            out
+        }
+        _ if ty.starts_with("int") => {
+            let Some(n) = bits("int") else {
+                return vec![];
+            };
+            let max = (U256::one() << (n - 1)) - 1;
+            vec![
+                ("zero".to_string(), [0; 32]),
+                ("minus one".to_string(), [0xff; 32]),
+                (format!("min {}", ty), to_word(!max)),
+                (format!("max {}", ty), to_word(max)),
+            ]
+        }
+        _ if ty.starts_with("bytes") => {
+            let Some(n) = bits("bytes") else {
+                return vec![];
+            };
+            let mut full = [0; 32];
+            full[..n].fill(0xff);
+            vec![
+                ("zero".to_string(), [0; 32]),
+                ("all ones".to_string(), full),
+            ]
+        }
+        _ => vec![],
+    }
+}
@@ -0,0 +1,260 @@
+use crate::constants::*;
+use crate::decoded::{signature_guess, DecodedCalldata};
+use crate::protocol::{Context, ProtocolDecoder};
+use serde::Deserialize;
+use std::path::{Path, PathBuf};
+use wasmi::{Engine, Linker, Module, Store, StoreLimits, StoreLimitsBuilder};
+
+// ------------------------------------------------------------
+//  WASM plugins
+// ------------------------------------------------------------
+
+/// Instructions a plugin may run per call before it's stopped.
+pub const FUEL: u64 = 50_000_000;
+
+/// Linear memory a plugin may grow to, in bytes.
+pub const MEMORY: usize = 16 << 20;
+
+/// Largest decode a plugin may return, in bytes of JSON.
+pub const OUTPUT: usize = 1 << 20;
+
+/// What a plugin makes of a call it claims, as JSON. Every field is optional.
+///
+/// ```json
+/// {
+///   "signature": "forward(address,bytes)",
+///   "types": ["address"],
+///   "calls": [{ "at": 100, "len": 68 }]
+/// }
+/// ```
+#[derive(Debug, Clone, Default, PartialEq, Eq, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct PluginDecode {
+    /// Signature of the call, for its `signature_guess`.
+    pub signature: Option<String>,
+    /// Solidity type of each word of the params, from the first; the words
+    /// past the list keep their guessed types.
+    pub types: Vec<String>,
+    /// Calls embedded in the params, decoded in turn by the registry.
+    pub calls: Vec<PluginCall>,
+}
+
+/// Where an embedded call is, in bytes from the claimed call's selector.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct PluginCall {
+    pub at: usize,
+    pub len: usize,
+}
+
+/// A protocol decoder compiled to WebAssembly, loaded at runtime.
+///
+/// A plugin is a module exporting:
+///
+/// - `memory`, its linear memory;
+/// - `selectors() -> i64`, the 4-byte selectors it claims, back to back;
+/// - `alloc(len: i32) -> i32`, where to write a call of `len` bytes;
+/// - `decode(ptr: i32, len: i32) -> i64`, a `PluginDecode` as UTF-8 JSON,
+///   or 0 to leave the call to the heuristics.
+///
+/// Slices are returned as `(ptr << 32) | len`. The plugin is sandboxed: it
+/// can't import anything, runs on a fresh instance for every call, and is
+/// stopped past `FUEL` instructions or `MEMORY` bytes, its call then being
+/// left to the heuristics.
+pub struct WasmPlugin {
+    name: String,
+    engine: Engine,
+    module: Module,
+    /// Sorted.
+    selectors: Vec<[u8; 4]>,
+}
+
+impl WasmPlugin {
+    /// Loads the plugin at `path`, named after the file.
+    pub fn load(path: &Path) -> Result<Self, String> {
+        let wasm = std::fs::read(path).map_err(|e| format!("{}: {}", path.display(), e))?;
+        let name = path
+            .file_stem()
+            .map_or("plugin".into(), |stem| stem.to_string_lossy());
+        Self::from_bytes(&name, &wasm).map_err(|e| format!("{}: {}", path.display(), e))
+    }
+
+    /// Compiles the `wasm` module, asking it for the selectors it claims.
+    pub fn from_bytes(name: &str, wasm: &[u8]) -> Result<Self, String> {
+        let mut config = wasmi::Config::default();
+        config.consume_fuel(true);
+        let engine = Engine::new(&config);
+        let module = Module::new(&engine, wasm).map_err(|e| e.to_string())?;
+        if let Some(import) = module.imports().next() {
+            return Err(format!(
+                "plugins can't import anything, it imports `{}::{}`",
+                import.module(),
+                import.name()
+            ));
+        }
+        let mut plugin = Self {
+            name: name.to_string(),
+            engine,
+            module,
+            selectors: vec![],
+        };
+        let mut sandbox = plugin.instantiate()?;
+        let list = sandbox.call::<(), i64>("selectors", ())?;
+        let list = sandbox.read(list)?;
+        if list.len() % SELECTOR != 0 {
+            return Err(format!(
+                "`selectors` returned {} bytes, not a list of 4-byte selectors",
+                list.len()
+            ));
+        }
+        plugin.selectors = list
+            .chunks(SELECTOR)
+            .map(|s| [s[0], s[1], s[2], s[3]])
+            .collect();
+        plugin.selectors.sort_unstable();
+        Ok(plugin)
+    }
+
+    /// Selectors the plugin claims, sorted.
+    pub fn selectors(&self) -> &[[u8; 4]] {
+        &self.selectors
+    }
+
+    /// What the plugin makes of `call`, `None` when it leaves it to the heuristics.
+    pub fn run(&self, call: &[u8]) -> Result<Option<PluginDecode>, String> {
+        let mut sandbox = self.instantiate()?;
+        let len = i32::try_from(call.len()).map_err(|_| "call too large".to_string())?;
+        let ptr = sandbox.call::<i32, i32>("alloc", len)?;
+        sandbox.write(ptr, call)?;
+        match sandbox.call::<(i32, i32), i64>("decode", (ptr, len))? {
+            0 => Ok(None),
+            out => {
+                let json = sandbox.read(out)?;
+                serde_json::from_slice(&json)
+                    .map(Some)
+                    .map_err(|e| format!("`decode` returned invalid JSON: {}", e))
+            }
+        }
+    }
+
+    fn instantiate(&self) -> Result<Sandbox, String> {
+        let limits = StoreLimitsBuilder::new()
+            .memory_size(MEMORY)
+            .instances(1)
+            .build();
+        let mut store = Store::new(&self.engine, limits);
+        store.limiter(|limits| limits);
+        store.set_fuel(FUEL).map_err(|e| e.to_string())?;
+        let instance = Linker::new(&self.engine)
+            .instantiate(&mut store, &self.module)
+            .and_then(|pre| pre.start(&mut store))
+            .map_err(|e| e.to_string())?;
+        Ok(Sandbox { store, instance })
+    }
+}
+
+/// An instance of a plugin, for one call.
+struct Sandbox {
+    store: Store<StoreLimits>,
+    instance: wasmi::Instance,
+}
+
+impl Sandbox {
+    fn call<P: wasmi::WasmParams, R: wasmi::WasmResults>(
+        &mut self,
+        name: &str,
+        params: P,
+    ) -> Result<R, String> {
+        self.instance
+            .get_typed_func::<P, R>(&self.store, name)
+            .map_err(|e| format!("export `{}`: {}", name, e))?
+            .call(&mut self.store, params)
+            .map_err(|e| format!("`{}` failed: {}", name, e))
+    }
+
+    fn memory(&self) -> Result<wasmi::Memory, String> {
+        self.instance
+            .get_memory(&self.store, "memory")
+            .ok_or_else(|| "no `memory` export".to_string())
+    }
+
+    fn write(&mut self, ptr: i32, bytes: &[u8]) -> Result<(), String> {
+        self.memory()?
+            .write(&mut self.store, ptr as u32 as usize, bytes)
+            .map_err(|e| e.to_string())
+    }
+
+    /// The bytes of a `(ptr << 32) | len` slice.
+    fn read(&self, slice: i64) -> Result<Vec<u8>, String> {
+        let (ptr, len) = ((slice as u64 >> 32) as usize, slice as u32 as usize);
+        if len > OUTPUT {
+            return Err(format!("returned {} bytes, over {}", len, OUTPUT));
+        }
+        let mut out = vec![0; len];
+        self.memory()?
+            .read(&self.store, ptr, &mut out)
+            .map_err(|e| e.to_string())?;
+        Ok(out)
+    }
+}
+
+impl ProtocolDecoder for WasmPlugin {
+    fn name(&self) -> &str {
+        &self.name
+    }
+
+    fn claims(&self, call: &[u8]) -> bool {
+        call.get(..SELECTOR)
+            .and_then(|s| <[u8; 4]>::try_from(s).ok())
+            .is_some_and(|s| self.selectors.binary_search(&s).is_ok())
+    }
+
+    fn decode(&self, call: &[u8], cx: &Context) -> Result<DecodedCalldata, String> {
+        let out = self
+            .run(call)?
+            .ok_or_else(|| format!("{} left the call", self.name))?;
+        let mut root = cx.node(call);
+        for (param, ty) in root.params.iter_mut().zip(out.types.iter()) {
+            param.prefer(ty);
+        }
+        root.signature_guess = match out.signature {
+            Some(signature) => signature,
+            None => signature_guess(&root.selector, &root.params),
+        };
+        for nested in out.calls {
+            let end = nested
+                .at
+                .checked_add(nested.len)
+                .filter(|end| *end <= call.len());
+            let end = end.ok_or_else(|| {
+                format!(
+                    "{} put a call at {}..{}, past the calldata",
+                    self.name,
+                    nested.at,
+                    nested.at.saturating_add(nested.len)
+                )
+            })?;
+            if let Some(decoded) = cx.nested(&call[nested.at..end], nested.at) {
+                root.calls.push(decoded);
+            }
+        }
+        Ok(root)
+    }
+}
+
+/// The `.wasm` files at `path`: the file itself, or those of the directory, by name.
+pub fn plugin_files(path: &Path) -> Result<Vec<PathBuf>, String> {
+    let error = |e: std::io::Error| format!("{}: {}", path.display(), e);
+    match path.is_dir() {
+        true => {
+            let mut files = std::fs::read_dir(path)
+                .map_err(error)?
+                .map(|entry| entry.map(|e| e.path()).map_err(error))
+                .collect::<Result<Vec<_>, _>>()?;
+            files.retain(|f| f.extension().is_some_and(|e| e == "wasm"));
+            files.sort();
+            Ok(files)
+        }
+        false => Ok(vec![path.to_path_buf()]),
+    }
+}
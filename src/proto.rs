@@ -0,0 +1,130 @@
+use crate::decoded;
+use crate::schema::JsonOutput;
+use crate::Calldata;
+use prost::Message;
+
+// ------------------------------------------------------------
+//  Protobuf output
+// ------------------------------------------------------------
+
+/// Messages of `proto/decode.proto`, kept in sync by hand so no `protoc` is needed.
+pub mod pb {
+    #[derive(Clone, Copy, PartialEq, prost::Message)]
+    pub struct Span {
+        #[prost(uint64, tag = "1")]
+        pub start: u64,
+        #[prost(uint64, tag = "2")]
+        pub end: u64,
+    }
+
+    #[derive(Clone, PartialEq, prost::Message)]
+    pub struct TypeCandidate {
+        #[prost(string, tag = "1")]
+        pub kind: String,
+        #[prost(string, tag = "2")]
+        pub solidity: String,
+        #[prost(double, tag = "3")]
+        pub confidence: f64,
+    }
+
+    #[derive(Clone, PartialEq, prost::Message)]
+    pub struct DecodedParam {
+        #[prost(uint64, tag = "1")]
+        pub index: u64,
+        #[prost(string, tag = "2")]
+        pub raw: String,
+        #[prost(string, tag = "3")]
+        pub value: String,
+        #[prost(message, repeated, tag = "4")]
+        pub types: Vec<TypeCandidate>,
+        #[prost(message, optional, tag = "5")]
+        pub span: Option<Span>,
+    }
+
+    #[derive(Clone, PartialEq, prost::Message)]
+    pub struct DecodedCalldata {
+        #[prost(string, tag = "1")]
+        pub selector: String,
+        #[prost(string, tag = "2")]
+        pub signature_guess: String,
+        #[prost(message, repeated, tag = "3")]
+        pub params: Vec<DecodedParam>,
+        #[prost(message, repeated, tag = "4")]
+        pub calls: Vec<DecodedCalldata>,
+        #[prost(message, optional, tag = "5")]
+        pub span: Option<Span>,
+    }
+
+    #[derive(Clone, PartialEq, prost::Message)]
+    pub struct DecodeResult {
+        #[prost(uint32, tag = "1")]
+        pub version: u32,
+        #[prost(string, tag = "2")]
+        pub calldata: String,
+        #[prost(message, optional, tag = "3")]
+        pub root: Option<DecodedCalldata>,
+    }
+}
+
+impl From<&decoded::Span> for pb::Span {
+    fn from(span: &decoded::Span) -> Self {
+        Self {
+            start: span.start as u64,
+            end: span.end as u64,
+        }
+    }
+}
+
+impl From<&decoded::TypeCandidate> for pb::TypeCandidate {
+    fn from(t: &decoded::TypeCandidate) -> Self {
+        Self {
+            kind: format!("{:?}", t.kind),
+            solidity: t.solidity.clone(),
+            confidence: t.confidence,
+        }
+    }
+}
+
+impl From<&decoded::DecodedParam> for pb::DecodedParam {
+    fn from(p: &decoded::DecodedParam) -> Self {
+        Self {
+            index: p.index as u64,
+            raw: p.raw.clone(),
+            value: p.value.clone(),
+            types: p.types.iter().map(pb::TypeCandidate::from).collect(),
+            span: Some((&p.span).into()),
+        }
+    }
+}
+
+impl From<&decoded::DecodedCalldata> for pb::DecodedCalldata {
+    fn from(c: &decoded::DecodedCalldata) -> Self {
+        Self {
+            selector: c.selector.clone(),
+            signature_guess: c.signature_guess.clone(),
+            params: c.params.iter().map(pb::DecodedParam::from).collect(),
+            calls: c.calls.iter().map(pb::DecodedCalldata::from).collect(),
+            span: Some((&c.span).into()),
+        }
+    }
+}
+
+impl From<&JsonOutput> for pb::DecodeResult {
+    fn from(o: &JsonOutput) -> Self {
+        Self {
+            version: o.version,
+            calldata: o.calldata.clone(),
+            root: Some((&o.root).into()),
+        }
+    }
+}
+
+/// Encodes the decode of `calldata` as a `DecodeResult` protobuf message.
+pub fn to_protobuf(calldata: &Calldata) -> Vec<u8> {
+    pb::DecodeResult::from(&JsonOutput::new(calldata)).encode_to_vec()
+}
+
+/// Decodes a `DecodeResult` message produced by `to_protobuf`.
+pub fn from_protobuf(bytes: &[u8]) -> Result<pb::DecodeResult, prost::DecodeError> {
+    pb::DecodeResult::decode(bytes)
+}
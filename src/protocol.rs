@@ -0,0 +1,824 @@
+use crate::arena::{CallId, DecodeArena};
+use crate::constants::*;
+use crate::decoded::{DecodedCalldata, Span};
+use crate::heuristics::{HeuristicChain, Heuristics};
+use crate::limits::Limits;
+use crate::prototypes::top_signature;
+use crate::layout::{call_score, MIN_CALL_SCORE};
+use crate::signatures::{head_types, selector_bytes, Layout, Signatures};
+use crate::wrappers::WrapperDecoder;
+use crate::{first_word, word_details_with, word_usize, Calldata, Words};
+use std::cell::Cell;
+use std::collections::HashMap;
+#[cfg(feature = "plugins")]
+use std::path::Path;
+use std::sync::{Arc, OnceLock};
+
+// ------------------------------------------------------------
+//  Protocol decoders
+// ------------------------------------------------------------
+
+/// Decodes the calls of a protocol exactly, where the heuristics would guess.
+///
+/// A decoder claims calls by their selector or shape, and returns their
+/// decode tree. Nested calls are handed back to the registry through the
+/// `Context`, so a protocol pack only knows its own layouts: a crate can
+/// ship one and have it plugged in with `ProtocolRegistry::with`.
+pub trait ProtocolDecoder: Send + Sync {
+    /// Short name of the protocol, e.g. `multicall`.
+    fn name(&self) -> &str;
+
+    /// Whether the decoder takes `call`, its selector followed by its params.
+    fn claims(&self, call: &[u8]) -> bool;
+
+    /// Decode tree of a claimed `call`, spans counted from its selector.
+    ///
+    /// An error leaves the call to the heuristics.
+    fn decode(&self, call: &[u8], cx: &Context) -> Result<DecodedCalldata, String>;
+
+    /// Same as `decode`, built into `arena` (see `DecodeArena::decode`), its
+    /// nested calls through `Context::nested_into`. By default `decode`'s
+    /// tree is copied in.
+    fn decode_into(
+        &self,
+        call: &[u8],
+        cx: &Context,
+        arena: &mut DecodeArena,
+    ) -> Result<CallId, String> {
+        let decoded = self.decode(call, cx)?;
+        let calls = decoded
+            .calls
+            .iter()
+            .map(|nested| CallId::of(arena.push_decoded(nested)))
+            .collect::<Vec<_>>();
+        Ok(arena.push_node(&decoded, &calls))
+    }
+}
+
+/// What a protocol decoder decodes with: the settings of the decode, and
+/// the registry for the calls it finds.
+pub struct Context<'a> {
+    registry: &'a ProtocolRegistry,
+    heuristics: Heuristics,
+    chain: &'a HeuristicChain,
+    limits: Limits,
+    /// Nesting of the claimed call, 0 for the top-level call.
+    depth: usize,
+    /// What the whole decode has spent of `max_calls` and `max_nodes`.
+    spent: &'a Spent,
+    /// Selectors the target implements, when known, sorted.
+    targets: Option<&'a [[u8; 4]]>,
+}
+
+/// Nested calls and nodes of one decode so far, counted as each part of the
+/// tree is built so a decoder stops taking calls once the limits are reached.
+#[derive(Default)]
+struct Spent {
+    calls: Cell<usize>,
+    nodes: Cell<usize>,
+}
+
+impl Spent {
+    /// Counts the calls under the root of a tree and all its nodes, as `size`.
+    fn add(&self, (calls, nodes): (usize, usize)) {
+        self.calls.set(self.calls.get() + calls);
+        self.nodes.set(self.nodes.get() + nodes);
+    }
+
+    /// Takes back what `add` counted for a tree.
+    fn remove(&self, (calls, nodes): (usize, usize)) {
+        self.calls.set(self.calls.get().saturating_sub(calls));
+        self.nodes.set(self.nodes.get().saturating_sub(nodes));
+    }
+}
+
+/// Calls under the root of `tree`, and its calls and params.
+fn size(tree: &DecodedCalldata) -> (usize, usize) {
+    let calls = tree.walk();
+    let nodes = calls.iter().map(|call| 1 + call.params.len()).sum();
+    (calls.len() - 1, nodes)
+}
+
+impl Context<'_> {
+    pub fn heuristics(&self) -> &Heuristics {
+        &self.heuristics
+    }
+
+    /// Heuristics typing the words the decoder leaves to them.
+    pub fn chain(&self) -> &HeuristicChain {
+        self.chain
+    }
+
+    pub fn limits(&self) -> &Limits {
+        &self.limits
+    }
+
+    /// Nesting of the claimed call, 0 for the top-level call.
+    pub fn depth(&self) -> usize {
+        self.depth
+    }
+
+    /// `call` with each word of its params typed by the heuristics, no
+    /// nested calls: the node to hang the calls found in it on.
+    pub fn node(&self, call: &[u8]) -> DecodedCalldata {
+        let selector = &call[..SELECTOR.min(call.len())];
+        let words = Words::new(call[selector.len()..].to_vec(), first_word(call.len()));
+        let params = word_details_with(selector, words.iter(), &self.heuristics, self.chain);
+        DecodedCalldata::from_params(&params, 0)
+    }
+
+    /// Decode tree of a call found `at` bytes into the claimed call, through
+    /// the registry then the heuristics. `None` when it's deeper than
+    /// `max_depth`, the decode already has `max_calls` calls or `max_nodes`
+    /// nodes, the `nested` heuristic is off, or the top-level call's target
+    /// doesn't implement it (see `Calldata::with_target_selectors`).
+    pub fn nested(&self, call: &[u8], at: usize) -> Option<DecodedCalldata> {
+        if !self.takes(call) {
+            return None;
+        }
+        self.spent.calls.set(self.spent.calls.get() + 1);
+        let mut decoded = self.registry.decode_call(call, &self.deeper());
+        decoded.shift(at);
+        Some(decoded)
+    }
+
+    /// Same as `nested`, built into `arena`.
+    pub fn nested_into(&self, call: &[u8], at: usize, arena: &mut DecodeArena) -> Option<CallId> {
+        if !self.takes(call) {
+            return None;
+        }
+        self.spent.calls.set(self.spent.calls.get() + 1);
+        let id = self.registry.decode_call_into(call, &self.deeper(), arena);
+        arena.shift(id, at);
+        Some(id)
+    }
+
+    /// Whether a call found in the claimed call is decoded, see `nested`.
+    fn takes(&self, call: &[u8]) -> bool {
+        let spent = self.spent;
+        let full = spent.calls.get() >= self.limits.max_calls
+            || spent.nodes.get() >= self.limits.max_nodes;
+        let foreign = match (self.depth, self.targets) {
+            (0, Some(targets)) => call
+                .get(..SELECTOR)
+                .and_then(|s| <[u8; 4]>::try_from(s).ok())
+                .is_none_or(|s| targets.binary_search(&s).is_err()),
+            _ => false,
+        };
+        let taken = self.heuristics.nested && self.depth < self.limits.max_depth;
+        taken && !full && !foreign
+    }
+
+    /// The context of the calls found in the claimed call.
+    fn deeper(&self) -> Self {
+        Self {
+            depth: self.depth + 1,
+            ..*self
+        }
+    }
+}
+
+// ------------------------------------------------------------
+//  Registry
+// ------------------------------------------------------------
+
+/// Protocol decoders consulted, in order, before the heuristics.
+///
+/// The first decoder claiming a call decodes it; the calls no decoder claims
+/// are left to the heuristics, and the calls those find are offered to the
+/// decoders in turn.
+#[derive(Clone, Default)]
+pub struct ProtocolRegistry {
+    decoders: Vec<Arc<dyn ProtocolDecoder>>,
+}
+
+impl std::fmt::Debug for ProtocolRegistry {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_list().entries(self.names()).finish()
+    }
+}
+
+impl ProtocolRegistry {
+    /// No decoders: everything is left to the heuristics.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// The decoders shipped with the crate: `MulticallDecoder`, `ZkSyncDecoder`
+    /// and `WrapperDecoder`.
+    pub fn builtin() -> Self {
+        Self::new()
+            .with(MulticallDecoder)
+            .with(ZkSyncDecoder)
+            .with(WrapperDecoder)
+    }
+
+    /// Adds `decoder`, consulted after the ones already there.
+    pub fn with(mut self, decoder: impl ProtocolDecoder + 'static) -> Self {
+        self.decoders.push(Arc::new(decoder));
+        self
+    }
+
+    /// Adds the WASM plugins at `path`, a `.wasm` file or a directory of
+    /// them (see `plugin::WasmPlugin`).
+    #[cfg(feature = "plugins")]
+    pub fn with_plugins(mut self, path: &Path) -> Result<Self, String> {
+        for file in crate::plugin::plugin_files(path)? {
+            self = self.with(crate::plugin::WasmPlugin::load(&file)?);
+        }
+        Ok(self)
+    }
+
+    /// Names of the decoders, in the order they're consulted.
+    pub fn names(&self) -> Vec<&str> {
+        self.decoders.iter().map(|d| d.name()).collect()
+    }
+
+    /// The first decoder claiming `call`.
+    pub fn claimer(&self, call: &[u8]) -> Option<&dyn ProtocolDecoder> {
+        self.decoders
+            .iter()
+            .find(|d| d.claims(call))
+            .map(|d| d.as_ref())
+    }
+
+    /// Decode tree of `calldata`, the claimed calls decoded by their protocol,
+    /// failing when it's over `max_nodes`. The calls and nodes are counted as
+    /// they're decoded, a decoder's calls past `max_calls` or `max_nodes`
+    /// being dropped rather than decoded.
+    pub fn decode(&self, calldata: &Calldata) -> Result<DecodedCalldata, String> {
+        let spent = Spent::default();
+        let cx = Context {
+            registry: self,
+            heuristics: *calldata.heuristics(),
+            chain: calldata.chain(),
+            limits: *calldata.limits(),
+            depth: 0,
+            spent: &spent,
+            targets: calldata.targets(),
+        };
+        let root = match self.claimed(calldata.bytes(), &cx) {
+            Some(root) => root,
+            None => {
+                let mut root = calldata.decode_heuristics();
+                spent.add(size(&root));
+                self.reclaim(calldata.bytes(), &mut root, &cx);
+                root
+            }
+        };
+        // The calls decoders took are typed by the ABI too.
+        #[cfg(feature = "ethers")]
+        let root = {
+            let mut root = root;
+            if let Some(abi) = calldata.abi() {
+                crate::abi::apply_abi(&mut root, calldata.bytes(), abi);
+            }
+            root
+        };
+        cx.limits.check_nodes(size(&root).1)?;
+        Ok(root)
+    }
+
+    /// Same as `decode`, built into `arena`.
+    pub(crate) fn decode_into(
+        &self,
+        calldata: &Calldata,
+        arena: &mut DecodeArena,
+    ) -> Result<CallId, String> {
+        let spent = Spent::default();
+        let cx = Context {
+            registry: self,
+            heuristics: *calldata.heuristics(),
+            chain: calldata.chain(),
+            limits: *calldata.limits(),
+            depth: 0,
+            spent: &spent,
+            targets: calldata.targets(),
+        };
+        let root = match self.claimed_into(calldata.bytes(), &cx, arena) {
+            Some(root) => root,
+            None => self.heuristics_into(calldata, &cx, arena),
+        };
+        cx.limits.check_nodes(arena.size(root).1)?;
+        Ok(root)
+    }
+
+    /// Same as `decode` for a call `cx.depth` levels down, spans counted from its selector.
+    fn decode_call(&self, call: &[u8], cx: &Context) -> DecodedCalldata {
+        if let Some(decoded) = self.claimed(call, cx) {
+            return decoded;
+        }
+        // Only the levels, calls and nodes left below this call.
+        let remaining = Limits {
+            max_depth: cx.limits.max_depth.saturating_sub(cx.depth),
+            max_calls: cx.limits.max_calls.saturating_sub(cx.spent.calls.get()),
+            max_nodes: cx.limits.max_nodes.saturating_sub(cx.spent.nodes.get()),
+            ..cx.limits
+        };
+        let mut decoded = match Calldata::from_bytes_with_chain(
+            call.to_vec(),
+            cx.heuristics,
+            remaining,
+            cx.chain.clone(),
+        ) {
+            Ok(calldata) => calldata.decode_heuristics(),
+            Err(_) => cx.node(call),
+        };
+        cx.spent.add(size(&decoded));
+        self.reclaim(call, &mut decoded, cx);
+        decoded
+    }
+
+    /// Same as `decode_call`, built into `arena`.
+    fn decode_call_into(&self, call: &[u8], cx: &Context, arena: &mut DecodeArena) -> CallId {
+        if let Some(id) = self.claimed_into(call, cx, arena) {
+            return id;
+        }
+        let remaining = Limits {
+            max_depth: cx.limits.max_depth.saturating_sub(cx.depth),
+            max_calls: cx.limits.max_calls.saturating_sub(cx.spent.calls.get()),
+            max_nodes: cx.limits.max_nodes.saturating_sub(cx.spent.nodes.get()),
+            ..cx.limits
+        };
+        match Calldata::from_bytes_with_chain(
+            call.to_vec(),
+            cx.heuristics,
+            remaining,
+            cx.chain.clone(),
+        ) {
+            Ok(calldata) => self.heuristics_into(&calldata, cx, arena),
+            Err(_) => {
+                let node = cx.node(call);
+                cx.spent.add(size(&node));
+                arena.push_node(&node, &[])
+            }
+        }
+    }
+
+    /// The tree the heuristics found in `calldata` built into `arena`, the
+    /// calls in it a decoder claims built by their protocol, as `decode_call`
+    /// then `reclaim` have it.
+    fn heuristics_into(&self, calldata: &Calldata, cx: &Context, arena: &mut DecodeArena) -> CallId {
+        cx.spent.add(DecodeArena::found_size(calldata));
+        if self.decoders.is_empty() {
+            return CallId::of(arena.push_heuristics(calldata, &mut |_, _, _, _| None));
+        }
+        let call = calldata.bytes();
+        let mut claim = |arena: &mut DecodeArena, depth: usize, span: Span, found| {
+            let cx = Context {
+                depth: cx.depth + depth,
+                ..*cx
+            };
+            // What the heuristics found under the call is decoded again.
+            cx.spent.remove(found);
+            let claimed = call
+                .get(span.start..span.end.min(call.len()))
+                .and_then(|bytes| self.claimed_into(bytes, &cx, arena));
+            match claimed {
+                Some(id) => arena.shift(id, span.start),
+                None => cx.spent.add(found),
+            }
+            claimed
+        };
+        CallId::of(arena.push_heuristics(calldata, &mut claim))
+    }
+
+    /// Same as `claimed`, built into `arena`; what the decoder built is
+    /// dropped when it fails.
+    fn claimed_into(&self, call: &[u8], cx: &Context, arena: &mut DecodeArena) -> Option<CallId> {
+        let decoder = self.claimer(call)?;
+        let (calls, nodes) = (cx.spent.calls.get(), cx.spent.nodes.get());
+        let mark = arena.mark();
+        match decoder.decode_into(call, cx, arena) {
+            Ok(id) => {
+                let (all_calls, all_nodes) = arena.size(id);
+                let inner_calls = cx.spent.calls.get() - calls;
+                let inner_nodes = cx.spent.nodes.get() - nodes;
+                cx.spent.calls.set(calls + inner_calls.max(all_calls));
+                cx.spent.nodes.set(nodes + inner_nodes.max(all_nodes));
+                Some(id)
+            }
+            Err(_) => {
+                arena.truncate(mark);
+                cx.spent.calls.set(calls);
+                cx.spent.nodes.set(nodes);
+                None
+            }
+        }
+    }
+
+    /// Decode by the first decoder claiming `call`, `None` when none does or it fails.
+    ///
+    /// The calls a decoder hands back through `Context::nested` count
+    /// themselves; the rest of its tree is counted here.
+    fn claimed(&self, call: &[u8], cx: &Context) -> Option<DecodedCalldata> {
+        let decoder = self.claimer(call)?;
+        let (calls, nodes) = (cx.spent.calls.get(), cx.spent.nodes.get());
+        match decoder.decode(call, cx) {
+            Ok(decoded) => {
+                let (all_calls, all_nodes) = size(&decoded);
+                let inner_calls = cx.spent.calls.get() - calls;
+                let inner_nodes = cx.spent.nodes.get() - nodes;
+                cx.spent.calls.set(calls + inner_calls.max(all_calls));
+                cx.spent.nodes.set(nodes + inner_nodes.max(all_nodes));
+                Some(decoded)
+            }
+            Err(_) => {
+                cx.spent.calls.set(calls);
+                cx.spent.nodes.set(nodes);
+                None
+            }
+        }
+    }
+
+    /// Decodes again, by their protocol, the calls the heuristics found in
+    /// `node` that a decoder claims.
+    fn reclaim(&self, call: &[u8], node: &mut DecodedCalldata, cx: &Context) {
+        if self.decoders.is_empty() {
+            return;
+        }
+        let cx = cx.deeper();
+        for nested in node.calls.iter_mut() {
+            let (start, end) = (nested.span.start, nested.span.end.min(call.len()));
+            // What the heuristics found under the call is decoded again.
+            cx.spent.remove(size(nested));
+            match call
+                .get(start..end)
+                .and_then(|bytes| self.claimed(bytes, &cx))
+            {
+                Some(mut decoded) => {
+                    decoded.shift(start);
+                    *nested = decoded;
+                }
+                None => {
+                    cx.spent.add(size(nested));
+                    self.reclaim(call, nested, &cx);
+                }
+            }
+        }
+    }
+}
+
+// ------------------------------------------------------------
+//  Multicall
+// ------------------------------------------------------------
+
+/// Where a batching method keeps its calls.
+struct Batch {
+    selector: [u8; 4],
+    /// Head word of the array of calls.
+    head: usize,
+    /// Word of the calldata in each element's tuple, `None` when the
+    /// elements are the calldata (`bytes[]`).
+    field: Option<usize>,
+}
+
+const BATCHES: [Batch; 4] = [
+    // multicall(bytes[])
+    Batch {
+        selector: [0xac, 0x96, 0x50, 0xd8],
+        head: 0,
+        field: None,
+    },
+    // multicall(uint256,bytes[])
+    Batch {
+        selector: [0x5a, 0xe4, 0x01, 0xdc],
+        head: 1,
+        field: None,
+    },
+    // aggregate((address,bytes)[])
+    Batch {
+        selector: [0x25, 0x2d, 0xba, 0x42],
+        head: 0,
+        field: Some(1),
+    },
+    // aggregate3((address,bool,bytes)[])
+    Batch {
+        selector: [0x82, 0xad, 0x56, 0xcb],
+        head: 0,
+        field: Some(2),
+    },
+];
+
+/// Uniswap's `multicall(bytes[])` and `multicall(uint256,bytes[])`, and
+/// Multicall's `aggregate` and `aggregate3`: the calls are read from the
+/// array by its ABI layout rather than searched for in the words.
+pub struct MulticallDecoder;
+
+impl MulticallDecoder {
+    fn batch(call: &[u8]) -> Option<&'static Batch> {
+        BATCHES.iter().find(|b| call.starts_with(&b.selector))
+    }
+
+    /// Start and end in `call` of each call of its batch.
+    fn calls(call: &[u8]) -> Result<Vec<(usize, usize)>, String> {
+        let batch = Self::batch(call).ok_or("not a multicall")?;
+        let calls = batch_calls(&call[SELECTOR..], batch.head, batch.field)?;
+        // Plain transfers carry no call.
+        let calls = calls.into_iter().filter(|(_, len)| *len >= SELECTOR);
+        Ok(calls.map(|(at, len)| (SELECTOR + at, SELECTOR + at + len)).collect())
+    }
+}
+
+impl ProtocolDecoder for MulticallDecoder {
+    fn name(&self) -> &str {
+        "multicall"
+    }
+
+    fn claims(&self, call: &[u8]) -> bool {
+        Self::batch(call).is_some()
+    }
+
+    fn decode(&self, call: &[u8], cx: &Context) -> Result<DecodedCalldata, String> {
+        let mut root = cx.node(call);
+        for (at, end) in Self::calls(call)? {
+            if let Some(nested) = cx.nested(&call[at..end], at) {
+                root.calls.push(nested);
+            }
+        }
+        root.signature_guess = top_signature(&root.selector, &root.params, root.calls.len());
+        Ok(root)
+    }
+
+    fn decode_into(
+        &self,
+        call: &[u8],
+        cx: &Context,
+        arena: &mut DecodeArena,
+    ) -> Result<CallId, String> {
+        let mut root = cx.node(call);
+        let calls = Self::calls(call)?
+            .into_iter()
+            .filter_map(|(at, end)| cx.nested_into(&call[at..end], at, arena))
+            .collect::<Vec<_>>();
+        root.signature_guess = top_signature(&root.selector, &root.params, calls.len());
+        Ok(arena.push_node(&root, &calls))
+    }
+}
+
+/// Byte offset in `body` and length of each call of the batch whose array's
+/// offset is the word at `head`, `field` being the word of the calldata in
+/// each element's tuple as for `Batch`.
+fn batch_calls(
+    body: &[u8],
+    head: usize,
+    field: Option<usize>,
+) -> Result<Vec<(usize, usize)>, String> {
+    let word = |at: usize| {
+        at.checked_add(WORD)
+            .and_then(|end| body.get(at..end))
+            .and_then(word_usize)
+            .filter(|v| *v <= body.len())
+            .ok_or_else(|| format!("no valid word at byte {} of the params", at))
+    };
+    let array = word(head * WORD)?;
+    let count = word(array)?;
+    let elements = array + WORD;
+    (0..count)
+        .map(|i| {
+            let element = elements + word(elements + i * WORD)?;
+            let bytes = match field {
+                Some(field) => element + word(element + field * WORD)?,
+                None => element,
+            };
+            let (at, len) = (bytes + WORD, word(bytes)?);
+            match at + len <= body.len() {
+                true => Ok((at, len)),
+                false => Err(format!("call {} runs past the params", i)),
+            }
+        })
+        .collect()
+}
+
+// ------------------------------------------------------------
+//  zkSync Era
+// ------------------------------------------------------------
+
+/// zkSync Era's ContractDeployer system contract, the `to` of a deployment.
+pub const CONTRACT_DEPLOYER: &str = "0x0000000000000000000000000000000000008006";
+
+/// The paymaster flows an Era transaction's `paymasterInput` starts with, then
+/// the ContractDeployer methods its deployments call.
+pub const ZKSYNC_SIGNATURES: [&str; 6] = [
+    "general(bytes)",
+    "approvalBased(address,uint256,bytes)",
+    "create(bytes32,bytes32,bytes)",
+    "create2(bytes32,bytes32,bytes)",
+    "createAccount(bytes32,bytes32,bytes,uint8)",
+    "create2Account(bytes32,bytes32,bytes,uint8)",
+];
+
+/// zkSync Era's paymaster inputs and ContractDeployer calls, typed by their
+/// signature: the salt and bytecode hash of a deployment are `bytes32` where
+/// the heuristics would read numbers, and the bytes passed to the paymaster
+/// or the constructor aren't searched for calls.
+pub struct ZkSyncDecoder;
+
+impl ZkSyncDecoder {
+    fn signature(call: &[u8]) -> Option<&'static str> {
+        static SELECTORS: OnceLock<Vec<[u8; 4]>> = OnceLock::new();
+        let selectors = SELECTORS.get_or_init(|| {
+            ZKSYNC_SIGNATURES
+                .iter()
+                .map(|s| selector_bytes(s))
+                .collect()
+        });
+        let i = selectors.iter().position(|s| call.starts_with(s))?;
+        Some(ZKSYNC_SIGNATURES[i])
+    }
+}
+
+impl ProtocolDecoder for ZkSyncDecoder {
+    fn name(&self) -> &str {
+        "zksync"
+    }
+
+    fn claims(&self, call: &[u8]) -> bool {
+        Self::signature(call).is_some()
+    }
+
+    fn decode(&self, call: &[u8], cx: &Context) -> Result<DecodedCalldata, String> {
+        let signature = Self::signature(call).ok_or("not a zkSync Era call")?;
+        let layout = Layout::parse(signature).ok_or("unparsable signature")?;
+        let body = &call[SELECTOR..];
+        if body.len() < layout.head_words * WORD {
+            return Err(format!("{} needs {} words", signature, layout.head_words));
+        }
+        let mut root = cx.node(call);
+        for (i, ty) in layout.inputs.iter().enumerate() {
+            if ty == "bytes" {
+                let (at, len) = dynamic_bytes(body, i)?;
+                if at + len > body.len() {
+                    return Err(format!("param {} runs past the params", i));
+                }
+                if let Some(length) = root.params.get_mut(at / WORD - 1) {
+                    length.prefer("uint256");
+                }
+            }
+            root.params[i].prefer(ty);
+        }
+        root.signature_guess = signature.to_string();
+        Ok(root)
+    }
+}
+
+/// Byte offset in `body` and length of the `bytes` param whose offset is the
+/// word at `index`.
+fn dynamic_bytes(body: &[u8], index: usize) -> Result<(usize, usize), String> {
+    let word = |at: usize| {
+        body.get(at..at + WORD)
+            .and_then(word_usize)
+            .filter(|v| *v <= body.len())
+            .ok_or_else(|| format!("no valid word at byte {} of the params", at))
+    };
+    let offset = word(index * WORD)?;
+    if offset % WORD != 0 || offset + WORD > body.len() {
+        return Err(format!("param {} has a bad offset {}", index, offset));
+    }
+    Ok((offset + WORD, word(offset)?))
+}
+
+// ------------------------------------------------------------
+//  Learned layouts
+// ------------------------------------------------------------
+
+/// Calls typed by a layout guessed for another call with the same selector,
+/// e.g. the first of a batch (see `Decoder::decode_batch`), or by the layout
+/// of their selector's known signature (see `Signatures::layout`).
+///
+/// A call the layout fits (see `Layout::fits`) has its head words typed as
+/// the layout's inputs and is named by its signature. Its nested calls are
+/// read from its `bytes` and `bytes[]` inputs by their offsets, where the
+/// heuristics would search every word; a region is a call when it scores as
+/// one (see `layout::call_score`). A call the layout doesn't fit, or whose
+/// inputs may hold calls elsewhere (in tuples, say), is left to the heuristics.
+#[derive(Clone, Default)]
+pub struct LayoutDecoder {
+    layouts: HashMap<[u8; 4], Layout>,
+    signatures: Option<Arc<Signatures>>,
+}
+
+impl LayoutDecoder {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Types the calls to selectors `signatures` know as the first of their
+    /// signatures that parses, the layouts cached by selector.
+    pub fn from_signatures(signatures: Arc<Signatures>) -> Self {
+        Self {
+            signatures: Some(signatures),
+            ..Self::default()
+        }
+    }
+
+    /// Types the calls to `selector` as `layout`, replacing any layout it had.
+    pub fn with_layout(mut self, selector: [u8; 4], layout: Layout) -> Self {
+        self.layouts.insert(selector, layout);
+        self
+    }
+
+    /// Number of selectors with a layout of their own, the signatures' aside.
+    pub fn len(&self) -> usize {
+        self.layouts.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.layouts.is_empty()
+    }
+
+    fn layout(&self, call: &[u8]) -> Option<Layout> {
+        let selector = <[u8; 4]>::try_from(call.get(..SELECTOR)?).ok()?;
+        match self.layouts.get(&selector) {
+            Some(layout) => Some(layout.clone()),
+            None => self.signatures.as_ref()?.layout(&faster_hex::hex_string(&selector)),
+        }
+    }
+
+    /// The node of `call` typed by its layout, and the start and end in
+    /// `call` of each call in its `bytes` inputs.
+    fn typed(
+        &self,
+        call: &[u8],
+        cx: &Context,
+    ) -> Result<(DecodedCalldata, Vec<(usize, usize)>), String> {
+        let layout = self.layout(call).ok_or("no layout for the selector")?;
+        if !layout.inputs.iter().all(|ty| followed(ty)) {
+            return Err(format!("{} may hold calls outside its bytes", layout.signature));
+        }
+        let mut root = cx.node(call);
+        if !layout.fits(&root.params) {
+            return Err(format!("{} doesn't fit the params", layout.signature));
+        }
+        let body = &call[SELECTOR..];
+        let mut regions = vec![];
+        let mut head = 0;
+        for ty in layout.inputs.iter() {
+            let types = head_types(ty).unwrap_or_default();
+            match ty.as_str() {
+                "bytes" => regions.push(dynamic_bytes(body, head)?),
+                "bytes[]" => regions.extend(batch_calls(body, head, None)?),
+                _ => {}
+            }
+            for (param, ty) in root.params[head..].iter_mut().zip(types.iter()) {
+                param.prefer(ty);
+            }
+            head += types.len();
+        }
+        let mut calls = vec![];
+        for (at, len) in regions {
+            let padding = body.get(at + len..(at + len).next_multiple_of(WORD));
+            let selector = &body[at..at + SELECTOR.min(len)];
+            let is_call = len % WORD == SELECTOR
+                && selector != EMPTY_4
+                && selector != MASK_4
+                && call_score(selector, len, padding.unwrap_or_default()) >= MIN_CALL_SCORE;
+            if !is_call {
+                continue;
+            }
+            calls.push((SELECTOR + at, SELECTOR + at + len));
+        }
+        root.signature_guess = layout.signature;
+        Ok((root, calls))
+    }
+}
+
+/// Whether the calls an input of type `ty` may hold are all found by
+/// `LayoutDecoder`: tuples and arrays of `bytes` other than `bytes[]` aren't.
+fn followed(ty: &str) -> bool {
+    !ty.contains('(') && !ty.contains("][") && (!ty.starts_with("bytes[") || ty == "bytes[]")
+}
+
+impl ProtocolDecoder for LayoutDecoder {
+    fn name(&self) -> &str {
+        "layouts"
+    }
+
+    fn claims(&self, call: &[u8]) -> bool {
+        self.layout(call).is_some()
+    }
+
+    fn decode(&self, call: &[u8], cx: &Context) -> Result<DecodedCalldata, String> {
+        let (mut root, calls) = self.typed(call, cx)?;
+        for (at, end) in calls {
+            if let Some(nested) = cx.nested(&call[at..end], at) {
+                root.calls.push(nested);
+            }
+        }
+        Ok(root)
+    }
+
+    fn decode_into(
+        &self,
+        call: &[u8],
+        cx: &Context,
+        arena: &mut DecodeArena,
+    ) -> Result<CallId, String> {
+        let (root, calls) = self.typed(call, cx)?;
+        let calls = calls
+            .into_iter()
+            .filter_map(|(at, end)| cx.nested_into(&call[at..end], at, arena))
+            .collect::<Vec<_>>();
+        Ok(arena.push_node(&root, &calls))
+    }
+}
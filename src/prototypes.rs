@@ -0,0 +1,481 @@
+use crate::decoded::{hex_u128, signature_guess, DecodedCalldata, DecodedParam, DecodedTuple};
+use crate::signatures::Signatures;
+use serde::{Deserialize, Serialize};
+
+// ------------------------------------------------------------
+//  Signature prototypes
+// ------------------------------------------------------------
+
+/// Share of the confidence given to signatures found in a signature source.
+const KNOWN_WEIGHT: f64 = 0.9;
+
+/// Where a candidate prototype comes from.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum PrototypeSource {
+    /// A signature source knows the selector.
+    Known,
+    /// The words lay out a single dynamic param holding the nested calls.
+    Structure,
+    /// Combination of the per-word type guesses.
+    Heuristic,
+}
+
+/// A candidate signature for a call.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Prototype {
+    pub signature: String,
+    /// 0..=1, the prototypes of a call sum to at most 1.
+    pub confidence: f64,
+    pub source: PrototypeSource,
+}
+
+/// Ranks candidate signatures for the call, most likely first.
+///
+/// ## Params
+/// 1. call - the decoded call.
+/// 2. sources - signatures to resolve the selector against.
+/// 3. limit - how many prototypes to return at most.
+pub fn rank_prototypes(
+    call: &DecodedCalldata,
+    sources: &Signatures,
+    limit: usize,
+) -> Vec<Prototype> {
+    let known = sources.resolve(&call.selector);
+    let mut guessed = guesses(&call.selector, &call.params, call.calls.len());
+    // Guesses the call doesn't re-encode from are dropped, unless none
+    // re-encode, e.g. when something was appended to the call.
+    #[cfg(feature = "ethers")]
+    {
+        let bytes = crate::verify::call_bytes(call);
+        let verified = guessed
+            .iter()
+            .filter(|g| crate::verify::round_trips(&g.0, &bytes))
+            .cloned()
+            .collect::<Vec<_>>();
+        if !verified.is_empty() {
+            guessed = verified;
+        }
+    }
+    let mut heuristic = 0;
+    guessed.retain(|g| {
+        heuristic += (g.2 == PrototypeSource::Heuristic) as usize;
+        heuristic <= limit
+    });
+
+    // Known signatures share most of the confidence, guesses get the rest.
+    let guess_weight = match known.is_empty() {
+        true => 1.0,
+        false => 1.0 - KNOWN_WEIGHT,
+    };
+    let total = guessed.iter().map(|g| g.1).sum::<f64>();
+    let mut out = known
+        .iter()
+        .map(|signature| Prototype {
+            signature: signature.clone(),
+            confidence: KNOWN_WEIGHT / known.len() as f64,
+            source: PrototypeSource::Known,
+        })
+        .collect::<Vec<Prototype>>();
+    out.extend(
+        guessed
+            .into_iter()
+            .map(|(signature, score, source)| Prototype {
+                signature,
+                confidence: match total > 0.0 {
+                    true => guess_weight * score / total,
+                    false => guess_weight,
+                },
+                source,
+            }),
+    );
+    out.sort_by(|a, b| b.confidence.total_cmp(&a.confidence));
+    out.truncate(limit);
+    out
+}
+
+/// The signatures guessed from the words, most likely first: the dynamic
+/// layout they reconstruct, then every combination of the params' candidate
+/// types, as some may not re-encode.
+fn guesses(
+    selector: &str,
+    params: &[DecodedParam],
+    calls: usize,
+) -> Vec<(String, f64, PrototypeSource)> {
+    let mut guessed: Vec<(String, f64, PrototypeSource)> = vec![];
+    if let Some(structure) = structure(params, calls) {
+        guessed.push((
+            format!("unknown_{}({})", selector, structure),
+            1.0,
+            PrototypeSource::Structure,
+        ));
+    }
+    for (types, score) in type_combinations(params, usize::MAX) {
+        let signature = format!("unknown_{}({})", selector, types.join(","));
+        if !guessed.iter().any(|g| g.0 == signature) {
+            guessed.push((signature, score, PrototypeSource::Heuristic));
+        }
+    }
+    guessed
+}
+
+/// The top of `candidate_signatures`, without ranking the rest: the
+/// dynamic layout the words reconstruct when there is one, else each
+/// param's most likely type as `signature_guess` has it.
+///
+/// Nothing is re-encoded, so the call is named the same with or without
+/// `ethers`.
+pub fn top_signature(selector: &str, params: &[DecodedParam], calls: usize) -> String {
+    match structure(params, calls) {
+        Some(structure) => format!("unknown_{}({})", selector, structure),
+        None => signature_guess(selector, params),
+    }
+}
+
+/// Types of the single dynamic param holding the nested calls, else of the
+/// dynamic layout of the words.
+fn structure(params: &[DecodedParam], calls: usize) -> Option<String> {
+    nested_structure(params, calls)
+        .map(str::to_string)
+        .or_else(|| params_layout(params))
+}
+
+/// `bytes[]` / `bytes` when the words are an offset of 0x20 to the nested call(s).
+///
+/// ```text
+/// [0] 0x20 -> [1] count of nested calls (bytes[]) or the byte length of the one nested call (bytes)
+/// ```
+pub fn structural_guess(call: &DecodedCalldata) -> Option<&'static str> {
+    nested_structure(&call.params, call.calls.len())
+}
+
+fn nested_structure(params: &[DecodedParam], calls: usize) -> Option<&'static str> {
+    let word = |i: usize| params.get(i).map(|p| hex_u128(&p.raw).unwrap_or(u128::MAX));
+    if calls == 0 || word(0)? != 32 {
+        return None;
+    }
+    let second = word(1)?;
+    match calls {
+        1 if second > 4 => Some("bytes"),
+        n if second == n as u128 => Some("bytes[]"),
+        _ => None,
+    }
+}
+
+/// Levels of tuples and arrays `layout_guess` looks into.
+const MAX_LAYOUT_DEPTH: usize = 8;
+
+/// The params' types when the words lay out dynamic params, e.g.
+/// `uint256,uint256[]` for a number, an offset, then a length and that many
+/// words.
+///
+/// The offsets in the head must point past it in order, each at the
+/// encoding of a dynamic param filling exactly the words up to the next one:
+///
+/// - a length then as many words, an array, or the length in bytes padded
+///   to words, `bytes` or `string`;
+/// - a length then as many offsets to elements laid out in turn, an array of
+///   them, e.g. `bytes[]`;
+/// - words with offsets of their own counted from the first, a tuple, e.g.
+///   `(address,bytes)`;
+/// - a length then words splitting evenly into that many elements of 2 or
+///   more, an array of static tuples, e.g. `(address,uint256)[]`.
+///
+/// Head words that aren't offsets take their most likely type, and array
+/// elements and tuple fields theirs when they all agree. A static tuple
+/// outside an array is encoded as its fields would be, so it's left flat.
+pub fn layout_guess(call: &DecodedCalldata) -> Option<String> {
+    params_layout(&call.params)
+}
+
+fn params_layout(params: &[DecodedParam]) -> Option<String> {
+    let words = words(params)?;
+    let layout = Encoding {
+        params,
+        words: &words,
+    };
+    layout.tuple(0, words.len(), 0).map(|types| types.join(","))
+}
+
+/// The dynamic tuples among the params when the words lay out dynamic
+/// params as `layout_guess` reads them, e.g. the `(address,bytes)` of
+/// `unknown_…(uint256,(address,bytes))`, each with its fields' types and
+/// the tuples in them. Static tuples, encoded as their fields would be,
+/// aren't told apart.
+pub fn layout_tuples(params: &[DecodedParam]) -> Vec<DecodedTuple> {
+    let Some(words) = words(params) else {
+        return vec![];
+    };
+    let layout = Encoding {
+        params,
+        words: &words,
+    };
+    match layout.tuple(0, words.len(), 0) {
+        Some(_) => layout.tuples(0, words.len(), 0),
+        None => vec![],
+    }
+}
+
+/// Each param's word as a number, saturated; `None` when one is short.
+fn words(params: &[DecodedParam]) -> Option<Vec<u128>> {
+    params
+        .iter()
+        .map(|p| match p.raw.len() {
+            64 => Some(hex_u128(&p.raw).unwrap_or(u128::MAX)),
+            _ => None,
+        })
+        .collect()
+}
+
+/// The words of a call read as an ABI encoding.
+struct Encoding<'a> {
+    params: &'a [DecodedParam],
+    words: &'a [u128],
+}
+
+impl Encoding<'_> {
+    /// Types of the tuple in words `[start, end)` with at least one dynamic
+    /// field, its offsets counted from `start`.
+    fn tuple(&self, start: usize, end: usize, depth: usize) -> Option<Vec<String>> {
+        if depth > MAX_LAYOUT_DEPTH {
+            return None;
+        }
+        let (head, offsets) = self.offsets(start, end);
+        if offsets.is_empty() {
+            return None;
+        }
+
+        let ends = offsets.iter().skip(1).map(|o| o.1).chain([end - start]);
+        let mut dynamic = offsets
+            .iter()
+            .zip(ends)
+            .map(|(o, e)| self.member(start + o.1, start + e, depth + 1))
+            .collect::<Option<Vec<String>>>()?
+            .into_iter();
+        (0..head)
+            .map(|i| match offsets.iter().any(|o| o.0 == i) {
+                true => dynamic.next(),
+                false => Some(top_type(&self.params[start + i])),
+            })
+            .collect()
+    }
+
+    /// Words in the head of the tuple in words `[start, end)`, and the
+    /// `(word, target)` of each offset in it, counted from `start`.
+    fn offsets(&self, start: usize, end: usize) -> (usize, Vec<(usize, usize)>) {
+        let n = (end - start) as u128;
+        // Offsets until the head ends where the first one points.
+        let mut head = end - start;
+        let mut offsets: Vec<(usize, usize)> = vec![];
+        let mut i = 0;
+        while i < head {
+            let v = self.words[start + i];
+            let last = offsets.last().map(|o| o.1).unwrap_or(0);
+            if v.is_multiple_of(32) && v / 32 > i as u128 && v / 32 < n && v / 32 > last as u128 {
+                let target = (v / 32) as usize;
+                if offsets.is_empty() {
+                    head = target;
+                }
+                offsets.push((i, target));
+            }
+            i += 1;
+        }
+        (head, offsets)
+    }
+
+    /// The dynamic tuples among the fields of the tuple in words
+    /// `[start, end)`, as `tuple` reads it.
+    fn tuples(&self, start: usize, end: usize, depth: usize) -> Vec<DecodedTuple> {
+        let (_, offsets) = self.offsets(start, end);
+        let ends = offsets.iter().skip(1).map(|o| o.1).chain([end - start]);
+        offsets
+            .iter()
+            .zip(ends)
+            .flat_map(|(o, e)| {
+                self.member_tuples(start + o.0, start + o.1, start + e, depth + 1)
+            })
+            .collect()
+    }
+
+    /// The dynamic tuples of the param in words `[start, end)` whose offset
+    /// is word `offset`, as `member` reads it: the param itself, or the
+    /// ones among the elements of its array.
+    fn member_tuples(
+        &self,
+        offset: usize,
+        start: usize,
+        end: usize,
+        depth: usize,
+    ) -> Vec<DecodedTuple> {
+        if self.flat(start, end).is_some() {
+            return vec![];
+        }
+        if self.dynamic_array(start, end, depth).is_some() {
+            let elements = self.elements(start, end).unwrap_or_default();
+            let ends = elements.iter().skip(1).copied().chain([end]);
+            return elements
+                .iter()
+                .zip(ends)
+                .enumerate()
+                .flat_map(|(i, (&s, e))| self.member_tuples(start + 1 + i, s, e, depth + 1))
+                .collect();
+        }
+        match self.tuple(start, end, depth) {
+            Some(types) => vec![DecodedTuple {
+                offset,
+                start,
+                end,
+                types,
+                tuples: self.tuples(start, end, depth),
+            }],
+            None => vec![],
+        }
+    }
+
+    /// Type of the dynamic param encoded in words `[start, end)`.
+    fn member(&self, start: usize, end: usize, depth: usize) -> Option<String> {
+        self.flat(start, end)
+            .or_else(|| self.dynamic_array(start, end, depth))
+            .or_else(|| {
+                let fields = self.tuple(start, end, depth)?;
+                Some(format!("({})", fields.join(",")))
+            })
+            .or_else(|| self.struct_array(start, end))
+    }
+
+    /// `T[]`, `bytes` or `string` from a length at `start` and the data after it.
+    fn flat(&self, start: usize, end: usize) -> Option<String> {
+        let (len, data) = (self.words[start], (end - start - 1) as u128);
+        let elements = &self.params[start + 1..end];
+        // A single word holds one element unless it's bytes padded on the right.
+        let bytes_like =
+            |raw: &str| raw.len() == 64 && !raw.starts_with("00") && raw.ends_with("00");
+        let is_array = len == data && len > 0 && !(len == 1 && bytes_like(&elements[0].raw));
+        if is_array {
+            return Some(format!("{}[]", common_type(elements.iter())));
+        }
+        match (len.div_ceil(32) == data, &self.params[start].text) {
+            (true, Some(_)) => Some("string".to_string()),
+            (true, None) => Some("bytes".to_string()),
+            (false, _) => None,
+        }
+    }
+
+    /// `T[]` of dynamic elements: a length at `start`, then the offsets of
+    /// the elements counted from the word after it.
+    fn dynamic_array(&self, start: usize, end: usize, depth: usize) -> Option<String> {
+        if depth >= MAX_LAYOUT_DEPTH {
+            return None;
+        }
+        let offsets = self.elements(start, end)?;
+        let ends = offsets.iter().skip(1).copied().chain([end]);
+        let elements = offsets
+            .iter()
+            .zip(ends)
+            .map(|(&s, e)| self.member(s, e, depth + 1))
+            .collect::<Option<Vec<String>>>()?;
+        match elements.iter().all(|t| *t == elements[0]) {
+            true => Some(format!("{}[]", elements[0])),
+            false => None,
+        }
+    }
+
+    /// First word of each element of the array of dynamic elements at
+    /// `start`: a length, then the offsets of the elements counted from the
+    /// word after it, pointing in order past them.
+    fn elements(&self, start: usize, end: usize) -> Option<Vec<usize>> {
+        let (base, len) = (start + 1, self.words[start]);
+        if len == 0 || len > (end - base) as u128 {
+            return None;
+        }
+        let offsets = self.words[base..base + len as usize]
+            .iter()
+            .map(
+                |v| match v.is_multiple_of(32) && v / 32 < (end - base) as u128 {
+                    true => Some(base + (v / 32) as usize),
+                    false => None,
+                },
+            )
+            .collect::<Option<Vec<usize>>>()?;
+        let increasing = offsets.windows(2).all(|w| w[0] < w[1]);
+        match offsets[0] == base + len as usize && increasing {
+            true => Some(offsets),
+            false => None,
+        }
+    }
+
+    /// `(T1,T2,...)[]` of static tuples: a length at `start`, then the
+    /// elements' fields one after the other.
+    fn struct_array(&self, start: usize, end: usize) -> Option<String> {
+        let (len, data) = (self.words[start], (end - start - 1) as u128);
+        if len == 0 || !data.is_multiple_of(len) || data / len < 2 {
+            return None;
+        }
+        let (len, stride) = (len as usize, (data / len) as usize);
+        let fields = (0..stride)
+            .map(|field| {
+                let column = (0..len).map(|e| &self.params[start + 1 + e * stride + field]);
+                common_type(column)
+            })
+            .collect::<Vec<String>>();
+        Some(format!("({})[]", fields.join(",")))
+    }
+}
+
+/// The most likely type all the params share, else `uint256`.
+fn common_type<'a>(mut params: impl Iterator<Item = &'a DecodedParam>) -> String {
+    let Some(first) = params.next().map(top_type) else {
+        return "uint256".to_string();
+    };
+    match params.all(|p| top_type(p) == first) {
+        true => first,
+        false => "uint256".to_string(),
+    }
+}
+
+fn top_type(param: &DecodedParam) -> String {
+    match param.types.first() {
+        Some(t) => t.solidity.clone(),
+        None => "bytes32".to_string(),
+    }
+}
+
+impl DecodedCalldata {
+    /// The `n` most likely signatures of the call from its words alone,
+    /// e.g. `unknown_5d842074(uint256,uint256[])`: the dynamic layout
+    /// reconstructed from offsets and lengths (see `layout_guess`), then
+    /// combinations of the params' candidate types ranked by confidence.
+    pub fn candidate_signatures(&self, n: usize) -> Vec<Prototype> {
+        rank_prototypes(self, &Signatures::new(), n)
+    }
+}
+
+/// The best `limit` type lists by product of candidate confidences.
+///
+/// Starts from each param's most likely type and tries every single-param swap to a lesser
+/// candidate, which covers the useful alternatives without enumerating every combination.
+fn type_combinations(params: &[DecodedParam], limit: usize) -> Vec<(Vec<String>, f64)> {
+    let best = params
+        .iter()
+        .map(|p| p.types.first().map(|t| (t.solidity.clone(), t.confidence)))
+        .map(|t| t.unwrap_or(("bytes32".to_string(), 1.0)))
+        .collect::<Vec<(String, f64)>>();
+    let score = |types: &[(String, f64)]| types.iter().map(|t| t.1).product::<f64>();
+
+    let mut combinations = vec![(best.clone(), score(&best))];
+    for (i, param) in params.iter().enumerate() {
+        for candidate in param.types.iter().skip(1) {
+            let mut types = best.clone();
+            types[i] = (candidate.solidity.clone(), candidate.confidence);
+            let s = score(&types);
+            combinations.push((types, s));
+        }
+    }
+    combinations.sort_by(|a, b| b.1.total_cmp(&a.1));
+    combinations.truncate(limit);
+    combinations
+        .into_iter()
+        .map(|(types, s)| (types.into_iter().map(|t| t.0).collect(), s))
+        .collect()
+}
@@ -0,0 +1,101 @@
+use crate::render::address::serialize_checksum;
+use primitive_types::H160;
+use serde::{Serialize, Serializer};
+
+// ------------------------------------------------------------
+//  Proxies
+// ------------------------------------------------------------
+
+/// EIP-1967 implementation slot, `keccak256("eip1967.proxy.implementation") - 1`.
+pub const IMPLEMENTATION_SLOT: [u8; 32] = [
+    0x36, 0x08, 0x94, 0xa1, 0x3b, 0xa1, 0xa3, 0x21, 0x06, 0x67, 0xc8, 0x28, 0x49, 0x2d, 0xb9, 0x8d,
+    0xca, 0x3e, 0x20, 0x76, 0xcc, 0x37, 0x35, 0xa9, 0x20, 0xa3, 0xca, 0x50, 0x5d, 0x38, 0x2b, 0xbc,
+];
+
+/// EIP-1967 beacon slot, `keccak256("eip1967.proxy.beacon") - 1`.
+pub const BEACON_SLOT: [u8; 32] = [
+    0xa3, 0xf0, 0xad, 0x74, 0xe5, 0x42, 0x3a, 0xeb, 0xfd, 0x80, 0xd3, 0xef, 0x43, 0x46, 0x57, 0x83,
+    0x35, 0xa9, 0xa7, 0x2a, 0xea, 0xee, 0x59, 0xff, 0x6c, 0xb3, 0x58, 0x2b, 0x35, 0x13, 0x3d, 0x50,
+];
+
+/// `implementation()`, which a beacon answers with the implementation.
+pub const BEACON_IMPLEMENTATION: [u8; 4] = [0x5c, 0x60, 0xda, 0x1b];
+
+/// Runtime code of an EIP-1167 minimal proxy, around the implementation's address.
+const MINIMAL_PROXY_PREFIX: [u8; 10] = [0x36, 0x3d, 0x3d, 0x37, 0x3d, 0x3d, 0x3d, 0x36, 0x3d, 0x73];
+const MINIMAL_PROXY_SUFFIX: [u8; 15] = [
+    0x5a, 0xf4, 0x3d, 0x82, 0x80, 0x3e, 0x90, 0x3d, 0x91, 0x60, 0x2b, 0x57, 0xfd, 0x5b, 0xf3,
+];
+
+/// Where a proxy keeps its implementation.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(tag = "kind", rename_all = "camelCase")]
+pub enum ProxyKind {
+    /// In the EIP-1967 implementation slot, e.g. transparent and UUPS proxies.
+    Eip1967,
+    /// Answered by the beacon in the EIP-1967 beacon slot.
+    Beacon {
+        #[serde(serialize_with = "serialize_checksum")]
+        beacon: H160,
+    },
+    /// In the EIP-1167 minimal proxy's code.
+    Minimal,
+}
+
+impl ProxyKind {
+    pub fn name(&self) -> &'static str {
+        match self {
+            ProxyKind::Eip1967 => "EIP-1967",
+            ProxyKind::Beacon { .. } => "EIP-1967 beacon",
+            ProxyKind::Minimal => "EIP-1167",
+        }
+    }
+}
+
+/// The implementation a proxy delegated to at a block.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ProxyResolution {
+    #[serde(flatten)]
+    pub kind: ProxyKind,
+    #[serde(serialize_with = "serialize_checksum")]
+    pub implementation: H160,
+    /// `None` for the latest block.
+    pub block: Option<u64>,
+    /// Selectors of the implementation's dispatch table, sorted.
+    #[serde(serialize_with = "serialize_selectors")]
+    pub selectors: Vec<[u8; 4]>,
+}
+
+/// Serializes selectors as `0x` prefixed hex.
+fn serialize_selectors<S: Serializer>(
+    selectors: &[[u8; 4]],
+    serializer: S,
+) -> Result<S::Ok, S::Error> {
+    serializer.collect_seq(
+        selectors
+            .iter()
+            .map(|s| format!("0x{}", faster_hex::hex_string(s))),
+    )
+}
+
+/// The address held in a storage slot, `None` when it's zero or has bits set
+/// above an address.
+pub fn slot_address(word: &[u8; 32]) -> Option<H160> {
+    match word[..12].iter().all(|b| *b == 0) && word[12..].iter().any(|b| *b != 0) {
+        true => Some(H160::from_slice(&word[12..])),
+        false => None,
+    }
+}
+
+/// The implementation of an EIP-1167 minimal proxy, given its runtime code.
+pub fn minimal_proxy_implementation(code: &[u8]) -> Option<H160> {
+    let prefix = MINIMAL_PROXY_PREFIX.len();
+    match code.len() == prefix + 20 + MINIMAL_PROXY_SUFFIX.len()
+        && code.starts_with(&MINIMAL_PROXY_PREFIX)
+        && code.ends_with(&MINIMAL_PROXY_SUFFIX)
+    {
+        true => Some(H160::from_slice(&code[prefix..prefix + 20])),
+        false => None,
+    }
+}
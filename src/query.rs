@@ -0,0 +1,352 @@
+use crate::decoded::DecodedCalldata;
+use crate::select::{parse_path, select, Segment};
+use primitive_types::U256;
+use serde_json::Value;
+
+// ------------------------------------------------------------
+//  Queries
+// ------------------------------------------------------------
+
+/// Which calls of a decode tree a query looks at.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Scope {
+    /// The top-level call and every call nested in it.
+    Calls,
+    /// The top-level call only.
+    Root,
+}
+
+/// How a field is compared to a literal.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Op {
+    Eq,
+    Ne,
+    Gt,
+    Ge,
+    Lt,
+    Le,
+    /// The field's text holds the literal, case insensitively.
+    Contains,
+}
+
+/// A condition on a call.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Expr {
+    Compare {
+        /// Path into the call, as for `--select`, e.g. `params[1]`.
+        path: Vec<Segment>,
+        op: Op,
+        literal: String,
+    },
+    And(Box<Expr>, Box<Expr>),
+    Or(Box<Expr>, Box<Expr>),
+    Not(Box<Expr>),
+}
+
+/// A filter over decode trees, e.g. to scan a batch for unlimited approvals:
+///
+/// `select calls where selector == 0x095ea7b3 and params[1] > 1e30`
+///
+/// Each call is matched on its fields as in the JSON output (`selector`,
+/// `signature_guess`, `params[i].types[0].solidity`, ...) plus its `depth`, 0
+/// for the top-level call. A param compares by its word: as a number with `>`, `<`,
+/// and with `==` and `!=` to numbers; otherwise by its value. Literals are
+/// numbers (`1000`, `0x3b9aca00`, `1.5e18`) or quoted text. Conditions combine
+/// with `and`, `or`, `not` and parentheses, and a field a call doesn't have
+/// fails its condition.
+///
+/// `select calls` looks at every call of the tree, `select root` at the
+/// top-level call only; the `select ... where` around the condition can be
+/// left out, looking at every call.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Query {
+    pub scope: Scope,
+    /// `None` matches every call in scope.
+    pub filter: Option<Expr>,
+}
+
+impl Query {
+    pub fn parse(text: &str) -> Result<Self, String> {
+        let tokens = tokenize(text)?;
+        let mut parser = Parser { tokens, pos: 0 };
+        let mut scope = Scope::Calls;
+        let mut filter = None;
+        match parser.keyword("select") {
+            true => {
+                scope = match parser.next() {
+                    Some(Token::Word(w)) if w.eq_ignore_ascii_case("calls") => Scope::Calls,
+                    Some(Token::Word(w)) if w.eq_ignore_ascii_case("root") => Scope::Root,
+                    _ => return Err("expected `calls` or `root` after `select`".to_string()),
+                };
+                if parser.keyword("where") {
+                    filter = Some(parser.or()?);
+                }
+            }
+            false => filter = Some(parser.or()?),
+        }
+        match parser.next() {
+            None => Ok(Self { scope, filter }),
+            Some(token) => Err(format!("unexpected {}", token)),
+        }
+    }
+
+    /// The calls of `root` in scope matching the query, parents first, with
+    /// their path: `""` for the top-level call, `calls[0]`, `calls[0].calls[1]`, ...
+    pub fn find<'a>(&self, root: &'a DecodedCalldata) -> Vec<(String, &'a DecodedCalldata)> {
+        let calls = match self.scope {
+            Scope::Calls => root.walk_paths(),
+            Scope::Root => vec![(String::new(), root)],
+        };
+        calls
+            .into_iter()
+            .filter(|(prefix, call)| {
+                let Some(filter) = &self.filter else {
+                    return true;
+                };
+                let mut value = serde_json::to_value(call).unwrap_or_default();
+                if let Value::Object(fields) = &mut value {
+                    fields.insert("depth".to_string(), prefix.matches("calls[").count().into());
+                }
+                filter.eval(&value)
+            })
+            .map(|(prefix, call)| (prefix.trim_end_matches('.').to_string(), call))
+            .collect()
+    }
+
+    /// Some call of `root` in scope matches the query.
+    pub fn matches(&self, root: &DecodedCalldata) -> bool {
+        !self.find(root).is_empty()
+    }
+}
+
+impl Expr {
+    fn eval(&self, call: &Value) -> bool {
+        match self {
+            Expr::And(a, b) => a.eval(call) && b.eval(call),
+            Expr::Or(a, b) => a.eval(call) || b.eval(call),
+            Expr::Not(a) => !a.eval(call),
+            Expr::Compare { path, op, literal } => match select(call, path) {
+                Some(field) => compare(field, *op, literal),
+                None => false,
+            },
+        }
+    }
+}
+
+fn compare(field: &Value, op: Op, literal: &str) -> bool {
+    let numbers = number_of(field).zip(parse_number(literal));
+    let text = text_of(field);
+    let same = |a: &str, b: &str| normalize(a) == normalize(b);
+    match (op, numbers) {
+        (Op::Eq, Some((a, b))) => a == b || same(&text, literal),
+        (Op::Eq, None) => same(&text, literal),
+        (Op::Ne, Some((a, b))) => a != b && !same(&text, literal),
+        (Op::Ne, None) => !same(&text, literal),
+        (Op::Gt, Some((a, b))) => a > b,
+        (Op::Ge, Some((a, b))) => a >= b,
+        (Op::Lt, Some((a, b))) => a < b,
+        (Op::Le, Some((a, b))) => a <= b,
+        (Op::Gt | Op::Ge | Op::Lt | Op::Le, None) => false,
+        (Op::Contains, _) => text.to_lowercase().contains(&literal.to_lowercase()),
+    }
+}
+
+/// Lowercase and without `0x`, so selectors and addresses compare however written.
+fn normalize(text: &str) -> String {
+    let text = text.to_lowercase();
+    text.strip_prefix("0x").unwrap_or(&text).to_string()
+}
+
+/// A param's word, or a numeric field.
+fn number_of(field: &Value) -> Option<U256> {
+    match field {
+        Value::Object(param) => match param.get("raw").and_then(|r| r.as_str()) {
+            Some(raw) if raw.len() == 64 => U256::from_str_radix(raw, 16).ok(),
+            _ => None,
+        },
+        Value::Number(n) => n.as_u64().map(U256::from),
+        Value::String(s) => parse_number(s),
+        _ => None,
+    }
+}
+
+/// A param's value, or the field as text.
+fn text_of(field: &Value) -> String {
+    match field {
+        Value::Object(param) => param
+            .get("value")
+            .and_then(|v| v.as_str())
+            .unwrap_or_default()
+            .to_string(),
+        Value::String(s) => s.clone(),
+        other => other.to_string(),
+    }
+}
+
+/// Decimal, `0x` hex or scientific (`1e30`, `1.5e18`) integers.
+fn parse_number(text: &str) -> Option<U256> {
+    if let Some(hex) = text.strip_prefix("0x") {
+        return match hex.len() <= 64 {
+            true => U256::from_str_radix(hex, 16).ok(),
+            false => None,
+        };
+    }
+    let (mantissa, exponent) = match text.split_once(['e', 'E']) {
+        Some((mantissa, exponent)) => (mantissa, exponent.parse::<usize>().ok()?),
+        None => (text, 0),
+    };
+    let (int, fraction) = mantissa.split_once('.').unwrap_or((mantissa, ""));
+    let fraction = fraction.trim_end_matches('0');
+    let digits = format!("{}{}", int, fraction);
+    if digits.is_empty() || !digits.bytes().all(|b| b.is_ascii_digit()) {
+        return None;
+    }
+    let shift = exponent.checked_sub(fraction.len())?;
+    let scale = U256::from(10).checked_pow(U256::from(shift))?;
+    U256::from_dec_str(&digits).ok()?.checked_mul(scale)
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum Token {
+    /// A keyword, path or unquoted literal.
+    Word(String),
+    Quoted(String),
+    Op(Op),
+    Open,
+    Close,
+}
+
+impl std::fmt::Display for Token {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Token::Word(w) => write!(f, "`{}`", w),
+            Token::Quoted(q) => write!(f, "\"{}\"", q),
+            Token::Op(op) => write!(f, "{:?}", op),
+            Token::Open => write!(f, "`(`"),
+            Token::Close => write!(f, "`)`"),
+        }
+    }
+}
+
+fn tokenize(text: &str) -> Result<Vec<Token>, String> {
+    let mut tokens = vec![];
+    let mut chars = text.char_indices().peekable();
+    while let Some((i, c)) = chars.next() {
+        let mut next_is = |expected: char| chars.next_if(|(_, c)| *c == expected).is_some();
+        let token = match c {
+            c if c.is_whitespace() => continue,
+            '(' => Token::Open,
+            ')' => Token::Close,
+            '=' => {
+                next_is('=');
+                Token::Op(Op::Eq)
+            }
+            '!' if next_is('=') => Token::Op(Op::Ne),
+            '>' if next_is('=') => Token::Op(Op::Ge),
+            '>' => Token::Op(Op::Gt),
+            '<' if next_is('=') => Token::Op(Op::Le),
+            '<' => Token::Op(Op::Lt),
+            '"' | '\'' => {
+                let rest = &text[i + 1..];
+                let end = rest
+                    .find(c)
+                    .ok_or_else(|| format!("unterminated text at {}", i))?;
+                for _ in rest[..=end].chars() {
+                    chars.next();
+                }
+                Token::Quoted(rest[..end].to_string())
+            }
+            c if is_word(c) => {
+                let mut word = c.to_string();
+                while let Some((_, c)) = chars.next_if(|(_, c)| is_word(*c)) {
+                    word.push(c);
+                }
+                Token::Word(word)
+            }
+            c => return Err(format!("unexpected `{}` at {}", c, i)),
+        };
+        tokens.push(token);
+    }
+    Ok(tokens)
+}
+
+fn is_word(c: char) -> bool {
+    c.is_ascii_alphanumeric() || matches!(c, '_' | '.' | '[' | ']')
+}
+
+/// Recursive descent over `or`, `and`, `not` and comparisons, loosest first.
+struct Parser {
+    tokens: Vec<Token>,
+    pos: usize,
+}
+
+impl Parser {
+    fn next(&mut self) -> Option<Token> {
+        let token = self.tokens.get(self.pos).cloned();
+        self.pos += 1;
+        token
+    }
+
+    /// Consumes `keyword` if it's next.
+    fn keyword(&mut self, keyword: &str) -> bool {
+        match self.tokens.get(self.pos) {
+            Some(Token::Word(w)) if w.eq_ignore_ascii_case(keyword) => {
+                self.pos += 1;
+                true
+            }
+            _ => false,
+        }
+    }
+
+    fn or(&mut self) -> Result<Expr, String> {
+        let mut expr = self.and()?;
+        while self.keyword("or") {
+            expr = Expr::Or(Box::new(expr), Box::new(self.and()?));
+        }
+        Ok(expr)
+    }
+
+    fn and(&mut self) -> Result<Expr, String> {
+        let mut expr = self.not()?;
+        while self.keyword("and") {
+            expr = Expr::And(Box::new(expr), Box::new(self.not()?));
+        }
+        Ok(expr)
+    }
+
+    fn not(&mut self) -> Result<Expr, String> {
+        match self.keyword("not") {
+            true => Ok(Expr::Not(Box::new(self.not()?))),
+            false => self.comparison(),
+        }
+    }
+
+    fn comparison(&mut self) -> Result<Expr, String> {
+        let path = match self.next() {
+            Some(Token::Open) => {
+                let expr = self.or()?;
+                return match self.next() {
+                    Some(Token::Close) => Ok(expr),
+                    _ => Err("expected `)`".to_string()),
+                };
+            }
+            Some(Token::Word(path)) => path,
+            Some(token) => return Err(format!("expected a field, found {}", token)),
+            None => return Err("expected a field".to_string()),
+        };
+        let op = match self.next() {
+            Some(Token::Op(op)) => op,
+            Some(Token::Word(w)) if w.eq_ignore_ascii_case("contains") => Op::Contains,
+            _ => return Err(format!("expected an operator after `{}`", path)),
+        };
+        let literal = match self.next() {
+            Some(Token::Word(literal) | Token::Quoted(literal)) => literal,
+            _ => return Err(format!("expected a value after `{}`", path)),
+        };
+        Ok(Expr::Compare {
+            path: parse_path(&path)?,
+            op,
+            literal,
+        })
+    }
+}
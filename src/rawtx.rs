@@ -0,0 +1,327 @@
+use crate::decoded::DecodedCalldata;
+use crate::heuristics::Heuristics;
+use crate::limits::Limits;
+use crate::render::address::{serialize_checksum, serialize_checksum_opt};
+use crate::rlp::Rlp;
+use crate::{decode_hex_checked, keccak256, Calldata};
+use primitive_types::{H160, H256, U256};
+use serde::{Serialize, Serializer};
+
+// ------------------------------------------------------------
+//  Raw transactions
+// ------------------------------------------------------------
+
+/// A signed transaction read from its raw encoding, as returned by
+/// `eth_getRawTransactionByHash` or broadcast with `eth_sendRawTransaction`.
+///
+/// The fee and list fields of the typed envelopes are `None` or empty for the
+/// types that don't have them.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RawTransaction {
+    /// Keccak-256 of the raw bytes, without the blob sidecar.
+    pub hash: H256,
+    /// EIP-2718 type: 0 legacy, 1 access list, 2 dynamic fee, 3 blob.
+    #[serde(rename = "type")]
+    pub tx_type: u8,
+    /// `None` for a pre-EIP-155 signature, which isn't bound to a chain.
+    pub chain_id: Option<u64>,
+    pub nonce: u64,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub gas_price: Option<U256>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub max_priority_fee_per_gas: Option<U256>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub max_fee_per_gas: Option<U256>,
+    pub gas_limit: U256,
+    /// `None` for contract creations, whose input is init code rather than calldata.
+    #[serde(serialize_with = "serialize_checksum_opt")]
+    pub to: Option<H160>,
+    pub value: U256,
+    #[serde(serialize_with = "serialize_hex")]
+    pub input: Vec<u8>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub access_list: Vec<AccessListItem>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub max_fee_per_blob_gas: Option<U256>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub blob_versioned_hashes: Vec<H256>,
+    /// Legacy `v`, or the y parity of a typed envelope's signature.
+    pub v: u64,
+    pub r: U256,
+    pub s: U256,
+}
+
+/// An address and the storage slots a transaction declares it will access.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AccessListItem {
+    #[serde(serialize_with = "serialize_checksum")]
+    pub address: H160,
+    pub storage_keys: Vec<H256>,
+}
+
+impl RawTransaction {
+    /// Parses the hex of a raw transaction, with or without the `0x` prefix.
+    pub fn from_hex(raw: &str) -> Result<Self, String> {
+        Self::from_bytes(&decode_hex_checked(raw)?)
+    }
+
+    /// Parses a raw transaction: a legacy RLP list
+    /// `[nonce, gasPrice, gasLimit, to, value, input, v, r, s]`, or an
+    /// EIP-2718 envelope, its type byte followed by the RLP list of its fields.
+    /// A blob transaction may come in its network form, wrapped with its sidecar.
+    pub fn from_bytes(raw: &[u8]) -> Result<Self, String> {
+        match raw.first() {
+            None => Err("empty transaction".to_string()),
+            Some(0x01) => Self::access_list(raw),
+            Some(0x02) => Self::dynamic_fee(raw),
+            Some(0x03) => Self::blob(raw),
+            Some(&ty) if ty < 0x80 => Err(format!("unknown transaction type {}", ty)),
+            Some(_) => Self::legacy(raw),
+        }
+    }
+
+    fn legacy(raw: &[u8]) -> Result<Self, String> {
+        let item = Rlp::decode(raw)?;
+        let fields = item.as_list()?;
+        let [nonce, gas_price, gas_limit, to, value, input, v, r, s] = fields else {
+            return Err(fields_error("a legacy transaction", 9, fields));
+        };
+        let v = v.as_u64()?;
+        let chain_id = match v {
+            27 | 28 => None,
+            35.. => Some((v - 35) / 2),
+            _ => return Err(format!("invalid signature v {}", v)),
+        };
+        Ok(Self {
+            hash: H256(keccak256(raw)),
+            tx_type: 0,
+            chain_id,
+            nonce: nonce.as_u64()?,
+            gas_price: Some(gas_price.as_u256()?),
+            max_priority_fee_per_gas: None,
+            max_fee_per_gas: None,
+            gas_limit: gas_limit.as_u256()?,
+            to: parse_to(to)?,
+            value: value.as_u256()?,
+            input: input.as_bytes()?.to_vec(),
+            access_list: vec![],
+            max_fee_per_blob_gas: None,
+            blob_versioned_hashes: vec![],
+            v,
+            r: r.as_u256()?,
+            s: s.as_u256()?,
+        })
+    }
+
+    /// EIP-2930: `0x01 || rlp([chainId, nonce, gasPrice, gasLimit, to, value,
+    /// input, accessList, yParity, r, s])`.
+    fn access_list(raw: &[u8]) -> Result<Self, String> {
+        let item = Rlp::decode(&raw[1..])?;
+        let fields = item.as_list()?;
+        let [chain_id, nonce, gas_price, gas_limit, to, value, input, access_list, y, r, s] =
+            fields
+        else {
+            return Err(fields_error("an access list transaction", 11, fields));
+        };
+        Ok(Self {
+            hash: H256(keccak256(raw)),
+            tx_type: 1,
+            chain_id: Some(chain_id.as_u64()?),
+            nonce: nonce.as_u64()?,
+            gas_price: Some(gas_price.as_u256()?),
+            max_priority_fee_per_gas: None,
+            max_fee_per_gas: None,
+            gas_limit: gas_limit.as_u256()?,
+            to: parse_to(to)?,
+            value: value.as_u256()?,
+            input: input.as_bytes()?.to_vec(),
+            access_list: parse_access_list(access_list)?,
+            max_fee_per_blob_gas: None,
+            blob_versioned_hashes: vec![],
+            v: parse_parity(y)?,
+            r: r.as_u256()?,
+            s: s.as_u256()?,
+        })
+    }
+
+    /// EIP-1559: `0x02 || rlp([chainId, nonce, maxPriorityFeePerGas,
+    /// maxFeePerGas, gasLimit, to, value, input, accessList, yParity, r, s])`.
+    fn dynamic_fee(raw: &[u8]) -> Result<Self, String> {
+        let item = Rlp::decode(&raw[1..])?;
+        let fields = item.as_list()?;
+        let [chain_id, nonce, tip, fee_cap, gas_limit, to, value, input, access_list, y, r, s] =
+            fields
+        else {
+            return Err(fields_error("a dynamic fee transaction", 12, fields));
+        };
+        Ok(Self {
+            hash: H256(keccak256(raw)),
+            tx_type: 2,
+            chain_id: Some(chain_id.as_u64()?),
+            nonce: nonce.as_u64()?,
+            gas_price: None,
+            max_priority_fee_per_gas: Some(tip.as_u256()?),
+            max_fee_per_gas: Some(fee_cap.as_u256()?),
+            gas_limit: gas_limit.as_u256()?,
+            to: parse_to(to)?,
+            value: value.as_u256()?,
+            input: input.as_bytes()?.to_vec(),
+            access_list: parse_access_list(access_list)?,
+            max_fee_per_blob_gas: None,
+            blob_versioned_hashes: vec![],
+            v: parse_parity(y)?,
+            r: r.as_u256()?,
+            s: s.as_u256()?,
+        })
+    }
+
+    /// EIP-4844: `0x03 || rlp([chainId, nonce, maxPriorityFeePerGas,
+    /// maxFeePerGas, gasLimit, to, value, input, accessList, maxFeePerBlobGas,
+    /// blobVersionedHashes, yParity, r, s])`, or in the network form
+    /// `0x03 || rlp([tx, blobs, commitments, proofs])`, hashed without the sidecar.
+    fn blob(raw: &[u8]) -> Result<Self, String> {
+        let item = Rlp::decode(&raw[1..])?;
+        let (fields, hashed) = match item.as_list()? {
+            [Rlp::List(fields), _, _, _] => {
+                let tx = Rlp::raw_items(&raw[1..])?[0];
+                (fields.as_slice(), [&raw[..1], tx].concat())
+            }
+            fields => (fields, raw.to_vec()),
+        };
+        let [chain, nonce, tip, fee_cap, gas, to, value, input, list, blob_fee, hashes, y, r, s] =
+            fields
+        else {
+            return Err(fields_error("a blob transaction", 14, fields));
+        };
+        let to = parse_to(to)?.ok_or("a blob transaction can't create a contract")?;
+        let blob_versioned_hashes = hashes
+            .as_list()?
+            .iter()
+            .map(parse_h256)
+            .collect::<Result<Vec<_>, String>>()?;
+        Ok(Self {
+            hash: H256(keccak256(&hashed)),
+            tx_type: 3,
+            chain_id: Some(chain.as_u64()?),
+            nonce: nonce.as_u64()?,
+            gas_price: None,
+            max_priority_fee_per_gas: Some(tip.as_u256()?),
+            max_fee_per_gas: Some(fee_cap.as_u256()?),
+            gas_limit: gas.as_u256()?,
+            to: Some(to),
+            value: value.as_u256()?,
+            input: input.as_bytes()?.to_vec(),
+            access_list: parse_access_list(list)?,
+            max_fee_per_blob_gas: Some(blob_fee.as_u256()?),
+            blob_versioned_hashes,
+            v: parse_parity(y)?,
+            r: r.as_u256()?,
+            s: s.as_u256()?,
+        })
+    }
+
+    /// Name of the envelope's type, e.g. `dynamic fee` for type 2.
+    pub fn type_name(&self) -> &'static str {
+        match self.tx_type {
+            0 => "legacy",
+            1 => "access list",
+            2 => "dynamic fee",
+            _ => "blob",
+        }
+    }
+
+    /// Whether the input is calldata to decode: a call carrying at least a selector.
+    pub fn has_calldata(&self) -> bool {
+        self.to.is_some() && self.input.len() >= 4
+    }
+}
+
+fn fields_error(what: &str, expected: usize, fields: &[Rlp]) -> String {
+    format!("{} has {} fields, found {}", what, expected, fields.len())
+}
+
+fn parse_parity(item: &Rlp) -> Result<u64, String> {
+    match item.as_u64()? {
+        y @ (0 | 1) => Ok(y),
+        y => Err(format!("invalid signature y parity {}", y)),
+    }
+}
+
+pub(crate) fn parse_h256(item: &Rlp) -> Result<H256, String> {
+    match item.as_bytes()? {
+        bytes if bytes.len() == 32 => Ok(H256::from_slice(bytes)),
+        bytes => Err(format!("{} byte hash isn't 32 bytes", bytes.len())),
+    }
+}
+
+/// `[[address, [storageKey, ...]], ...]`
+pub(crate) fn parse_access_list(item: &Rlp) -> Result<Vec<AccessListItem>, String> {
+    item.as_list()?
+        .iter()
+        .map(|entry| {
+            let [address, keys] = entry.as_list()? else {
+                return Err("access list entries are [address, storageKeys]".to_string());
+            };
+            Ok(AccessListItem {
+                address: parse_to(address)?.ok_or("empty access list address")?,
+                storage_keys: keys
+                    .as_list()?
+                    .iter()
+                    .map(parse_h256)
+                    .collect::<Result<_, _>>()?,
+            })
+        })
+        .collect()
+}
+
+/// The recipient, empty for a contract creation.
+pub(crate) fn parse_to(item: &Rlp) -> Result<Option<H160>, String> {
+    match item.as_bytes()? {
+        [] => Ok(None),
+        bytes if bytes.len() == 20 => Ok(Some(H160::from_slice(bytes))),
+        bytes => Err(format!("{} byte recipient isn't an address", bytes.len())),
+    }
+}
+
+pub(crate) fn serialize_hex<S: Serializer>(bytes: &[u8], serializer: S) -> Result<S::Ok, S::Error> {
+    serializer.serialize_str(&format!("0x{}", faster_hex::hex_string(bytes)))
+}
+
+/// A raw transaction along with the decode of its input.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DecodedRawTransaction {
+    pub transaction: RawTransaction,
+    /// `None` when the input isn't calldata, see [`RawTransaction::has_calldata`].
+    pub decoded: Option<DecodedCalldata>,
+}
+
+impl DecodedRawTransaction {
+    /// Decodes the input of `transaction` under `heuristics` and `limits`.
+    pub fn new(
+        transaction: RawTransaction,
+        heuristics: Heuristics,
+        limits: Limits,
+    ) -> Result<Self, String> {
+        let decoded = match transaction.has_calldata() {
+            true => {
+                let input = transaction.input.clone();
+                Some(Calldata::from_bytes_with_limits(input, heuristics, limits)?.decode())
+            }
+            false => None,
+        };
+        Ok(Self {
+            transaction,
+            decoded,
+        })
+    }
+}
+
+/// Parses a raw transaction's hex and decodes its input with the default
+/// heuristics and limits.
+pub fn decode_raw_transaction(raw: &str) -> Result<DecodedRawTransaction, String> {
+    let transaction = RawTransaction::from_hex(raw)?;
+    DecodedRawTransaction::new(transaction, Heuristics::default(), Limits::default())
+}
@@ -0,0 +1,220 @@
+use crate::decoded::{render_value, DecodedCalldata, Span};
+use crate::keccak256;
+use crate::type_guesser::ParamTypes;
+use primitive_types::U256;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+
+// ------------------------------------------------------------
+//  Redaction
+// ------------------------------------------------------------
+
+/// What a redacted value was.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum RedactKind {
+    /// An `address` param.
+    Address,
+    /// A `uintN` param wider than a byte: amounts, but also ids and deadlines.
+    Amount,
+    /// A `bytes32` param: salts, hashes, identifiers.
+    Salt,
+}
+
+impl RedactKind {
+    pub fn name(&self) -> &'static str {
+        match self {
+            RedactKind::Address => "address",
+            RedactKind::Amount => "amount",
+            RedactKind::Salt => "salt",
+        }
+    }
+}
+
+/// How redacted values read in the decode.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum RedactMode {
+    /// As their pseudonym, e.g. a made-up address.
+    #[default]
+    Pseudonymize,
+    /// As a numbered placeholder, e.g. `[address 1]`.
+    Mask,
+}
+
+/// A param that was redacted.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Redacted {
+    pub kind: RedactKind,
+    /// e.g. `calls[0].params[2]`.
+    pub path: String,
+    pub span: Span,
+    /// e.g. `address 1`, the same for the same value wherever it appears.
+    pub label: String,
+}
+
+/// Calldata and its decode with the sensitive values replaced.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Redaction {
+    /// The calldata with the pseudonyms in place of the values, `0x` prefixed.
+    pub calldata: String,
+    pub decoded: DecodedCalldata,
+    /// In calldata order.
+    pub redacted: Vec<Redacted>,
+}
+
+/// Replaces addresses, amounts and salts in calldata and its decode, e.g. to
+/// share the calldata of an incident before disclosure.
+///
+/// Each value is replaced by a pseudonym keyed by a secret: the same value
+/// gets the same pseudonym throughout, so which params hold the same address
+/// still shows, while the key keeps the values from being guessed back by
+/// hashing candidates. Amounts keep their number of bytes. Offsets, lengths
+/// and selectors are left alone, so the redacted calldata decodes to the same
+/// structure.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Redactor {
+    key: Vec<u8>,
+    mode: RedactMode,
+    kinds: Vec<RedactKind>,
+}
+
+impl Redactor {
+    /// Redacts every kind of value, pseudonymized with `key`.
+    pub fn new(key: &[u8]) -> Self {
+        Self {
+            key: key.to_vec(),
+            mode: RedactMode::default(),
+            kinds: vec![RedactKind::Address, RedactKind::Amount, RedactKind::Salt],
+        }
+    }
+
+    pub fn with_mode(mut self, mode: RedactMode) -> Self {
+        self.mode = mode;
+        self
+    }
+
+    /// Only redacts these kinds of values.
+    pub fn with_kinds(mut self, kinds: Vec<RedactKind>) -> Self {
+        self.kinds = kinds;
+        self
+    }
+
+    /// Redacts `calldata`, `root` being its decode.
+    ///
+    /// Zero words are left as they are, as they don't tell anything, and so are
+    /// amounts no larger than the calldata, which could be offsets or lengths.
+    pub fn redact(&self, calldata: &[u8], root: &DecodedCalldata) -> Redaction {
+        let mut bytes = calldata.to_vec();
+        let mut numbers = BTreeMap::<(RedactKind, [u8; 32]), usize>::new();
+        let mut counts = BTreeMap::<RedactKind, usize>::new();
+        let mut masks = BTreeMap::<usize, String>::new();
+        let mut redacted = vec![];
+        for (path, param) in root.leaf_params() {
+            let span = param.span;
+            let Some(word) = calldata
+                .get(span.start..span.end)
+                .and_then(|w| <[u8; 32]>::try_from(w).ok())
+            else {
+                continue;
+            };
+            let Some(kind) = param
+                .types
+                .first()
+                .and_then(|t| kind_of(&t.solidity))
+                .filter(|kind| self.kinds.contains(kind) && !structural(*kind, &word, calldata))
+            else {
+                continue;
+            };
+            let number = *numbers.entry((kind, word)).or_insert_with(|| {
+                let count = counts.entry(kind).or_default();
+                *count += 1;
+                *count
+            });
+            let label = format!("{} {}", kind.name(), number);
+            bytes[span.start..span.end].copy_from_slice(&self.pseudonym(kind, &word));
+            if self.mode == RedactMode::Mask {
+                masks.insert(span.start, format!("[{}]", label));
+            }
+            redacted.push(Redacted {
+                kind,
+                path,
+                span,
+                label,
+            });
+        }
+        let mut decoded = root.clone();
+        rewrite(&mut decoded, &bytes, &masks);
+        Redaction {
+            calldata: format!("0x{}", faster_hex::hex_string(&bytes)),
+            decoded,
+            redacted,
+        }
+    }
+
+    /// A value of the same kind derived from `word` and the key.
+    fn pseudonym(&self, kind: RedactKind, word: &[u8; 32]) -> [u8; 32] {
+        let mut input = self.key.clone();
+        input.push(kind as u8);
+        input.extend_from_slice(word);
+        let hash = keccak256(&input);
+        let mut out = [0; 32];
+        let start = match kind {
+            RedactKind::Address => 12,
+            RedactKind::Amount => word.iter().position(|b| *b != 0).unwrap_or(31),
+            RedactKind::Salt => 0,
+        };
+        out[start..].copy_from_slice(&hash[start..]);
+        // With the top bit of its leading byte set, an amount keeps its number
+        // of bytes and doesn't shrink into an offset.
+        if kind == RedactKind::Amount {
+            out[start] |= 0x80;
+        }
+        out
+    }
+}
+
+/// Zero, or an amount that could be an offset or a length in `calldata`.
+fn structural(kind: RedactKind, word: &[u8; 32], calldata: &[u8]) -> bool {
+    match kind {
+        RedactKind::Amount => U256::from_big_endian(word) <= U256::from(calldata.len()),
+        RedactKind::Address | RedactKind::Salt => *word == [0; 32],
+    }
+}
+
+fn kind_of(ty: &str) -> Option<RedactKind> {
+    match ty {
+        "address" => Some(RedactKind::Address),
+        "bytes32" => Some(RedactKind::Salt),
+        "uint8" => None,
+        _ if ty.starts_with("uint") => Some(RedactKind::Amount),
+        _ => None,
+    }
+}
+
+/// Sets the words of `call` and its nested calls to those of `bytes`, masked
+/// ones to their placeholder, the params holding nested calls included.
+fn rewrite(call: &mut DecodedCalldata, bytes: &[u8], masks: &BTreeMap<usize, String>) {
+    for param in call.params.iter_mut() {
+        let Some(word) = bytes.get(param.span.start..param.span.end) else {
+            continue;
+        };
+        let raw = faster_hex::hex_string(word);
+        if raw == param.raw {
+            continue;
+        }
+        param.value = match masks.get(&param.span.start) {
+            Some(mask) => mask.clone(),
+            None => {
+                let kinds = param.types.iter().map(|t| t.kind.clone()).collect();
+                render_value(&ParamTypes::new(kinds), &raw)
+            }
+        };
+        param.raw = raw;
+    }
+    for nested in call.calls.iter_mut() {
+        rewrite(nested, bytes, masks);
+    }
+}
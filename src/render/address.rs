@@ -0,0 +1,85 @@
+use crate::constants::Types;
+use crate::decode_hex;
+use crate::decoded::DecodedCalldata;
+use crate::keccak256;
+use serde::{Deserialize, Serialize, Serializer};
+
+// ------------------------------------------------------------
+//  Address case
+// ------------------------------------------------------------
+
+/// How addresses are written in the output.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum AddressCase {
+    /// EIP-55 mixed case, which wallets verify.
+    #[default]
+    Checksum,
+    /// All lowercase, as the raw hex.
+    Lowercase,
+}
+
+/// `0x` prefixed EIP-55 checksum of the 20 bytes of an address.
+pub fn to_checksum(address: &[u8]) -> String {
+    let lower = faster_hex::hex_string(address);
+    let hash = keccak256(lower.as_bytes());
+    let mut out = String::with_capacity(42);
+    out.push_str("0x");
+    for (i, c) in lower.chars().enumerate() {
+        // A letter is uppercased when its nibble of the hash of the lowercase hex is 8 or more.
+        let nibble = (hash[i / 2] >> (4 * (1 - i % 2))) & 0xf;
+        match nibble >= 8 {
+            true => out.push(c.to_ascii_uppercase()),
+            false => out.push(c),
+        }
+    }
+    out
+}
+
+/// `0x` prefixed EIP-55 checksummed address of 40 hex digits, `None` for anything else.
+pub fn checksum_hex(hex: &str) -> Option<String> {
+    let digits = hex.strip_prefix("0x").unwrap_or(hex);
+    match digits.len() == 40 && digits.bytes().all(|c| c.is_ascii_hexdigit()) {
+        true => Some(to_checksum(&decode_hex(digits))),
+        false => None,
+    }
+}
+
+/// Serializes an address checksummed, for `#[serde(serialize_with)]`.
+pub fn serialize_checksum<S: Serializer>(
+    address: &impl AsRef<[u8]>,
+    s: S,
+) -> Result<S::Ok, S::Error> {
+    s.serialize_str(&to_checksum(address.as_ref()))
+}
+
+/// Serializes an optional address checksummed, for `#[serde(serialize_with)]`.
+pub fn serialize_checksum_opt<S: Serializer, A: AsRef<[u8]>>(
+    address: &Option<A>,
+    s: S,
+) -> Result<S::Ok, S::Error> {
+    match address {
+        Some(address) => serialize_checksum(address, s),
+        None => s.serialize_none(),
+    }
+}
+
+/// Rewrites every address param in `case`, decodes having them checksummed.
+pub fn apply_address_case(root: &mut DecodedCalldata, case: AddressCase) {
+    if case == AddressCase::Checksum {
+        return;
+    }
+    for param in root.params.iter_mut() {
+        let is_address = matches!(
+            param.types.first().map(|t| &t.kind),
+            Some(Types::Address | Types::Address0)
+        );
+        if let (true, Some(address)) = (is_address, param.value.get(..42)) {
+            let lower = address.to_lowercase();
+            param.value.replace_range(..42, &lower);
+        }
+    }
+    for nested in root.calls.iter_mut() {
+        apply_address_case(nested, case);
+    }
+}
@@ -0,0 +1,88 @@
+use serde::{Deserialize, Serialize};
+use std::io::IsTerminal;
+
+// ------------------------------------------------------------
+//  ANSI colors
+// ------------------------------------------------------------
+
+const RESET: &str = "\x1b[0m";
+const CYAN: &str = "\x1b[36m";
+const MAGENTA: &str = "\x1b[35m";
+const YELLOW: &str = "\x1b[33m";
+const RED: &str = "\x1b[31m";
+const DIM: &str = "\x1b[2m";
+
+/// When to emit ANSI escape codes.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ColorMode {
+    /// Color only when stdout is a terminal and `NO_COLOR` isn't set.
+    #[default]
+    Auto,
+    Always,
+    Never,
+}
+
+impl ColorMode {
+    pub fn enabled(&self) -> bool {
+        match self {
+            ColorMode::Always => true,
+            ColorMode::Never => false,
+            ColorMode::Auto => {
+                std::env::var_os("NO_COLOR").is_none() && std::io::stdout().is_terminal()
+            }
+        }
+    }
+}
+
+/// Paints the pieces of a rendered decode.
+///
+/// Selectors are cyan, addresses magenta, amounts yellow and warnings red.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct Style {
+    enabled: bool,
+}
+
+impl Style {
+    pub fn new(mode: ColorMode) -> Self {
+        Self {
+            enabled: mode.enabled(),
+        }
+    }
+
+    /// A style that never colors.
+    pub fn plain() -> Self {
+        Self { enabled: false }
+    }
+
+    pub fn is_enabled(&self) -> bool {
+        self.enabled
+    }
+
+    fn paint(&self, color: &str, s: &str) -> String {
+        match self.enabled {
+            true => format!("{}{}{}", color, s, RESET),
+            false => s.to_string(),
+        }
+    }
+
+    pub fn selector(&self, s: &str) -> String {
+        self.paint(CYAN, s)
+    }
+
+    pub fn address(&self, s: &str) -> String {
+        self.paint(MAGENTA, s)
+    }
+
+    pub fn amount(&self, s: &str) -> String {
+        self.paint(YELLOW, s)
+    }
+
+    pub fn warning(&self, s: &str) -> String {
+        self.paint(RED, s)
+    }
+
+    pub fn dim(&self, s: &str) -> String {
+        self.paint(DIM, s)
+    }
+}
@@ -0,0 +1,54 @@
+use crate::decoded::DecodedCalldata;
+use crate::render::flatten;
+
+// ------------------------------------------------------------
+//  CSV export
+// ------------------------------------------------------------
+
+pub const CSV_HEADER: &str = "tx_id,path,selector,signature_guess,param_index,type,value";
+
+/// Quotes a field when it holds a separator, quote or newline.
+pub fn escape(field: &str) -> String {
+    match field.contains([',', '"', '\n', '\r']) {
+        true => format!("\"{}\"", field.replace('"', "\"\"")),
+        false => field.to_string(),
+    }
+}
+
+/// One row per param of every call in the tree (no header).
+///
+/// ## Params
+/// 1. id - identifies the decoded input, e.g. its tx hash.
+/// 2. root - the decode tree.
+pub fn csv_rows(id: &str, root: &DecodedCalldata) -> Vec<String> {
+    flatten(id, root)
+        .into_iter()
+        .map(|row| {
+            [
+                row.tx_id,
+                row.path,
+                row.selector,
+                row.signature_guess,
+                row.param_index.to_string(),
+                row.ty,
+                row.value,
+            ]
+            .iter()
+            .map(|f| escape(f))
+            .collect::<Vec<String>>()
+            .join(",")
+        })
+        .collect()
+}
+
+/// Flattens a batch of decodes into a CSV document, header included.
+pub fn render_csv<'a>(batch: impl IntoIterator<Item = (&'a str, &'a DecodedCalldata)>) -> String {
+    let mut out = format!("{}\n", CSV_HEADER);
+    for (id, root) in batch {
+        for row in csv_rows(id, root) {
+            out.push_str(&row);
+            out.push('\n');
+        }
+    }
+    out
+}
@@ -0,0 +1,65 @@
+use crate::decoded::DecodedCalldata;
+
+// ------------------------------------------------------------
+//  Call-tree diagrams
+// ------------------------------------------------------------
+
+/// Flattens the tree into `(id, parent id, call index, node)`, depth-first.
+fn nodes(root: &DecodedCalldata) -> Vec<(String, Option<String>, usize, &DecodedCalldata)> {
+    let mut out = vec![];
+    let mut stack = vec![("n0".to_string(), None, 0, root)];
+    while let Some((id, parent, index, node)) = stack.pop() {
+        for (i, call) in node.calls.iter().enumerate().rev() {
+            stack.push((format!("{}_{}", id, i), Some(id.clone()), i, call));
+        }
+        out.push((id, parent, index, node));
+    }
+    out
+}
+
+fn label(call: &DecodedCalldata) -> String {
+    let name = call.signature_guess.split('(').next().unwrap_or_default();
+    format!("0x{}\\n{}", call.selector, name)
+}
+
+/// Renders a mermaid flowchart of the nested calls.
+///
+/// ```text
+/// flowchart TD
+///     n0["0xac9650d8<br/>unknown_ac9650d8"]
+///     n0_0["0x88316456<br/>unknown_88316456"]
+///     n0 -->|0| n0_0
+/// ```
+pub fn render_mermaid(root: &DecodedCalldata) -> String {
+    let nodes = nodes(root);
+    let mut out = String::from("flowchart TD\n");
+    for (id, _, _, node) in nodes.iter() {
+        out.push_str(&format!(
+            "    {}[\"{}\"]\n",
+            id,
+            label(node).replace("\\n", "<br/>")
+        ));
+    }
+    for (id, parent, index, _) in nodes.iter() {
+        if let Some(parent) = parent {
+            out.push_str(&format!("    {} -->|{}| {}\n", parent, index, id));
+        }
+    }
+    out
+}
+
+/// Renders a Graphviz DOT digraph of the nested calls.
+pub fn render_dot(root: &DecodedCalldata) -> String {
+    let nodes = nodes(root);
+    let mut out = String::from("digraph calls {\n    node [shape=box, fontname=\"monospace\"];\n");
+    for (id, _, _, node) in nodes.iter() {
+        out.push_str(&format!("    {} [label=\"{}\"];\n", id, label(node)));
+    }
+    for (id, parent, index, _) in nodes.iter() {
+        if let Some(parent) = parent {
+            out.push_str(&format!("    {} -> {} [label=\"{}\"];\n", parent, id, index));
+        }
+    }
+    out.push_str("}\n");
+    out
+}
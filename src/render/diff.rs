@@ -0,0 +1,76 @@
+use crate::diff::{CallDiff, Change, ParamDiff};
+use crate::render::color::Style;
+
+// ------------------------------------------------------------
+//  Side-by-side diff
+// ------------------------------------------------------------
+
+const WIDTH: usize = 60;
+
+fn cell(param: &Option<crate::decoded::DecodedParam>) -> String {
+    match param {
+        Some(p) => {
+            let ty = p.types.first().map(|t| t.solidity.as_str()).unwrap_or("");
+            format!("{}: {}", ty, p.value)
+        }
+        None => "-".to_string(),
+    }
+}
+
+fn fit(s: &str) -> String {
+    match s.chars().count() > WIDTH {
+        true => format!("{}…", s.chars().take(WIDTH - 1).collect::<String>()),
+        false => format!("{:<WIDTH$}", s),
+    }
+}
+
+/// Renders two decodes next to each other, marking and highlighting changed params.
+///
+/// ```text
+///   0xa9059cbb                       | 0xa9059cbb
+///   [0] address: 0xc02a…             | address: 0xc02a…
+/// * [1] uint256: 1000                | uint256: 2000
+/// ```
+pub fn render_diff(diff: &CallDiff, style: &Style) -> String {
+    let mut out = String::new();
+    write_call(&mut out, diff, "", style);
+    out
+}
+
+fn write_call(out: &mut String, diff: &CallDiff, indent: &str, style: &Style) {
+    let selector = |s: &Option<String>| match s {
+        Some(s) => format!("0x{}", s),
+        None => "-".to_string(),
+    };
+    let marker = match diff.left_selector == diff.right_selector {
+        true => ' ',
+        false => '*',
+    };
+    let left = fit(&format!("{}{}", indent, selector(&diff.left_selector)));
+    let right = selector(&diff.right_selector);
+    let line = format!("{} {} | {}", marker, left, right);
+    out.push_str(&match marker {
+        '*' => style.warning(&line),
+        _ => style.selector(&line),
+    });
+    out.push('\n');
+
+    for param in diff.params.iter() {
+        write_param(out, param, indent, style);
+    }
+    let indent = format!("{}  ", indent);
+    for call in diff.calls.iter() {
+        write_call(out, call, &indent, style);
+    }
+}
+
+fn write_param(out: &mut String, param: &ParamDiff, indent: &str, style: &Style) {
+    let left = fit(&format!("{}[{}] {}", indent, param.index, cell(&param.left)));
+    let right = cell(&param.right);
+    let line = match param.change {
+        Change::Same => format!("  {} | {}", left, right),
+        _ => style.amount(&format!("* {} | {}", left, right)),
+    };
+    out.push_str(&line);
+    out.push('\n');
+}
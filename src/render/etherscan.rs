@@ -0,0 +1,70 @@
+use crate::decoded::DecodedCalldata;
+
+// ------------------------------------------------------------
+//  Etherscan "Decode Input Data" view
+// ------------------------------------------------------------
+
+/// Renders the call the way Etherscan's "Decode Input Data" panel does.
+///
+/// ```text
+/// Function: unknown_a9059cbb(address,uint256)
+///
+/// MethodID: 0xa9059cbb
+/// [0]:  000000000000000000000000c02aaa39b223fe8d0a0e5c4f27ead9083c756cc2
+/// [1]:  0000000000000000000000000000000000000000000000000de0b6b3a7640000
+///
+/// #  Name  Type     Data
+/// 0  _0    address  0xc02aaa39b223fe8d0a0e5c4f27ead9083c756cc2
+/// 1  _1    uint256  1000000000000000000
+/// ```
+///
+/// Nested calls follow as their own sections, titled by their path in the tree.
+pub fn render_etherscan(root: &DecodedCalldata) -> String {
+    let mut out = String::new();
+    write_call(&mut out, root, None);
+    out
+}
+
+fn write_call(out: &mut String, call: &DecodedCalldata, path: Option<&str>) {
+    if let Some(path) = path {
+        out.push_str(&format!("\n---------- Call {} ----------\n", path));
+    }
+    out.push_str(&format!("Function: {}\n\n", call.signature_guess));
+    out.push_str(&format!("MethodID: 0x{}\n", call.selector));
+    for param in call.params.iter() {
+        let label = format!("[{}]:", param.index);
+        out.push_str(&format!("{:<6}{}\n", label, param.raw));
+    }
+
+    let rows = call
+        .params
+        .iter()
+        .map(|p| {
+            let ty = p.types.first().map(|t| t.solidity.clone()).unwrap_or_default();
+            (p.index.to_string(), format!("_{}", p.index), ty, p.labelled_value())
+        })
+        .collect::<Vec<_>>();
+    if !rows.is_empty() {
+        let width = |f: fn(&(String, String, String, String)) -> usize, min: usize| {
+            rows.iter().map(f).max().unwrap_or(0).max(min)
+        };
+        let w0 = width(|r| r.0.len(), 1);
+        let w1 = width(|r| r.1.len(), 4);
+        let w2 = width(|r| r.2.len(), 4);
+        out.push_str(&format!(
+            "\n{:<w0$}  {:<w1$}  {:<w2$}  Data\n",
+            "#", "Name", "Type"
+        ));
+        for (i, name, ty, data) in rows.iter() {
+            out.push_str(&format!("{:<w0$}  {:<w1$}  {:<w2$}  {}\n", i, name, ty, data));
+        }
+    }
+
+    for (i, nested) in call.calls.iter().enumerate() {
+        let path = match path {
+            Some(p) => format!("{}.{}", p, i),
+            None => i.to_string(),
+        };
+        write_call(out, nested, Some(&path));
+    }
+}
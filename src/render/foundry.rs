@@ -0,0 +1,90 @@
+use crate::decoded::DecodedCalldata;
+use crate::render::address::to_checksum;
+use crate::render::solidity::{literal, param_type};
+use primitive_types::{H160 as Address, U256};
+
+// ------------------------------------------------------------
+//  Foundry test
+// ------------------------------------------------------------
+
+/// Context of the call being reproduced.
+#[derive(Debug, Clone)]
+pub struct ReproOptions {
+    /// Name of the generated test contract.
+    pub contract: String,
+    /// Contract the calldata was sent to.
+    pub target: Address,
+    /// Account pranked as `msg.sender`.
+    pub sender: Address,
+    /// Wei sent along with the call.
+    pub value: U256,
+}
+
+impl Default for ReproOptions {
+    fn default() -> Self {
+        Self {
+            contract: "ReproTest".to_string(),
+            target: Address::zero(),
+            sender: Address::zero(),
+            value: U256::zero(),
+        }
+    }
+}
+
+/// Re-assembles the calldata hex from the root's selector and raw words.
+pub fn calldata_hex(root: &DecodedCalldata) -> String {
+    let mut hex = root.selector.clone();
+    for param in root.params.iter() {
+        hex.push_str(&param.raw);
+    }
+    hex
+}
+
+/// Emits a Forge test that pranks the sender and replays the call against the target.
+///
+/// The decoded params of the top-level call are written out as constants so they
+/// can be tweaked and re-encoded with `abi.encodeWithSelector`.
+pub fn render_foundry_test(root: &DecodedCalldata, options: &ReproOptions) -> String {
+    let mut out = String::from(
+        "// SPDX-License-Identifier: UNLICENSED\npragma solidity ^0.8.13;\n\nimport \"forge-std/Test.sol\";\n\n",
+    );
+    out.push_str(&format!("contract {} is Test {{\n", options.contract));
+    out.push_str(&format!(
+        "    address constant TARGET = {};\n",
+        to_checksum(options.target.as_bytes())
+    ));
+    out.push_str(&format!(
+        "    address constant SENDER = {};\n",
+        to_checksum(options.sender.as_bytes())
+    ));
+    out.push_str(&format!("    bytes4 constant SELECTOR = 0x{};\n", root.selector));
+    for param in root.params.iter() {
+        let ty = param_type(param).replace(" calldata", "");
+        out.push_str(&format!(
+            "    {} constant ARG{} = {};\n",
+            ty,
+            param.index,
+            literal(param)
+        ));
+    }
+
+    out.push_str(&format!("\n    // {}\n", root.signature_guess));
+    out.push_str("    function test_reproduce() public {\n");
+    out.push_str(&format!(
+        "        bytes memory data = hex\"{}\";\n",
+        calldata_hex(root)
+    ));
+    out.push_str("        vm.prank(SENDER);\n");
+    match options.value.is_zero() {
+        true => out.push_str("        (bool ok, ) = TARGET.call(data);\n"),
+        false => {
+            out.push_str(&format!("        vm.deal(SENDER, {});\n", options.value));
+            out.push_str(&format!(
+                "        (bool ok, ) = TARGET.call{{value: {}}}(data);\n",
+                options.value
+            ));
+        }
+    }
+    out.push_str("        assertTrue(ok);\n    }\n}\n");
+    out
+}
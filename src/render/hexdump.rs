@@ -0,0 +1,173 @@
+use crate::decoded::{hex_u128, DecodedCalldata, DecodedParam};
+use crate::render::color::Style;
+use crate::render::tree::typed_value;
+
+// ------------------------------------------------------------
+//  Annotated hex dump
+// ------------------------------------------------------------
+
+/// What a word of the body is used for.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum WordRole {
+    /// Points this many bytes past the start of its enclosing region.
+    Offset(usize),
+    /// Byte length of the nested call that follows.
+    Length(usize),
+    /// Overlaps these params (or the selector) of a nested call.
+    Nested {
+        call: usize,
+        selector: String,
+        parts: Vec<String>,
+    },
+    /// Standalone value.
+    Value,
+}
+
+/// Works out which role each of the root's params plays.
+pub fn word_roles(root: &DecodedCalldata) -> Vec<WordRole> {
+    let body_len = root.params.iter().map(|p| p.raw.len() / 2).sum::<usize>();
+    root.params
+        .iter()
+        .map(|param| {
+            if let Some(role) = nested_role(root, param) {
+                return role;
+            }
+            let Some(v) = hex_u128(&param.raw) else {
+                return WordRole::Value;
+            };
+
+            // A length word ends exactly where a nested call starts.
+            if let Some(call) = root.calls.iter().find(|c| c.span.start == param.span.end) {
+                if v == (call.span.end - call.span.start) as u128 {
+                    return WordRole::Length(v as usize);
+                }
+            }
+            if v != 0 && v < body_len as u128 && v % 32 == 0 {
+                return WordRole::Offset(v as usize);
+            }
+            WordRole::Value
+        })
+        .collect()
+}
+
+fn nested_role(root: &DecodedCalldata, param: &DecodedParam) -> Option<WordRole> {
+    let (call, nested) = root
+        .calls
+        .iter()
+        .enumerate()
+        .find(|(_, c)| param.span.start < c.span.end && c.span.start < param.span.end)?;
+
+    let mut parts = vec![];
+    if param.span.start < nested.span.start + 4 && nested.span.start < param.span.end {
+        parts.push("selector".to_string());
+    }
+    for p in nested.params.iter() {
+        if param.span.start < p.span.end && p.span.start < param.span.end {
+            parts.push(format!("[{}]", p.index));
+        }
+    }
+    Some(WordRole::Nested {
+        call,
+        selector: nested.selector.clone(),
+        parts,
+    })
+}
+
+/// Word each offset of `roles` points to, when it's among the words.
+///
+/// Offsets in the head count from the first word; offsets in an array count
+/// from the word after the array's length, so the words of an array pointed
+/// to take the word after its length as their base.
+pub fn offset_targets(root: &DecodedCalldata, roles: &[WordRole]) -> Vec<Option<usize>> {
+    let mut bases = vec![0; roles.len()];
+    let mut targets = vec![None; roles.len()];
+    for (i, role) in roles.iter().enumerate() {
+        let WordRole::Offset(to) = role else {
+            continue;
+        };
+        let target = bases[i] + to / 32;
+        if target >= roles.len() {
+            continue;
+        }
+        targets[i] = Some(target);
+        // The count is read from the calldata: only the words there can be elements.
+        let left = roles.len() - target - 1;
+        let count = hex_u128(&root.params[target].raw).map_or(0, |c| c.min(left as u128) as usize);
+        let elements = (target + 1)..(target + 1 + count);
+        for base in bases[elements].iter_mut().filter(|b| **b == 0) {
+            *base = target + 1;
+        }
+    }
+    targets
+}
+
+/// Prints the calldata one word per line with its index, body offset and
+/// role, then the words of each nested call the same way.
+///
+/// ```text
+/// selector: 0xac9650d8
+/// [00] 0x0000 0000000000000000000000000000000000000000000000000000000000000020 // offset +0x20 → word 1
+/// [01] 0x0020 0000000000000000000000000000000000000000000000000000000000000002 // uint8: 2
+/// ...
+///
+/// calls[0] at byte 0xa4, selector: 0x88316456
+/// [00] 0x0000 000000000000000000000000c011a73ee8576fb46f5e1c5751ca3b9fe0af2a6f // address: 0xC011…
+/// ```
+pub fn render_hexdump(root: &DecodedCalldata) -> String {
+    render_hexdump_styled(root, &Style::plain())
+}
+
+/// Same as `render_hexdump`, painting selectors, addresses and amounts with `style`.
+pub fn render_hexdump_styled(root: &DecodedCalldata, style: &Style) -> String {
+    let mut out = String::new();
+    for (path, call) in root.walk_paths() {
+        let selector = style.selector(&format!("0x{}", call.selector));
+        match path.is_empty() {
+            true => out.push_str(&format!("selector: {}\n", selector)),
+            false => out.push_str(&format!(
+                "\n{} at byte 0x{:x}, selector: {}\n",
+                path.trim_end_matches('.'),
+                call.span.start,
+                selector
+            )),
+        }
+        out.push_str(&render_words(call, style));
+    }
+    out
+}
+
+/// The words of one call, without its nested calls' words.
+fn render_words(call: &DecodedCalldata, style: &Style) -> String {
+    let roles = word_roles(call);
+    let targets = offset_targets(call, &roles);
+    let mut out = String::new();
+    for ((param, role), target) in call.params.iter().zip(roles).zip(targets) {
+        let note = match role {
+            WordRole::Offset(to) => style.dim(&match target {
+                Some(word) => format!("offset +0x{:x} → word {}", to, word),
+                None => format!("offset +0x{:x}", to),
+            }),
+            WordRole::Length(len) => style.dim(&format!("length of next call ({} bytes)", len)),
+            WordRole::Nested {
+                call,
+                selector,
+                parts,
+            } => format!(
+                "call {} ({}) {}",
+                call,
+                style.selector(&format!("0x{}", selector)),
+                parts.join(", ")
+            ),
+            WordRole::Value => typed_value(param, style),
+        };
+        out.push_str(&format!(
+            "[{:02}] 0x{:04x} {:<64} {} {}\n",
+            param.index,
+            param.index * 32,
+            param.raw,
+            style.dim("//"),
+            note
+        ));
+    }
+    out
+}
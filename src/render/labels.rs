@@ -0,0 +1,66 @@
+use crate::constants::Types;
+use crate::decoded::DecodedCalldata;
+use std::collections::BTreeMap;
+
+// ------------------------------------------------------------
+//  Address labels
+// ------------------------------------------------------------
+
+/// Human names for addresses, keyed by lowercase `0x` address.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Labels(BTreeMap<String, String>);
+
+impl Labels {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Parses one `0xaddress name` pair per line, `#` starting a comment.
+    pub fn from_text(text: &str) -> Self {
+        let mut labels = Self::new();
+        for line in text.lines() {
+            let line = line.split('#').next().unwrap_or_default().trim();
+            if let Some((address, name)) = line.split_once(char::is_whitespace) {
+                labels.insert(address, name.trim());
+            }
+        }
+        labels
+    }
+
+    pub fn insert(&mut self, address: &str, name: &str) {
+        self.0.insert(address.to_lowercase(), name.to_string());
+    }
+
+    pub fn get(&self, address: &str) -> Option<&str> {
+        self.0.get(&address.to_lowercase()).map(|s| s.as_str())
+    }
+
+    /// Adds the labels of `other`, overriding existing names.
+    pub fn extend(&mut self, other: Labels) {
+        self.0.extend(other.0);
+    }
+
+    pub fn len(&self) -> usize {
+        self.0.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+}
+
+/// Sets the `label` of every address param that has one.
+pub fn apply_labels(root: &mut DecodedCalldata, labels: &Labels) {
+    for param in root.params.iter_mut() {
+        let is_address = matches!(
+            param.types.first().map(|t| &t.kind),
+            Some(Types::Address | Types::Address0)
+        );
+        if let (true, Some(name)) = (is_address, labels.get(&param.value)) {
+            param.label = Some(name.to_string());
+        }
+    }
+    for nested in root.calls.iter_mut() {
+        apply_labels(nested, labels);
+    }
+}
@@ -0,0 +1,95 @@
+use crate::decoded::DecodedCalldata;
+
+// ------------------------------------------------------------
+//  Markdown report
+// ------------------------------------------------------------
+
+/// Irregularities worth pointing out in a report.
+pub fn anomalies(root: &DecodedCalldata) -> Vec<String> {
+    let mut out = vec![];
+    for call in root.walk() {
+        for param in call.params.iter() {
+            if param.raw.len() != 64 {
+                out.push(format!(
+                    "0x{} param [{}] is {} bytes instead of 32",
+                    call.selector,
+                    param.index,
+                    param.raw.len() / 2
+                ));
+            }
+        }
+    }
+    out
+}
+
+/// Renders a self-contained Markdown report of the decode.
+///
+/// ## Params
+/// 1. title - heading of the report, e.g. the tx hash.
+/// 2. root - the decode tree.
+pub fn render_markdown(title: &str, root: &DecodedCalldata) -> String {
+    let calls = root.walk();
+    let mut out = format!("# {}\n\n## Summary\n\n", title);
+    out.push_str(&format!("- **Selector:** `0x{}`\n", root.selector));
+    out.push_str(&format!("- **Signature guess:** `{}`\n", root.signature_guess));
+    out.push_str(&format!("- **Params:** {}\n", root.params.len()));
+    out.push_str(&format!("- **Nested calls:** {}\n", calls.len() - 1));
+
+    out.push_str("\n## Signature guesses\n\n| Call | Selector | Signature |\n| --- | --- | --- |\n");
+    let mut paths = vec![];
+    collect_paths(root, "root".to_string(), &mut paths);
+    for (path, call) in paths.iter() {
+        out.push_str(&format!(
+            "| {} | `0x{}` | `{}` |\n",
+            path, call.selector, call.signature_guess
+        ));
+    }
+
+    out.push_str("\n## Warnings\n\n");
+    let anomalies = anomalies(root);
+    match anomalies.is_empty() {
+        true => out.push_str("None.\n"),
+        false => {
+            for a in anomalies {
+                out.push_str(&format!("- {}\n", a));
+            }
+        }
+    }
+
+    for (path, call) in paths.iter() {
+        out.push_str(&format!("\n## Call `{}`: `0x{}`\n\n", path, call.selector));
+        if call.params.is_empty() {
+            out.push_str("No params.\n");
+            continue;
+        }
+        out.push_str("| # | Type | Candidates | Value |\n| --- | --- | --- | --- |\n");
+        for param in call.params.iter() {
+            let ty = param.types.first().map(|t| t.solidity.as_str()).unwrap_or("");
+            let candidates = param
+                .types
+                .iter()
+                .map(|t| format!("{:?} ({:.0}%)", t.kind, t.confidence * 100.0))
+                .collect::<Vec<String>>()
+                .join(", ");
+            out.push_str(&format!(
+                "| {} | `{}` | {} | `{}` |\n",
+                param.index,
+                ty,
+                candidates,
+                param.labelled_value()
+            ));
+        }
+    }
+    out
+}
+
+fn collect_paths<'a>(
+    call: &'a DecodedCalldata,
+    path: String,
+    out: &mut Vec<(String, &'a DecodedCalldata)>,
+) {
+    out.push((path.clone(), call));
+    for (i, nested) in call.calls.iter().enumerate() {
+        collect_paths(nested, format!("{}.{}", path, i), out);
+    }
+}
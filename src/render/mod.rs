@@ -0,0 +1,79 @@
+pub mod address;
+pub mod color;
+pub mod csv;
+pub mod diagram;
+pub mod diff;
+pub mod etherscan;
+pub mod foundry;
+pub mod hexdump;
+pub mod labels;
+pub mod markdown;
+pub mod numeric;
+pub mod output;
+pub mod solidity;
+pub mod sql;
+pub mod tree;
+
+use crate::decoded::DecodedCalldata;
+
+/// A param of a decoded call, flattened for tabular outputs (CSV, SQL, Parquet).
+#[derive(Debug, Clone, PartialEq)]
+pub struct FlatRow {
+    pub tx_id: String,
+    /// Position of the call in the tree, e.g. `0.1`; empty for the top-level call.
+    pub path: String,
+    pub depth: u32,
+    /// `0x` prefixed selector of the call.
+    pub selector: String,
+    pub signature_guess: String,
+    pub param_index: u32,
+    /// Most likely Solidity type.
+    pub ty: String,
+    /// All candidate Solidity types, `|` separated.
+    pub candidates: String,
+    pub raw: String,
+    pub value: String,
+}
+
+/// One row per param of every call in the tree, depth-first.
+///
+/// ## Params
+/// 1. id - identifies the decoded input, e.g. its tx hash.
+/// 2. root - the decode tree.
+pub fn flatten(id: &str, root: &DecodedCalldata) -> Vec<FlatRow> {
+    let mut rows = vec![];
+    let mut stack = vec![(String::new(), 0, root)];
+    while let Some((path, depth, call)) = stack.pop() {
+        for (i, nested) in call.calls.iter().enumerate().rev() {
+            let path = match path.is_empty() {
+                true => i.to_string(),
+                false => format!("{}.{}", path, i),
+            };
+            stack.push((path, depth + 1, nested));
+        }
+        for param in call.params.iter() {
+            rows.push(FlatRow {
+                tx_id: id.to_string(),
+                path: path.clone(),
+                depth,
+                selector: format!("0x{}", call.selector),
+                signature_guess: call.signature_guess.clone(),
+                param_index: param.index as u32,
+                ty: param
+                    .types
+                    .first()
+                    .map(|t| t.solidity.clone())
+                    .unwrap_or_default(),
+                candidates: param
+                    .types
+                    .iter()
+                    .map(|t| t.solidity.as_str())
+                    .collect::<Vec<&str>>()
+                    .join("|"),
+                raw: param.raw.clone(),
+                value: param.value.clone(),
+            });
+        }
+    }
+    rows
+}
@@ -0,0 +1,128 @@
+use crate::constants::Types;
+use crate::decoded::DecodedCalldata;
+use primitive_types::U256;
+use std::collections::BTreeMap;
+
+// ------------------------------------------------------------
+//  Numeric rendering
+// ------------------------------------------------------------
+
+/// How uint values are written out.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum NumberFormat {
+    /// `0x` prefixed, without leading zeros.
+    Hex,
+    #[default]
+    Decimal,
+    /// `1.2345e18`, with up to 6 significant digits.
+    Scientific,
+    /// Scaled down by this many decimals, e.g. `1.5` for 1.5e18 at 18 decimals.
+    Units(u32),
+    /// Scaled by decimals guessed from the value's magnitude (18, 6 or none).
+    AutoUnits,
+}
+
+/// Number formats to use when rendering a decode.
+#[derive(Debug, Clone, Default)]
+pub struct NumericOptions {
+    /// Format for every uint param without an override.
+    pub default: NumberFormat,
+    /// Per-param formats keyed by `(call path, param index)`, the root call's path being `""`.
+    pub overrides: BTreeMap<(String, usize), NumberFormat>,
+}
+
+impl NumericOptions {
+    pub fn new(default: NumberFormat) -> Self {
+        Self {
+            default,
+            overrides: BTreeMap::new(),
+        }
+    }
+
+    /// Overrides the format of one param, e.g. `("0.1", 3)` for param 3 of call 1 in call 0.
+    pub fn with_override(mut self, path: &str, index: usize, format: NumberFormat) -> Self {
+        self.overrides.insert((path.to_string(), index), format);
+        self
+    }
+}
+
+/// Guesses token decimals from a raw amount's magnitude.
+pub fn guess_decimals(v: U256) -> u32 {
+    if v >= U256::exp10(15) {
+        18
+    } else if v >= U256::exp10(5) {
+        6
+    } else {
+        0
+    }
+}
+
+/// Writes `v` in the requested format.
+pub fn format_uint(v: U256, format: NumberFormat) -> String {
+    match format {
+        NumberFormat::Hex => format!("{:#x}", v),
+        NumberFormat::Decimal => v.to_string(),
+        NumberFormat::Scientific => {
+            let digits = v.to_string();
+            if digits.len() <= 6 {
+                return digits;
+            }
+            let mantissa = digits[1..6].trim_end_matches('0');
+            match mantissa.is_empty() {
+                true => format!("{}e{}", &digits[..1], digits.len() - 1),
+                false => format!("{}.{}e{}", &digits[..1], mantissa, digits.len() - 1),
+            }
+        }
+        NumberFormat::Units(0) => v.to_string(),
+        NumberFormat::Units(decimals) => match format_units(v, decimals) {
+            Some(s) => {
+                let s = s.trim_end_matches('0');
+                s.strip_suffix('.').unwrap_or(s).to_string()
+            }
+            None => v.to_string(),
+        },
+        NumberFormat::AutoUnits => format_uint(v, NumberFormat::Units(guess_decimals(v))),
+    }
+}
+
+/// `v` divided by `10^decimals`, every decimal written; `None` past the 77
+/// decimals a `U256` can scale by.
+fn format_units(v: U256, decimals: u32) -> Option<String> {
+    if decimals > 77 {
+        return None;
+    }
+    let (int, frac) = v.div_mod(U256::exp10(decimals as usize));
+    let width = decimals as usize;
+    Some(format!("{}.{:0>width$}", int, frac.to_string()))
+}
+
+/// Re-renders the value of every uint-typed param in the tree.
+pub fn apply_numeric(root: &mut DecodedCalldata, options: &NumericOptions) {
+    apply_call(root, "", options);
+}
+
+fn apply_call(call: &mut DecodedCalldata, path: &str, options: &NumericOptions) {
+    for param in call.params.iter_mut() {
+        let is_uint = matches!(
+            param.types.first().map(|t| &t.kind),
+            Some(Types::Uint | Types::Uint8 | Types::ZeroUint | Types::MaxUint128 | Types::AnyZero)
+        );
+        if !is_uint {
+            continue;
+        }
+        let Ok(v) = U256::from_str_radix(&param.raw, 16) else { continue };
+        let format = options
+            .overrides
+            .get(&(path.to_string(), param.index))
+            .copied()
+            .unwrap_or(options.default);
+        param.value = format_uint(v, format);
+    }
+    for (i, nested) in call.calls.iter_mut().enumerate() {
+        let path = match path.is_empty() {
+            true => i.to_string(),
+            false => format!("{}.{}", path, i),
+        };
+        apply_call(nested, &path, options);
+    }
+}
@@ -0,0 +1,1020 @@
+use crate::abiblob::DecodedAbi;
+use crate::advisor::{Advice, CompressionReport};
+#[cfg(feature = "rollup")]
+use crate::arbitrum::{L2Message, Segment, SequencerBatch};
+#[cfg(feature = "rollup")]
+use crate::batcher::{Batch, DecodedChannel};
+use crate::blob::{BlobContent, BlobEncoding, DecodedBlob};
+use crate::bundle::{AddressRole, DecodedBundle};
+use crate::explain::{Explanation, Outcome};
+use crate::l1fee::{FeeFormula, L1FeeEstimate};
+use crate::logs::DecodedLog;
+use crate::mutate::{Mutation, MutationKind};
+use crate::rawtx::DecodedRawTransaction;
+use crate::redact::Redaction;
+use crate::render::address::to_checksum;
+use crate::render::color::Style;
+use crate::render::csv::{csv_rows, escape, CSV_HEADER};
+use crate::render::etherscan::render_etherscan;
+use crate::render::hexdump::render_hexdump_styled;
+use crate::render::tree::{
+    render_abi_styled, render_log_styled, render_return_styled, render_tree_styled,
+};
+use crate::returndata::DecodedReturn;
+#[cfg(feature = "ethers")]
+use crate::rpc::{BlockReport, DecodedTransaction};
+use crate::schema::JsonOutput;
+use crate::simulate::{CallOutcome, Disambiguation};
+use crate::stats::BatchSummary;
+use serde::{Deserialize, Serialize};
+
+// ------------------------------------------------------------
+//  Output formats
+// ------------------------------------------------------------
+
+/// The formats every CLI subcommand can print.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum OutputFormat {
+    /// Tree with params under each call.
+    #[default]
+    Pretty,
+    /// Only the nested call structure.
+    Tree,
+    /// The versioned JSON schema, one object per line.
+    Json,
+    /// The versioned schema as YAML, one `---` document per decode.
+    Yaml,
+    Etherscan,
+    /// One row per param, see `CSV_HEADER`.
+    Csv,
+    /// Each word annotated with its role and value, see `render::hexdump`.
+    Words,
+}
+
+impl OutputFormat {
+    pub const NAMES: [&'static str; 7] = [
+        "pretty",
+        "tree",
+        "json",
+        "yaml",
+        "etherscan",
+        "csv",
+        "words",
+    ];
+
+    /// Whether the format is meant for other programs rather than people.
+    pub fn is_machine(&self) -> bool {
+        matches!(
+            self,
+            OutputFormat::Json | OutputFormat::Yaml | OutputFormat::Csv
+        )
+    }
+
+    /// Line to print once before the first decode, if any.
+    pub fn header(&self) -> Option<&'static str> {
+        match self {
+            OutputFormat::Csv => Some(CSV_HEADER),
+            _ => None,
+        }
+    }
+}
+
+impl std::str::FromStr for OutputFormat {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "pretty" => Ok(OutputFormat::Pretty),
+            "tree" => Ok(OutputFormat::Tree),
+            "json" => Ok(OutputFormat::Json),
+            "yaml" => Ok(OutputFormat::Yaml),
+            "etherscan" => Ok(OutputFormat::Etherscan),
+            "csv" => Ok(OutputFormat::Csv),
+            "words" => Ok(OutputFormat::Words),
+            _ => Err(format!(
+                "unknown format `{}`, expected one of: {}",
+                s,
+                Self::NAMES.join(", ")
+            )),
+        }
+    }
+}
+
+/// Renders one decode, newline terminated. CSV rows come without their header.
+///
+/// ## Params
+/// 1. format - output format.
+/// 2. id - identifies the decode in CSV rows, e.g. its position in a batch.
+/// 3. output - the decode.
+/// 4. style - colors for the tree formats.
+pub fn render_output(format: OutputFormat, id: &str, output: &JsonOutput, style: &Style) -> String {
+    match format {
+        OutputFormat::Pretty => render_tree_styled(&output.root, true, style),
+        OutputFormat::Tree => render_tree_styled(&output.root, false, style),
+        OutputFormat::Json => format!("{}\n", to_json(output)),
+        OutputFormat::Yaml => format!("---\n{}", to_yaml(output)),
+        OutputFormat::Etherscan => render_etherscan(&output.root),
+        OutputFormat::Csv => rows(id, &output.root),
+        OutputFormat::Words => render_hexdump_styled(&output.root, style),
+    }
+}
+
+/// Renders a decoded log; the formats made for calls (Etherscan, CSV, words) show it as the
+/// pretty tree.
+pub fn render_log_output(format: OutputFormat, log: &DecodedLog, style: &Style) -> String {
+    match format {
+        OutputFormat::Tree => render_log_styled(log, false, style),
+        OutputFormat::Json => format!("{}\n", to_json(log)),
+        OutputFormat::Yaml => format!("---\n{}", to_yaml(log)),
+        OutputFormat::Pretty
+        | OutputFormat::Etherscan
+        | OutputFormat::Csv
+        | OutputFormat::Words => render_log_styled(log, true, style),
+    }
+}
+
+/// Renders a raw transaction: its envelope (hash, chain, target, value) and
+/// the decode of its input.
+pub fn render_raw_transaction(
+    format: OutputFormat,
+    tx: &DecodedRawTransaction,
+    style: &Style,
+) -> String {
+    let envelope = &tx.transaction;
+    let hash = format!("0x{}", faster_hex::hex_string(envelope.hash.as_bytes()));
+    let body = match (format, &tx.decoded) {
+        (OutputFormat::Json, _) => return format!("{}\n", to_json(tx)),
+        (OutputFormat::Yaml, _) => return format!("---\n{}", to_yaml(tx)),
+        (OutputFormat::Csv, Some(decoded)) => return rows(&hash, decoded),
+        (OutputFormat::Csv, None) => return String::new(),
+        (OutputFormat::Pretty, Some(decoded)) => render_tree_styled(decoded, true, style),
+        (OutputFormat::Tree, Some(decoded)) => render_tree_styled(decoded, false, style),
+        (OutputFormat::Etherscan, Some(decoded)) => render_etherscan(decoded),
+        (OutputFormat::Words, Some(decoded)) => render_hexdump_styled(decoded, style),
+        (_, None) => style.dim(&format!("({} bytes of input)\n", envelope.input.len())),
+    };
+    let mut out = format!("Tx:    {}\n", hash);
+    out.push_str(&format!(
+        "Type:  {} ({})\n",
+        envelope.tx_type,
+        envelope.type_name()
+    ));
+    if let Some(chain_id) = envelope.chain_id {
+        out.push_str(&format!("Chain: {}\n", chain_id));
+    }
+    match envelope.to {
+        Some(to) => out.push_str(&format!("To:    {}\n", style.address(&to_checksum(to.as_bytes())))),
+        None => out.push_str("To:    (contract creation)\n"),
+    }
+    out.push_str(&format!(
+        "Value: {}\n",
+        style.amount(&envelope.value.to_string())
+    ));
+    out.push_str(&format!("Nonce: {}\n", envelope.nonce));
+    for item in envelope.access_list.iter() {
+        out.push_str(&format!(
+            "Access: {} ({} storage keys)\n",
+            style.address(&to_checksum(item.address.as_bytes())),
+            item.storage_keys.len()
+        ));
+    }
+    for hash in envelope.blob_versioned_hashes.iter() {
+        out.push_str(&format!("Blob:  {:?}\n", hash));
+    }
+    out.push('\n');
+    out.push_str(&body);
+    out
+}
+
+/// Renders a bundle: each transaction in order with its sender, then the
+/// addresses the transactions share and how each one uses them.
+pub fn render_bundle(format: OutputFormat, bundle: &DecodedBundle, style: &Style) -> String {
+    match format {
+        OutputFormat::Json => return format!("{}\n", to_json(bundle)),
+        OutputFormat::Yaml => return format!("---\n{}", to_yaml(bundle)),
+        OutputFormat::Csv => {
+            let txs = bundle.transactions.iter();
+            return txs
+                .map(|tx| render_raw_transaction(format, &tx.decoded, style))
+                .collect();
+        }
+        _ => {}
+    }
+    let mut out = String::new();
+    for tx in bundle.transactions.iter() {
+        out.push_str(&format!(
+            "{}\n",
+            style.dim(&format!("Transaction {}", tx.index))
+        ));
+        if let Some(from) = tx.from {
+            out.push_str(&format!(
+                "From:  {}\n",
+                style.address(&to_checksum(from.as_bytes()))
+            ));
+        }
+        out.push_str(&render_raw_transaction(format, &tx.decoded, style));
+        out.push('\n');
+    }
+    if !bundle.shared.is_empty() {
+        out.push_str("Shared addresses\n");
+    }
+    for shared in bundle.shared.iter() {
+        let uses = shared
+            .uses
+            .iter()
+            .map(|u| match (u.role, &u.path) {
+                (AddressRole::Sender, _) => format!("#{} sender", u.transaction),
+                (AddressRole::Recipient, _) => format!("#{} recipient", u.transaction),
+                (AddressRole::Param, Some(path)) => format!("#{} {}", u.transaction, path),
+                (AddressRole::Param, None) => format!("#{} param", u.transaction),
+            })
+            .collect::<Vec<_>>()
+            .join(", ");
+        out.push_str(&format!(
+            "  {} {}\n",
+            style.address(&to_checksum(shared.address.as_bytes())),
+            style.dim(&uses)
+        ));
+    }
+    out
+}
+
+/// Renders a decoded blob: its encoding and payload size, then what the
+/// payload holds, transactions and calldatas rendered in `format`.
+///
+/// ## Params
+/// 1. index - the blob's position in its sidecar.
+pub fn render_blob(
+    format: OutputFormat,
+    index: usize,
+    blob: &DecodedBlob,
+    style: &Style,
+) -> String {
+    match format {
+        OutputFormat::Json => return format!("{}\n", to_json(blob)),
+        OutputFormat::Yaml => return format!("---\n{}", to_yaml(blob)),
+        _ => {}
+    }
+    let encoding = match blob.encoding {
+        BlobEncoding::OpStack => "OP Stack encoding",
+        BlobEncoding::Padded => "padded encoding",
+        BlobEncoding::Raw => "payload",
+    };
+    let header = format!("Blob {}: {}, {} bytes", index, encoding, blob.payload_len);
+    let mut out = match format {
+        OutputFormat::Csv => String::new(),
+        _ => format!("{}\n", style.dim(&header)),
+    };
+    match &blob.content {
+        BlobContent::Frames { frames } => {
+            for frame in frames.iter().filter(|_| format != OutputFormat::Csv) {
+                out.push_str(&format!(
+                    "Frame {} of channel 0x{}: {} bytes{}\n",
+                    frame.frame_number,
+                    frame.channel_id,
+                    frame.data_len,
+                    match frame.is_last {
+                        true => ", last",
+                        false => "",
+                    }
+                ));
+            }
+        }
+        BlobContent::Transactions { transactions } => {
+            for tx in transactions {
+                out.push_str(&render_raw_transaction(format, tx, style));
+                out.push('\n');
+            }
+        }
+        BlobContent::Calldatas { calls } => {
+            for (i, call) in calls.iter().enumerate() {
+                out.push_str(&match format {
+                    OutputFormat::Tree => render_tree_styled(call, false, style),
+                    OutputFormat::Etherscan => render_etherscan(call),
+                    OutputFormat::Words => render_hexdump_styled(call, style),
+                    OutputFormat::Csv => rows(&format!("{}/{}", index, i), call),
+                    _ => render_tree_styled(call, true, style),
+                });
+            }
+        }
+        BlobContent::Unknown if format != OutputFormat::Csv => {
+            out.push_str(&style.dim("(no recognisable content)\n"));
+        }
+        BlobContent::Unknown => {}
+    }
+    out
+}
+
+/// Renders batcher channels: each channel's frames and compression, then
+/// its batches with their transactions, or their calls' rows as CSV.
+#[cfg(feature = "rollup")]
+pub fn render_channels(format: OutputFormat, channels: &[DecodedChannel], style: &Style) -> String {
+    match format {
+        OutputFormat::Json => return format!("{}\n", to_json(&channels)),
+        OutputFormat::Yaml => return format!("---\n{}", to_yaml(&channels)),
+        _ => {}
+    }
+    let mut out = String::new();
+    for channel in channels.iter() {
+        if format != OutputFormat::Csv {
+            let compression = match channel.compression {
+                Some(compression) => format!(", {:?}", compression).to_lowercase(),
+                None => String::new(),
+            };
+            let header = format!(
+                "Channel 0x{}: {} frames{}, {} batches",
+                channel.id,
+                channel.frames,
+                compression,
+                channel.batches.len()
+            );
+            out.push_str(&format!("{}\n", style.dim(&header)));
+            if let Some(error) = &channel.error {
+                out.push_str(&style.dim(&format!("({})\n", error)));
+            }
+        }
+        for (b, batch) in channel.batches.iter().enumerate() {
+            match (format, batch) {
+                (OutputFormat::Csv, _) => {
+                    for (i, call) in batch.calls().into_iter().enumerate() {
+                        out.push_str(&rows(&format!("{}/{}/{}", channel.id, b, i), call));
+                    }
+                }
+                (_, Batch::Singular(singular)) => {
+                    out.push_str(&format!(
+                        "Batch {}: block at {} of epoch {}, {} transactions\n\n",
+                        b,
+                        singular.timestamp,
+                        singular.epoch_number,
+                        singular.transactions.len()
+                    ));
+                    for tx in singular.transactions.iter() {
+                        out.push_str(&render_raw_transaction(format, tx, style));
+                        out.push('\n');
+                    }
+                }
+                (_, Batch::Span(span)) => {
+                    out.push_str(&format!(
+                        "Batch {}: span of {} blocks to L1 origin {}, {} transactions\n\n",
+                        b,
+                        span.block_tx_counts.len(),
+                        span.l1_origin_num,
+                        span.transactions.len()
+                    ));
+                    for tx in span.transactions.iter() {
+                        let to = match tx.to {
+                            Some(to) => style.address(&to_checksum(to.as_bytes())),
+                            None => "(contract creation)".to_string(),
+                        };
+                        let line = format!("Block {} tx to {}, nonce {}\n", tx.block, to, tx.nonce);
+                        out.push_str(&line);
+                        out.push_str(&match (format, &tx.decoded) {
+                            (OutputFormat::Tree, Some(call)) => {
+                                render_tree_styled(call, false, style)
+                            }
+                            (OutputFormat::Etherscan, Some(call)) => render_etherscan(call),
+                            (OutputFormat::Words, Some(call)) => render_hexdump_styled(call, style),
+                            (_, Some(call)) => render_tree_styled(call, true, style),
+                            (_, None) => {
+                                style.dim(&format!("({} bytes of input)\n", tx.input.len()))
+                            }
+                        });
+                        out.push('\n');
+                    }
+                }
+            }
+        }
+    }
+    out
+}
+
+/// Renders an Arbitrum sequencer batch: its segments in order, the L2
+/// messages with their transactions, or their calls' rows as CSV.
+#[cfg(feature = "rollup")]
+pub fn render_sequencer_batch(
+    format: OutputFormat,
+    batch: &SequencerBatch,
+    style: &Style,
+) -> String {
+    match format {
+        OutputFormat::Json => return format!("{}\n", to_json(batch)),
+        OutputFormat::Yaml => return format!("---\n{}", to_yaml(batch)),
+        OutputFormat::Csv => {
+            let calls = batch.calls().into_iter().enumerate();
+            return calls
+                .map(|(i, call)| rows(&format!("{}/{}", batch.sequence_number, i), call))
+                .collect();
+        }
+        _ => {}
+    }
+    let header = format!(
+        "Sequencer batch {}: {}, {} segments",
+        batch.sequence_number,
+        format!("{:?}", batch.format).to_lowercase(),
+        batch.segments.len()
+    );
+    let mut out = format!("{}\n", style.dim(&header));
+    if let Some(error) = &batch.error {
+        out.push_str(&style.dim(&format!("({})\n", error)));
+    }
+    for segment in batch.segments.iter() {
+        match segment {
+            Segment::L2Message { message, .. } => {
+                render_l2_message(format, message, style, &mut out)
+            }
+            Segment::DelayedMessages => out.push_str("Delayed message\n"),
+            Segment::AdvanceTimestamp { by } => out.push_str(&format!("Timestamp +{}s\n", by)),
+            Segment::AdvanceL1BlockNumber { by } => out.push_str(&format!("L1 block +{}\n", by)),
+        }
+    }
+    out
+}
+
+#[cfg(feature = "rollup")]
+fn render_l2_message(format: OutputFormat, message: &L2Message, style: &Style, out: &mut String) {
+    match message {
+        L2Message::Batch { messages } => {
+            for message in messages.iter() {
+                render_l2_message(format, message, style, out);
+            }
+        }
+        L2Message::SignedTx { transaction } => {
+            out.push('\n');
+            out.push_str(&render_raw_transaction(format, transaction, style));
+        }
+        L2Message::UnsignedTx { transaction } => {
+            let to = match transaction.to {
+                Some(to) => style.address(&to_checksum(to.as_bytes())),
+                None => "(contract creation)".to_string(),
+            };
+            out.push_str(&format!("\nUnsigned tx to {}\n", to));
+            out.push_str(&match (format, &transaction.decoded) {
+                (OutputFormat::Tree, Some(call)) => render_tree_styled(call, false, style),
+                (OutputFormat::Etherscan, Some(call)) => render_etherscan(call),
+                (OutputFormat::Words, Some(call)) => render_hexdump_styled(call, style),
+                (_, Some(call)) => render_tree_styled(call, true, style),
+                (_, None) => style.dim(&format!("({} bytes of input)\n", transaction.input.len())),
+            });
+        }
+        L2Message::Unknown { byte, len } => {
+            out.push_str(&style.dim(&format!("(L2 message of kind {}, {} bytes)\n", byte, len)));
+        }
+    }
+}
+
+/// Renders compression advice: the savings then one line per suggestion, or
+/// the suggestions alone as CSV.
+pub fn render_advice(format: OutputFormat, report: &CompressionReport, style: &Style) -> String {
+    match format {
+        OutputFormat::Json => return format!("{}\n", to_json(report)),
+        OutputFormat::Yaml => return format!("---\n{}", to_yaml(report)),
+        _ => {}
+    }
+    let mut out = match format {
+        OutputFormat::Csv => "kind,path,start,end,saved_bytes,saved_gas,detail\n".to_string(),
+        _ => format!(
+            "Saves up to {} of {} bytes, {} of {} calldata gas\n",
+            report.saved_bytes, report.bytes, report.saved_gas, report.calldata_gas
+        ),
+    };
+    for suggestion in &report.suggestions {
+        let (kind, path, detail) = match &suggestion.advice {
+            Advice::NarrowerType {
+                path,
+                guessed,
+                fits,
+            } => (
+                "narrowerType",
+                path.as_str(),
+                format!("{} fits {}", guessed, fits),
+            ),
+            Advice::ZeroRun { len } => ("zeroRun", "", format!("{} zero bytes", len)),
+            Advice::RepeatedAddress {
+                path,
+                address,
+                first,
+            } => (
+                "repeatedAddress",
+                path.as_str(),
+                format!("{} already in {}", address, first),
+            ),
+        };
+        let span = suggestion.span;
+        out.push_str(&match format {
+            OutputFormat::Csv => format!(
+                "{},{},{},{},{},{},{}\n",
+                kind,
+                path,
+                span.start,
+                span.end,
+                suggestion.saved_bytes,
+                suggestion.saved_gas,
+                detail
+            ),
+            _ => format!(
+                "{} {:<20} {}  {}\n",
+                style.dim(&format!("[{}..{}]", span.start, span.end)),
+                match path {
+                    "" => kind,
+                    path => path,
+                },
+                detail,
+                style.dim(&format!(
+                    "-{} bytes, -{} gas",
+                    suggestion.saved_bytes, suggestion.saved_gas
+                ))
+            ),
+        });
+    }
+    out
+}
+
+/// Renders fuzzing variants: each one's label then its calldata, or one row per
+/// variant as CSV.
+pub fn render_mutations(format: OutputFormat, mutations: &[Mutation], style: &Style) -> String {
+    match format {
+        OutputFormat::Json => return format!("{}\n", to_json(&mutations)),
+        OutputFormat::Yaml => return format!("---\n{}", to_yaml(&mutations)),
+        _ => {}
+    }
+    let mut out = match format {
+        OutputFormat::Csv => "kind,path,label,start,end,calldata\n".to_string(),
+        _ => String::new(),
+    };
+    for mutation in mutations {
+        out.push_str(&match format {
+            OutputFormat::Csv => format!(
+                "{},{},{},{},{},{}\n",
+                match mutation.kind {
+                    MutationKind::Boundary => "boundary",
+                    MutationKind::ArrayLength => "arrayLength",
+                    MutationKind::FlippedAddress => "flippedAddress",
+                },
+                mutation.path,
+                mutation.label,
+                mutation.span.start,
+                mutation.span.end,
+                mutation.calldata
+            ),
+            _ => format!(
+                "{}\n{}\n",
+                style.dim(&format!("# {}: {}", mutation.path, mutation.label)),
+                mutation.calldata
+            ),
+        });
+    }
+    out
+}
+
+/// Renders a redaction: the redacted calldata then its decode, or one row per
+/// redacted param as CSV.
+pub fn render_redaction(format: OutputFormat, redaction: &Redaction, style: &Style) -> String {
+    let tree = match format {
+        OutputFormat::Json => return format!("{}\n", to_json(redaction)),
+        OutputFormat::Yaml => return format!("---\n{}", to_yaml(redaction)),
+        OutputFormat::Csv => {
+            let mut out = "kind,path,label,start,end\n".to_string();
+            for r in &redaction.redacted {
+                out.push_str(&format!(
+                    "{},{},{},{},{}\n",
+                    r.kind.name(),
+                    r.path,
+                    r.label,
+                    r.span.start,
+                    r.span.end
+                ));
+            }
+            return out;
+        }
+        OutputFormat::Tree => render_tree_styled(&redaction.decoded, false, style),
+        OutputFormat::Words => render_hexdump_styled(&redaction.decoded, style),
+        OutputFormat::Pretty | OutputFormat::Etherscan => {
+            render_tree_styled(&redaction.decoded, true, style)
+        }
+    };
+    format!(
+        "{}\n{}\n{}",
+        redaction.calldata,
+        style.dim(&format!("# {} values redacted", redaction.redacted.len())),
+        tree
+    )
+}
+
+/// Renders an explanation: the layout decisions then each call's params with
+/// their checks, or one row per decision and check as CSV.
+pub fn render_explanation(
+    format: OutputFormat,
+    explanation: &Explanation,
+    style: &Style,
+) -> String {
+    match format {
+        OutputFormat::Json => return format!("{}\n", to_json(explanation)),
+        OutputFormat::Yaml => return format!("---\n{}", to_yaml(explanation)),
+        OutputFormat::Csv => {
+            let mut out = "path,at,index,rule,outcome,reason\n".to_string();
+            for d in &explanation.layout {
+                out.push_str(&format!(
+                    "{},{},,{},{},{}\n",
+                    explanation.path_of(d),
+                    d.at,
+                    d.role.name(),
+                    d.outcome.name(),
+                    escape(&d.reason)
+                ));
+            }
+            for call in &explanation.calls {
+                for param in &call.params {
+                    for check in &param.checks {
+                        out.push_str(&format!(
+                            "{},{},{},{},{},{}\n",
+                            call.path,
+                            call.at + 4 + param.index * 32,
+                            param.index,
+                            check.rule,
+                            check.outcome.name(),
+                            escape(&check.reason)
+                        ));
+                    }
+                }
+            }
+            return out;
+        }
+        OutputFormat::Pretty
+        | OutputFormat::Tree
+        | OutputFormat::Etherscan
+        | OutputFormat::Words => {}
+    }
+    let outcome = |outcome: Outcome| {
+        let name = format!("{:<9}", outcome.name());
+        match outcome {
+            Outcome::Fired => name,
+            Outcome::Rejected => style.dim(&name),
+            Outcome::Disabled => style.warning(&name),
+        }
+    };
+    let mut out = "Layout\n".to_string();
+    if explanation.layout.is_empty() {
+        out.push_str(&style.dim("  (no offsets, lengths or calls)\n"));
+    }
+    for d in &explanation.layout {
+        let path = match explanation.path_of(d) {
+            "" => String::new(),
+            path => style.dim(&format!(" in {}", path)),
+        };
+        out.push_str(&format!(
+            "  {} {:<8} {} {}{}\n",
+            style.dim(&format!("{:<6}", format!("@{}", d.at))),
+            d.role.name(),
+            outcome(d.outcome),
+            d.reason,
+            path
+        ));
+    }
+    for call in &explanation.calls {
+        out.push_str(&format!(
+            "\n{} {}\n",
+            match call.path.as_str() {
+                "" => "Call",
+                path => path,
+            },
+            style.selector(&format!("0x{}", call.selector))
+        ));
+        for param in &call.params {
+            out.push_str(&format!(
+                "  [{}] {} {}\n",
+                param.index,
+                style.dim(&param.raw),
+                param.types.first().map_or("?", |t| t.solidity.as_str())
+            ));
+            for check in &param.checks {
+                out.push_str(&format!(
+                    "      {:<13} {} {}\n",
+                    check.rule,
+                    outcome(check.outcome),
+                    check.reason
+                ));
+            }
+        }
+    }
+    out
+}
+
+/// Renders batch statistics: the totals, each selector with the types of its
+/// params, then the targets, or one row per selector as CSV.
+pub fn render_stats(format: OutputFormat, summary: &BatchSummary, style: &Style) -> String {
+    match format {
+        OutputFormat::Json => return format!("{}\n", to_json(summary)),
+        OutputFormat::Yaml => return format!("---\n{}", to_yaml(summary)),
+        OutputFormat::Csv => {
+            let mut out = "selector,signature,calls,top_level,share,params\n".to_string();
+            for selector in &summary.selectors {
+                let params = selector
+                    .params
+                    .iter()
+                    .map(|p| p.types.first().map(|(ty, _)| ty.as_str()).unwrap_or(""))
+                    .collect::<Vec<_>>()
+                    .join(" ");
+                out.push_str(&format!(
+                    "{},\"{}\",{},{},{:.4},{}\n",
+                    selector.selector,
+                    selector.signature.as_deref().unwrap_or(""),
+                    selector.calls,
+                    selector.top_level,
+                    selector.share,
+                    params
+                ));
+            }
+            return out;
+        }
+        _ => {}
+    }
+    let mut out = format!(
+        "{} decoded ({} failed), {} calls, nesting depth {:.2} on average, {} at most\n",
+        summary.decoded, summary.failed, summary.calls, summary.average_depth, summary.max_depth
+    );
+    for selector in &summary.selectors {
+        out.push_str(&format!(
+            "\n{} {} {}\n",
+            style.selector(&selector.selector),
+            selector.signature.as_deref().unwrap_or("unknown"),
+            style.dim(&format!(
+                "{} calls ({:.1}%), {} top-level",
+                selector.calls,
+                selector.share * 100.0,
+                selector.top_level
+            ))
+        ));
+        for param in &selector.params {
+            let types = param
+                .types
+                .iter()
+                .map(|(ty, count)| format!("{} x{}", ty, count))
+                .collect::<Vec<_>>()
+                .join(", ");
+            out.push_str(&format!("  [{}] {}\n", param.index, types));
+        }
+    }
+    if !summary.targets.is_empty() {
+        out.push_str("\nTargets\n");
+        for target in &summary.targets {
+            out.push_str(&format!(
+                "  {} {}\n",
+                style.address(&target.address),
+                style.dim(&format!("{} calls", target.calls))
+            ));
+        }
+    }
+    out
+}
+
+/// Renders a simulated disambiguation: how the call ended, then each candidate
+/// with how its probe ended, the winner marked, or one row per candidate as CSV.
+pub fn render_disambiguation(
+    format: OutputFormat,
+    result: &Disambiguation,
+    style: &Style,
+) -> String {
+    let outcome = |outcome: &CallOutcome| match outcome {
+        CallOutcome::Returned => "returned".to_string(),
+        CallOutcome::Reverted { data } => format!("reverted with {}", data),
+    };
+    match format {
+        OutputFormat::Json => return format!("{}\n", to_json(result)),
+        OutputFormat::Yaml => return format!("---\n{}", to_yaml(result)),
+        OutputFormat::Csv => {
+            let mut out = "signature,confidence,width,decoded,winner,outcome\n".to_string();
+            for r in &result.results {
+                out.push_str(&format!(
+                    "\"{}\",{:.4},{},{},{},\"{}\"\n",
+                    r.probe.signature,
+                    r.probe.confidence,
+                    r.probe.width,
+                    r.decoded,
+                    result.winner.as_ref() == Some(&r.probe.signature),
+                    outcome(&r.outcome)
+                ));
+            }
+            return out;
+        }
+        _ => {}
+    }
+    let mut out = format!("Call {}\n", outcome(&result.baseline));
+    for r in &result.results {
+        let mark = match result.winner.as_ref() == Some(&r.probe.signature) {
+            true => "*",
+            false => " ",
+        };
+        let line = format!("{} {:.3}  {}", mark, r.probe.confidence, r.probe.signature);
+        out.push_str(&format!(
+            "{}  {}\n",
+            match r.decoded {
+                true => line,
+                false => style.dim(&line),
+            },
+            style.dim(&outcome(&r.outcome))
+        ));
+    }
+    match (&result.winner, result.baseline.is_decoding_revert()) {
+        (Some(_), _) => {}
+        (None, true) => out.push_str(&style.warning(
+            "Inconclusive: the call itself reverts without data, like on bad arguments\n",
+        )),
+        (None, false) => out.push_str(&style.warning("Inconclusive: no candidate decoded\n")),
+    }
+    out
+}
+
+/// Renders an L1 data-fee estimate: the totals then one row per part of the
+/// calldata, or the rows alone as CSV.
+pub fn render_l1_fee(format: OutputFormat, estimate: &L1FeeEstimate, style: &Style) -> String {
+    match format {
+        OutputFormat::Json => return format!("{}\n", to_json(estimate)),
+        OutputFormat::Yaml => return format!("---\n{}", to_yaml(estimate)),
+        OutputFormat::Csv => {
+            let mut out = "path,start,end,bytes,zero_bytes,calldata_gas,fee\n".to_string();
+            for part in &estimate.parts {
+                out.push_str(&format!(
+                    "{},{},{},{},{},{},{}\n",
+                    part.path,
+                    part.span.start,
+                    part.span.end,
+                    part.bytes,
+                    part.zero_bytes,
+                    part.calldata_gas,
+                    part.fee
+                ));
+            }
+            return out;
+        }
+        _ => {}
+    }
+    let formula = match estimate.formula {
+        FeeFormula::Bedrock => "Bedrock",
+        FeeFormula::Ecotone => "Ecotone",
+        FeeFormula::Fjord => "Fjord",
+    };
+    let mut out = format!("L1 fee: {} wei ({})\n", estimate.fee, formula);
+    out.push_str(&style.dim(&format!(
+        "{} bytes, {} zero, {} calldata gas, FastLZ size {}, estimated size {}\n",
+        estimate.bytes,
+        estimate.zero_bytes,
+        estimate.calldata_gas,
+        estimate.fastlz_size,
+        estimate.estimated_size
+    )));
+    let width = estimate
+        .parts
+        .iter()
+        .map(|p| p.path.len())
+        .max()
+        .unwrap_or(0);
+    for part in &estimate.parts {
+        out.push_str(&format!(
+            "{:width$}  {:>6} bytes  {:>6} gas  {} wei\n",
+            part.path,
+            part.bytes,
+            part.calldata_gas,
+            part.fee,
+            width = width
+        ));
+    }
+    out
+}
+
+/// Renders decoded return data, the same way as `render_log_output`.
+pub fn render_return_output(format: OutputFormat, ret: &DecodedReturn, style: &Style) -> String {
+    match format {
+        OutputFormat::Tree => render_return_styled(ret, false, style),
+        OutputFormat::Json => format!("{}\n", to_json(ret)),
+        OutputFormat::Yaml => format!("---\n{}", to_yaml(ret)),
+        OutputFormat::Pretty
+        | OutputFormat::Etherscan
+        | OutputFormat::Csv
+        | OutputFormat::Words => render_return_styled(ret, true, style),
+    }
+}
+
+/// Renders decoded ABI bytes in `format`, as `render_return_output` does return data.
+pub fn render_abi_output(format: OutputFormat, abi: &DecodedAbi, style: &Style) -> String {
+    match format {
+        OutputFormat::Tree => render_abi_styled(abi, false, style),
+        OutputFormat::Json => format!("{}\n", to_json(abi)),
+        OutputFormat::Yaml => format!("---\n{}", to_yaml(abi)),
+        OutputFormat::Pretty
+        | OutputFormat::Etherscan
+        | OutputFormat::Csv
+        | OutputFormat::Words => render_abi_styled(abi, true, style),
+    }
+}
+
+/// Renders a fetched transaction: its envelope (hash, sender, target, value) and decode.
+#[cfg(feature = "ethers")]
+pub fn render_transaction(format: OutputFormat, tx: &DecodedTransaction, style: &Style) -> String {
+    let body = match format {
+        OutputFormat::Json => return format!("{}\n", to_json(tx)),
+        OutputFormat::Yaml => return format!("---\n{}", to_yaml(tx)),
+        OutputFormat::Csv => return rows(&format!("{:?}", tx.hash), &tx.decoded),
+        OutputFormat::Pretty => render_tree_styled(&tx.decoded, true, style),
+        OutputFormat::Tree => render_tree_styled(&tx.decoded, false, style),
+        OutputFormat::Etherscan => render_etherscan(&tx.decoded),
+        OutputFormat::Words => render_hexdump_styled(&tx.decoded, style),
+    };
+    let mut out = format!("Tx:    {:?}\n", tx.hash);
+    out.push_str(&format!(
+        "From:  {}\n",
+        style.address(&to_checksum(tx.from.as_bytes()))
+    ));
+    match tx.to {
+        Some(to) => out.push_str(&format!(
+            "To:    {}\n",
+            style.address(&to_checksum(to.as_bytes()))
+        )),
+        None => out.push_str("To:    (contract creation)\n"),
+    }
+    if let Some(proxy) = &tx.proxy {
+        out.push_str(&format!(
+            "Impl:  {} ({})\n",
+            style.address(&to_checksum(proxy.implementation.as_bytes())),
+            proxy.kind.name()
+        ));
+    }
+    out.push_str(&format!("Value: {}\n", style.amount(&tx.value.to_string())));
+    if let Some(block) = tx.block_number {
+        out.push_str(&format!("Block: {}\n", block));
+    }
+    out.push('\n');
+    out.push_str(&body);
+    out
+}
+
+/// Renders a decoded block: a per-selector summary followed by every transaction.
+///
+/// ## Params
+/// 1. format - output format; CSV lists the transactions' params, without header.
+/// 2. report - the decoded block.
+/// 3. summary - only print the per-selector summary.
+/// 4. style - colors for the tree formats.
+#[cfg(feature = "ethers")]
+pub fn render_block(
+    format: OutputFormat,
+    report: &BlockReport,
+    summary: bool,
+    style: &Style,
+) -> String {
+    match format {
+        OutputFormat::Json => return format!("{}\n", to_json(report)),
+        OutputFormat::Yaml => return format!("---\n{}", to_yaml(report)),
+        OutputFormat::Csv => {
+            return report
+                .transactions
+                .iter()
+                .map(|tx| render_transaction(format, tx, style))
+                .collect()
+        }
+        _ => {}
+    }
+    let mut out = format!(
+        "Block {}: {} transactions, {} decoded, {} skipped\n\n",
+        report.number,
+        report.total,
+        report.transactions.len(),
+        report.skipped
+    );
+    let width = report
+        .groups
+        .iter()
+        .map(|g| g.count.to_string().len())
+        .max()
+        .unwrap_or(1);
+    for group in report.groups.iter() {
+        out.push_str(&format!(
+            "{:>width$}  {}  {}\n",
+            group.count,
+            style.selector(&group.selector),
+            group.signatures.first().map(|s| s.as_str()).unwrap_or("?"),
+        ));
+    }
+    if !summary {
+        for tx in report.transactions.iter() {
+            out.push('\n');
+            out.push_str(&render_transaction(format, tx, style));
+        }
+    }
+    out
+}
+
+fn rows(id: &str, root: &crate::decoded::DecodedCalldata) -> String {
+    csv_rows(id, root)
+        .into_iter()
+        .map(|row| format!("{}\n", row))
+        .collect()
+}
+
+fn to_json(value: &impl Serialize) -> String {
+    serde_json::to_string(value).expect("decode tree is serializable")
+}
+
+fn to_yaml(value: &impl Serialize) -> String {
+    serde_yaml::to_string(value).expect("decode tree is serializable")
+}
@@ -0,0 +1,90 @@
+use crate::constants::Types;
+use crate::decoded::{DecodedCalldata, DecodedParam};
+use crate::render::address::checksum_hex;
+
+// ------------------------------------------------------------
+//  Solidity interface
+// ------------------------------------------------------------
+
+/// Emits an `interface IUnknown` with every guessed function in the tree
+/// (top-level and nested, once per selector), followed by example calls
+/// using the decoded values.
+pub fn render_interface(root: &DecodedCalldata) -> String {
+    render_interface_named(root, "IUnknown")
+}
+
+/// Same as `render_interface`, with a custom interface name.
+pub fn render_interface_named(root: &DecodedCalldata, name: &str) -> String {
+    let mut calls: Vec<&DecodedCalldata> = vec![];
+    for call in root.walk() {
+        if !calls.iter().any(|c| c.selector == call.selector) {
+            calls.push(call);
+        }
+    }
+
+    let mut out = format!("interface {} {{\n", name);
+    for call in calls.iter() {
+        let params = call
+            .params
+            .iter()
+            .map(|p| format!("{} arg{}", param_type(p), p.index))
+            .collect::<Vec<String>>();
+        out.push_str(&format!(
+            "    function {}({}) external;\n",
+            function_name(call),
+            params.join(", ")
+        ));
+    }
+    out.push_str("}\n\n// Example calls:\n");
+    for call in root.walk() {
+        out.push_str(&format!("// {}\n", example_call(call, name)));
+    }
+    out
+}
+
+/// `IUnknown(target).name(arg0, ...);` with the decoded values as literals.
+pub fn example_call(call: &DecodedCalldata, interface: &str) -> String {
+    let args = call.params.iter().map(literal).collect::<Vec<String>>();
+    format!(
+        "{}(target).{}({});",
+        interface,
+        function_name(call),
+        args.join(", ")
+    )
+}
+
+/// Name of the function, `unknown_<selector>` when not resolved.
+pub fn function_name(call: &DecodedCalldata) -> String {
+    let name = call.signature_guess.split('(').next().unwrap_or_default();
+    match name.is_empty() {
+        true => format!("unknown_{}", call.selector),
+        false => name.to_string(),
+    }
+}
+
+/// The param's Solidity type with a data location where one is required.
+pub fn param_type(param: &DecodedParam) -> String {
+    match param.types.first().map(|t| t.solidity.as_str()) {
+        Some(ty @ ("string" | "bytes")) => format!("{} calldata", ty),
+        Some(ty) => ty.to_string(),
+        None => "bytes32".to_string(),
+    }
+}
+
+/// A Solidity literal of the param's decoded value.
+pub fn literal(param: &DecodedParam) -> String {
+    let Some(t) = param.types.first() else {
+        return format!("bytes32(0x{})", param.raw);
+    };
+    match t.kind {
+        Types::Address | Types::Address0 => match checksum_hex(&param.value) {
+            Some(a) => a,
+            None => format!("address(0x{})", param.raw),
+        },
+        Types::String => format!("hex\"{}\"", param.raw.trim_end_matches("00")),
+        Types::Bytes | Types::Bytes1 | Types::Bytes20 | Types::Selector | Types::AnyMax => {
+            format!("{}({})", t.solidity, param.value)
+        }
+        _ => param.value.clone(),
+    }
+}
@@ -0,0 +1,145 @@
+use crate::decoded::DecodedCalldata;
+use crate::render::{flatten, FlatRow};
+
+// ------------------------------------------------------------
+//  SQL emitter
+// ------------------------------------------------------------
+
+/// Database the statements are written for.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Dialect {
+    Postgres,
+    ClickHouse,
+}
+
+/// Columns of the table, in insertion order.
+pub const COLUMNS: [&str; 10] = [
+    "tx_id",
+    "path",
+    "depth",
+    "selector",
+    "signature_guess",
+    "param_index",
+    "type",
+    "candidates",
+    "raw",
+    "value",
+];
+
+/// `CREATE TABLE` statement for the decoded params table.
+pub fn create_table(table: &str, dialect: Dialect) -> String {
+    match dialect {
+        Dialect::Postgres => format!(
+            "CREATE TABLE IF NOT EXISTS {} (\n    tx_id TEXT NOT NULL,\n    path TEXT NOT NULL,\n    depth INTEGER NOT NULL,\n    selector TEXT NOT NULL,\n    signature_guess TEXT NOT NULL,\n    param_index INTEGER NOT NULL,\n    type TEXT NOT NULL,\n    candidates TEXT NOT NULL,\n    raw TEXT NOT NULL,\n    value TEXT NOT NULL\n);\n",
+            table
+        ),
+        Dialect::ClickHouse => format!(
+            "CREATE TABLE IF NOT EXISTS {} (\n    tx_id String,\n    path String,\n    depth UInt32,\n    selector String,\n    signature_guess String,\n    param_index UInt32,\n    type String,\n    candidates String,\n    raw String,\n    value String\n) ENGINE = MergeTree ORDER BY (selector, tx_id);\n",
+            table
+        ),
+    }
+}
+
+/// An `INSERT` with placeholders, to be run once per row of `insert_params`.
+///
+/// Postgres uses `$1..$n`, ClickHouse uses typed `{pN:Type}` query parameters.
+pub fn parameterized_insert(table: &str, dialect: Dialect) -> String {
+    let placeholders = COLUMNS
+        .iter()
+        .enumerate()
+        .map(|(i, column)| match dialect {
+            Dialect::Postgres => format!("${}", i + 1),
+            Dialect::ClickHouse => match *column {
+                "depth" | "param_index" => format!("{{p{}:UInt32}}", i + 1),
+                _ => format!("{{p{}:String}}", i + 1),
+            },
+        })
+        .collect::<Vec<String>>();
+    format!(
+        "INSERT INTO {} ({}) VALUES ({})",
+        table,
+        COLUMNS.join(", "),
+        placeholders.join(", ")
+    )
+}
+
+fn fields(row: FlatRow) -> Vec<String> {
+    vec![
+        row.tx_id,
+        row.path,
+        row.depth.to_string(),
+        row.selector,
+        row.signature_guess,
+        row.param_index.to_string(),
+        row.ty,
+        row.candidates,
+        row.raw,
+        row.value,
+    ]
+}
+
+/// Bound values for `parameterized_insert`, one `Vec` per row.
+pub fn insert_params<'a>(
+    batch: impl IntoIterator<Item = (&'a str, &'a DecodedCalldata)>,
+) -> Vec<Vec<String>> {
+    batch
+        .into_iter()
+        .flat_map(|(id, root)| flatten(id, root))
+        .map(fields)
+        .collect()
+}
+
+/// Quotes a SQL string literal.
+pub fn quote(value: &str) -> String {
+    format!("'{}'", value.replace('\\', "\\\\").replace('\'', "''"))
+}
+
+/// A single multi-row `INSERT` with the values inlined, for ad-hoc loading.
+pub fn render_inserts<'a>(
+    table: &str,
+    batch: impl IntoIterator<Item = (&'a str, &'a DecodedCalldata)>,
+) -> String {
+    let rows = insert_params(batch)
+        .into_iter()
+        .map(|row| {
+            let values = row
+                .iter()
+                .enumerate()
+                .map(|(i, v)| match COLUMNS[i] {
+                    "depth" | "param_index" => v.clone(),
+                    _ => quote(v),
+                })
+                .collect::<Vec<String>>();
+            format!("({})", values.join(", "))
+        })
+        .collect::<Vec<String>>();
+    if rows.is_empty() {
+        return String::new();
+    }
+    format!(
+        "INSERT INTO {} ({}) VALUES\n{};\n",
+        table,
+        COLUMNS.join(", "),
+        rows.join(",\n")
+    )
+}
+
+/// Rows in the tab-separated text format read by Postgres `COPY ... FROM STDIN`
+/// and ClickHouse `FORMAT TabSeparated`.
+pub fn render_copy<'a>(batch: impl IntoIterator<Item = (&'a str, &'a DecodedCalldata)>) -> String {
+    let mut out = String::new();
+    for row in insert_params(batch) {
+        let escaped = row
+            .iter()
+            .map(|v| {
+                v.replace('\\', "\\\\")
+                    .replace('\t', "\\t")
+                    .replace('\n', "\\n")
+                    .replace('\r', "\\r")
+            })
+            .collect::<Vec<String>>();
+        out.push_str(&escaped.join("\t"));
+        out.push('\n');
+    }
+    out
+}
@@ -0,0 +1,247 @@
+use crate::abiblob::DecodedAbi;
+use crate::constants::Types;
+use crate::decoded::{DecodeWarning, DecodedCalldata, DecodedParam};
+use crate::logs::DecodedLog;
+use crate::render::color::Style;
+use crate::returndata::DecodedReturn;
+
+// ------------------------------------------------------------
+//  Tree renderer
+// ------------------------------------------------------------
+
+const BRANCH: &str = "├── ";
+const LAST: &str = "└── ";
+const PIPE: &str = "│   ";
+const SPACE: &str = "    ";
+
+/// Renders the call and its nested calls as an indented tree, params included.
+///
+/// ```text
+/// 0xac9650d8 unknown_ac9650d8(uint256,...)
+/// ├── [0] uint256: 32
+/// └── → [0] 0x88316456 unknown_88316456(address,...)
+///     └── [0] address: 0xc011a73ee8576fb46f5e1c5751ca3b9fe0af2a6f
+/// ```
+pub fn render_tree(call: &DecodedCalldata) -> String {
+    render_tree_styled(call, true, &Style::plain())
+}
+
+/// Renders only the call structure, one line per call: `multicall → [0] ... → ...`.
+pub fn render_call_tree(call: &DecodedCalldata) -> String {
+    render_tree_styled(call, false, &Style::plain())
+}
+
+/// Renders the tree, painting selectors, addresses and amounts with `style`.
+///
+/// ## Params
+/// 1. call - root of the decode tree.
+/// 2. params - whether each call's params are listed under it.
+/// 3. style - colors to use, `Style::plain()` for none.
+pub fn render_tree_styled(call: &DecodedCalldata, params: bool, style: &Style) -> String {
+    let mut out = format!("{}\n", call_line(call, style));
+    write_children(&mut out, call, "", params, style);
+    out
+}
+
+fn call_line(call: &DecodedCalldata, style: &Style) -> String {
+    let layer = match &call.layer {
+        Some(layer) => format!(" {}", style.dim(&format!("({})", layer))),
+        None => String::new(),
+    };
+    format!(
+        "{} {}{}",
+        style.selector(&format!("0x{}", call.selector)),
+        call.signature_guess,
+        layer
+    )
+}
+
+/// `[index] type: value` for a param, its most likely type first.
+pub fn param_line(param: &DecodedParam, style: &Style) -> String {
+    format!("[{}] {}", param.index, typed_value(param, style))
+}
+
+/// `type: value` for a param, its most likely type, with its label, amount,
+/// text and signature when it has them.
+pub fn typed_value(param: &DecodedParam, style: &Style) -> String {
+    let Some(t) = param.types.first() else {
+        return param.labelled_value();
+    };
+    let value = match t.kind {
+        Types::Address | Types::Address0 => style.address(&param.value),
+        Types::Selector => style.selector(&param.value),
+        Types::Uint | Types::Int | Types::MaxUint128 | Types::AnyMax => style.amount(&param.value),
+        _ => param.value.clone(),
+    };
+    let text = match &param.text {
+        Some(text) if text.lossy => format!(" \"{}\" (lossy)", text.text),
+        Some(text) => format!(" \"{}\"", text.text),
+        None => String::new(),
+    };
+    let signature = match &param.signature {
+        Some(signature) => format!(" {}", style.dim(&signature.label())),
+        None => String::new(),
+    };
+    format!(
+        "{}: {}{}{}{}",
+        style.dim(&t.solidity),
+        value,
+        param.annotations(),
+        text,
+        signature
+    )
+}
+
+/// Renders a decoded log as a tree: its topics, then its data like a call's params.
+///
+/// ```text
+/// 0xddf252ad Transfer(address,address,uint256)
+/// ├── topic [1] address: 0xC02aaA39b223FE8D0A0e5C4F27eAD9083C756Cc2
+/// ├── topic [2] address: 0x0000000000000000000000000000000000000000
+/// └── [0] uint256: 1000000000000000000
+/// ```
+pub fn render_log(log: &DecodedLog) -> String {
+    render_log_styled(log, true, &Style::plain())
+}
+
+/// Same as `render_log`, painted with `style`; `params` as for `render_tree_styled`.
+pub fn render_log_styled(log: &DecodedLog, params: bool, style: &Style) -> String {
+    let id = match &log.topic0 {
+        Some(topic0) => style.selector(&format!("0x{}", &topic0[..8])),
+        None => style.dim("anonymous"),
+    };
+    let mut out = format!("{} {}\n", id, log.signature_guess);
+    let mut lines: Vec<(String, Option<&DecodedCalldata>)> = vec![];
+    if params {
+        for topic in log.topics.iter() {
+            lines.push((format!("topic {}", param_line(topic, style)), None));
+        }
+    }
+    push_lines(
+        &mut lines,
+        &log.params,
+        &log.warnings,
+        &log.calls,
+        params,
+        style,
+    );
+    write_lines(&mut out, lines, "", params, style);
+    out
+}
+
+/// Renders decoded return data as a tree of its params, under the function
+/// that returned it when known.
+///
+/// ```text
+/// balanceOf(address) returns (uint256)
+/// └── [0] uint256: 1000000000000000000
+/// ```
+pub fn render_return(ret: &DecodedReturn) -> String {
+    render_return_styled(ret, true, &Style::plain())
+}
+
+/// Same as `render_return`, painted with `style`; `params` as for `render_tree_styled`.
+pub fn render_return_styled(ret: &DecodedReturn, params: bool, style: &Style) -> String {
+    let mut out = match &ret.signature {
+        Some(signature) => format!("{} returns {}\n", signature, ret.returns_guess),
+        None => format!("{} {}\n", style.dim("returns"), ret.returns_guess),
+    };
+    let mut lines = vec![];
+    push_lines(
+        &mut lines,
+        &ret.params,
+        &ret.warnings,
+        &ret.calls,
+        params,
+        style,
+    );
+    write_lines(&mut out, lines, "", params, style);
+    out
+}
+
+/// Renders decoded ABI bytes as a tree of their params, under their guessed types.
+///
+/// ```text
+/// abi.decode (address,uint256)
+/// ├── [0] address: 0xdAC17F958D2ee523a2206206994597C13D831ec7
+/// └── [1] uint256: 1000000000
+/// ```
+pub fn render_abi_styled(abi: &DecodedAbi, params: bool, style: &Style) -> String {
+    let mut out = format!("{} {}\n", style.dim("abi.decode"), abi.types_guess);
+    let mut lines = vec![];
+    push_lines(
+        &mut lines,
+        &abi.params,
+        &abi.warnings,
+        &abi.calls,
+        params,
+        style,
+    );
+    write_lines(&mut out, lines, "", params, style);
+    out
+}
+
+fn write_children(
+    out: &mut String,
+    call: &DecodedCalldata,
+    prefix: &str,
+    params: bool,
+    style: &Style,
+) {
+    let mut lines = vec![];
+    push_lines(
+        &mut lines,
+        &call.params,
+        &call.warnings,
+        &call.calls,
+        params,
+        style,
+    );
+    write_lines(out, lines, prefix, params, style);
+}
+
+/// Lines of the params, warnings and nested calls under a call or log.
+fn push_lines<'a>(
+    lines: &mut Vec<(String, Option<&'a DecodedCalldata>)>,
+    param_list: &[DecodedParam],
+    warnings: &[DecodeWarning],
+    calls: &'a [DecodedCalldata],
+    params: bool,
+    style: &Style,
+) {
+    if params {
+        for param in param_list.iter() {
+            lines.push((param_line(param, style), None));
+        }
+    }
+    for warning in warnings.iter() {
+        lines.push((style.warning(&format!("! {}", warning)), None));
+    }
+    for (i, nested) in calls.iter().enumerate() {
+        lines.push((
+            format!("→ [{}] {}", i, call_line(nested, style)),
+            Some(nested),
+        ));
+    }
+}
+
+fn write_lines(
+    out: &mut String,
+    lines: Vec<(String, Option<&DecodedCalldata>)>,
+    prefix: &str,
+    params: bool,
+    style: &Style,
+) {
+    let len = lines.len();
+    for (i, (line, nested)) in lines.into_iter().enumerate() {
+        let last = i + 1 == len;
+        out.push_str(prefix);
+        out.push_str(if last { LAST } else { BRANCH });
+        out.push_str(&line);
+        out.push('\n');
+        if let Some(nested) = nested {
+            let prefix = format!("{}{}", prefix, if last { SPACE } else { PIPE });
+            write_children(out, nested, &prefix, params, style);
+        }
+    }
+}
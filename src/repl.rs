@@ -0,0 +1,191 @@
+use crate::decoded::DecodedCalldata;
+use crate::heuristics::Heuristics;
+use crate::render::color::Style;
+use crate::render::output::{render_output, OutputFormat};
+use crate::schema::JsonOutput;
+use crate::signatures::{SignatureMap, SignatureSource, Signatures};
+use crate::Calldata;
+use std::collections::BTreeMap;
+
+// ------------------------------------------------------------
+//  REPL
+// ------------------------------------------------------------
+
+pub const HELP: &str = "\
+<calldata>                  decode and print the call tree
+:redo                       decode the last calldata again
+:heuristics                 list heuristics and whether they're enabled
+:set <heuristic> on|off     toggle a heuristic
+:sig <signature>            register a signature, e.g. :sig foo(uint256)
+:sigs <file>                register the signatures in a file
+:format <format>            pretty, tree, json, yaml, etherscan, csv or words
+:help                       show this message
+:quit                       exit";
+
+/// What the caller should do after a line was handled.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Reply {
+    /// Print this and keep reading.
+    Output(String),
+    /// Stop reading.
+    Quit,
+}
+
+/// State kept between the inputs of an interactive session.
+pub struct Session {
+    pub heuristics: Heuristics,
+    pub format: OutputFormat,
+    signatures: Signatures,
+    /// Signatures registered during the session.
+    registered: SignatureMap,
+    /// Resolved selectors, kept across inputs.
+    cache: BTreeMap<String, Vec<String>>,
+    last: Option<String>,
+    style: Style,
+}
+
+impl Session {
+    pub fn new(signatures: Signatures, style: Style) -> Self {
+        Self {
+            heuristics: Heuristics::default(),
+            format: OutputFormat::default(),
+            signatures,
+            registered: SignatureMap::new(),
+            cache: BTreeMap::new(),
+            last: None,
+            style,
+        }
+    }
+
+    /// Handles one line of input.
+    pub fn handle(&mut self, line: &str) -> Reply {
+        let line = line.trim();
+        let (command, arg) = line.split_once(char::is_whitespace).unwrap_or((line, ""));
+        let arg = arg.trim();
+        let output = match command {
+            "" => return Reply::Output(String::new()),
+            ":q" | ":quit" | ":exit" => return Reply::Quit,
+            ":h" | ":help" => HELP.to_string(),
+            ":heuristics" => Heuristics::NAMES
+                .iter()
+                .map(|name| {
+                    let on = self.heuristics.get(name).unwrap_or_default();
+                    format!("{:<13} {}", name, if on { "on" } else { "off" })
+                })
+                .collect::<Vec<String>>()
+                .join("\n"),
+            ":set" => match arg.split_once(char::is_whitespace) {
+                Some((name, value)) => match parse_switch(value) {
+                    Ok(on) => match self.heuristics.set(name, on) {
+                        Ok(()) => self.redecode(),
+                        Err(e) => e,
+                    },
+                    Err(e) => e,
+                },
+                None => "usage: :set <heuristic> on|off".to_string(),
+            },
+            ":format" => match arg.parse() {
+                Ok(format) => {
+                    self.format = format;
+                    self.redecode()
+                }
+                Err(e) => e,
+            },
+            ":sig" => {
+                let map = SignatureMap::from_text(arg);
+                self.register(&map);
+                match map.is_empty() {
+                    true => "usage: :sig <signature>".to_string(),
+                    false => map
+                        .iter()
+                        .flat_map(|(selector, sigs)| {
+                            sigs.iter().map(move |s| format!("0x{} {}", selector, s))
+                        })
+                        .collect::<Vec<String>>()
+                        .join("\n"),
+                }
+            }
+            ":sigs" => match std::fs::read_to_string(arg) {
+                Ok(text) => {
+                    let map = SignatureMap::from_text(&text);
+                    let count = map.len();
+                    self.register(&map);
+                    format!("registered {} selectors", count)
+                }
+                Err(e) => format!("{}: {}", arg, e),
+            },
+            ":redo" => self.redecode(),
+            _ if command.starts_with(':') => format!("unknown command `{}`, try :help", command),
+            _ => {
+                self.last = Some(line.to_string());
+                self.redecode()
+            }
+        };
+        Reply::Output(output)
+    }
+
+    /// Signatures known for the selector, resolved once per session.
+    pub fn resolve(&mut self, selector: &str) -> Vec<String> {
+        if let Some(hit) = self.cache.get(selector) {
+            return hit.clone();
+        }
+        let mut resolved = self.registered.lookup(selector);
+        for signature in self.signatures.resolve(selector) {
+            if !resolved.contains(&signature) {
+                resolved.push(signature);
+            }
+        }
+        self.cache.insert(selector.to_string(), resolved.clone());
+        resolved
+    }
+
+    /// Number of selectors currently cached.
+    pub fn cached(&self) -> usize {
+        self.cache.len()
+    }
+
+    fn register(&mut self, map: &SignatureMap) {
+        for (selector, signatures) in map.iter() {
+            for signature in signatures {
+                self.registered.insert_with_selector(selector, signature);
+            }
+            // Only the entries that gained a signature go stale.
+            self.cache.remove(selector);
+        }
+    }
+
+    fn redecode(&mut self) -> String {
+        let Some(calldata) = self.last.clone() else {
+            return "nothing decoded yet".to_string();
+        };
+        let calldata = Calldata::with_heuristics(&calldata, self.heuristics);
+        let mut root = calldata.decode();
+        self.name_calls(&mut root);
+        let output = JsonOutput::with_root(&calldata, root);
+        let mut out = self
+            .format
+            .header()
+            .map(|h| format!("{}\n", h))
+            .unwrap_or_default();
+        out.push_str(&render_output(self.format, "0", &output, &self.style));
+        out.trim_end().to_string()
+    }
+
+    /// Swaps guessed signatures for resolved ones where a resolution exists.
+    fn name_calls(&mut self, call: &mut DecodedCalldata) {
+        if let Some(signature) = self.resolve(&call.selector).first() {
+            call.signature_guess = signature.clone();
+        }
+        for nested in call.calls.iter_mut() {
+            self.name_calls(nested);
+        }
+    }
+}
+
+fn parse_switch(value: &str) -> Result<bool, String> {
+    match value.trim() {
+        "on" | "true" | "1" => Ok(true),
+        "off" | "false" | "0" => Ok(false),
+        other => Err(format!("expected on or off, got `{}`", other)),
+    }
+}
@@ -0,0 +1,330 @@
+use crate::abiblob::decode_abi_blob_with;
+use crate::decode_hex_checked;
+use crate::decoded::{DecodeWarning, DecodedCalldata, DecodedParam};
+use crate::heuristics::Heuristics;
+use crate::limits::Limits;
+use crate::signatures::{head_types, selector_of, Layout};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+
+// ------------------------------------------------------------
+//  Return types
+// ------------------------------------------------------------
+
+/// Outputs of well-known functions, as `signature(inputs)(outputs)`.
+pub const BUILTIN_RETURNS: [&str; 16] = [
+    "name()(string)",
+    "symbol()(string)",
+    "decimals()(uint8)",
+    "totalSupply()(uint256)",
+    "balanceOf(address)(uint256)",
+    "allowance(address,address)(uint256)",
+    "transfer(address,uint256)(bool)",
+    "transferFrom(address,address,uint256)(bool)",
+    "approve(address,uint256)(bool)",
+    "ownerOf(uint256)(address)",
+    "owner()(address)",
+    "getReserves()(uint112,uint112,uint32)",
+    "multicall(bytes[])(bytes[])",
+    "multicall(uint256,bytes[])(bytes[])",
+    "aggregate((address,bytes)[])(uint256,bytes[])",
+    "isValidSignature(bytes32,bytes)(bytes4)",
+];
+
+/// A function and the types it returns.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ReturnSignature {
+    /// The function, e.g. `balanceOf(address)`.
+    pub signature: String,
+    /// Solidity type of each output.
+    pub outputs: Vec<String>,
+}
+
+impl ReturnSignature {
+    /// Parses `balanceOf(address)(uint256)` or `balanceOf(address) returns (uint256)`,
+    /// `None` if it isn't one.
+    pub fn parse(declaration: &str) -> Option<Self> {
+        let declaration = declaration.trim();
+        let (signature, outputs) = split_outputs(declaration)?;
+        let layout = Layout::parse(signature)?;
+        let outputs = outputs.trim();
+        let outputs = outputs.strip_prefix("returns").unwrap_or(outputs).trim();
+        let outputs = match outputs {
+            "" | "()" => vec![],
+            tuple => Layout::parse(&format!("f{}", tuple))?.inputs,
+        };
+        Some(Self {
+            signature: format!("{}({})", name_of(signature), layout.inputs.join(",")),
+            outputs,
+        })
+    }
+
+    /// Solidity type of each head word of the outputs, a dynamic output
+    /// being its offset word.
+    pub fn head_types(&self) -> Vec<String> {
+        self.outputs
+            .iter()
+            .flat_map(|ty| head_types(ty).unwrap_or_default())
+            .collect()
+    }
+}
+
+/// Splits a declaration after the parenthesis closing its inputs.
+fn split_outputs(declaration: &str) -> Option<(&str, &str)> {
+    let open = declaration.find('(')?;
+    let mut depth = 0;
+    for (i, c) in declaration.char_indices().skip(open) {
+        match c {
+            '(' => depth += 1,
+            ')' => depth -= 1,
+            _ => {}
+        }
+        if depth == 0 {
+            return Some(declaration.split_at(i + 1));
+        }
+    }
+    None
+}
+
+fn name_of(signature: &str) -> &str {
+    signature.split('(').next().unwrap_or_default().trim()
+}
+
+/// Known return types by the selector of their function.
+#[derive(Debug, Clone, Default)]
+pub struct ReturnSignatures(BTreeMap<String, Vec<ReturnSignature>>);
+
+impl ReturnSignatures {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Table of the `BUILTIN_RETURNS`.
+    pub fn builtin() -> Self {
+        let mut returns = Self::new();
+        for declaration in BUILTIN_RETURNS {
+            returns
+                .insert(declaration)
+                .expect("builtin returns are valid declarations");
+        }
+        returns
+    }
+
+    /// Adds a declaration under the selector of its function.
+    pub fn insert(&mut self, declaration: &str) -> Result<(), String> {
+        let returns = ReturnSignature::parse(declaration)
+            .ok_or_else(|| format!("not a function with its outputs: {}", declaration))?;
+        let entry = self.0.entry(selector_of(&returns.signature)).or_default();
+        if !entry.contains(&returns) {
+            entry.push(returns);
+        }
+        Ok(())
+    }
+
+    /// Parses one declaration per line, `#` starting a comment:
+    ///
+    /// ```text
+    /// balanceOf(address)(uint256)
+    /// getReserves() returns (uint112,uint112,uint32)
+    /// ```
+    pub fn from_text(text: &str) -> Result<Self, String> {
+        let mut returns = Self::new();
+        returns.insert_text(text)?;
+        Ok(returns)
+    }
+
+    /// Adds the declarations of `text`, in the format of [`Self::from_text`].
+    pub fn insert_text(&mut self, text: &str) -> Result<(), String> {
+        for line in text.lines() {
+            let line = line.split('#').next().unwrap_or_default().trim();
+            if !line.is_empty() {
+                self.insert(line)?;
+            }
+        }
+        Ok(())
+    }
+
+    /// Return types of the functions whose selector is `selector` (8 hex chars,
+    /// with or without `0x`).
+    pub fn resolve(&self, selector: &str) -> &[ReturnSignature] {
+        let selector = selector.trim_start_matches("0x").to_lowercase();
+        self.0.get(&selector).map(Vec::as_slice).unwrap_or_default()
+    }
+
+    pub fn len(&self) -> usize {
+        self.0.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+}
+
+// ------------------------------------------------------------
+//  Return data
+// ------------------------------------------------------------
+
+/// Decode tree of the data a call returned.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DecodedReturn {
+    /// The function that returned the data: the known one matching the
+    /// request's selector, else the request's guessed signature.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub signature: Option<String>,
+    /// Whether the outputs were typed from a known declaration.
+    pub known: bool,
+    /// The outputs as a tuple, e.g. `(uint112,uint112,uint32)`: declared when
+    /// known, else built from each param's most likely type.
+    pub returns_guess: String,
+    /// Params of the data, spans being byte offsets in the data.
+    pub params: Vec<DecodedParam>,
+    /// Method calls embedded in the data's params.
+    pub calls: Vec<DecodedCalldata>,
+    /// Irregularities found in the data.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub warnings: Vec<DecodeWarning>,
+}
+
+/// Decodes return data under one set of heuristics, limits and known outputs.
+#[derive(Debug, Clone)]
+pub struct ReturnDecoder {
+    heuristics: Heuristics,
+    limits: Limits,
+    returns: ReturnSignatures,
+}
+
+impl Default for ReturnDecoder {
+    fn default() -> Self {
+        Self {
+            heuristics: Heuristics::default(),
+            limits: Limits::default(),
+            returns: ReturnSignatures::builtin(),
+        }
+    }
+}
+
+impl ReturnDecoder {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn with_heuristics(mut self, heuristics: Heuristics) -> Self {
+        self.heuristics = heuristics;
+        self
+    }
+
+    pub fn with_limits(mut self, limits: Limits) -> Self {
+        self.limits = limits;
+        self
+    }
+
+    /// Replaces the builtin return types.
+    pub fn with_returns(mut self, returns: ReturnSignatures) -> Self {
+        self.returns = returns;
+        self
+    }
+
+    /// Decodes `returndata` on its own, with the calldata heuristics.
+    ///
+    /// ## Returns
+    /// 1. The decode, or an error when the data is over the limits.
+    pub fn decode(&self, returndata: &[u8]) -> Result<DecodedReturn, String> {
+        self.decode_with(returndata, None, None)
+    }
+
+    /// Decodes `returndata` as the result of `call`: typed by the known
+    /// outputs of the call's selector, and with the heuristics otherwise.
+    ///
+    /// ## Params
+    /// 1. call - the decode of the request's calldata.
+    /// 2. returndata - what the call returned.
+    pub fn decode_for(
+        &self,
+        call: &DecodedCalldata,
+        returndata: &[u8],
+    ) -> Result<DecodedReturn, String> {
+        let known = self.returns.resolve(&call.selector).first();
+        self.decode_with(returndata, Some(&call.signature_guess), known)
+    }
+
+    fn decode_with(
+        &self,
+        returndata: &[u8],
+        request: Option<&str>,
+        known: Option<&ReturnSignature>,
+    ) -> Result<DecodedReturn, String> {
+        let mut data = decode_abi_blob_with(returndata, self.heuristics, self.limits)?;
+
+        if let Some(known) = known {
+            for (param, ty) in data.params.iter_mut().zip(known.head_types()) {
+                param.prefer(&ty);
+            }
+        }
+        let returns_guess = match known {
+            Some(known) => format!("({})", known.outputs.join(",")),
+            None => data.types_guess,
+        };
+        Ok(DecodedReturn {
+            signature: known
+                .map(|k| k.signature.clone())
+                .or(request.map(str::to_string)),
+            known: known.is_some(),
+            returns_guess,
+            params: data.params,
+            calls: data.calls,
+            warnings: data.warnings,
+        })
+    }
+}
+
+/// The data an `eth_call` returned: the counterpart of `Calldata` for
+/// results, decoded as params with no selector in front.
+#[derive(Debug, Clone)]
+pub struct ReturnData {
+    bytes: Vec<u8>,
+    decoder: ReturnDecoder,
+}
+
+impl ReturnData {
+    /// Return data decoded with the default heuristics, limits and known outputs.
+    pub fn new(bytes: &[u8]) -> Self {
+        Self {
+            bytes: bytes.to_vec(),
+            decoder: ReturnDecoder::new(),
+        }
+    }
+
+    /// Same as `new`, from hex with or without the `0x` prefix.
+    pub fn from_hex(hex: &str) -> Result<Self, String> {
+        let bytes = decode_hex_checked(hex).map_err(|e| e.to_string())?;
+        Ok(Self::new(&bytes))
+    }
+
+    /// Decodes under `decoder`'s heuristics, limits and known outputs.
+    pub fn with_decoder(mut self, decoder: ReturnDecoder) -> Self {
+        self.decoder = decoder;
+        self
+    }
+
+    pub fn bytes(&self) -> &[u8] {
+        &self.bytes
+    }
+
+    /// Decode tree of the data on its own (see `ReturnDecoder::decode`).
+    pub fn decode(&self) -> Result<DecodedReturn, String> {
+        self.decoder.decode(&self.bytes)
+    }
+
+    /// Decode tree of the data as the result of `call` (see `ReturnDecoder::decode_for`).
+    pub fn decode_for(&self, call: &DecodedCalldata) -> Result<DecodedReturn, String> {
+        self.decoder.decode_for(call, &self.bytes)
+    }
+}
+
+/// Decodes the data an `eth_call` returned with the default heuristics and
+/// limits, without knowing which function returned it.
+pub fn decode_returndata(returndata: &[u8]) -> Result<DecodedReturn, String> {
+    ReturnDecoder::new().decode(returndata)
+}
@@ -0,0 +1,169 @@
+use primitive_types::U256;
+
+/// How deep lists may nest before decoding gives up, so hostile input can't
+/// overflow the stack.
+pub const MAX_DEPTH: usize = 64;
+
+// ------------------------------------------------------------
+//  RLP
+// ------------------------------------------------------------
+
+/// An RLP item, borrowing its strings from the encoded bytes.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Rlp<'a> {
+    Bytes(&'a [u8]),
+    List(Vec<Rlp<'a>>),
+}
+
+impl<'a> Rlp<'a> {
+    /// Decodes `bytes` as exactly one item, rejecting non-canonical lengths
+    /// and trailing bytes.
+    pub fn decode(bytes: &'a [u8]) -> Result<Self, String> {
+        let (item, rest) = Self::decode_prefix(bytes)?;
+        match rest.len() {
+            0 => Ok(item),
+            n => Err(format!("{} bytes after the RLP item", n)),
+        }
+    }
+
+    /// Decodes the item at the start of `bytes`.
+    ///
+    /// ## Returns
+    /// 1. The item and the bytes after it.
+    pub fn decode_prefix(bytes: &'a [u8]) -> Result<(Self, &'a [u8]), String> {
+        Self::decode_nested(bytes, 0)
+    }
+
+    fn decode_nested(bytes: &'a [u8], depth: usize) -> Result<(Self, &'a [u8]), String> {
+        let (is_list, payload, rest) = header(bytes)?;
+        match is_list {
+            true => Ok((Rlp::List(Self::decode_items(payload, depth + 1)?), rest)),
+            false => Ok((Rlp::Bytes(payload), rest)),
+        }
+    }
+
+    /// The encoded bytes of each item of the list `bytes` encodes, e.g. to
+    /// hash one of them as it was received.
+    pub fn raw_items(bytes: &'a [u8]) -> Result<Vec<&'a [u8]>, String> {
+        let (is_list, mut payload, rest) = header(bytes)?;
+        if !is_list {
+            return Err("expected an RLP list, found a string".to_string());
+        }
+        if !rest.is_empty() {
+            return Err(format!("{} bytes after the RLP item", rest.len()));
+        }
+        let mut items = vec![];
+        while !payload.is_empty() {
+            let (_, rest) = Self::decode_prefix(payload)?;
+            items.push(&payload[..payload.len() - rest.len()]);
+            payload = rest;
+        }
+        Ok(items)
+    }
+
+    fn decode_items(mut payload: &'a [u8], depth: usize) -> Result<Vec<Self>, String> {
+        if depth > MAX_DEPTH {
+            return Err(format!("RLP lists nested over {} deep", MAX_DEPTH));
+        }
+        let mut items = vec![];
+        while !payload.is_empty() {
+            let (item, rest) = Self::decode_nested(payload, depth)?;
+            items.push(item);
+            payload = rest;
+        }
+        Ok(items)
+    }
+
+    pub fn as_bytes(&self) -> Result<&'a [u8], String> {
+        match self {
+            Rlp::Bytes(bytes) => Ok(bytes),
+            Rlp::List(_) => Err("expected an RLP string, found a list".to_string()),
+        }
+    }
+
+    pub fn as_list(&self) -> Result<&[Rlp<'a>], String> {
+        match self {
+            Rlp::List(items) => Ok(items),
+            Rlp::Bytes(_) => Err("expected an RLP list, found a string".to_string()),
+        }
+    }
+
+    /// The string as a big-endian integer of at most 32 bytes, without leading zeros.
+    pub fn as_u256(&self) -> Result<U256, String> {
+        let bytes = self.as_bytes()?;
+        match (bytes.len(), bytes.first()) {
+            (33.., _) => Err(format!("{} byte integer is over 256 bits", bytes.len())),
+            (_, Some(0)) => Err("integer with leading zeros".to_string()),
+            _ => Ok(U256::from_big_endian(bytes)),
+        }
+    }
+
+    pub fn as_u64(&self) -> Result<u64, String> {
+        let value = self.as_u256()?;
+        match value > U256::from(u64::MAX) {
+            true => Err(format!("{} is over 64 bits", value)),
+            false => Ok(value.as_u64()),
+        }
+    }
+}
+
+/// Splits the item at the start of `bytes` into whether it's a list, its
+/// payload and the bytes after it.
+fn header(bytes: &[u8]) -> Result<(bool, &[u8], &[u8]), String> {
+    let (&prefix, rest) = bytes.split_first().ok_or("empty RLP item")?;
+    match prefix {
+        0x00..=0x7f => Ok((false, &bytes[..1], rest)),
+        0x80..=0xb7 => {
+            let (payload, rest) = take(rest, (prefix - 0x80) as usize)?;
+            if let [byte] = payload {
+                if *byte < 0x80 {
+                    return Err(format!("single byte 0x{:02x} encoded as a string", byte));
+                }
+            }
+            Ok((false, payload, rest))
+        }
+        0xb8..=0xbf => {
+            let (len, rest) = long_length(rest, (prefix - 0xb7) as usize)?;
+            let (payload, rest) = take(rest, len)?;
+            Ok((false, payload, rest))
+        }
+        0xc0..=0xf7 => {
+            let (payload, rest) = take(rest, (prefix - 0xc0) as usize)?;
+            Ok((true, payload, rest))
+        }
+        0xf8..=0xff => {
+            let (len, rest) = long_length(rest, (prefix - 0xf7) as usize)?;
+            let (payload, rest) = take(rest, len)?;
+            Ok((true, payload, rest))
+        }
+    }
+}
+
+fn take(bytes: &[u8], len: usize) -> Result<(&[u8], &[u8]), String> {
+    match bytes.len() < len {
+        true => Err(format!(
+            "RLP item of {} bytes with only {} left",
+            len,
+            bytes.len()
+        )),
+        false => Ok(bytes.split_at(len)),
+    }
+}
+
+/// Length of a long string or list, written in `size` big-endian bytes.
+fn long_length(bytes: &[u8], size: usize) -> Result<(usize, &[u8]), String> {
+    let (len_bytes, rest) = take(bytes, size)?;
+    if len_bytes[0] == 0 {
+        return Err("RLP length with leading zeros".to_string());
+    }
+    let len = len_bytes
+        .iter()
+        .try_fold(0usize, |len, b| {
+            len.checked_mul(256).map(|l| l + *b as usize)
+        })
+        .ok_or("RLP length over the address space")?;
+    match len < 56 {
+        true => Err(format!("RLP length {} should use the short form", len)),
+        false => Ok((len, rest)),
+    }
+}
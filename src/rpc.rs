@@ -0,0 +1,392 @@
+use crate::bytecode::dispatch_selectors;
+use crate::decoded::DecodedCalldata;
+use crate::prototypes::Prototype;
+use crate::proxy::{
+    minimal_proxy_implementation, slot_address, ProxyKind, ProxyResolution, BEACON_IMPLEMENTATION,
+    BEACON_SLOT, IMPLEMENTATION_SLOT,
+};
+use crate::render::address::{serialize_checksum, serialize_checksum_opt};
+use crate::signatures::Signatures;
+use crate::simulate::{pick, probes, CallOutcome, Disambiguation};
+use crate::Calldata;
+use ethers::providers::{Http, HttpClientError, Middleware, Provider, ProviderError};
+#[cfg(feature = "cli")]
+use ethers::providers::Ws;
+use ethers::types::transaction::eip2718::TypedTransaction;
+use ethers::types::{Address, BlockId, BlockNumber, Bytes, Transaction, TransactionRequest, H256, U256};
+use serde::Serialize;
+
+// ------------------------------------------------------------
+//  RPC
+// ------------------------------------------------------------
+
+/// A transaction's decoded input along with the addresses and value around it.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DecodedTransaction {
+    pub hash: H256,
+    #[serde(serialize_with = "serialize_checksum")]
+    pub from: Address,
+    /// `None` for contract creations, whose input is init code rather than calldata.
+    #[serde(serialize_with = "serialize_checksum_opt")]
+    pub to: Option<Address>,
+    pub value: U256,
+    pub block_number: Option<u64>,
+    /// The implementation `to` delegated to, when it was resolved.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub proxy: Option<ProxyResolution>,
+    pub decoded: DecodedCalldata,
+}
+
+impl DecodedTransaction {
+    /// Decodes the input of an already fetched transaction.
+    pub fn from_transaction(tx: &Transaction) -> Result<Self, String> {
+        if tx.input.len() < 4 {
+            return Err(format!("transaction {:?} has no calldata", tx.hash));
+        }
+        let calldata = Calldata::new(&format!("{:x}", tx.input));
+        Ok(Self {
+            hash: tx.hash,
+            from: tx.from,
+            to: tx.to,
+            value: tx.value,
+            block_number: tx.block_number.map(|b| b.as_u64()),
+            proxy: None,
+            decoded: calldata.decode(),
+        })
+    }
+}
+
+/// Connects to an HTTP JSON-RPC endpoint.
+pub fn http_provider(url: &str) -> Result<Provider<Http>, String> {
+    Provider::<Http>::try_from(url).map_err(|e| e.to_string())
+}
+
+/// Connects to a WebSocket JSON-RPC endpoint.
+#[cfg(feature = "cli")]
+pub async fn ws_provider(url: &str) -> Result<Provider<Ws>, String> {
+    Provider::<Ws>::connect(url).await.map_err(|e| e.to_string())
+}
+
+/// Fetches a transaction by hash.
+pub async fn fetch_transaction<M: Middleware>(provider: &M, hash: H256) -> Result<Transaction, String> {
+    provider
+        .get_transaction(hash)
+        .await
+        .map_err(|e| e.to_string())?
+        .ok_or_else(|| format!("transaction {:?} not found", hash))
+}
+
+/// Fetches a transaction by hash and decodes its input.
+pub async fn decode_transaction<M: Middleware>(
+    provider: &M,
+    hash: H256,
+) -> Result<DecodedTransaction, String> {
+    let tx = fetch_transaction(provider, hash).await?;
+    DecodedTransaction::from_transaction(&tx)
+}
+
+/// Same as `decode_transaction` for an already fetched transaction sent to a
+/// proxy: the implementation it delegated to is resolved (see `resolve_proxy`)
+/// and the calls nested in the input are limited to the implementation's
+/// selectors, as they are when decoding with its bytecode.
+///
+/// The implementation is read at the block before the transaction's, the
+/// state the transaction ran on unless the proxy was upgraded earlier in the
+/// same block; at the latest block for a pending transaction.
+pub async fn decode_transaction_resolved<M: Middleware>(
+    provider: &M,
+    tx: &Transaction,
+) -> Result<DecodedTransaction, String> {
+    let mut decoded = DecodedTransaction::from_transaction(tx)?;
+    let Some(to) = tx.to else {
+        return Ok(decoded);
+    };
+    let block = decoded.block_number.map(|b| b.saturating_sub(1));
+    let Some(proxy) = resolve_proxy(provider, to, block).await? else {
+        return Ok(decoded);
+    };
+    if !proxy.selectors.is_empty() {
+        decoded.decoded = Calldata::new(&format!("{:x}", tx.input))
+            .with_target_selectors(proxy.selectors.clone())
+            .decode();
+    }
+    decoded.proxy = Some(proxy);
+    Ok(decoded)
+}
+
+// ------------------------------------------------------------
+//  Proxies
+// ------------------------------------------------------------
+
+/// Resolves the implementation `proxy` delegates to at `block`: the one in
+/// its EIP-1967 implementation slot, else the one its EIP-1967 beacon answers
+/// with, else the one in its code for an EIP-1167 minimal proxy.
+///
+/// ## Params
+/// 1. provider - the endpoint to read the state from; an archive node for old blocks.
+/// 2. proxy - the address a transaction was sent to.
+/// 3. block - the block to read the state at, `None` for the latest.
+///
+/// ## Returns
+/// 1. The implementation and the selectors of its code at `block`, or `None`
+///    when `proxy` isn't a proxy of these kinds.
+pub async fn resolve_proxy<M: Middleware>(
+    provider: &M,
+    proxy: Address,
+    block: Option<u64>,
+) -> Result<Option<ProxyResolution>, String> {
+    let at = block.map(|b| BlockId::Number(BlockNumber::Number(b.into())));
+    let resolved = match storage_address(provider, proxy, IMPLEMENTATION_SLOT, at).await? {
+        Some(implementation) => Some((ProxyKind::Eip1967, implementation)),
+        None => match storage_address(provider, proxy, BEACON_SLOT, at).await? {
+            Some(beacon) => beacon_implementation(provider, beacon, at)
+                .await?
+                .map(|implementation| (ProxyKind::Beacon { beacon }, implementation)),
+            None => minimal_proxy_implementation(&code(provider, proxy, at).await?)
+                .map(|implementation| (ProxyKind::Minimal, implementation)),
+        },
+    };
+    let Some((kind, implementation)) = resolved else {
+        return Ok(None);
+    };
+    Ok(Some(ProxyResolution {
+        kind,
+        implementation,
+        block,
+        selectors: dispatch_selectors(&code(provider, implementation, at).await?),
+    }))
+}
+
+async fn storage_address<M: Middleware>(
+    provider: &M,
+    address: Address,
+    slot: [u8; 32],
+    at: Option<BlockId>,
+) -> Result<Option<Address>, String> {
+    let word = provider
+        .get_storage_at(address, H256(slot), at)
+        .await
+        .map_err(|e| e.to_string())?;
+    Ok(slot_address(&word.0))
+}
+
+/// What `beacon`'s `implementation()` returns, `None` when the call fails.
+async fn beacon_implementation<M: Middleware>(
+    provider: &M,
+    beacon: Address,
+    at: Option<BlockId>,
+) -> Result<Option<Address>, String> {
+    let request = TransactionRequest::new()
+        .to(beacon)
+        .data(Bytes::from(BEACON_IMPLEMENTATION.to_vec()));
+    let Ok(output) = provider.call(&TypedTransaction::Legacy(request), at).await else {
+        return Ok(None);
+    };
+    Ok(output
+        .get(..32)
+        .and_then(|w| <[u8; 32]>::try_from(w).ok())
+        .and_then(|w| slot_address(&w)))
+}
+
+async fn code<M: Middleware>(
+    provider: &M,
+    address: Address,
+    at: Option<BlockId>,
+) -> Result<Vec<u8>, String> {
+    provider
+        .get_code(address, at)
+        .await
+        .map(|code| code.to_vec())
+        .map_err(|e| e.to_string())
+}
+
+// ------------------------------------------------------------
+//  Simulation
+// ------------------------------------------------------------
+
+/// Runs `calldata` against `to` with `eth_call` at the latest block.
+///
+/// ## Returns
+/// 1. How the call ended, or an error when the endpoint failed rather than the call.
+pub async fn simulate_call(
+    provider: &Provider<Http>,
+    to: Address,
+    from: Option<Address>,
+    calldata: &[u8],
+) -> Result<CallOutcome, String> {
+    let mut request = TransactionRequest::new().to(to).data(Bytes::from(calldata.to_vec()));
+    if let Some(from) = from {
+        request = request.from(from);
+    }
+    match provider.call(&TypedTransaction::Legacy(request), None).await {
+        Ok(_) => Ok(CallOutcome::Returned),
+        Err(e) => revert(&e).ok_or_else(|| e.to_string()),
+    }
+}
+
+/// The revert an `eth_call` error reports, `None` when it isn't one.
+fn revert(error: &ProviderError) -> Option<CallOutcome> {
+    let ProviderError::JsonRpcClientError(e) = error else {
+        return None;
+    };
+    let Some(HttpClientError::JsonRpcError(e)) = e.downcast_ref::<HttpClientError>() else {
+        return None;
+    };
+    if !e.message.contains("revert") {
+        return None;
+    }
+    // Geth leaves `data` out when the call reverted without any.
+    let data = match e.data.as_ref().and_then(|d| d.as_str()) {
+        Some(data) => data.to_string(),
+        None => "0x".to_string(),
+    };
+    Some(CallOutcome::Reverted { data })
+}
+
+/// Simulates the call and a probe for each candidate signature against `to`,
+/// picking the candidate the target decodes (see `simulate::probes`).
+///
+/// ## Params
+/// 1. provider - the endpoint to simulate on.
+/// 2. to - the contract the calldata is for.
+/// 3. from - the sender, for targets that check it before decoding.
+/// 4. calldata - the call, selector included.
+/// 5. call - its decode.
+/// 6. candidates - signatures for the call, e.g. from `rank_prototypes`.
+pub async fn disambiguate(
+    provider: &Provider<Http>,
+    to: Address,
+    from: Option<Address>,
+    calldata: &[u8],
+    call: &DecodedCalldata,
+    candidates: &[Prototype],
+) -> Result<Disambiguation, String> {
+    let baseline = simulate_call(provider, to, from, calldata).await?;
+    let probes = probes(calldata, call, candidates);
+    let mut outcomes = vec![];
+    for probe in probes.iter() {
+        let bytes = crate::decode_hex(&probe.calldata);
+        outcomes.push(simulate_call(provider, to, from, &bytes).await?);
+    }
+    Ok(pick(baseline, probes, outcomes))
+}
+
+// ------------------------------------------------------------
+//  Blocks
+// ------------------------------------------------------------
+
+/// Transactions of a block sharing a top-level selector.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SelectorGroup {
+    /// `0x` prefixed selector.
+    pub selector: String,
+    pub signatures: Vec<String>,
+    pub count: usize,
+    pub hashes: Vec<H256>,
+}
+
+/// Every decodable transaction of a block, grouped by selector.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BlockReport {
+    pub number: u64,
+    /// Transactions in the block, decoded or not.
+    pub total: usize,
+    /// Transactions without calldata (plain transfers) or that failed to decode.
+    pub skipped: usize,
+    /// Largest group first.
+    pub groups: Vec<SelectorGroup>,
+    pub transactions: Vec<DecodedTransaction>,
+}
+
+impl BlockReport {
+    /// Decodes the transactions in parallel and groups them by selector.
+    ///
+    /// ## Params
+    /// 1. number - the block's number.
+    /// 2. txs - the block's transactions.
+    /// 3. sources - signatures to name the groups with.
+    pub fn new(number: u64, txs: &[Transaction], sources: &Signatures) -> Self {
+        let decoded = decode_transactions(txs);
+        let total = decoded.len();
+        let transactions = decoded
+            .into_iter()
+            .filter_map(|d| d.ok())
+            .collect::<Vec<DecodedTransaction>>();
+        Self {
+            number,
+            total,
+            skipped: total - transactions.len(),
+            groups: group_by_selector(&transactions, sources),
+            transactions,
+        }
+    }
+}
+
+/// Decodes transactions across all cores, keeping their order.
+///
+/// A transaction whose decode panics is reported as an error instead of taking the batch down.
+pub fn decode_transactions(txs: &[Transaction]) -> Vec<Result<DecodedTransaction, String>> {
+    let threads = std::thread::available_parallelism()
+        .map(|n| n.get())
+        .unwrap_or(1);
+    let chunk = txs.len().div_ceil(threads).max(1);
+    std::thread::scope(|scope| {
+        let handles = txs
+            .chunks(chunk)
+            .map(|txs| {
+                scope.spawn(move || {
+                    txs.iter()
+                        .map(|tx| {
+                            std::panic::catch_unwind(|| DecodedTransaction::from_transaction(tx))
+                                .unwrap_or_else(|_| Err(format!("failed to decode {:?}", tx.hash)))
+                        })
+                        .collect::<Vec<_>>()
+                })
+            })
+            .collect::<Vec<_>>();
+        handles
+            .into_iter()
+            .flat_map(|h| h.join().expect("decode errors are caught per transaction"))
+            .collect()
+    })
+}
+
+/// Groups transactions by their top-level selector, largest group first.
+pub fn group_by_selector(txs: &[DecodedTransaction], sources: &Signatures) -> Vec<SelectorGroup> {
+    let mut groups: Vec<SelectorGroup> = vec![];
+    for tx in txs.iter() {
+        let selector = format!("0x{}", tx.decoded.selector);
+        match groups.iter_mut().find(|g| g.selector == selector) {
+            Some(group) => {
+                group.count += 1;
+                group.hashes.push(tx.hash);
+            }
+            None => groups.push(SelectorGroup {
+                signatures: sources.resolve(&tx.decoded.selector),
+                selector,
+                count: 1,
+                hashes: vec![tx.hash],
+            }),
+        }
+    }
+    // Stable, so equal counts keep their first-seen order.
+    groups.sort_by_key(|g| std::cmp::Reverse(g.count));
+    groups
+}
+
+/// Fetches a block with its transactions and decodes them.
+pub async fn decode_block<M: Middleware>(
+    provider: &M,
+    number: u64,
+    sources: &Signatures,
+) -> Result<BlockReport, String> {
+    let block = provider
+        .get_block_with_txs(BlockNumber::Number(number.into()))
+        .await
+        .map_err(|e| e.to_string())?
+        .ok_or_else(|| format!("block {} not found", number))?;
+    Ok(BlockReport::new(number, &block.transactions, sources))
+}
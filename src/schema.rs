@@ -0,0 +1,233 @@
+use crate::decoded::{DecodeStatus, DecodedCalldata};
+use crate::heuristics::Heuristics;
+use crate::limits::Limits;
+use crate::render::address::{apply_address_case, AddressCase};
+use crate::{check_hex, Calldata};
+use serde::{Deserialize, Serialize};
+use std::io::Write;
+
+// ------------------------------------------------------------
+//  JSON schema
+// ------------------------------------------------------------
+
+/// Version of the JSON output, bumped on any breaking change to its shape.
+pub const SCHEMA_VERSION: u32 = 1;
+
+/// Versioned, machine-readable decode result (shared by the JSON and YAML outputs).
+///
+/// ```json
+/// {
+///   "version": 1,
+///   "calldata": "0x...",
+///   "root": {
+///     "selector": "ac9650d8",
+///     "signatureGuess": "unknown_ac9650d8(...)",
+///     "params": [{ "index": 0, "raw": "...", "value": "32", "types": [...], "span": {...} }],
+///     "calls": [...],
+///     "span": { "start": 0, "end": 612 }
+///   }
+/// }
+/// ```
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct JsonOutput {
+    pub version: u32,
+    /// The decoded calldata, `0x` prefixed.
+    pub calldata: String,
+    pub root: DecodedCalldata,
+}
+
+impl JsonOutput {
+    pub fn new(calldata: &Calldata) -> Self {
+        Self::with_root(calldata, calldata.decode())
+    }
+
+    /// Decodes hex calldata from outside the crate (bindings, FFI), within the default limits.
+    ///
+    /// ## Returns
+    /// 1. The decode, or why the input isn't calldata or is over the limits.
+    pub fn from_hex(hex: &str) -> Result<Self, String> {
+        check_hex(hex).map_err(|e| e.to_string())?;
+        let calldata = Calldata::with_limits(hex.trim(), Heuristics::default(), Limits::default())?;
+        Ok(Self::new(&calldata))
+    }
+
+    /// Wraps a decode tree that was post-processed (labels, number formats, ...).
+    pub fn with_root(calldata: &Calldata, root: DecodedCalldata) -> Self {
+        Self {
+            version: SCHEMA_VERSION,
+            calldata: format!("0x{}", calldata.calldata),
+            root,
+        }
+    }
+}
+
+/// Serializes the decode of `calldata` into the versioned JSON schema.
+pub fn to_json(calldata: &Calldata) -> String {
+    serde_json::to_string(&JsonOutput::new(calldata)).expect("decode tree is serializable")
+}
+
+/// Same as `to_json` but indented for humans.
+pub fn to_json_pretty(calldata: &Calldata) -> String {
+    serde_json::to_string_pretty(&JsonOutput::new(calldata)).expect("decode tree is serializable")
+}
+
+/// Streams decodes as JSON Lines (NDJSON): one `to_json` object per line.
+///
+/// Each line is flushed as soon as it's written so consumers (jq, log shippers)
+/// see results without waiting for the whole batch.
+pub fn write_jsonl<'a, W: Write>(
+    writer: &mut W,
+    batch: impl IntoIterator<Item = &'a Calldata>,
+) -> std::io::Result<usize> {
+    let mut written = 0;
+    for calldata in batch {
+        serde_json::to_writer(&mut *writer, &JsonOutput::new(calldata))?;
+        writer.write_all(b"\n")?;
+        writer.flush()?;
+        written += 1;
+    }
+    Ok(written)
+}
+
+/// Serializes the decode of `calldata` into YAML, using the same schema as `to_json`.
+pub fn to_yaml(calldata: &Calldata) -> String {
+    serde_yaml::to_string(&JsonOutput::new(calldata)).expect("decode tree is serializable")
+}
+
+/// Parses YAML previously produced by `to_yaml`, rejecting other schema versions.
+pub fn from_yaml(yaml: &str) -> Result<JsonOutput, String> {
+    let output: JsonOutput = serde_yaml::from_str(yaml).map_err(|e| e.to_string())?;
+    check_version(output)
+}
+
+/// Parses JSON previously produced by `to_json`, rejecting other schema versions.
+pub fn from_json(json: &str) -> Result<JsonOutput, String> {
+    let output: JsonOutput = serde_json::from_str(json).map_err(|e| e.to_string())?;
+    check_version(output)
+}
+
+fn check_version(output: JsonOutput) -> Result<JsonOutput, String> {
+    if output.version != SCHEMA_VERSION {
+        return Err(format!(
+            "unsupported schema version {} (expected {})",
+            output.version, SCHEMA_VERSION
+        ));
+    }
+    Ok(output)
+}
+
+// ------------------------------------------------------------
+//  JSON requests
+// ------------------------------------------------------------
+
+/// A decode asked for in JSON, the stable entry point of services that don't
+/// link against the Rust types.
+///
+/// ```json
+/// {
+///   "version": 1,
+///   "calldata": "0x...",
+///   "options": { "heuristics": { "nested": false }, "addressCase": "lowercase" }
+/// }
+/// ```
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", deny_unknown_fields)]
+pub struct DecodeRequest {
+    /// Schema version of the output the caller reads; the current one when left out.
+    #[serde(default = "schema_version")]
+    pub version: u32,
+    /// Calldata hex, with or without the `0x` prefix.
+    pub calldata: String,
+    #[serde(default)]
+    pub options: DecodeOptions,
+}
+
+/// Settings of a [`DecodeRequest`], each defaulting as the library's.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default, rename_all = "camelCase", deny_unknown_fields)]
+pub struct DecodeOptions {
+    pub heuristics: Heuristics,
+    pub limits: Limits,
+    pub address_case: AddressCase,
+}
+
+/// Answer to a [`DecodeRequest`]: the output, or why there is none.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DecodeResponse {
+    pub version: u32,
+    pub status: DecodeStatus,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub output: Option<JsonOutput>,
+    /// Why the request couldn't be decoded, `status` then being `invalid`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+    /// One-line summary of the decode, e.g. `transfer 1000 to USDC`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub summary: Option<String>,
+}
+
+impl DecodeResponse {
+    pub fn new(request: &DecodeRequest) -> Self {
+        match decode_request(request) {
+            Ok(output) => Self::decoded(output),
+            Err(e) => Self::error(e),
+        }
+    }
+
+    pub fn decoded(output: JsonOutput) -> Self {
+        Self {
+            version: SCHEMA_VERSION,
+            status: output.root.status(),
+            output: Some(output),
+            error: None,
+            summary: None,
+        }
+    }
+
+    pub fn with_summary(mut self, summary: String) -> Self {
+        self.summary = Some(summary);
+        self
+    }
+
+    pub fn error(error: String) -> Self {
+        Self {
+            version: SCHEMA_VERSION,
+            status: DecodeStatus::Invalid,
+            output: None,
+            error: Some(error),
+            summary: None,
+        }
+    }
+}
+
+/// Decodes a JSON [`DecodeRequest`] into a JSON [`DecodeResponse`]. Never
+/// fails: a malformed request is answered with its error.
+pub fn decode_json(request: &str) -> String {
+    let response = match serde_json::from_str::<DecodeRequest>(request) {
+        Ok(request) => DecodeResponse::new(&request),
+        Err(e) => DecodeResponse::error(format!("invalid request: {}", e)),
+    };
+    serde_json::to_string(&response).expect("decode response is serializable")
+}
+
+fn decode_request(request: &DecodeRequest) -> Result<JsonOutput, String> {
+    if request.version != SCHEMA_VERSION {
+        return Err(format!(
+            "unsupported schema version {} (expected {})",
+            request.version, SCHEMA_VERSION
+        ));
+    }
+    let options = request.options;
+    check_hex(&request.calldata).map_err(|e| e.to_string())?;
+    let calldata =
+        Calldata::with_limits(request.calldata.trim(), options.heuristics, options.limits)?;
+    let mut root = calldata.decode();
+    apply_address_case(&mut root, options.address_case);
+    Ok(JsonOutput::with_root(&calldata, root))
+}
+
+fn schema_version() -> u32 {
+    SCHEMA_VERSION
+}
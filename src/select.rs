@@ -0,0 +1,139 @@
+use crate::render::csv::escape;
+use crate::render::output::OutputFormat;
+use serde_json::Value;
+
+// ------------------------------------------------------------
+//  Field selection
+// ------------------------------------------------------------
+
+/// One step of a selection path.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Segment {
+    Key(String),
+    Index(usize),
+}
+
+/// Parses `params[0].value`-style paths into segments.
+pub fn parse_path(path: &str) -> Result<Vec<Segment>, String> {
+    let invalid = || format!("invalid path `{}`", path);
+    let mut segments = vec![];
+    for part in path.trim().split('.') {
+        let (key, mut rest) = part.split_at(part.find('[').unwrap_or(part.len()));
+        if !key.is_empty() {
+            segments.push(Segment::Key(key.to_string()));
+        }
+        while !rest.is_empty() {
+            let end = rest.find(']').ok_or_else(invalid)?;
+            let index = rest[1..end].parse::<usize>().map_err(|_| invalid())?;
+            segments.push(Segment::Index(index));
+            rest = &rest[end + 1..];
+            if !rest.is_empty() && !rest.starts_with('[') {
+                return Err(invalid());
+            }
+        }
+        if key.is_empty() && part.is_empty() {
+            return Err(invalid());
+        }
+    }
+    Ok(segments)
+}
+
+/// Follows `segments` into `value`, keys also matching in `snake_case`.
+pub fn select<'a>(value: &'a Value, segments: &[Segment]) -> Option<&'a Value> {
+    segments
+        .iter()
+        .try_fold(value, |value, segment| match segment {
+            Segment::Index(i) => value.get(i),
+            Segment::Key(key) => value.get(key).or_else(|| value.get(camel_case(key))),
+        })
+}
+
+/// The value at each path, `null` where the path leads nowhere.
+pub fn project(value: &Value, paths: &[String]) -> Result<Vec<Value>, String> {
+    paths
+        .iter()
+        .map(|path| {
+            Ok(select(value, &parse_path(path)?)
+                .cloned()
+                .unwrap_or(Value::Null))
+        })
+        .collect()
+}
+
+/// Renders projected values as one line.
+///
+/// JSON gives an object keyed by path, CSV a row (see `projection_header`) and everything
+/// else tab-separated values, strings unquoted.
+pub fn render_projection(format: OutputFormat, paths: &[String], values: &[Value]) -> String {
+    match format {
+        // Fields keep the order they were selected in.
+        OutputFormat::Json => format!(
+            "{{{}}}\n",
+            paths
+                .iter()
+                .zip(values.iter())
+                .map(|(path, value)| format!("{}:{}", Value::String(path.clone()), value))
+                .collect::<Vec<String>>()
+                .join(",")
+        ),
+        OutputFormat::Yaml => {
+            let mapping = paths
+                .iter()
+                .zip(values.iter())
+                .map(|(path, value)| {
+                    let value = serde_yaml::to_value(value).unwrap_or_default();
+                    (serde_yaml::Value::String(path.clone()), value)
+                })
+                .collect::<serde_yaml::Mapping>();
+            format!(
+                "---\n{}",
+                serde_yaml::to_string(&mapping).unwrap_or_default()
+            )
+        }
+        OutputFormat::Csv => format!(
+            "{}\n",
+            values
+                .iter()
+                .map(|v| escape(&plain(v)))
+                .collect::<Vec<String>>()
+                .join(",")
+        ),
+        _ => format!(
+            "{}\n",
+            values.iter().map(plain).collect::<Vec<String>>().join("\t")
+        ),
+    }
+}
+
+/// CSV header of a projection.
+pub fn projection_header(paths: &[String]) -> String {
+    paths
+        .iter()
+        .map(|p| escape(p))
+        .collect::<Vec<String>>()
+        .join(",")
+}
+
+fn plain(value: &Value) -> String {
+    match value {
+        Value::String(s) => s.clone(),
+        Value::Null => String::new(),
+        other => other.to_string(),
+    }
+}
+
+fn camel_case(key: &str) -> String {
+    let mut out = String::new();
+    let mut upper = false;
+    for c in key.chars() {
+        match (c, upper) {
+            ('_', _) => upper = true,
+            (c, true) => {
+                out.extend(c.to_uppercase());
+                upper = false;
+            }
+            (c, false) => out.push(c),
+        }
+    }
+    out
+}
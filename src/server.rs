@@ -0,0 +1,114 @@
+use crate::decode_hex;
+use crate::decoder::Decoder;
+use crate::schema::{DecodeResponse, JsonOutput, SCHEMA_VERSION};
+use axum::extract::{DefaultBodyLimit, State};
+use axum::http::StatusCode;
+use axum::routing::post;
+use axum::{Json, Router};
+use serde::{Deserialize, Serialize};
+use std::sync::Arc;
+
+// ------------------------------------------------------------
+//  HTTP service
+// ------------------------------------------------------------
+
+/// Most calldatas decoded by one `POST /decode-batch`.
+pub const MAX_BATCH: usize = 1024;
+
+/// Body of `POST /decode`.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", deny_unknown_fields)]
+pub struct DecodeBody {
+    /// Calldata hex, with or without the `0x` prefix.
+    pub calldata: String,
+}
+
+/// Body of `POST /decode-batch`.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", deny_unknown_fields)]
+pub struct BatchBody {
+    pub calldatas: Vec<String>,
+}
+
+/// Routes of the service, every request decoding with the shared `decoder`
+/// and its caches.
+///
+/// - `POST /decode` answers a [`DecodeResponse`] with the decode's summary,
+///   with status 422 when the calldata couldn't be decoded.
+/// - `POST /decode-batch` answers a [`DecodeResponse`] per calldata, in order.
+pub fn router(decoder: Arc<Decoder>) -> Router {
+    // Hex takes two characters a byte.
+    let body_limit = decoder
+        .limits()
+        .max_input
+        .saturating_mul(2)
+        .saturating_add(1024);
+    Router::new()
+        .route("/decode", post(decode))
+        .route("/decode-batch", post(decode_batch))
+        .layer(DefaultBodyLimit::max(body_limit))
+        .with_state(decoder)
+}
+
+/// Serves the routes on `addr`, e.g. `127.0.0.1:8080`, until the process stops.
+pub async fn serve(addr: &str, decoder: Arc<Decoder>) -> std::io::Result<()> {
+    let listener = tokio::net::TcpListener::bind(addr).await?;
+    axum::serve(listener, router(decoder)).await
+}
+
+async fn decode(
+    State(decoder): State<Arc<Decoder>>,
+    Json(body): Json<DecodeBody>,
+) -> (StatusCode, Json<DecodeResponse>) {
+    // Decoding is CPU bound, kept off the threads serving connections.
+    let response = tokio::task::spawn_blocking(move || respond(&decoder, &body.calldata))
+        .await
+        .unwrap_or_else(|e| DecodeResponse::error(e.to_string()));
+    let status = match response.error {
+        Some(_) => StatusCode::UNPROCESSABLE_ENTITY,
+        None => StatusCode::OK,
+    };
+    (status, Json(response))
+}
+
+async fn decode_batch(
+    State(decoder): State<Arc<Decoder>>,
+    Json(body): Json<BatchBody>,
+) -> Result<Json<Vec<DecodeResponse>>, (StatusCode, Json<DecodeResponse>)> {
+    if body.calldatas.len() > MAX_BATCH {
+        let error = format!(
+            "{} calldatas is over the batch limit of {}",
+            body.calldatas.len(),
+            MAX_BATCH
+        );
+        return Err((
+            StatusCode::PAYLOAD_TOO_LARGE,
+            Json(DecodeResponse::error(error)),
+        ));
+    }
+    let responses = tokio::task::spawn_blocking(move || {
+        let calldatas = body.calldatas.iter();
+        calldatas.map(|c| respond(&decoder, c)).collect::<Vec<_>>()
+    })
+    .await
+    .map_err(|e| {
+        let response = DecodeResponse::error(e.to_string());
+        (StatusCode::INTERNAL_SERVER_ERROR, Json(response))
+    })?;
+    Ok(Json(responses))
+}
+
+fn respond(decoder: &Decoder, calldata: &str) -> DecodeResponse {
+    match decoder.decode(calldata) {
+        Ok(root) => {
+            let summary = decoder.summarize(&root);
+            DecodeResponse::decoded(JsonOutput {
+                version: SCHEMA_VERSION,
+                calldata: format!("0x{}", faster_hex::hex_string(&decode_hex(calldata))),
+                root,
+            })
+            .with_summary(summary)
+        }
+        Err(e) => DecodeResponse::error(e),
+    }
+}
@@ -0,0 +1,581 @@
+use crate::cache::{Lru, DEFAULT_CAPACITY};
+use crate::decoded::{DecodedCalldata, DecodedParam};
+use crate::keccak256;
+use primitive_types::U256;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::collections::BTreeMap;
+use std::path::Path;
+use std::sync::{Mutex, OnceLock};
+
+// ------------------------------------------------------------
+//  Signature sources
+// ------------------------------------------------------------
+
+/// Well-known functions, resolved without any network access or database.
+pub const BUILTIN: &[&str] = &[
+    "transfer(address,uint256)",
+    "transferFrom(address,address,uint256)",
+    "approve(address,uint256)",
+    "balanceOf(address)",
+    "deposit()",
+    "withdraw(uint256)",
+    "multicall(bytes[])",
+    "multicall(uint256,bytes[])",
+    "aggregate((address,bytes)[])",
+    "refundETH()",
+    "unwrapWETH9(uint256,address)",
+    "sweepToken(address,uint256,address)",
+    "selfPermitAllowed(address,uint256,uint256,uint8,bytes32,bytes32)",
+    "createAndInitializePoolIfNecessary(address,address,uint24,uint160)",
+    "mint((address,address,uint24,int24,int24,uint256,uint256,uint256,uint256,address,uint256))",
+    "exactInputSingle((address,address,uint24,address,uint256,uint256,uint160))",
+    "exactInputSingle((address,address,uint24,address,uint256,uint256,uint256,uint160))",
+    "swapExactTokensForTokens(uint256,uint256,address[],address,uint256)",
+    "swapExactETHForTokens(uint256,address[],address,uint256)",
+    "execTransaction(address,uint256,bytes,uint8,uint256,uint256,uint256,address,address,bytes)",
+    "execute(address,uint256,bytes)",
+    "isValidSignature(bytes32,bytes)",
+];
+
+/// Whether the 4 bytes are the selector of a `BUILTIN` signature.
+pub fn is_builtin_selector(selector: &[u8]) -> bool {
+    static SELECTORS: OnceLock<Vec<[u8; 4]>> = OnceLock::new();
+    let selectors = SELECTORS.get_or_init(|| BUILTIN.iter().map(|s| selector_bytes(s)).collect());
+    selectors.iter().any(|s| s[..] == *selector)
+}
+
+/// Selector of a function signature, e.g. `a9059cbb` for `transfer(address,uint256)`.
+pub fn selector_of(signature: &str) -> String {
+    faster_hex::hex_string(&selector_bytes(signature))
+}
+
+pub(crate) fn selector_bytes(signature: &str) -> [u8; 4] {
+    let hash = keccak256(signature.as_bytes());
+    [hash[0], hash[1], hash[2], hash[3]]
+}
+
+/// Something that can turn a selector into candidate signatures.
+pub trait SignatureSource {
+    /// Signatures matching the selector (8 hex chars, no prefix), most likely first.
+    fn lookup(&self, selector: &str) -> Vec<String>;
+}
+
+/// In-memory selector → signatures table.
+#[derive(Debug, Clone, Default)]
+pub struct SignatureMap(BTreeMap<String, Vec<String>>);
+
+impl SignatureMap {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Table of the `BUILTIN` signatures.
+    pub fn builtin() -> Self {
+        let mut map = Self::new();
+        for signature in BUILTIN {
+            map.insert(signature);
+        }
+        map
+    }
+
+    /// Adds a signature under its computed selector.
+    pub fn insert(&mut self, signature: &str) {
+        self.insert_with_selector(&selector_of(signature), signature);
+    }
+
+    /// Adds a signature under a given selector (for collisions or unverifiable entries).
+    pub fn insert_with_selector(&mut self, selector: &str, signature: &str) {
+        let entry = self
+            .0
+            .entry(selector.trim_start_matches("0x").to_lowercase())
+            .or_default();
+        if !entry.iter().any(|s| s == signature) {
+            entry.push(signature.to_string());
+        }
+    }
+
+    /// Parses one signature per line, optionally preceded by its selector:
+    ///
+    /// ```text
+    /// transfer(address,uint256)
+    /// 0x095ea7b3 approve(address,uint256)
+    /// ```
+    pub fn from_text(text: &str) -> Self {
+        let mut map = Self::new();
+        for line in text.lines() {
+            let line = line.split('#').next().unwrap_or_default().trim();
+            match line.split_once(char::is_whitespace) {
+                Some((selector, signature)) if !selector.contains('(') => {
+                    map.insert_with_selector(selector, signature.trim())
+                }
+                _ if !line.is_empty() => map.insert(line),
+                _ => {}
+            }
+        }
+        map
+    }
+
+    /// Parses a JSON table, either an object of selectors and their signature
+    /// or signatures, or an array of signatures:
+    ///
+    /// ```json
+    /// {"0xa9059cbb": "transfer(address,uint256)", "0xdeadbeef": ["bar(address)"]}
+    /// ```
+    pub fn from_json(text: &str) -> Result<Self, String> {
+        let json = serde_json::from_str::<Value>(text).map_err(|e| e.to_string())?;
+        let mut map = Self::new();
+        match json {
+            Value::Object(table) => {
+                for (selector, signatures) in table {
+                    let signatures = match signatures {
+                        Value::Array(signatures) => signatures,
+                        signature => vec![signature],
+                    };
+                    for signature in signatures {
+                        let Value::String(signature) = signature else {
+                            return Err(format!("{}: expected signature strings", selector));
+                        };
+                        map.insert_with_selector(&selector, &signature);
+                    }
+                }
+            }
+            Value::Array(signatures) => {
+                for signature in signatures {
+                    let Value::String(signature) = signature else {
+                        return Err("expected an array of signature strings".to_string());
+                    };
+                    map.insert(&signature);
+                }
+            }
+            _ => return Err("expected an object or an array of signatures".to_string()),
+        }
+        Ok(map)
+    }
+
+    /// Parses a CSV table of one `selector,signature` or `signature` per row,
+    /// the signature optionally quoted; a header row is skipped.
+    pub fn from_csv(text: &str) -> Result<Self, String> {
+        let mut map = Self::new();
+        for (n, line) in text.lines().enumerate() {
+            let line = line.trim();
+            if line.is_empty() || (n == 0 && !line.contains('(')) {
+                continue;
+            }
+            let unquote = |s: &str| s.trim().trim_matches('"').to_string();
+            match line.split_once(',') {
+                Some((selector, signature)) if !selector.contains('(') => {
+                    let selector = unquote(selector);
+                    let hex = selector.trim_start_matches("0x");
+                    if hex.len() != 8 || !hex.bytes().all(|b| b.is_ascii_hexdigit()) {
+                        return Err(format!("line {}: {:?} isn't a selector", n + 1, selector));
+                    }
+                    map.insert_with_selector(&selector, &unquote(signature));
+                }
+                _ => map.insert(&unquote(line)),
+            }
+        }
+        Ok(map)
+    }
+
+    /// Parses a signature file as its extension says: `.json` as `from_json`,
+    /// `.csv` as `from_csv`, anything else as `from_text`.
+    pub fn parse(path: &Path, text: &str) -> Result<Self, String> {
+        let extension = path
+            .extension()
+            .and_then(|e| e.to_str())
+            .unwrap_or_default();
+        match extension.to_lowercase().as_str() {
+            "json" => Self::from_json(text),
+            "csv" => Self::from_csv(text),
+            _ => Ok(Self::from_text(text)),
+        }
+        .map_err(|e| format!("{}: {}", path.display(), e))
+    }
+
+    /// Selectors and their signatures, ordered by selector.
+    pub fn iter(&self) -> impl Iterator<Item = (&String, &Vec<String>)> {
+        self.0.iter()
+    }
+
+    pub fn len(&self) -> usize {
+        self.0.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+}
+
+impl SignatureSource for SignatureMap {
+    fn lookup(&self, selector: &str) -> Vec<String> {
+        self.0
+            .get(&selector.trim_start_matches("0x").to_lowercase())
+            .cloned()
+            .unwrap_or_default()
+    }
+}
+
+/// How a known signature lays out its inputs.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Layout {
+    /// The signature the layout was read from.
+    pub signature: String,
+    /// Solidity type of each input.
+    pub inputs: Vec<String>,
+    /// Words the inputs take in the head; a dynamic input is one offset word.
+    pub head_words: usize,
+}
+
+impl Layout {
+    /// Parses a signature such as `multicall(uint256,bytes[])`, `None` if it isn't one.
+    pub fn parse(signature: &str) -> Option<Self> {
+        let (name, rest) = signature.trim().split_once('(')?;
+        let mut chars = name.chars();
+        let is_identifier = chars
+            .next()
+            .is_some_and(|c| c.is_ascii_alphabetic() || c == '_' || c == '$')
+            && chars.all(|c| c.is_ascii_alphanumeric() || c == '_' || c == '$');
+        if !is_identifier {
+            return None;
+        }
+        let inputs = match rest.strip_suffix(')')?.trim() {
+            "" => vec![],
+            args => split_top_level(args)?
+                .into_iter()
+                .map(SolType::parse)
+                .collect::<Option<Vec<_>>>()?,
+        };
+        Some(Self {
+            signature: signature.to_string(),
+            inputs: inputs.iter().map(|t| t.to_string()).collect(),
+            head_words: inputs.iter().map(SolType::head_words).sum(),
+        })
+    }
+
+    /// Whether calldata params could be an encoding of the inputs: one param
+    /// per head word, or more when an input is dynamic, each dynamic input's
+    /// offset word pointing at a word past the head.
+    pub fn fits(&self, params: &[DecodedParam]) -> bool {
+        let types = self
+            .inputs
+            .iter()
+            .flat_map(|ty| head_types(ty).unwrap_or_default())
+            .collect::<Vec<_>>();
+        let len = params.len() * 32;
+        match types.iter().all(|ty| is_value_type(ty)) {
+            true => params.len() == self.head_words,
+            false => {
+                params.len() > self.head_words
+                    && types
+                        .iter()
+                        .zip(params)
+                        .filter(|(ty, _)| !is_value_type(ty))
+                        .all(|(_, param)| {
+                            U256::from_str_radix(&param.raw, 16).is_ok_and(|offset| {
+                                offset % 32 == U256::zero()
+                                    && offset >= U256::from(self.head_words * 32)
+                                    && offset < U256::from(len)
+                            })
+                        })
+            }
+        }
+    }
+}
+
+/// Solidity type of each head word of a type, e.g. `["address", "uint256"]`
+/// for `(address,uint256)`; `None` if it isn't a type.
+pub fn head_types(ty: &str) -> Option<Vec<String>> {
+    SolType::parse(ty).map(|t| t.head_types())
+}
+
+/// Whether a Solidity type is encoded in place, in one word.
+pub(crate) fn is_value_type(ty: &str) -> bool {
+    !(ty == "string" || ty == "bytes" || ty.ends_with(']') || ty.starts_with('('))
+}
+
+/// A Solidity type as written in a signature.
+pub(crate) enum SolType {
+    Elementary(String),
+    Array(Box<SolType>),
+    FixedArray(Box<SolType>, usize),
+    Tuple(Vec<SolType>),
+}
+
+impl SolType {
+    /// Parses a type, `uint` and `int` standing for their 256 bit versions and
+    /// a param name after the type being ignored.
+    pub(crate) fn parse(s: &str) -> Option<Self> {
+        let s = s.trim();
+        if let Some(rest) = s.strip_suffix(']') {
+            let open = rest.rfind('[')?;
+            let inner = Box::new(Self::parse(&rest[..open])?);
+            return match &rest[open + 1..] {
+                "" => Some(Self::Array(inner)),
+                len => Some(Self::FixedArray(inner, len.parse().ok()?)),
+            };
+        }
+        let tuple = s.strip_prefix("tuple").unwrap_or(s);
+        if let Some(inner) = tuple.strip_prefix('(').and_then(|t| t.strip_suffix(')')) {
+            return match inner.trim() {
+                "" => Some(Self::Tuple(vec![])),
+                inner => Some(Self::Tuple(
+                    split_top_level(inner)?
+                        .into_iter()
+                        .map(Self::parse)
+                        .collect::<Option<_>>()?,
+                )),
+            };
+        }
+        let name = s.split_whitespace().next()?;
+        let bits = |digits: &str| digits.parse::<usize>().ok();
+        let valid = match name {
+            "address" | "bool" | "string" | "bytes" | "function" | "uint" | "int" => true,
+            _ if name.starts_with("bytes") => {
+                bits(&name[5..]).is_some_and(|n| (1..=32).contains(&n))
+            }
+            _ if name.starts_with("uint") => {
+                bits(&name[4..]).is_some_and(|n| n % 8 == 0 && (8..=256).contains(&n))
+            }
+            _ if name.starts_with("int") => {
+                bits(&name[3..]).is_some_and(|n| n % 8 == 0 && (8..=256).contains(&n))
+            }
+            _ => false,
+        };
+        match (valid, name) {
+            (false, _) => None,
+            (true, "uint") => Some(Self::Elementary("uint256".to_string())),
+            (true, "int") => Some(Self::Elementary("int256".to_string())),
+            (true, name) => Some(Self::Elementary(name.to_string())),
+        }
+    }
+
+    fn is_dynamic(&self) -> bool {
+        match self {
+            Self::Elementary(name) => name == "string" || name == "bytes",
+            Self::Array(_) => true,
+            Self::FixedArray(inner, _) => inner.is_dynamic(),
+            Self::Tuple(inner) => inner.iter().any(Self::is_dynamic),
+        }
+    }
+
+    /// Head words of a type: static arrays and tuples are inlined, dynamic types are an offset.
+    fn head_words(&self) -> usize {
+        match self {
+            _ if self.is_dynamic() => 1,
+            Self::FixedArray(inner, len) => len * inner.head_words(),
+            Self::Tuple(inner) => inner.iter().map(Self::head_words).sum(),
+            _ => 1,
+        }
+    }
+
+    /// Static arrays and tuples inlined, dynamic types and the rest a word each.
+    fn head_types(&self) -> Vec<String> {
+        match self {
+            _ if self.is_dynamic() => vec![self.to_string()],
+            Self::FixedArray(inner, len) => (0..*len).flat_map(|_| inner.head_types()).collect(),
+            Self::Tuple(inner) => inner.iter().flat_map(Self::head_types).collect(),
+            _ => vec![self.to_string()],
+        }
+    }
+}
+
+impl std::fmt::Display for SolType {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Elementary(name) => f.write_str(name),
+            Self::Array(inner) => write!(f, "{}[]", inner),
+            Self::FixedArray(inner, len) => write!(f, "{}[{}]", inner, len),
+            Self::Tuple(inner) => {
+                let inner = inner.iter().map(|t| t.to_string()).collect::<Vec<_>>();
+                write!(f, "({})", inner.join(","))
+            }
+        }
+    }
+}
+
+/// Splits a list of types on its commas outside of parentheses, `None` if they don't balance.
+fn split_top_level(list: &str) -> Option<Vec<&str>> {
+    let (mut parts, mut depth, mut start) = (vec![], 0usize, 0);
+    for (i, c) in list.char_indices() {
+        match c {
+            '(' => depth += 1,
+            ')' => depth = depth.checked_sub(1)?,
+            ',' if depth == 0 => {
+                parts.push(&list[start..i]);
+                start = i + 1;
+            }
+            _ => {}
+        }
+    }
+    parts.push(&list[start..]);
+    (depth == 0).then_some(parts)
+}
+
+/// Resolutions and layouts by selector.
+struct SelectorCache {
+    resolved: Lru<String, Vec<String>>,
+    layouts: Lru<String, Option<Layout>>,
+}
+
+impl SelectorCache {
+    fn new(capacity: usize) -> Self {
+        Self {
+            resolved: Lru::new(capacity),
+            layouts: Lru::new(capacity),
+        }
+    }
+}
+
+impl Default for SelectorCache {
+    fn default() -> Self {
+        Self::new(DEFAULT_CAPACITY)
+    }
+}
+
+/// Sources consulted in order, merging their answers.
+///
+/// Answers are cached by selector, so a `Signatures` shared across a batch of
+/// decodes only asks its sources once per selector.
+#[derive(Default)]
+pub struct Signatures {
+    sources: Vec<Box<dyn SignatureSource + Send + Sync>>,
+    cache: Mutex<SelectorCache>,
+}
+
+impl Signatures {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Only the built-in table.
+    pub fn builtin() -> Self {
+        Self::new().with_source(SignatureMap::builtin())
+    }
+
+    pub fn with_source(mut self, source: impl SignatureSource + Send + Sync + 'static) -> Self {
+        self.sources.push(Box::new(source));
+        self.cache = Mutex::default();
+        self
+    }
+
+    /// Keeps the answers for at most `capacity` selectors, least recently used evicted first.
+    pub fn with_cache_capacity(mut self, capacity: usize) -> Self {
+        self.cache = Mutex::new(SelectorCache::new(capacity));
+        self
+    }
+
+    /// `(hits, misses)` of the resolution cache so far.
+    pub fn cache_stats(&self) -> (u64, u64) {
+        self.cache
+            .lock()
+            .map(|c| c.resolved.stats())
+            .unwrap_or_default()
+    }
+
+    /// All signatures found for the selector, without duplicates, in source order.
+    pub fn resolve(&self, selector: &str) -> Vec<String> {
+        let key = selector.trim_start_matches("0x").to_lowercase();
+        if let Some(hit) = self
+            .cache
+            .lock()
+            .ok()
+            .and_then(|mut c| c.resolved.get(&key))
+        {
+            return hit;
+        }
+        // Sources may be slow, so they're asked without holding the cache.
+        let resolved = self.lookup(&key);
+        if let Ok(mut cache) = self.cache.lock() {
+            cache.resolved.insert(key, resolved.clone());
+        }
+        resolved
+    }
+
+    /// Layout of the selector's first signature, if one is known and parses.
+    pub fn layout(&self, selector: &str) -> Option<Layout> {
+        let key = selector.trim_start_matches("0x").to_lowercase();
+        if let Some(hit) = self.cache.lock().ok().and_then(|mut c| c.layouts.get(&key)) {
+            return hit;
+        }
+        let layout = self.resolve(&key).iter().find_map(|s| Layout::parse(s));
+        if let Ok(mut cache) = self.cache.lock() {
+            cache.layouts.insert(key, layout.clone());
+        }
+        layout
+    }
+
+    fn lookup(&self, selector: &str) -> Vec<String> {
+        let mut out: Vec<String> = vec![];
+        for source in self.sources.iter() {
+            for signature in source.lookup(selector) {
+                if !out.contains(&signature) {
+                    out.push(signature);
+                }
+            }
+        }
+        out
+    }
+}
+
+/// A selector found in the calldata and what it resolves to.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SelectorHit {
+    /// 0 for the top-level call, 1 for its nested calls, ...
+    pub depth: usize,
+    /// `0x` prefixed selector.
+    pub selector: String,
+    /// Byte offset of the selector in the calldata.
+    pub offset: usize,
+    pub signatures: Vec<String>,
+}
+
+/// Every selector of the tree, depth-first, resolved against `sources`.
+pub fn find_selectors(root: &DecodedCalldata, sources: &Signatures) -> Vec<SelectorHit> {
+    let mut hits = vec![];
+    let mut stack = vec![(0, root)];
+    while let Some((depth, call)) = stack.pop() {
+        hits.push(SelectorHit {
+            depth,
+            selector: format!("0x{}", call.selector),
+            offset: call.span.start,
+            signatures: sources.resolve(&call.selector),
+        });
+        for nested in call.calls.iter().rev() {
+            stack.push((depth + 1, nested));
+        }
+    }
+    hits
+}
+
+// ------------------------------------------------------------
+//  Typing by signature
+// ------------------------------------------------------------
+
+/// Types each call of the tree as the first signature `sources` resolve for
+/// it whose layout fits its params (see `Layout::fits`): the head words
+/// rendered as the declared types and the signature written as the call's
+/// guess. Calls no signature fits keep their heuristic types.
+pub fn apply_signatures(root: &mut DecodedCalldata, sources: &Signatures) {
+    for call in root.calls.iter_mut() {
+        apply_signatures(call, sources);
+    }
+    let fitting = sources
+        .resolve(&root.selector)
+        .iter()
+        .filter_map(|s| Layout::parse(s))
+        .find(|layout| layout.fits(&root.params));
+    let Some(layout) = fitting else {
+        return;
+    };
+    let types = layout
+        .inputs
+        .iter()
+        .flat_map(|ty| head_types(ty).unwrap_or_default());
+    for (param, ty) in root.params.iter_mut().zip(types) {
+        param.prefer(&ty);
+    }
+    root.signature_guess = layout.signature;
+}
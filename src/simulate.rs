@@ -0,0 +1,193 @@
+use crate::decoded::DecodedCalldata;
+use crate::prototypes::Prototype;
+use crate::signatures::{head_types, is_value_type, Layout};
+use primitive_types::U256;
+use serde::{Deserialize, Serialize};
+
+// ------------------------------------------------------------
+//  Simulated disambiguation
+// ------------------------------------------------------------
+
+/// How a simulated call ended.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "kind", rename_all = "camelCase")]
+pub enum CallOutcome {
+    Returned,
+    /// `data` is the revert data, `0x` prefixed.
+    Reverted {
+        data: String,
+    },
+}
+
+impl CallOutcome {
+    /// Reverted without data, which is how Solidity's ABI decoder rejects
+    /// arguments that don't fit the function's types.
+    pub fn is_decoding_revert(&self) -> bool {
+        matches!(self, CallOutcome::Reverted { data } if data == "0x" || data.is_empty())
+    }
+}
+
+/// Calldata probing one candidate signature.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Probe {
+    pub signature: String,
+    /// Confidence of the candidate before simulating.
+    pub confidence: f64,
+    /// The call, the words the candidates disagree on set to the widest value
+    /// of the candidate's type for them. `0x` prefixed.
+    pub calldata: String,
+    /// Bits of the candidate's types for those words; of two candidates the
+    /// target decodes, the wider one is closer to its real types.
+    pub width: u32,
+}
+
+/// A probe and how the target took it.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ProbeResult {
+    #[serde(flatten)]
+    pub probe: Probe,
+    pub outcome: CallOutcome,
+    /// The target got past decoding the arguments.
+    pub decoded: bool,
+}
+
+/// Which candidate signature the target agrees with.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Disambiguation {
+    /// How the call itself ended.
+    pub baseline: CallOutcome,
+    /// In the candidates' order.
+    pub results: Vec<ProbeResult>,
+    /// `None` when the call itself reverts while decoding, or no probe got
+    /// past decoding.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub winner: Option<String>,
+}
+
+/// Builds a probe of the top-level call for each candidate, to be simulated
+/// against the target, e.g. with `eth_call`.
+///
+/// The words all candidates type the same keep their value. Each word they
+/// disagree on is set to the widest value of the candidate's type for it (the
+/// max `uintN`, the min `intN`, an all-ones address or `bytesN`), which the
+/// target only decodes when its real type is at least as wide.
+///
+/// ## Params
+/// 1. calldata - the call, selector included.
+/// 2. call - its decode.
+/// 3. candidates - signatures for the call, e.g. from `rank_prototypes`.
+pub fn probes(calldata: &[u8], call: &DecodedCalldata, candidates: &[Prototype]) -> Vec<Probe> {
+    let heads = candidates
+        .iter()
+        .map(|c| {
+            Layout::parse(&c.signature)
+                .and_then(|l| head_types(&format!("({})", l.inputs.join(","))))
+                .unwrap_or_default()
+        })
+        .collect::<Vec<_>>();
+    let disputed = |i: usize| {
+        let mut types = heads.iter().map(|h| h.get(i));
+        let first = types.next().flatten();
+        types.any(|t| t != first)
+    };
+    candidates
+        .iter()
+        .zip(heads.iter())
+        .map(|(candidate, head)| {
+            let mut bytes = calldata.to_vec();
+            let mut width = 0;
+            for (i, ty) in head.iter().enumerate() {
+                let start = call.span.start + 4 + i * 32;
+                let Some(word) = bytes.get_mut(start..start + 32) else {
+                    continue;
+                };
+                if !disputed(i) || !is_value_type(ty) {
+                    continue;
+                }
+                if let Some((bits, value)) = widest(ty) {
+                    word.copy_from_slice(&value);
+                    width += bits;
+                }
+            }
+            Probe {
+                signature: candidate.signature.clone(),
+                confidence: candidate.confidence,
+                calldata: format!("0x{}", faster_hex::hex_string(&bytes)),
+                width,
+            }
+        })
+        .collect()
+}
+
+/// Picks the widest candidate whose probe the target decoded, the first one
+/// of equal width.
+///
+/// ## Params
+/// 1. baseline - how the call itself ended.
+/// 2. probes - from `probes`.
+/// 3. outcomes - how each probe ended, in the same order.
+pub fn pick(
+    baseline: CallOutcome,
+    probes: Vec<Probe>,
+    outcomes: Vec<CallOutcome>,
+) -> Disambiguation {
+    let results = probes
+        .into_iter()
+        .zip(outcomes)
+        .map(|(probe, outcome)| ProbeResult {
+            probe,
+            decoded: !outcome.is_decoding_revert(),
+            outcome,
+        })
+        .collect::<Vec<_>>();
+    let winner = match baseline.is_decoding_revert() {
+        true => None,
+        false => results
+            .iter()
+            .filter(|r| r.decoded)
+            .reduce(|best, r| match r.probe.width > best.probe.width {
+                true => r,
+                false => best,
+            })
+            .map(|r| r.probe.signature.clone()),
+    };
+    Disambiguation {
+        baseline,
+        results,
+        winner,
+    }
+}
+
+/// Bits of a value type and its widest value, the one narrower types reject.
+fn widest(ty: &str) -> Option<(u32, [u8; 32])> {
+    let word = |value: U256| {
+        let mut word = [0; 32];
+        value.to_big_endian(&mut word);
+        word
+    };
+    let ones = |bits: u32| match bits {
+        256 => U256::MAX,
+        bits => (U256::one() << bits) - 1,
+    };
+    let bits = |prefix: &str, max: u32| {
+        ty[prefix.len()..]
+            .parse::<u32>()
+            .ok()
+            .filter(|n| (1..=max).contains(n))
+    };
+    match ty {
+        "address" => Some((160, word(ones(160)))),
+        "bool" => Some((1, word(U256::one()))),
+        _ if ty.starts_with("uint") => bits("uint", 256).map(|n| (n, word(ones(n)))),
+        _ if ty.starts_with("int") => bits("int", 256).map(|n| (n, word(!ones(n - 1)))),
+        _ if ty.starts_with("bytes") => bits("bytes", 32).map(|n| {
+            let mut value = [0; 32];
+            value[..n as usize].fill(0xff);
+            (n * 8, value)
+        }),
+        _ => None,
+    }
+}
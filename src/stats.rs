@@ -0,0 +1,210 @@
+use crate::decoded::DecodedCalldata;
+use crate::signatures::Signatures;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+
+// ------------------------------------------------------------
+//  Batch statistics
+// ------------------------------------------------------------
+
+/// Counts gathered over a batch of decodes, one decode at a time.
+///
+/// Statistics of batches decoded apart, e.g. on several threads, add up with
+/// [`BatchStats::merge`].
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct BatchStats {
+    decoded: usize,
+    failed: usize,
+    /// Sum of the nesting depths, for the average.
+    depths: usize,
+    max_depth: usize,
+    selectors: BTreeMap<String, SelectorCounts>,
+    targets: BTreeMap<String, usize>,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+struct SelectorCounts {
+    calls: usize,
+    top_level: usize,
+    /// Per param position, how often each type was the most likely one.
+    types: Vec<BTreeMap<String, usize>>,
+}
+
+impl BatchStats {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Counts a decode, its nested calls included.
+    ///
+    /// ## Params
+    /// 1. root - the decode.
+    /// 2. target - the address the calldata was sent to, when known, with or
+    ///    without the `0x` prefix.
+    pub fn add(&mut self, root: &DecodedCalldata, target: Option<&str>) {
+        self.decoded += 1;
+        let depth = depth(root);
+        self.depths += depth;
+        self.max_depth = self.max_depth.max(depth);
+        for (i, call) in root.walk().into_iter().enumerate() {
+            let counts = self.selectors.entry(call.selector.clone()).or_default();
+            counts.calls += 1;
+            counts.top_level += (i == 0) as usize;
+            if counts.types.len() < call.params.len() {
+                counts.types.resize(call.params.len(), BTreeMap::new());
+            }
+            for (types, param) in counts.types.iter_mut().zip(&call.params) {
+                let ty = match param.types.first() {
+                    Some(t) => t.solidity.clone(),
+                    None => "bytes".to_string(),
+                };
+                *types.entry(ty).or_default() += 1;
+            }
+        }
+        if let Some(target) = target {
+            let target = target.trim_start_matches("0x").to_lowercase();
+            *self.targets.entry(format!("0x{}", target)).or_default() += 1;
+        }
+    }
+
+    /// Counts an input that didn't decode.
+    pub fn add_failure(&mut self) {
+        self.failed += 1;
+    }
+
+    /// Adds the counts of another batch.
+    pub fn merge(&mut self, other: &BatchStats) {
+        self.decoded += other.decoded;
+        self.failed += other.failed;
+        self.depths += other.depths;
+        self.max_depth = self.max_depth.max(other.max_depth);
+        for (selector, theirs) in other.selectors.iter() {
+            let ours = self.selectors.entry(selector.clone()).or_default();
+            ours.calls += theirs.calls;
+            ours.top_level += theirs.top_level;
+            if ours.types.len() < theirs.types.len() {
+                ours.types.resize(theirs.types.len(), BTreeMap::new());
+            }
+            for (ours, theirs) in ours.types.iter_mut().zip(&theirs.types) {
+                for (ty, count) in theirs {
+                    *ours.entry(ty.clone()).or_default() += count;
+                }
+            }
+        }
+        for (target, count) in other.targets.iter() {
+            *self.targets.entry(target.clone()).or_default() += count;
+        }
+    }
+
+    /// The statistics so far.
+    ///
+    /// ## Params
+    /// 1. top - how many selectors and targets to keep, the most frequent ones.
+    /// 2. sources - signatures to name the selectors with.
+    pub fn summary(&self, top: usize, sources: &Signatures) -> BatchSummary {
+        let calls = self.selectors.values().map(|c| c.calls).sum::<usize>();
+        let mut selectors = self
+            .selectors
+            .iter()
+            .map(|(selector, counts)| SelectorSummary {
+                selector: selector.clone(),
+                signature: sources.resolve(selector).into_iter().next(),
+                calls: counts.calls,
+                top_level: counts.top_level,
+                share: counts.calls as f64 / calls.max(1) as f64,
+                params: counts
+                    .types
+                    .iter()
+                    .enumerate()
+                    .map(|(index, types)| ParamTypes {
+                        index,
+                        types: ranked(types),
+                    })
+                    .collect(),
+            })
+            .collect::<Vec<_>>();
+        // Ties stay in selector order, the sort being stable.
+        selectors.sort_by_key(|s| std::cmp::Reverse(s.calls));
+        selectors.truncate(top);
+        let mut targets = ranked(&self.targets)
+            .into_iter()
+            .map(|(address, calls)| TargetCount { address, calls })
+            .collect::<Vec<_>>();
+        targets.truncate(top);
+        BatchSummary {
+            decoded: self.decoded,
+            failed: self.failed,
+            calls,
+            average_depth: self.depths as f64 / self.decoded.max(1) as f64,
+            max_depth: self.max_depth,
+            selectors,
+            targets,
+        }
+    }
+}
+
+/// Levels of calls nested in `call`, 0 when there's none.
+fn depth(call: &DecodedCalldata) -> usize {
+    call.calls.iter().map(|c| depth(c) + 1).max().unwrap_or(0)
+}
+
+/// Entries by decreasing count, ties in key order.
+fn ranked(counts: &BTreeMap<String, usize>) -> Vec<(String, usize)> {
+    let mut ranked = counts
+        .iter()
+        .map(|(key, count)| (key.clone(), *count))
+        .collect::<Vec<_>>();
+    ranked.sort_by_key(|(_, count)| std::cmp::Reverse(*count));
+    ranked
+}
+
+/// What a batch of calldatas looks like.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BatchSummary {
+    /// Inputs decoded.
+    pub decoded: usize,
+    /// Inputs that didn't decode.
+    pub failed: usize,
+    /// Calls decoded, nested ones included.
+    pub calls: usize,
+    /// Levels of nested calls per decode.
+    pub average_depth: f64,
+    pub max_depth: usize,
+    /// Most frequent first.
+    pub selectors: Vec<SelectorSummary>,
+    /// Most called first, when the targets are known.
+    pub targets: Vec<TargetCount>,
+}
+
+/// How often a selector was called, and with which param types.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SelectorSummary {
+    pub selector: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub signature: Option<String>,
+    /// Calls with this selector, nested ones included.
+    pub calls: usize,
+    /// Of which top-level calls.
+    pub top_level: usize,
+    /// Share of all the calls, 0..=1.
+    pub share: f64,
+    pub params: Vec<ParamTypes>,
+}
+
+/// Most likely types of the param at `index`, with how often each was guessed,
+/// most frequent first.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ParamTypes {
+    pub index: usize,
+    pub types: Vec<(String, usize)>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TargetCount {
+    pub address: String,
+    pub calls: usize,
+}
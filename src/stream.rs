@@ -0,0 +1,234 @@
+use crate::constants::*;
+use crate::decoded::{candidates, render_value, DecodedParam, Span};
+use crate::heuristics::Heuristics;
+use crate::render::color::Style;
+use crate::render::output::OutputFormat;
+use crate::render::tree::param_line;
+use crate::{guess_tail_type, HexError};
+use serde::{Deserialize, Serialize};
+use std::io::Read;
+
+// ------------------------------------------------------------
+//  Streaming decoder
+// ------------------------------------------------------------
+
+/// Bytes read from the input at a time.
+pub const CHUNK: usize = 64 * 1024;
+
+/// A decoded piece of the calldata, emitted as soon as its bytes are read.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "region", rename_all = "camelCase")]
+pub enum Region {
+    /// The top-level method selector.
+    Selector { selector: String, span: Span },
+    /// One word of the body, guessed on its own.
+    Param(Box<DecodedParam>),
+}
+
+/// Decodes calldata word by word while reading it, for payloads (rollup batches,
+/// blob-carrying transactions) too large to hold as a whole.
+///
+/// Peak memory is one read chunk plus a word, whatever the input size. Each
+/// word is guessed on its own: nested calls aren't split out, by the layout
+/// pass or the protocol decoders, since that needs the rest of the calldata;
+/// use `Calldata` for those.
+pub struct StreamDecoder<R> {
+    reader: R,
+    /// Whether the input is hex text rather than raw bytes.
+    hex: bool,
+    heuristics: Heuristics,
+    /// Read buffer, reused across reads.
+    chunk: Vec<u8>,
+    /// Bytes read but not yet emitted start at `pos`.
+    pending: Vec<u8>,
+    pos: usize,
+    /// Hex digit left over from the last read.
+    nibble: Option<u8>,
+    /// Hex digits read, for error positions.
+    read: usize,
+    /// Bytes of input text read, for error positions.
+    text: usize,
+    /// Byte offset of `pending[pos]` in the calldata.
+    offset: usize,
+    index: usize,
+    eof: bool,
+    done: bool,
+}
+
+impl<R: Read> StreamDecoder<R> {
+    /// Reads hex calldata, optionally `0x` prefixed; whitespace and line breaks are skipped.
+    pub fn hex(reader: R) -> Self {
+        Self::new(reader, true)
+    }
+
+    /// Reads calldata as raw bytes.
+    pub fn binary(reader: R) -> Self {
+        Self::new(reader, false)
+    }
+
+    fn new(reader: R, hex: bool) -> Self {
+        Self {
+            reader,
+            hex,
+            heuristics: Heuristics::default(),
+            chunk: vec![0; CHUNK],
+            pending: Vec::with_capacity(CHUNK + WORD),
+            pos: 0,
+            nibble: None,
+            read: 0,
+            text: 0,
+            offset: 0,
+            index: 0,
+            eof: false,
+            done: false,
+        }
+    }
+
+    /// Guesses words applying only the enabled `heuristics`.
+    pub fn with_heuristics(mut self, heuristics: Heuristics) -> Self {
+        self.heuristics = heuristics;
+        self
+    }
+
+    fn available(&self) -> usize {
+        self.pending.len() - self.pos
+    }
+
+    /// Reads until `want` bytes are pending or the input ends.
+    fn fill(&mut self, want: usize) -> Result<(), String> {
+        let mut chunk = std::mem::take(&mut self.chunk);
+        while self.available() < want && !self.eof {
+            // Drop what was emitted so the buffer never grows past a chunk.
+            self.pending.drain(..self.pos);
+            self.pos = 0;
+            let n = self.reader.read(&mut chunk).map_err(|e| e.to_string())?;
+            match (n, self.hex) {
+                (0, _) => self.eof = true,
+                (n, true) => self.push_hex(&chunk[..n])?,
+                (n, false) => self.pending.extend_from_slice(&chunk[..n]),
+            }
+        }
+        self.chunk = chunk;
+        match (self.eof, self.nibble) {
+            (true, Some(_)) => Err(HexError::OddLength { digits: self.read }.to_string()),
+            _ => Ok(()),
+        }
+    }
+
+    fn push_hex(&mut self, text: &[u8]) -> Result<(), String> {
+        for c in text.iter() {
+            self.text += 1;
+            if c.is_ascii_whitespace() {
+                continue;
+            }
+            // `0x` prefix: a lone leading zero followed by `x`.
+            let prefix = self.read == 1 && self.offset == 0 && self.pending.is_empty();
+            match ((*c as char).to_digit(16), self.nibble) {
+                (Some(lo), Some(hi)) => {
+                    self.pending.push(hi << 4 | lo as u8);
+                    self.nibble = None;
+                }
+                (Some(hi), None) => self.nibble = Some(hi as u8),
+                (None, Some(0)) if prefix && (*c == b'x' || *c == b'X') => {
+                    self.nibble = None;
+                    self.read = 0;
+                    continue;
+                }
+                (None, _) => {
+                    let e = HexError::InvalidChar {
+                        found: *c as char,
+                        at: self.text - 1,
+                        byte: self.read / 2,
+                    };
+                    return Err(e.to_string());
+                }
+            }
+            self.read += 1;
+        }
+        Ok(())
+    }
+
+    /// Takes up to `n` pending bytes, returning them with their span.
+    fn take(&mut self, n: usize) -> (Vec<u8>, Span) {
+        let n = n.min(self.available());
+        let bytes = self.pending[self.pos..self.pos + n].to_vec();
+        let span = Span {
+            start: self.offset,
+            end: self.offset + n,
+        };
+        self.pos += n;
+        self.offset += n;
+        (bytes, span)
+    }
+
+    fn next_region(&mut self) -> Result<Option<Region>, String> {
+        if self.offset == 0 {
+            self.fill(SELECTOR)?;
+            if self.available() < SELECTOR {
+                return Err(format!(
+                    "{} bytes is shorter than a selector",
+                    self.available()
+                ));
+            }
+            let (selector, span) = self.take(SELECTOR);
+            return Ok(Some(Region::Selector {
+                selector: faster_hex::hex_string(&selector),
+                span,
+            }));
+        }
+
+        self.fill(WORD)?;
+        if self.available() == 0 {
+            return Ok(None);
+        }
+        let (word, span) = self.take(WORD);
+        // Only the last word can be short.
+        let types = guess_tail_type(&word, &self.heuristics);
+        let raw = faster_hex::hex_string(&word);
+        let param = DecodedParam {
+            index: self.index,
+            value: render_value(&types, &raw),
+            types: candidates(&types, &raw),
+            raw,
+            span,
+            // Strings span several words, the stream only ever has one.
+            text: None,
+            signature: None,
+            label: None,
+            amount: None,
+        };
+        self.index += 1;
+        Ok(Some(Region::Param(Box::new(param))))
+    }
+}
+
+impl<R: Read> Iterator for StreamDecoder<R> {
+    type Item = Result<Region, String>;
+
+    /// The selector, then each word of the body; an error ends the stream.
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.done {
+            return None;
+        }
+        let region = self.next_region().transpose();
+        self.done = !matches!(region, Some(Ok(_)));
+        region
+    }
+}
+
+/// Renders a region as it's emitted: a JSON object per line for `json`, a YAML
+/// document for `yaml`, otherwise a line per selector and param.
+pub fn render_region(format: OutputFormat, region: &Region, style: &Style) -> String {
+    match (format, region) {
+        (OutputFormat::Json, region) => {
+            format!("{}\n", serde_json::to_string(region).unwrap_or_default())
+        }
+        (OutputFormat::Yaml, region) => {
+            format!("---\n{}", serde_yaml::to_string(region).unwrap_or_default())
+        }
+        (_, Region::Selector { selector, .. }) => {
+            format!("{}\n", style.selector(&format!("0x{}", selector)))
+        }
+        (_, Region::Param(param)) => format!("{}\n", param_line(param, style)),
+    }
+}
@@ -0,0 +1,171 @@
+use crate::codegen::static_type;
+use crate::constants::*;
+use crate::decoded::{candidates, fitting};
+use crate::type_guesser::ParamTypes;
+use crate::word_usize;
+
+// ------------------------------------------------------------
+//  Struct arrays
+// ------------------------------------------------------------
+
+/// An array of static structs found in a call's words: a length followed by
+/// `count` elements of `stride` words each.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct StructArray {
+    /// Index of the length word.
+    pub length: usize,
+    pub count: usize,
+    /// Words per element.
+    pub stride: usize,
+}
+
+impl StructArray {
+    /// Index of the first word of the elements.
+    pub fn start(&self) -> usize {
+        self.length + 1
+    }
+
+    /// Index of the word after the last element.
+    pub fn end(&self) -> usize {
+        self.start() + self.count * self.stride
+    }
+
+    /// Indices of the words of field `column`, element by element.
+    pub fn column(&self, column: usize) -> impl Iterator<Item = usize> {
+        let (start, stride) = (self.start() + column, self.stride);
+        (0..self.count).map(move |element| start + element * stride)
+    }
+}
+
+/// The arrays of static structs in `words`, the params of a call.
+///
+/// An offset in the head points at a length of `count` elements; the words
+/// from there to the next thing an offset points at (or the end) are split
+/// into `count` elements, when they split evenly into 2 words or more each.
+/// Arrays of dynamic elements, whose first words are offsets to each, aren't taken.
+pub fn struct_arrays(words: &[&[u8]]) -> Vec<StructArray> {
+    let full = |i: usize| words.get(i).filter(|w| w.len() == WORD);
+    let value = |i: usize| full(i).and_then(|w| word_usize(w));
+    // Words an offset in the head points at, counted from the first word.
+    let mut targets = (0..words.len())
+        .filter_map(|i| {
+            let v = value(i)?;
+            let target = v / WORD;
+            (v > 0 && v.is_multiple_of(WORD) && target > i && target < words.len())
+                .then_some(target)
+        })
+        .collect::<Vec<_>>();
+    targets.sort_unstable();
+    targets.dedup();
+
+    let mut arrays = vec![];
+    for (n, &length) in targets.iter().enumerate() {
+        let end = targets.get(n + 1).copied().unwrap_or(words.len());
+        let tail = end - length - 1;
+        let Some(count) = value(length).filter(|c| *c >= 2 && *c <= tail) else {
+            continue;
+        };
+        if !tail.is_multiple_of(count) || tail / count < 2 {
+            continue;
+        }
+        // Offsets to each element, increasing within the tail.
+        let offsets = (0..count)
+            .map(|k| value(length + 1 + k))
+            .collect::<Option<Vec<_>>>();
+        let dynamic = offsets.is_some_and(|o| {
+            o.iter().all(|v| v.is_multiple_of(WORD) && *v < tail * WORD)
+                && o.windows(2).all(|w| w[0] < w[1])
+        });
+        if dynamic || (length + 1..end).any(|i| full(i).is_none()) {
+            continue;
+        }
+        arrays.push(StructArray {
+            length,
+            count,
+            stride: tail / count,
+        });
+    }
+    arrays
+}
+
+/// The type a field of a struct array was voted, for one of its words.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ColumnVote {
+    /// Index of the word.
+    pub index: usize,
+    /// The voted type, first in the word's candidates.
+    pub kind: Types,
+    /// How the column voted.
+    pub reason: String,
+}
+
+/// Votes, field by field, on the types of the words of each struct array in
+/// `words`, typed one by one as `types`: the type most words of a column
+/// were guessed as that every word of the column can hold. A column of 0s
+/// and 1s is voted `bool`, a column of zero or max words isn't voted on.
+///
+/// Only the words whose first candidate the vote changes are returned.
+pub fn column_votes(words: &[&[u8]], types: &[ParamTypes]) -> Vec<ColumnVote> {
+    let mut votes = vec![];
+    for array in struct_arrays(words) {
+        for column in 0..array.stride {
+            let indices = array.column(column).collect::<Vec<_>>();
+            let Some(voted) = vote(&indices, words, types) else {
+                continue;
+            };
+            for &index in indices.iter() {
+                let current = &types[index].types()[0];
+                if current.solidity_name() == voted && *current != Types::AnyZero {
+                    continue;
+                }
+                let kind = types[index]
+                    .types()
+                    .iter()
+                    .find(|k| **k != Types::AnyZero && k.solidity_name() == voted)
+                    .cloned()
+                    .unwrap_or_else(|| fitting(&voted));
+                votes.push(ColumnVote {
+                    index,
+                    kind,
+                    reason: format!(
+                        "field {} of {} elements of {} words each, voted {}",
+                        column, array.count, array.stride, voted
+                    ),
+                });
+            }
+        }
+    }
+    votes
+}
+
+/// The Solidity type of the words at `indices`, `None` when they don't say.
+fn vote(indices: &[usize], words: &[&[u8]], types: &[ParamTypes]) -> Option<String> {
+    let column = indices
+        .iter()
+        .map(|&i| {
+            let word = <[u8; WORD]>::try_from(words[i]).ok()?;
+            Some((candidates(&types[i], &faster_hex::hex_string(&word)), word))
+        })
+        .collect::<Option<Vec<_>>>()?;
+    let telling = column
+        .iter()
+        .any(|(c, _)| !matches!(c[0].kind, Types::AnyZero | Types::AnyMax));
+    if !telling {
+        return None;
+    }
+    let bit = |word: &[u8; WORD]| word[..WORD - 1].iter().all(|b| *b == 0) && word[WORD - 1] <= 1;
+    match column.iter().all(|(_, word)| bit(word)) {
+        true => Some("bool".to_string()),
+        false => Some(static_type(column.iter().map(|(c, w)| (c.as_slice(), w)))),
+    }
+}
+
+/// Moves each vote's type first among its word's candidates.
+pub(crate) fn apply_votes(types: &mut [ParamTypes], votes: &[ColumnVote]) {
+    for vote in votes {
+        let mut kinds = types[vote.index].types().to_vec();
+        kinds.retain(|k| *k != vote.kind);
+        kinds.insert(0, vote.kind.clone());
+        types[vote.index] = ParamTypes::new(kinds);
+    }
+}
@@ -0,0 +1,157 @@
+use crate::decode_hex;
+use crate::decoded::DecodedCalldata;
+use crate::render::address::to_checksum;
+use crate::render::labels::Labels;
+use crate::signatures::Signatures;
+use primitive_types::U256;
+
+// ------------------------------------------------------------
+//  Summaries
+// ------------------------------------------------------------
+
+/// One-line description of a call and the calls nested in it, e.g.
+/// `multicall: mint SYN/WETH position @fee 1%, then refundETH`.
+///
+/// Well-known functions (token transfers and approvals, WETH, Uniswap
+/// routers and position manager, Safe) read as what they do; other known
+/// ones by their name, and unknown ones by their selector. Addresses read as
+/// their label, e.g. a token's symbol, or shortened.
+///
+/// ## Params
+/// 1. root - the decode.
+/// 2. sources - signatures to name the calls with.
+/// 3. labels - names of addresses.
+pub fn summarize(root: &DecodedCalldata, sources: &Signatures, labels: &Labels) -> String {
+    let call = Call { call: root, labels };
+    let nested = root
+        .calls
+        .iter()
+        .map(|c| summarize(c, sources, labels))
+        .collect::<Vec<_>>();
+    let Some(layout) = sources.layout(&root.selector) else {
+        return with_nested(format!("0x{}", root.selector), &nested);
+    };
+    let name = layout.signature.split('(').next().unwrap_or_default();
+    match layout.signature.as_str() {
+        "transfer(address,uint256)" => format!("transfer {} to {}", call.amount(1), call.address(0)),
+        "transferFrom(address,address,uint256)" => format!(
+            "transfer {} from {} to {}",
+            call.amount(2),
+            call.address(0),
+            call.address(1)
+        ),
+        "approve(address,uint256)" => format!("approve {} for {}", call.address(0), call.amount(1)),
+        "deposit()" => "wrap ETH".to_string(),
+        "withdraw(uint256)" => format!("unwrap {}", call.amount(0)),
+        "unwrapWETH9(uint256,address)" => {
+            format!("unwrap at least {} WETH to {}", call.amount(0), call.address(1))
+        }
+        "sweepToken(address,uint256,address)" => {
+            format!("sweep {} to {}", call.address(0), call.address(2))
+        }
+        "createAndInitializePoolIfNecessary(address,address,uint24,uint160)" => format!(
+            "create {}/{} pool @fee {}",
+            call.address(0),
+            call.address(1),
+            call.fee(2)
+        ),
+        "mint((address,address,uint24,int24,int24,uint256,uint256,uint256,uint256,address,uint256))" => {
+            format!(
+                "mint {}/{} position @fee {}",
+                call.address(0),
+                call.address(1),
+                call.fee(2)
+            )
+        }
+        "exactInputSingle((address,address,uint24,address,uint256,uint256,uint160))" => format!(
+            "swap {} {} for {} @fee {}",
+            call.amount(4),
+            call.address(0),
+            call.address(1),
+            call.fee(2)
+        ),
+        "exactInputSingle((address,address,uint24,address,uint256,uint256,uint256,uint160))" => {
+            format!(
+                "swap {} {} for {} @fee {}",
+                call.amount(5),
+                call.address(0),
+                call.address(1),
+                call.fee(2)
+            )
+        }
+        "swapExactTokensForTokens(uint256,uint256,address[],address,uint256)" => {
+            format!("swap {} tokens to {}", call.amount(0), call.address(3))
+        }
+        "swapExactETHForTokens(uint256,address[],address,uint256)" => {
+            format!("swap ETH for tokens to {}", call.address(2))
+        }
+        "execTransaction(address,uint256,bytes,uint8,uint256,uint256,uint256,address,address,bytes)" => {
+            with_nested(format!("Safe transaction to {}", call.address(0)), &nested)
+        }
+        "execute(address,uint256,bytes)" => {
+            with_nested(format!("execute on {}", call.address(0)), &nested)
+        }
+        _ => with_nested(name.to_string(), &nested),
+    }
+}
+
+/// `head: first, then second, ...`, or `head` alone.
+fn with_nested(head: String, nested: &[String]) -> String {
+    match nested.is_empty() {
+        true => head,
+        false => format!("{}: {}", head, nested.join(", then ")),
+    }
+}
+
+/// The words of a call, read as the types its signature declares.
+struct Call<'a> {
+    call: &'a DecodedCalldata,
+    labels: &'a Labels,
+}
+
+impl Call<'_> {
+    fn word(&self, index: usize) -> Option<Vec<u8>> {
+        let param = self.call.params.get(index)?;
+        match param.raw.len() == 64 {
+            true => Some(decode_hex(&param.raw)),
+            false => None,
+        }
+    }
+
+    /// The address's label, or its checksummed form shortened to `0x1234…abcd`.
+    fn address(&self, index: usize) -> String {
+        let Some(word) = self.word(index) else {
+            return "?".to_string();
+        };
+        let address = to_checksum(&word[12..]);
+        match self.labels.get(&address) {
+            Some(label) => label.to_string(),
+            None => format!("{}…{}", &address[..6], &address[38..]),
+        }
+    }
+
+    /// The amount in base units, `unlimited` for the max `uint256`.
+    fn amount(&self, index: usize) -> String {
+        match self.word(index).map(|w| U256::from_big_endian(&w)) {
+            Some(amount) if amount == U256::MAX => "unlimited".to_string(),
+            Some(amount) => amount.to_string(),
+            None => "?".to_string(),
+        }
+    }
+
+    /// A Uniswap fee tier, in hundredths of a basis point, as a percentage.
+    fn fee(&self, index: usize) -> String {
+        let Some(fee) = self.word(index).map(|w| U256::from_big_endian(&w)) else {
+            return "?".to_string();
+        };
+        if fee > U256::from(1_000_000) {
+            return fee.to_string();
+        }
+        let fee = fee.as_u64();
+        let fraction = format!("{:04}", fee % 10_000);
+        match fraction.trim_end_matches('0') {
+            "" => format!("{}%", fee / 10_000),
+            fraction => format!("{}.{}%", fee / 10_000, fraction),
+        }
+    }
+}
@@ -0,0 +1,77 @@
+/*
+cargo test test_abi -- --nocapture --test-threads=1
+*/
+#[cfg(all(test, feature = "ethers"))]
+mod test_abi {
+    use crate::builder::CalldataBuilder;
+    use crate::protocol::ProtocolRegistry;
+    use crate::Calldata;
+    use ethers::abi::parse_abi;
+    use primitive_types::H160;
+
+    fn set_owner() -> CalldataBuilder {
+        CalldataBuilder::new("setOwner(address)").address(H160::from_low_u64_be(0x1234))
+    }
+
+    #[test]
+    fn test_abi_exact() {
+        let hex = set_owner().build_hex().unwrap();
+        let heuristic = Calldata::new(&hex).decode();
+        assert_ne!(heuristic.params[0].types[0].solidity, "address");
+
+        let abi = parse_abi(&["function setOwner(address owner)"]).unwrap();
+        let calldata = Calldata::new(&hex).with_abi(&abi);
+        assert!(calldata.abi().is_some());
+        let root = calldata.decode();
+        assert_eq!(root.signature_guess, "setOwner(address)");
+        assert_eq!(root.params[0].types[0].solidity, "address");
+
+        // Calldata that doesn't decode as the function keeps its guesses.
+        let truncated = &hex[..hex.len() - 2];
+        let root = Calldata::new(truncated).with_abi(&abi).decode();
+        assert_eq!(root, Calldata::new(truncated).decode());
+    }
+
+    #[test]
+    fn test_abi_nested_fallback() {
+        let hex = CalldataBuilder::multicall_with_deadline(1_700_000_000u64, &[set_owner()])
+            .build_hex()
+            .unwrap();
+        let heuristic = Calldata::new(&hex).decode();
+
+        // Only the outer call is in the ABI: the nested one is left as guessed.
+        let abi = parse_abi(&["function multicall(uint256 deadline, bytes[] data)"]).unwrap();
+        let root = Calldata::new(&hex).with_abi(&abi).decode();
+        assert_eq!(root.signature_guess, "multicall(uint256,bytes[])");
+        let types = root
+            .params
+            .iter()
+            .take(5)
+            .map(|p| p.types[0].solidity.as_str())
+            .collect::<Vec<_>>();
+        assert_eq!(types, ["uint256", "bytes[]", "uint256", "bytes", "uint256"]);
+        assert_eq!(root.calls, heuristic.calls);
+
+        let abi = parse_abi(&[
+            "function multicall(uint256 deadline, bytes[] data)",
+            "function setOwner(address owner)",
+        ])
+        .unwrap();
+        let root = Calldata::new(&hex).with_abi(&abi).decode();
+        assert_eq!(root.calls[0].signature_guess, "setOwner(address)");
+        assert_eq!(root.calls[0].params[0].types[0].solidity, "address");
+    }
+
+    #[test]
+    fn test_abi_registry() {
+        // The multicall decoder takes the call: its nested calls are still typed by the ABI.
+        let hex = CalldataBuilder::multicall(&[set_owner()])
+            .build_hex()
+            .unwrap();
+        let abi = parse_abi(&["function setOwner(address owner)"]).unwrap();
+        let calldata = Calldata::new(&hex).with_abi(&abi);
+        let root = ProtocolRegistry::builtin().decode(&calldata).unwrap();
+        assert_eq!(root.calls[0].signature_guess, "setOwner(address)");
+        assert_eq!(root.calls[0].params[0].types[0].solidity, "address");
+    }
+}
@@ -0,0 +1,48 @@
+/*
+cargo test test_abiblob -- --nocapture --test-threads=1
+*/
+#[cfg(test)]
+mod test_abiblob {
+    use crate::abiblob::*;
+    use crate::builder::{encode_tuple, AbiValue};
+    use crate::corpus::TRANSFER;
+    use crate::decode_hex;
+    use crate::returndata::decode_returndata;
+    use primitive_types::H160;
+
+    fn usdt() -> H160 {
+        H160::from_slice(&decode_hex("dac17f958d2ee523a2206206994597c13d831ec7"))
+    }
+
+    #[test]
+    fn test_decode_abi_blob() {
+        let encoded = encode_tuple(&[
+            AbiValue::Address(usdt()),
+            AbiValue::Uint(1_000_000_000u64.into()),
+        ]);
+        let decoded = decode_abi_blob(&encoded).unwrap();
+        assert_eq!(decoded.types_guess, "(address,uint256)");
+        assert_eq!(decoded.params[1].value, "1000000000");
+        assert_eq!(decoded.params[1].span.start, 32);
+        assert!(decoded.calls.is_empty());
+        assert!(decode_abi_blob(&[]).unwrap().params.is_empty());
+    }
+
+    #[test]
+    fn test_nested_call() {
+        // A bridge-style payload: the target and the call to make on it.
+        let encoded = encode_tuple(&[
+            AbiValue::Address(usdt()),
+            AbiValue::Bytes(decode_hex(TRANSFER)),
+        ]);
+        let decoded = decode_abi_blob(&encoded).unwrap();
+        assert_eq!(decoded.calls.len(), 1);
+        assert_eq!(decoded.calls[0].selector, "a9059cbb");
+        // The call starts after the two head words and the length.
+        assert_eq!(decoded.calls[0].span.start, 96);
+
+        let returned = decode_returndata(&encoded).unwrap();
+        assert_eq!(returned.params, decoded.params);
+        assert_eq!(returned.returns_guess, decoded.types_guess);
+    }
+}
@@ -0,0 +1,67 @@
+/*
+cargo test test_advisor -- --nocapture --test-threads=1
+*/
+#[cfg(test)]
+mod test_advisor {
+    use crate::advisor::*;
+    use crate::corpus::{MULTICALL, TRANSFER};
+    use crate::Calldata;
+
+    fn report(hex: &str) -> CompressionReport {
+        let calldata = Calldata::new(hex);
+        advise(calldata.bytes(), &calldata.decode())
+    }
+
+    fn fits(report: &CompressionReport, path: &str) -> Option<String> {
+        report.suggestions.iter().find_map(|s| match &s.advice {
+            Advice::NarrowerType { path: p, fits, .. } if p == path => Some(fits.clone()),
+            _ => None,
+        })
+    }
+
+    #[test]
+    fn test_narrower_types() {
+        let transfer = report(TRANSFER);
+        assert_eq!(fits(&transfer, "params[0]").as_deref(), Some("address"));
+        assert_eq!(fits(&transfer, "params[1]").as_deref(), Some("uint32"));
+        assert_eq!(transfer.saved_bytes, 12 + 28);
+        assert_eq!(transfer.saved_gas, (12 + 28) * 4);
+
+        // Ticks are negative and fit an int24; the words holding the nested
+        // call aren't looked at twice.
+        let multicall = report(MULTICALL);
+        assert_eq!(
+            fits(&multicall, "calls[0].params[3]").as_deref(),
+            Some("int24")
+        );
+        assert!(fits(&multicall, "params[5]").is_none());
+        assert!(multicall.saved_bytes < multicall.bytes);
+    }
+
+    #[test]
+    fn test_zero_runs_and_repeats() {
+        let address = "000000000000000000000000d8da6bf26964af9d7eed9e10e3f4a2ff91e9a7a6";
+        let one = format!("{:064x}", 1);
+        let transfer_from = format!("0x23b872dd{}{}{}", address, address, one);
+        let repeated = report(&transfer_from);
+        let repeat = repeated
+            .suggestions
+            .iter()
+            .find(|s| matches!(s.advice, Advice::RepeatedAddress { .. }))
+            .unwrap();
+        assert_eq!((repeat.span.start, repeat.saved_bytes), (36, 31));
+        assert_eq!(repeat.saved_gas, 20 * 16 + 12 * 4 - 16);
+        // The repeat beats packing the same address.
+        assert_eq!(repeated.saved_bytes, 12 + 31 + 31);
+
+        let zeros = report(&format!("0xa9059cbb{}", "0".repeat(64 * 3)));
+        assert!(zeros.suggestions.contains(&Suggestion {
+            advice: Advice::ZeroRun { len: 96 },
+            span: crate::decoded::Span { start: 4, end: 100 },
+            saved_bytes: 94,
+            saved_gas: 96 * 4 - 20,
+        }));
+        // The run covers the params, whose narrower types aren't counted on top.
+        assert_eq!(zeros.saved_bytes, 94);
+    }
+}
@@ -0,0 +1,127 @@
+/*
+cargo test test_alert -- --nocapture --test-threads=1
+*/
+#[cfg(all(test, feature = "cli"))]
+mod test_alert {
+    use crate::alert::*;
+    use crate::corpus::{MULTICALL, TRANSFER};
+    use crate::rpc::DecodedTransaction;
+    use ethers::types::{Bytes, Transaction};
+    use std::io::{Read, Write};
+
+    const RULES: &str = r#"
+        [[rule]]
+        name = "usdt"
+        to = ["0xdAC17F958D2ee523a2206206994597C13D831ec7"]
+        query = "params[1] >= 1e9"
+
+        [[rule]]
+        name = "mint"
+        selectors = ["0x88316456"]
+        nested = true
+        query = "depth > 0 and selector == 0x88316456"
+    "#;
+
+    fn transaction(to: &str, input: &str) -> DecodedTransaction {
+        DecodedTransaction::from_transaction(&Transaction {
+            to: Some(to.parse().unwrap()),
+            input: input.parse::<Bytes>().unwrap(),
+            ..Default::default()
+        })
+        .unwrap()
+    }
+
+    #[test]
+    fn test_rules() {
+        let alerter = Alerter::new(Rules::from_toml(RULES).unwrap()).unwrap();
+        let prefilter = alerter.prefilter();
+        assert!(prefilter.selectors.is_empty() && prefilter.to.is_empty() && prefilter.nested);
+
+        let usdt = "0xdac17f958d2ee523a2206206994597c13d831ec7";
+        let alerts = alerter.check(&transaction(usdt, TRANSFER));
+        assert_eq!(alerts.len(), 1);
+        assert_eq!(
+            (alerts[0].rule.as_str(), alerts[0].calls.clone()),
+            ("usdt", vec![String::new()])
+        );
+        assert!(alerter
+            .check(&transaction(&format!("0x{}", "11".repeat(20)), TRANSFER))
+            .is_empty());
+
+        let alerts = alerter.check(&transaction(
+            "0xc36442b4a4522e871399cd717abdd847ab11fe88",
+            MULTICALL,
+        ));
+        assert_eq!(alerts.len(), 1);
+        assert_eq!(
+            (alerts[0].rule.as_str(), alerts[0].calls.clone()),
+            ("mint", vec!["calls[0]".to_string()])
+        );
+        assert_eq!(
+            alerter.rule(&alerts[0]).unwrap().selectors,
+            vec!["0x88316456"]
+        );
+
+        let bad = Rules::from_toml("[[rule]]\nname = \"x\"\nquery = \"depth >\"").unwrap();
+        assert_eq!(
+            Alerter::new(bad).unwrap_err(),
+            "rule `x`: expected a value after `depth`"
+        );
+        assert!(Rules::from_toml("[[rule]]\nwebhok = \"\"").is_err());
+    }
+
+    #[tokio::test]
+    async fn test_actions() {
+        let alerter = Alerter::new(Rules::from_toml(RULES).unwrap()).unwrap();
+        let alert = alerter
+            .check(&transaction(
+                "0xdac17f958d2ee523a2206206994597c13d831ec7",
+                TRANSFER,
+            ))
+            .remove(0);
+
+        let path =
+            std::env::temp_dir().join(format!("calldata-decoder-alert-{}", std::process::id()));
+        let command = [
+            "sh",
+            "-c",
+            "echo $CALLDATA_DECODER_RULE > \"$0\"; cat >> \"$0\"",
+        ]
+        .iter()
+        .map(|s| s.to_string())
+        .chain([path.display().to_string()])
+        .collect::<Vec<_>>();
+        assert!(run_command(&command, &alert)
+            .unwrap()
+            .wait()
+            .unwrap()
+            .success());
+        let written = std::fs::read_to_string(&path).unwrap();
+        let (rule, json) = written.split_once('\n').unwrap();
+        assert_eq!(rule, "usdt");
+        assert_eq!(json.trim(), serde_json::to_string(&alert).unwrap());
+        std::fs::remove_file(path).unwrap();
+
+        let listener = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+        let url = format!("http://{}/hook", listener.local_addr().unwrap());
+        let server = std::thread::spawn(move || {
+            let (mut stream, _) = listener.accept().unwrap();
+            let mut request = vec![0; 1 << 16];
+            let mut read = 0;
+            while !String::from_utf8_lossy(&request[..read]).contains("\"transaction\"") {
+                read += stream.read(&mut request[read..]).unwrap();
+            }
+            stream
+                .write_all(b"HTTP/1.1 204 No Content\r\n\r\n")
+                .unwrap();
+            String::from_utf8_lossy(&request[..read]).to_string()
+        });
+        post_webhook(&reqwest::Client::new(), &url, &alert)
+            .await
+            .unwrap();
+        let request = server.join().unwrap();
+        assert!(request.starts_with("POST /hook"));
+        assert!(request.contains("content-type: application/json"));
+        assert!(request.contains("\"rule\":\"usdt\""));
+    }
+}
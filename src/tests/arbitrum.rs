@@ -0,0 +1,182 @@
+/*
+cargo test test_arbitrum -- --nocapture --test-threads=1
+*/
+#[cfg(all(test, feature = "rollup"))]
+mod test_arbitrum {
+    use crate::arbitrum::*;
+    use crate::builder::CalldataBuilder;
+    use crate::decode_hex;
+    use crate::limits::Limits;
+    use primitive_types::{H160, U256};
+    use std::io::Write;
+
+    /// A legacy transfer to WETH, as in the raw transaction tests.
+    const LEGACY: &str = "f8a9078504a817c80082ea6094c02aaa39b223fe8d0a0e5c4f27ead9083c756cc280b844a9059cbb000000000000000000000000d8da6bf26964af9d7eed9e10e3f4a2ff91e9a7a60000000000000000000000000000000000000000000000000de0b6b3a764000025a028ef61340bd939bc2195fe537567866003e1a15d3c71ff63e1590620aa636276a067cbe9d8997f761aecb703304b3800ccf555c9f3dc64214b297fb1966a3b6d83";
+    const APPROVE: &str = "095ea7b3000000000000000000000000d8da6bf26964af9d7eed9e10e3f4a2ff91e9a7a6ffffffffffffffffffffffffffffffffffffffffffffffffffffffffffffffff";
+
+    /// An RLP string, long or short.
+    fn rlp_bytes(bytes: &[u8]) -> Vec<u8> {
+        match bytes.len() {
+            1 if bytes[0] < 0x80 => bytes.to_vec(),
+            len @ 0..=55 => [vec![0x80 + len as u8], bytes.to_vec()].concat(),
+            len @ 56..=255 => [vec![0xb8, len as u8], bytes.to_vec()].concat(),
+            len => [
+                vec![0xb9],
+                (len as u16).to_be_bytes().to_vec(),
+                bytes.to_vec(),
+            ]
+            .concat(),
+        }
+    }
+
+    fn brotli(data: &[u8]) -> Vec<u8> {
+        let mut out = vec![];
+        {
+            let mut writer = brotli::CompressorWriter::new(&mut out, 4096, 9, 22);
+            writer.write_all(data).unwrap();
+        }
+        out
+    }
+
+    /// A contract transaction calling `approve` on WETH.
+    fn contract_tx() -> Vec<u8> {
+        let word = |n: u64| {
+            let mut word = [0u8; 32];
+            word[24..].copy_from_slice(&n.to_be_bytes());
+            word.to_vec()
+        };
+        let mut to = [0u8; 32];
+        to[12..].copy_from_slice(&decode_hex("c02aaa39b223fe8d0a0e5c4f27ead9083c756cc2"));
+        [
+            vec![1],
+            word(100_000),
+            word(10),
+            to.to_vec(),
+            word(0),
+            decode_hex(APPROVE),
+        ]
+        .concat()
+    }
+
+    /// A batch message of the legacy transfer and the contract transaction.
+    fn batch_message() -> Vec<u8> {
+        let mut message = vec![3];
+        for nested in [[vec![4], decode_hex(LEGACY)].concat(), contract_tx()] {
+            message.extend((nested.len() as u64).to_be_bytes());
+            message.extend(nested);
+        }
+        message
+    }
+
+    fn segments() -> Vec<u8> {
+        [
+            rlp_bytes(&[3, 12]),
+            rlp_bytes(&[vec![0], batch_message()].concat()),
+            rlp_bytes(&[2]),
+            rlp_bytes(&[4, 1]),
+            rlp_bytes(&[vec![1], brotli(&[vec![4], decode_hex(LEGACY)].concat())].concat()),
+        ]
+        .concat()
+    }
+
+    fn post(data: &[u8]) -> Vec<u8> {
+        CalldataBuilder::new(BATCH_SIGNATURES[1])
+            .uint(42u64)
+            .bytes(data)
+            .uint(7u64)
+            .address(H160::zero())
+            .uint(0u64)
+            .uint(5u64)
+            .build()
+            .unwrap()
+    }
+
+    #[test]
+    fn test_arbitrum_batch() {
+        let calldata = post(&[vec![0], brotli(&segments())].concat());
+        let (sequence_number, data) = batch_data(&calldata).unwrap();
+        assert_eq!(sequence_number, U256::from(42));
+        assert_eq!(data[0], 0);
+
+        let batch = SequencerDecoder::new().decode_calldata(&calldata).unwrap();
+        assert_eq!(batch.format, BatchFormat::Brotli);
+        assert_eq!(batch.error, None);
+        assert_eq!(batch.segments.len(), 5);
+        assert_eq!(batch.segments[0], Segment::AdvanceTimestamp { by: 12 });
+        assert_eq!(batch.segments[2], Segment::DelayedMessages);
+        assert_eq!(batch.segments[3], Segment::AdvanceL1BlockNumber { by: 1 });
+
+        let Segment::L2Message {
+            compressed: false,
+            message: L2Message::Batch { messages },
+        } = &batch.segments[1]
+        else {
+            panic!("expected a batch message");
+        };
+        let L2Message::SignedTx { transaction } = &messages[0] else {
+            panic!("expected a signed transaction");
+        };
+        assert_eq!(transaction.transaction.nonce, 7);
+        let L2Message::UnsignedTx { transaction } = &messages[1] else {
+            panic!("expected a contract transaction");
+        };
+        assert_eq!(transaction.nonce, None);
+        assert_eq!(transaction.gas_limit, U256::from(100_000));
+        assert_eq!(
+            transaction.to,
+            Some(H160::from_slice(&decode_hex(
+                "c02aaa39b223fe8d0a0e5c4f27ead9083c756cc2"
+            )))
+        );
+        assert!(matches!(
+            batch.segments[4],
+            Segment::L2Message {
+                compressed: true,
+                ..
+            }
+        ));
+
+        let selectors = batch
+            .calls()
+            .iter()
+            .map(|c| c.selector.as_str())
+            .collect::<Vec<_>>();
+        assert_eq!(selectors, vec!["a9059cbb", "095ea7b3", "a9059cbb"]);
+    }
+
+    #[test]
+    fn test_arbitrum_errors() {
+        let decoder = SequencerDecoder::new();
+        assert!(decoder.decode_calldata(&decode_hex(APPROVE)).is_err());
+        assert_eq!(batch_data(&decode_hex(APPROVE)), None);
+
+        // Data elsewhere is recognised but not decoded.
+        let das = decoder.decode_calldata(&post(&[0x88, 1, 2])).unwrap();
+        assert_eq!((das.format, das.error), (BatchFormat::Das, None));
+        let blobs = decoder.decode_calldata(&post(&[0x50])).unwrap();
+        assert_eq!(blobs.format, BatchFormat::Blobs);
+        assert!(blobs.segments.is_empty());
+
+        // Segments before a bad one are kept.
+        let data = [segments(), rlp_bytes(&[9])].concat();
+        let batch = decoder.decode(U256::one(), &[vec![0], brotli(&data)].concat());
+        assert_eq!(batch.segments.len(), 5);
+        assert_eq!(
+            batch.error.as_deref(),
+            Some("segment 5: unknown segment kind 9")
+        );
+
+        // Batch messages nest no deeper than `max_depth`.
+        let nested = [vec![3], 1u64.to_be_bytes().to_vec(), vec![3]];
+        let data = rlp_bytes(&[vec![0], nested.concat()].concat());
+        let limits = Limits {
+            max_depth: 1,
+            ..Limits::default()
+        };
+        let shallow = SequencerDecoder::new().with_limits(limits);
+        let batch = shallow.decode(U256::one(), &[vec![0], brotli(&data)].concat());
+        assert!(batch.error.unwrap().contains("nested past 1"));
+        let batch = decoder.decode(U256::one(), &[vec![0], brotli(&data)].concat());
+        assert_eq!(batch.error, None);
+    }
+}
@@ -0,0 +1,44 @@
+/*
+cargo test test_arena -- --nocapture --test-threads=1
+*/
+#[cfg(test)]
+mod test_arena {
+    use crate::arena::DecodeArena;
+    use crate::Calldata;
+
+    const MULTICALL: &str = "0xac9650d800000000000000000000000000000000000000000000000000000000000000200000000000000000000000000000000000000000000000000000000000000002000000000000000000000000000000000000000000000000000000000000004000000000000000000000000000000000000000000000000000000000000001e0000000000000000000000000000000000000000000000000000000000000016488316456000000000000000000000000c011a73ee8576fb46f5e1c5751ca3b9fe0af2a6f000000000000000000000000c02aaa39b223fe8d0a0e5c4f27ead9083c756cc20000000000000000000000000000000000000000000000000000000000002710fffffffffffffffffffffffffffffffffffffffffffffffffffffffffffee530ffffffffffffffffffffffffffffffffffffffffffffffffffffffffffff1b18000000000000000000000000000000000000000000000000016345785d89fd6800000000000000000000000000000000000000000000000000007f73eca3063a000000000000000000000000000000000000000000000000016042b530ddaec600000000000000000000000000000000000000000000000000007e59f044bada000000000000000000000000f847e9d51989033b691b8be943f8e9e268f99b9e000000000000000000000000000000000000000000000000000000006377347700000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000412210e8a00000000000000000000000000000000000000000000000000000000";
+    const TRANSFER: &str = "0xa9059cbb000000000000000000000000c02aaa39b223fe8d0a0e5c4f27ead9083c756cc20000000000000000000000000000000000000000000000000de0b6b3a7640000";
+
+    #[test]
+    fn test_arena_matches_decode() {
+        let mut arena = DecodeArena::new();
+        for calldata in [MULTICALL, TRANSFER, "0xa9059cbb", "0xa9059cbb7b16"] {
+            let calldata = Calldata::new(calldata);
+            let root = arena.decode(&calldata);
+            assert_eq!(arena.call(root).to_decoded(), calldata.decode());
+        }
+        assert_eq!(arena.len(), 6);
+    }
+
+    #[test]
+    fn test_arena_refs() {
+        let mut arena = DecodeArena::new();
+        let root = arena.decode(&Calldata::new(MULTICALL));
+        let call = arena.call(root);
+        assert_eq!(call.selector(), "ac9650d8");
+        let nested = call.calls().collect::<Vec<_>>();
+        assert_eq!(nested.len(), 2);
+        assert_eq!(nested[0].selector(), "88316456");
+        let param = nested[0].params().next().unwrap();
+        assert_eq!(param.value(), "0xC011a73ee8576Fb46F5E1c5751cA3B9Fe0af2a6F");
+        assert_eq!(param.types()[0].0.solidity_name(), "address");
+
+        arena.clear();
+        assert!(arena.is_empty());
+        let root = arena.decode(&Calldata::new(TRANSFER));
+        assert_eq!(
+            arena.call(root).signature_guess(),
+            "unknown_a9059cbb(address,uint256)"
+        );
+    }
+}
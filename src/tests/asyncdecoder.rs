@@ -0,0 +1,103 @@
+/*
+cargo test test_asyncdecoder -- --nocapture --test-threads=1
+*/
+#[cfg(all(test, feature = "cli"))]
+mod test_asyncdecoder {
+    use crate::asyncdecoder::*;
+    use crate::corpus::TRANSFER;
+    use crate::decoder::Decoder;
+    use crate::signatures::selector_of;
+    use std::io::{Read, Write};
+    use std::net::TcpListener;
+
+    /// Answers `connections` requests with `body`, then stops listening.
+    fn directory(body: String, connections: usize) -> String {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let url = format!(
+            "http://{}/lookup?function={{selector}}",
+            listener.local_addr().unwrap()
+        );
+        std::thread::spawn(move || {
+            for stream in listener.incoming().take(connections) {
+                let mut stream = stream.unwrap();
+                let mut request = vec![0; 4096];
+                let mut read = 0;
+                while !request[..read].ends_with(b"\r\n\r\n") {
+                    read += stream.read(&mut request[read..]).unwrap();
+                }
+                let response = format!(
+                    "HTTP/1.1 200 OK\r\ncontent-length: {}\r\nconnection: close\r\n\r\n{}",
+                    body.len(),
+                    body
+                );
+                stream.write_all(response.as_bytes()).unwrap();
+            }
+        });
+        url
+    }
+
+    #[test]
+    fn test_directory_signatures() {
+        let openchain = serde_json::json!({
+            "ok": true,
+            "result": {"function": {"0xa9059cbb": [
+                {"name": "transfer(address,uint256)", "filtered": false},
+                {"name": "many_msg_babbage(bytes1)", "filtered": true},
+            ]}}
+        });
+        assert_eq!(
+            directory_signatures(&openchain),
+            vec!["transfer(address,uint256)", "many_msg_babbage(bytes1)"]
+        );
+        let fourbyte = serde_json::json!({"results": [
+            {"id": 2, "text_signature": "many_msg_babbage(bytes1)"},
+            {"id": 1, "text_signature": "transfer(address,uint256)"},
+        ]});
+        assert_eq!(
+            directory_signatures(&fourbyte),
+            vec!["transfer(address,uint256)", "many_msg_babbage(bytes1)"]
+        );
+        assert!(directory_signatures(&serde_json::json!({"ok": false})).is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_async_decode() {
+        let signature = "claimRewards(uint256)";
+        let selector = format!("0x{}", selector_of(signature));
+        let calldata = format!("{}{:064x}", selector, 7);
+        let body = serde_json::json!({
+            "result": {"function": {&selector: [{"name": signature}, {"name": "fake()"}]}}
+        });
+        let decoder = AsyncDecoder::new(Decoder::new())
+            .with_directory(SignatureDirectory::new(&directory(body.to_string(), 1)));
+
+        let enriched = decoder.decode(&calldata).await.unwrap();
+        assert_eq!(
+            enriched.decoded,
+            decoder.decoder().decode(&calldata).unwrap()
+        );
+        assert_eq!(enriched.selectors[0].signatures, vec![signature]);
+        assert!(enriched.errors.is_empty());
+        // Answered from the cache, the directory being gone.
+        assert_eq!(decoder.decode(&calldata).await.unwrap(), enriched);
+        // Known selectors aren't looked up.
+        let known = decoder.decode(TRANSFER).await.unwrap();
+        assert_eq!(
+            known.selectors[0].signatures,
+            vec!["transfer(address,uint256)"]
+        );
+
+        let closed = TcpListener::bind("127.0.0.1:0")
+            .unwrap()
+            .local_addr()
+            .unwrap();
+        let decoder = AsyncDecoder::new(Decoder::new()).with_directory(SignatureDirectory::new(
+            &format!("http://{}/{{selector}}", closed),
+        ));
+        let enriched = decoder.decode(&calldata).await.unwrap();
+        assert!(enriched.selectors[0].signatures.is_empty());
+        assert_eq!(enriched.errors.len(), 1);
+        assert!(enriched.errors[0].starts_with(&selector));
+        assert!(decoder.decode("0xzz").await.is_err());
+    }
+}
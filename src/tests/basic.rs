@@ -3,7 +3,14 @@ cargo test test_basic -- --nocapture --test-threads=1
 */
 #[cfg(test)]
 mod test_basic {
-    use crate::Calldata;
+    use crate::constants::Types;
+    use crate::decoded::{render_value, DecodeStatus};
+    use crate::type_guesser::ParamTypes;
+    use crate::{
+        check_hex, decode_hex, guess_param_type, word_u128, word_u64, Calldata, DecodeError,
+        HexError, Words,
+    };
+    use primitive_types::U256;
 
     /// 0x5d842074 // fn selector
     /// 000000000000000000000000000000000000000000000006c6b935b8bbd40000 // uint256
@@ -22,4 +29,265 @@ mod test_basic {
         let calldata = Calldata::new(calldata);
         calldata.print();
     }
+
+    #[test]
+    fn test_from_bytes() {
+        let hex = "a9059cbb000000000000000000000000c02aaa39b223fe8d0a0e5c4f27ead9083c756cc20000000000000000000000000000000000000000000000000de0b6b3a7640000";
+        let bytes = crate::decode_hex(hex);
+        let calldata = Calldata::from_bytes(&bytes);
+        assert_eq!(calldata.calldata, hex);
+        assert_eq!(
+            calldata.decode(),
+            Calldata::new(&format!("0x{}", hex)).decode()
+        );
+    }
+
+    #[test]
+    fn test_decode_hex() {
+        assert_eq!(decode_hex("0xA9059cBB"), vec![0xa9, 0x05, 0x9c, 0xbb]);
+        assert_eq!(decode_hex(" a9059cbb\n"), vec![0xa9, 0x05, 0x9c, 0xbb]);
+        // Stops at the first pair that isn't hex.
+        assert_eq!(decode_hex("0xa905zz00"), vec![0xa9, 0x05]);
+        assert_eq!(decode_hex("0xa9059"), vec![0xa9, 0x05]);
+    }
+
+    #[test]
+    fn test_check_hex() {
+        // Harmless differences are normalized.
+        let wrapped = "  0Xa9059cbb 0000\n0001\t";
+        assert_eq!(check_hex(wrapped), Ok(()));
+        assert_eq!(decode_hex(wrapped), decode_hex("0xa9059cbb00000001"));
+
+        // Positions are in the input as given, leading whitespace and prefix included.
+        assert_eq!(
+            check_hex(" 0xa9059cbb00g0"),
+            Err(HexError::InvalidChar {
+                found: 'g',
+                at: 13,
+                byte: 5
+            })
+        );
+        assert_eq!(
+            check_hex("0x0xa9059cbb"),
+            Err(HexError::InvalidChar {
+                found: 'x',
+                at: 3,
+                byte: 0
+            })
+        );
+        assert_eq!(
+            check_hex("0xa9059cbb0"),
+            Err(HexError::OddLength { digits: 9 })
+        );
+        assert_eq!(check_hex("0Xa905"), Err(HexError::TooShort { bytes: 2 }));
+        let e: String = check_hex("0xa9059cbé").unwrap_err().into();
+        assert_eq!(
+            e,
+            "invalid hex character 'é' at position 9 (calldata byte 3)"
+        );
+    }
+
+    #[test]
+    fn test_try_decode() {
+        assert_eq!(
+            Calldata::try_new("0xa9059cbb0").unwrap_err(),
+            DecodeError::InvalidHex(HexError::OddLength { digits: 9 })
+        );
+        assert_eq!(
+            Calldata::try_new("0xa905").unwrap_err(),
+            DecodeError::TooShort { bytes: 2 }
+        );
+        assert_eq!(
+            Calldata::try_from_bytes(&[0xa9]).unwrap_err(),
+            DecodeError::TooShort { bytes: 1 }
+        );
+
+        let transfer = "0xa9059cbb0000000000000000000000000000000000000000000000000000000000000001";
+        let calldata = Calldata::try_new(transfer).unwrap();
+        assert_eq!(calldata.try_decode(), Ok(calldata.decode()));
+
+        // A length past the end of the calldata.
+        let truncated = "0xac9650d80000000000000000000000000000000000000000000000000000000040000004aabbccdd00000000000000000000000000000000000000000000000000000000";
+        assert_eq!(
+            Calldata::try_new(truncated).unwrap().try_decode(),
+            Err(DecodeError::TruncatedDynamicData {
+                at: 4,
+                declared: (1 << 30) + 4,
+                remaining: 32
+            })
+        );
+        // An amount that happens to be a multiple of 32 past the params isn't an offset.
+        let amount = "0xa9059cbb000000000000000000000000d8da6bf26964af9d7eed9e10e3f4a2ff91e9a7a60000000000000000000000000000000000000000000000000000000000000200";
+        let calldata = Calldata::try_new(amount).unwrap();
+        assert_eq!(calldata.try_decode(), Ok(calldata.decode()));
+
+        // An offset 512 bytes into 160 bytes of params, next to one to a call.
+        let offset = "0xac9650d800000000000000000000000000000000000000000000000000000000000000400000000000000000000000000000000000000000000000000000000000000200000000000000000000000000000000000000000000000000000000000000002412345678000000000000000000000000000000000000000000000000000000000000000500000000000000000000000000000000000000000000000000000000";
+        let e = Calldata::new(offset).try_decode().unwrap_err();
+        assert_eq!(
+            e,
+            DecodeError::OffsetOutOfBounds {
+                at: 36,
+                offset: 512,
+                len: 160
+            }
+        );
+        assert_eq!(
+            String::from(e),
+            "offset 512 at byte 36 points past the 160 bytes of params"
+        );
+        assert_eq!(
+            Calldata::new("0x1234567800000000000000000000000000000000000000000000000000000000000000400000000000000000000000000000000000000000000000000000000000000005").try_decode(),
+            Err(DecodeError::OffsetOutOfBounds {
+                at: 4,
+                offset: 64,
+                len: 64
+            })
+        );
+        let tail = format!("{}abcdef", transfer);
+        assert_eq!(
+            Calldata::new(&tail).try_decode(),
+            Err(DecodeError::TrailingBytes { at: 36, len: 3 })
+        );
+
+        // Irregular but whole calldata still decodes.
+        let calldata = Calldata::new("0xa9059cbb00000000000000000000000000000000000000000000000000000000000000010000000000000000000000000000000000000000000000000000000000000024123456780000000000000000000000000000000000000000000000000000000000000005000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000020");
+        assert!(!calldata.decode().warnings.is_empty());
+        assert!(calldata.try_decode().is_ok());
+    }
+
+    #[test]
+    fn test_case_insensitive() {
+        let hex = "a9059cbb000000000000000000000000c02aaa39b223fe8d0a0e5c4f27ead9083c756cc20000000000000000000000000000000000000000000000000de0b6b3a7640000";
+        let upper = Calldata::new(&format!("0x{}", hex.to_uppercase()));
+        assert_eq!(upper.calldata, hex);
+        assert_eq!(upper.decode(), Calldata::new(hex).decode());
+    }
+
+    #[test]
+    fn test_short_inputs() {
+        // No selector: whatever bytes there are, and nothing to decode.
+        for hex in ["0x", "0xde", "0xdead", "0xdeadbe"] {
+            let root = Calldata::new(hex).decode();
+            assert_eq!(root.selector, hex[2..]);
+            assert!(root.params.is_empty());
+            assert_eq!(root.status(), DecodeStatus::Invalid);
+        }
+
+        // Selector only.
+        let root = Calldata::new("0xa9059cbb").decode();
+        assert_eq!(root.selector, "a9059cbb");
+        assert!(root.params.is_empty());
+        assert_eq!(root.status(), DecodeStatus::Decoded);
+
+        // Selector and a sub-word tail, left as bytes.
+        for tail in [
+            "01",
+            "7b16",
+            "00000000000000000000000000000000000000000000000000000000000001",
+        ] {
+            let root = Calldata::new(&format!("0xa9059cbb{}", tail)).decode();
+            assert_eq!(root.params.len(), 1);
+            assert_eq!(root.params[0].raw, tail);
+            assert_eq!(root.params[0].value, format!("0x{}", tail));
+            assert_eq!(root.params[0].types[0].kind, Types::Bytes);
+            assert_eq!(root.status(), DecodeStatus::Partial);
+        }
+
+        // A full word then a tail: only the tail is left as bytes.
+        let word = "0000000000000000000000000000000000000000000000000000000000000001";
+        let root = Calldata::new(&format!("0xa9059cbb{}ff", word)).decode();
+        assert_eq!(root.params[0].value, "1");
+        assert_eq!(root.params[1].value, "0xff");
+        assert_eq!(root.params[1].types[0].kind, Types::Bytes);
+    }
+
+    #[test]
+    fn test_negative_ints() {
+        let word = "fffffffffffffffffffffffffffffffffffffffffffffffffffffffffffee530";
+        assert_eq!(guess_param_type(word).types()[0], Types::Int);
+        assert_eq!(
+            guess_param_type(&word.to_uppercase()).types()[0],
+            Types::Int
+        );
+        let root = Calldata::new(&format!("0x12345678{}", word)).decode();
+        assert_eq!(root.params[0].value, "-72400");
+    }
+
+    #[test]
+    fn test_words() {
+        // First word shares its 32 bytes with the selector.
+        let words = Words::new((0..96).collect(), 28);
+        assert_eq!(words.len(), 4);
+        assert_eq!(words.get(0).unwrap().len(), 28);
+        assert_eq!(words.get(1).unwrap()[0], 28);
+        assert_eq!(words.get(3), Some(&[92, 93, 94, 95][..]));
+        assert!(words.get(4).is_none());
+    }
+
+    #[test]
+    fn test_primitive_fast_paths() {
+        let mut word = [0u8; 32];
+        word[31] = 0x20;
+        assert_eq!(word_u64(&word), Some(32));
+        assert_eq!(word_u128(&word), Some(32));
+        word[24] = 0x80;
+        assert_eq!(word_u64(&word), Some(0x8000_0000_0000_0020));
+        word[23] = 1;
+        assert_eq!(word_u64(&word), None);
+        assert_eq!(word_u128(&word), Some(0x01_8000_0000_0000_0020));
+        word[15] = 1;
+        assert_eq!(word_u128(&word), None);
+        assert_eq!(word_u64(&[1, 0]), Some(256));
+        assert_eq!(word_u64(&[0; 33]), None);
+
+        // Decimal values agree with U256 on both sides of the u128 boundary.
+        let uint = ParamTypes::new(vec![Types::Uint]);
+        let int = ParamTypes::new(vec![Types::Int]);
+        for raw in [
+            "0000000000000000000000000000000000000000000000000000000000000000",
+            "00000000000000000000000000000000ffffffffffffffffffffffffffffffff",
+            "0000000000000000000000000000000100000000000000000000000000000000",
+            "ffffffffffffffffffffffffffffffff80000000000000000000000000000000",
+            "ffffffffffffffffffffffffffffffff7fffffffffffffffffffffffffffffff",
+            "8000000000000000000000000000000000000000000000000000000000000000",
+        ] {
+            let v = U256::from_str_radix(raw, 16).unwrap();
+            assert_eq!(render_value(&uint, raw), v.to_string());
+            let signed = match v.bit(255) {
+                true => format!("-{}", (!v).overflowing_add(U256::one()).0),
+                false => v.to_string(),
+            };
+            assert_eq!(render_value(&int, raw), signed);
+        }
+    }
+
+    #[test]
+    fn test_all_selectors() {
+        use crate::corpus::MULTICALL;
+        use crate::signatures::{find_selectors, Signatures};
+
+        let calldata = Calldata::new(MULTICALL);
+        let found = calldata.all_selectors();
+        let selectors = found.iter().map(|f| f.selector).collect::<Vec<_>>();
+        assert_eq!(
+            selectors,
+            vec![
+                [0xac, 0x96, 0x50, 0xd8],
+                [0x88, 0x31, 0x64, 0x56],
+                [0x12, 0x21, 0x0e, 0x8a]
+            ]
+        );
+        // Where the decode tree has them.
+        let hits = find_selectors(&calldata.decode(), &Signatures::new());
+        let sites = hits.iter().map(|h| (h.depth, h.offset)).collect::<Vec<_>>();
+        let found_sites = found
+            .iter()
+            .map(|f| (f.depth, f.offset))
+            .collect::<Vec<_>>();
+        assert_eq!(found_sites, sites);
+        assert!(found.iter().any(|f| f.selector == [0x12, 0x21, 0x0e, 0x8a]));
+
+        assert!(Calldata::new("0xa905").all_selectors().is_empty());
+    }
 }
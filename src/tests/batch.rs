@@ -0,0 +1,114 @@
+/*
+cargo test --features batch test_batch -- --nocapture --test-threads=1
+*/
+#[cfg(all(test, feature = "batch"))]
+mod test_batch {
+    use crate::batch::*;
+    use crate::Calldata;
+    use std::path::PathBuf;
+
+    fn temp_file(name: &str, bytes: &[u8]) -> PathBuf {
+        let path =
+            std::env::temp_dir().join(format!("calldata-decoder-{}-{}", name, std::process::id()));
+        std::fs::write(&path, bytes).unwrap();
+        path
+    }
+
+    const TRANSFER: &str = "0xa9059cbb000000000000000000000000c02aaa39b223fe8d0a0e5c4f27ead9083c756cc20000000000000000000000000000000000000000000000000de0b6b3a7640000";
+    const APPROVE: &str = "0x095ea7b3000000000000000000000000c02aaa39b223fe8d0a0e5c4f27ead9083c756cc2ffffffffffffffffffffffffffffffffffffffffffffffffffffffffffffffff";
+
+    #[test]
+    fn test_decode_batch() {
+        let calldatas = vec![TRANSFER, "0xzz", APPROVE, "0xa905"];
+        let results = decode_batch(&calldatas);
+        assert_eq!(results.len(), 4);
+        assert_eq!(
+            results[0].as_ref().unwrap(),
+            &Calldata::new(TRANSFER).decode()
+        );
+        assert!(results[1].as_ref().unwrap_err().contains("invalid hex"));
+        assert_eq!(results[2].as_ref().unwrap().selector, "095ea7b3");
+        assert!(results[3].is_err());
+    }
+
+    #[test]
+    fn test_decode_batch_bounded() {
+        let calldatas = (0..25).map(|i| match i % 2 {
+            0 => TRANSFER.to_string(),
+            _ => APPROVE.to_string(),
+        });
+        let mut seen = vec![];
+        decode_batch_bounded(calldatas, 4, |i, result| {
+            seen.push((i, result.unwrap().selector));
+        });
+        assert_eq!(seen.len(), 25);
+        assert!(seen.iter().enumerate().all(|(n, (i, _))| n == *i));
+        assert_eq!(seen[24].1, "a9059cbb");
+        assert_eq!(seen[23].1, "095ea7b3");
+    }
+
+    #[test]
+    fn test_mapped_hex_lines() {
+        let text = format!(
+            "{}\n\n# comment\n0xzz\n0x{}  # approve\n",
+            TRANSFER,
+            &APPROVE[2..].to_uppercase()
+        );
+        let path = temp_file("lines", text.as_bytes());
+        let file = CalldataFile::open(&path, RecordFormat::HexLines).unwrap();
+        assert_eq!(file.records().count(), 3);
+
+        let mut seen = vec![];
+        file.decode(2, |i, result| {
+            seen.push((i, result.map(|(r, d)| (r.to_hex(), d))))
+        });
+        assert_eq!(seen.len(), 3);
+        let (hex, decoded) = seen[0].1.as_ref().unwrap();
+        assert_eq!(
+            (hex.as_str(), decoded),
+            (TRANSFER, &Calldata::new(TRANSFER).decode())
+        );
+        assert!(seen[1].1.as_ref().unwrap_err().contains("invalid hex"));
+        assert_eq!(seen[2].1.as_ref().unwrap().0, APPROVE);
+        std::fs::remove_file(path).unwrap();
+    }
+
+    #[test]
+    fn test_mapped_length_prefixed() {
+        let mut bytes = vec![];
+        for record in [
+            &crate::decode_hex(TRANSFER)[..],
+            &[0xa9, 0x05],
+        ] {
+            bytes.extend((record.len() as u32).to_be_bytes());
+            bytes.extend(record);
+        }
+        bytes.extend([0, 0, 0, 9, 1, 2]);
+        let path = temp_file("prefixed", &bytes);
+        let file = CalldataFile::open(&path, RecordFormat::LengthPrefixed).unwrap();
+        let records = file.records().collect::<Vec<_>>();
+        assert_eq!(records.len(), 3);
+        assert_eq!(
+            records[0]
+                .as_ref()
+                .unwrap()
+                .decode(Default::default(), Default::default()),
+            Ok(Calldata::new(TRANSFER).decode())
+        );
+        assert!(records[1]
+            .as_ref()
+            .unwrap()
+            .decode(Default::default(), Default::default())
+            .is_err());
+        assert_eq!(
+            records[2],
+            Err("record at byte 78 is truncated".to_string())
+        );
+
+        let empty = temp_file("empty", &[]);
+        let file = CalldataFile::open(&empty, RecordFormat::LengthPrefixed).unwrap();
+        assert_eq!(file.records().count(), 0);
+        std::fs::remove_file(path).unwrap();
+        std::fs::remove_file(empty).unwrap();
+    }
+}
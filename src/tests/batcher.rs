@@ -0,0 +1,254 @@
+/*
+cargo test test_batcher -- --nocapture --test-threads=1
+*/
+#[cfg(all(test, feature = "rollup"))]
+mod test_batcher {
+    use crate::batcher::*;
+    use crate::decode_hex;
+    use primitive_types::{H160, U256};
+    use std::io::Write;
+
+    /// A legacy transfer to WETH, as in the raw transaction tests.
+    const LEGACY: &str = "f8a9078504a817c80082ea6094c02aaa39b223fe8d0a0e5c4f27ead9083c756cc280b844a9059cbb000000000000000000000000d8da6bf26964af9d7eed9e10e3f4a2ff91e9a7a60000000000000000000000000000000000000000000000000de0b6b3a764000025a028ef61340bd939bc2195fe537567866003e1a15d3c71ff63e1590620aa636276a067cbe9d8997f761aecb703304b3800ccf555c9f3dc64214b297fb1966a3b6d83";
+    const TRANSFER: &str = "a9059cbb000000000000000000000000d8da6bf26964af9d7eed9e10e3f4a2ff91e9a7a60000000000000000000000000000000000000000000000000de0b6b3a7640000";
+
+    fn rlp_header(len: usize, short: u8) -> Vec<u8> {
+        match len {
+            0..=55 => vec![short + len as u8],
+            _ => {
+                let size = len.to_be_bytes();
+                let size = &size[size.iter().position(|b| *b != 0).unwrap()..];
+                [&[short + 55 + size.len() as u8][..], size].concat()
+            }
+        }
+    }
+
+    fn rlp_bytes(bytes: &[u8]) -> Vec<u8> {
+        match bytes {
+            [b] if *b < 0x80 => vec![*b],
+            _ => [rlp_header(bytes.len(), 0x80), bytes.to_vec()].concat(),
+        }
+    }
+
+    fn rlp_list(items: &[Vec<u8>]) -> Vec<u8> {
+        let payload = items.concat();
+        [rlp_header(payload.len(), 0xc0), payload].concat()
+    }
+
+    fn rlp_uint(n: u64) -> Vec<u8> {
+        let bytes = n.to_be_bytes();
+        let start = bytes.iter().position(|b| *b != 0).unwrap_or(8);
+        rlp_bytes(&bytes[start..])
+    }
+
+    /// A block of epoch 7 holding the legacy transfer.
+    fn singular() -> Vec<u8> {
+        let content = rlp_list(&[
+            rlp_bytes(&[0x11; 32]),
+            rlp_uint(7),
+            rlp_bytes(&[0x22; 32]),
+            rlp_uint(1_700_000_000),
+            rlp_list(&[rlp_bytes(&decode_hex(LEGACY))]),
+        ]);
+        [vec![0], content].concat()
+    }
+
+    /// Two blocks, the first with a legacy transfer, the second with a
+    /// dynamic fee contract creation.
+    fn span() -> Vec<u8> {
+        let mut content = vec![100, 7];
+        content.extend([0x33; 20]);
+        content.extend([0x44; 20]);
+        content.extend([2, 0b01, 1, 1]);
+        // Contract creation bits, y parity bits, then the signatures.
+        content.extend([0b10, 0b00]);
+        content.extend([0x55; 128]);
+        content.extend(decode_hex("c02aaa39b223fe8d0a0e5c4f27ead9083c756cc2"));
+        content.extend(rlp_list(&[
+            rlp_uint(0),
+            rlp_uint(1_000_000_000),
+            rlp_bytes(&decode_hex(TRANSFER)),
+        ]));
+        content.push(2);
+        content.extend(rlp_list(&[
+            rlp_uint(1),
+            rlp_uint(2),
+            rlp_uint(1_000),
+            rlp_bytes(&[0x60, 0x80, 0x60, 0x40, 0x52]),
+            rlp_list(&[]),
+        ]));
+        // Nonces, gas limits (300, a two byte varint) and the protected bit.
+        content.extend([3, 4, 0xac, 0x02, 0xac, 0x02, 0b1]);
+        [vec![1], content].concat()
+    }
+
+    fn zlib(data: &[u8]) -> Vec<u8> {
+        let mut encoder =
+            flate2::write::ZlibEncoder::new(Vec::new(), flate2::Compression::default());
+        encoder.write_all(data).unwrap();
+        encoder.finish().unwrap()
+    }
+
+    fn brotli(data: &[u8]) -> Vec<u8> {
+        let mut out = vec![1];
+        {
+            let mut writer = brotli::CompressorWriter::new(&mut out, 4096, 9, 22);
+            writer.write_all(data).unwrap();
+        }
+        out
+    }
+
+    /// `channel` split in two frames, each in its own payload.
+    fn payloads(id: u8, channel: &[u8]) -> [Vec<u8>; 2] {
+        let frame = |number: u16, data: &[u8], is_last: bool| {
+            let mut frame = vec![0];
+            frame.extend([id; 16]);
+            frame.extend(number.to_be_bytes());
+            frame.extend((data.len() as u32).to_be_bytes());
+            frame.extend(data);
+            frame.push(is_last as u8);
+            frame
+        };
+        let (first, second) = channel.split_at(channel.len() / 2);
+        [frame(0, first, false), frame(1, second, true)]
+    }
+
+    fn channel_data() -> Vec<u8> {
+        [rlp_bytes(&singular()), rlp_bytes(&span())].concat()
+    }
+
+    #[test]
+    fn test_batcher_channels() {
+        let [first, second] = payloads(0xaa, &zlib(&channel_data()));
+        let mut decoder = BatcherDecoder::new();
+        assert_eq!(decoder.push(&first).unwrap(), 1);
+        let pending = decoder.channels();
+        assert!(!pending[0].complete);
+        assert_eq!(pending[0].error.as_deref(), Some("no last frame yet"));
+
+        assert_eq!(decoder.push(&second).unwrap(), 1);
+        // A frame seen again is ignored.
+        decoder.push(&second).unwrap();
+        let channels = decoder.channels();
+        assert_eq!(channels.len(), 1);
+        let channel = &channels[0];
+        assert_eq!(channel.id, "aa".repeat(16));
+        assert_eq!((channel.frames, channel.complete), (2, true));
+        assert_eq!(channel.compression, Some(Compression::Zlib));
+        assert_eq!(channel.error, None);
+        assert_eq!(channel.batches.len(), 2);
+
+        let Batch::Singular(singular) = &channel.batches[0] else {
+            panic!("expected a singular batch");
+        };
+        assert_eq!(
+            (singular.epoch_number, singular.timestamp),
+            (7, 1_700_000_000)
+        );
+        assert_eq!(singular.transactions[0].transaction.nonce, 7);
+        assert_eq!(channel.batches[0].calls()[0].selector, "a9059cbb");
+
+        let Batch::Span(span) = &channel.batches[1] else {
+            panic!("expected a span batch");
+        };
+        assert_eq!((span.rel_timestamp, span.l1_origin_num), (100, 7));
+        assert_eq!(span.parent_check, "33".repeat(20));
+        assert_eq!(span.origin_bits, vec![true, false]);
+        assert_eq!(span.block_tx_counts, vec![1, 1]);
+        let [transfer, creation] = &span.transactions[..] else {
+            panic!("expected 2 transactions");
+        };
+        assert_eq!((transfer.block, transfer.tx_type), (0, 0));
+        assert_eq!(
+            transfer.to,
+            Some(H160::from_slice(&decode_hex(
+                "c02aaa39b223fe8d0a0e5c4f27ead9083c756cc2"
+            )))
+        );
+        assert_eq!(transfer.gas_price, Some(U256::from(1_000_000_000u64)));
+        assert_eq!((transfer.nonce, transfer.gas_limit), (3, 300));
+        assert_eq!(
+            transfer.decoded.as_ref().unwrap().params[1].value,
+            "1000000000000000000"
+        );
+        assert_eq!(
+            (creation.block, creation.tx_type, creation.to),
+            (1, 2, None)
+        );
+        assert_eq!(creation.max_fee_per_gas, Some(U256::from(1_000)));
+        assert_eq!(creation.decoded, None);
+        assert_eq!(channel.batches[1].calls().len(), 1);
+
+        // Fjord channels are brotli, and a single payload can hold a channel.
+        let [first, second] = payloads(0xbb, &brotli(&channel_data()));
+        let payload = [first, second[1..].to_vec()].concat();
+        let channels = decode_batcher_payload(&payload).unwrap();
+        assert_eq!(channels[0].compression, Some(Compression::Brotli));
+        assert_eq!(channels[0].batches, channel.batches);
+    }
+
+    #[test]
+    fn test_batcher_errors() {
+        assert!(decode_batcher_payload(&decode_hex(TRANSFER)).is_err());
+
+        // Batches decoded before a bad one are kept.
+        let channel = [channel_data(), rlp_bytes(&[9, 1, 2])].concat();
+        let [first, second] = payloads(0xcc, &zlib(&channel));
+        let mut decoder = BatcherDecoder::new();
+        decoder.push(&first).unwrap();
+        decoder.push(&second).unwrap();
+        let channels = decoder.channels();
+        assert_eq!(channels[0].batches.len(), 2);
+        assert_eq!(
+            channels[0].error.as_deref(),
+            Some("batch 2: unknown batch version 9")
+        );
+
+        let [first, second] = payloads(0xdd, &[0x02, 0x00]);
+        let mut decoder = BatcherDecoder::new();
+        decoder.push(&second).unwrap();
+        assert_eq!(
+            decoder.channels()[0].error.as_deref(),
+            Some("1 of 2 frames received")
+        );
+        decoder.push(&first).unwrap();
+        assert!(decoder.channels()[0]
+            .error
+            .as_ref()
+            .unwrap()
+            .contains("compression"));
+
+        // Counts past what the bytes can hold are rejected before allocating.
+        let mut huge = vec![1, 100, 7];
+        huge.extend([0; 40]);
+        huge.extend([0xff, 0xff, 0xff, 0x7f]);
+        let [first, second] = payloads(0xee, &zlib(&rlp_bytes(&huge)));
+        let channels = decode_batcher_payload(&[first, second[1..].to_vec()].concat()).unwrap();
+        assert!(channels[0].batches.is_empty());
+        assert!(channels[0]
+            .error
+            .as_ref()
+            .unwrap()
+            .contains("out of bounds"));
+    }
+
+    #[test]
+    fn test_batcher_inbox() {
+        let address = |hex: &str| H160::from_slice(&decode_hex(hex));
+        let base = address("ff00000000000000000000000000000000008453");
+        assert_eq!(batch_inbox_name(&base), Some("Base"));
+        assert!(is_batch_inbox(&base));
+        assert!(is_batch_inbox(&address(
+            "ff00000000000000000000000000000000007777"
+        )));
+        assert!(!is_batch_inbox(&address(
+            "ff000000000000000000000000000000000000ab"
+        )));
+        assert!(!is_batch_inbox(&address(
+            "ff00000000000000000000000000000000000000"
+        )));
+        assert!(!is_batch_inbox(&address(
+            "c02aaa39b223fe8d0a0e5c4f27ead9083c756cc2"
+        )));
+    }
+}
@@ -0,0 +1,120 @@
+/*
+cargo test test_bind -- --nocapture --test-threads=1
+*/
+#[cfg(test)]
+mod test_bind {
+    use crate::bind::*;
+    use crate::corpus::TRANSFER;
+    use crate::Calldata;
+    use primitive_types::{H160, U256};
+
+    #[test]
+    fn test_bind_fields() {
+        let call = Calldata::new(TRANSFER).decode();
+        let to: H160 = field(&call, "to", 0, SolType::Address).unwrap();
+        assert_eq!(
+            to,
+            "0xdac17f958d2ee523a2206206994597c13d831ec7"
+                .parse()
+                .unwrap()
+        );
+        let amount: u64 = field(&call, "amount", 1, SolType::Uint(256)).unwrap();
+        assert_eq!(amount, 1_000_000_000);
+        assert_eq!(
+            field::<U256>(&call, "amount", 1, SolType::Uint(32)).unwrap(),
+            U256::from(amount)
+        );
+
+        // The word doesn't hold the declared type, or the field's type can't hold it.
+        let e = field::<U256>(&call, "amount", 1, SolType::Uint(24)).unwrap_err();
+        assert!(matches!(
+            e,
+            BindError::Mismatch {
+                index: 1,
+                expected: SolType::Uint(24),
+                ..
+            }
+        ));
+        assert!(
+            e.to_string().starts_with("field `amount`: params[1] "),
+            "{}",
+            e
+        );
+        let e = field::<u8>(&call, "amount", 1, SolType::Uint(256)).unwrap_err();
+        assert!(
+            e.to_string().ends_with("the value doesn't fit in a u8"),
+            "{}",
+            e
+        );
+        let e = field::<bool>(&call, "to", 0, SolType::Address).unwrap_err();
+        assert!(
+            e.to_string().ends_with("a bool field can't hold a address"),
+            "{}",
+            e
+        );
+        assert_eq!(
+            field::<U256>(&call, "deadline", 2, SolType::Uint(256)),
+            Err(BindError::MissingParam {
+                field: "deadline",
+                index: 2,
+                params: 2
+            })
+        );
+        assert_eq!(check_selector(&call, Some("a9059cbb")), Ok(()));
+        assert!(check_selector(&call, Some("095ea7b3")).is_err());
+
+        // Negative ints are sign extended from their type's top bit.
+        let mut word = [0xff; 32];
+        word[31] = 0xfe;
+        assert_eq!(SolType::Int(8).check(&word), Ok(()));
+        assert_eq!(i8::from_word(&word, SolType::Int(8)), Ok(-2));
+        word[30] = 0x7f;
+        assert!(SolType::Int(16).check(&word).is_err());
+        assert!(SolType::Bool.check(&word).is_err());
+        assert!(SolType::FixedBytes(4).check(&[0x11; 32]).is_err());
+    }
+
+    #[cfg(feature = "derive")]
+    #[test]
+    fn test_bind_derive() {
+        use crate::corpus::MULTICALL;
+
+        #[derive(Debug, PartialEq, FromCalldata)]
+        #[calldata(signature = "transfer(address,uint)")]
+        struct Transfer {
+            to: H160,
+            amount: U256,
+        }
+
+        /// `mint` of the multicall's first call, only some params bound.
+        #[derive(Debug, PartialEq, FromCalldata)]
+        #[calldata(selector = "0x88316456")]
+        struct Mint {
+            #[calldata(ty = "address")]
+            token0: H160,
+            #[calldata(ty = "uint24", index = 2)]
+            fee: u32,
+            #[calldata(ty = "int24", index = 3)]
+            tick_lower: i32,
+        }
+
+        #[derive(Debug, PartialEq, FromCalldata)]
+        #[calldata(signature = "refundETH()")]
+        struct RefundEth {}
+
+        assert_eq!(Transfer::SELECTOR, Some("a9059cbb"));
+        let transfer = Transfer::from_call(&Calldata::new(TRANSFER).decode()).unwrap();
+        assert_eq!(transfer.amount, U256::from(1_000_000_000));
+
+        let multicall = Calldata::new(MULTICALL).decode();
+        assert!(matches!(
+            Transfer::from_call(&multicall),
+            Err(BindError::Selector { .. })
+        ));
+        assert_eq!(RefundEth::find(&multicall), vec![RefundEth {}]);
+        let mints = Mint::find(&multicall);
+        assert_eq!(mints.len(), 1);
+        assert_eq!(mints[0].fee, 10_000);
+        assert_eq!(mints[0].tick_lower, -72_400);
+    }
+}
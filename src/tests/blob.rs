@@ -0,0 +1,142 @@
+/*
+cargo test test_blob -- --nocapture --test-threads=1
+*/
+#[cfg(test)]
+mod test_blob {
+    use crate::blob::*;
+    use crate::decode_hex;
+
+    const TRANSFER: &str = "a9059cbb000000000000000000000000d8da6bf26964af9d7eed9e10e3f4a2ff91e9a7a60000000000000000000000000000000000000000000000000de0b6b3a7640000";
+    const APPROVE: &str = "095ea7b3000000000000000000000000d8da6bf26964af9d7eed9e10e3f4a2ff91e9a7a6ffffffffffffffffffffffffffffffffffffffffffffffffffffffffffffffff";
+    /// A legacy transfer to WETH, as in the raw transaction tests.
+    const LEGACY: &str = "f8a9078504a817c80082ea6094c02aaa39b223fe8d0a0e5c4f27ead9083c756cc280b844a9059cbb000000000000000000000000d8da6bf26964af9d7eed9e10e3f4a2ff91e9a7a60000000000000000000000000000000000000000000000000de0b6b3a764000025a028ef61340bd939bc2195fe537567866003e1a15d3c71ff63e1590620aa636276a067cbe9d8997f761aecb703304b3800ccf555c9f3dc64214b297fb1966a3b6d83";
+
+    /// Payload bytes that use all 8 bits, to exercise the 6-bit packing.
+    fn payload(len: usize) -> Vec<u8> {
+        (0..len).map(|i| (i * 131 % 251) as u8 | 0x80).collect()
+    }
+
+    /// `payload` in field elements of 31 bytes behind a zero byte.
+    fn padded(payload: &[u8]) -> Vec<u8> {
+        let mut blob = vec![0; BLOB_SIZE];
+        for (i, chunk) in payload.chunks(31).enumerate() {
+            blob[i * 32 + 1..i * 32 + 1 + chunk.len()].copy_from_slice(chunk);
+        }
+        blob
+    }
+
+    #[test]
+    fn test_op_blob_roundtrip() {
+        for len in [0, 1, 27, 28, 123, 124, 250, 1000, MAX_OP_PAYLOAD] {
+            let data = payload(len);
+            let blob = encode_op_blob(&data).unwrap();
+            assert_eq!(blob.len(), BLOB_SIZE);
+            assert!(blob.chunks(32).all(|fe| fe[0] & 0b1100_0000 == 0));
+            assert_eq!(decode_op_blob(&blob).unwrap(), data, "len {}", len);
+        }
+        assert!(encode_op_blob(&payload(MAX_OP_PAYLOAD + 1)).is_err());
+
+        let mut blob = encode_op_blob(&payload(100)).unwrap();
+        blob[64] |= 0x80;
+        assert!(decode_op_blob(&blob).is_err());
+        let mut blob = encode_op_blob(&payload(100)).unwrap();
+        blob[BLOB_SIZE - 1] = 1;
+        assert!(decode_op_blob(&blob).is_err());
+        let mut blob = encode_op_blob(&payload(100)).unwrap();
+        blob[1] = 1;
+        assert!(decode_op_blob(&blob).is_err());
+    }
+
+    #[test]
+    fn test_blob_frames() {
+        // Derivation version 0 then two frames of one channel.
+        let mut data = vec![0];
+        for (number, body, last) in [(0u16, &b"abc"[..], 0u8), (1, b"de", 1)] {
+            data.extend_from_slice(&[0x11; 16]);
+            data.extend_from_slice(&number.to_be_bytes());
+            data.extend_from_slice(&(body.len() as u32).to_be_bytes());
+            data.extend_from_slice(body);
+            data.push(last);
+        }
+        let decoded = BlobDecoder::new()
+            .decode(&encode_op_blob(&data).unwrap())
+            .unwrap();
+        assert_eq!(decoded.encoding, BlobEncoding::OpStack);
+        assert_eq!(decoded.payload_len, data.len());
+        let BlobContent::Frames { frames } = decoded.content else {
+            panic!("not frames: {:?}", decoded.content);
+        };
+        assert_eq!(frames.len(), 2);
+        assert_eq!(frames[0].channel_id, "11".repeat(16));
+        assert_eq!((frames[0].frame_number, frames[0].data_len), (0, 3));
+        assert_eq!((frames[1].start, frames[1].is_last), (27, true));
+
+        // A truncated frame isn't batch data.
+        let decoded = BlobDecoder::new().decode(&data[..data.len() - 1]).unwrap();
+        assert_eq!(decoded.encoding, BlobEncoding::Raw);
+        assert_eq!(decoded.content, BlobContent::Unknown);
+    }
+
+    #[test]
+    fn test_blob_transactions() {
+        // An RLP list of a legacy transaction and the same one as a string.
+        let legacy = decode_hex(LEGACY);
+        let mut items = legacy.clone();
+        items.extend_from_slice(&[0xb8, legacy.len() as u8]);
+        items.extend_from_slice(&legacy);
+        let mut list = vec![0xf9, (items.len() >> 8) as u8, items.len() as u8];
+        list.extend_from_slice(&items);
+
+        let decoded = BlobDecoder::new().decode(&padded(&list)).unwrap();
+        assert_eq!(decoded.encoding, BlobEncoding::Padded);
+        let BlobContent::Transactions { transactions } = decoded.content else {
+            panic!("not transactions: {:?}", decoded.content);
+        };
+        assert_eq!(transactions.len(), 2);
+        assert_eq!(transactions[0].transaction.nonce, 7);
+        let call = transactions[1].decoded.as_ref().unwrap();
+        assert_eq!(call.selector, "a9059cbb");
+    }
+
+    #[test]
+    fn test_blob_calldatas() {
+        let data = decode_hex(&format!("{}{}", TRANSFER, APPROVE));
+        let decoded = BlobDecoder::new()
+            .decode(&encode_op_blob(&data).unwrap())
+            .unwrap();
+        let BlobContent::Calldatas { calls } = decoded.content else {
+            panic!("not calldatas: {:?}", decoded.content);
+        };
+        assert_eq!(calls.len(), 2);
+        assert_eq!(calls[0].selector, "a9059cbb");
+        assert_eq!(calls[0].params.len(), 2);
+        assert_eq!(calls[1].selector, "095ea7b3");
+
+        // The padded encoding trims the transfer amount's trailing zeros.
+        let decoded = BlobDecoder::new()
+            .decode(&padded(&decode_hex(TRANSFER)))
+            .unwrap();
+        assert_eq!(decoded.payload_len, 66);
+        let BlobContent::Calldatas { calls } = decoded.content else {
+            panic!("not calldatas: {:?}", decoded.content);
+        };
+        assert_eq!(calls[0].params[1].value, "1000000000000000000");
+
+        let decoded = BlobDecoder::new().decode(&padded(&payload(500))).unwrap();
+        assert_eq!(decoded.content, BlobContent::Unknown);
+    }
+
+    #[test]
+    fn test_read_blobs() {
+        let blob = encode_op_blob(&decode_hex(TRANSFER)).unwrap();
+        let hex = format!("0x{}", faster_hex::hex_string(&blob));
+        let json = format!(r#"{{"data":[{{"index":"0","blob":"{}"}}]}}"#, hex);
+        assert_eq!(read_blobs(&json).unwrap(), vec![blob.clone()]);
+        assert_eq!(read_blobs(&hex).unwrap(), vec![blob.clone()]);
+
+        let decoded = decode_blobs(&json).unwrap();
+        assert!(matches!(decoded[0].content, BlobContent::Calldatas { .. }));
+        assert!(read_blobs("{\"data\": 1}").is_err());
+        assert!(read_blobs("0x03c0").is_err());
+    }
+}
@@ -0,0 +1,137 @@
+/*
+cargo test test_builder -- --nocapture --test-threads=1
+*/
+#[cfg(test)]
+mod test_builder {
+    use crate::builder::*;
+    use crate::corpus::TRANSFER;
+    use crate::{decode_hex, Calldata};
+    use primitive_types::{H160, U256};
+
+    fn usdt() -> H160 {
+        H160::from_slice(&decode_hex("dac17f958d2ee523a2206206994597c13d831ec7"))
+    }
+
+    #[test]
+    fn test_build_static() {
+        let transfer = CalldataBuilder::new("transfer(address to, uint256 amount)")
+            .address(usdt())
+            .uint(1_000_000_000u64);
+        assert_eq!(transfer.build_hex().unwrap(), TRANSFER);
+
+        let unchecked = CalldataBuilder::from_selector([0xa9, 0x05, 0x9c, 0xbb])
+            .address(usdt())
+            .uint(1_000_000_000u64);
+        assert_eq!(unchecked.build_hex().unwrap(), TRANSFER);
+
+        let negative = CalldataBuilder::new("f(int24)").int(-1).build().unwrap();
+        assert_eq!(negative[4..], [0xff; 32]);
+    }
+
+    #[test]
+    fn test_build_dynamic() {
+        // The example of the Solidity ABI specification.
+        let calldata = CalldataBuilder::new("f(uint256,uint32[],bytes10,bytes)")
+            .uint(0x123)
+            .array(vec![
+                AbiValue::Uint(0x456.into()),
+                AbiValue::Uint(0x789.into()),
+            ])
+            .fixed_bytes(b"1234567890")
+            .bytes(b"Hello, world!")
+            .build_hex()
+            .unwrap();
+        assert_eq!(
+            calldata,
+            [
+                "0x8be65246",
+                "0000000000000000000000000000000000000000000000000000000000000123",
+                "0000000000000000000000000000000000000000000000000000000000000080",
+                "3132333435363738393000000000000000000000000000000000000000000000",
+                "00000000000000000000000000000000000000000000000000000000000000e0",
+                "0000000000000000000000000000000000000000000000000000000000000002",
+                "0000000000000000000000000000000000000000000000000000000000000456",
+                "0000000000000000000000000000000000000000000000000000000000000789",
+                "000000000000000000000000000000000000000000000000000000000000000d",
+                "48656c6c6f2c20776f726c642100000000000000000000000000000000000000",
+            ]
+            .concat()
+        );
+    }
+
+    #[test]
+    fn test_build_multicall() {
+        let transfer = CalldataBuilder::new("transfer(address,uint256)")
+            .address(usdt())
+            .uint(1u64);
+        let approve = CalldataBuilder::new("approve(address,uint256)")
+            .address(usdt())
+            .uint(U256::MAX);
+        let multicall = CalldataBuilder::multicall(&[transfer.clone(), approve.clone()]);
+        let decoded = Calldata::from_bytes(&multicall.build().unwrap()).decode();
+        assert_eq!(decoded.selector, "ac9650d8");
+        let selectors = decoded
+            .calls
+            .iter()
+            .map(|c| c.selector.as_str())
+            .collect::<Vec<_>>();
+        assert_eq!(selectors, vec!["a9059cbb", "095ea7b3"]);
+
+        let aggregate = CalldataBuilder::aggregate(&[(usdt(), transfer.clone())]);
+        let decoded = Calldata::from_bytes(&aggregate.build().unwrap()).decode();
+        assert_eq!(decoded.calls[0].selector, "a9059cbb");
+
+        // A call that doesn't build fails the multicall holding it.
+        let broken = CalldataBuilder::new("transfer(address,uint256)").address(usdt());
+        let multicall = CalldataBuilder::multicall_with_deadline(1u64, &[transfer, broken]);
+        assert_eq!(multicall.build().unwrap_err(), "2 inputs, got 1 values");
+    }
+
+    #[test]
+    fn test_build_errors() {
+        assert!(CalldataBuilder::new("transfer(adress,uint256)")
+            .build()
+            .is_err());
+        let wrong = CalldataBuilder::new("f(uint8)").uint(256u64).build();
+        assert!(wrong.unwrap_err().starts_with("value 0 isn't a uint8"));
+        assert!(CalldataBuilder::new("f(int8)").int(-128).build().is_ok());
+        assert!(CalldataBuilder::new("f(int8)").int(128).build().is_err());
+        assert!(CalldataBuilder::new("f(bytes4)")
+            .fixed_bytes(&[1, 2])
+            .build()
+            .is_err());
+        let tuple = AbiValue::Tuple(vec![AbiValue::Address(usdt()), AbiValue::Bool(true)]);
+        assert!(tuple.matches("(address,bool)"));
+        assert!(!tuple.matches("(address,uint256)"));
+    }
+
+    #[cfg(feature = "ethers")]
+    #[test]
+    fn test_build_against_ethers() {
+        let signature = "f((address,bytes)[],int24,string[2],bool)";
+        let ours = CalldataBuilder::new(signature)
+            .array(vec![AbiValue::Tuple(vec![
+                AbiValue::Address(usdt()),
+                AbiValue::Bytes(vec![0x12, 0x34]),
+            ])])
+            .int(-5)
+            .push(AbiValue::FixedArray(vec![
+                AbiValue::String("a".to_string()),
+                AbiValue::String("bc".to_string()),
+            ]))
+            .bool(true)
+            .build_hex()
+            .unwrap();
+        let theirs = crate::encoder::encode_call(
+            signature,
+            &[
+                "[(0xdac17f958d2ee523a2206206994597c13d831ec7,0x1234)]",
+                "-5",
+                "[a,bc]",
+                "true",
+            ],
+        )
+        .unwrap();
+        assert_eq!(ours, theirs);
+    }
+}
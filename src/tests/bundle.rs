@@ -0,0 +1,87 @@
+/*
+cargo test test_bundle -- --nocapture --test-threads=1
+*/
+#[cfg(test)]
+mod test_bundle {
+    use crate::bundle::*;
+    use crate::render::color::Style;
+    use crate::render::output::{render_bundle, OutputFormat};
+
+    /// The signed transaction of the EIP-155 example, 1 ether to 0x3535..35.
+    const EIP155: &str = "0xf86c098504a817c800825208943535353535353535353535353535353535353535880de0b6b3a76400008025a028ef61340bd939bc2195fe537567866003e1a15d3c71ff63e1590620aa636276a067cbe9d8997f761aecb703304b3800ccf555c9f3dc64214b297fb1966a3b6d83";
+    /// Two `transfer`s of WETH to vitalik.eth, a legacy and a dynamic fee one.
+    const TRANSFER: &str = "0xf8a9078504a817c80082ea6094c02aaa39b223fe8d0a0e5c4f27ead9083c756cc280b844a9059cbb000000000000000000000000d8da6bf26964af9d7eed9e10e3f4a2ff91e9a7a60000000000000000000000000000000000000000000000000de0b6b3a764000025a028ef61340bd939bc2195fe537567866003e1a15d3c71ff63e1590620aa636276a067cbe9d8997f761aecb703304b3800ccf555c9f3dc64214b297fb1966a3b6d83";
+    const DYNAMIC_FEE: &str = "0x02f8b00104843b9aca008506fc23ac0082ea6094c02aaa39b223fe8d0a0e5c4f27ead9083c756cc280b844a9059cbb000000000000000000000000d8da6bf26964af9d7eed9e10e3f4a2ff91e9a7a60000000000000000000000000000000000000000000000000de0b6b3a7640000c080a028ef61340bd939bc2195fe537567866003e1a15d3c71ff63e1590620aa636276a067cbe9d8997f761aecb703304b3800ccf555c9f3dc64214b297fb1966a3b6d83";
+
+    #[test]
+    fn test_parse_bundle() {
+        let array = format!(r#"["{}", "{}"]"#, EIP155, TRANSFER);
+        let params = format!(
+            r#"{{"txs": ["{}", "{}"], "blockNumber": "0x1"}}"#,
+            EIP155, TRANSFER
+        );
+        let request = format!(
+            r#"{{"jsonrpc": "2.0", "id": 1, "method": "eth_sendBundle", "params": [{}]}}"#,
+            params
+        );
+        let lines = format!("{}\n\n{}\n", EIP155, TRANSFER);
+        for text in [array, params, request, lines] {
+            let raws = parse_bundle(&text).unwrap();
+            assert_eq!(raws.len(), 2, "{}", text);
+            assert_eq!(raws[1][0], 0xf8);
+        }
+        assert!(parse_bundle("[]").is_err());
+        assert!(parse_bundle(r#"{"transactions": []}"#).is_err());
+        assert!(parse_bundle("[1, 2]").is_err());
+    }
+
+    #[test]
+    fn test_decode_bundle() {
+        let text = format!(r#"["{}", "{}", "{}"]"#, EIP155, TRANSFER, DYNAMIC_FEE);
+        let bundle = decode_bundle(&text).unwrap();
+        assert_eq!(bundle.transactions.len(), 3);
+        assert!(bundle.transactions[0].decoded.decoded.is_none());
+        assert_eq!(
+            bundle.transactions[2]
+                .decoded
+                .decoded
+                .as_ref()
+                .unwrap()
+                .selector,
+            "a9059cbb"
+        );
+
+        // WETH is called by both transfers, which both pay vitalik.eth.
+        let shared = bundle
+            .shared
+            .iter()
+            .map(|s| (format!("{:?}", s.address), s.uses.len()))
+            .collect::<Vec<_>>();
+        assert_eq!(
+            shared,
+            vec![
+                ("0xc02aaa39b223fe8d0a0e5c4f27ead9083c756cc2".to_string(), 2),
+                ("0xd8da6bf26964af9d7eed9e10e3f4a2ff91e9a7a6".to_string(), 2),
+            ]
+        );
+        assert_eq!(bundle.shared[1].uses[0].role, AddressRole::Param);
+        assert_eq!(bundle.shared[1].uses[0].path.as_deref(), Some("params[0]"));
+
+        #[cfg(feature = "ethers")]
+        assert_eq!(
+            format!("{:?}", bundle.transactions[0].from.unwrap()),
+            "0x9d8a62f656a8d1615c1294fd71e9cfb3e4855a4f"
+        );
+
+        let out = render_bundle(OutputFormat::Pretty, &bundle, &Style::plain());
+        println!("{}", out);
+        assert!(
+            out.contains("0xC02aaA39b223FE8D0A0e5C4F27eAD9083C756Cc2 #1 recipient, #2 recipient")
+        );
+
+        let broken = format!(r#"["{}", "0x02"]"#, EIP155);
+        assert!(decode_bundle(&broken)
+            .unwrap_err()
+            .starts_with("transaction 1: "));
+    }
+}
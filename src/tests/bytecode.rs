@@ -0,0 +1,76 @@
+/*
+cargo test test_bytecode -- --nocapture --test-threads=1
+*/
+#[cfg(test)]
+mod test_bytecode {
+    use crate::bytecode::*;
+    use crate::corpus::MULTICALL;
+    use crate::decoded::DecodedCalldata;
+    use crate::{decode_hex, Calldata};
+
+    /// A Solidity-style dispatcher for `multicall(bytes[])` and the Uniswap
+    /// position manager's `mint`, with a `PUSH32` hiding a fake comparison.
+    fn dispatcher() -> Vec<u8> {
+        decode_hex(concat!(
+            "60003560e01c",         // PUSH1 0 CALLDATALOAD PUSH1 0xe0 SHR
+            "8063ac9650d814610040", // DUP1 PUSH4 multicall EQ PUSH2
+            "57",                   // JUMPI
+            "63883164568114610080", // PUSH4 mint DUP2 EQ PUSH2 (via-IR order)
+            "57",                   // JUMPI
+            "7f63deadbeef14",       // PUSH32 whose data reads as PUSH4 .. EQ
+            "0000000000000000000000000000000000000000000000000000",
+            "63ffffffff16", // PUSH4 mask AND
+        ))
+    }
+
+    #[test]
+    fn test_dispatch_selectors() {
+        assert_eq!(
+            dispatch_selectors(&dispatcher()),
+            vec![[0x88, 0x31, 0x64, 0x56], [0xac, 0x96, 0x50, 0xd8]]
+        );
+        assert!(dispatch_selectors(&decode_hex("63a9059cbb")).is_empty());
+        assert!(dispatch_selectors(&[]).is_empty());
+    }
+
+    #[test]
+    fn test_compiler_profile() {
+        use crate::heuristics::Profile;
+        assert_eq!(compiler_profile(&dispatcher()), Some(Profile::Solidity));
+        // Vyper compares the selector with XOR.
+        let vyper = decode_hex(concat!(
+            "60003560e01c",         // PUSH1 0 CALLDATALOAD PUSH1 0xe0 SHR
+            "63a9059cbb811861003c", // PUSH4 transfer DUP2 XOR PUSH2
+            "57",                   // JUMPI
+        ));
+        assert_eq!(compiler_profile(&vyper), Some(Profile::Vyper));
+        // Else the metadata tells it: {"vyper": [0, 3, 7]} then its length.
+        let mut metadata = dispatcher();
+        metadata.extend(decode_hex("a165767970657283000307000b"));
+        assert_eq!(compiler_profile(&metadata), Some(Profile::Vyper));
+        assert_eq!(compiler_profile(&[]), None);
+    }
+
+    #[test]
+    fn test_target_selectors() {
+        let unconstrained = Calldata::new(MULTICALL).decode();
+        let selectors = |root: &DecodedCalldata| {
+            root.calls
+                .iter()
+                .map(|c| c.selector.clone())
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(selectors(&unconstrained), vec!["88316456", "12210e8a"]);
+
+        // The target has no `refundETH`, so the 4 bytes after its length are data.
+        let constrained = Calldata::new(MULTICALL)
+            .with_target_selectors(dispatch_selectors(&dispatcher()))
+            .decode();
+        assert_eq!(selectors(&constrained), vec!["88316456"]);
+        assert_eq!(constrained.params, unconstrained.params);
+
+        let all = vec![[0x12, 0x21, 0x0e, 0x8a], [0x88, 0x31, 0x64, 0x56]];
+        let decoded = Calldata::new(MULTICALL).with_target_selectors(all).decode();
+        assert_eq!(decoded, unconstrained);
+    }
+}
@@ -0,0 +1,43 @@
+/*
+cargo test test_cache -- --nocapture --test-threads=1
+*/
+#[cfg(test)]
+mod test_cache {
+    use crate::cache::Lru;
+
+    #[test]
+    fn test_lru_eviction() {
+        let mut lru = Lru::new(2);
+        lru.insert("a", 1);
+        lru.insert("b", 2);
+        // Using `a` makes `b` the least recently used.
+        assert_eq!(lru.get(&"a"), Some(1));
+        lru.insert("c", 3);
+        assert_eq!(lru.len(), 2);
+        assert_eq!(lru.get(&"b"), None);
+        assert_eq!(lru.get(&"a"), Some(1));
+        assert_eq!(lru.get(&"c"), Some(3));
+        assert_eq!(lru.stats(), (3, 1));
+
+        // Re-inserting replaces without evicting.
+        lru.insert("c", 4);
+        assert_eq!(lru.len(), 2);
+        assert_eq!(lru.get(&"c"), Some(4));
+    }
+
+    #[test]
+    fn test_get_or_insert_with() {
+        let mut lru = Lru::new(8);
+        let mut calls = 0;
+        for _ in 0..3 {
+            let v = lru.get_or_insert_with(&"k", || {
+                calls += 1;
+                "v".to_string()
+            });
+            assert_eq!(v, "v");
+        }
+        assert_eq!(calls, 1);
+        lru.clear();
+        assert!(lru.is_empty());
+    }
+}
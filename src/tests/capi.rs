@@ -0,0 +1,74 @@
+/*
+cargo test --features capi test_capi -- --nocapture --test-threads=1
+*/
+#[cfg(all(test, feature = "capi"))]
+mod test_capi {
+    use crate::capi::*;
+    use crate::schema::{from_json, JsonOutput, SCHEMA_VERSION};
+    use crate::Calldata;
+    use std::ffi::{c_char, CStr, CString};
+    use std::ptr;
+
+    const TRANSFER: &str = "0xa9059cbb000000000000000000000000c02aaa39b223fe8d0a0e5c4f27ead9083c756cc20000000000000000000000000000000000000000000000000de0b6b3a7640000";
+
+    /// Status and output of `cd_decode`, the output freed.
+    fn decode(hex: *const c_char) -> (i32, String) {
+        let mut out = ptr::null_mut();
+        let status = unsafe { cd_decode(hex, &mut out) };
+        assert!(!out.is_null());
+        let text = unsafe { CStr::from_ptr(out) }.to_str().unwrap().to_string();
+        unsafe { cd_free(out) };
+        (status, text)
+    }
+
+    #[test]
+    fn test_decode() {
+        let hex = CString::new(TRANSFER).unwrap();
+        let (status, json) = decode(hex.as_ptr());
+        assert_eq!(status, 0);
+        assert_eq!(
+            from_json(&json).unwrap(),
+            JsonOutput::new(&Calldata::new(TRANSFER))
+        );
+        assert_eq!(cd_schema_version(), SCHEMA_VERSION);
+    }
+
+    #[test]
+    fn test_decode_errors() {
+        let hex = CString::new("0xa9059cbz").unwrap();
+        let (status, error) = decode(hex.as_ptr());
+        assert_eq!(status, 3);
+        assert!(error.contains("'z'"));
+
+        assert_eq!(decode(ptr::null()), (3, "calldata is NULL".to_string()));
+        let hex = CString::new(TRANSFER).unwrap();
+        assert_eq!(unsafe { cd_decode(hex.as_ptr(), ptr::null_mut()) }, 3);
+        unsafe { cd_free(ptr::null_mut()) };
+
+        // A truncated length is a partial decode.
+        let hex = CString::new("0xac9650d80000000000000000000000000000000000000000000000000000000040000004aabbccdd00000000000000000000000000000000000000000000000000000000").unwrap();
+        assert_eq!(decode(hex.as_ptr()).0, 2);
+    }
+
+    #[test]
+    fn test_decode_json() {
+        let request = CString::new(format!(r#"{{"calldata": "{}"}}"#, TRANSFER)).unwrap();
+        let response = unsafe { cd_decode_json(request.as_ptr()) };
+        let json = unsafe { CStr::from_ptr(response) }
+            .to_str()
+            .unwrap()
+            .to_string();
+        unsafe { cd_free(response) };
+        let value: serde_json::Value = serde_json::from_str(&json).unwrap();
+        assert_eq!(value["status"], "decoded");
+        assert_eq!(value["output"]["root"]["selector"], "a9059cbb");
+
+        let response = unsafe { cd_decode_json(ptr::null()) };
+        let json = unsafe { CStr::from_ptr(response) }
+            .to_str()
+            .unwrap()
+            .to_string();
+        unsafe { cd_free(response) };
+        assert!(json.contains("request is NULL"));
+    }
+}
@@ -0,0 +1,81 @@
+/*
+cargo test test_codegen -- --nocapture --test-threads=1
+*/
+#[cfg(test)]
+mod test_codegen {
+    use crate::codegen::*;
+    use crate::corpus::{MULTICALL, TRANSFER};
+    use crate::Calldata;
+
+    /// `swapExactTokensForTokens(uint256,uint256,address[],address,uint256)`
+    /// with `amountIn` and the path's tokens.
+    fn swap(amount_in: u64, path: &[&str]) -> String {
+        let word = |hex: &str| format!("{:0>64}", hex);
+        let mut out = "0x38ed1739".to_string();
+        out.push_str(&word(&format!("{:x}", amount_in)));
+        out.push_str(&word("1388"));
+        out.push_str(&word("a0"));
+        out.push_str(&word("f847e9d51989033b691b8be943f8e9e268f99b9e"));
+        out.push_str(&word("6553f100"));
+        out.push_str(&word(&format!("{:x}", path.len())));
+        for token in path {
+            out.push_str(&word(token));
+        }
+        out
+    }
+
+    #[test]
+    fn test_codegen_infer() {
+        let roots = [MULTICALL, MULTICALL].map(|hex| Calldata::new(hex).decode());
+        let multicall = infer_signature("0xac9650d8", &roots).unwrap();
+        assert_eq!(multicall.signature(), "unknown_ac9650d8(bytes[])");
+        assert_eq!(multicall.samples, 2);
+
+        // Nested calls count too.
+        let mint = infer_signature("88316456", &roots).unwrap();
+        assert_eq!(mint.params.len(), 11);
+        assert_eq!(mint.params[0].solidity, "address");
+        assert_eq!(mint.params[3].solidity, "int256");
+
+        // A small amount in one call doesn't narrow the type the others need.
+        let weth = "c02aaa39b223fe8d0a0e5c4f27ead9083c756cc2";
+        let usdt = "dac17f958d2ee523a2206206994597c13d831ec7";
+        let roots = [
+            swap(1, &[weth, usdt]),
+            swap(1_000_000_000_000_000_000, &[usdt, weth, usdt]),
+        ]
+        .map(|hex| Calldata::new(&hex).decode());
+        let swap = infer_signature("38ed1739", &roots).unwrap();
+        assert_eq!(
+            swap.signature(),
+            "unknown_38ed1739(uint256,uint256,address[],address,uint256)"
+        );
+
+        assert!(infer_signature("a9059cbb", &roots).is_err());
+        let roots =
+            [TRANSFER, &TRANSFER[..TRANSFER.len() - 64]].map(|hex| Calldata::new(hex).decode());
+        let e = infer_signature("a9059cbb", &roots).unwrap_err();
+        assert!(e.contains("don't share a layout"), "{}", e);
+    }
+
+    #[test]
+    fn test_codegen_render() {
+        let roots = [Calldata::new(TRANSFER).decode()];
+        let mut transfer = infer_signature("a9059cbb", &roots).unwrap();
+        transfer.name = "transfer".to_string();
+        let source = render_decoder(&transfer);
+        println!("{}", source);
+        assert!(source.starts_with("// Decoder for `transfer(address,uint256)` (0xa9059cbb)"));
+        assert!(source.contains("pub mod transfer {"));
+        assert!(source.contains("use primitive_types::{H160, U256};"));
+        assert!(source.contains("pub const SELECTOR: [u8; 4] = [0xa9, 0x05, 0x9c, 0xbb];"));
+        assert!(source.contains("pub struct Transfer {"));
+        assert!(source.contains("arg0: H160(last(uint(word(body, 0)?, 20)?)),"));
+        assert!(source.contains("arg1: U256::from_big_endian(&uint(word(body, 1)?, 32)?),"));
+
+        let roots = [Calldata::new(MULTICALL).decode()];
+        let source = render_decoder(&infer_signature("ac9650d8", &roots).unwrap());
+        assert!(source.contains("pub arg0: Vec<Vec<u8>>,"));
+        assert!(!source.contains("use primitive_types"));
+    }
+}
@@ -0,0 +1,121 @@
+/*
+cargo test test_config -- --nocapture --test-threads=1
+*/
+#[cfg(all(test, feature = "cli"))]
+mod test_config {
+    use crate::config::*;
+    use crate::render::address::AddressCase;
+    use crate::render::color::ColorMode;
+    use crate::render::labels::{apply_labels, Labels};
+    use crate::tokens::Tokens;
+    use crate::Calldata;
+
+    const TRANSFER: &str = "0xa9059cbb000000000000000000000000c02aaa39b223fe8d0a0e5c4f27ead9083c756cc20000000000000000000000000000000000000000000000000de0b6b3a7640000";
+
+    #[test]
+    fn test_from_toml() {
+        let config = Config::from_toml(
+            r#"
+            chain = "mainnet"
+            color = "never"
+            address_case = "lowercase"
+
+            [rpc]
+            mainnet = "https://mainnet.example"
+            base = "wss://base.example"
+
+            [api_keys]
+            etherscan = "KEY"
+
+            [heuristics]
+            nested = false
+
+            [limits]
+            max_depth = 2
+            "#,
+        )
+        .unwrap();
+        assert_eq!(config.rpc_url(None).unwrap(), "https://mainnet.example");
+        assert_eq!(config.rpc_url(Some("base")).unwrap(), "wss://base.example");
+        assert_eq!(config.rpc_url(Some("optimism")), None);
+        assert_eq!(config.api_keys["etherscan"], "KEY");
+        assert_eq!(config.color, Some(ColorMode::Never));
+        assert_eq!(config.address_case, Some(AddressCase::Lowercase));
+        assert!(!config.heuristics.nested);
+        assert!(config.heuristics.addresses);
+        assert_eq!(config.limits.max_depth, 2);
+        assert_eq!(config.limits.max_calls, 1024);
+
+        assert_eq!(Config::from_toml("").unwrap(), Config::default());
+        assert!(Config::from_toml("rpc_urls = 1").is_err());
+    }
+
+    #[test]
+    fn test_files() {
+        let dir =
+            std::env::temp_dir().join(format!("calldata-decoder-config-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        std::fs::write(
+            dir.join("labels.txt"),
+            "# tokens\n0xC02aaa39b223FE8D0A0e5C4F27eAD9083C756Cc2 WETH\n",
+        )
+        .unwrap();
+        std::fs::write(dir.join("sigs.txt"), "foo(uint256)\n").unwrap();
+        std::fs::write(
+            dir.join("tokens.txt"),
+            "base 0x0000000000000000000000000000000000000abc FOO 9\n",
+        )
+        .unwrap();
+        std::fs::write(
+            dir.join("config.toml"),
+            format!(
+                "labels = [{:?}]\nsignatures = [{:?}]\ntokens = [{:?}]\n",
+                dir.join("labels.txt"),
+                dir.join("sigs.txt"),
+                dir.join("tokens.txt")
+            ),
+        )
+        .unwrap();
+
+        let config = Config::load(&dir.join("config.toml")).unwrap();
+        let labels = config.labels().unwrap();
+        assert_eq!(
+            labels.get("0xc02aaa39b223fe8d0a0e5c4f27ead9083c756cc2"),
+            Some("WETH")
+        );
+
+        let mut root = Calldata::new(TRANSFER).decode();
+        apply_labels(&mut root, &labels);
+        assert_eq!(root.params[0].label.as_deref(), Some("WETH"));
+        assert_eq!(
+            root.params[0].value,
+            "0xC02aaA39b223FE8D0A0e5C4F27eAD9083C756Cc2"
+        );
+
+        let signatures = config.signature_sources().unwrap();
+        assert_eq!(
+            signatures.resolve(&crate::signatures::selector_of("foo(uint256)")),
+            vec!["foo(uint256)"]
+        );
+        assert_eq!(
+            signatures.resolve("a9059cbb"),
+            vec!["transfer(address,uint256)"]
+        );
+
+        let tokens = config.tokens().unwrap();
+        assert_eq!(tokens.len(), Tokens::builtin().len() + 1);
+        assert_eq!(
+            tokens
+                .get(8453, "0x0000000000000000000000000000000000000abc")
+                .unwrap()
+                .decimals,
+            9
+        );
+        std::fs::write(dir.join("tokens.txt"), "base FOO\n").unwrap();
+        assert!(config.tokens().unwrap_err().contains("tokens.txt: line 1"));
+
+        assert!(Config::load(&dir.join("missing.toml")).is_err());
+        assert!(Labels::from_text("0xabc").is_empty());
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+}
@@ -0,0 +1,49 @@
+/*
+cargo test test_corpus -- --nocapture --test-threads=1
+*/
+#[cfg(test)]
+mod test_corpus {
+    use crate::arena::DecodeArena;
+    use crate::corpus;
+    use crate::decoded::DecodeStatus;
+    use crate::lazy::LazyCall;
+    use crate::{check_hex, decode_hex, Calldata};
+
+    #[test]
+    fn test_corpus_decodes() {
+        for (name, hex) in corpus::ALL {
+            assert_eq!(check_hex(hex), Ok(()), "{}", name);
+            let root = Calldata::new(hex).decode();
+            assert_eq!(root.status(), DecodeStatus::Decoded, "{}", name);
+            assert_eq!(root.selector, hex[2..10], "{}", name);
+        }
+        let multicall = Calldata::new(corpus::MULTICALL).decode();
+        assert_eq!(multicall.calls.len(), 2);
+    }
+
+    #[test]
+    fn test_corpus_mutations() {
+        // Truncated and corrupted calldata decodes without panicking.
+        for (name, hex) in corpus::ALL {
+            let bytes = decode_hex(hex);
+            let mut inputs = (0..bytes.len())
+                .map(|n| bytes[..n].to_vec())
+                .collect::<Vec<_>>();
+            for (i, flip) in [(0, 0x00), (3, 0xff), (31, 0x04), (35, 0x80)] {
+                let mut bytes = bytes.clone();
+                for b in bytes.iter_mut().skip(i).step_by(32) {
+                    *b = flip;
+                }
+                inputs.push(bytes);
+            }
+            for input in inputs {
+                let calldata = Calldata::from_bytes(&input);
+                let root = calldata.decode();
+                let mut arena = DecodeArena::new();
+                let id = arena.decode(&calldata);
+                assert_eq!(arena.call(id).to_decoded(), root, "{}", name);
+                LazyCall::from_bytes(input).decode();
+            }
+        }
+    }
+}
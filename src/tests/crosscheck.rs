@@ -0,0 +1,67 @@
+/*
+cargo test test_crosscheck -- --nocapture --test-threads=1
+*/
+#[cfg(test)]
+mod test_crosscheck {
+    use crate::crosscheck::*;
+    use crate::Calldata;
+
+    const HEIMDALL_OUTPUT: &str = "
+info: decoded 2 parameters
+  0x5d842074 unknown(uint256,uint256[])
+     input 0: 125000000000000000000
+     input 1: [600000000000000000000, 800000000000000000000]
+";
+
+    #[test]
+    fn test_parse_heimdall_signature() {
+        let (signature, types) = parse_heimdall_signature(HEIMDALL_OUTPUT).unwrap();
+        assert_eq!(signature, "unknown(uint256,uint256[])");
+        assert_eq!(types, vec!["uint256", "uint256[]"]);
+    }
+
+    #[test]
+    fn test_split_tuple_types() {
+        let types = split_types("(address,uint24),bytes,uint8");
+        assert_eq!(types, vec!["(address,uint24)", "bytes", "uint8"]);
+    }
+
+    #[test]
+    fn test_cross_check_agrees() {
+        let calldata = Calldata::new("0x5d842074000000000000000000000000000000000000000000000006c6b935b8bbd400000000000000000000000000000000000000000000000000000000000000000040000000000000000000000000000000000000000000000000000000000000000200000000000000000000000000000000000000000000002086ac35105260000000000000000000000000000000000000000000000000002b5e3af16b18800000");
+        let check = cross_check(&calldata, HEIMDALL_OUTPUT);
+        println!("{:#?}", check);
+        assert_eq!(check.params.len(), 2);
+        assert_eq!(check.agreed(), 2);
+        assert_eq!(check.confidence(), 1.0);
+    }
+
+    #[test]
+    fn test_cross_check_disagrees() {
+        let calldata = Calldata::new("0xa9059cbb000000000000000000000000c02aaa39b223fe8d0a0e5c4f27ead9083c756cc20000000000000000000000000000000000000000000000000de0b6b3a7640000");
+        let check = cross_check(&calldata, "transfer(bool,uint256)");
+        assert_eq!(check.params[0].agreement, Agreement::Disagree);
+        assert_eq!(check.params[1].agreement, Agreement::Agree);
+        assert_eq!(check.params[0].guessed[0], "address");
+        assert!(check.params[1].guessed.contains(&"uint256".to_string()));
+    }
+
+    #[test]
+    fn test_cross_check_dynamic() {
+        // A number isn't an offset to data, though it's guessed as one.
+        let calldata = Calldata::new("0xa9059cbb000000000000000000000000c02aaa39b223fe8d0a0e5c4f27ead9083c756cc20000000000000000000000000000000000000000000000000de0b6b3a7640000");
+        let check = cross_check(&calldata, "transfer(address,bytes)");
+        assert_eq!(check.params[1].agreement, Agreement::Disagree);
+        assert_eq!(check.confidence(), 0.5);
+
+        // Tuples aren't checked, and don't count towards the confidence.
+        let check = cross_check(&calldata, "f((address,uint256),uint256,address)");
+        let agreements = check.params.iter().map(|p| &p.agreement).collect::<Vec<_>>();
+        assert_eq!(
+            agreements,
+            vec![&Agreement::Unchecked, &Agreement::Agree, &Agreement::Missing]
+        );
+        assert!(check.params[2].guessed.is_empty());
+        assert_eq!(check.confidence(), 0.5);
+    }
+}
@@ -0,0 +1,160 @@
+/*
+cargo test test_decoder -- --nocapture --test-threads=1
+*/
+#[cfg(test)]
+mod test_decoder {
+    use crate::builder::CalldataBuilder;
+    use crate::corpus;
+    use crate::decoder::Decoder;
+    use crate::limits::Limits;
+    use crate::protocol::ProtocolRegistry;
+    use crate::render::labels::Labels;
+    use crate::signatures::{SignatureMap, Signatures};
+    use crate::{decode_hex, Calldata, DecodeError};
+    use primitive_types::H160;
+    use std::sync::Arc;
+
+    #[test]
+    fn test_decoder_shared() {
+        fn shareable<T: Send + Sync>() {}
+        shareable::<Decoder>();
+
+        let decoder = Arc::new(Decoder::new());
+        let handles = (0..4)
+            .map(|_| {
+                let decoder = Arc::clone(&decoder);
+                std::thread::spawn(move || {
+                    corpus::ALL
+                        .iter()
+                        .map(|(_, hex)| decoder.decode(hex).unwrap())
+                        .collect::<Vec<_>>()
+                })
+            })
+            .collect::<Vec<_>>();
+        for handle in handles {
+            let roots = handle.join().unwrap();
+            // The protocols' decode, e.g. the Safe transaction unwrapped.
+            for ((name, hex), root) in corpus::ALL.iter().zip(roots) {
+                let expected = ProtocolRegistry::builtin()
+                    .decode(&Calldata::new(hex))
+                    .unwrap();
+                assert_eq!(root, expected, "{}", name);
+            }
+        }
+        let (hits, misses) = decoder.cache_stats();
+        assert_eq!(hits + misses, 4 * corpus::ALL.len() as u64);
+        assert!(misses >= corpus::ALL.len() as u64);
+    }
+
+    #[test]
+    fn test_decoder_settings() {
+        let mut labels = Labels::new();
+        labels.insert("0xdAC17F958D2ee523a2206206994597C13D831ec7", "USDT");
+        let decoder = Decoder::new().with_labels(labels);
+        let root = decoder.decode(corpus::TRANSFER).unwrap();
+        assert_eq!(root.params[0].label.as_deref(), Some("USDT"));
+        assert_eq!(
+            decoder.resolve(&root.selector),
+            vec!["transfer(address,uint256)"]
+        );
+        assert_eq!(decoder.selectors(&root).len(), 1);
+
+        assert!(decoder.decode("0xa9059c").is_err());
+        assert!(decoder.decode_bytes(&[0xa9, 0x05]).is_err());
+    }
+
+    #[test]
+    fn test_decoder_signature_types() {
+        let hex = CalldataBuilder::new("setOwner(address)")
+            .address(H160::from_low_u64_be(0x1234))
+            .build_hex()
+            .unwrap();
+        let mut map = SignatureMap::new();
+        map.insert("setOwner(address)");
+        let decoder = Decoder::new().with_signatures(Signatures::new().with_source(map));
+        assert_ne!(
+            decoder.decode(&hex).unwrap().signature_guess,
+            "setOwner(address)"
+        );
+
+        let decoder = decoder.with_signature_types(true);
+        assert!(decoder.signature_types());
+        let root = decoder.decode(&hex).unwrap();
+        assert_eq!(root.signature_guess, "setOwner(address)");
+        assert_eq!(root.params[0].types[0].solidity, "address");
+
+        // A known layout decides where the calls are, not the heuristics.
+        let call = format!(
+            "0x12345678{:064x}{:064x}{}{}",
+            0x20,
+            0x44,
+            &corpus::TRANSFER[2..],
+            "0".repeat(56)
+        );
+        let untyped = Decoder::new().decode(&call).unwrap();
+        assert_eq!(untyped.calls[0].selector, "a9059cbb");
+        let mut map = SignatureMap::new();
+        map.insert_with_selector("12345678", "words(uint256,uint256,uint256,uint256,uint256)");
+        let decoder = Decoder::new()
+            .with_signatures(Signatures::new().with_source(map))
+            .with_signature_types(true);
+        let root = decoder.decode(&call).unwrap();
+        assert_eq!(root.signature_guess, "words(uint256,uint256,uint256,uint256,uint256)");
+        assert!(root.calls.is_empty());
+        // The layout is resolved once for the selector.
+        decoder.decode_bytes(&decode_hex(&call.replace("a9059cbb", "23b872dd"))).unwrap();
+        assert_eq!(decoder.signatures().cache_stats().1, 1);
+    }
+
+    #[test]
+    fn test_decoder_batch() {
+        let decoder = Decoder::new();
+        let upper = format!("0x{}", corpus::TRANSFER[2..].to_uppercase());
+        let calldatas = vec![
+            corpus::TRANSFER,
+            corpus::SAFE,
+            "0xzz",
+            upper.as_str(),
+            "0xa905",
+            corpus::TRANSFER,
+        ];
+        let results = decoder.decode_batch(&calldatas);
+        assert_eq!(results.len(), 6);
+        let transfer = decoder.decode(corpus::TRANSFER).unwrap();
+        assert_eq!(results[0].as_ref().unwrap(), &transfer);
+        assert_eq!(results[3].as_ref().unwrap(), &transfer);
+        assert_eq!(results[5].as_ref().unwrap(), &transfer);
+        assert_eq!(results[1].as_ref().unwrap().calls[0].selector, "a9059cbb");
+        assert!(matches!(results[2], Err(DecodeError::InvalidHex(_))));
+        assert_eq!(results[4], Err(DecodeError::TooShort { bytes: 2 }));
+        // The same bytes are decoded once, however they're written.
+        let (hits, misses) = decoder.cache_stats();
+        assert_eq!((hits, misses), (1, 2));
+
+        let small = Decoder::new().with_limits(Limits {
+            max_input: 100,
+            ..Limits::default()
+        });
+        let results = small.decode_batch(&[corpus::TRANSFER, corpus::SAFE]);
+        assert!(results[0].is_ok());
+        assert!(matches!(results[1], Err(DecodeError::OverLimit(_))));
+    }
+
+    #[test]
+    fn test_decoder_batch_layouts() {
+        let decoder = Decoder::new();
+        let amount = "0x12345678000000000000000000000000dac17f958d2ee523a2206206994597c13d831ec7000000000000000000000000000000000000000000000000000000003b9aca00";
+        // Alone, an amount that reads as an address is typed as one.
+        let large = "0x12345678000000000000000000000000dac17f958d2ee523a2206206994597c13d831ec7000000000000000000000000c02aaa39b223fe8d0a0e5c4f27ead9083c756cc2";
+        assert_eq!(
+            decoder.decode(large).unwrap().signature_guess,
+            "unknown_12345678(address,address)"
+        );
+        // In a batch, the first call of the selector types it.
+        let results = Decoder::new().decode_batch(&[amount, large]);
+        let large = results[1].as_ref().unwrap();
+        assert_eq!(large.signature_guess, "unknown_12345678(address,uint256)");
+        assert_eq!(large.params[1].types[0].solidity, "uint256");
+        assert_eq!(results[0].as_ref().unwrap(), &decoder.decode(amount).unwrap());
+    }
+}
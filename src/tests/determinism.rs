@@ -0,0 +1,108 @@
+/*
+cargo test test_determinism -- --nocapture --test-threads=1
+*/
+#[cfg(test)]
+mod test_determinism {
+    use crate::arena::DecodeArena;
+    use crate::corpus;
+    use crate::decoder::Decoder;
+    use crate::lazy::LazyCall;
+    use crate::protocol::ProtocolRegistry;
+    use crate::prototypes::rank_prototypes;
+    use crate::schema::JsonOutput;
+    use crate::signatures::Signatures;
+    use crate::{decode_hex, Calldata};
+
+    fn json(hex: &str) -> String {
+        serde_json::to_string(&JsonOutput::new(&Calldata::new(hex))).unwrap()
+    }
+
+    #[test]
+    fn test_determinism_repeated() {
+        for (name, hex) in corpus::ALL {
+            let first = json(hex);
+            for _ in 0..8 {
+                assert_eq!(json(hex), first, "{}", name);
+            }
+        }
+    }
+
+    #[test]
+    fn test_determinism_threads() {
+        let expected = corpus::ALL
+            .iter()
+            .map(|(_, hex)| json(hex))
+            .collect::<Vec<_>>();
+        std::thread::scope(|scope| {
+            let handles = (0..4)
+                .map(|_| {
+                    scope.spawn(|| {
+                        corpus::ALL
+                            .iter()
+                            .map(|(_, hex)| json(hex))
+                            .collect::<Vec<_>>()
+                    })
+                })
+                .collect::<Vec<_>>();
+            for handle in handles {
+                assert_eq!(handle.join().unwrap(), expected);
+            }
+        });
+    }
+
+    #[test]
+    fn test_determinism_decode_paths() {
+        let decoder = Decoder::new();
+        for (name, hex) in corpus::ALL {
+            let bytes = decode_hex(hex);
+            let calldata = Calldata::from_bytes(&bytes);
+            let root = calldata.decode();
+            let mut arena = DecodeArena::new();
+            let id = arena.decode(&calldata);
+            assert_eq!(arena.call(id).to_decoded(), root, "{}", name);
+            assert_eq!(
+                LazyCall::from_bytes(bytes.clone()).decode(),
+                root,
+                "{}",
+                name
+            );
+            // The second decode is the cached one, both through the protocols.
+            let root = ProtocolRegistry::builtin().decode(&calldata).unwrap();
+            assert_eq!(decoder.decode(hex).unwrap(), root, "{}", name);
+            assert_eq!(decoder.decode(hex).unwrap(), root, "{}", name);
+        }
+    }
+
+    #[test]
+    fn test_determinism_candidate_order() {
+        let signatures = Signatures::builtin();
+        for (name, hex) in corpus::ALL {
+            let root = Calldata::new(hex).decode();
+            for call in root.walk() {
+                for param in call.params.iter() {
+                    let confidences = param.types.iter().map(|t| t.confidence).collect::<Vec<_>>();
+                    assert!(
+                        confidences.windows(2).all(|w| w[0] >= w[1]),
+                        "{} {:?}",
+                        name,
+                        confidences
+                    );
+                }
+                let prototypes = rank_prototypes(call, &signatures, 8);
+                assert_eq!(
+                    rank_prototypes(call, &signatures, 8),
+                    prototypes,
+                    "{}",
+                    name
+                );
+                assert!(
+                    prototypes
+                        .windows(2)
+                        .all(|w| w[0].confidence >= w[1].confidence),
+                    "{}",
+                    name
+                );
+            }
+        }
+    }
+}
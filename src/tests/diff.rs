@@ -0,0 +1,54 @@
+/*
+cargo test test_diff -- --nocapture --test-threads=1
+*/
+#[cfg(test)]
+mod test_diff {
+    use crate::diff::*;
+    use crate::render::color::{ColorMode, Style};
+    use crate::render::diff::render_diff;
+    use crate::Calldata;
+
+    const TRANSFER_1: &str = "0xa9059cbb000000000000000000000000c02aaa39b223fe8d0a0e5c4f27ead9083c756cc20000000000000000000000000000000000000000000000000de0b6b3a7640000";
+    const TRANSFER_2: &str = "0xa9059cbb000000000000000000000000c02aaa39b223fe8d0a0e5c4f27ead9083c756cc20000000000000000000000000000000000000000000000001bc16d674ec80000";
+
+    #[test]
+    fn test_diff_identical() {
+        let a = Calldata::new(TRANSFER_1).decode();
+        let diff = diff(&a, &a);
+        assert!(diff.is_identical());
+        assert_eq!(diff.changes(), 0);
+    }
+
+    #[test]
+    fn test_diff_changed_param() {
+        let a = Calldata::new(TRANSFER_1).decode();
+        let b = Calldata::new(TRANSFER_2).decode();
+        let diff = diff(&a, &b);
+        assert!(!diff.is_identical());
+        assert_eq!(diff.changes(), 1);
+        assert_eq!(diff.params[0].change, Change::Same);
+        assert_eq!(diff.params[1].change, Change::Changed);
+
+        let rendered = render_diff(&diff, &Style::plain());
+        println!("{}", rendered);
+        let lines = rendered.lines().collect::<Vec<&str>>();
+        assert!(lines[0].starts_with("  0xa9059cbb "));
+        assert!(lines[1].starts_with("  [0] address: 0xC02aaA39b223FE8D0A0e5C4F27eAD9083C756Cc2 "));
+        assert!(lines[2].starts_with("* [1] uint256: 1000000000000000000"));
+        assert!(lines[2].ends_with("| uint256: 2000000000000000000"));
+
+        let colored = render_diff(&diff, &Style::new(ColorMode::Always));
+        assert!(colored.contains("\x1b[33m* [1]"));
+    }
+
+    #[test]
+    fn test_diff_added_call() {
+        let a = Calldata::new(TRANSFER_1).decode();
+        let mut b = a.clone();
+        b.calls.push(a.clone());
+        let diff = diff(&a, &b);
+        assert_eq!(diff.calls.len(), 1);
+        assert_eq!(diff.calls[0].left_selector, None);
+        assert_eq!(diff.calls[0].params[0].change, Change::Added);
+    }
+}
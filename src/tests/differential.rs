@@ -0,0 +1,91 @@
+/*
+cargo test test_differential -- --nocapture --test-threads=1
+*/
+#[cfg(all(test, feature = "ethers"))]
+mod test_differential {
+    use crate::corpus::{MULTICALL, SAFE, TRANSFER};
+    use crate::differential::*;
+    use crate::encoder::encode_call;
+    use crate::Calldata;
+    use ethers::abi::{Abi, HumanReadableParser};
+
+    fn abi() -> Abi {
+        let mut abi = Abi::default();
+        for signature in [
+            "function transfer(address,uint256)",
+            "function multicall(bytes[])",
+            "function mint((address,address,uint24,int24,int24,uint256,uint256,uint256,uint256,address,uint256))",
+            "function refundETH()",
+            "function execTransaction(address,uint256,bytes,uint8,uint256,uint256,uint256,address,address,bytes)",
+        ] {
+            let function = HumanReadableParser::parse_function(signature).unwrap();
+            abi.functions
+                .entry(function.name.clone())
+                .or_default()
+                .push(function);
+        }
+        abi
+    }
+
+    #[test]
+    fn test_differential_corpus() {
+        let abi = abi();
+        let multicall = differential(&Calldata::new(MULTICALL), &abi).unwrap();
+        assert_eq!(multicall.signature, "multicall(bytes[])");
+        assert_eq!((multicall.expected_calls, multicall.found_calls), (2, 2));
+        let nested = multicall.calls.iter().map(|c| c.signature.as_str());
+        assert_eq!(
+            nested.collect::<Vec<_>>(),
+            vec![
+                "mint((address,address,uint24,int24,int24,uint256,uint256,uint256,uint256,address,uint256))",
+                "refundETH()"
+            ]
+        );
+        assert_eq!(multicall.score().words, 12);
+
+        let mut report = AccuracyReport::new();
+        for hex in [TRANSFER, MULTICALL, SAFE] {
+            report.add(&differential(&Calldata::new(hex), &abi).unwrap());
+        }
+        println!("{}", report);
+        assert_eq!(report.compared, 3);
+        assert_eq!(report.total.words, 26);
+        assert_eq!(report.total.top_accuracy(), 1.0);
+        assert_eq!(report.by_type["address"].words, 8);
+        // The Safe's `data` holds a `transfer` too.
+        assert_eq!(report.total.expected_calls, 3);
+    }
+
+    #[test]
+    fn test_differential_disagrees() {
+        // An address of 1 reads as a small number.
+        let hex = encode_call(
+            "transfer(address,uint256)",
+            &["0x0000000000000000000000000000000000000001", "5"],
+        )
+        .unwrap();
+        let compared = differential(&Calldata::new(&hex), &abi()).unwrap();
+        let disagreements = compared.disagreements();
+        assert_eq!(disagreements.len(), 1);
+        assert_eq!(disagreements[0].expected, "address");
+        assert_eq!(disagreements[0].verdict, Verdict::Wrong);
+        assert_eq!(compared.score().top_accuracy(), 0.5);
+    }
+
+    #[test]
+    fn test_differential_unknown_selector() {
+        let abi = abi();
+        let e = differential(&Calldata::new("0x095ea7b3"), &abi).unwrap_err();
+        assert_eq!(e, "no function with selector 0x095ea7b3 in the ABI");
+    }
+
+    #[test]
+    fn test_load_abi() {
+        let path = std::env::temp_dir().join(format!("abi-{}.json", std::process::id()));
+        let artifact = r#"{"abi":[{"type":"function","name":"transfer","inputs":[{"name":"to","type":"address"},{"name":"amount","type":"uint256"}],"outputs":[{"name":"","type":"bool"}],"stateMutability":"nonpayable"}]}"#;
+        std::fs::write(&path, artifact).unwrap();
+        let abi = load_abi(&path).unwrap();
+        std::fs::remove_file(&path).unwrap();
+        assert!(differential(&Calldata::new(TRANSFER), &abi).is_ok());
+    }
+}
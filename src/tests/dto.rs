@@ -0,0 +1,43 @@
+/*
+cargo test test_dto -- --nocapture --test-threads=1
+*/
+#[cfg(test)]
+mod test_dto {
+    use crate::dto::*;
+    use crate::Calldata;
+
+    const MULTICALL: &str = "0xac9650d800000000000000000000000000000000000000000000000000000000000000200000000000000000000000000000000000000000000000000000000000000002000000000000000000000000000000000000000000000000000000000000004000000000000000000000000000000000000000000000000000000000000001e0000000000000000000000000000000000000000000000000000000000000016488316456000000000000000000000000c011a73ee8576fb46f5e1c5751ca3b9fe0af2a6f000000000000000000000000c02aaa39b223fe8d0a0e5c4f27ead9083c756cc20000000000000000000000000000000000000000000000000000000000002710fffffffffffffffffffffffffffffffffffffffffffffffffffffffffffee530ffffffffffffffffffffffffffffffffffffffffffffffffffffffffffff1b18000000000000000000000000000000000000000000000000016345785d89fd6800000000000000000000000000000000000000000000000000007f73eca3063a000000000000000000000000000000000000000000000000016042b530ddaec600000000000000000000000000000000000000000000000000007e59f044bada000000000000000000000000f847e9d51989033b691b8be943f8e9e268f99b9e000000000000000000000000000000000000000000000000000000006377347700000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000412210e8a00000000000000000000000000000000000000000000000000000000";
+
+    #[test]
+    fn test_dto_tree() {
+        let decoded = Calldata::new(MULTICALL).decode();
+        let dto = DecodedCalldataDto::from(&decoded);
+        assert_eq!(dto.selector, "ac9650d8");
+        assert_eq!(dto.signature_guess, decoded.signature_guess);
+        assert_eq!(dto.params.len(), decoded.params.len());
+        assert_eq!((dto.params[0].span_start, dto.params[0].span_end), (4, 36));
+        assert_eq!(dto.params[0].value, "32");
+
+        let nested = &dto.calls[0];
+        assert_eq!(nested.selector, "88316456");
+        assert_eq!(nested.span_start, 164);
+        assert_eq!(
+            nested.params[0].value,
+            "0xC011a73ee8576Fb46F5E1c5751cA3B9Fe0af2a6F"
+        );
+        assert_eq!(nested.params[0].types[0].kind, "Address");
+        assert_eq!(nested.params[0].types[0].solidity, "address");
+        assert_eq!(DecodedCalldataDto::from(decoded), dto);
+    }
+
+    #[test]
+    fn test_dto_warnings() {
+        // The first offset pointing back at the length of a call before it.
+        let decoded = Calldata::new("0xa9059cbb00000000000000000000000000000000000000000000000000000000000000010000000000000000000000000000000000000000000000000000000000000024123456780000000000000000000000000000000000000000000000000000000000000005000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000020").decode();
+        let dto = DecodedCalldataDto::from(&decoded);
+        let json = serde_json::to_value(&decoded.warnings[0]).unwrap();
+        assert_eq!(dto.warnings[0].kind, json["kind"]);
+        assert_eq!(dto.warnings[0].at, 132);
+        assert_eq!(dto.warnings[0].message, decoded.warnings[0].to_string());
+    }
+}
@@ -0,0 +1,79 @@
+/*
+cargo test --features batch,parquet test_dump -- --nocapture --test-threads=1
+*/
+#[cfg(all(test, feature = "batch"))]
+mod test_dump {
+    use crate::corpus::TRANSFER;
+    use crate::dump::*;
+    use crate::Calldata;
+
+    #[test]
+    fn test_csv_dump() {
+        let text = format!(
+            "block_number,HASH,to,input\n\
+             1,0xaa,0xdAC17F958D2ee523a2206206994597C13D831ec7,{}\n\
+             2,0xbb,0x0000000000000000000000000000000000000001,0x\n\
+             3,\"0x\"\"cc\",,0xzz\n",
+            TRANSFER
+        );
+        let rows = read_csv(&text).unwrap();
+        assert_eq!(rows.len(), 3);
+        assert_eq!(rows[2].hash, "0x\"cc");
+        assert_eq!(rows[2].to, None);
+
+        let dump = Dump::new(rows);
+        assert_eq!(dump.rows().len(), 2);
+        let mut seen = vec![];
+        dump.decode(1, |row, result| seen.push((row.hash.clone(), result)));
+        assert_eq!(seen[0].0, "0xaa");
+        assert_eq!(
+            seen[0].1.as_ref().unwrap(),
+            &Calldata::new(TRANSFER).decode()
+        );
+        assert!(seen[1].1.as_ref().unwrap_err().contains("invalid hex"));
+
+        let dump = Dump::new(read_csv(&text).unwrap())
+            .with_to("0xdac17f958d2ee523a2206206994597c13d831ec7");
+        assert_eq!(dump.rows().len(), 1);
+        assert!(read_csv("hash,to\n0xaa,0xbb\n").is_err());
+    }
+
+    #[cfg(feature = "parquet")]
+    #[test]
+    fn test_parquet_dump() {
+        use arrow_array::{ArrayRef, BinaryArray, RecordBatch, StringArray};
+        use parquet::arrow::ArrowWriter;
+        use std::sync::Arc;
+
+        let input = crate::decode_hex(TRANSFER);
+        let batch = RecordBatch::try_from_iter([
+            (
+                "transaction_hash",
+                Arc::new(StringArray::from(vec!["0xaa", "0xbb"])) as ArrayRef,
+            ),
+            (
+                "input",
+                Arc::new(BinaryArray::from(vec![&input[..], &[]])) as ArrayRef,
+            ),
+        ])
+        .unwrap();
+        let path = std::env::temp_dir().join("calldata_decoder_test_dump.parquet");
+        let mut writer =
+            ArrowWriter::try_new(std::fs::File::create(&path).unwrap(), batch.schema(), None)
+                .unwrap();
+        writer.write(&batch).unwrap();
+        writer.close().unwrap();
+
+        let format = DumpFormat::from_path(&path).unwrap();
+        let dump = Dump::open(&path, format).unwrap();
+        assert_eq!(
+            dump.rows(),
+            [DumpRow {
+                hash: "0xaa".to_string(),
+                to: None,
+                input: TRANSFER.to_string(),
+            }]
+        );
+        std::fs::remove_file(path).unwrap();
+    }
+}
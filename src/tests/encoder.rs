@@ -0,0 +1,52 @@
+/*
+cargo test test_encoder -- --nocapture --test-threads=1
+*/
+#[cfg(all(test, feature = "ethers"))]
+mod test_encoder {
+    use crate::encoder::*;
+    use crate::Calldata;
+
+    const TRANSFER: &str = "0xa9059cbb000000000000000000000000c02aaa39b223fe8d0a0e5c4f27ead9083c756cc20000000000000000000000000000000000000000000000000de0b6b3a7640000";
+
+    #[test]
+    fn test_encode_call() {
+        let weth = "0xc02aaa39b223fe8d0a0e5c4f27ead9083c756cc2";
+        for amount in [
+            "1e18",
+            "1000000000000000000",
+            "1 ether",
+            "0xde0b6b3a7640000",
+        ] {
+            let encoded = encode_call("transfer(address,uint256)", &[weth, amount]).unwrap();
+            assert_eq!(encoded, TRANSFER, "{}", amount);
+        }
+
+        // Decoding it again gives the same words back.
+        let encoded = encode_call(
+            "function transfer(address to, uint256 amount)",
+            &[weth, "1e18"],
+        )
+        .unwrap();
+        assert_eq!(Calldata::new(&encoded).decode().params.len(), 2);
+    }
+
+    #[test]
+    fn test_encode_errors() {
+        assert!(encode_call("transfer(address,uint256)", &["0x01"]).is_err());
+        assert!(encode_call("transfer(address,uint256)", &["0xzz", "1"]).is_err());
+        assert!(encode_call(
+            "transfer(address,uint256)",
+            &["0xc02aaa39b223fe8d0a0e5c4f27ead9083c756cc2", "1.5e0"]
+        )
+        .is_err());
+    }
+
+    #[test]
+    fn test_expand_scientific() {
+        assert_eq!(expand_scientific("1e18").unwrap(), "1000000000000000000");
+        assert_eq!(expand_scientific("2.5e3").unwrap(), "2500");
+        assert_eq!(expand_scientific("-1E2").unwrap(), "-100");
+        assert_eq!(expand_scientific("0xe1").unwrap(), "225");
+        assert_eq!(expand_scientific("1.5 ether").unwrap(), "1.5 ether");
+    }
+}
@@ -0,0 +1,140 @@
+/*
+cargo test test_explain -- --nocapture --test-threads=1
+*/
+#[cfg(test)]
+mod test_explain {
+    use crate::corpus::{MULTICALL, TRANSFER};
+    use crate::explain::*;
+    use crate::heuristics::Heuristics;
+    use crate::layout::Fact;
+    use crate::render::color::Style;
+    use crate::render::output::{render_explanation, OutputFormat};
+    use crate::Calldata;
+    use std::collections::BTreeMap;
+
+    fn outcomes(checks: &[Check]) -> Vec<(&'static str, Outcome)> {
+        checks.iter().map(|c| (c.rule, c.outcome)).collect()
+    }
+
+    #[test]
+    fn test_explain_matches_decode() {
+        let calldata = Calldata::new(MULTICALL);
+        let decoded = calldata.decode();
+        let explanation = calldata.explain();
+
+        let calls = decoded
+            .walk_paths()
+            .into_iter()
+            .map(|(path, call)| (path.trim_end_matches('.').to_string(), call))
+            .collect::<BTreeMap<_, _>>();
+        assert_eq!(explanation.calls.len(), calls.len());
+        for trace in &explanation.calls {
+            let call = calls[&trace.path];
+            assert_eq!(trace.selector, call.selector);
+            assert_eq!(trace.at, call.span.start);
+            let types = call.params.iter().map(|p| &p.types).collect::<Vec<_>>();
+            assert_eq!(
+                trace.params.iter().map(|p| &p.types).collect::<Vec<_>>(),
+                types
+            );
+        }
+
+        // Every nested call was taken after its length, and the words around them reasoned about.
+        let fired = |role: Role| {
+            explanation
+                .layout
+                .iter()
+                .filter(|d| d.role == role && d.outcome == Outcome::Fired)
+                .count()
+        };
+        assert_eq!(fired(Role::Call), calls.len() - 1);
+        assert_eq!(
+            fired(Role::Offset),
+            calldata
+                .layout()
+                .facts
+                .iter()
+                .filter(|f| matches!(f, Fact::Offset { .. }))
+                .count()
+        );
+        assert!(explanation
+            .layout
+            .iter()
+            .any(|d| d.role == Role::Offset && d.outcome == Outcome::Rejected));
+        let refund = explanation
+            .calls
+            .iter()
+            .find(|c| c.selector == "12210e8a")
+            .unwrap();
+        let decision = explanation
+            .layout
+            .iter()
+            .find(|d| d.at == refund.at)
+            .unwrap();
+        assert!(
+            decision.reason.contains("known selector +2"),
+            "{}",
+            decision.reason
+        );
+
+        // Left out by the heuristics, the calls are still reasoned about.
+        let calldata = Calldata::with_heuristics(
+            MULTICALL,
+            Heuristics {
+                nested: false,
+                ..Heuristics::all()
+            },
+        );
+        let explanation = calldata.explain();
+        assert_eq!(explanation.calls.len(), 1);
+        assert!(explanation
+            .layout
+            .iter()
+            .any(|d| d.role == Role::Call && d.outcome == Outcome::Disabled));
+    }
+
+    #[test]
+    fn test_explain_checks() {
+        let explanation = Calldata::new(TRANSFER).explain();
+        let params = &explanation.calls[0].params;
+        assert_eq!(
+            outcomes(&params[0].checks),
+            vec![
+                ("selectors", Outcome::Rejected),
+                ("ints", Outcome::Rejected),
+                ("addresses", Outcome::Fired)
+            ]
+        );
+        assert_eq!(params[1].checks.last().unwrap().rule, "fallback");
+        assert_eq!(params[1].types[0].solidity, "uint256");
+
+        // A disabled heuristic says what it would have seen.
+        let heuristics = Heuristics {
+            addresses: false,
+            ..Heuristics::all()
+        };
+        let explanation = Calldata::with_heuristics(TRANSFER, heuristics).explain();
+        let checks = &explanation.calls[0].params[0].checks;
+        assert_eq!(checks[2].outcome, Outcome::Disabled);
+        assert_eq!(checks[2].reason, "12 zero bytes then 20 significant bytes");
+        assert_eq!(checks.last().unwrap().rule, "fallback");
+
+        // A short last word is left as bytes.
+        let explanation = Calldata::new(
+            "0xa9059cbb00000000000000000000000000000000000000000000000000000000000000aabbcc",
+        )
+        .explain();
+        let tail = explanation.calls[0].params.last().unwrap();
+        assert_eq!(outcomes(&tail.checks), vec![("tail", Outcome::Fired)]);
+
+        // One CSV row per decision and check.
+        let explanation = Calldata::new(TRANSFER).explain();
+        let csv = render_explanation(OutputFormat::Csv, &explanation, &Style::plain());
+        let checks = explanation.calls[0]
+            .params
+            .iter()
+            .map(|p| p.checks.len())
+            .sum::<usize>();
+        assert_eq!(csv.lines().count(), 1 + explanation.layout.len() + checks);
+    }
+}
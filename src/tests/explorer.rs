@@ -0,0 +1,67 @@
+/*
+cargo test test_explorer --features tui -- --nocapture --test-threads=1
+*/
+#[cfg(all(test, feature = "tui"))]
+mod test_explorer {
+    use crate::explorer::*;
+    use crate::Calldata;
+    use ratatui::backend::TestBackend;
+    use ratatui::crossterm::event::KeyCode;
+    use ratatui::Terminal;
+
+    const MULTICALL: &str = "0xac9650d800000000000000000000000000000000000000000000000000000000000000200000000000000000000000000000000000000000000000000000000000000002000000000000000000000000000000000000000000000000000000000000004000000000000000000000000000000000000000000000000000000000000001e0000000000000000000000000000000000000000000000000000000000000016488316456000000000000000000000000c011a73ee8576fb46f5e1c5751ca3b9fe0af2a6f000000000000000000000000c02aaa39b223fe8d0a0e5c4f27ead9083c756cc20000000000000000000000000000000000000000000000000000000000002710fffffffffffffffffffffffffffffffffffffffffffffffffffffffffffee530ffffffffffffffffffffffffffffffffffffffffffffffffffffffffffff1b18000000000000000000000000000000000000000000000000016345785d89fd6800000000000000000000000000000000000000000000000000007f73eca3063a000000000000000000000000000000000000000000000000016042b530ddaec600000000000000000000000000000000000000000000000000007e59f044bada000000000000000000000000f847e9d51989033b691b8be943f8e9e268f99b9e000000000000000000000000000000000000000000000000000000006377347700000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000412210e8a00000000000000000000000000000000000000000000000000000000";
+
+    #[test]
+    fn test_expand_collapse() {
+        let mut explorer = Explorer::new(&Calldata::new(MULTICALL));
+        let all = explorer.rows().len();
+        let root = Calldata::new(MULTICALL).decode();
+        let calls = root.walk();
+        assert_eq!(
+            all,
+            calls.len() + calls.iter().map(|c| c.params.len()).sum::<usize>()
+        );
+
+        // Collapsing the root from one of its params hides everything below it.
+        explorer.handle_key(KeyCode::Down);
+        explorer.handle_key(KeyCode::Left);
+        assert_eq!(explorer.rows().len(), 1);
+        assert_eq!(explorer.selected().unwrap().param, None);
+
+        explorer.handle_key(KeyCode::Enter);
+        assert_eq!(explorer.rows().len(), all);
+        assert_eq!(explorer.handle_key(KeyCode::Char('q')), Action::Quit);
+    }
+
+    #[test]
+    fn test_copy_and_spans() {
+        let mut explorer = Explorer::new(&Calldata::new(MULTICALL));
+        let nested = explorer
+            .rows()
+            .iter()
+            .position(|r| r.path == vec![0] && r.param == Some(0))
+            .unwrap();
+        for _ in 0..nested {
+            explorer.handle_key(KeyCode::Down);
+        }
+        let row = explorer.selected().unwrap();
+        assert_eq!(row.value, "0xC011a73ee8576Fb46F5E1c5751cA3B9Fe0af2a6F");
+        assert_eq!(
+            explorer.handle_key(KeyCode::Char('c')),
+            Action::Copy(row.value)
+        );
+
+        // The selected param's bytes are drawn in the hex pane.
+        let mut terminal = Terminal::new(TestBackend::new(200, 40)).unwrap();
+        terminal.draw(|frame| explorer.draw(frame)).unwrap();
+        let screen = format!("{:?}", terminal.backend().buffer());
+        assert!(screen.contains("c011a73ee8576fb46f5e1c57"));
+        assert!(screen.contains("copied 0xC011a73e"));
+    }
+
+    #[test]
+    fn test_osc52() {
+        assert_eq!(osc52("0xab"), "\x1b]52;c;MHhhYg==\x07");
+        assert_eq!(osc52("abc"), "\x1b]52;c;YWJj\x07");
+    }
+}
@@ -0,0 +1,40 @@
+/*
+cargo test --features parquet test_export -- --nocapture --test-threads=1
+*/
+#[cfg(all(test, feature = "parquet"))]
+mod test_export {
+    use crate::export::*;
+    use crate::Calldata;
+    use arrow_array::{Array, StringArray, UInt32Array};
+    use parquet::arrow::arrow_reader::ParquetRecordBatchReaderBuilder;
+
+    const MULTICALL: &str = "0xac9650d800000000000000000000000000000000000000000000000000000000000000200000000000000000000000000000000000000000000000000000000000000002000000000000000000000000000000000000000000000000000000000000004000000000000000000000000000000000000000000000000000000000000001e0000000000000000000000000000000000000000000000000000000000000016488316456000000000000000000000000c011a73ee8576fb46f5e1c5751ca3b9fe0af2a6f000000000000000000000000c02aaa39b223fe8d0a0e5c4f27ead9083c756cc20000000000000000000000000000000000000000000000000000000000002710fffffffffffffffffffffffffffffffffffffffffffffffffffffffffffee530ffffffffffffffffffffffffffffffffffffffffffffffffffffffffffff1b18000000000000000000000000000000000000000000000000016345785d89fd6800000000000000000000000000000000000000000000000000007f73eca3063a000000000000000000000000000000000000000000000000016042b530ddaec600000000000000000000000000000000000000000000000000007e59f044bada000000000000000000000000f847e9d51989033b691b8be943f8e9e268f99b9e000000000000000000000000000000000000000000000000000000006377347700000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000412210e8a00000000000000000000000000000000000000000000000000000000";
+
+    #[test]
+    fn test_record_batch() {
+        let decoded = Calldata::new(MULTICALL).decode();
+        let batch = to_record_batch([("0xbb", &decoded)]).unwrap();
+        assert_eq!(batch.num_rows(), 19 + 11);
+        assert_eq!(batch.num_columns(), schema().fields().len());
+
+        let depth = batch.column(2).as_any().downcast_ref::<UInt32Array>().unwrap();
+        assert_eq!(depth.value(0), 0);
+        assert_eq!(depth.value(19), 1);
+        let selector = batch.column(3).as_any().downcast_ref::<StringArray>().unwrap();
+        assert_eq!(selector.value(19), "0x88316456");
+    }
+
+    #[test]
+    fn test_parquet_round_trip() {
+        let decoded = Calldata::new(MULTICALL).decode();
+        let path = std::env::temp_dir().join("calldata_decoder_test_export.parquet");
+        write_parquet(std::fs::File::create(&path).unwrap(), [("0xbb", &decoded)]).unwrap();
+
+        let reader = ParquetRecordBatchReaderBuilder::try_new(std::fs::File::open(&path).unwrap())
+            .unwrap()
+            .build()
+            .unwrap();
+        let rows = reader.map(|b| b.unwrap().num_rows()).sum::<usize>();
+        assert_eq!(rows, 30);
+    }
+}
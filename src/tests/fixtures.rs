@@ -0,0 +1,78 @@
+/*
+cargo test test_fixtures -- --nocapture --test-threads=1
+*/
+#[cfg(test)]
+mod test_fixtures {
+    use crate::corpus::{MULTICALL, TRANSFER};
+    use crate::fixtures::*;
+    use crate::schema::JsonOutput;
+    use crate::Calldata;
+    use std::path::Path;
+
+    #[test]
+    fn test_fixtures() {
+        let dir = Path::new(env!("CARGO_MANIFEST_DIR")).join("fixtures");
+        assert_eq!(check_dir(&dir), Ok(6));
+    }
+
+    #[test]
+    fn test_fixture_mismatch() {
+        let dir = std::env::temp_dir().join(format!("fixtures-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+
+        let mut fixture = Fixture::new("transfer", TRANSFER);
+        assert_eq!(fixture.check(), Ok(()));
+        fixture.expected.root.params[1].value = "1000".to_string();
+        fixture.save(&dir).unwrap();
+
+        let loaded = load_dir(&dir).unwrap();
+        assert_eq!(loaded, vec![fixture]);
+        assert_eq!(
+            check_dir(&dir),
+            Err(r#"transfer: root.params[1].value: expected "1000", got "1000000000""#.to_string())
+        );
+
+        // Labels and amounts in the CLI's output aren't part of the decode.
+        let mut labelled = Fixture::new("transfer", TRANSFER);
+        labelled.expected.root.params[0].label = Some("WETH".to_string());
+        labelled.save(&dir).unwrap();
+        assert_eq!(check_dir(&dir), Ok(1));
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn test_fixture_golden() {
+        let dir = Path::new(env!("CARGO_MANIFEST_DIR")).join("fixtures/golden");
+        assert_decodes_to(TRANSFER, dir.join("erc20_transfer.txt"));
+
+        let output = JsonOutput::new(&Calldata::new(MULTICALL));
+        let json = canonical_json(&output);
+        assert_eq!(json, canonical_json(&output));
+        assert!(json.starts_with("{\n  \"calldata\": \"0x"));
+        assert!(json.ends_with("}\n"));
+        let text = canonical_text(&output);
+        assert!(text.contains("\nroot.calls[1] "));
+        assert!(text.contains("\nroot.calls[0].params[2] uint256 10000\n"));
+
+        let tmp = std::env::temp_dir().join(format!("golden-{}", std::process::id()));
+        std::fs::create_dir_all(&tmp).unwrap();
+        let (json_path, text_path) = (tmp.join("multicall.json"), tmp.join("multicall.txt"));
+        assert!(check_golden(&output, &json_path)
+            .unwrap_err()
+            .contains("UPDATE_GOLDEN=1"));
+        std::fs::write(&json_path, &json).unwrap();
+        std::fs::write(&text_path, &text).unwrap();
+        assert_eq!(check_golden(&output, &json_path), Ok(()));
+        assert_eq!(check_golden(&output, &text_path), Ok(()));
+
+        // An upstream change is reported where it shows.
+        let mut changed = output.clone();
+        changed.root.calls[0].params[2].value = "1".to_string();
+        let error = check_golden(&changed, &json_path).unwrap_err();
+        assert!(error.ends_with(r#"root.calls[0].params[2].value: expected "10000", got "1""#));
+        let error = check_golden(&changed, &text_path).unwrap_err();
+        assert!(error.contains("line "), "{}", error);
+        assert!(error.ends_with(" 1\""), "{}", error);
+        std::fs::remove_dir_all(&tmp).unwrap();
+    }
+}
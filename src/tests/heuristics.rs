@@ -0,0 +1,150 @@
+/*
+cargo test test_heuristics -- --nocapture --test-threads=1
+*/
+#[cfg(test)]
+mod test_heuristics {
+    use crate::constants::Types;
+    use crate::corpus::TRANSFER;
+    use crate::decoder::Decoder;
+    use crate::explain::Outcome;
+    use crate::heuristics::*;
+    use crate::limits::Limits;
+    use crate::{decode_hex, guess_param_type, word_u64, Calldata};
+
+    const USDT: &str = "000000000000000000000000dac17f958d2ee523a2206206994597c13d831ec7";
+    /// 2023-11-14, a unix timestamp.
+    const TIMESTAMP: &str = "0000000000000000000000000000000000000000000000000000000065535a00";
+
+    /// Types the addresses of a token registry as addresses only.
+    struct Registry(Vec<Vec<u8>>);
+
+    impl Heuristic for Registry {
+        fn name(&self) -> &'static str {
+            "registry"
+        }
+
+        fn inspect(&self, word: &[u8], _cx: &WordContext) -> Option<Guess> {
+            match self.0.iter().any(|token| word.ends_with(token)) {
+                true => Some(Guess::certain(vec![Types::Address])),
+                false => None,
+            }
+        }
+    }
+
+    /// Values between 2017 and 2033 as timestamps, but not for sure.
+    struct Timestamps;
+
+    impl Heuristic for Timestamps {
+        fn name(&self) -> &'static str {
+            "timestamps"
+        }
+
+        fn inspect(&self, word: &[u8], _cx: &WordContext) -> Option<Guess> {
+            match word_u64(word)? {
+                1_500_000_000..=2_000_000_000 => Some(Guess::new(vec![Types::Uint], 0.5)),
+                _ => None,
+            }
+        }
+
+        fn reason(&self, word: &[u8], _cx: &WordContext) -> Option<String> {
+            Some(format!("the value is {:?}", word_u64(word)))
+        }
+    }
+
+    fn registry() -> Registry {
+        Registry(vec![decode_hex(&USDT[24..])])
+    }
+
+    fn guess(chain: &HeuristicChain, word: &str) -> Vec<Types> {
+        let heuristics = Heuristics::default();
+        let types = chain.guess(&decode_hex(word), &WordContext::new(&heuristics));
+        types.types().to_vec()
+    }
+
+    #[test]
+    fn test_builtin_chain() {
+        let chain = HeuristicChain::builtin();
+        assert_eq!(
+            chain.names(),
+            vec![
+                "zero",
+                "max",
+                "selectors",
+                "ints",
+                "addresses",
+                "small_values"
+            ]
+        );
+        for word in [USDT, TIMESTAMP, &"00".repeat(32), &"ff".repeat(32)] {
+            assert_eq!(guess(&chain, word), guess_param_type(word).types());
+        }
+        // Without heuristics, every word falls back.
+        assert_eq!(
+            guess(&HeuristicChain::new(), USDT),
+            vec![Types::Uint, Types::Int, Types::Bytes]
+        );
+    }
+
+    #[test]
+    fn test_heuristic_order() {
+        let chain = HeuristicChain::builtin().without("addresses");
+        assert_eq!(guess(&chain, USDT)[0], Types::Uint);
+
+        // A certain guess ends the chain, so the first heuristic matching wins.
+        let chain = HeuristicChain::builtin().with(registry());
+        assert_eq!(guess(&chain, USDT).len(), 3);
+        let chain = chain.ordered(&["registry"]).unwrap();
+        assert_eq!(chain.names()[0], "registry");
+        assert_eq!(guess(&chain, USDT), vec![Types::Address]);
+        assert!(chain.ordered(&["nope"]).is_err());
+
+        // A tentative guess beats the fallback, not a certain guess.
+        let chain = HeuristicChain::builtin().with_first(Timestamps);
+        assert_eq!(guess(&chain, TIMESTAMP), vec![Types::Uint]);
+        let five = format!("{:064x}", 5);
+        assert_eq!(guess(&chain, &five), vec![Types::Uint8, Types::Bytes1]);
+    }
+
+    #[test]
+    fn test_decoder_heuristic() {
+        let decoder = Decoder::new();
+        let decoded = decoder.decode(TRANSFER).unwrap();
+        assert_eq!(decoded.params[0].types.len(), 3);
+
+        let decoder = Decoder::new().with_heuristic(registry());
+        assert_eq!(decoder.heuristic_chain().names()[0], "registry");
+        let decoded = decoder.decode(TRANSFER).unwrap();
+        assert_eq!(decoded.params[0].types.len(), 1);
+        assert_eq!(decoded.params[0].types[0].solidity, "address");
+
+        // Flags still toggle the built-in heuristics by name.
+        let mut heuristics = Heuristics::all();
+        heuristics.set("small_values", false).unwrap();
+        let decoded = Decoder::new()
+            .with_heuristics(heuristics)
+            .with_heuristic_chain(HeuristicChain::builtin().without("addresses"))
+            .decode(TRANSFER)
+            .unwrap();
+        assert_eq!(decoded.params[0].types[0].solidity, "uint256");
+    }
+
+    #[test]
+    fn test_explain_heuristic() {
+        let chain = HeuristicChain::builtin().with_first(Timestamps);
+        let calldata = format!("0xa9059cbb{}{}", TIMESTAMP, USDT);
+        let bytes = decode_hex(&calldata);
+        let calldata =
+            Calldata::from_bytes_with_chain(bytes, Heuristics::default(), Limits::default(), chain)
+                .unwrap();
+        let explanation = calldata.explain();
+        let checks = &explanation.calls[0].params[0].checks;
+        assert_eq!(checks[0].rule, "timestamps");
+        assert_eq!(checks[0].outcome, Outcome::Fired);
+        assert_eq!(checks.last().unwrap().rule, "small_values");
+        assert_eq!(explanation.calls[0].params[0].types[0].solidity, "uint256");
+
+        let checks = &explanation.calls[0].params[1].checks;
+        assert_eq!(checks[0].outcome, Outcome::Rejected);
+        assert_eq!(checks.last().unwrap().rule, "addresses");
+    }
+}
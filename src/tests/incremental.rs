@@ -0,0 +1,94 @@
+/*
+cargo test test_incremental -- --nocapture --test-threads=1
+*/
+#[cfg(test)]
+mod test_incremental {
+    use crate::constants::WORD;
+    use crate::corpus::{MULTICALL, TRANSFER};
+    use crate::incremental::*;
+    use crate::limits::Limits;
+    use crate::{decode_hex, Calldata};
+
+    #[test]
+    fn test_incremental_chunks() {
+        let bytes = decode_hex(MULTICALL);
+        let whole = Calldata::from_bytes(&bytes).decode();
+        let mut decoder = IncrementalDecoder::new();
+
+        // Nothing until the selector is in.
+        let current = decoder.push(&bytes[..3]).unwrap();
+        assert_eq!(current.root, None);
+        let current = decoder.push(&bytes[3..20]).unwrap();
+        assert_eq!(current.root.as_ref().unwrap().selector, whole.selector);
+        assert!(current.root.as_ref().unwrap().params.is_empty());
+        assert_eq!((current.received, current.decoded), (20, 4));
+
+        // Word by word, each word read as it will be in the whole decode.
+        let mut words = 0;
+        for chunk in bytes[20..].chunks(7) {
+            let current = decoder.push(chunk).unwrap().clone();
+            let root = current.root.unwrap();
+            assert!(root.params.len() >= words);
+            assert!(root.warnings.is_empty());
+            assert_eq!(
+                root.params[..2.min(root.params.len())],
+                whole.params[..2.min(root.params.len())]
+            );
+            words = root.params.len();
+            assert_eq!(current.decoded, 4 + words * WORD);
+            assert!(!current.complete);
+        }
+        // The calls were split out once all of them was in.
+        assert_eq!(decoder.current().root.as_ref().unwrap(), &whole);
+        assert_eq!(decoder.finish().unwrap(), whole);
+    }
+
+    #[test]
+    fn test_incremental_expected() {
+        let bytes = decode_hex(TRANSFER);
+        let whole = Calldata::from_bytes(&bytes).decode();
+        let mut decoder = IncrementalDecoder::new().with_expected_len(bytes.len());
+        for chunk in bytes.chunks(10) {
+            decoder.push(chunk).unwrap();
+        }
+        assert!(decoder.current().complete);
+        assert_eq!(decoder.current().root.as_ref().unwrap(), &whole);
+        assert!(decoder.push(&[0]).is_err());
+        assert_eq!(decoder.finish().unwrap(), whole);
+
+        let mut short = IncrementalDecoder::new().with_expected_len(bytes.len());
+        short.push(&bytes[..40]).unwrap();
+        assert!(short.finish().is_err());
+
+        let limits = Limits {
+            max_input: 16,
+            ..Limits::default()
+        };
+        let mut limited = IncrementalDecoder::new().with_limits(limits);
+        assert!(limited.push(&bytes[..16]).is_ok());
+        assert!(limited.push(&bytes[16..17]).is_err());
+    }
+
+    #[test]
+    fn test_incremental_hex() {
+        let whole = Calldata::new(MULTICALL).decode();
+        let text = format!("0x{}", MULTICALL.trim_start_matches("0x"));
+        // Split mid-prefix and mid-byte.
+        let mut decoder = IncrementalDecoder::new();
+        decoder.push_hex(&text[..1]).unwrap();
+        decoder.push_hex(&text[1..5]).unwrap();
+        assert_eq!(decoder.received().len(), 1);
+        for chunk in text.as_bytes()[5..].chunks(33) {
+            decoder
+                .push_hex(std::str::from_utf8(chunk).unwrap())
+                .unwrap();
+        }
+        assert_eq!(decoder.finish().unwrap(), whole);
+
+        let mut odd = IncrementalDecoder::new();
+        odd.push_hex("a9059cbb 0").unwrap();
+        assert!(odd.finish().unwrap_err().contains("odd"));
+        let error = IncrementalDecoder::new().push_hex("a905zz").unwrap_err();
+        assert!(error.contains('z'), "{}", error);
+    }
+}
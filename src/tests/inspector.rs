@@ -0,0 +1,68 @@
+/*
+cargo test --features revm test_inspector -- --nocapture --test-threads=1
+*/
+#[cfg(all(test, feature = "revm"))]
+mod test_inspector {
+    use crate::inspector::*;
+    use crate::Calldata;
+    use revm::db::InMemoryDB;
+    use revm::inspector_handle_register;
+    use revm::interpreter::opcode::*;
+    use revm::primitives::{address, AccountInfo, Address, Bytecode, Bytes, TxKind};
+    use revm::Evm;
+
+    const TRANSFER: &str = "0xa9059cbb000000000000000000000000c02aaa39b223fe8d0a0e5c4f27ead9083c756cc20000000000000000000000000000000000000000000000000de0b6b3a7640000";
+    const CALLER: Address = address!("1000000000000000000000000000000000000000");
+    const FORWARDER: Address = address!("2000000000000000000000000000000000000000");
+    const TOKEN: Address = address!("3000000000000000000000000000000000000000");
+
+    /// Code calling `TOKEN` with its own calldata.
+    fn forwarder() -> Bytecode {
+        let mut code = vec![CALLDATASIZE, PUSH1, 0, PUSH1, 0, CALLDATACOPY];
+        code.extend([PUSH1, 0, PUSH1, 0, CALLDATASIZE, PUSH1, 0, PUSH1, 0, PUSH20]);
+        code.extend(TOKEN.as_slice());
+        code.extend([GAS, CALL, STOP]);
+        Bytecode::new_raw(Bytes::from(code))
+    }
+
+    #[test]
+    fn test_decoded_frames() {
+        let mut db = InMemoryDB::default();
+        let code = AccountInfo {
+            code: Some(forwarder()),
+            ..Default::default()
+        };
+        db.insert_account_info(FORWARDER, code);
+        let mut evm = Evm::builder()
+            .with_db(db)
+            .with_external_context(DecodingInspector::new())
+            .modify_tx_env(|tx| {
+                tx.caller = CALLER;
+                tx.transact_to = TxKind::Call(FORWARDER);
+                tx.data = TRANSFER.parse().unwrap();
+                tx.gas_limit = 100_000;
+                tx.gas_price = Default::default();
+            })
+            .append_handler_register(inspector_handle_register)
+            .build();
+        evm.transact().unwrap();
+
+        let frames = evm.into_context().external.into_frames();
+        assert_eq!(frames.len(), 1);
+        let decoded = Calldata::new(TRANSFER).decode();
+        let outer = &frames[0];
+        assert_eq!(outer.kind, FrameKind::Call);
+        assert_eq!(outer.caller, CALLER.to_checksum(None));
+        assert_eq!(outer.target, Some(FORWARDER.to_checksum(None)));
+        assert_eq!(outer.decoded.as_ref(), Some(&decoded));
+        assert!(outer.success);
+
+        assert_eq!(outer.calls.len(), 1);
+        let inner = &outer.calls[0];
+        assert_eq!(inner.caller, FORWARDER.to_checksum(None));
+        assert_eq!(inner.target, Some(TOKEN.to_checksum(None)));
+        assert_eq!(inner.value, "0");
+        assert_eq!(inner.decoded.as_ref(), Some(&decoded));
+        assert!(inner.calls.is_empty());
+    }
+}
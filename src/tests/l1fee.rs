@@ -0,0 +1,113 @@
+/*
+cargo test test_l1fee -- --nocapture --test-threads=1
+*/
+#[cfg(test)]
+mod test_l1fee {
+    use crate::corpus::{MULTICALL, TRANSFER};
+    use crate::decode_hex;
+    use crate::l1fee::*;
+    use crate::Calldata;
+    use primitive_types::U256;
+
+    // A signed dynamic-fee transaction, whose FastLZ size op-geth and revm agree on.
+    const SIGNED: &str = "02f901550a758302df1483be21b88304743f94f80e51afb613d764fa61751affd3313c190a86bb870151bd62fd12adb8e41ef24f3f000000000000000000000000000000000000000000000000000000000000006e000000000000000000000000af88d065e77c8cc2239327c5edb3a432268e5831000000000000000000000000000000000000000000000000000000000003c1e5000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000a000000000000000000000000000000000000000000000000000000000000000148c89ed219d02f1a5be012c689b4f5b731827bebe000000000000000000000000c001a033fd89cb37c31b2cba46b6466e040c61fc9b2a3675a7f5f493ebd5ad77c497f8a07cdf65680e238392693019b4092f610222e71b7cec06449cb922b93b6a12744e";
+
+    /// Thousands everywhere, as in revm's tests, and the bytes taken as a whole transaction.
+    fn params(formula: FeeFormula) -> L1FeeParams {
+        L1FeeParams {
+            formula,
+            l1_base_fee: 1_000,
+            blob_base_fee: 1_000,
+            base_fee_scalar: 1_000,
+            blob_base_fee_scalar: 1_000,
+            overhead: 100,
+            scalar: 1_000_000,
+            envelope_bytes: 0,
+        }
+    }
+
+    fn estimate(hex: &str, params: &L1FeeParams) -> L1FeeEstimate {
+        let calldata = Calldata::new(hex);
+        estimate_l1_fee(calldata.bytes(), &calldata.decode(), params)
+    }
+
+    #[test]
+    fn test_fastlz() {
+        assert_eq!(fastlz_compressed_len(&[]), 0);
+        assert_eq!(fastlz_compressed_len(&[0; 1000]), 21);
+        assert_eq!(fastlz_compressed_len(&[42; 1000]), 21);
+        assert_eq!(fastlz_compressed_len(&decode_hex("facade")), 4);
+        assert_eq!(fastlz_compressed_len(&decode_hex(SIGNED)), 202);
+
+        let mut input = vec![];
+        let mut len = 0;
+        for byte in 0..=255u8 {
+            input.push(byte);
+            let next = fastlz_compressed_len(&input);
+            assert!(next > len);
+            len = next;
+        }
+    }
+
+    #[test]
+    fn test_l1_fee_formulas() {
+        // 126387400 * (1000 * 1000 * 16 + 1000 * 1000) / 1e12
+        let fjord = estimate(SIGNED, &params(FeeFormula::Fjord));
+        assert_eq!(fjord.fastlz_size, 202);
+        assert_eq!(fjord.estimated_size, 126);
+        assert_eq!(fjord.fee, U256::from(2148));
+        // Under the 100 byte floor.
+        let small = estimate(TRANSFER, &params(FeeFormula::Fjord));
+        assert_eq!(small.estimated_size, 100);
+        assert_eq!(small.fee, U256::from(1700));
+
+        // 4 + 32 + 32 bytes, 41 of them zero: 27 * 16 + 41 * 4 = 596 gas.
+        let ecotone = estimate(TRANSFER, &params(FeeFormula::Ecotone));
+        assert_eq!((ecotone.bytes, ecotone.zero_bytes), (68, 41));
+        assert_eq!(ecotone.calldata_gas, 596);
+        assert_eq!(ecotone.fee, U256::from(596u64 * 17_000_000 / 16_000_000));
+        let bedrock = estimate(TRANSFER, &params(FeeFormula::Bedrock));
+        assert_eq!(bedrock.fee, U256::from((596 + 100) * 1_000));
+
+        // The envelope is priced as non-zero bytes.
+        let mut enveloped = params(FeeFormula::Ecotone);
+        enveloped.envelope_bytes = 68;
+        let enveloped = estimate(TRANSFER, &enveloped);
+        assert_eq!(enveloped.calldata_gas, 596 + 68 * 16);
+        assert_eq!(enveloped.parts.last().unwrap().path, "envelope");
+    }
+
+    #[test]
+    fn test_l1_fee_parts() {
+        let transfer = estimate(TRANSFER, &params(FeeFormula::Ecotone));
+        let paths = transfer
+            .parts
+            .iter()
+            .map(|p| p.path.as_str())
+            .collect::<Vec<_>>();
+        assert_eq!(paths, vec!["selector", "params[0]", "params[1]"]);
+        assert_eq!(transfer.parts[0].calldata_gas, 64);
+        assert_eq!(transfer.parts[1].zero_bytes, 12);
+
+        // Nested calls own their bytes, so no byte is counted twice.
+        let multicall = estimate(MULTICALL, &L1FeeParams::default());
+        let bytes = multicall.parts.iter().map(|p| p.bytes).sum::<usize>();
+        assert_eq!(bytes, multicall.bytes);
+        let gas = multicall.parts.iter().map(|p| p.calldata_gas).sum::<u64>();
+        assert_eq!(gas, multicall.calldata_gas);
+        assert!(multicall
+            .parts
+            .iter()
+            .any(|p| p.path == "calls[0].selector"));
+        assert!(multicall
+            .parts
+            .iter()
+            .any(|p| p.path == "calls[1].selector"));
+        assert!(!multicall.parts.iter().any(|p| p.path == "params[5]"));
+        let fees = multicall
+            .parts
+            .iter()
+            .fold(U256::zero(), |sum, p| sum + p.fee);
+        assert!(fees <= multicall.fee && fees + multicall.parts.len() >= multicall.fee);
+    }
+}
@@ -0,0 +1,219 @@
+/*
+cargo test test_layout -- --nocapture --test-threads=1
+*/
+#[cfg(test)]
+mod test_layout {
+    use crate::constants::Types;
+    use crate::decoded::{DecodeStatus, DecodeWarning};
+    #[cfg(feature = "ethers")]
+    use crate::encoder::encode_call;
+    use crate::heuristics::{Heuristics, Profile};
+    use crate::layout::{call_score, CallFact, Fact, MIN_CALL_SCORE};
+    use crate::Calldata;
+
+    /// multicall(bytes[]) of 2 calls, the words after the selector not aligned to it.
+    const MULTICALL: &str = "0xac9650d800000000000000000000000000000000000000000000000000000000000000200000000000000000000000000000000000000000000000000000000000000002000000000000000000000000000000000000000000000000000000000000004000000000000000000000000000000000000000000000000000000000000001e0000000000000000000000000000000000000000000000000000000000000016488316456000000000000000000000000c011a73ee8576fb46f5e1c5751ca3b9fe0af2a6f000000000000000000000000c02aaa39b223fe8d0a0e5c4f27ead9083c756cc20000000000000000000000000000000000000000000000000000000000002710fffffffffffffffffffffffffffffffffffffffffffffffffffffffffffee530ffffffffffffffffffffffffffffffffffffffffffffffffffffffffffff1b18000000000000000000000000000000000000000000000000016345785d89fd6800000000000000000000000000000000000000000000000000007f73eca3063a000000000000000000000000000000000000000000000000016042b530ddaec600000000000000000000000000000000000000000000000000007e59f044bada000000000000000000000000f847e9d51989033b691b8be943f8e9e268f99b9e000000000000000000000000000000000000000000000000000000006377347700000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000412210e8a00000000000000000000000000000000000000000000000000000000";
+
+    #[test]
+    fn test_layout_facts() {
+        let calldata = Calldata::new(MULTICALL);
+        let layout = calldata.layout();
+        let skeleton = layout
+            .facts
+            .iter()
+            .filter(|fact| !matches!(fact, Fact::Call { .. }))
+            .cloned()
+            .collect::<Vec<Fact>>();
+        assert_eq!(
+            skeleton,
+            vec![
+                Fact::Offset { at: 4, value: 32 },
+                Fact::Offset { at: 68, value: 64 },
+                Fact::Offset {
+                    at: 100,
+                    value: 480
+                },
+                Fact::Length {
+                    at: 132,
+                    value: 356
+                },
+                Fact::Padding { at: 520 },
+                Fact::Length { at: 548, value: 4 },
+            ]
+        );
+
+        let calls = layout.calls().collect::<Vec<CallFact>>();
+        assert_eq!(calls.len(), 2);
+        assert_eq!((calls[0].at, calls[0].parent), (164, None));
+        assert_eq!(calls[0].selector, [0x88, 0x31, 0x64, 0x56]);
+        assert_eq!(calls[0].body.len(), 352);
+        assert_eq!(calls[1].at, 580);
+        assert!(calls[1].body.is_empty());
+
+        // The tree takes its spans from the table.
+        let root = calldata.decode();
+        assert_eq!(root.calls[0].span.start, 164);
+        assert_eq!(root.calls[1].span.start, 580);
+    }
+
+    #[test]
+    fn test_layout_without_nesting() {
+        let heuristics = Heuristics {
+            nested: false,
+            ..Heuristics::default()
+        };
+        let layout = Calldata::with_heuristics(MULTICALL, heuristics)
+            .layout()
+            .clone();
+        assert_eq!(layout.calls().count(), 0);
+        assert!(layout.facts.contains(&Fact::Offset { at: 4, value: 32 }));
+    }
+
+    #[test]
+    fn test_offset_bounds() {
+        // `transfer(to, 512)`: an amount past 64 bytes of params is just an amount.
+        let hex = "0xa9059cbb00000000000000000000000000000000000000000000000000000000000000200000000000000000000000000000000000000000000000000000000000000200";
+        let calldata = Calldata::new(hex);
+        assert_eq!(
+            calldata.layout().facts,
+            vec![Fact::Offset { at: 4, value: 32 }]
+        );
+        let root = calldata.decode();
+        assert!(root.warnings.is_empty());
+        assert_eq!(root.status(), DecodeStatus::Decoded);
+
+        // An offset to a call, then one 512 bytes into 160 bytes of params.
+        let hex = "0xac9650d800000000000000000000000000000000000000000000000000000000000000400000000000000000000000000000000000000000000000000000000000000200000000000000000000000000000000000000000000000000000000000000002412345678000000000000000000000000000000000000000000000000000000000000000500000000000000000000000000000000000000000000000000000000";
+        let calldata = Calldata::new(hex);
+        let warning = DecodeWarning::OffsetOutOfBounds {
+            at: 36,
+            offset: 512,
+            len: 160,
+        };
+        assert!(calldata.layout().facts.contains(&Fact::Warning {
+            parent: None,
+            warning: warning.clone()
+        }));
+        let root = calldata.decode();
+        assert_eq!(root.calls.len(), 1);
+        assert_eq!(root.warnings, vec![warning]);
+        assert_eq!(root.status(), DecodeStatus::Partial);
+
+        // The first word of the params pointing at their end.
+        let root = Calldata::new("0x1234567800000000000000000000000000000000000000000000000000000000000000400000000000000000000000000000000000000000000000000000000000000005").decode();
+        assert_eq!(
+            root.warnings,
+            vec![DecodeWarning::OffsetOutOfBounds {
+                at: 4,
+                offset: 64,
+                len: 64
+            }]
+        );
+
+        // The second element of a two-element array of offsets, 1024 bytes into 128.
+        let root = Calldata::new("0x123456780000000000000000000000000000000000000000000000000000000000000020000000000000000000000000000000000000000000000000000000000000000200000000000000000000000000000000000000000000000000000000000000400000000000000000000000000000000000000000000000000000000000000400").decode();
+        assert_eq!(
+            root.warnings,
+            vec![DecodeWarning::OffsetOutOfBounds {
+                at: 100,
+                offset: 1024,
+                len: 128
+            }]
+        );
+    }
+
+    #[test]
+    fn test_irregular_warnings() {
+        // `transfer(to, 32)`: an amount pointing at its own word is just an amount.
+        let calldata = Calldata::new("0xa9059cbb000000000000000000000000000000000000000000000000000000000000000100000000000000000000000000000000000000000000000000000000000000200000000000000000000000000000000000000000000000000000000000000005");
+        assert!(calldata.decode().warnings.is_empty());
+
+        // The first offset pointing back at the length of a call before it.
+        let calldata = Calldata::new("0xa9059cbb00000000000000000000000000000000000000000000000000000000000000010000000000000000000000000000000000000000000000000000000000000024123456780000000000000000000000000000000000000000000000000000000000000005000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000020");
+        let root = calldata.decode();
+        assert_eq!(root.calls.len(), 1);
+        assert_eq!(
+            root.warnings,
+            vec![DecodeWarning::NonCanonicalOffset {
+                at: 132,
+                offset: 32
+            }]
+        );
+
+        // Three bytes after the last word.
+        let calldata = Calldata::new(
+            "0xa9059cbb0000000000000000000000000000000000000000000000000000000000000001abcdef",
+        );
+        let root = calldata.decode();
+        assert_eq!(
+            root.warnings,
+            vec![DecodeWarning::UnparsedTail { at: 36, len: 3 }]
+        );
+        assert_eq!(root.params[1].raw, "abcdef");
+        assert_eq!(root.status(), DecodeStatus::Partial);
+
+        // A call of one word whose padding ends in 0xff.
+        let calldata = Calldata::new("0xac9650d800000000000000000000000000000000000000000000000000000000000000200000000000000000000000000000000000000000000000000000000000000024123456780000000000000000000000000000000000000000000000000000000000000005000000000000000000000000000000000000000000000000000000ff");
+        let root = calldata.decode();
+        assert_eq!(root.calls.len(), 1);
+        assert_eq!(
+            root.warnings,
+            vec![DecodeWarning::PaddingMismatch { at: 104, len: 28 }]
+        );
+        assert_eq!(root.status(), DecodeStatus::Partial);
+    }
+
+    #[test]
+    fn test_call_score() {
+        let zeros = [0u8; 28];
+        let refund_eth = [0x12, 0x21, 0x0e, 0x8a];
+        assert_eq!(call_score(&refund_eth, 4, &zeros), 3);
+        // Unknown and lone, kept only when it isn't text and is padded.
+        assert_eq!(call_score(&[0x12, 0x34, 0x56, 0x78], 4, &zeros), 1);
+        assert!(call_score(b"USDC", 4, &zeros) < MIN_CALL_SCORE);
+        assert!(call_score(&[0x12, 0x34, 0x56, 0x78], 4, &[1; 28]) < MIN_CALL_SCORE);
+        assert_eq!(call_score(&[0x12, 0x34, 0x56, 0x78], 36, &[1; 28]), 0);
+    }
+
+    #[test]
+    #[cfg(feature = "ethers")]
+    fn test_short_string_not_a_call() {
+        let hex = encode_call("symbol(string)", &["USDC"]).unwrap();
+        let calldata = Calldata::new(&hex);
+        assert_eq!(calldata.layout().calls().count(), 0);
+        assert!(calldata.decode().calls.is_empty());
+    }
+
+    #[test]
+    fn test_vyper_profile() {
+        // execute(uint256[2],address,bytes) of a transfer, the array's first entry unused.
+        let hex = concat!(
+            "0xebdd73e0",
+            "0000000000000000000000000000000000000000000000000000000000000000",
+            "0000000000000000000000000000000000000000000000000000000000000005",
+            "000000000000000000000000dac17f958d2ee523a2206206994597c13d831ec7",
+            "0000000000000000000000000000000000000000000000000000000000000080",
+            "0000000000000000000000000000000000000000000000000000000000000044",
+            "a9059cbb000000000000000000000000dac17f958d2ee523a2206206994597c1",
+            "3d831ec700000000000000000000000000000000000000000000000000000000",
+            "3b9aca0000000000000000000000000000000000000000000000000000000000",
+        );
+        // The zero entry re-aligns the words after it, hiding the transfer.
+        let solidity = Calldata::new(hex);
+        assert!(solidity.layout().facts.contains(&Fact::Padding { at: 4 }));
+        assert!(solidity.decode().calls.is_empty());
+
+        let vyper = Calldata::with_heuristics(hex, Heuristics::all().with_profile(Profile::Vyper));
+        assert!(!vyper
+            .layout()
+            .facts
+            .iter()
+            .any(|f| matches!(f, Fact::Padding { .. })));
+        let root = vyper.decode();
+        assert_eq!(root.calls.len(), 1);
+        assert_eq!(root.calls[0].selector, "a9059cbb");
+        // Small values are Curve-style `int128` indices.
+        assert_eq!(root.params[1].types[0].kind, Types::Int128);
+        assert_eq!(root.params[1].value, "5");
+    }
+}
@@ -0,0 +1,44 @@
+/*
+cargo test test_lazy -- --nocapture --test-threads=1
+*/
+#[cfg(test)]
+mod test_lazy {
+    use crate::lazy::LazyCall;
+    use crate::Calldata;
+
+    const MULTICALL: &str = "0xac9650d800000000000000000000000000000000000000000000000000000000000000200000000000000000000000000000000000000000000000000000000000000002000000000000000000000000000000000000000000000000000000000000004000000000000000000000000000000000000000000000000000000000000001e0000000000000000000000000000000000000000000000000000000000000016488316456000000000000000000000000c011a73ee8576fb46f5e1c5751ca3b9fe0af2a6f000000000000000000000000c02aaa39b223fe8d0a0e5c4f27ead9083c756cc20000000000000000000000000000000000000000000000000000000000002710fffffffffffffffffffffffffffffffffffffffffffffffffffffffffffee530ffffffffffffffffffffffffffffffffffffffffffffffffffffffffffff1b18000000000000000000000000000000000000000000000000016345785d89fd6800000000000000000000000000000000000000000000000000007f73eca3063a000000000000000000000000000000000000000000000000016042b530ddaec600000000000000000000000000000000000000000000000000007e59f044bada000000000000000000000000f847e9d51989033b691b8be943f8e9e268f99b9e000000000000000000000000000000000000000000000000000000006377347700000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000412210e8a00000000000000000000000000000000000000000000000000000000";
+    const NESTED_TWICE: &str = "0xac9650d80000000000000000000000000000000000000000000000000000000000000044aabbccdd000000000000000000000000000000000000000000000000000000000000000412210e8a00000000000000000000000000000000000000000000000000000000";
+
+    #[test]
+    fn test_lazy_matches_decode() {
+        for calldata in [MULTICALL, NESTED_TWICE, "0xa9059cbb", "0xa9059cbb7b16", ""] {
+            let lazy = LazyCall::new(calldata);
+            assert_eq!(lazy.decode(), Calldata::new(calldata).decode());
+        }
+    }
+
+    #[test]
+    fn test_lazy_parses_on_access() {
+        let root = LazyCall::new(NESTED_TWICE);
+        assert_eq!(root.selector(), [0xac, 0x96, 0x50, 0xd8]);
+        assert!(!root.is_parsed());
+
+        assert_eq!(root.calls().len(), 1);
+        assert!(root.is_parsed());
+        let nested = &root.calls()[0];
+        assert_eq!(
+            (nested.selector(), nested.start()),
+            (&[0xaa, 0xbb, 0xcc, 0xdd][..], 36)
+        );
+        assert!(!nested.is_parsed());
+        assert_eq!(nested.node().params.len(), 2);
+
+        // Stops looking once a selector matches.
+        let root = LazyCall::new(NESTED_TWICE);
+        assert!(root.any_selector(|s| s == [0xac, 0x96, 0x50, 0xd8]));
+        assert!(!root.is_parsed());
+        assert!(root.any_selector(|s| s == [0x12, 0x21, 0x0e, 0x8a]));
+        assert!(root.calls()[0].is_parsed());
+        assert!(!root.any_selector(|s| s == [0xde, 0xad, 0xbe, 0xef]));
+    }
+}
@@ -0,0 +1,240 @@
+/*
+cargo test test_limits -- --nocapture --test-threads=1
+*/
+#[cfg(test)]
+mod test_limits {
+    use crate::arena::DecodeArena;
+    use crate::builder::CalldataBuilder;
+    use crate::decoded::{DecodeStatus, DecodeWarning};
+    use crate::heuristics::Heuristics;
+    use crate::lazy::LazyCall;
+    use crate::limits::Limits;
+    use crate::render::tree::render_tree;
+    use crate::Calldata;
+    use primitive_types::H160;
+    use proptest::prelude::*;
+
+    /// 0xac9650d8 // fn selector
+    /// 0000000000000000000000000000000000000000000000000000000000000044 // length of call
+    /// aabbccdd // nested fn selector
+    /// 0000000000000000000000000000000000000000000000000000000000000004 // length of call
+    /// 12210e8a // nested fn selector, inside the nested call
+    /// 00000000000000000000000000000000000000000000000000000000 // padding
+    const NESTED_TWICE: &str = "0xac9650d80000000000000000000000000000000000000000000000000000000000000044aabbccdd000000000000000000000000000000000000000000000000000000000000000412210e8a00000000000000000000000000000000000000000000000000000000";
+
+    #[test]
+    fn test_nested_depth() {
+        let calldata = Calldata::new(NESTED_TWICE);
+        let root = calldata.decode();
+        assert_eq!(root.calls.len(), 1);
+        assert_eq!(root.calls[0].selector, "aabbccdd");
+        assert_eq!(root.calls[0].span.start, 36);
+        assert_eq!(root.calls[0].calls.len(), 1);
+        assert_eq!(root.calls[0].calls[0].selector, "12210e8a");
+        assert_eq!(root.calls[0].calls[0].span.start, 72);
+
+        let mut arena = DecodeArena::new();
+        let id = arena.decode(&calldata);
+        assert_eq!(arena.call(id).to_decoded(), root);
+    }
+
+    #[test]
+    fn test_nested_limits() {
+        let shallow = Limits {
+            max_depth: 1,
+            ..Limits::default()
+        };
+        let root = Calldata::with_limits(NESTED_TWICE, Heuristics::default(), shallow)
+            .unwrap()
+            .decode();
+        assert_eq!(root.calls.len(), 1);
+        assert!(root.calls[0].calls.is_empty());
+
+        let few = Limits {
+            max_calls: 1,
+            ..Limits::default()
+        };
+        let root = Calldata::with_limits(NESTED_TWICE, Heuristics::default(), few)
+            .unwrap()
+            .decode();
+        assert_eq!(root.walk().len(), 2);
+
+        let none = Limits {
+            max_depth: 0,
+            ..Limits::default()
+        };
+        let root = Calldata::with_limits(NESTED_TWICE, Heuristics::default(), none)
+            .unwrap()
+            .decode();
+        assert!(root.calls.is_empty());
+    }
+
+    #[test]
+    fn test_nested_deep() {
+        let transfer = CalldataBuilder::new("transfer(address,uint256)")
+            .address(H160::repeat_byte(0x11))
+            .uint(1u64);
+        let inner = CalldataBuilder::multicall(&[transfer.clone(), transfer]);
+        let outer = CalldataBuilder::multicall(&[inner]);
+        let execute = CalldataBuilder::new("execute(address,uint256,bytes)")
+            .address(H160::repeat_byte(0x22))
+            .uint(1u64)
+            .call(&outer)
+            .build()
+            .unwrap();
+
+        // Found by the heuristics alone, every level down.
+        let root = Calldata::from_bytes(&execute).decode();
+        let selectors = root
+            .walk()
+            .iter()
+            .map(|c| c.selector.as_str())
+            .collect::<Vec<_>>();
+        assert_eq!(
+            selectors,
+            vec!["b61d27f6", "ac9650d8", "ac9650d8", "a9059cbb", "a9059cbb"]
+        );
+        assert_eq!(root.calls[0].calls[0].calls.len(), 2);
+
+        let limits = Limits {
+            max_depth: 2,
+            ..Limits::default()
+        };
+        let root = Calldata::from_bytes_with_limits(execute, Heuristics::default(), limits)
+            .unwrap()
+            .decode();
+        assert_eq!(root.walk().len(), 3);
+    }
+
+    #[test]
+    fn test_size_limits() {
+        let tiny = Limits {
+            max_input: 8,
+            ..Limits::default()
+        };
+        let err = Calldata::with_limits(NESTED_TWICE, Heuristics::default(), tiny).unwrap_err();
+        assert!(err.contains("`max_input`"), "{}", err);
+
+        // The outer call declares 0x44 bytes, over the 0x40 honored.
+        let short = Limits {
+            max_region: 0x40,
+            ..Limits::default()
+        };
+        let root = Calldata::with_limits(NESTED_TWICE, Heuristics::default(), short)
+            .unwrap()
+            .decode();
+        assert!(root.calls.is_empty());
+
+        let nodes = Calldata::new(NESTED_TWICE).node_count();
+        let few = Limits {
+            max_nodes: nodes - 1,
+            ..Limits::default()
+        };
+        let err = Calldata::with_limits(NESTED_TWICE, Heuristics::default(), few).unwrap_err();
+        assert!(err.contains("`max_nodes`"), "{}", err);
+        let enough = Limits {
+            max_nodes: nodes,
+            ..Limits::default()
+        };
+        assert!(Calldata::with_limits(NESTED_TWICE, Heuristics::default(), enough).is_ok());
+    }
+
+    /// 0xac9650d8 // fn selector
+    /// 0000000000000000000000000000000000000000000000000000000040000004 // length of call, 2^30 + 4
+    /// aabbccdd // nested fn selector
+    /// 00000000000000000000000000000000000000000000000000000000 // padding
+    const ABSURD_LENGTH: &str = "0xac9650d80000000000000000000000000000000000000000000000000000000040000004aabbccdd00000000000000000000000000000000000000000000000000000000";
+
+    #[test]
+    fn test_absurd_length() {
+        let calldata = Calldata::new(ABSURD_LENGTH);
+        let root = calldata.decode();
+        assert!(root.calls.is_empty());
+        assert_eq!(root.params.len(), 2);
+        assert_eq!(
+            root.warnings,
+            vec![DecodeWarning::LengthOutOfBounds {
+                at: 4,
+                declared: (1 << 30) + 4,
+                remaining: 32,
+            }]
+        );
+        assert_eq!(root.status(), DecodeStatus::Partial);
+        assert!(render_tree(&root).contains("! length at byte 4 declares 1073741828 bytes"));
+
+        let mut arena = DecodeArena::new();
+        let id = arena.decode(&calldata);
+        assert_eq!(arena.call(id).to_decoded(), root);
+        assert_eq!(LazyCall::new(ABSURD_LENGTH).decode(), root);
+    }
+
+    /// 0xac9650d8 // fn selector
+    /// 000000000000000000000000000000000000000000000000ffffffffffffffe4 // length of call, near `u64::MAX`
+    /// aabbccdd // nested fn selector
+    /// 00000000000000000000000000000000000000000000000000000000 // padding
+    const OVERFLOWING_LENGTH: &str = "0xac9650d8000000000000000000000000000000000000000000000000ffffffffffffffe4aabbccdd00000000000000000000000000000000000000000000000000000000";
+
+    #[test]
+    fn test_overflowing_length() {
+        let calldata = Calldata::new(OVERFLOWING_LENGTH);
+        let root = calldata.decode();
+        assert!(root.calls.is_empty());
+        assert_eq!(
+            root.warnings,
+            vec![DecodeWarning::LengthOutOfBounds {
+                at: 4,
+                declared: 0xffffffffffffffe4,
+                remaining: 32,
+            }]
+        );
+
+        let mut arena = DecodeArena::new();
+        let id = arena.decode(&calldata);
+        assert_eq!(arena.call(id).to_decoded(), root);
+        assert_eq!(LazyCall::new(OVERFLOWING_LENGTH).decode(), root);
+    }
+
+    /// Words a parser doing unchecked math on them would overflow with.
+    fn hostile_word() -> impl Strategy<Value = [u8; 32]> {
+        let tail = |v: u64| {
+            let mut word = [0u8; 32];
+            word[24..].copy_from_slice(&v.to_be_bytes());
+            word
+        };
+        prop_oneof![
+            Just([0u8; 32]),
+            Just([0xff; 32]),
+            (0..=u64::MAX).prop_map(tail),
+            // Lengths of a call, a selector more than whole words.
+            (0..=u64::MAX / 32).prop_map(move |v| tail(v * 32 + 4)),
+            (0..=u64::MAX / 32).prop_map(move |v| tail(v * 32)),
+            any::<[u8; 4]>().prop_map(|selector| {
+                let mut word = [0u8; 32];
+                word[..4].copy_from_slice(&selector);
+                word
+            }),
+            any::<[u8; 32]>(),
+        ]
+    }
+
+    proptest! {
+        #![proptest_config(ProptestConfig::with_cases(256))]
+
+        #[test]
+        fn test_hostile_words(
+            words in prop::collection::vec(hostile_word(), 0..12),
+            cut in 0..32usize,
+        ) {
+            let mut bytes = vec![0xac, 0x96, 0x50, 0xd8];
+            bytes.extend(words.concat());
+            bytes.truncate(bytes.len().saturating_sub(cut).max(4));
+
+            let calldata = Calldata::from_bytes(&bytes);
+            let root = calldata.decode();
+            let mut arena = DecodeArena::new();
+            let id = arena.decode(&calldata);
+            prop_assert_eq!(arena.call(id).to_decoded(), root.clone());
+            prop_assert_eq!(LazyCall::from_bytes(bytes.clone()).decode(), root);
+        }
+    }
+}
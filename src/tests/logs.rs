@@ -0,0 +1,151 @@
+/*
+cargo test test_logs -- --nocapture --test-threads=1
+*/
+#[cfg(test)]
+mod test_logs {
+    use crate::logs::*;
+    use crate::render::tree::render_log;
+
+    const TRANSFER_TOPIC: &str =
+        "0xddf252ad1be2c89b69c2b068fc378daa952ba7f163c4a11628f55a4df523b3ef";
+    const FROM: &str = "0x000000000000000000000000c02aaa39b223fe8d0a0e5c4f27ead9083c756cc2";
+    const ZERO: &str = "0x0000000000000000000000000000000000000000000000000000000000000000";
+    const AMOUNT: &str = "0x0000000000000000000000000000000000000000000000000de0b6b3a7640000";
+
+    #[test]
+    fn test_event_signatures() {
+        let event =
+            EventSignature::parse("Transfer(address indexed from, address indexed to, uint)")
+                .unwrap();
+        assert_eq!(event.signature, "Transfer(address,address,uint256)");
+        assert_eq!(event.indexed, vec![true, true, false]);
+        assert_eq!(event.topic0(), &TRANSFER_TOPIC[2..]);
+        assert_eq!(event.topic_types(), vec!["address", "address"]);
+        assert_eq!(event.data_head_types(), vec!["uint256"]);
+
+        let event = EventSignature::parse("Named(string indexed,(uint8,bool)[2])").unwrap();
+        assert_eq!(event.topic_types(), vec!["bytes32"]);
+        assert_eq!(
+            event.data_head_types(),
+            vec!["uint8", "bool", "uint8", "bool"]
+        );
+        assert!(EventSignature::parse("Transfer(adress indexed)").is_none());
+
+        let events = EventSignatures::builtin();
+        assert_eq!(events.resolve(TRANSFER_TOPIC).len(), 2);
+        assert!(events.resolve(ZERO).is_empty());
+        assert!(EventSignatures::from_text("Foo(uint256)\n# comment\nnot an event").is_err());
+    }
+
+    #[test]
+    fn test_known_event() {
+        // An ERC-20 transfer from the WETH address to the zero address.
+        let log = EventLog::from_hex(&[TRANSFER_TOPIC, FROM, ZERO], AMOUNT).unwrap();
+        let decoded = LogDecoder::new().decode(&log).unwrap();
+        println!("{}", render_log(&decoded));
+        assert_eq!(decoded.topic0.as_deref(), Some(&TRANSFER_TOPIC[2..]));
+        assert_eq!(decoded.events, vec!["Transfer(address,address,uint256)"]);
+        assert_eq!(decoded.signature_guess, "Transfer(address,address,uint256)");
+        assert_eq!(decoded.topics[0].index, 1);
+        assert_eq!(
+            decoded.topics[0].value,
+            "0xC02aaA39b223FE8D0A0e5C4F27eAD9083C756Cc2"
+        );
+        // The zero word is rendered as the declared address.
+        assert_eq!(decoded.topics[1].types[0].solidity, "address");
+        assert_eq!(
+            decoded.topics[1].value,
+            "0x0000000000000000000000000000000000000000"
+        );
+        assert_eq!(decoded.params[0].value, "1000000000000000000");
+        assert_eq!(
+            (decoded.params[0].span.start, decoded.params[0].span.end),
+            (0, 32)
+        );
+
+        // ERC-721 transfers index the token id too.
+        let log = EventLog::from_hex(&[TRANSFER_TOPIC, FROM, ZERO, AMOUNT], "0x").unwrap();
+        let decoded = LogDecoder::new().decode(&log).unwrap();
+        assert_eq!(decoded.topics[2].types[0].solidity, "uint256");
+        assert!(decoded.params.is_empty());
+    }
+
+    #[test]
+    fn test_unknown_event() {
+        // Topic0 unknown: an address topic, and a `bytes` holding a transfer call.
+        let topic0 = "0x1111111111111111111111111111111111111111111111111111111111111111";
+        let data = concat!(
+            "0x0000000000000000000000000000000000000000000000000000000000000020",
+            "0000000000000000000000000000000000000000000000000000000000000044",
+            "a9059cbb000000000000000000000000c02aaa39b223fe8d0a0e5c4f27ead9083c756cc2",
+            "0000000000000000000000000000000000000000000000000de0b6b3a7640000",
+            "00000000000000000000000000000000000000000000000000000000",
+        );
+        let log = EventLog::from_hex(&[topic0, FROM], data).unwrap();
+        let decoded = LogDecoder::new().decode(&log).unwrap();
+        println!("{}", render_log(&decoded));
+        assert!(decoded.events.is_empty());
+        assert!(decoded
+            .signature_guess
+            .starts_with("event_11111111(address indexed,"));
+        assert_eq!(decoded.calls.len(), 1);
+        assert_eq!(decoded.calls[0].selector, "a9059cbb");
+        assert_eq!(decoded.calls[0].span.start, 64);
+
+        let anonymous = LogDecoder::new()
+            .decode(&EventLog::from_hex(&[] as &[&str], AMOUNT).unwrap())
+            .unwrap();
+        assert_eq!(anonymous.signature_guess, "anonymous(uint256)");
+        assert_eq!(anonymous.topic0, None);
+    }
+
+    #[test]
+    fn test_log_errors() {
+        assert!(EventLog::from_hex(&["0x01"], "")
+            .unwrap_err()
+            .contains("not 32"));
+        assert!(EventLog::from_hex(&[ZERO], "0xzz")
+            .unwrap_err()
+            .starts_with("data:"));
+        assert!(EventLog::from_hex(&[ZERO; 5], "").is_err());
+        assert_eq!(
+            EventLog::from_hex(&[ZERO], "").unwrap().data,
+            Vec::<u8>::new()
+        );
+    }
+
+    #[test]
+    fn test_log_data() {
+        let log = LogData::new(&[TRANSFER_TOPIC, FROM, ZERO], AMOUNT).unwrap();
+        assert_eq!(log.log().topics.len(), 3);
+        assert_eq!(
+            log.decode().unwrap(),
+            LogDecoder::new().decode(log.log()).unwrap()
+        );
+
+        // Unknown event: the topics are typed as addresses, uints and hashes.
+        let topic0 = "0x1111111111111111111111111111111111111111111111111111111111111111";
+        let hash = "0x3f2a6bb4f2d9fa2b8f5e4f0b1a1f8cbe7c1b5c1e2d6a4c9b0f1e2d3c4b5a6978";
+        let id = format!("0x{:064x}", 5);
+        let decoded = LogData::new(&[topic0, FROM, hash, &id], "0x")
+            .unwrap()
+            .decode()
+            .unwrap();
+        let topics = decoded
+            .topics
+            .iter()
+            .map(|t| t.types[0].solidity.as_str())
+            .collect::<Vec<_>>();
+        assert_eq!(topics, vec!["address", "bytes32", "uint8"]);
+        assert_eq!(decoded.topics[1].value, hash);
+        assert!(decoded.signature_guess.contains("bytes32 indexed"));
+
+        let decoded = LogData::new(&[topic0, hash], "0x")
+            .unwrap()
+            .with_decoder(LogDecoder::new().with_events(EventSignatures::new()))
+            .decode()
+            .unwrap();
+        assert_eq!(decoded.topics[0].types[1].solidity, "uint256");
+        assert!(LogData::new(&["0x01"], "").is_err());
+    }
+}
@@ -0,0 +1,79 @@
+/*
+cargo test --features middleware test_middleware -- --nocapture --test-threads=1
+*/
+#[cfg(all(test, feature = "middleware"))]
+mod test_middleware {
+    use crate::middleware::*;
+    use crate::Calldata;
+    use ethers::providers::{Middleware, Provider};
+    use ethers::types::{Block, Bytes, Transaction, TransactionRequest, TxHash};
+    use futures::executor::block_on;
+    use std::sync::{Arc, Mutex};
+
+    const TRANSFER: &str = "0xa9059cbb000000000000000000000000c02aaa39b223fe8d0a0e5c4f27ead9083c756cc20000000000000000000000000000000000000000000000000de0b6b3a7640000";
+
+    fn transaction(input: &str) -> Transaction {
+        Transaction {
+            input: input.parse::<Bytes>().unwrap(),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_get_transaction() {
+        let (provider, mock) = Provider::mocked();
+        let middleware = DecodingMiddleware::new(provider);
+
+        mock.push(transaction(TRANSFER)).unwrap();
+        let tx = block_on(middleware.get_transaction(TxHash::zero()))
+            .unwrap()
+            .unwrap();
+        assert_eq!(decoded(&tx), Some(Calldata::new(TRANSFER).decode()));
+        assert_eq!(tx.input.to_string(), TRANSFER);
+
+        // Plain transfers of ether have nothing to decode.
+        mock.push(transaction("0x")).unwrap();
+        let tx = block_on(middleware.get_transaction(TxHash::zero()))
+            .unwrap()
+            .unwrap();
+        assert_eq!(decoded(&tx), None);
+    }
+
+    #[test]
+    fn test_get_block_with_txs() {
+        let (provider, mock) = Provider::mocked();
+        let middleware = DecodingMiddleware::new(provider);
+        let block = Block {
+            transactions: vec![transaction(TRANSFER), transaction("0x")],
+            ..Default::default()
+        };
+        mock.push(block).unwrap();
+        let block = block_on(middleware.get_block_with_txs(1u64))
+            .unwrap()
+            .unwrap();
+        assert_eq!(
+            decoded(&block.transactions[0]).unwrap().selector,
+            "a9059cbb"
+        );
+        assert_eq!(decoded(&block.transactions[1]), None);
+    }
+
+    #[test]
+    fn test_send_transaction() {
+        let (provider, mock) = Provider::mocked();
+        let sent = Arc::new(Mutex::new(vec![]));
+        let seen = sent.clone();
+        let middleware = DecodingMiddleware::new(provider)
+            .on_send(move |hash, call| seen.lock().unwrap().push((hash, call.selector.clone())));
+
+        let hash = TxHash::repeat_byte(1);
+        mock.push(hash).unwrap();
+        let tx = TransactionRequest::new()
+            .data(TRANSFER.parse::<Bytes>().unwrap())
+            .gas(21000)
+            .gas_price(1);
+        let pending = block_on(middleware.send_transaction(tx, None)).unwrap();
+        assert_eq!(pending.tx_hash(), hash);
+        assert_eq!(*sent.lock().unwrap(), vec![(hash, "a9059cbb".to_string())]);
+    }
+}
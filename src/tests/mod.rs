@@ -1 +1,70 @@
-pub mod basic;
\ No newline at end of file
+pub mod abi;
+pub mod abiblob;
+pub mod advisor;
+pub mod arbitrum;
+pub mod alert;
+pub mod arena;
+pub mod asyncdecoder;
+pub mod basic;
+pub mod batch;
+pub mod batcher;
+pub mod bind;
+pub mod blob;
+pub mod builder;
+pub mod bundle;
+pub mod bytecode;
+pub mod cache;
+pub mod capi;
+pub mod codegen;
+pub mod config;
+pub mod corpus;
+pub mod crosscheck;
+pub mod decoder;
+pub mod determinism;
+pub mod diff;
+pub mod differential;
+pub mod dto;
+pub mod dump;
+pub mod encoder;
+pub mod explain;
+pub mod explorer;
+pub mod fixtures;
+pub mod heuristics;
+pub mod incremental;
+pub mod inspector;
+pub mod export;
+pub mod l1fee;
+pub mod layout;
+pub mod lazy;
+pub mod limits;
+pub mod logs;
+pub mod middleware;
+pub mod mutate;
+pub mod plugin;
+pub mod proto;
+pub mod protocol;
+pub mod prototypes;
+pub mod proxy;
+pub mod query;
+pub mod rawtx;
+pub mod redact;
+pub mod render;
+pub mod repl;
+pub mod returndata;
+pub mod roundtrip;
+pub mod rpc;
+pub mod schema;
+pub mod select;
+pub mod server;
+pub mod signatures;
+pub mod simulate;
+pub mod stats;
+pub mod stream;
+pub mod stride;
+pub mod summary;
+pub mod text;
+pub mod tokens;
+pub mod verify;
+pub mod walletsig;
+pub mod watch;
+pub mod wrappers;
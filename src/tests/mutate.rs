@@ -0,0 +1,97 @@
+/*
+cargo test test_mutate -- --nocapture --test-threads=1
+*/
+#[cfg(test)]
+mod test_mutate {
+    use crate::builder::{AbiValue, CalldataBuilder};
+    use crate::corpus::{MULTICALL, TRANSFER};
+    use crate::mutate::*;
+    use crate::{decode_hex, Calldata};
+    use primitive_types::H160;
+
+    fn generate(calldata: &[u8]) -> Vec<Mutation> {
+        mutations(calldata, &Calldata::from_bytes(calldata).decode())
+    }
+
+    fn labels(mutations: &[Mutation], path: &str) -> Vec<String> {
+        mutations
+            .iter()
+            .filter(|m| m.path == path)
+            .map(|m| m.label.clone())
+            .collect()
+    }
+
+    #[test]
+    fn test_boundaries() {
+        let calldata = decode_hex(TRANSFER);
+        let mutations = generate(&calldata);
+        assert_eq!(
+            labels(&mutations, "params[0]"),
+            vec![
+                "zero",
+                "precompile 0x01",
+                "max",
+                "flipped",
+                "last bit flipped"
+            ]
+        );
+        assert_eq!(
+            labels(&mutations, "params[1]"),
+            vec!["zero", "one", "max uint256"]
+        );
+
+        // Every variant is one word away from the calldata and decodes the same way.
+        for mutation in &mutations {
+            let variant = decode_hex(&mutation.calldata);
+            assert_eq!(variant.len(), calldata.len());
+            let changed = (0..calldata.len()).filter(|i| variant[*i] != calldata[*i]);
+            assert!(changed
+                .clone()
+                .all(|i| mutation.span.start <= i && i < mutation.span.end));
+            assert_eq!(Calldata::from_bytes(&variant).decode().params.len(), 2);
+        }
+        let flipped = mutations.iter().find(|m| m.label == "flipped").unwrap();
+        assert!(flipped
+            .calldata
+            .contains("253e806a72d11adc5ddf9df966ba683ec27ce138"));
+    }
+
+    #[test]
+    fn test_array_lengths() {
+        let calldata = CalldataBuilder::new("f(address[],int24)")
+            .array(vec![
+                AbiValue::Address(H160::repeat_byte(0x11)),
+                AbiValue::Address(H160::repeat_byte(0x22)),
+            ])
+            .int(-60)
+            .build()
+            .unwrap();
+        let mutations = generate(&calldata);
+        // The offset is left alone, the length it points at changes.
+        assert!(labels(&mutations, "params[0]").is_empty());
+        assert_eq!(
+            labels(&mutations, "params[2]"),
+            vec![
+                "length 2 -> 0",
+                "length 2 -> 1",
+                "length 2 -> 3",
+                "length 2 -> 4294967295"
+            ]
+        );
+        assert!(labels(&mutations, "params[1]").contains(&"min int256".to_string()));
+        assert!(mutations
+            .iter()
+            .all(|m| m.kind != MutationKind::ArrayLength || m.path == "params[2]"));
+    }
+
+    #[test]
+    fn test_nested_mutations() {
+        let calldata = decode_hex(MULTICALL);
+        let mutations = generate(&calldata);
+        assert!(mutations
+            .iter()
+            .any(|m| m.path.starts_with("calls[0].params")));
+        assert!(!mutations.iter().any(|m| m.path == "params[5]"));
+        assert_eq!(mutations, generate(&calldata));
+    }
+}
@@ -0,0 +1,115 @@
+/*
+cargo test --features plugins test_plugin -- --nocapture --test-threads=1
+*/
+#[cfg(all(test, feature = "plugins"))]
+mod test_plugin {
+    use crate::builder::CalldataBuilder;
+    use crate::corpus::TRANSFER;
+    use crate::plugin::*;
+    use crate::protocol::ProtocolRegistry;
+    use crate::{decode_hex, Calldata};
+    use primitive_types::H160;
+
+    /// `forward(address,bytes)` under selector `0x12345678`, the bytes being a call.
+    fn forward() -> Vec<u8> {
+        CalldataBuilder::from_selector([0x12, 0x34, 0x56, 0x78])
+            .address(H160::repeat_byte(0x44))
+            .bytes(&decode_hex(TRANSFER))
+            .build()
+            .unwrap()
+    }
+
+    /// A plugin claiming `0x12345678`, whose `decode` runs `body` and
+    /// returns `json`.
+    fn plugin(json: &str, body: &str) -> Vec<u8> {
+        let slice = (16i64 << 32) | json.len() as i64;
+        let json = json.replace('"', "\\\"");
+        let wat = format!(
+            r#"(module
+                (memory (export "memory") 1)
+                (data (i32.const 0) "\12\34\56\78")
+                (data (i32.const 16) "{json}")
+                (func (export "selectors") (result i64) (i64.const 4))
+                (func (export "alloc") (param i32) (result i32) (i32.const 1024))
+                (func (export "decode") (param i32 i32) (result i64)
+                    {body}
+                    (i64.const {slice})))"#,
+        );
+        wat::parse_str(wat).unwrap()
+    }
+
+    const FORWARD: &str = concat!(
+        r#"{"signature":"forward(address,bytes)","types":["address"],"#,
+        r#""calls":[{"at":100,"len":68}]}"#
+    );
+
+    #[test]
+    fn test_plugin_decode() {
+        let forward_plugin = WasmPlugin::from_bytes("forward", &plugin(FORWARD, "")).unwrap();
+        assert_eq!(forward_plugin.selectors(), &[[0x12, 0x34, 0x56, 0x78]]);
+        let registry = ProtocolRegistry::builtin().with(forward_plugin);
+        assert_eq!(registry.names(), vec!["multicall", "zksync", "wrappers", "forward"]);
+
+        let calldata = forward();
+        let root = registry.decode(&Calldata::from_bytes(&calldata)).unwrap();
+        assert_eq!(root.signature_guess, "forward(address,bytes)");
+        assert_eq!(root.params[0].types[0].solidity, "address");
+        assert_eq!(root.calls.len(), 1);
+        assert_eq!(root.calls[0].selector, "a9059cbb");
+        assert_eq!(root.calls[0].span.start, 100);
+
+        // Out of bounds, the call is left to the heuristics.
+        let past = FORWARD.replace("\"len\":68", "\"len\":6800");
+        let registry = ProtocolRegistry::new()
+            .with(WasmPlugin::from_bytes("past", &plugin(&past, "")).unwrap());
+        let heuristic = Calldata::from_bytes(&calldata);
+        assert_eq!(registry.decode(&heuristic).unwrap(), heuristic.decode());
+    }
+
+    #[test]
+    fn test_plugin_sandbox() {
+        // Stopped once out of fuel, the call is left to the heuristics.
+        let spin = WasmPlugin::from_bytes("spin", &plugin(FORWARD, "(loop (br 0))")).unwrap();
+        let error = spin.run(&forward()).unwrap_err();
+        assert!(error.contains("`decode` failed"), "{}", error);
+        let registry = ProtocolRegistry::new().with(spin);
+        let calldata = Calldata::from_bytes(&forward());
+        assert_eq!(registry.decode(&calldata).unwrap(), calldata.decode());
+
+        // Growing past the memory limit fails rather than allocating.
+        let grow = plugin(FORWARD, "(drop (memory.grow (i32.const 1024)))");
+        let grow = WasmPlugin::from_bytes("grow", &grow).unwrap();
+        assert!(grow.run(&forward()).is_ok());
+        let huge = plugin(
+            FORWARD,
+            "(if (i32.eq (memory.grow (i32.const 1024)) (i32.const -1)) (then unreachable))",
+        );
+        assert!(WasmPlugin::from_bytes("huge", &huge)
+            .unwrap()
+            .run(&forward())
+            .is_err());
+
+        // No host functions to import.
+        let import = wat::parse_str(r#"(module (import "env" "log" (func)))"#).unwrap();
+        let error = WasmPlugin::from_bytes("import", &import).err().unwrap();
+        assert!(error.contains("env::log"), "{}", error);
+        let missing = wat::parse_str("(module)").unwrap();
+        assert!(WasmPlugin::from_bytes("missing", &missing).is_err());
+    }
+
+    #[test]
+    fn test_plugin_files() {
+        let dir =
+            std::env::temp_dir().join(format!("calldata-decoder-plugins-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        std::fs::write(dir.join("forward.wasm"), plugin(FORWARD, "")).unwrap();
+        std::fs::write(dir.join("notes.txt"), "not a plugin").unwrap();
+        assert_eq!(plugin_files(&dir).unwrap(), vec![dir.join("forward.wasm")]);
+        let registry = ProtocolRegistry::new().with_plugins(&dir).unwrap();
+        assert_eq!(registry.names(), vec!["forward"]);
+        assert!(ProtocolRegistry::new()
+            .with_plugins(&dir.join("notes.txt"))
+            .is_err());
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+}
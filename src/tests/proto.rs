@@ -0,0 +1,24 @@
+/*
+cargo test --features proto test_proto -- --nocapture --test-threads=1
+*/
+#[cfg(all(test, feature = "proto"))]
+mod test_proto {
+    use crate::proto::*;
+    use crate::schema::SCHEMA_VERSION;
+    use crate::Calldata;
+
+    #[test]
+    fn test_protobuf_round_trip() {
+        let calldata = Calldata::new("0xa9059cbb000000000000000000000000c02aaa39b223fe8d0a0e5c4f27ead9083c756cc20000000000000000000000000000000000000000000000000de0b6b3a7640000");
+        let bytes = to_protobuf(&calldata);
+        let result = from_protobuf(&bytes).unwrap();
+        assert_eq!(result.version, SCHEMA_VERSION);
+
+        let root = result.root.unwrap();
+        assert_eq!(root.selector, "a9059cbb");
+        assert_eq!(root.signature_guess, "unknown_a9059cbb(address,uint256)");
+        assert_eq!(root.params[1].value, "1000000000000000000");
+        assert_eq!(root.params[0].types[0].kind, "Address");
+        assert_eq!(root.params[1].span.unwrap().start, 36);
+    }
+}
@@ -0,0 +1,244 @@
+/*
+cargo test test_protocol -- --nocapture --test-threads=1
+*/
+#[cfg(test)]
+mod test_protocol {
+    use crate::builder::CalldataBuilder;
+    use crate::constants::WORD;
+    use crate::corpus;
+    use crate::decoded::DecodedCalldata;
+    use crate::decoder::Decoder;
+    use crate::limits::Limits;
+    use crate::protocol::*;
+    use crate::{decode_hex, Calldata};
+    use primitive_types::H160;
+
+    fn transfer() -> CalldataBuilder {
+        CalldataBuilder::new("transfer(address,uint256)")
+            .address(H160::repeat_byte(0x11))
+            .uint(1u64)
+    }
+
+    /// A multicall with a deadline of an `aggregate3` of two transfers.
+    fn nested_batch() -> Vec<u8> {
+        let aggregate3 = CalldataBuilder::aggregate3(&[
+            (H160::repeat_byte(0x22), false, transfer()),
+            (H160::repeat_byte(0x33), true, transfer()),
+        ]);
+        CalldataBuilder::multicall_with_deadline(1_700_000_000u64, &[aggregate3, transfer()])
+            .build()
+            .unwrap()
+    }
+
+    /// Every call's span starts at its selector in `calldata`.
+    fn check_spans(calldata: &[u8], root: &DecodedCalldata) {
+        for call in root.walk() {
+            let at = call.span.start;
+            assert_eq!(faster_hex::hex_string(&calldata[at..at + 4]), call.selector);
+        }
+    }
+
+    #[test]
+    fn test_protocol_multicall() {
+        let registry = ProtocolRegistry::builtin();
+        assert_eq!(registry.names(), vec!["multicall", "zksync", "wrappers"]);
+        // Canonical multicalls read the same by their layout as by the
+        // heuristics; the Safe transaction is unwrapped (see `wrappers`).
+        for (name, hex) in corpus::ALL.into_iter().filter(|c| c.1 != corpus::SAFE) {
+            let calldata = Calldata::new(hex);
+            assert_eq!(
+                registry.decode(&calldata).unwrap(),
+                calldata.decode_heuristics(),
+                "{}",
+                name
+            );
+        }
+
+        // `Calldata::decode` goes through the decoders too.
+        let safe = Calldata::new(corpus::SAFE);
+        assert_eq!(safe.decode().calls.len(), 1);
+        let safe = safe.with_protocols(ProtocolRegistry::new());
+        assert!(safe.decode().calls.is_empty());
+
+        let bytes = nested_batch();
+        let root = registry.decode(&Calldata::from_bytes(&bytes)).unwrap();
+        assert_eq!(root.selector, "5ae401dc");
+        let selectors = root
+            .calls
+            .iter()
+            .map(|c| c.selector.as_str())
+            .collect::<Vec<_>>();
+        assert_eq!(selectors, vec!["82ad56cb", "a9059cbb"]);
+        assert_eq!(root.calls[0].calls.len(), 2);
+        assert!(root.calls[0].calls.iter().all(|c| c.selector == "a9059cbb"));
+        check_spans(&bytes, &root);
+
+        // The transfers inside aggregate3 are past `max_depth`.
+        let limits = Limits {
+            max_depth: 1,
+            ..Limits::default()
+        };
+        let calldata =
+            Calldata::from_bytes_with_limits(bytes.clone(), Default::default(), limits).unwrap();
+        let root = registry.decode(&calldata).unwrap();
+        assert_eq!(root.calls.len(), 2);
+        assert!(root.calls[0].calls.is_empty());
+
+        // `max_calls` is over the whole tree, the decoders' calls included:
+        // the aggregate3 and its first transfer.
+        let limits = Limits {
+            max_calls: 2,
+            ..Limits::default()
+        };
+        let calldata =
+            Calldata::from_bytes_with_limits(bytes.clone(), Default::default(), limits).unwrap();
+        let root = registry.decode(&calldata).unwrap();
+        assert_eq!(root.walk().len(), 3);
+        assert_eq!(root.calls[0].calls.len(), 1);
+
+        // `max_nodes` too, counted as the tree is built.
+        let full = Calldata::from_bytes(&bytes).decode();
+        let nodes = full
+            .walk()
+            .iter()
+            .map(|c| 1 + c.params.len())
+            .sum::<usize>();
+        let decode = |max_nodes: usize| {
+            let limits = Limits {
+                max_nodes,
+                ..Limits::default()
+            };
+            let calldata =
+                Calldata::from_bytes_with_limits(bytes.clone(), Default::default(), limits)?;
+            registry.decode(&calldata)
+        };
+        assert_eq!(decode(nodes), Ok(full));
+        assert!(decode(nodes - 1).is_err());
+    }
+
+    /// Names the transfers it claims.
+    struct Transfers;
+
+    impl ProtocolDecoder for Transfers {
+        fn name(&self) -> &str {
+            "erc20"
+        }
+
+        fn claims(&self, call: &[u8]) -> bool {
+            call.starts_with(&[0xa9, 0x05, 0x9c, 0xbb])
+        }
+
+        fn decode(&self, call: &[u8], cx: &Context) -> Result<DecodedCalldata, String> {
+            let mut node = cx.node(call);
+            node.signature_guess = "transfer(address,uint256)".to_string();
+            Ok(node)
+        }
+    }
+
+    #[test]
+    fn test_protocol_registry() {
+        let registry = ProtocolRegistry::builtin().with(Transfers);
+        assert_eq!(
+            registry.names(),
+            vec!["multicall", "zksync", "wrappers", "erc20"]
+        );
+
+        // At the top level, inside a claimed call, and inside a call the heuristics found.
+        let root = registry.decode(&Calldata::new(corpus::TRANSFER)).unwrap();
+        assert_eq!(root.signature_guess, "transfer(address,uint256)");
+        let bytes = nested_batch();
+        let root = registry.decode(&Calldata::from_bytes(&bytes)).unwrap();
+        let named = root.walk().into_iter().filter(|c| c.selector == "a9059cbb");
+        assert_eq!(named.clone().count(), 3);
+        assert!(named
+            .into_iter()
+            .all(|c| c.signature_guess == "transfer(address,uint256)"));
+        check_spans(&bytes, &root);
+
+        let execute = CalldataBuilder::new("execute(address,bytes)")
+            .address(H160::repeat_byte(0x44))
+            .bytes(&transfer().build().unwrap())
+            .build()
+            .unwrap();
+        let root = registry.decode(&Calldata::from_bytes(&execute)).unwrap();
+        assert_eq!(root.calls[0].signature_guess, "transfer(address,uint256)");
+        check_spans(&execute, &root);
+
+        // No decoders is the heuristics alone.
+        let calldata = Calldata::from_bytes(&nested_batch());
+        assert_eq!(
+            ProtocolRegistry::new().decode(&calldata).unwrap(),
+            calldata.decode_heuristics()
+        );
+        let builtin = ProtocolRegistry::builtin().decode(&calldata).unwrap();
+        assert_eq!(calldata.decode(), builtin);
+
+        let decoder = Decoder::new().with_protocols(registry);
+        let root = decoder.decode(corpus::TRANSFER).unwrap();
+        assert_eq!(root.signature_guess, "transfer(address,uint256)");
+        assert!(decoder.decode_bytes(&decode_hex("0xac9650d8")).is_ok());
+    }
+
+    #[test]
+    fn test_protocol_zksync() {
+        let registry = ProtocolRegistry::builtin();
+        let solidity = |root: &DecodedCalldata| {
+            root.params
+                .iter()
+                .map(|p| p.types[0].solidity.clone())
+                .collect::<Vec<_>>()
+        };
+
+        // The inner input goes to the paymaster: it isn't searched for calls.
+        let inner = transfer().build().unwrap();
+        let approval = CalldataBuilder::new("approvalBased(address,uint256,bytes)")
+            .address(H160::repeat_byte(0x44))
+            .uint(1_000_000u64)
+            .bytes(&inner)
+            .build()
+            .unwrap();
+        let heuristics = Calldata::from_bytes(&approval).with_protocols(ProtocolRegistry::new());
+        assert_eq!(heuristics.decode().calls.len(), 1);
+        let root = registry.decode(&Calldata::from_bytes(&approval)).unwrap();
+        assert_eq!(root.signature_guess, "approvalBased(address,uint256,bytes)");
+        assert_eq!(
+            solidity(&root)[..4],
+            ["address", "uint256", "bytes", "uint256"]
+        );
+        assert!(root.calls.is_empty());
+
+        let general = CalldataBuilder::new("general(bytes)")
+            .bytes(&[])
+            .build()
+            .unwrap();
+        let root = registry.decode(&Calldata::from_bytes(&general)).unwrap();
+        assert_eq!(root.signature_guess, "general(bytes)");
+        assert_eq!(solidity(&root), ["bytes", "uint256"]);
+
+        // A salt and bytecode hash with leading zeros still read as bytes32.
+        let mut salt = [0u8; 32];
+        salt[31] = 1;
+        let create = CalldataBuilder::new("createAccount(bytes32,bytes32,bytes,uint8)")
+            .fixed_bytes(&salt)
+            .fixed_bytes(&[0x01; 32])
+            .bytes(&[0x22; 40])
+            .uint(1u64)
+            .build()
+            .unwrap();
+        let root = registry.decode(&Calldata::from_bytes(&create)).unwrap();
+        assert_eq!(
+            root.signature_guess,
+            "createAccount(bytes32,bytes32,bytes,uint8)"
+        );
+        assert_eq!(
+            solidity(&root)[..5],
+            ["bytes32", "bytes32", "bytes", "uint8", "uint256"]
+        );
+        assert_eq!(root.params[0].value, format!("0x{}01", "00".repeat(31)));
+
+        // Bytes running past the calldata are left to the heuristics.
+        let truncated = &create[..create.len() - WORD];
+        let root = registry.decode(&Calldata::from_bytes(truncated)).unwrap();
+        assert!(root.signature_guess.starts_with("unknown_"));
+    }
+}
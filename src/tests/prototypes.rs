@@ -0,0 +1,216 @@
+/*
+cargo test test_prototypes -- --nocapture --test-threads=1
+*/
+#[cfg(test)]
+mod test_prototypes {
+    use crate::builder::{AbiValue, CalldataBuilder};
+    use crate::decoded::DecodedTuple;
+    use crate::protocol::ProtocolRegistry;
+    use crate::prototypes::*;
+    use crate::signatures::Signatures;
+    use crate::Calldata;
+    use primitive_types::{H160, U256};
+
+    const MULTICALL: &str = "0xac9650d800000000000000000000000000000000000000000000000000000000000000200000000000000000000000000000000000000000000000000000000000000002000000000000000000000000000000000000000000000000000000000000004000000000000000000000000000000000000000000000000000000000000001e0000000000000000000000000000000000000000000000000000000000000016488316456000000000000000000000000c011a73ee8576fb46f5e1c5751ca3b9fe0af2a6f000000000000000000000000c02aaa39b223fe8d0a0e5c4f27ead9083c756cc20000000000000000000000000000000000000000000000000000000000002710fffffffffffffffffffffffffffffffffffffffffffffffffffffffffffee530ffffffffffffffffffffffffffffffffffffffffffffffffffffffffffff1b18000000000000000000000000000000000000000000000000016345785d89fd6800000000000000000000000000000000000000000000000000007f73eca3063a000000000000000000000000000000000000000000000000016042b530ddaec600000000000000000000000000000000000000000000000000007e59f044bada000000000000000000000000f847e9d51989033b691b8be943f8e9e268f99b9e000000000000000000000000000000000000000000000000000000006377347700000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000412210e8a00000000000000000000000000000000000000000000000000000000";
+    const UINT_ARRAY: &str = "0x5d842074000000000000000000000000000000000000000000000006c6b935b8bbd400000000000000000000000000000000000000000000000000000000000000000040000000000000000000000000000000000000000000000000000000000000000200000000000000000000000000000000000000000000002086ac35105260000000000000000000000000000000000000000000000000002b5e3af16b18800000";
+    const TRANSFER: &str = "0xa9059cbb000000000000000000000000c02aaa39b223fe8d0a0e5c4f27ead9083c756cc20000000000000000000000000000000000000000000000000de0b6b3a7640000";
+
+    #[test]
+    fn test_rank_multicall() {
+        let root = Calldata::new(MULTICALL).decode();
+        assert_eq!(structural_guess(&root), Some("bytes[]"));
+
+        let prototypes = rank_prototypes(&root, &Signatures::builtin(), 5);
+        // Flat guesses that don't re-encode are pruned with `ethers`.
+        assert!((2..=5).contains(&prototypes.len()));
+        assert_eq!(prototypes[0].signature, "multicall(bytes[])");
+        assert_eq!(prototypes[0].source, PrototypeSource::Known);
+        assert_eq!(prototypes[1].signature, "unknown_ac9650d8(bytes[])");
+        assert_eq!(prototypes[1].source, PrototypeSource::Structure);
+        assert!(prototypes
+            .windows(2)
+            .all(|w| w[0].confidence >= w[1].confidence));
+        assert!(prototypes.iter().map(|p| p.confidence).sum::<f64>() <= 1.0 + 1e-9);
+    }
+
+    #[test]
+    fn test_rank_unknown_selector() {
+        let root = Calldata::new(TRANSFER).decode();
+        assert_eq!(structural_guess(&root), None);
+
+        // Nothing known: the heuristic guesses share all the confidence.
+        let prototypes = rank_prototypes(&root, &Signatures::new(), 10);
+        assert_eq!(prototypes[0].signature, "unknown_a9059cbb(address,uint256)");
+        assert!(prototypes
+            .iter()
+            .all(|p| p.source == PrototypeSource::Heuristic));
+        let total = prototypes.iter().map(|p| p.confidence).sum::<f64>();
+        assert!((total - 1.0).abs() < 1e-9);
+        #[cfg(not(feature = "ethers"))]
+        assert!(prototypes
+            .iter()
+            .any(|p| p.signature == "unknown_a9059cbb(bytes20,uint256)"));
+    }
+
+    #[test]
+    fn test_candidate_signatures() {
+        let root = Calldata::new(UINT_ARRAY).decode();
+        assert_eq!(layout_guess(&root).as_deref(), Some("uint256,uint256[]"));
+
+        let prototypes = root.candidate_signatures(3);
+        assert_eq!(
+            prototypes[0].signature,
+            "unknown_5d842074(uint256,uint256[])"
+        );
+        assert_eq!(prototypes[0].source, PrototypeSource::Structure);
+        assert!(prototypes.len() <= 3);
+
+        // No offsets, no layout: only the candidates' combinations.
+        let root = Calldata::new(TRANSFER).decode();
+        assert_eq!(layout_guess(&root), None);
+        assert_eq!(
+            root.candidate_signatures(1)[0].signature,
+            "unknown_a9059cbb(address,uint256)"
+        );
+
+        // The tree is named by the top guess before re-encoding, nested
+        // calls included, so it's the same with or without `ethers`.
+        for (name, hex) in crate::corpus::ALL {
+            let root = Calldata::new(hex)
+                .with_protocols(ProtocolRegistry::new())
+                .decode();
+            for call in root.walk() {
+                let top = top_signature(&call.selector, &call.params, call.calls.len());
+                assert_eq!(call.signature_guess, top, "{}", name);
+                #[cfg(not(feature = "ethers"))]
+                assert_eq!(call.candidate_signatures(1)[0].signature, top, "{}", name);
+            }
+        }
+        // Seaport's `fulfillBasicOrder` takes one dynamic struct.
+        let seaport = Calldata::new(crate::corpus::SEAPORT).decode();
+        assert!(seaport.signature_guess.starts_with("unknown_fb0f3ee1((uint256,"));
+        assert!(seaport.signature_guess.ends_with(",(uint256,uint256)[],bytes))"));
+    }
+
+    #[test]
+    fn test_candidate_confidence() {
+        use crate::constants::Types;
+        use crate::decoded::candidates;
+        use crate::type_guesser::{fit, ParamTypes};
+
+        let word = |s: &str| crate::decode_hex(s);
+        let weth = "000000000000000000000000c02aaa39b223fe8d0a0e5c4f27ead9083c756cc2";
+        let low = "0000000000000000000000000000000000000000000000000000000000000040";
+        assert_eq!(fit(&Types::Address, &word(weth)), 1.0);
+        assert!(fit(&Types::Address, &word(low)) < 1.0);
+
+        // A full address outranks the same candidates on a small number.
+        let types = ParamTypes::new(vec![Types::Address, Types::Uint, Types::Bytes]);
+        let full = candidates(&types, weth);
+        let small = candidates(&types, low);
+        assert!(full.windows(2).all(|w| w[0].confidence >= w[1].confidence));
+        assert!((full.iter().map(|c| c.confidence).sum::<f64>() - 1.0).abs() < 1e-9);
+        assert!(full[0].confidence > small[0].confidence);
+    }
+
+    #[test]
+    fn test_layout_tuples() {
+        let guess = |builder: CalldataBuilder| {
+            let bytes = builder.build().unwrap();
+            layout_guess(&Calldata::from_bytes(&bytes).decode())
+        };
+        let (a, b) = (H160::repeat_byte(0x11), H160::repeat_byte(0x22));
+        let amount = |v: u64| AbiValue::Uint(U256::from(v) * U256::exp10(18));
+
+        // A dynamic tuple, its bytes' offset counted from the tuple.
+        let tuple = CalldataBuilder::new("f(uint256,(address,bytes))")
+            .push(amount(7))
+            .tuple(vec![AbiValue::Address(a), AbiValue::Bytes(vec![0xab; 40])]);
+        assert_eq!(guess(tuple).as_deref(), Some("uint256,(address,bytes)"));
+
+        // Static tuples in an array, the fields one after the other.
+        let structs = CalldataBuilder::new("f((address,uint256)[])").array(vec![
+            AbiValue::Tuple(vec![AbiValue::Address(a), amount(1)]),
+            AbiValue::Tuple(vec![AbiValue::Address(b), amount(2)]),
+            AbiValue::Tuple(vec![AbiValue::Address(a), amount(3)]),
+        ]);
+        assert_eq!(guess(structs).as_deref(), Some("(address,uint256)[]"));
+
+        // Dynamic tuples in an array, each behind an offset.
+        let element = |address: H160, len: usize| {
+            AbiValue::Tuple(vec![
+                AbiValue::Address(address),
+                AbiValue::Bytes(vec![0xcd; len]),
+            ])
+        };
+        let dynamic = CalldataBuilder::new("f((address,bytes)[],address)")
+            .array(vec![element(a, 3), element(b, 70)])
+            .address(b);
+        assert_eq!(guess(dynamic).as_deref(), Some("(address,bytes)[],address"));
+
+        // A static tuple on its own is encoded as its fields.
+        let flat = CalldataBuilder::new("f((address,uint256),bytes)")
+            .tuple(vec![AbiValue::Address(a), amount(1)])
+            .bytes(&[0xef; 5]);
+        assert_eq!(guess(flat).as_deref(), Some("address,uint256,bytes"));
+    }
+
+    #[test]
+    fn test_layout_tuple_nodes() {
+        let tuples = |builder: CalldataBuilder| {
+            let bytes = builder.build().unwrap();
+            Calldata::from_bytes(&bytes).decode().tuples
+        };
+        let node = |offset, start, end, types: &[&str]| DecodedTuple {
+            offset,
+            start,
+            end,
+            types: types.iter().map(|t| t.to_string()).collect(),
+            tuples: vec![],
+        };
+        let (a, b) = (H160::repeat_byte(0x11), H160::repeat_byte(0x22));
+
+        // The tuple's words, from the one its offset points at to the end of its bytes.
+        let tuple = CalldataBuilder::new("f(uint256,(address,bytes))")
+            .push(AbiValue::Uint(U256::from(7)))
+            .tuple(vec![AbiValue::Address(a), AbiValue::Bytes(vec![0xab; 40])]);
+        assert_eq!(tuples(tuple), vec![node(1, 2, 7, &["address", "bytes"])]);
+
+        // Each element of an array of dynamic tuples, behind its own offset.
+        let element = |address: H160, len: usize| {
+            AbiValue::Tuple(vec![
+                AbiValue::Address(address),
+                AbiValue::Bytes(vec![0xcd; len]),
+            ])
+        };
+        let dynamic = CalldataBuilder::new("f((address,bytes)[],address)")
+            .array(vec![element(a, 3), element(b, 70)])
+            .address(b);
+        assert_eq!(
+            tuples(dynamic),
+            vec![
+                node(3, 5, 9, &["address", "bytes"]),
+                node(4, 9, 15, &["address", "bytes"]),
+            ]
+        );
+
+        // Static tuples are encoded as their fields, with no offset to bound
+        // them, so they can't be told apart from flat params.
+        let flat = CalldataBuilder::new("f((address,uint256),bytes)")
+            .tuple(vec![AbiValue::Address(a), AbiValue::Uint(U256::one())])
+            .bytes(&[0xef; 5]);
+        assert_eq!(tuples(flat), vec![]);
+    }
+
+    #[test]
+    fn test_layout_depth() {
+        // Arrays of one array nested thousands deep, each a length and an offset.
+        let nested = format!(
+            "0xa9059cbb{:064x}{}",
+            0x20,
+            format!("{:064x}{:064x}", 1, 0x20).repeat(4000)
+        );
+        let guess = layout_guess(&Calldata::new(&nested).decode());
+        assert!(guess.is_none_or(|g| g.matches("[]").count() <= 8));
+    }
+}
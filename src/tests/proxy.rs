@@ -0,0 +1,75 @@
+/*
+cargo test test_proxy -- --nocapture --test-threads=1
+*/
+#[cfg(test)]
+mod test_proxy {
+    use crate::proxy::*;
+    use crate::{decode_hex, keccak256};
+    use primitive_types::{H160, U256};
+
+    #[test]
+    fn test_proxy_slots() {
+        let slot = |name: &str| {
+            let mut word = [0; 32];
+            (U256::from_big_endian(&keccak256(name.as_bytes())) - 1).to_big_endian(&mut word);
+            word
+        };
+        assert_eq!(IMPLEMENTATION_SLOT, slot("eip1967.proxy.implementation"));
+        assert_eq!(BEACON_SLOT, slot("eip1967.proxy.beacon"));
+        assert_eq!(BEACON_IMPLEMENTATION, keccak256(b"implementation()")[..4]);
+
+        let mut word = [0; 32];
+        assert_eq!(slot_address(&word), None);
+        word[31] = 1;
+        assert_eq!(slot_address(&word), Some(H160::from_low_u64_be(1)));
+        word[0] = 1;
+        assert_eq!(slot_address(&word), None);
+
+        let code = decode_hex(
+            "363d3d373d3d3d363d73bebebebebebebebebebebebebebebebebebebebe5af43d82803e903d91602b57fd5bf3",
+        );
+        assert_eq!(
+            minimal_proxy_implementation(&code),
+            Some(H160::repeat_byte(0xbe))
+        );
+        assert_eq!(minimal_proxy_implementation(&code[1..]), None);
+    }
+
+    #[cfg(feature = "ethers")]
+    #[test]
+    fn test_resolve_beacon_proxy() {
+        use crate::corpus::MULTICALL;
+        use crate::rpc::decode_transaction_resolved;
+        use ethers::providers::Provider;
+        use ethers::types::{Bytes, Transaction, H256};
+        use futures::executor::block_on;
+
+        let beacon = H160::repeat_byte(0xbc);
+        let implementation = H160::repeat_byte(0x1e);
+        let (provider, mock) = Provider::mocked();
+        // Served last pushed first: the empty implementation slot, the beacon
+        // slot, the beacon's answer, then code dispatching to `multicall` only.
+        mock.push::<Bytes, _>(Bytes::from(decode_hex("8063ac9650d81461004057")))
+            .unwrap();
+        mock.push::<Bytes, _>(Bytes::from(H256::from(implementation).0.to_vec()))
+            .unwrap();
+        mock.push::<H256, _>(H256::from(beacon)).unwrap();
+        mock.push::<H256, _>(H256::zero()).unwrap();
+
+        let tx = Transaction {
+            to: Some(H160::repeat_byte(0x99)),
+            block_number: Some(16_000_000.into()),
+            input: MULTICALL.parse::<Bytes>().unwrap(),
+            ..Default::default()
+        };
+        let decoded = block_on(decode_transaction_resolved(&provider, &tx)).unwrap();
+        let proxy = decoded.proxy.unwrap();
+        assert_eq!(proxy.kind, ProxyKind::Beacon { beacon });
+        assert_eq!(proxy.implementation, implementation);
+        assert_eq!(proxy.block, Some(15_999_999));
+        assert_eq!(proxy.selectors, vec![[0xac, 0x96, 0x50, 0xd8]]);
+        // `mint` and `refundETH` aren't the implementation's, so they stay bytes.
+        assert!(decoded.decoded.calls.is_empty());
+        assert_ne!(decoded.decoded, crate::Calldata::new(MULTICALL).decode());
+    }
+}
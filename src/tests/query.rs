@@ -0,0 +1,78 @@
+/*
+cargo test test_query -- --nocapture --test-threads=1
+*/
+#[cfg(test)]
+mod test_query {
+    use crate::corpus::{MULTICALL, TRANSFER};
+    use crate::query::*;
+    use crate::Calldata;
+
+    const APPROVE: &str = "0x095ea7b3000000000000000000000000c02aaa39b223fe8d0a0e5c4f27ead9083c756cc2ffffffffffffffffffffffffffffffffffffffffffffffffffffffffffffffff";
+
+    fn paths(query: &str, calldata: &str) -> Vec<String> {
+        let root = Calldata::new(calldata).decode();
+        Query::parse(query)
+            .unwrap()
+            .find(&root)
+            .into_iter()
+            .map(|(path, _)| path)
+            .collect()
+    }
+
+    #[test]
+    fn test_query_matches() {
+        let unlimited = "select calls where selector == 0x095ea7b3 and params[1] > 1e30";
+        assert_eq!(paths(unlimited, APPROVE), vec![""]);
+        assert!(paths(unlimited, TRANSFER).is_empty());
+
+        assert_eq!(
+            paths(
+                "params[0] == 0xdac17f958d2ee523a2206206994597c13d831ec7",
+                TRANSFER
+            ),
+            vec![""]
+        );
+        assert_eq!(paths("params[1] == 1e9", TRANSFER), vec![""]);
+        assert_eq!(
+            paths("params[1] <= 0x3b9aca00 and not depth > 0", TRANSFER),
+            vec![""]
+        );
+
+        assert_eq!(
+            paths("select calls where depth > 0", MULTICALL),
+            vec!["calls[0]", "calls[1]"]
+        );
+        assert_eq!(
+            paths(
+                "(selector = 88316456 or selector = '12210e8a') and not params[0].types[0].solidity == 'bytes'",
+                MULTICALL
+            ),
+            vec!["calls[0]", "calls[1]"]
+        );
+        assert_eq!(
+            paths("select root where depth > 0", MULTICALL),
+            Vec::<String>::new()
+        );
+        assert_eq!(paths("select calls", MULTICALL).len(), 3);
+        assert!(paths("signature_guess contains UNKNOWN_AC96", MULTICALL).len() == 1);
+        assert!(paths("params[7] > 0", TRANSFER).is_empty());
+    }
+
+    #[test]
+    fn test_query_errors() {
+        for (query, error) in [
+            (
+                "select where depth > 0",
+                "expected `calls` or `root` after `select`",
+            ),
+            ("depth >", "expected a value after `depth`"),
+            ("(depth > 0", "expected `)`"),
+            ("depth 0", "expected an operator after `depth`"),
+            ("depth > 0 depth", "unexpected `depth`"),
+            ("selector == 'a9", "unterminated text at 12"),
+            ("params[x] > 0", "invalid path `params[x]`"),
+        ] {
+            assert_eq!(Query::parse(query), Err(error.to_string()), "{}", query);
+        }
+    }
+}
@@ -0,0 +1,144 @@
+/*
+cargo test test_rawtx -- --nocapture --test-threads=1
+*/
+#[cfg(test)]
+mod test_rawtx {
+    use crate::decode_hex;
+    use crate::rawtx::*;
+    use crate::render::color::Style;
+    use crate::render::output::{render_raw_transaction, OutputFormat};
+    use crate::rlp::Rlp;
+    use primitive_types::U256;
+
+    /// The signed transaction of the EIP-155 example: 1 ether to 0x3535..35 on mainnet.
+    const EIP155: &str = "0xf86c098504a817c800825208943535353535353535353535353535353535353535880de0b6b3a76400008025a028ef61340bd939bc2195fe537567866003e1a15d3c71ff63e1590620aa636276a067cbe9d8997f761aecb703304b3800ccf555c9f3dc64214b297fb1966a3b6d83";
+    /// An ERC-20 `transfer` to WETH, signed the same way.
+    const TRANSFER: &str = "0xf8a9078504a817c80082ea6094c02aaa39b223fe8d0a0e5c4f27ead9083c756cc280b844a9059cbb000000000000000000000000d8da6bf26964af9d7eed9e10e3f4a2ff91e9a7a60000000000000000000000000000000000000000000000000de0b6b3a764000025a028ef61340bd939bc2195fe537567866003e1a15d3c71ff63e1590620aa636276a067cbe9d8997f761aecb703304b3800ccf555c9f3dc64214b297fb1966a3b6d83";
+
+    /// The same transfer in each typed envelope: an access list to WETH's slot 3,
+    /// dynamic fees, and a blob transaction in its plain and network forms.
+    const ACCESS_LIST: &str = "0x01f8e401038504a817c80082ea6094c02aaa39b223fe8d0a0e5c4f27ead9083c756cc280b844a9059cbb000000000000000000000000d8da6bf26964af9d7eed9e10e3f4a2ff91e9a7a60000000000000000000000000000000000000000000000000de0b6b3a7640000f838f794c02aaa39b223fe8d0a0e5c4f27ead9083c756cc2e1a0000000000000000000000000000000000000000000000000000000000000000301a028ef61340bd939bc2195fe537567866003e1a15d3c71ff63e1590620aa636276a067cbe9d8997f761aecb703304b3800ccf555c9f3dc64214b297fb1966a3b6d83";
+    const DYNAMIC_FEE: &str = "0x02f8b00104843b9aca008506fc23ac0082ea6094c02aaa39b223fe8d0a0e5c4f27ead9083c756cc280b844a9059cbb000000000000000000000000d8da6bf26964af9d7eed9e10e3f4a2ff91e9a7a60000000000000000000000000000000000000000000000000de0b6b3a7640000c080a028ef61340bd939bc2195fe537567866003e1a15d3c71ff63e1590620aa636276a067cbe9d8997f761aecb703304b3800ccf555c9f3dc64214b297fb1966a3b6d83";
+    const BLOB: &str = "0x03f8d30105843b9aca008506fc23ac0082ea6094c02aaa39b223fe8d0a0e5c4f27ead9083c756cc280b844a9059cbb000000000000000000000000d8da6bf26964af9d7eed9e10e3f4a2ff91e9a7a60000000000000000000000000000000000000000000000000de0b6b3a7640000c003e1a001000000000000000000000000000000000000000000000000000000000000aa01a028ef61340bd939bc2195fe537567866003e1a15d3c71ff63e1590620aa636276a067cbe9d8997f761aecb703304b3800ccf555c9f3dc64214b297fb1966a3b6d83";
+    const BLOB_NETWORK: &str = "0x03f9017df8d30105843b9aca008506fc23ac0082ea6094c02aaa39b223fe8d0a0e5c4f27ead9083c756cc280b844a9059cbb000000000000000000000000d8da6bf26964af9d7eed9e10e3f4a2ff91e9a7a60000000000000000000000000000000000000000000000000de0b6b3a7640000c003e1a001000000000000000000000000000000000000000000000000000000000000aa01a028ef61340bd939bc2195fe537567866003e1a15d3c71ff63e1590620aa636276a067cbe9d8997f761aecb703304b3800ccf555c9f3dc64214b297fb1966a3b6d83f842b84000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000f1b0000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000f1b0000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000";
+
+    #[test]
+    fn test_rlp() {
+        let bytes = decode_hex("c88363617483646f67");
+        let item = Rlp::decode(&bytes).unwrap();
+        let list = item.as_list().unwrap();
+        assert_eq!(list[0].as_bytes().unwrap(), b"cat");
+        assert_eq!(list[1].as_bytes().unwrap(), b"dog");
+        assert!(item.as_bytes().is_err());
+
+        assert_eq!(Rlp::decode(&[0x0f]).unwrap().as_u64().unwrap(), 15);
+        assert_eq!(Rlp::decode(&[0x80]).unwrap().as_u64().unwrap(), 0);
+        let max = [&[0xa0][..], &[0xff; 32]].concat();
+        assert_eq!(Rlp::decode(&max).unwrap().as_u256().unwrap(), U256::MAX);
+
+        // Non-canonical and truncated encodings.
+        assert!(Rlp::decode(&[0x81, 0x05]).is_err());
+        assert!(Rlp::decode(&[0x82, 0x00, 0x01]).unwrap().as_u64().is_err());
+        assert!(Rlp::decode(&[0xb8, 0x02, 0x01, 0x02]).is_err());
+        assert!(Rlp::decode(&[0x83, 0x01]).is_err());
+        assert!(Rlp::decode(&[0x01, 0x02]).is_err());
+        assert!(Rlp::decode(&[]).is_err());
+
+        let items = Rlp::raw_items(&bytes).unwrap();
+        assert_eq!(items, vec![&bytes[1..5], &bytes[5..]]);
+        assert!(Rlp::raw_items(&bytes[1..5]).is_err());
+
+        // Lists nested far deeper than any transaction are refused, not
+        // recursed into until the stack runs out.
+        let nested = |depth: usize| {
+            (0..depth).fold(vec![0xc0], |inner, _| {
+                let len = inner.len().to_be_bytes();
+                let len = &len[len.iter().position(|&b| b != 0).unwrap()..];
+                let prefix = match inner.len() {
+                    0..=55 => vec![0xc0 + inner.len() as u8],
+                    _ => [&[0xf7 + len.len() as u8][..], len].concat(),
+                };
+                [prefix, inner].concat()
+            })
+        };
+        assert!(Rlp::decode(&nested(crate::rlp::MAX_DEPTH - 1)).is_ok());
+        assert!(Rlp::decode(&nested(crate::rlp::MAX_DEPTH)).is_err());
+        assert!(Rlp::decode(&nested(100_000)).is_err());
+        assert!(RawTransaction::from_bytes(&nested(100_000)).is_err());
+    }
+
+    #[test]
+    fn test_legacy_transaction() {
+        let tx = RawTransaction::from_hex(EIP155).unwrap();
+        assert_eq!(
+            format!("{:?}", tx.hash),
+            "0x33469b22e9f636356c4160a87eb19df52b7412e8eac32a4a55ffe88ea8350788"
+        );
+        assert_eq!(tx.chain_id, Some(1));
+        assert_eq!(tx.nonce, 9);
+        assert_eq!(tx.tx_type, 0);
+        assert_eq!(tx.gas_price, Some(U256::from(20_000_000_000u64)));
+        assert_eq!(tx.gas_limit, U256::from(21_000));
+        assert_eq!(tx.value, U256::exp10(18));
+        assert_eq!(tx.v, 37);
+        assert!(!tx.has_calldata());
+
+        let decoded = decode_raw_transaction(TRANSFER).unwrap();
+        let call = decoded.decoded.as_ref().unwrap();
+        assert_eq!(call.selector, "a9059cbb");
+        assert_eq!(call.params[1].value, "1000000000000000000");
+        let out = render_raw_transaction(OutputFormat::Pretty, &decoded, &Style::plain());
+        println!("{}", out);
+        assert!(out.contains("To:    0xC02aaA39b223FE8D0A0e5C4F27eAD9083C756Cc2"));
+        assert!(out.contains("[0] address: 0xd8Da6bF26964Af9D7eed9e10E3F4a2Ff91e9a7A6"));
+    }
+
+    #[test]
+    fn test_typed_transactions() {
+        let tx = RawTransaction::from_hex(ACCESS_LIST).unwrap();
+        assert_eq!(
+            (tx.tx_type, tx.chain_id, tx.nonce, tx.v),
+            (1, Some(1), 3, 1)
+        );
+        assert_eq!(tx.gas_price, Some(U256::from(20_000_000_000u64)));
+        assert_eq!(tx.access_list.len(), 1);
+        assert_eq!(tx.access_list[0].address, tx.to.unwrap());
+        assert_eq!(tx.access_list[0].storage_keys[0].to_low_u64_be(), 3);
+
+        let tx = RawTransaction::from_hex(DYNAMIC_FEE).unwrap();
+        assert_eq!((tx.tx_type, tx.nonce, tx.v), (2, 4, 0));
+        assert_eq!(tx.gas_price, None);
+        assert_eq!(tx.max_priority_fee_per_gas, Some(U256::exp10(9)));
+        assert_eq!(tx.max_fee_per_gas, Some(U256::from(30) * U256::exp10(9)));
+        assert!(tx.access_list.is_empty());
+
+        let decoded = decode_raw_transaction(BLOB).unwrap();
+        let tx = &decoded.transaction;
+        assert_eq!((tx.tx_type, tx.type_name()), (3, "blob"));
+        assert_eq!(tx.max_fee_per_blob_gas, Some(U256::from(3)));
+        assert_eq!(tx.blob_versioned_hashes.len(), 1);
+        assert_eq!(tx.blob_versioned_hashes[0].as_bytes()[0], 1);
+        assert_eq!(decoded.decoded.as_ref().unwrap().selector, "a9059cbb");
+        let out = render_raw_transaction(OutputFormat::Pretty, &decoded, &Style::plain());
+        println!("{}", out);
+        assert!(out.contains("Type:  3 (blob)"));
+        assert!(out.contains("Blob:  0x0100"));
+
+        // The sidecar isn't part of the transaction or its hash.
+        let network = RawTransaction::from_hex(BLOB_NETWORK).unwrap();
+        assert_eq!(&network, tx);
+    }
+
+    #[test]
+    fn test_raw_transaction_errors() {
+        assert!(RawTransaction::from_hex("").is_err());
+        assert!(RawTransaction::from_hex("0xzz").is_err());
+        // Six fields: an unsigned transaction.
+        assert!(RawTransaction::from_hex("0xc6808080808080").is_err());
+        // Trailing bytes after the list.
+        assert!(RawTransaction::from_hex(&format!("{}00", EIP155)).is_err());
+        // An unknown type, and a dynamic fee envelope missing its signature.
+        assert!(RawTransaction::from_hex("0x05c0").is_err());
+        assert!(RawTransaction::from_hex("0x02c9010480808080808080").is_err());
+    }
+}
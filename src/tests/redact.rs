@@ -0,0 +1,88 @@
+/*
+cargo test test_redact -- --nocapture --test-threads=1
+*/
+#[cfg(test)]
+mod test_redact {
+    use crate::corpus::{MULTICALL, TRANSFER};
+    use crate::decoded::DecodedCalldata;
+    use crate::redact::*;
+    use crate::{decode_hex, Calldata};
+
+    fn redact(calldata: &str, redactor: &Redactor) -> (DecodedCalldata, Redaction) {
+        let calldata = Calldata::new(calldata);
+        let decoded = calldata.decode();
+        let redaction = redactor.redact(calldata.bytes(), &decoded);
+        (decoded, redaction)
+    }
+
+    #[test]
+    fn test_redact_transfer() {
+        let (decoded, redaction) = redact(TRANSFER, &Redactor::new(b"key"));
+        assert_eq!(redaction.redacted.len(), 2);
+        assert_eq!(redaction.redacted[0].kind, RedactKind::Address);
+        assert_eq!(redaction.redacted[1].kind, RedactKind::Amount);
+        let bytes = decode_hex(&redaction.calldata);
+        assert_eq!(bytes[..4], decode_hex(TRANSFER)[..4]);
+        assert_ne!(redaction.decoded.params[0].value, decoded.params[0].value);
+
+        // Pseudonymized, the decode is that of the redacted calldata.
+        assert_eq!(Calldata::from_bytes(&bytes).decode(), redaction.decoded);
+
+        // The same key gives the same pseudonyms, another key others.
+        assert_eq!(redact(TRANSFER, &Redactor::new(b"key")).1, redaction);
+        let other = redact(TRANSFER, &Redactor::new(b"other")).1;
+        assert_ne!(other.calldata, redaction.calldata);
+
+        let masked = Redactor::new(b"key")
+            .with_mode(RedactMode::Mask)
+            .with_kinds(vec![RedactKind::Address]);
+        let (_, redaction) = redact(TRANSFER, &masked);
+        assert_eq!(redaction.redacted.len(), 1);
+        assert_eq!(redaction.decoded.params[0].value, "[address 1]");
+        assert_eq!(redaction.decoded.params[1], decoded.params[1]);
+    }
+
+    #[test]
+    fn test_redact_multicall() {
+        let (decoded, redaction) = redact(MULTICALL, &Redactor::new(b"key"));
+        let bytes = decode_hex(MULTICALL);
+        let word = |r: &Redacted| &bytes[r.span.start..r.span.end];
+        assert!(redaction
+            .redacted
+            .iter()
+            .any(|r| r.path.starts_with("calls[")));
+
+        // The redacted calldata decodes to the same calls and layout.
+        let shape = |call: &DecodedCalldata| {
+            call.walk()
+                .iter()
+                .map(|c| (c.selector.clone(), c.span, c.params.len()))
+                .collect::<Vec<_>>()
+        };
+        let redecoded = Calldata::new(&redaction.calldata).decode();
+        assert_eq!(shape(&redecoded), shape(&decoded));
+
+        // No word of the original values is left, in the nested calls or in
+        // the params holding them.
+        let words = |call: &DecodedCalldata| {
+            call.walk()
+                .iter()
+                .flat_map(|c| c.params.iter().map(|p| p.raw.clone()))
+                .collect::<Vec<_>>()
+        };
+        let redacted = words(&redaction.decoded);
+        for r in redaction.redacted.iter() {
+            let original = faster_hex::hex_string(word(r));
+            assert!(!redacted.contains(&original), "{} left in", r.path);
+        }
+
+        // The same value gets the same label wherever it appears.
+        for a in redaction.redacted.iter() {
+            for b in redaction.redacted.iter() {
+                if a.kind == b.kind {
+                    assert_eq!(word(a) == word(b), a.label == b.label);
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,314 @@
+/*
+cargo test test_render -- --nocapture --test-threads=1
+*/
+#[cfg(test)]
+mod test_render {
+    use crate::render::address::*;
+    use crate::render::color::*;
+    use crate::render::csv::*;
+    use crate::render::diagram::*;
+    use crate::render::etherscan::*;
+    use crate::render::foundry::*;
+    use crate::render::hexdump::*;
+    use crate::render::markdown::*;
+    use crate::render::numeric::*;
+    use crate::render::output::*;
+    use crate::render::solidity::*;
+    use crate::render::sql::*;
+    use crate::render::tree::*;
+    use crate::schema::{from_json, from_yaml, JsonOutput};
+    use crate::Calldata;
+
+    const MULTICALL: &str = "0xac9650d800000000000000000000000000000000000000000000000000000000000000200000000000000000000000000000000000000000000000000000000000000002000000000000000000000000000000000000000000000000000000000000004000000000000000000000000000000000000000000000000000000000000001e0000000000000000000000000000000000000000000000000000000000000016488316456000000000000000000000000c011a73ee8576fb46f5e1c5751ca3b9fe0af2a6f000000000000000000000000c02aaa39b223fe8d0a0e5c4f27ead9083c756cc20000000000000000000000000000000000000000000000000000000000002710fffffffffffffffffffffffffffffffffffffffffffffffffffffffffffee530ffffffffffffffffffffffffffffffffffffffffffffffffffffffffffff1b18000000000000000000000000000000000000000000000000016345785d89fd6800000000000000000000000000000000000000000000000000007f73eca3063a000000000000000000000000000000000000000000000000016042b530ddaec600000000000000000000000000000000000000000000000000007e59f044bada000000000000000000000000f847e9d51989033b691b8be943f8e9e268f99b9e000000000000000000000000000000000000000000000000000000006377347700000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000412210e8a00000000000000000000000000000000000000000000000000000000";
+
+    #[test]
+    fn test_render_call_tree() {
+        let tree = render_call_tree(&Calldata::new(MULTICALL).decode());
+        println!("{}", tree);
+        let lines = tree.lines().collect::<Vec<&str>>();
+        assert_eq!(lines.len(), 3);
+        assert!(lines[0].starts_with("0xac9650d8 unknown_ac9650d8("));
+        assert!(lines[1].starts_with("├── → [0] 0x88316456 unknown_88316456(address,address,"));
+        assert_eq!(lines[2], "└── → [1] 0x12210e8a unknown_12210e8a()");
+    }
+
+    #[test]
+    fn test_render_tree_with_params() {
+        let tree = render_tree(&Calldata::new(MULTICALL).decode());
+        println!("{}", tree);
+        assert!(tree.contains("├── [0] uint256: 32\n"));
+        assert!(tree.contains("│   ├── [0] address: 0xC011a73ee8576Fb46F5E1c5751cA3B9Fe0af2a6F\n"));
+        assert!(tree.ends_with("└── → [1] 0x12210e8a unknown_12210e8a()\n"));
+    }
+
+    #[test]
+    fn test_render_colored() {
+        let decoded = Calldata::new(MULTICALL).decode();
+        let colored = render_tree_styled(&decoded, true, &Style::new(ColorMode::Always));
+        println!("{}", colored);
+        assert!(colored.starts_with("\x1b[36m0xac9650d8\x1b[0m"));
+        assert!(colored.contains("\x1b[35m0xC011a73ee8576Fb46F5E1c5751cA3B9Fe0af2a6F\x1b[0m"));
+
+        let plain = render_tree_styled(&decoded, true, &Style::new(ColorMode::Never));
+        assert!(!plain.contains('\x1b'));
+        assert_eq!(plain, render_tree(&decoded));
+    }
+
+    #[test]
+    fn test_render_hexdump() {
+        let decoded = Calldata::new(MULTICALL).decode();
+        let roles = word_roles(&decoded);
+        assert_eq!(roles[0], WordRole::Offset(0x20));
+        assert_eq!(roles[2], WordRole::Offset(0x40));
+        assert_eq!(roles[4], WordRole::Length(356));
+        assert_eq!(
+            roles[5],
+            WordRole::Nested {
+                call: 0,
+                selector: "88316456".to_string(),
+                parts: vec!["selector".to_string(), "[0]".to_string()],
+            }
+        );
+        assert_eq!(roles[17], WordRole::Length(4));
+
+        let dump = render_hexdump(&decoded);
+        println!("{}", dump);
+        assert!(dump.starts_with("selector: 0xac9650d8\n"));
+        assert!(dump.contains(
+            "[01] 0x0020 0000000000000000000000000000000000000000000000000000000000000002 // uint8: 2\n"
+        ));
+        assert!(dump.contains("// call 1 (0x12210e8a) selector\n"));
+
+        // Offsets in the array count from the word after its length.
+        assert_eq!(
+            offset_targets(&decoded, &roles)[..4],
+            [Some(1), None, Some(4), Some(17)]
+        );
+        assert!(dump.contains("// offset +0x40 → word 4\n"));
+        // The nested calls' words follow, each typed with its value.
+        assert!(dump.contains("\ncalls[0] at byte 0xa4, selector: 0x88316456\n"));
+        assert!(dump.contains(
+            "[00] 0x0000 000000000000000000000000c011a73ee8576fb46f5e1c5751ca3b9fe0af2a6f // address: 0xC011a73ee8576Fb46F5E1c5751cA3B9Fe0af2a6F\n"
+        ));
+        assert!(dump.ends_with("calls[1] at byte 0x244, selector: 0x12210e8a\n"));
+
+        assert_eq!(decoded.to_string(), dump);
+        assert_eq!(Calldata::new(MULTICALL).to_string(), dump);
+        let colored = render_hexdump_styled(&decoded, &Style::new(ColorMode::Always));
+        assert!(colored.contains("\x1b[35m0xC011a73ee8576Fb46F5E1c5751cA3B9Fe0af2a6F\x1b[0m"));
+
+        // A count read from the calldata can't run the elements past its words.
+        let huge = format!("0xa9059cbb{:064x}{:0>64}", 0x20, "ffffffffffffffff");
+        let decoded = Calldata::new(&huge).decode();
+        let roles = word_roles(&decoded);
+        assert_eq!(offset_targets(&decoded, &roles), vec![Some(1), None]);
+        assert!(decoded.to_string().contains("// offset +0x20 → word 1\n"));
+    }
+
+    #[test]
+    fn test_render_etherscan() {
+        let transfer = "0xa9059cbb000000000000000000000000c02aaa39b223fe8d0a0e5c4f27ead9083c756cc20000000000000000000000000000000000000000000000000de0b6b3a7640000";
+        let view = render_etherscan(&Calldata::new(transfer).decode());
+        println!("{}", view);
+        assert_eq!(
+            view,
+            "Function: unknown_a9059cbb(address,uint256)
+
+MethodID: 0xa9059cbb
+[0]:  000000000000000000000000c02aaa39b223fe8d0a0e5c4f27ead9083c756cc2
+[1]:  0000000000000000000000000000000000000000000000000de0b6b3a7640000
+
+#  Name  Type     Data
+0  _0    address  0xC02aaA39b223FE8D0A0e5C4F27eAD9083C756Cc2
+1  _1    uint256  1000000000000000000
+"
+        );
+
+        let view = render_etherscan(&Calldata::new(MULTICALL).decode());
+        println!("{}", view);
+        assert!(view.contains("[18]: 12210e8a00000000000000000000000000000000000000000000000000000000\n"));
+        assert!(view.contains("\n---------- Call 1 ----------\nFunction: unknown_12210e8a()\n"));
+    }
+
+    #[test]
+    fn test_render_interface() {
+        let interface = render_interface(&Calldata::new(MULTICALL).decode());
+        println!("{}", interface);
+        assert!(interface.starts_with("interface IUnknown {\n    function unknown_ac9650d8(uint256 arg0, uint8 arg1,"));
+        assert!(interface.contains("    function unknown_88316456(address arg0, address arg1,"));
+        assert!(interface.contains("    function unknown_12210e8a() external;\n}\n"));
+        assert!(interface.contains(
+            "// IUnknown(target).unknown_88316456(0xC011a73ee8576Fb46F5E1c5751cA3B9Fe0af2a6F, "
+        ));
+        assert!(interface.ends_with("// IUnknown(target).unknown_12210e8a();\n"));
+    }
+
+    #[test]
+    fn test_render_foundry_test() {
+        let options = ReproOptions {
+            target: "0xc36442b4a4522e871399cd717abdd847ab11fe88".parse().unwrap(),
+            value: 1000.into(),
+            ..Default::default()
+        };
+        let decoded = Calldata::new(MULTICALL).decode();
+        assert_eq!(format!("0x{}", calldata_hex(&decoded)), MULTICALL);
+
+        let test = render_foundry_test(&decoded, &options);
+        println!("{}", test);
+        assert!(test.contains("contract ReproTest is Test {\n"));
+        assert!(test.contains("    address constant TARGET = 0xC36442b4a4522E871399CD717aBDD847Ab11FE88;\n"));
+        assert!(test.contains("    bytes4 constant SELECTOR = 0xac9650d8;\n"));
+        assert!(test.contains("    uint256 constant ARG0 = 32;\n"));
+        assert!(test.contains("        (bool ok, ) = TARGET.call{value: 1000}(data);\n"));
+        assert!(test.contains(&format!("hex\"{}\"", &MULTICALL[2..])));
+    }
+
+    #[test]
+    fn test_render_diagrams() {
+        let decoded = Calldata::new(MULTICALL).decode();
+        let mermaid = render_mermaid(&decoded);
+        println!("{}", mermaid);
+        assert_eq!(
+            mermaid,
+            "flowchart TD
+    n0[\"0xac9650d8<br/>unknown_ac9650d8\"]
+    n0_0[\"0x88316456<br/>unknown_88316456\"]
+    n0_1[\"0x12210e8a<br/>unknown_12210e8a\"]
+    n0 -->|0| n0_0
+    n0 -->|1| n0_1
+"
+        );
+
+        let dot = render_dot(&decoded);
+        println!("{}", dot);
+        assert!(dot.starts_with("digraph calls {\n"));
+        assert!(dot.contains("    n0_1 [label=\"0x12210e8a\\nunknown_12210e8a\"];\n"));
+        assert!(dot.contains("    n0 -> n0_1 [label=\"1\"];\n"));
+    }
+
+    #[test]
+    fn test_render_csv() {
+        let transfer = Calldata::new("0xa9059cbb000000000000000000000000c02aaa39b223fe8d0a0e5c4f27ead9083c756cc20000000000000000000000000000000000000000000000000de0b6b3a7640000").decode();
+        let multicall = Calldata::new(MULTICALL).decode();
+        let csv = render_csv([("0xaa", &transfer), ("0xbb", &multicall)]);
+        println!("{}", csv);
+
+        let lines = csv.lines().collect::<Vec<&str>>();
+        assert_eq!(lines[0], CSV_HEADER);
+        assert_eq!(
+            lines[1],
+            "0xaa,,0xa9059cbb,\"unknown_a9059cbb(address,uint256)\",0,address,0xC02aaA39b223FE8D0A0e5C4F27eAD9083C756Cc2"
+        );
+        // 2 transfer params + 19 multicall words + 11 nested params.
+        assert_eq!(lines.len(), 1 + 2 + 19 + 11);
+        assert!(lines[22].starts_with("0xbb,0,0x88316456,"));
+        assert_eq!(escape("a\"b"), "\"a\"\"b\"");
+    }
+
+    #[test]
+    fn test_render_markdown() {
+        let report = render_markdown("Multicall", &Calldata::new(MULTICALL).decode());
+        println!("{}", report);
+        assert!(report.starts_with("# Multicall\n\n## Summary\n\n- **Selector:** `0xac9650d8`\n"));
+        assert!(report.contains("- **Nested calls:** 2\n"));
+        assert!(report.contains("| root.1 | `0x12210e8a` | `unknown_12210e8a()` |\n"));
+        assert!(report.contains("## Warnings\n\nNone.\n"));
+        assert!(report.contains("\n## Call `root.1`: `0x12210e8a`\n\nNo params.\n"));
+        assert!(report.contains(
+            "| 0 | `address` | Address (50%), Bytes20 (33%), Uint (17%) | `0xC011a73ee8576Fb46F5E1c5751cA3B9Fe0af2a6F` |\n"
+        ));
+    }
+
+    #[test]
+    fn test_render_sql() {
+        let decoded = Calldata::new("0xa9059cbb000000000000000000000000c02aaa39b223fe8d0a0e5c4f27ead9083c756cc20000000000000000000000000000000000000000000000000de0b6b3a7640000").decode();
+        assert!(create_table("params", Dialect::ClickHouse).contains("ENGINE = MergeTree"));
+        assert_eq!(
+            parameterized_insert("params", Dialect::Postgres),
+            "INSERT INTO params (tx_id, path, depth, selector, signature_guess, param_index, type, candidates, raw, value) VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10)"
+        );
+        assert!(parameterized_insert("params", Dialect::ClickHouse).contains("{p3:UInt32}, {p4:String}"));
+
+        let params = insert_params([("0xaa", &decoded)]);
+        assert_eq!(params.len(), 2);
+        assert_eq!(params[1][9], "1000000000000000000");
+
+        let inserts = render_inserts("params", [("it's", &decoded)]);
+        println!("{}", inserts);
+        assert!(inserts.contains("('it''s', '', 0, '0xa9059cbb', 'unknown_a9059cbb(address,uint256)', 1, 'uint256',"));
+
+        let copy = render_copy([("0xaa", &decoded)]);
+        assert_eq!(copy.lines().count(), 2);
+        assert!(copy.starts_with("0xaa\t\t0\t0xa9059cbb\t"));
+    }
+
+    #[test]
+    fn test_numeric_formats() {
+        let v = primitive_types::U256::from_dec_str("1500000000000000000").unwrap();
+        assert_eq!(format_uint(v, NumberFormat::Hex), "0x14d1120d7b160000");
+        assert_eq!(format_uint(v, NumberFormat::Decimal), "1500000000000000000");
+        assert_eq!(format_uint(v, NumberFormat::Scientific), "1.5e18");
+        assert_eq!(format_uint(v, NumberFormat::Units(18)), "1.5");
+        assert_eq!(format_uint(v, NumberFormat::AutoUnits), "1.5");
+        assert_eq!(format_uint(2500000.into(), NumberFormat::AutoUnits), "2.5");
+        assert_eq!(format_uint(10000.into(), NumberFormat::Scientific), "10000");
+        assert_eq!(format_uint(1000000.into(), NumberFormat::Scientific), "1e6");
+    }
+
+    #[test]
+    fn test_apply_numeric() {
+        let mut decoded = Calldata::new(MULTICALL).decode();
+        let options = NumericOptions::new(NumberFormat::Hex)
+            .with_override("0", 2, NumberFormat::Decimal);
+        apply_numeric(&mut decoded, &options);
+        assert_eq!(decoded.params[0].value, "0x20");
+        assert_eq!(decoded.calls[0].params[2].value, "10000");
+        assert_eq!(decoded.calls[0].params[5].value, "0x16345785d89fd68");
+        // Addresses are left alone.
+        assert_eq!(decoded.calls[0].params[0].value, "0xC011a73ee8576Fb46F5E1c5751cA3B9Fe0af2a6F");
+    }
+
+    #[test]
+    fn test_address_case() {
+        assert_eq!(
+            checksum_hex("0xc02aaa39b223fe8d0a0e5c4f27ead9083c756cc2").as_deref(),
+            Some("0xC02aaA39b223FE8D0A0e5C4F27eAD9083C756Cc2")
+        );
+        assert_eq!(checksum_hex("c02aaa39"), None);
+
+        let mut decoded = Calldata::new(MULTICALL).decode();
+        apply_address_case(&mut decoded, AddressCase::Checksum);
+        assert_eq!(
+            decoded.calls[0].params[0].value,
+            "0xC011a73ee8576Fb46F5E1c5751cA3B9Fe0af2a6F"
+        );
+        apply_address_case(&mut decoded, AddressCase::Lowercase);
+        assert_eq!(
+            decoded.calls[0].params[0].value,
+            "0xc011a73ee8576fb46f5e1c5751ca3b9fe0af2a6f"
+        );
+        // Other params are left alone.
+        assert_eq!(decoded.calls[0].params[5].value, "99999999999999336");
+    }
+
+    #[test]
+    fn test_render_output_formats() {
+        let output = JsonOutput::new(&Calldata::new(MULTICALL));
+        let render = |format| render_output(format, "7", &output, &Style::plain());
+
+        assert_eq!(render(OutputFormat::Pretty), render_tree(&output.root));
+        assert_eq!(render(OutputFormat::Tree), render_call_tree(&output.root));
+        assert_eq!(render(OutputFormat::Etherscan), render_etherscan(&output.root));
+        assert_eq!(render(OutputFormat::Words), render_hexdump(&output.root));
+        assert_eq!(from_json(render(OutputFormat::Json).trim_end()).unwrap(), output);
+        assert_eq!(from_yaml(&render(OutputFormat::Yaml)).unwrap(), output);
+
+        let csv = render(OutputFormat::Csv);
+        assert!(csv.lines().all(|l| l.starts_with("7,")));
+        assert_eq!(OutputFormat::Csv.header(), Some(CSV_HEADER));
+        assert_eq!(OutputFormat::Pretty.header(), None);
+
+        assert_eq!("yaml".parse::<OutputFormat>().unwrap(), OutputFormat::Yaml);
+        assert!("xml".parse::<OutputFormat>().is_err());
+    }
+}
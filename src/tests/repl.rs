@@ -0,0 +1,77 @@
+/*
+cargo test test_repl -- --nocapture --test-threads=1
+*/
+#[cfg(test)]
+mod test_repl {
+    use crate::heuristics::Heuristics;
+    use crate::render::color::Style;
+    use crate::repl::*;
+    use crate::signatures::Signatures;
+    use crate::Calldata;
+
+    const MULTICALL: &str = "0xac9650d800000000000000000000000000000000000000000000000000000000000000200000000000000000000000000000000000000000000000000000000000000002000000000000000000000000000000000000000000000000000000000000004000000000000000000000000000000000000000000000000000000000000001e0000000000000000000000000000000000000000000000000000000000000016488316456000000000000000000000000c011a73ee8576fb46f5e1c5751ca3b9fe0af2a6f000000000000000000000000c02aaa39b223fe8d0a0e5c4f27ead9083c756cc20000000000000000000000000000000000000000000000000000000000002710fffffffffffffffffffffffffffffffffffffffffffffffffffffffffffee530ffffffffffffffffffffffffffffffffffffffffffffffffffffffffffff1b18000000000000000000000000000000000000000000000000016345785d89fd6800000000000000000000000000000000000000000000000000007f73eca3063a000000000000000000000000000000000000000000000000016042b530ddaec600000000000000000000000000000000000000000000000000007e59f044bada000000000000000000000000f847e9d51989033b691b8be943f8e9e268f99b9e000000000000000000000000000000000000000000000000000000006377347700000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000412210e8a00000000000000000000000000000000000000000000000000000000";
+
+    fn output(reply: Reply) -> String {
+        match reply {
+            Reply::Output(out) => out,
+            Reply::Quit => panic!("unexpected quit"),
+        }
+    }
+
+    #[test]
+    fn test_session() {
+        let mut session = Session::new(Signatures::builtin(), Style::plain());
+        assert_eq!(output(session.handle(":redo")), "nothing decoded yet");
+
+        let out = output(session.handle(MULTICALL));
+        assert!(out.starts_with("0xac9650d8 multicall(bytes[])"), "{}", out);
+        assert!(out.contains("0x12210e8a refundETH()"), "{}", out);
+        assert_eq!(session.cached(), 3);
+
+        // Nested calls disappear once the heuristic is off, the cache stays warm.
+        let out = output(session.handle(":set nested off"));
+        assert!(!out.contains("refundETH"), "{}", out);
+        assert_eq!(session.cached(), 3);
+        assert!(output(session.handle(":heuristics")).contains("nested        off"));
+        assert!(output(session.handle(":set bogus on")).contains("unknown heuristic"));
+
+        output(session.handle(":set nested on"));
+        let out = output(session.handle(":format tree"));
+        assert_eq!(out.lines().count(), 3, "{}", out);
+        assert!(output(session.handle(":format json")).starts_with("{\"version\":1"));
+        assert!(output(session.handle(":format xml")).contains("unknown format"));
+
+        assert_eq!(session.handle(":quit"), Reply::Quit);
+    }
+
+    #[test]
+    fn test_register() {
+        let mut session = Session::new(Signatures::new(), Style::plain());
+        output(session.handle(MULTICALL));
+        assert!(session.resolve("ac9650d8").is_empty());
+
+        assert_eq!(
+            output(session.handle(":sig multicall(bytes[])")),
+            "0xac9650d8 multicall(bytes[])"
+        );
+        assert_eq!(session.resolve("ac9650d8"), vec!["multicall(bytes[])"]);
+        assert!(output(session.handle(":redo")).starts_with("0xac9650d8 multicall(bytes[])"));
+        assert!(output(session.handle(":nope")).contains("unknown command"));
+    }
+
+    #[test]
+    fn test_heuristics() {
+        let transfer = "0xa9059cbb000000000000000000000000c02aaa39b223fe8d0a0e5c4f27ead9083c756cc20000000000000000000000000000000000000000000000000de0b6b3a7640000";
+        let mut heuristics = Heuristics::default();
+        assert_eq!(heuristics.get("addresses"), Some(true));
+        heuristics.set("addresses", false).unwrap();
+
+        let root = Calldata::with_heuristics(transfer, heuristics).decode();
+        assert_eq!(root.params[0].types[0].solidity, "uint256");
+        assert_eq!(
+            Calldata::new(transfer).decode().params[0].types[0].solidity,
+            "address"
+        );
+        assert!(Heuristics::none().set("nope", true).is_err());
+    }
+}
@@ -0,0 +1,106 @@
+/*
+cargo test test_returndata -- --nocapture --test-threads=1
+*/
+#[cfg(test)]
+mod test_returndata {
+    use crate::decode_hex;
+    use crate::render::tree::render_return;
+    use crate::returndata::*;
+    use crate::Calldata;
+
+    const BALANCE_OF: &str =
+        "0x70a08231000000000000000000000000c02aaa39b223fe8d0a0e5c4f27ead9083c756cc2";
+    const AMOUNT: &str = "0x0000000000000000000000000000000000000000000000000de0b6b3a7640000";
+
+    #[test]
+    fn test_return_signatures() {
+        let returns =
+            ReturnSignature::parse("getReserves() returns (uint112 a, uint112, uint32)").unwrap();
+        assert_eq!(returns.signature, "getReserves()");
+        assert_eq!(returns.outputs, vec!["uint112", "uint112", "uint32"]);
+
+        let returns =
+            ReturnSignature::parse("f(uint)((address,bytes)[],string,(bool,uint8))").unwrap();
+        assert_eq!(returns.signature, "f(uint256)");
+        assert_eq!(
+            returns.head_types(),
+            vec!["(address,bytes)[]", "string", "bool", "uint8"]
+        );
+
+        assert!(ReturnSignature::parse("f()").unwrap().outputs.is_empty());
+        assert!(ReturnSignature::parse("f(uint256)(adress)").is_none());
+        assert!(ReturnSignature::parse("f(uint256").is_none());
+
+        let known = ReturnSignatures::builtin();
+        assert_eq!(known.len(), BUILTIN_RETURNS.len());
+        assert_eq!(known.resolve("0x70A08231")[0].outputs, vec!["uint256"]);
+        assert!(known.resolve("12345678").is_empty());
+        assert!(ReturnSignatures::from_text("f()(bool)\n# comment\nnot a function").is_err());
+    }
+
+    #[test]
+    fn test_known_return() {
+        let call = Calldata::new(BALANCE_OF).decode();
+        let decoded = ReturnDecoder::new()
+            .decode_for(&call, &decode_hex(AMOUNT))
+            .unwrap();
+        println!("{}", render_return(&decoded));
+        assert!(decoded.known);
+        assert_eq!(decoded.signature.as_deref(), Some("balanceOf(address)"));
+        assert_eq!(decoded.returns_guess, "(uint256)");
+        assert_eq!(decoded.params[0].value, "1000000000000000000");
+        assert_eq!(decoded.params[0].span.start, 0);
+
+        // A zero balance is still rendered as the declared uint256.
+        let zero = decode_hex(&"0".repeat(64));
+        let decoded = ReturnDecoder::new().decode_for(&call, &zero).unwrap();
+        assert_eq!(decoded.params[0].types[0].solidity, "uint256");
+        assert_eq!(decoded.params[0].value, "0");
+    }
+
+    #[test]
+    fn test_unknown_return() {
+        // (address, string "WETH") from a function with no known outputs.
+        let data = decode_hex(&format!(
+            "{:0>64}{:064x}{:064x}{:0<64}",
+            "c02aaa39b223fe8d0a0e5c4f27ead9083c756cc2", 64, 4, "57455448"
+        ));
+        let call = Calldata::new("0x12345678").decode();
+        let decoded = ReturnDecoder::new().decode_for(&call, &data).unwrap();
+        println!("{}", render_return(&decoded));
+        assert!(!decoded.known);
+        assert_eq!(decoded.signature.as_deref(), Some("unknown_12345678()"));
+        assert_eq!(decoded.params[0].types[0].solidity, "address");
+        assert_eq!(decoded.params[2].text.as_ref().unwrap().text, "WETH");
+        assert!(decoded.returns_guess.starts_with("(address,"));
+
+        let decoded = decode_returndata(&data).unwrap();
+        assert!(decoded.signature.is_none());
+        assert_eq!(decoded.params.len(), 4);
+
+        // A function returning nothing.
+        let decoded = decode_returndata(&[]).unwrap();
+        assert_eq!(decoded.returns_guess, "()");
+        assert!(decoded.params.is_empty());
+    }
+
+    #[test]
+    fn test_return_data() {
+        let data = ReturnData::from_hex(AMOUNT).unwrap();
+        assert_eq!(data.bytes(), decode_hex(AMOUNT));
+        assert_eq!(
+            data.decode().unwrap(),
+            decode_returndata(&decode_hex(AMOUNT)).unwrap()
+        );
+        let call = Calldata::new(BALANCE_OF).decode();
+        assert!(data.decode_for(&call).unwrap().known);
+
+        // Known outputs replaced: typed by the heuristics alone.
+        let decoded = ReturnData::new(&decode_hex(AMOUNT))
+            .with_decoder(ReturnDecoder::new().with_returns(ReturnSignatures::new()))
+            .decode_for(&call)
+            .unwrap();
+        assert!(!decoded.known);
+        assert!(ReturnData::from_hex("0xzz").is_err());
+    }
+}
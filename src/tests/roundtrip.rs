@@ -0,0 +1,122 @@
+/*
+cargo test test_roundtrip -- --nocapture --test-threads=1
+*/
+#[cfg(all(test, feature = "ethers"))]
+mod test_roundtrip {
+    use crate::decoded::DecodedCalldata;
+    use crate::layout::Fact;
+    use crate::Calldata;
+    use ethers::abi::{encode, Token};
+    use ethers::types::{Address, U256};
+    use proptest::prelude::*;
+
+    /// A param and the Solidity type it was encoded as.
+    #[derive(Debug, Clone)]
+    struct Param {
+        ty: &'static str,
+        token: Token,
+    }
+
+    impl Param {
+        fn is_dynamic(&self) -> bool {
+            matches!(
+                self.token,
+                Token::Bytes(_) | Token::String(_) | Token::Array(_)
+            )
+        }
+    }
+
+    /// Static params whose value alone is enough to tell their type.
+    fn static_param() -> impl Strategy<Value = Param> {
+        prop_oneof![
+            // A leading zero nibble makes an address read as a plain number.
+            (0x10u8.., any::<[u8; 19]>()).prop_map(|(first, rest)| {
+                let mut bytes = [first; 20];
+                bytes[1..].copy_from_slice(&rest);
+                Param {
+                    ty: "address",
+                    token: Token::Address(Address::from(bytes)),
+                }
+            }),
+            (2u64..).prop_map(|n| Param {
+                ty: "uint256",
+                token: Token::Uint(U256::from(n)),
+            }),
+            Just(Param {
+                ty: "bool",
+                token: Token::Bool(true),
+            }),
+        ]
+    }
+
+    /// Static and dynamic params, the dynamic ones with a non-empty tail.
+    fn param() -> impl Strategy<Value = Param> {
+        prop_oneof![
+            3 => static_param(),
+            1 => prop::collection::vec(any::<u8>(), 1..80).prop_map(|b| Param {
+                ty: "bytes",
+                token: Token::Bytes(b),
+            }),
+            1 => "[a-z]{1,40}".prop_map(|s| Param {
+                ty: "string",
+                token: Token::String(s),
+            }),
+            1 => prop::collection::vec(1u64.., 1..5).prop_map(|v| Param {
+                ty: "uint256[]",
+                token: Token::Array(v.into_iter().map(|n| Token::Uint(U256::from(n))).collect()),
+            }),
+        ]
+    }
+
+    /// Selector followed by the ABI encoding of `params`.
+    fn calldata(selector: [u8; 4], params: &[Param]) -> Vec<u8> {
+        let tokens = params.iter().map(|p| p.token.clone()).collect::<Vec<_>>();
+        [selector.to_vec(), encode(&tokens)].concat()
+    }
+
+    fn solidity(root: &DecodedCalldata, index: usize) -> Vec<String> {
+        root.params[index]
+            .types
+            .iter()
+            .map(|t| t.solidity.clone())
+            .collect()
+    }
+
+    proptest! {
+        #![proptest_config(ProptestConfig::with_cases(256))]
+
+        #[test]
+        fn test_roundtrip_param_count(selector in any::<[u8; 4]>(), params in prop::collection::vec(param(), 0..8)) {
+            let bytes = calldata(selector, &params);
+            let root = Calldata::from_bytes(&bytes).decode();
+            prop_assert_eq!(root.params.len(), (bytes.len() - 4) / 32);
+        }
+
+        #[test]
+        fn test_roundtrip_static_types(selector in any::<[u8; 4]>(), params in prop::collection::vec(static_param(), 1..8)) {
+            let bytes = calldata(selector, &params);
+            let root = Calldata::from_bytes(&bytes).decode();
+            for (i, param) in params.iter().enumerate() {
+                prop_assert!(
+                    root.params[i].types.iter().any(|t| t.kind.matches_solidity(param.ty)),
+                    "param {} is {} but guessed {:?}", i, param.ty, solidity(&root, i)
+                );
+            }
+        }
+
+        #[test]
+        fn test_roundtrip_dynamic_regions(selector in any::<[u8; 4]>(), params in prop::collection::vec(param(), 1..6)) {
+            let bytes = calldata(selector, &params);
+            let calldata = Calldata::from_bytes(&bytes);
+            let layout = calldata.layout();
+            for (i, param) in params.iter().enumerate().filter(|(_, p)| p.is_dynamic()) {
+                let at = 4 + i * 32;
+                let value = U256::from_big_endian(&bytes[at..at + 32]).as_usize();
+                prop_assert!(
+                    layout.facts.contains(&Fact::Offset { at, value }),
+                    "offset of param {} ({}) at byte {} not found in {:?}", i, param.ty, at, layout.facts
+                );
+            }
+        }
+    }
+}
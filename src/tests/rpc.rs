@@ -0,0 +1,85 @@
+/*
+cargo test test_rpc -- --nocapture --test-threads=1
+*/
+#[cfg(all(test, feature = "ethers"))]
+mod test_rpc {
+    use crate::render::color::Style;
+    use crate::render::output::{render_block, render_transaction, OutputFormat};
+    use crate::rpc::*;
+    use ethers::types::{Bytes, Transaction};
+
+    #[test]
+    fn test_decode_fetched_transaction() {
+        let tx = Transaction {
+            from: "0xf847e9d51989033b691b8be943f8e9e268f99b9e".parse().unwrap(),
+            to: Some("0xc02aaa39b223fe8d0a0e5c4f27ead9083c756cc2".parse().unwrap()),
+            value: 5.into(),
+            block_number: Some(16_000_000.into()),
+            input: "0xa9059cbb000000000000000000000000c02aaa39b223fe8d0a0e5c4f27ead9083c756cc20000000000000000000000000000000000000000000000000de0b6b3a7640000"
+                .parse::<Bytes>()
+                .unwrap(),
+            ..Default::default()
+        };
+        let decoded = DecodedTransaction::from_transaction(&tx).unwrap();
+        assert_eq!(decoded.block_number, Some(16_000_000));
+        assert_eq!(decoded.decoded.selector, "a9059cbb");
+        assert_eq!(decoded.decoded.params.len(), 2);
+
+        let json = serde_json::to_value(&decoded).unwrap();
+        assert_eq!(json["from"], "0xf847E9d51989033b691b8BE943F8E9E268F99B9E");
+        assert_eq!(json["to"], "0xC02aaA39b223FE8D0A0e5C4F27eAD9083C756Cc2");
+        assert_eq!(json["decoded"]["selector"], "a9059cbb");
+
+        let pretty = render_transaction(OutputFormat::Pretty, &decoded, &Style::plain());
+        assert!(pretty.starts_with("Tx:    0x0000"));
+        assert!(pretty.contains("To:    0xC02aaA39b223FE8D0A0e5C4F27eAD9083C756Cc2\n"));
+        assert!(pretty.contains("Block: 16000000\n\n0xa9059cbb "));
+        let csv = render_transaction(OutputFormat::Csv, &decoded, &Style::plain());
+        assert_eq!(csv.lines().count(), 2);
+        let json = render_transaction(OutputFormat::Json, &decoded, &Style::plain());
+        assert!(json.starts_with("{\"hash\":"));
+    }
+
+    #[test]
+    fn test_transaction_without_calldata() {
+        let tx = Transaction::default();
+        assert!(DecodedTransaction::from_transaction(&tx).is_err());
+    }
+
+    #[test]
+    fn test_block_report() {
+        let transfer = "0xa9059cbb000000000000000000000000c02aaa39b223fe8d0a0e5c4f27ead9083c756cc20000000000000000000000000000000000000000000000000de0b6b3a7640000";
+        let approve = "0x095ea7b3000000000000000000000000c02aaa39b223fe8d0a0e5c4f27ead9083c756cc20000000000000000000000000000000000000000000000000de0b6b3a7640000";
+        let txs = [transfer, "0x", approve, transfer]
+            .iter()
+            .enumerate()
+            .map(|(i, input)| Transaction {
+                hash: ethers::types::H256::from_low_u64_be(i as u64),
+                input: input.parse::<Bytes>().unwrap(),
+                ..Default::default()
+            })
+            .collect::<Vec<Transaction>>();
+
+        let report = BlockReport::new(18_000_000, &txs, &crate::signatures::Signatures::builtin());
+        assert_eq!(report.total, 4);
+        assert_eq!(report.skipped, 1);
+        // Decodes keep the block's order.
+        let hashes = report.transactions.iter().map(|t| t.hash.to_low_u64_be()).collect::<Vec<u64>>();
+        assert_eq!(hashes, vec![0, 2, 3]);
+
+        assert_eq!(report.groups.len(), 2);
+        assert_eq!(report.groups[0].selector, "0xa9059cbb");
+        assert_eq!(report.groups[0].count, 2);
+        assert_eq!(report.groups[0].signatures, vec!["transfer(address,uint256)"]);
+        assert_eq!(report.groups[1].signatures, vec!["approve(address,uint256)"]);
+
+        let summary = render_block(OutputFormat::Pretty, &report, true, &Style::plain());
+        assert_eq!(
+            summary,
+            "Block 18000000: 4 transactions, 3 decoded, 1 skipped\n\n2  0xa9059cbb  transfer(address,uint256)\n1  0x095ea7b3  approve(address,uint256)\n"
+        );
+        let full = render_block(OutputFormat::Tree, &report, false, &Style::plain());
+        assert_eq!(full.matches("Tx:").count(), 3);
+        assert_eq!(render_block(OutputFormat::Csv, &report, false, &Style::plain()).lines().count(), 6);
+    }
+}
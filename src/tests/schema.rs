@@ -0,0 +1,126 @@
+/*
+cargo test test_schema -- --nocapture --test-threads=1
+*/
+#[cfg(test)]
+mod test_schema {
+    use crate::constants::Types;
+    use crate::decoded::DecodeStatus;
+    use crate::schema::*;
+    use crate::Calldata;
+
+    const MULTICALL: &str = "0xac9650d800000000000000000000000000000000000000000000000000000000000000200000000000000000000000000000000000000000000000000000000000000002000000000000000000000000000000000000000000000000000000000000004000000000000000000000000000000000000000000000000000000000000001e0000000000000000000000000000000000000000000000000000000000000016488316456000000000000000000000000c011a73ee8576fb46f5e1c5751ca3b9fe0af2a6f000000000000000000000000c02aaa39b223fe8d0a0e5c4f27ead9083c756cc20000000000000000000000000000000000000000000000000000000000002710fffffffffffffffffffffffffffffffffffffffffffffffffffffffffffee530ffffffffffffffffffffffffffffffffffffffffffffffffffffffffffff1b18000000000000000000000000000000000000000000000000016345785d89fd6800000000000000000000000000000000000000000000000000007f73eca3063a000000000000000000000000000000000000000000000000016042b530ddaec600000000000000000000000000000000000000000000000000007e59f044bada000000000000000000000000f847e9d51989033b691b8be943f8e9e268f99b9e000000000000000000000000000000000000000000000000000000006377347700000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000412210e8a00000000000000000000000000000000000000000000000000000000";
+
+    #[test]
+    fn test_json_shape() {
+        let calldata = Calldata::new(MULTICALL);
+        let json = to_json_pretty(&calldata);
+        println!("{}", json);
+
+        let value: serde_json::Value = serde_json::from_str(&json).unwrap();
+        assert_eq!(value["version"], SCHEMA_VERSION);
+        assert_eq!(value["root"]["selector"], "ac9650d8");
+        assert_eq!(value["root"]["params"][0]["value"], "32");
+        assert_eq!(value["root"]["params"][0]["span"]["start"], 4);
+        assert_eq!(value["root"]["calls"][0]["selector"], "88316456");
+        assert_eq!(value["root"]["calls"][0]["span"]["start"], 164);
+        assert_eq!(
+            value["root"]["calls"][0]["params"][0]["value"],
+            "0xC011a73ee8576Fb46F5E1c5751cA3B9Fe0af2a6F"
+        );
+        assert!(value["root"]["signatureGuess"]
+            .as_str()
+            .unwrap()
+            .starts_with("unknown_ac9650d8("));
+    }
+
+    #[test]
+    fn test_json_round_trip() {
+        let calldata = Calldata::new(MULTICALL);
+        let output = from_json(&to_json(&calldata)).unwrap();
+        assert_eq!(output, JsonOutput::new(&calldata));
+        assert_eq!(output.root.calls[0].params[0].types[0].kind, Types::Address);
+    }
+
+    #[test]
+    fn test_from_hex() {
+        let output = JsonOutput::from_hex(MULTICALL).unwrap();
+        assert_eq!(output, JsonOutput::new(&Calldata::new(MULTICALL)));
+        assert_eq!(
+            JsonOutput::from_hex(&format!(" {}\n", MULTICALL)).unwrap(),
+            output
+        );
+
+        assert!(JsonOutput::from_hex("0xa9059cbz")
+            .unwrap_err()
+            .contains("'z'"));
+        assert!(JsonOutput::from_hex("0xa9059cb").is_err());
+    }
+
+    #[test]
+    fn test_decode_json() {
+        let request = format!(r#"{{"calldata": "{}"}}"#, MULTICALL);
+        let response: DecodeResponse = serde_json::from_str(&decode_json(&request)).unwrap();
+        assert_eq!(response.version, SCHEMA_VERSION);
+        assert_eq!(response.status, DecodeStatus::Decoded);
+        assert_eq!(
+            response.output,
+            Some(JsonOutput::from_hex(MULTICALL).unwrap())
+        );
+        assert_eq!(response.error, None);
+
+        let request = format!(
+            r#"{{"version": 1, "calldata": "{}", "options": {{"heuristics": {{"nested": false}}, "addressCase": "lowercase"}}}}"#,
+            MULTICALL
+        );
+        let response: DecodeResponse = serde_json::from_str(&decode_json(&request)).unwrap();
+        let root = response.output.unwrap().root;
+        assert!(root.calls.is_empty());
+
+        let value: serde_json::Value = serde_json::from_str(&decode_json("{}")).unwrap();
+        assert_eq!(value["status"], "invalid");
+        assert!(value["error"]
+            .as_str()
+            .unwrap()
+            .contains("missing field `calldata`"));
+        assert!(value.get("output").is_none());
+        for request in [
+            r#"{"version": 2, "calldata": "0xa9059cbb"}"#,
+            r#"{"calldata": "0xa9059cbz"}"#,
+            r#"{"calldata": "0xa9059cbb", "options": {"color": true}}"#,
+        ] {
+            let response: DecodeResponse = serde_json::from_str(&decode_json(request)).unwrap();
+            assert_eq!(response.status, DecodeStatus::Invalid);
+            assert!(response.error.is_some(), "{}", request);
+        }
+    }
+
+    #[test]
+    fn test_json_rejects_other_versions() {
+        let calldata = Calldata::new(MULTICALL);
+        let json = to_json(&calldata).replacen("\"version\":1", "\"version\":99", 1);
+        assert!(from_json(&json).is_err());
+    }
+
+    #[test]
+    fn test_yaml_round_trip() {
+        let calldata = Calldata::new(MULTICALL);
+        let yaml = to_yaml(&calldata);
+        println!("{}", yaml);
+        assert!(yaml.starts_with("version: 1\n"));
+        assert!(yaml.contains("signatureGuess: unknown_ac9650d8("));
+        assert_eq!(from_yaml(&yaml).unwrap(), JsonOutput::new(&calldata));
+    }
+
+    #[test]
+    fn test_jsonl() {
+        let batch = [Calldata::new(MULTICALL), Calldata::new(MULTICALL)];
+        let mut out = vec![];
+        assert_eq!(write_jsonl(&mut out, batch.iter()).unwrap(), 2);
+
+        let out = String::from_utf8(out).unwrap();
+        let lines = out.lines().collect::<Vec<&str>>();
+        assert_eq!(lines.len(), 2);
+        assert_eq!(lines[0], to_json(&batch[0]));
+        assert!(out.ends_with("}\n"));
+    }
+}
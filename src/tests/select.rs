@@ -0,0 +1,87 @@
+/*
+cargo test test_select -- --nocapture --test-threads=1
+*/
+#[cfg(test)]
+mod test_select {
+    use crate::decoded::DecodeStatus;
+    use crate::render::output::OutputFormat;
+    use crate::select::*;
+    use crate::{check_hex, Calldata};
+
+    const TRANSFER: &str = "0xa9059cbb000000000000000000000000c02aaa39b223fe8d0a0e5c4f27ead9083c756cc20000000000000000000000000000000000000000000000000de0b6b3a7640000";
+
+    #[test]
+    fn test_parse_path() {
+        assert_eq!(
+            parse_path("params[0].value").unwrap(),
+            vec![
+                Segment::Key("params".to_string()),
+                Segment::Index(0),
+                Segment::Key("value".to_string())
+            ]
+        );
+        assert_eq!(parse_path("calls[1][0]").unwrap().len(), 3);
+        assert!(parse_path("params[x]").is_err());
+        assert!(parse_path("params[0").is_err());
+        assert!(parse_path("a..b").is_err());
+    }
+
+    #[test]
+    fn test_project() {
+        let root = serde_json::to_value(Calldata::new(TRANSFER).decode()).unwrap();
+        let paths = [
+            "selector",
+            "params[1].value",
+            "signature_guess",
+            "params[9].value",
+        ]
+        .iter()
+        .map(|p| p.to_string())
+        .collect::<Vec<String>>();
+        let values = project(&root, &paths).unwrap();
+        assert_eq!(values[0], "a9059cbb");
+        assert_eq!(values[1], "1000000000000000000");
+        assert_eq!(values[2], "unknown_a9059cbb(address,uint256)");
+        assert!(values[3].is_null());
+
+        assert_eq!(
+            render_projection(OutputFormat::Pretty, &paths, &values),
+            "a9059cbb\t1000000000000000000\tunknown_a9059cbb(address,uint256)\t\n"
+        );
+        assert_eq!(
+            render_projection(OutputFormat::Csv, &paths, &values),
+            "a9059cbb,1000000000000000000,\"unknown_a9059cbb(address,uint256)\",\n"
+        );
+        assert_eq!(
+            projection_header(&paths),
+            "selector,params[1].value,signature_guess,params[9].value"
+        );
+        let json = render_projection(OutputFormat::Json, &paths, &values);
+        assert!(json.starts_with("{\"selector\":\"a9059cbb\",\"params[1].value\":"));
+    }
+
+    #[test]
+    fn test_status() {
+        assert_eq!(
+            Calldata::new(TRANSFER).decode().status(),
+            DecodeStatus::Decoded
+        );
+        // A trailing half word doesn't fit the layout.
+        let partial = format!("{}0011223344556677", TRANSFER);
+        assert_eq!(
+            Calldata::new(&partial).decode().status(),
+            DecodeStatus::Partial
+        );
+
+        assert!(check_hex(TRANSFER).is_ok());
+        let e = check_hex("0xa9059cbz").unwrap_err();
+        assert!(e.to_string().contains("position 9"));
+        assert!(check_hex("0xa9059cb").is_err());
+        assert!(check_hex("0xa905").is_err());
+        assert_eq!(DecodeStatus::Partial.exit_code(), 2);
+        assert_eq!(
+            DecodeStatus::Partial.max(DecodeStatus::Invalid),
+            DecodeStatus::Invalid
+        );
+    }
+}
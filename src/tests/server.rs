@@ -0,0 +1,75 @@
+/*
+cargo test --features server test_server -- --nocapture --test-threads=1
+*/
+#[cfg(all(test, feature = "server"))]
+mod test_server {
+    use crate::decoded::DecodeStatus;
+    use crate::decoder::Decoder;
+    use crate::schema::{DecodeResponse, JsonOutput};
+    use crate::server::*;
+    use axum::body::{to_bytes, Body};
+    use axum::http::{Request, StatusCode};
+    use std::sync::Arc;
+    use tower::ServiceExt;
+
+    const TRANSFER: &str = "0xa9059cbb000000000000000000000000c02aaa39b223fe8d0a0e5c4f27ead9083c756cc20000000000000000000000000000000000000000000000000de0b6b3a7640000";
+
+    /// Status and body of a POST of `body` to `path`.
+    async fn post(path: &str, body: String) -> (StatusCode, serde_json::Value) {
+        let request = Request::post(path)
+            .header("content-type", "application/json")
+            .body(Body::from(body))
+            .unwrap();
+        let response = router(Arc::new(Decoder::new()))
+            .oneshot(request)
+            .await
+            .unwrap();
+        let status = response.status();
+        let bytes = to_bytes(response.into_body(), usize::MAX).await.unwrap();
+        (status, serde_json::from_slice(&bytes).unwrap_or_default())
+    }
+
+    #[tokio::test]
+    async fn test_decode() {
+        let (status, body) = post("/decode", format!(r#"{{"calldata": "{}"}}"#, TRANSFER)).await;
+        assert_eq!(status, StatusCode::OK);
+        let response: DecodeResponse = serde_json::from_value(body).unwrap();
+        assert_eq!(response.status, DecodeStatus::Decoded);
+        assert_eq!(
+            response.output,
+            Some(JsonOutput::from_hex(TRANSFER).unwrap())
+        );
+        assert_eq!(
+            response.summary.as_deref(),
+            Some("transfer 1000000000000000000 to 0xC02a…6Cc2")
+        );
+
+        let (status, body) = post("/decode", r#"{"calldata": "0xa9059cbz"}"#.to_string()).await;
+        assert_eq!(status, StatusCode::UNPROCESSABLE_ENTITY);
+        assert_eq!(body["status"], "invalid");
+        assert!(body["error"].as_str().unwrap().contains("'z'"));
+
+        let (status, _) = post("/decode", "not json".to_string()).await;
+        assert!(status.is_client_error());
+    }
+
+    #[tokio::test]
+    async fn test_decode_batch() {
+        let body = format!(r#"{{"calldatas": ["{}", "0x01"]}}"#, TRANSFER);
+        let (status, body) = post("/decode-batch", body).await;
+        assert_eq!(status, StatusCode::OK);
+        let responses: Vec<DecodeResponse> = serde_json::from_value(body).unwrap();
+        assert_eq!(responses.len(), 2);
+        assert_eq!(responses[0].status, DecodeStatus::Decoded);
+        assert_eq!(responses[1].status, DecodeStatus::Invalid);
+
+        let calldatas = vec![TRANSFER; MAX_BATCH + 1];
+        let body = serde_json::to_string(&BatchBody {
+            calldatas: calldatas.into_iter().map(String::from).collect(),
+        })
+        .unwrap();
+        let (status, body) = post("/decode-batch", body).await;
+        assert_eq!(status, StatusCode::PAYLOAD_TOO_LARGE);
+        assert!(body["error"].as_str().unwrap().contains("batch limit"));
+    }
+}
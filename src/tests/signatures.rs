@@ -0,0 +1,168 @@
+/*
+cargo test test_signatures -- --nocapture --test-threads=1
+*/
+#[cfg(test)]
+mod test_signatures {
+    use crate::builder::CalldataBuilder;
+    use crate::signatures::*;
+    use crate::Calldata;
+    use primitive_types::H160;
+    use std::path::Path;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::sync::Arc;
+
+    /// Counts the lookups that reach it.
+    struct Counting(Arc<AtomicUsize>);
+
+    impl SignatureSource for Counting {
+        fn lookup(&self, selector: &str) -> Vec<String> {
+            self.0.fetch_add(1, Ordering::SeqCst);
+            SignatureMap::builtin().lookup(selector)
+        }
+    }
+
+    #[test]
+    fn test_selector_of() {
+        assert_eq!(selector_of("transfer(address,uint256)"), "a9059cbb");
+        assert_eq!(selector_of("multicall(bytes[])"), "ac9650d8");
+    }
+
+    #[test]
+    fn test_builtin() {
+        let signatures = Signatures::builtin();
+        assert_eq!(signatures.resolve("0xAC9650D8"), vec!["multicall(bytes[])"]);
+        assert_eq!(signatures.resolve("12210e8a"), vec!["refundETH()"]);
+        assert_eq!(
+            signatures.resolve("88316456"),
+            vec!["mint((address,address,uint24,int24,int24,uint256,uint256,uint256,uint256,address,uint256))"]
+        );
+        assert!(signatures.resolve("deadbeef").is_empty());
+    }
+
+    #[test]
+    fn test_from_text() {
+        let map = SignatureMap::from_text(
+            "# custom\nfoo(uint256)\n0xdeadbeef bar(address) # collision\n\n0xa9059cbb transfer(address,uint256)\n",
+        );
+        assert_eq!(map.len(), 3);
+        assert_eq!(map.lookup("deadbeef"), vec!["bar(address)"]);
+
+        let signatures = Signatures::builtin().with_source(map);
+        assert_eq!(
+            signatures.resolve("a9059cbb"),
+            vec!["transfer(address,uint256)"]
+        );
+        assert_eq!(
+            signatures.resolve(&selector_of("foo(uint256)")),
+            vec!["foo(uint256)"]
+        );
+    }
+
+    #[test]
+    fn test_resolve_cache() {
+        let count = Arc::new(AtomicUsize::new(0));
+        let signatures = Signatures::new()
+            .with_source(Counting(count.clone()))
+            .with_cache_capacity(1);
+        for selector in ["a9059cbb", "0xA9059CBB", "a9059cbb"] {
+            assert_eq!(
+                signatures.resolve(selector),
+                vec!["transfer(address,uint256)"]
+            );
+        }
+        assert_eq!(count.load(Ordering::SeqCst), 1);
+        assert_eq!(signatures.cache_stats(), (2, 1));
+
+        // A second selector evicts the first.
+        signatures.resolve("deadbeef");
+        signatures.resolve("a9059cbb");
+        assert_eq!(count.load(Ordering::SeqCst), 3);
+    }
+
+    #[test]
+    fn test_layout() {
+        let layout = Layout::parse("multicall(uint256,bytes[])").unwrap();
+        assert_eq!(layout.inputs, vec!["uint256", "bytes[]"]);
+        assert_eq!(layout.head_words, 2);
+        let layout = Layout::parse(
+            "exactInputSingle((address,address,uint24,address,uint256,uint256,uint160))",
+        );
+        assert_eq!(layout.unwrap().head_words, 7);
+        assert_eq!(
+            Layout::parse("f(uint256[3],(bytes,uint8))")
+                .unwrap()
+                .head_words,
+            4
+        );
+        assert!(Layout::parse("not a signature").is_none());
+
+        let signatures = Signatures::builtin();
+        assert_eq!(
+            signatures.layout("0x095ea7b3").unwrap().signature,
+            "approve(address,uint256)"
+        );
+        assert!(signatures.layout("deadbeef").is_none());
+    }
+
+    #[test]
+    fn test_from_json_csv() {
+        let map = SignatureMap::from_json(
+            r#"{"0xdeadbeef": ["bar(address)", "baz(uint256)"], "a9059cbb": "transfer(address,uint256)"}"#,
+        )
+        .unwrap();
+        assert_eq!(map.lookup("deadbeef"), vec!["bar(address)", "baz(uint256)"]);
+        let map = SignatureMap::from_json(r#"["foo(uint256)"]"#).unwrap();
+        assert_eq!(
+            map.lookup(&selector_of("foo(uint256)")),
+            vec!["foo(uint256)"]
+        );
+        assert!(SignatureMap::from_json(r#"{"deadbeef": 1}"#).is_err());
+        assert!(SignatureMap::from_json("1").is_err());
+
+        let map = SignatureMap::from_csv(
+            "selector,signature\n0xdeadbeef,\"bar(address,uint256)\"\nfoo(uint256)\n",
+        )
+        .unwrap();
+        assert_eq!(map.len(), 2);
+        assert_eq!(map.lookup("deadbeef"), vec!["bar(address,uint256)"]);
+        let err = SignatureMap::from_csv("0xdead,bar(address)").unwrap_err();
+        assert!(err.starts_with("line 1"));
+
+        let map = SignatureMap::parse(Path::new("sigs.CSV"), "0xdeadbeef,bar(address)").unwrap();
+        assert_eq!(map.lookup("deadbeef"), vec!["bar(address)"]);
+        let map = SignatureMap::parse(Path::new("sigs.txt"), "0xdeadbeef bar(address)").unwrap();
+        assert_eq!(map.lookup("deadbeef"), vec!["bar(address)"]);
+        let err = SignatureMap::parse(Path::new("sigs.json"), "{").unwrap_err();
+        assert!(err.starts_with("sigs.json: "));
+    }
+
+    #[test]
+    fn test_apply_signatures() {
+        // A low address reads as a number until its signature says otherwise.
+        let hex = CalldataBuilder::new("setOwner(address)")
+            .address(H160::from_low_u64_be(0x1234))
+            .build_hex()
+            .unwrap();
+        let mut root = Calldata::new(&hex).decode();
+        assert_ne!(root.params[0].types[0].solidity, "address");
+
+        // A signature that doesn't fit the params is passed over.
+        let mut map = SignatureMap::new();
+        map.insert_with_selector(&root.selector, "setOwners(address,address)");
+        map.insert("setOwner(address)");
+        apply_signatures(&mut root, &Signatures::new().with_source(map));
+        assert_eq!(root.signature_guess, "setOwner(address)");
+        assert_eq!(root.params[0].types[0].solidity, "address");
+
+        let hex = CalldataBuilder::new("store(bytes)")
+            .bytes(&[1, 2, 3])
+            .build_hex()
+            .unwrap();
+        let decoded = Calldata::new(&hex).decode();
+        let layout = |s: &str| Layout::parse(s).unwrap();
+        assert!(layout("store(bytes)").fits(&decoded.params));
+        assert!(!layout("store(uint256,bytes)").fits(&decoded.params));
+        assert!(!layout("store(uint256,uint256)").fits(&decoded.params));
+        assert!(!layout("store(bytes,uint256,uint256)").fits(&decoded.params));
+    }
+}
@@ -0,0 +1,86 @@
+/*
+cargo test test_simulate -- --nocapture --test-threads=1
+*/
+#[cfg(test)]
+mod test_simulate {
+    use crate::prototypes::{Prototype, PrototypeSource};
+    use crate::simulate::*;
+    use crate::{decode_hex, Calldata};
+
+    /// `f(uint8,uint256)` with 7 and 1000: the first word could be any
+    /// unsigned width or an address.
+    const CALL: &str = concat!(
+        "0x12345678",
+        "0000000000000000000000000000000000000000000000000000000000000007",
+        "00000000000000000000000000000000000000000000000000000000000003e8",
+    );
+
+    fn candidate(signature: &str, confidence: f64) -> Prototype {
+        Prototype {
+            signature: signature.to_string(),
+            confidence,
+            source: PrototypeSource::Heuristic,
+        }
+    }
+
+    fn candidates() -> Vec<Prototype> {
+        vec![
+            candidate("f(uint8,uint256)", 0.4),
+            candidate("f(address,uint256)", 0.35),
+            candidate("f(uint256,uint256)", 0.25),
+        ]
+    }
+
+    fn reverted(data: &str) -> CallOutcome {
+        CallOutcome::Reverted {
+            data: data.to_string(),
+        }
+    }
+
+    #[test]
+    fn test_probes() {
+        let bytes = decode_hex(CALL);
+        let call = Calldata::from_bytes(&bytes).decode();
+        let probes = probes(&bytes, &call, &candidates());
+        let first_word = |p: &Probe| decode_hex(&p.calldata)[4..36].to_vec();
+        let widths = probes.iter().map(|p| p.width).collect::<Vec<_>>();
+        assert_eq!(widths, vec![8, 160, 256]);
+
+        let mut uint8 = [0; 32];
+        uint8[31] = 0xff;
+        assert_eq!(first_word(&probes[0]), uint8);
+        let mut address = [0; 32];
+        address[12..].fill(0xff);
+        assert_eq!(first_word(&probes[1]), address);
+        assert_eq!(first_word(&probes[2]), [0xff; 32]);
+
+        // The word all candidates type the same keeps its value.
+        for probe in probes.iter() {
+            assert_eq!(decode_hex(&probe.calldata)[36..], bytes[36..]);
+        }
+    }
+
+    #[test]
+    fn test_pick() {
+        let bytes = decode_hex(CALL);
+        let call = Calldata::from_bytes(&bytes).decode();
+        let probes = probes(&bytes, &call, &candidates());
+
+        // An address target: the uint8 probe decodes too, the uint256 one doesn't.
+        let outcomes = vec![
+            CallOutcome::Returned,
+            reverted("0x08c379a0"),
+            reverted("0x"),
+        ];
+        let result = pick(CallOutcome::Returned, probes.clone(), outcomes.clone());
+        assert_eq!(result.winner.as_deref(), Some("f(address,uint256)"));
+        assert_eq!(
+            result.results.iter().map(|r| r.decoded).collect::<Vec<_>>(),
+            vec![true, true, false]
+        );
+
+        // A call that doesn't decode as it is can't tell the candidates apart.
+        let result = pick(reverted("0x"), probes, outcomes);
+        assert_eq!(result.winner, None);
+    }
+}
@@ -0,0 +1,74 @@
+/*
+cargo test test_stats -- --nocapture --test-threads=1
+*/
+#[cfg(test)]
+mod test_stats {
+    use crate::corpus::{MULTICALL, TRANSFER};
+    use crate::signatures::Signatures;
+    use crate::stats::*;
+    use crate::Calldata;
+
+    fn add(stats: &mut BatchStats, calldata: &str, target: Option<&str>) {
+        stats.add(&Calldata::new(calldata).decode(), target);
+    }
+
+    #[test]
+    fn test_selectors_and_targets() {
+        let mut stats = BatchStats::new();
+        let usdt = "0xdAC17F958D2ee523a2206206994597C13D831ec7";
+        add(&mut stats, TRANSFER, Some(usdt));
+        add(&mut stats, TRANSFER, Some(&usdt[2..]));
+        add(&mut stats, MULTICALL, None);
+        stats.add_failure();
+        let summary = stats.summary(10, &Signatures::builtin());
+
+        assert_eq!(summary.decoded, 3);
+        assert_eq!(summary.failed, 1);
+        // Two transfers, the multicall and its two nested calls.
+        assert_eq!(summary.calls, 5);
+        assert_eq!(summary.max_depth, 1);
+        assert!((summary.average_depth - 1.0 / 3.0).abs() < 1e-9);
+
+        let transfer = &summary.selectors[0];
+        assert_eq!(transfer.selector, "a9059cbb");
+        assert_eq!(
+            transfer.signature.as_deref(),
+            Some("transfer(address,uint256)")
+        );
+        assert_eq!((transfer.calls, transfer.top_level), (2, 2));
+        assert!((transfer.share - 0.4).abs() < 1e-9);
+        assert_eq!(transfer.params[0].types, vec![("address".to_string(), 2)]);
+        assert_eq!(transfer.params[1].types, vec![("uint256".to_string(), 2)]);
+
+        let nested = summary
+            .selectors
+            .iter()
+            .find(|s| s.selector == "12210e8a")
+            .unwrap();
+        assert_eq!((nested.calls, nested.top_level), (1, 0));
+
+        assert_eq!(
+            summary.targets,
+            vec![TargetCount {
+                address: "0xdac17f958d2ee523a2206206994597c13d831ec7".to_string(),
+                calls: 2,
+            }]
+        );
+        assert_eq!(stats.summary(1, &Signatures::new()).selectors.len(), 1);
+    }
+
+    #[test]
+    fn test_merge() {
+        let (mut all, mut first, mut second) =
+            (BatchStats::new(), BatchStats::new(), BatchStats::new());
+        for (i, calldata) in [TRANSFER, MULTICALL, TRANSFER].iter().enumerate() {
+            add(&mut all, calldata, Some("0x01"));
+            match i.is_multiple_of(2) {
+                true => add(&mut first, calldata, Some("0x01")),
+                false => add(&mut second, calldata, Some("0x01")),
+            }
+        }
+        first.merge(&second);
+        assert_eq!(first, all);
+    }
+}
@@ -0,0 +1,99 @@
+/*
+cargo test test_stream -- --nocapture --test-threads=1
+*/
+#[cfg(test)]
+mod test_stream {
+    use crate::render::color::Style;
+    use crate::render::output::OutputFormat;
+    use crate::stream::*;
+    use crate::Calldata;
+    use std::io::Read;
+
+    const TRANSFER: &str = "0xa9059cbb000000000000000000000000c02aaa39b223fe8d0a0e5c4f27ead9083c756cc20000000000000000000000000000000000000000000000000de0b6b3a7640000";
+
+    /// Hands out one byte per read, to cross every chunk boundary.
+    struct Trickle<'a>(&'a [u8]);
+
+    impl Read for Trickle<'_> {
+        fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+            match self.0.split_first() {
+                Some((b, rest)) => {
+                    buf[0] = *b;
+                    self.0 = rest;
+                    Ok(1)
+                }
+                None => Ok(0),
+            }
+        }
+    }
+
+    fn regions(decoder: impl Iterator<Item = Result<Region, String>>) -> Vec<Region> {
+        decoder.collect::<Result<Vec<Region>, String>>().unwrap()
+    }
+
+    #[test]
+    fn test_stream_matches_decode() {
+        let root = Calldata::new(TRANSFER).decode();
+        let streamed = regions(StreamDecoder::hex(TRANSFER.as_bytes()));
+        assert_eq!(streamed.len(), 3);
+        match &streamed[0] {
+            Region::Selector { selector, span } => {
+                assert_eq!(selector, "a9059cbb");
+                assert_eq!(span.end, 4);
+            }
+            region => panic!("expected the selector, got {:?}", region),
+        }
+        for (region, param) in streamed[1..].iter().zip(root.params.iter()) {
+            assert_eq!(region, &Region::Param(Box::new(param.clone())));
+        }
+
+        // Same regions however the input is split or wrapped.
+        let wrapped = TRANSFER
+            .replacen("0x", "0X", 1)
+            .replace("0000000000", "0000000000\n ");
+        assert_eq!(
+            regions(StreamDecoder::hex(Trickle(wrapped.as_bytes()))),
+            streamed
+        );
+        let bytes = crate::decode_hex(TRANSFER);
+        assert_eq!(regions(StreamDecoder::binary(Trickle(&bytes))), streamed);
+    }
+
+    #[test]
+    fn test_stream_errors() {
+        let mut decoder = StreamDecoder::hex("0xa9059cbb00zz".as_bytes());
+        assert!(decoder
+            .next()
+            .unwrap()
+            .unwrap_err()
+            .contains("position 12 (calldata byte 5)"));
+        assert!(decoder.next().is_none());
+
+        let mut decoder = StreamDecoder::hex("0xa905".as_bytes());
+        assert!(decoder.next().unwrap().is_err());
+
+        let mut decoder = StreamDecoder::hex("0xa9059cbb0".as_bytes());
+        assert!(decoder.next().unwrap().is_ok());
+        assert!(decoder.next().unwrap().unwrap_err().contains("odd"));
+
+        // A trailing partial word is still emitted.
+        let streamed = regions(StreamDecoder::hex("0xa9059cbb7b16".as_bytes()));
+        assert!(matches!(&streamed[1], Region::Param(p) if p.raw == "7b16" && p.value == "0x7b16"));
+    }
+
+    #[test]
+    fn test_render_region() {
+        let streamed = regions(StreamDecoder::hex(TRANSFER.as_bytes()));
+        let style = Style::plain();
+        assert_eq!(
+            render_region(OutputFormat::Pretty, &streamed[0], &style),
+            "0xa9059cbb\n"
+        );
+        assert_eq!(
+            render_region(OutputFormat::Pretty, &streamed[2], &style),
+            "[1] uint256: 1000000000000000000\n"
+        );
+        let json = render_region(OutputFormat::Json, &streamed[1], &style);
+        assert!(json.starts_with("{\"region\":\"param\",\"index\":0,"));
+    }
+}
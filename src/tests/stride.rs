@@ -0,0 +1,97 @@
+/*
+cargo test test_stride -- --nocapture --test-threads=1
+*/
+#[cfg(test)]
+mod test_stride {
+    use crate::builder::{AbiValue, CalldataBuilder};
+    use crate::constants::{Types, WORD};
+    use crate::corpus::MULTICALL;
+    use crate::heuristics::Heuristics;
+    use crate::stride::*;
+    use crate::{decode_hex, Calldata};
+    use primitive_types::H160;
+
+    /// `deposit(uint256,(address,uint256,bool)[])` of 3 elements, the second
+    /// with a zero address and a large amount.
+    fn deposit() -> Vec<u8> {
+        let element = |to: H160, amount: u64, flag: bool| {
+            AbiValue::Tuple(vec![
+                AbiValue::Address(to),
+                AbiValue::Uint(amount.into()),
+                AbiValue::Bool(flag),
+            ])
+        };
+        CalldataBuilder::new("deposit(uint256,(address,uint256,bool)[])")
+            .uint(7u64)
+            .array(vec![
+                element(H160::repeat_byte(0x11), 1, true),
+                element(H160::zero(), 1_000_000, false),
+                element(H160::repeat_byte(0x22), 5, true),
+            ])
+            .build()
+            .unwrap()
+    }
+
+    #[test]
+    fn test_stride_arrays() {
+        let calldata = deposit();
+        let words = calldata[4..].chunks(WORD).collect::<Vec<_>>();
+        let arrays = struct_arrays(&words);
+        assert_eq!(
+            arrays,
+            vec![StructArray {
+                length: 2,
+                count: 3,
+                stride: 3
+            }]
+        );
+        assert_eq!(arrays[0].column(1).collect::<Vec<_>>(), vec![4, 7, 10]);
+        assert_eq!(arrays[0].end(), words.len());
+
+        // Elements behind offsets are dynamic, not structs.
+        let multicall = decode_hex(MULTICALL);
+        let words = multicall[4..].chunks(WORD).collect::<Vec<_>>();
+        assert!(struct_arrays(&words).is_empty());
+    }
+
+    #[test]
+    fn test_stride_votes() {
+        let calldata = deposit();
+        let root = Calldata::from_bytes(&calldata).decode();
+        let top = |i: usize| root.params[i].types[0].kind.clone();
+        // The zero address and the small amounts take their column's type.
+        assert_eq!(
+            [top(3), top(6), top(9)],
+            [Types::Address, Types::Address, Types::Address]
+        );
+        assert_eq!(
+            [top(4), top(7), top(10)],
+            [Types::Uint, Types::Uint, Types::Uint]
+        );
+        assert_eq!(
+            [top(5), top(8), top(11)],
+            [Types::Bool, Types::Bool, Types::Bool]
+        );
+        assert_eq!(
+            root.params[6].value,
+            "0x0000000000000000000000000000000000000000"
+        );
+        // Outside the array, words are typed alone.
+        assert_eq!(top(0), Types::Uint8);
+
+        let mut heuristics = Heuristics::all();
+        heuristics.strides = false;
+        let alone = Calldata::from_bytes_with_heuristics(calldata.clone(), heuristics).decode();
+        assert_eq!(alone.params[4].types[0].kind, Types::Uint8);
+        assert_eq!(alone.params[6].types[0].kind, Types::AnyZero);
+
+        let explanation = Calldata::from_bytes(&calldata).explain();
+        let amount = &explanation.calls[0].params[4];
+        let vote = amount.checks.iter().find(|c| c.rule == "strides").unwrap();
+        assert_eq!(
+            vote.reason,
+            "field 1 of 3 elements of 3 words each, voted uint256"
+        );
+        assert_eq!(amount.types, root.params[4].types);
+    }
+}
@@ -0,0 +1,56 @@
+/*
+cargo test test_summary -- --nocapture --test-threads=1
+*/
+#[cfg(test)]
+mod test_summary {
+    use crate::corpus::{MULTICALL, TRANSFER};
+    use crate::decoder::Decoder;
+    use crate::render::labels::Labels;
+    use crate::signatures::Signatures;
+    use crate::summary::*;
+    use crate::Calldata;
+
+    fn labels() -> Labels {
+        Labels::from_text(
+            "0xc011a73ee8576fb46f5e1c5751ca3b9fe0af2a6f SYN\n\
+             0xc02aaa39b223fe8d0a0e5c4f27ead9083c756cc2 WETH\n",
+        )
+    }
+
+    #[test]
+    fn test_summarize() {
+        let sources = Signatures::builtin();
+        let multicall = Calldata::new(MULTICALL).decode();
+        assert_eq!(
+            summarize(&multicall, &sources, &labels()),
+            "multicall: mint SYN/WETH position @fee 1%, then refundETH"
+        );
+        assert_eq!(
+            summarize(&multicall, &sources, &Labels::new()),
+            "multicall: mint 0xC011…2a6F/0xC02a…6Cc2 position @fee 1%, then refundETH"
+        );
+
+        let transfer = Calldata::new(TRANSFER).decode();
+        assert_eq!(
+            summarize(&transfer, &sources, &labels()),
+            "transfer 1000000000 to 0xdAC1…1ec7"
+        );
+
+        // Unknown selectors read as themselves, their nested calls still summarized.
+        let unknown = Calldata::new(MULTICALL).decode();
+        assert_eq!(
+            summarize(&unknown, &Signatures::new(), &Labels::new()),
+            "0xac9650d8: 0x88316456, then 0x12210e8a"
+        );
+    }
+
+    #[test]
+    fn test_decoder_summarize() {
+        let decoder = Decoder::new().with_labels(labels());
+        let root = decoder.decode(MULTICALL).unwrap();
+        assert_eq!(
+            decoder.summarize(&root),
+            "multicall: mint SYN/WETH position @fee 1%, then refundETH"
+        );
+    }
+}
@@ -0,0 +1,67 @@
+/*
+cargo test test_text -- --nocapture --test-threads=1
+*/
+#[cfg(test)]
+mod test_text {
+    use crate::arena::DecodeArena;
+    use crate::render::color::{ColorMode, Style};
+    use crate::render::tree::render_tree_styled;
+    use crate::text::*;
+    use crate::Calldata;
+
+    #[test]
+    fn test_escape_text() {
+        assert_eq!(escape_text("plain text"), "plain text");
+        assert_eq!(escape_text("\x1b[2Jgone"), "\\u{1b}[2Jgone");
+        assert_eq!(escape_text("a\nb\tc"), "a\\nb\\tc");
+        assert_eq!(escape_text("\u{202e}txt.exe"), "\\u{202e}txt.exe");
+        // A literal `\u{1b}` can't pass for an escaped one.
+        assert_eq!(escape_text("\\u{1b}"), "\\\\u{1b}");
+    }
+
+    #[test]
+    fn test_decoded_text() {
+        assert_eq!(
+            DecodedText::new("héllo".as_bytes()),
+            DecodedText {
+                text: "héllo".to_string(),
+                lossy: false
+            }
+        );
+        assert_eq!(
+            DecodedText::new(b"hello\xff"),
+            DecodedText {
+                text: "hello\u{fffd}".to_string(),
+                lossy: true
+            }
+        );
+        assert!(looks_like_text(b"Uniswap V3"));
+        assert!(!looks_like_text(b"no\0nul"));
+        assert!(!looks_like_text(&[0xa9, 0x05, 0x9c, 0xbb]));
+        assert!(looks_like_text(b"123") && looks_like_text(b"4.5"));
+        assert!(!looks_like_text(b"--") && !looks_like_text(b"1 2\x01"));
+    }
+
+    #[test]
+    fn test_string_params() {
+        // setName("hello")
+        let calldata = Calldata::new("0xc47f00270000000000000000000000000000000000000000000000000000000000000020000000000000000000000000000000000000000000000000000000000000000568656c6c6f000000000000000000000000000000000000000000000000000000");
+        let root = calldata.decode();
+        assert_eq!(root.params[1].text, Some(DecodedText::new(b"hello")));
+        assert!(root.params.iter().filter(|p| p.text.is_some()).count() == 1);
+
+        let mut arena = DecodeArena::new();
+        let id = arena.decode(&calldata);
+        assert_eq!(arena.call(id).to_decoded(), root);
+    }
+
+    #[test]
+    fn test_string_escapes() {
+        // setName("\x1b]0;owned\x07\x1b[2J")
+        let root = Calldata::new("0xc47f00270000000000000000000000000000000000000000000000000000000000000020000000000000000000000000000000000000000000000000000000000000000e1b5d303b6f776e6564071b5b324a000000000000000000000000000000000000").decode();
+        let tree = render_tree_styled(&root, true, &Style::new(ColorMode::Never));
+        println!("{}", tree);
+        assert!(!tree.contains('\x1b') && !tree.contains('\x07'));
+        assert!(tree.contains(r#""\u{1b}]0;owned\u{7}\u{1b}[2J""#));
+    }
+}
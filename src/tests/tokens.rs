@@ -0,0 +1,108 @@
+/*
+cargo test test_tokens -- --nocapture --test-threads=1
+*/
+#[cfg(test)]
+mod test_tokens {
+    use crate::builder::CalldataBuilder;
+    use crate::corpus::TRANSFER;
+    use crate::render::tree::render_tree;
+    use crate::tokens::*;
+    use crate::Calldata;
+    use primitive_types::{H160, U256};
+
+    const USDC_BASE: &str = "0x833589fcd6edb6e08f4c7c32d4f71b54bda02913";
+    const USDT: &str = "0xdAC17F958D2ee523a2206206994597C13D831ec7";
+
+    #[test]
+    fn test_tokens_builtin() {
+        let tokens = Tokens::builtin();
+        assert!(tokens.len() > 40);
+        let usdt = tokens.get(1, USDT).unwrap();
+        assert_eq!((usdt.symbol.as_str(), usdt.decimals), ("USDT", 6));
+        assert_eq!(tokens.get(8453, USDC_BASE).unwrap().symbol, "USDC");
+        // Addresses are per chain.
+        assert_eq!(tokens.get(10, USDT), None);
+        let weth = "0x4200000000000000000000000000000000000006";
+        assert_eq!(tokens.get(10, weth), tokens.get(8453, weth));
+
+        assert_eq!(
+            (chain_id("Base"), chain_id("ethereum")),
+            (Some(8453), Some(1))
+        );
+        assert_eq!((chain_id("324"), chain_id("nowhere")), (Some(324), None));
+
+        let labels = tokens.labels(8453);
+        assert_eq!(labels.get(USDC_BASE), Some("USDC"));
+        assert_eq!(labels.get(USDT), None);
+        assert!(labels.len() < tokens.len());
+
+        assert_eq!(usdt.amount(U256::from(1_500_000)), "1.5 USDT");
+        assert_eq!(usdt.amount(U256::from(1_000_000_000)), "1000 USDT");
+        assert_eq!(usdt.amount(U256::MAX), "unlimited USDT");
+    }
+
+    #[test]
+    fn test_tokens_from_text() {
+        let text = "# bridged\nbase 0x0000000000000000000000000000000000000abc  FOO 9\n\n324 0x0000000000000000000000000000000000000Def BAR 0 # zkSync\n";
+        let mut tokens = Tokens::builtin();
+        let extra = Tokens::from_text(text).unwrap();
+        assert_eq!(extra.len(), 2);
+        tokens.extend(extra);
+        let foo = tokens
+            .get(8453, "0x0000000000000000000000000000000000000ABC")
+            .unwrap();
+        assert_eq!((foo.symbol.as_str(), foo.decimals), ("FOO", 9));
+        assert_eq!(
+            tokens
+                .get(324, "0x0000000000000000000000000000000000000def")
+                .unwrap()
+                .symbol,
+            "BAR"
+        );
+
+        for bad in [
+            "base 0xabc FOO 9",
+            "nowhere 0x0000000000000000000000000000000000000abc FOO 9",
+            "base 0x0000000000000000000000000000000000000abc FOO 300",
+            "base 0x0000000000000000000000000000000000000abc FOO",
+        ] {
+            let error = Tokens::from_text(&format!("\n{}", bad)).unwrap_err();
+            assert!(error.starts_with("line 2: "), "{}", error);
+        }
+    }
+
+    #[test]
+    fn test_tokens_amount() {
+        let tokens = Tokens::builtin();
+        let usdt = tokens.get(1, USDT).unwrap();
+        let mut root = Calldata::new(TRANSFER).decode();
+        apply_token_amount(&mut root, usdt);
+        assert_eq!(root.params[1].amount.as_deref(), Some("1000 USDT"));
+        // Only the renderers read by people show it; the value is left alone.
+        assert_eq!(root.params[1].value, "1000000000");
+        assert!(render_tree(&root).contains("uint256: 1000000000 (1000 USDT)"));
+        let json = serde_json::to_value(&root).unwrap();
+        assert_eq!(json["params"][1]["value"], "1000000000");
+        assert_eq!(json["params"][1]["amount"], "1000 USDT");
+        assert!(json["params"][0].get("amount").is_none());
+
+        let approve = CalldataBuilder::new("approve(address,uint256)")
+            .address(H160::repeat_byte(0x11))
+            .uint(U256::MAX)
+            .build()
+            .unwrap();
+        let mut root = Calldata::from_bytes(&approve).decode();
+        apply_token_amount(&mut root, usdt);
+        assert_eq!(root.params[1].amount.as_deref(), Some("unlimited USDT"));
+
+        // Only the ERC-20 methods moving an amount.
+        let other = CalldataBuilder::new("withdraw(uint256)")
+            .uint(5u64)
+            .build()
+            .unwrap();
+        let mut root = Calldata::from_bytes(&other).decode();
+        let before = root.clone();
+        apply_token_amount(&mut root, usdt);
+        assert_eq!(root, before);
+    }
+}
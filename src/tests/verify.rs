@@ -0,0 +1,102 @@
+/*
+cargo test test_verify -- --nocapture --test-threads=1
+*/
+#[cfg(all(test, feature = "ethers"))]
+mod test_verify {
+    use crate::builder::{AbiValue, CalldataBuilder};
+    use crate::prototypes::{rank_prototypes, PrototypeSource};
+    use crate::signatures::{SignatureMap, Signatures};
+    use crate::verify::*;
+    use crate::{decode_hex, Calldata};
+    use ethers::abi::Token;
+    use ethers::types::U256;
+    use primitive_types::H160;
+
+    const UINT_ARRAY: &str = "0x5d842074000000000000000000000000000000000000000000000006c6b935b8bbd400000000000000000000000000000000000000000000000000000000000000000040000000000000000000000000000000000000000000000000000000000000000200000000000000000000000000000000000000000000002086ac35105260000000000000000000000000000000000000000000000000002b5e3af16b18800000";
+    const TRANSFER: &str = "0xa9059cbb000000000000000000000000c02aaa39b223fe8d0a0e5c4f27ead9083c756cc20000000000000000000000000000000000000000000000000de0b6b3a7640000";
+
+    #[test]
+    fn test_verify_round_trip() {
+        let bytes = decode_hex(UINT_ARRAY);
+        let tokens = verify("unknown_5d842074(uint256,uint256[])", &bytes).unwrap();
+        assert_eq!(tokens.len(), 2);
+        match &tokens[1] {
+            Token::Array(values) => assert_eq!(values.len(), 2),
+            token => panic!("expected an array, got {:?}", token),
+        }
+        // The offset read as a number leaves the array's words unaccounted for.
+        let e = verify("unknown_5d842074(uint256,uint256)", &bytes).unwrap_err();
+        assert!(e.contains("re-encodes to 64 bytes of 160"), "{}", e);
+        // Flat, every word is a number.
+        assert!(round_trips(
+            "unknown_5d842074(uint256,uint256,uint256,uint256,uint256)",
+            &bytes
+        ));
+
+        let bytes = decode_hex(TRANSFER);
+        assert!(round_trips("transfer(address,uint256)", &bytes));
+        // Left padded, so neither a `bytes20` nor a `bool`.
+        assert!(!round_trips("transfer(bytes20,uint256)", &bytes));
+        assert!(!round_trips("transfer(address,bool)", &bytes));
+        // 1e18 is past a `uint8`.
+        assert!(!round_trips("transfer(address,uint8)", &bytes));
+        assert!(verify("transfer(address", &bytes).is_err());
+        assert!(verify("transfer(address,uint256)", &bytes[..3]).is_err());
+    }
+
+    #[test]
+    fn test_verify_signed() {
+        let mut word = [0xffu8; 32];
+        word[31] = 0xfe;
+        let bytes = [&[0u8; 4][..], &word].concat();
+        // -2 sign-extended fits an `int8`.
+        let tokens = verify("f(int8)", &bytes).unwrap();
+        assert_eq!(tokens[0], Token::Int(U256::MAX - 1));
+        // Not once the upper bits disagree with the sign.
+        word[0] = 0x7f;
+        let bytes = [&[0u8; 4][..], &word].concat();
+        assert!(!round_trips("f(int8)", &bytes));
+        assert!(round_trips("f(int256)", &bytes));
+    }
+
+    #[test]
+    fn test_verify_prunes_guesses() {
+        let root = Calldata::new(TRANSFER).decode();
+        assert_eq!(call_bytes(&root), decode_hex(TRANSFER));
+
+        let prototypes = rank_prototypes(&root, &Signatures::new(), 10);
+        assert_eq!(prototypes[0].signature, "unknown_a9059cbb(address,uint256)");
+        assert!(prototypes
+            .iter()
+            .all(|p| round_trips(&p.signature, &call_bytes(&root))));
+        assert!(!prototypes
+            .iter()
+            .any(|p| p.signature == "unknown_a9059cbb(bytes20,uint256)"));
+        let total = prototypes.iter().map(|p| p.confidence).sum::<f64>();
+        assert!((total - 1.0).abs() < 1e-9);
+
+        // Tuples in the layout guessed re-encode too.
+        let bytes = CalldataBuilder::new("f((address,uint256)[],bytes)")
+            .array(vec![AbiValue::Tuple(vec![
+                AbiValue::Address(H160::repeat_byte(0x11)),
+                AbiValue::Uint(U256::exp10(18)),
+            ])])
+            .bytes(&[0xab; 40])
+            .build()
+            .unwrap();
+        let call = Calldata::from_bytes(&bytes).decode();
+        let prototypes = call.candidate_signatures(3);
+        assert_eq!(
+            prototypes[0].signature,
+            format!("unknown_{}((address,uint256)[],bytes)", call.selector)
+        );
+        assert_eq!(prototypes[0].source, PrototypeSource::Structure);
+
+        // Known signatures are kept whether or not they re-encode.
+        let mut map = SignatureMap::new();
+        map.insert_with_selector("a9059cbb", "transfer(bytes20,uint256)");
+        let prototypes = rank_prototypes(&root, &Signatures::new().with_source(map), 10);
+        assert_eq!(prototypes[0].signature, "transfer(bytes20,uint256)");
+        assert_eq!(prototypes[0].source, PrototypeSource::Known);
+    }
+}
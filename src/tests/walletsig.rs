@@ -0,0 +1,85 @@
+/*
+cargo test test_walletsig -- --nocapture --test-threads=1
+*/
+#[cfg(test)]
+mod test_walletsig {
+    use crate::builder::{encode_tuple, AbiValue, CalldataBuilder};
+    use crate::corpus::TRANSFER;
+    use crate::walletsig::*;
+    use crate::{decode_hex, Calldata};
+    use primitive_types::H160;
+
+    fn signature(v: u8) -> Vec<u8> {
+        let mut signature = [[0x11; 32], [0x22; 32]].concat();
+        signature.push(v);
+        signature
+    }
+
+    fn is_valid_signature(signature: &[u8]) -> Calldata {
+        let calldata = CalldataBuilder::new("isValidSignature(bytes32,bytes)")
+            .fixed_bytes(&[0xaa; 32])
+            .bytes(signature)
+            .build()
+            .unwrap();
+        Calldata::from_bytes(&calldata)
+    }
+
+    #[test]
+    fn test_ecdsa() {
+        let decoded = is_valid_signature(&signature(27)).decode();
+        // The hash, the offset, then the length of the signature.
+        assert_eq!(
+            decoded.params[2].signature,
+            Some(DecodedSignature::Ecdsa {
+                r: format!("0x{}", "11".repeat(32)),
+                s: format!("0x{}", "22".repeat(32)),
+                v: 27,
+                high_s: false,
+            })
+        );
+        let signed = decoded.params.iter().filter(|p| p.signature.is_some());
+        assert_eq!(signed.count(), 1);
+
+        let mut malleable = signature(28);
+        malleable[32] = 0x80;
+        assert!(matches!(
+            DecodedSignature::decode(&malleable),
+            Some(DecodedSignature::Ecdsa { high_s: true, .. })
+        ));
+        assert_eq!(DecodedSignature::decode(&signature(29)), None);
+        assert_eq!(DecodedSignature::decode(&signature(27)[1..]), None);
+    }
+
+    #[test]
+    fn test_erc6492() {
+        let factory = H160::repeat_byte(0xfa);
+        let deploy = decode_hex(TRANSFER);
+        let mut wrapped = encode_tuple(&[
+            AbiValue::Address(factory),
+            AbiValue::Bytes(deploy),
+            AbiValue::Bytes(signature(27)),
+        ]);
+        wrapped.extend(decode_hex(ERC6492_SUFFIX));
+
+        let decoded = is_valid_signature(&wrapped).decode();
+        let Some(DecodedSignature::Erc6492 {
+            factory: deployer,
+            factory_calldata,
+            inner,
+            ..
+        }) = &decoded.params[2].signature
+        else {
+            panic!("not ERC-6492: {:?}", decoded.params[2]);
+        };
+        assert_eq!(deployer, &format!("0x{}", "fa".repeat(20)));
+        assert_eq!(factory_calldata, TRANSFER);
+        assert!(matches!(
+            inner.as_deref(),
+            Some(DecodedSignature::Ecdsa { v: 27, .. })
+        ));
+
+        // Without the suffix it's opaque bytes.
+        wrapped.truncate(wrapped.len() - 32);
+        assert_eq!(DecodedSignature::decode(&wrapped), None);
+    }
+}
@@ -0,0 +1,62 @@
+/*
+cargo test test_watch -- --nocapture --test-threads=1
+*/
+#[cfg(all(test, feature = "ethers"))]
+mod test_watch {
+    use crate::watch::*;
+    use ethers::types::{Bytes, Transaction};
+
+    const MULTICALL: &str = "0xac9650d800000000000000000000000000000000000000000000000000000000000000200000000000000000000000000000000000000000000000000000000000000002000000000000000000000000000000000000000000000000000000000000004000000000000000000000000000000000000000000000000000000000000001e0000000000000000000000000000000000000000000000000000000000000016488316456000000000000000000000000c011a73ee8576fb46f5e1c5751ca3b9fe0af2a6f000000000000000000000000c02aaa39b223fe8d0a0e5c4f27ead9083c756cc20000000000000000000000000000000000000000000000000000000000002710fffffffffffffffffffffffffffffffffffffffffffffffffffffffffffee530ffffffffffffffffffffffffffffffffffffffffffffffffffffffffffff1b18000000000000000000000000000000000000000000000000016345785d89fd6800000000000000000000000000000000000000000000000000007f73eca3063a000000000000000000000000000000000000000000000000016042b530ddaec600000000000000000000000000000000000000000000000000007e59f044bada000000000000000000000000f847e9d51989033b691b8be943f8e9e268f99b9e000000000000000000000000000000000000000000000000000000006377347700000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000412210e8a00000000000000000000000000000000000000000000000000000000";
+    const ROUTER: &str = "0xc36442b4a4522e871399cd717abdd847ab11fe88";
+
+    fn tx() -> Transaction {
+        Transaction {
+            to: Some(ROUTER.parse().unwrap()),
+            input: MULTICALL.parse::<Bytes>().unwrap(),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_filter_top_level_selector() {
+        let filter = TxFilter {
+            selectors: vec!["0xAC9650D8".to_string()],
+            to: vec![ROUTER.parse().unwrap()],
+            nested: false,
+        };
+        let decoded = filter_transaction(&tx(), &filter).unwrap();
+        assert_eq!(decoded.decoded.selector, "ac9650d8");
+
+        let filter = TxFilter {
+            selectors: vec!["12210e8a".to_string()],
+            ..Default::default()
+        };
+        assert!(filter_transaction(&tx(), &filter).is_none());
+    }
+
+    #[test]
+    fn test_filter_nested_selector() {
+        let filter = TxFilter {
+            selectors: vec!["12210e8a".to_string()],
+            nested: true,
+            ..Default::default()
+        };
+        assert!(filter_transaction(&tx(), &filter).is_some());
+
+        let filter = TxFilter {
+            selectors: vec!["deadbeef".to_string()],
+            nested: true,
+            ..Default::default()
+        };
+        assert!(!filter.matches_transaction(&tx()));
+    }
+
+    #[test]
+    fn test_filter_target() {
+        let filter = TxFilter {
+            to: vec!["0xc02aaa39b223fe8d0a0e5c4f27ead9083c756cc2".parse().unwrap()],
+            ..Default::default()
+        };
+        assert!(filter_transaction(&tx(), &filter).is_none());
+    }
+}
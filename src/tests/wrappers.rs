@@ -0,0 +1,178 @@
+/*
+cargo test test_wrappers -- --nocapture --test-threads=1
+*/
+#[cfg(test)]
+mod test_wrappers {
+    use crate::builder::{AbiValue, CalldataBuilder};
+    use crate::corpus;
+    use crate::decoded::DecodedCalldata;
+    use crate::protocol::ProtocolRegistry;
+    use crate::render::address::to_checksum;
+    use crate::render::tree::render_call_tree;
+    use crate::Calldata;
+    use primitive_types::{H160, U256};
+
+    fn transfer() -> CalldataBuilder {
+        CalldataBuilder::new("transfer(address,uint256)")
+            .address(H160::repeat_byte(0x11))
+            .uint(1u64)
+    }
+
+    fn decode(builder: CalldataBuilder) -> DecodedCalldata {
+        let bytes = builder.build().unwrap();
+        ProtocolRegistry::builtin()
+            .decode(&Calldata::from_bytes(&bytes))
+            .unwrap()
+    }
+
+    fn layers(root: &DecodedCalldata) -> Vec<String> {
+        root.calls
+            .iter()
+            .map(|c| c.layer.clone().unwrap_or_default())
+            .collect()
+    }
+
+    #[test]
+    fn test_wrappers_safe() {
+        let root = ProtocolRegistry::builtin()
+            .decode(&Calldata::new(corpus::SAFE))
+            .unwrap();
+        assert!(root.signature_guess.starts_with("execTransaction("));
+        assert_eq!(root.calls.len(), 1);
+        assert_eq!(root.calls[0].selector, "a9059cbb");
+        assert_eq!(
+            layers(&root),
+            ["Safe transaction to 0xA0b86991c6218b36c1d19D4a2e9Eb0cE3606eB48"]
+        );
+        assert_eq!(root.params[3].types[0].solidity, "uint8");
+        let tree = render_call_tree(&root);
+        assert!(tree.contains("(Safe transaction to 0xA0b8"), "{}", tree);
+
+        // A delegatecall with value, wrapping another Safe's transaction.
+        let inner = safe(H160::repeat_byte(0x22), 0u64, 0, transfer());
+        let root = decode(safe(H160::repeat_byte(0x33), 5u64, 1, inner));
+        assert_eq!(
+            layers(&root),
+            [format!(
+                "Safe transaction to 0x{} value 5 (delegatecall)",
+                "33".repeat(20)
+            )]
+        );
+        let nested = &root.calls[0];
+        assert!(nested.signature_guess.starts_with("execTransaction("));
+        assert_eq!(nested.calls[0].selector, "a9059cbb");
+        assert_eq!(nested.calls[0].span.start, nested.span.start + 356);
+    }
+
+    fn safe(to: H160, value: u64, operation: u64, call: CalldataBuilder) -> CalldataBuilder {
+        CalldataBuilder::new(
+            "execTransaction(address,uint256,bytes,uint8,uint256,uint256,uint256,address,address,bytes)",
+        )
+        .address(to)
+        .uint(value)
+        .call(&call)
+        .uint(operation)
+        .uint(0u64)
+        .uint(0u64)
+        .uint(0u64)
+        .address(H160::zero())
+        .address(H160::zero())
+        .bytes(&[0x1c; 65])
+    }
+
+    #[test]
+    fn test_wrappers_user_ops() {
+        let op = |sender: u8, call: &CalldataBuilder| {
+            let mut fields = vec![
+                AbiValue::Address(H160::repeat_byte(sender)),
+                AbiValue::Uint(U256::from(7)),
+                AbiValue::Bytes(vec![]),
+                AbiValue::Bytes(call.build().unwrap()),
+            ];
+            fields.extend((0..5).map(|_| AbiValue::Uint(U256::from(100_000))));
+            fields.extend([AbiValue::Bytes(vec![]), AbiValue::Bytes(vec![0x1b; 65])]);
+            AbiValue::Tuple(fields)
+        };
+        let execute = CalldataBuilder::new("execute(address,uint256,bytes)")
+            .address(H160::repeat_byte(0x44))
+            .uint(0u64)
+            .call(&transfer());
+        let handle_ops = CalldataBuilder::new(
+            "handleOps((address,uint256,bytes,bytes,uint256,uint256,uint256,uint256,uint256,bytes,bytes)[],address)",
+        )
+        .array(vec![op(0xaa, &execute), op(0xbb, &transfer())])
+        .address(H160::repeat_byte(0xcc));
+
+        let root = decode(handle_ops);
+        assert_eq!(
+            layers(&root),
+            [
+                format!("UserOperation 0 from {}", to_checksum(&[0xaa; 20])),
+                format!("UserOperation 1 from {}", to_checksum(&[0xbb; 20])),
+            ]
+        );
+        // The account's execute is unwrapped in turn.
+        let account = &root.calls[0];
+        assert_eq!(account.signature_guess, "execute(address,uint256,bytes)");
+        assert_eq!(
+            layers(account),
+            [format!("execute to 0x{}", "44".repeat(20))]
+        );
+        assert_eq!(account.calls[0].selector, "a9059cbb");
+    }
+
+    #[test]
+    fn test_wrappers_timelock() {
+        let targets = [0x55u8, 0x66].map(|b| AbiValue::Address(H160::repeat_byte(b)));
+        let schedule = CalldataBuilder::new(
+            "scheduleBatch(address[],uint256[],bytes[],bytes32,bytes32,uint256)",
+        )
+        .array(targets.to_vec())
+        .array(vec![
+            AbiValue::Uint(U256::zero()),
+            AbiValue::Uint(U256::from(2)),
+        ])
+        .array(vec![
+            AbiValue::Bytes(transfer().build().unwrap()),
+            AbiValue::Bytes(vec![]),
+        ])
+        .fixed_bytes(&[0; 32])
+        .fixed_bytes(&[0x01; 32])
+        .uint(86_400u64);
+        let root = decode(schedule);
+        // The second only sends value, there's no call to decode.
+        assert_eq!(
+            layers(&root),
+            [format!("timelock schedule 0 to 0x{}", "55".repeat(20))]
+        );
+
+        let execute = CalldataBuilder::new("execute(address,uint256,bytes,bytes32,bytes32)")
+            .address(H160::repeat_byte(0x55))
+            .uint(0u64)
+            .call(&transfer())
+            .fixed_bytes(&[0; 32])
+            .fixed_bytes(&[0x01; 32]);
+        let root = decode(execute);
+        assert_eq!(
+            root.signature_guess,
+            "execute(address,uint256,bytes,bytes32,bytes32)"
+        );
+        assert_eq!(root.params[4].types[0].solidity, "bytes32");
+        assert_eq!(
+            layers(&root),
+            [format!("timelock execute to 0x{}", "55".repeat(20))]
+        );
+
+        // Arrays of different lengths are left to the heuristics.
+        let mismatched =
+            CalldataBuilder::new("executeBatch(address[],uint256[],bytes[],bytes32,bytes32)")
+                .array(targets.to_vec())
+                .array(vec![AbiValue::Uint(U256::zero())])
+                .array(vec![AbiValue::Bytes(transfer().build().unwrap())])
+                .fixed_bytes(&[0; 32])
+                .fixed_bytes(&[0; 32]);
+        let root = decode(mismatched);
+        assert!(root.signature_guess.starts_with("unknown_"));
+        assert!(root.calls.iter().all(|c| c.layer.is_none()));
+    }
+}
@@ -0,0 +1,109 @@
+use crate::constants::WORD;
+use crate::decode_hex;
+use crate::decoded::{hex_u128, DecodedParam};
+use serde::{Deserialize, Serialize};
+
+// ------------------------------------------------------------
+//  Text
+// ------------------------------------------------------------
+
+/// Longest string looked for, in bytes.
+pub const MAX_TEXT: usize = 4096;
+
+/// The bytes of a `string` param as text that is safe to print.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DecodedText {
+    /// The text, control and bidi characters escaped as `\n`, `\u{1b}`, ...
+    pub text: String,
+    /// The bytes weren't valid UTF-8, invalid sequences being replaced with `\u{fffd}`.
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub lossy: bool,
+}
+
+impl DecodedText {
+    pub fn new(bytes: &[u8]) -> Self {
+        match std::str::from_utf8(bytes) {
+            Ok(text) => Self {
+                text: escape_text(text),
+                lossy: false,
+            },
+            Err(_) => Self {
+                text: escape_text(&String::from_utf8_lossy(bytes)),
+                lossy: true,
+            },
+        }
+    }
+}
+
+/// Escapes what a terminal or a viewer could act on instead of showing:
+/// control characters (escape sequences included) and bidi overrides.
+/// Backslashes are doubled so the escapes can't be forged.
+pub fn escape_text(text: &str) -> String {
+    let mut out = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '\\' => out.push_str("\\\\"),
+            '\n' => out.push_str("\\n"),
+            '\r' => out.push_str("\\r"),
+            '\t' => out.push_str("\\t"),
+            c if c.is_control() || is_bidi(c) => out.push_str(&format!("\\u{{{:x}}}", c as u32)),
+            c => out.push(c),
+        }
+    }
+    out
+}
+
+/// Characters that reorder the text around them.
+fn is_bidi(c: char) -> bool {
+    matches!(c, '\u{061c}' | '\u{200e}' | '\u{200f}' | '\u{202a}'..='\u{202e}' | '\u{2066}'..='\u{2069}')
+}
+
+/// Whether the bytes read as text rather than data: no NUL, at most 1 in 8
+/// characters invalid UTF-8, at least half printable, and a letter somewhere,
+/// or else only printable ASCII with a digit, e.g. `"123"` or `"4.5"`.
+///
+/// Control characters count as text, only less of it: an escape sequence
+/// hidden in a string is still a string, and gets escaped.
+pub fn looks_like_text(bytes: &[u8]) -> bool {
+    if bytes.is_empty() || bytes.contains(&0) {
+        return false;
+    }
+    let text = String::from_utf8_lossy(bytes);
+    let (mut chars, mut invalid, mut control) = (0, 0, 0);
+    for c in text.chars() {
+        chars += 1;
+        match c {
+            char::REPLACEMENT_CHARACTER => invalid += 1,
+            c if c.is_control() => control += 1,
+            _ => {}
+        }
+    }
+    let worded = text.chars().any(char::is_alphabetic);
+    let numeric = bytes.iter().all(u8::is_ascii_graphic) && bytes.iter().any(u8::is_ascii_digit);
+    invalid * 8 <= chars && (invalid + control) * 2 <= chars && (worded || numeric)
+}
+
+/// Sets the text of each param that is the length of a string: followed by
+/// that many bytes of text, zero padded to a full word.
+pub fn attach_texts(params: &mut [DecodedParam]) {
+    for i in 0..params.len() {
+        let Some(len) = hex_u128(&params[i].raw)
+            .and_then(|len| usize::try_from(len).ok())
+            .filter(|len| (1..=MAX_TEXT).contains(len))
+        else {
+            continue;
+        };
+        let Some(words) = params.get(i + 1..i + 1 + len.div_ceil(WORD)) else {
+            continue;
+        };
+        if words.iter().any(|w| w.raw.len() != WORD * 2) {
+            continue;
+        }
+        let bytes = decode_hex(&words.iter().map(|w| w.raw.as_str()).collect::<String>());
+        let (text, padding) = bytes.split_at(len);
+        if padding.iter().all(|b| *b == 0) && looks_like_text(text) {
+            params[i].text = Some(DecodedText::new(text));
+        }
+    }
+}
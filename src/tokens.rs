@@ -0,0 +1,166 @@
+use crate::decoded::DecodedCalldata;
+use crate::render::labels::Labels;
+use crate::render::numeric::{format_uint, NumberFormat};
+use primitive_types::U256;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+
+// ------------------------------------------------------------
+//  Token database
+// ------------------------------------------------------------
+
+/// Chain names as written in the config, and their chain id.
+pub const CHAINS: &[(&str, u64)] = &[
+    ("mainnet", 1),
+    ("ethereum", 1),
+    ("optimism", 10),
+    ("bsc", 56),
+    ("polygon", 137),
+    ("base", 8453),
+    ("arbitrum", 42161),
+    ("sepolia", 11155111),
+];
+
+/// Popular tokens of the major chains, one `chain 0xaddress symbol decimals`
+/// per line as read by `Tokens::from_text`.
+pub const BUILTIN: &str = include_str!("tokens.txt");
+
+/// Chain id of a chain name of `CHAINS` or a decimal chain id.
+pub fn chain_id(chain: &str) -> Option<u64> {
+    let chain = chain.trim().to_lowercase();
+    CHAINS
+        .iter()
+        .find(|(name, _)| *name == chain)
+        .map(|(_, id)| *id)
+        .or_else(|| chain.parse().ok())
+}
+
+/// An ERC-20 token, as its amounts are written.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Token {
+    pub symbol: String,
+    pub decimals: u8,
+}
+
+impl Token {
+    /// A raw amount scaled by the decimals, e.g. `1.5 USDC` for 1500000,
+    /// `unlimited USDC` for the max `uint256`.
+    pub fn amount(&self, v: U256) -> String {
+        match v == U256::MAX {
+            true => format!("unlimited {}", self.symbol),
+            false => format!(
+                "{} {}",
+                format_uint(v, NumberFormat::Units(self.decimals as u32)),
+                self.symbol
+            ),
+        }
+    }
+}
+
+/// Tokens keyed by chain id and lowercase `0x` address, read without any
+/// network access: the amounts and addresses of a decode get their token
+/// with no RPC endpoint configured.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Tokens(BTreeMap<(u64, String), Token>);
+
+impl Tokens {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// The `BUILTIN` tokens.
+    pub fn builtin() -> Self {
+        Self::from_text(BUILTIN).expect("builtin tokens parse")
+    }
+
+    /// Parses one `chain 0xaddress symbol decimals` token per line, the chain
+    /// a name of `CHAINS` or a chain id, `#` starting a comment.
+    pub fn from_text(text: &str) -> Result<Self, String> {
+        let mut tokens = Self::new();
+        for (n, line) in text.lines().enumerate() {
+            let line = line.split('#').next().unwrap_or_default();
+            let fields = line.split_whitespace().collect::<Vec<_>>();
+            let token = match fields[..] {
+                [] => continue,
+                [chain, address, symbol, decimals] => chain_id(chain)
+                    .zip(decimals.parse().ok())
+                    .filter(|_| is_address(address))
+                    .map(|(chain, decimals)| (chain, address, symbol, decimals)),
+                _ => None,
+            };
+            let Some((chain, address, symbol, decimals)) = token else {
+                return Err(format!(
+                    "line {}: expected `chain 0xaddress symbol decimals`, got {:?}",
+                    n + 1,
+                    line.trim()
+                ));
+            };
+            tokens.insert(chain, address, symbol, decimals);
+        }
+        Ok(tokens)
+    }
+
+    pub fn insert(&mut self, chain: u64, address: &str, symbol: &str, decimals: u8) {
+        let token = Token {
+            symbol: symbol.to_string(),
+            decimals,
+        };
+        self.0.insert((chain, address.to_lowercase()), token);
+    }
+
+    pub fn get(&self, chain: u64, address: &str) -> Option<&Token> {
+        self.0.get(&(chain, address.to_lowercase()))
+    }
+
+    /// Adds the tokens of `other`, overriding existing ones.
+    pub fn extend(&mut self, other: Tokens) {
+        self.0.extend(other.0);
+    }
+
+    pub fn len(&self) -> usize {
+        self.0.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+
+    /// The symbol of each token of `chain`, as address labels.
+    pub fn labels(&self, chain: u64) -> Labels {
+        let mut labels = Labels::new();
+        let tokens = self.0.range((chain, String::new())..);
+        for ((_, address), token) in tokens.take_while(|((c, _), _)| *c == chain) {
+            labels.insert(address, &token.symbol);
+        }
+        labels
+    }
+}
+
+fn is_address(s: &str) -> bool {
+    s.len() == 42 && s.starts_with("0x") && s[2..].bytes().all(|b| b.is_ascii_hexdigit())
+}
+
+/// Selectors of the ERC-20 methods moving an amount, and the param it's in.
+const AMOUNTS: [(&str, usize); 3] = [
+    // transfer(address,uint256)
+    ("a9059cbb", 1),
+    // approve(address,uint256)
+    ("095ea7b3", 1),
+    // transferFrom(address,address,uint256)
+    ("23b872dd", 2),
+];
+
+/// Sets the `amount` in `token`'s units of an ERC-20 `transfer`, `approve` or
+/// `transferFrom` sent to it, e.g. `1.5 USDC` for `1500000`.
+pub fn apply_token_amount(root: &mut DecodedCalldata, token: &Token) {
+    let Some((_, index)) = AMOUNTS.iter().find(|(s, _)| *s == root.selector) else {
+        return;
+    };
+    if root.params.len() != index + 1 {
+        return;
+    }
+    let param = &mut root.params[*index];
+    if let Ok(v) = U256::from_str_radix(&param.raw, 16) {
+        param.amount = Some(token.amount(v));
+    }
+}
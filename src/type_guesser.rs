@@ -6,6 +6,41 @@ impl ParamTypes {
     pub fn new(t: Vec<Types>) -> Self {
         Self(t)
     }
+
+    /// All candidate types, in the order they were guessed.
+    pub fn types(&self) -> &[Types] {
+        &self.0
+    }
+}
+
+/// How well a word fits a guessed type by its shape, from 0 to 1: how it's
+/// padded and how large its value is. Words that aren't a full word fit
+/// every type.
+///
+/// Small numbers are rarely addresses, numbers filling the word rarely
+/// amounts, and fixed bytes are left-aligned and padded on the right.
+pub fn fit(kind: &Types, word: &[u8]) -> f64 {
+    if word.len() != 32 {
+        return 1.0;
+    }
+    let leading = word.iter().take_while(|b| **b == 0).count();
+    let trailing = word.iter().rev().take_while(|b| **b == 0).count();
+    match kind {
+        Types::Address => match leading {
+            12..=17 => 1.0,
+            18..=23 => 0.5,
+            _ => 0.25,
+        },
+        Types::Uint | Types::Uint8 | Types::ZeroUint | Types::Int128 => match leading {
+            0..=3 => 0.5,
+            _ => 1.0,
+        },
+        Types::Bytes | Types::Bytes1 | Types::Selector => match (leading, trailing) {
+            (0, 1..) => 1.0,
+            _ => 0.5,
+        },
+        _ => 1.0,
+    }
 }
 
 impl std::fmt::Debug for ParamTypes {
@@ -0,0 +1,104 @@
+use crate::decoded::DecodedCalldata;
+use crate::encoder::parse_function;
+use ethers::abi::{decode, encode, ParamType, Token};
+use ethers::types::U256;
+
+// ------------------------------------------------------------
+//  Round-trip verification
+// ------------------------------------------------------------
+
+/// Decodes a call's params as the inputs of `signature` and encodes the
+/// values again, which only gives back the same bytes when the types fit.
+///
+/// Guesses this rules out: an address with bits above its 160, a `bool`
+/// other than 0 or 1, a `uint8` over 255, `bytes4` that aren't padded on the
+/// right, an offset read as a number by a signature with fewer params than
+/// words, or a word read as an offset that an encoder wouldn't have written.
+/// Only the params are compared, so the signature may be a guess for an
+/// unknown selector, e.g. `unknown_5d842074(uint256,uint256[])`.
+///
+/// ## Params
+/// 1. signature - e.g. `transfer(address,uint256)`.
+/// 2. calldata - the call, selector included.
+///
+/// ## Returns
+/// 1. The values, or where the re-encoding differs from the call.
+pub fn verify(signature: &str, calldata: &[u8]) -> Result<Vec<Token>, String> {
+    let function = parse_function(signature)?;
+    let Some(params) = calldata.get(4..) else {
+        return Err(format!("{} bytes is too short for a call", calldata.len()));
+    };
+    let kinds = function
+        .inputs
+        .iter()
+        .map(|input| input.kind.clone())
+        .collect::<Vec<ParamType>>();
+    let tokens = decode(&kinds, params).map_err(|e| format!("{}: {}", signature, e))?;
+    let tokens = kinds
+        .iter()
+        .zip(tokens)
+        .map(|(kind, token)| narrow(kind, token))
+        .collect::<Vec<Token>>();
+
+    let encoded = encode(&tokens);
+    match encoded.iter().zip(params).position(|(a, b)| a != b) {
+        Some(i) => Err(format!(
+            "{}: word {} re-encodes differently",
+            signature,
+            i / 32
+        )),
+        None if encoded.len() != params.len() => Err(format!(
+            "{}: re-encodes to {} bytes of {}",
+            signature,
+            encoded.len(),
+            params.len()
+        )),
+        None => Ok(tokens),
+    }
+}
+
+/// Whether the call re-encodes from `signature` (see `verify`).
+pub fn round_trips(signature: &str, calldata: &[u8]) -> bool {
+    verify(signature, calldata).is_ok()
+}
+
+/// The bytes of a decoded call: its selector then its words.
+pub fn call_bytes(call: &DecodedCalldata) -> Vec<u8> {
+    let mut hex = call.selector.clone();
+    for param in call.params.iter() {
+        hex.push_str(&param.raw);
+    }
+    crate::decode_hex(&hex)
+}
+
+/// The value as its type holds it: numbers cut to their bits, which ethers
+/// decodes whole, so that a `uint8` over 255 doesn't re-encode as itself.
+fn narrow(kind: &ParamType, token: Token) -> Token {
+    match (kind, token) {
+        (ParamType::Uint(bits), Token::Uint(v)) if *bits < 256 => {
+            Token::Uint(v & ((U256::one() << *bits) - 1))
+        }
+        (ParamType::Int(bits), Token::Int(v)) if *bits < 256 => {
+            let mask = (U256::one() << *bits) - 1;
+            // Sign-extended from the type's top bit.
+            match v.bit(*bits - 1) {
+                true => Token::Int(v | !mask),
+                false => Token::Int(v & mask),
+            }
+        }
+        (ParamType::Array(inner), Token::Array(tokens)) => {
+            Token::Array(tokens.into_iter().map(|t| narrow(inner, t)).collect())
+        }
+        (ParamType::FixedArray(inner, _), Token::FixedArray(tokens)) => {
+            Token::FixedArray(tokens.into_iter().map(|t| narrow(inner, t)).collect())
+        }
+        (ParamType::Tuple(kinds), Token::Tuple(tokens)) => Token::Tuple(
+            kinds
+                .iter()
+                .zip(tokens)
+                .map(|(kind, t)| narrow(kind, t))
+                .collect(),
+        ),
+        (_, token) => token,
+    }
+}
@@ -0,0 +1,166 @@
+use crate::constants::WORD;
+use crate::decode_hex;
+use crate::decoded::{hex_u128, DecodedParam};
+use serde::{Deserialize, Serialize};
+
+// ------------------------------------------------------------
+//  Wallet signatures
+// ------------------------------------------------------------
+
+/// Longest `bytes` looked at for a signature.
+pub const MAX_SIGNATURE: usize = 8192;
+
+/// Word ERC-6492 appends to the signature of a wallet that isn't deployed yet.
+pub const ERC6492_SUFFIX: &str = "6492649264926492649264926492649264926492649264926492649264926492";
+
+/// Half the secp256k1 order, the largest `s` of a non-malleable signature (EIP-2).
+const HALF_ORDER: &str = "7fffffffffffffffffffffffffffffff5d576e7357a4501ddfe92f46681b20a0";
+
+/// A signature found in the bytes of a `bytes` param, e.g. the one passed to
+/// EIP-1271's `isValidSignature(bytes32,bytes)`.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "kind", rename_all = "camelCase")]
+pub enum DecodedSignature {
+    /// A packed 65-byte `r ‖ s ‖ v` ECDSA signature.
+    #[serde(rename_all = "camelCase")]
+    Ecdsa {
+        /// `0x` prefixed, 32 bytes.
+        r: String,
+        /// `0x` prefixed, 32 bytes.
+        s: String,
+        /// Recovery id, 27 or 28 (0 or 1 for some signers).
+        v: u8,
+        /// `s` is in the upper half of the curve order: `ecrecover` accepts it
+        /// but OpenZeppelin's `ECDSA` and most wallets don't.
+        #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+        high_s: bool,
+    },
+    /// An ERC-6492 signature of a wallet that isn't deployed yet: the factory
+    /// and calldata deploying it, and the signature to check once it is.
+    #[serde(rename_all = "camelCase")]
+    Erc6492 {
+        factory: String,
+        /// `0x` prefixed.
+        factory_calldata: String,
+        /// The wrapped signature, `0x` prefixed.
+        signature: String,
+        /// The wrapped signature decoded, when it's one this module reads.
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        inner: Option<Box<DecodedSignature>>,
+    },
+}
+
+impl DecodedSignature {
+    /// Reads the bytes of a `bytes` param as a signature: 65 bytes ending with
+    /// a valid `v`, or a signature wrapped by ERC-6492.
+    pub fn decode(bytes: &[u8]) -> Option<Self> {
+        match bytes.len() == 65 {
+            true => ecdsa(bytes),
+            false => erc6492(bytes),
+        }
+    }
+
+    /// The signature on one line, e.g. `ECDSA v=27 r=0x.. s=0x..`.
+    pub fn label(&self) -> String {
+        match self {
+            DecodedSignature::Ecdsa { r, s, v, high_s } => format!(
+                "ECDSA v={} r={} s={}{}",
+                v,
+                r,
+                s,
+                match high_s {
+                    true => " (high s)",
+                    false => "",
+                }
+            ),
+            DecodedSignature::Erc6492 {
+                factory,
+                factory_calldata,
+                signature,
+                inner,
+            } => format!(
+                "ERC-6492 deployed by {} with {} bytes of calldata, then {}",
+                factory,
+                (factory_calldata.len() - 2) / 2,
+                match inner {
+                    Some(inner) => inner.label(),
+                    None => format!("{} signature bytes", (signature.len() - 2) / 2),
+                }
+            ),
+        }
+    }
+}
+
+fn ecdsa(bytes: &[u8]) -> Option<DecodedSignature> {
+    let v = bytes[64];
+    if !matches!(v, 0 | 1 | 27 | 28) {
+        return None;
+    }
+    Some(DecodedSignature::Ecdsa {
+        r: format!("0x{}", faster_hex::hex_string(&bytes[..32])),
+        s: format!("0x{}", faster_hex::hex_string(&bytes[32..64])),
+        v,
+        high_s: bytes[32..64] > decode_hex(HALF_ORDER)[..],
+    })
+}
+
+/// `abi.encode(address factory, bytes factoryCalldata, bytes signature)`
+/// followed by `ERC6492_SUFFIX`.
+fn erc6492(bytes: &[u8]) -> Option<DecodedSignature> {
+    let (body, suffix) = bytes.split_at(bytes.len().checked_sub(WORD)?);
+    if faster_hex::hex_string(suffix) != ERC6492_SUFFIX {
+        return None;
+    }
+    let factory = body.get(..WORD)?;
+    if factory[..12].iter().any(|b| *b != 0) {
+        return None;
+    }
+    let factory_calldata = dynamic_bytes(body, 1)?;
+    let signature = dynamic_bytes(body, 2)?;
+    Some(DecodedSignature::Erc6492 {
+        factory: format!("0x{}", faster_hex::hex_string(&factory[12..])),
+        factory_calldata: format!("0x{}", faster_hex::hex_string(factory_calldata)),
+        signature: format!("0x{}", faster_hex::hex_string(signature)),
+        inner: DecodedSignature::decode(signature).map(Box::new),
+    })
+}
+
+/// The `bytes` the word at `index` of `encoded` is the offset of.
+fn dynamic_bytes(encoded: &[u8], index: usize) -> Option<&[u8]> {
+    let offset = word_usize(encoded, index * WORD)?;
+    let len = word_usize(encoded, offset)?;
+    let start = offset.checked_add(WORD)?;
+    encoded.get(start..start.checked_add(len)?)
+}
+
+fn word_usize(encoded: &[u8], at: usize) -> Option<usize> {
+    let word = encoded.get(at..at.checked_add(WORD)?)?;
+    hex_u128(&faster_hex::hex_string(word)).and_then(|n| usize::try_from(n).ok())
+}
+
+/// Sets the signature of each param that is the length of a `bytes` holding
+/// one, as `attach_texts` does for strings.
+pub fn attach_signatures(params: &mut [DecodedParam]) {
+    for i in 0..params.len() {
+        if params[i].text.is_some() {
+            continue;
+        }
+        let Some(len) = hex_u128(&params[i].raw)
+            .and_then(|len| usize::try_from(len).ok())
+            .filter(|len| (65..=MAX_SIGNATURE).contains(len))
+        else {
+            continue;
+        };
+        let Some(words) = params.get(i + 1..i + 1 + len.div_ceil(WORD)) else {
+            continue;
+        };
+        if words.iter().any(|w| w.raw.len() != WORD * 2) {
+            continue;
+        }
+        let bytes = decode_hex(&words.iter().map(|w| w.raw.as_str()).collect::<String>());
+        let (signature, padding) = bytes.split_at(len);
+        if padding.iter().all(|b| *b == 0) {
+            params[i].signature = DecodedSignature::decode(signature);
+        }
+    }
+}
@@ -0,0 +1,27 @@
+use crate::schema::JsonOutput;
+use serde::Serialize;
+use serde_wasm_bindgen::Serializer;
+use wasm_bindgen::prelude::*;
+
+// ------------------------------------------------------------
+//  WebAssembly
+// ------------------------------------------------------------
+
+/// Decodes hex calldata into the JSON output as a plain JS object, the shape
+/// documented on [`JsonOutput`].
+///
+/// ```js
+/// import init, { decode } from "calldata-decoder";
+/// await init();
+/// decode("0xa9059cbb...").root.params[0].value;
+/// ```
+///
+/// ## Returns
+/// 1. The decode, or an error message when the input isn't calldata or is over the default limits.
+#[wasm_bindgen]
+pub fn decode(hex: &str) -> Result<JsValue, JsValue> {
+    let output = JsonOutput::from_hex(hex).map_err(|e| JsValue::from_str(&e))?;
+    output
+        .serialize(&Serializer::json_compatible())
+        .map_err(JsValue::from)
+}
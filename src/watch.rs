@@ -0,0 +1,127 @@
+use crate::lazy::LazyCall;
+use crate::rpc::DecodedTransaction;
+use ethers::providers::{Middleware, StreamExt};
+use ethers::types::{Address, Transaction};
+
+// ------------------------------------------------------------
+//  Watch
+// ------------------------------------------------------------
+
+/// Which transactions are streamed.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum WatchMode {
+    /// Transactions as they enter the node's mempool.
+    Pending,
+    /// Transactions of each new block.
+    Confirmed,
+}
+
+/// Narrows down the streamed transactions; empty lists match everything.
+#[derive(Debug, Clone, Default)]
+pub struct TxFilter {
+    /// Selectors (8 hex chars, `0x` optional) the call must target.
+    pub selectors: Vec<String>,
+    /// Addresses the transaction must be sent to.
+    pub to: Vec<Address>,
+    /// Also match selectors of nested calls, not only the top-level one.
+    pub nested: bool,
+}
+
+impl TxFilter {
+    /// Cheap check on the raw transaction, before decoding it.
+    pub fn matches_transaction(&self, tx: &Transaction) -> bool {
+        if !self.to.is_empty() && !tx.to.map(|to| self.to.contains(&to)).unwrap_or(false) {
+            return false;
+        }
+        if self.selectors.is_empty() {
+            return true;
+        }
+        if self.nested {
+            // Only parses nested calls until a selector matches.
+            let call = LazyCall::from_bytes(tx.input.to_vec());
+            return call.any_selector(|s| s.len() == 4 && self.has_selector(&hex_selector(s)));
+        }
+        tx.input.len() >= 4 && self.has_selector(&hex_selector(&tx.input[..4]))
+    }
+
+    /// Full check on the decoded transaction.
+    pub fn matches(&self, tx: &DecodedTransaction) -> bool {
+        if !self.to.is_empty() && !tx.to.map(|to| self.to.contains(&to)).unwrap_or(false) {
+            return false;
+        }
+        if self.selectors.is_empty() {
+            return true;
+        }
+        match self.nested {
+            true => tx.decoded.walk().iter().any(|c| self.has_selector(&c.selector)),
+            false => self.has_selector(&tx.decoded.selector),
+        }
+    }
+
+    fn has_selector(&self, selector: &str) -> bool {
+        self.selectors
+            .iter()
+            .any(|s| s.trim_start_matches("0x").eq_ignore_ascii_case(selector))
+    }
+}
+
+fn hex_selector(bytes: &[u8]) -> String {
+    bytes.iter().map(|b| format!("{:02x}", b)).collect()
+}
+
+/// Decodes a transaction if it passes the filter.
+pub fn filter_transaction(tx: &Transaction, filter: &TxFilter) -> Option<DecodedTransaction> {
+    if !filter.matches_transaction(tx) {
+        return None;
+    }
+    DecodedTransaction::from_transaction(tx)
+        .ok()
+        .filter(|decoded| filter.matches(decoded))
+}
+
+/// Streams new transactions from the node, calling `on_tx` with each one that matches.
+///
+/// Runs until the node's filter stream ends or an RPC call fails.
+///
+/// ## Params
+/// 1. provider - connected node (HTTP or WebSocket).
+/// 2. filter - which transactions to report.
+/// 3. mode - pending (mempool) or confirmed (new blocks) transactions.
+/// 4. on_tx - called with each decoded match.
+pub async fn watch<M: Middleware>(
+    provider: &M,
+    filter: &TxFilter,
+    mode: WatchMode,
+    mut on_tx: impl FnMut(DecodedTransaction),
+) -> Result<(), String> {
+    match mode {
+        WatchMode::Pending => {
+            let mut stream = provider
+                .watch_pending_transactions()
+                .await
+                .map_err(|e| e.to_string())?;
+            while let Some(hash) = stream.next().await {
+                // Pending txs can be dropped before we get to fetch them.
+                let Ok(Some(tx)) = provider.get_transaction(hash).await else { continue };
+                if let Some(decoded) = filter_transaction(&tx, filter) {
+                    on_tx(decoded);
+                }
+            }
+        }
+        WatchMode::Confirmed => {
+            let mut stream = provider.watch_blocks().await.map_err(|e| e.to_string())?;
+            while let Some(hash) = stream.next().await {
+                let block = provider
+                    .get_block_with_txs(hash)
+                    .await
+                    .map_err(|e| e.to_string())?;
+                for tx in block.iter().flat_map(|b| b.transactions.iter()) {
+                    if let Some(decoded) = filter_transaction(tx, filter) {
+                        on_tx(decoded);
+                    }
+                }
+            }
+        }
+    }
+    Ok(())
+}
@@ -0,0 +1,276 @@
+use crate::constants::*;
+use crate::decoded::DecodedCalldata;
+use crate::protocol::{Context, ProtocolDecoder};
+use crate::render::address::to_checksum;
+use crate::signatures::{selector_bytes, Layout};
+use crate::word_usize;
+use primitive_types::U256;
+use std::sync::OnceLock;
+
+// ------------------------------------------------------------
+//  Wrappers
+// ------------------------------------------------------------
+
+/// Where a wrapper keeps the calls it makes.
+enum Inner {
+    /// One call, its `to`, `value` and `data` at head words of the params.
+    /// `operation` is the Safe's word choosing a call or a delegatecall.
+    Call {
+        to: usize,
+        value: usize,
+        data: usize,
+        operation: Option<usize>,
+    },
+    /// Calls spread over arrays of targets, values and payloads at head words.
+    Batch {
+        to: usize,
+        value: usize,
+        data: usize,
+    },
+    /// ERC-4337 user operations, an array of tuples at head word 0, with the
+    /// sender and the callData at words of each tuple.
+    UserOps { sender: usize, data: usize },
+}
+
+struct Wrapper {
+    signature: &'static str,
+    /// What the calls it makes are, e.g. `Safe transaction`.
+    name: &'static str,
+    inner: Inner,
+}
+
+/// Call made by the params `to`, `value` and `data`, the params of most.
+const fn call(signature: &'static str, name: &'static str) -> Wrapper {
+    Wrapper {
+        signature,
+        name,
+        inner: Inner::Call {
+            to: 0,
+            value: 1,
+            data: 2,
+            operation: None,
+        },
+    }
+}
+
+const fn batch(signature: &'static str, name: &'static str) -> Wrapper {
+    Wrapper {
+        signature,
+        name,
+        inner: Inner::Batch {
+            to: 0,
+            value: 1,
+            data: 2,
+        },
+    }
+}
+
+const WRAPPERS: [Wrapper; 8] = [
+    Wrapper {
+        signature: "execTransaction(address,uint256,bytes,uint8,uint256,uint256,uint256,address,address,bytes)",
+        name: "Safe transaction",
+        inner: Inner::Call {
+            to: 0,
+            value: 1,
+            data: 2,
+            operation: Some(3),
+        },
+    },
+    // Smart accounts, e.g. ERC-4337's SimpleAccount.
+    call("execute(address,uint256,bytes)", "execute"),
+    // EntryPoint v0.6.
+    Wrapper {
+        signature: "handleOps((address,uint256,bytes,bytes,uint256,uint256,uint256,uint256,uint256,bytes,bytes)[],address)",
+        name: "UserOperation",
+        inner: Inner::UserOps { sender: 0, data: 3 },
+    },
+    // EntryPoint v0.7, gas limits and fees packed in bytes32.
+    Wrapper {
+        signature: "handleOps((address,uint256,bytes,bytes,bytes32,uint256,bytes32,bytes,bytes)[],address)",
+        name: "UserOperation",
+        inner: Inner::UserOps { sender: 0, data: 3 },
+    },
+    // OpenZeppelin's TimelockController.
+    call(
+        "schedule(address,uint256,bytes,bytes32,bytes32,uint256)",
+        "timelock schedule",
+    ),
+    call(
+        "execute(address,uint256,bytes,bytes32,bytes32)",
+        "timelock execute",
+    ),
+    batch(
+        "scheduleBatch(address[],uint256[],bytes[],bytes32,bytes32,uint256)",
+        "timelock schedule",
+    ),
+    batch(
+        "executeBatch(address[],uint256[],bytes[],bytes32,bytes32)",
+        "timelock execute",
+    ),
+];
+
+/// Envelopes whose calls are made to another contract: Safe's
+/// `execTransaction`, smart accounts' `execute(address,uint256,bytes)`,
+/// ERC-4337's `handleOps` and OpenZeppelin's TimelockController `schedule`
+/// and `execute` (batches too).
+///
+/// The wrapper is typed by its signature, and each call in it is read from
+/// its `data` by the ABI layout and decoded in turn, its `layer` saying how
+/// it's made: `Safe transaction to 0x… value 1 (delegatecall)`,
+/// `UserOperation 0 from 0x…`, `timelock schedule 1 to 0x…`.
+pub struct WrapperDecoder;
+
+impl WrapperDecoder {
+    fn wrapper(call: &[u8]) -> Option<&'static Wrapper> {
+        static SELECTORS: OnceLock<Vec<[u8; 4]>> = OnceLock::new();
+        let selectors = SELECTORS.get_or_init(|| {
+            WRAPPERS
+                .iter()
+                .map(|w| selector_bytes(w.signature))
+                .collect()
+        });
+        let i = selectors.iter().position(|s| call.starts_with(s))?;
+        Some(&WRAPPERS[i])
+    }
+}
+
+impl ProtocolDecoder for WrapperDecoder {
+    fn name(&self) -> &str {
+        "wrappers"
+    }
+
+    fn claims(&self, call: &[u8]) -> bool {
+        Self::wrapper(call).is_some()
+    }
+
+    fn decode(&self, call: &[u8], cx: &Context) -> Result<DecodedCalldata, String> {
+        let wrapper = Self::wrapper(call).ok_or("not a wrapper")?;
+        let layout = Layout::parse(wrapper.signature).ok_or("unparsable signature")?;
+        let body = &call[SELECTOR..];
+        if body.len() < layout.head_words * WORD {
+            return Err(format!(
+                "{} needs {} words",
+                wrapper.signature, layout.head_words
+            ));
+        }
+        let calls = inner_calls(body, wrapper)?;
+
+        let mut root = cx.node(call);
+        for (i, ty) in layout.inputs.iter().enumerate() {
+            root.params[i].prefer(ty);
+        }
+        root.signature_guess = wrapper.signature.to_string();
+        for (layer, at, len) in calls {
+            // Plain transfers carry no call.
+            if len < SELECTOR {
+                continue;
+            }
+            let (at, end) = (SELECTOR + at, SELECTOR + at + len);
+            if let Some(mut nested) = cx.nested(&call[at..end], at) {
+                nested.layer = Some(layer);
+                root.calls.push(nested);
+            }
+        }
+        Ok(root)
+    }
+}
+
+/// The layer, byte offset in `body` and length of each call the wrapper makes.
+fn inner_calls(body: &[u8], wrapper: &Wrapper) -> Result<Vec<(String, usize, usize)>, String> {
+    let word = |at: usize| {
+        at.checked_add(WORD)
+            .and_then(|end| body.get(at..end))
+            .ok_or_else(|| format!("no word at byte {} of the params", at))
+    };
+    let number = |at: usize| {
+        word(at).and_then(|w| {
+            word_usize(w)
+                .filter(|v| *v <= body.len())
+                .ok_or_else(|| format!("no valid word at byte {} of the params", at))
+        })
+    };
+    let address = |at: usize| word(at).map(|w| to_checksum(&w[12..]));
+    let amount = |at: usize| word(at).map(U256::from_big_endian);
+    // The bytes whose offset, counted from `base`, is the word at `head`.
+    let bytes = |base: usize, head: usize| {
+        let start = base + number(head)?;
+        let (at, len) = (start + WORD, number(start)?);
+        match at + len <= body.len() {
+            true => Ok((at, len)),
+            false => Err(format!("bytes at {} run past the params", start)),
+        }
+    };
+    let to = |target: String, value: U256| match value.is_zero() {
+        true => format!("to {}", target),
+        false => format!("to {} value {}", target, value),
+    };
+
+    match wrapper.inner {
+        Inner::Call {
+            to: target,
+            value,
+            data,
+            operation,
+        } => {
+            let mut layer = format!(
+                "{} {}",
+                wrapper.name,
+                to(address(target * WORD)?, amount(value * WORD)?)
+            );
+            if let Some(operation) = operation {
+                if !amount(operation * WORD)?.is_zero() {
+                    layer.push_str(" (delegatecall)");
+                }
+            }
+            let (at, len) = bytes(0, data * WORD)?;
+            Ok(vec![(layer, at, len)])
+        }
+        Inner::Batch {
+            to: targets,
+            value: values,
+            data: payloads,
+        } => {
+            let (targets, values, payloads) = (
+                number(targets * WORD)?,
+                number(values * WORD)?,
+                number(payloads * WORD)?,
+            );
+            let count = number(targets)?;
+            if number(values)? != count || number(payloads)? != count {
+                return Err("targets, values and payloads differ in length".to_string());
+            }
+            (0..count)
+                .map(|i| {
+                    let layer = format!(
+                        "{} {} {}",
+                        wrapper.name,
+                        i,
+                        to(
+                            address(targets + WORD + i * WORD)?,
+                            amount(values + WORD + i * WORD)?
+                        )
+                    );
+                    let (at, len) = bytes(payloads + WORD, payloads + WORD + i * WORD)?;
+                    Ok((layer, at, len))
+                })
+                .collect()
+        }
+        Inner::UserOps { sender, data } => {
+            let ops = number(0)?;
+            let count = number(ops)?;
+            (0..count)
+                .map(|i| {
+                    let op = ops + WORD + number(ops + WORD + i * WORD)?;
+                    let layer = format!(
+                        "{} {} from {}",
+                        wrapper.name,
+                        i,
+                        address(op + sender * WORD)?
+                    );
+                    let (at, len) = bytes(op, op + data * WORD)?;
+                    Ok((layer, at, len))
+                })
+                .collect()
+        }
+    }
+}
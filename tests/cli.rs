@@ -0,0 +1,55 @@
+/*
+cargo test --test cli -- --nocapture --test-threads=1
+*/
+#![cfg(feature = "cli")]
+
+use calldata_decoder::corpus::MULTICALL;
+use std::process::Command;
+
+/// The functions of the two calls in `MULTICALL`.
+const ABI: &str = r#"[
+    {"type":"function","name":"refundETH","inputs":[],"outputs":[],"stateMutability":"payable"},
+    {"type":"function","name":"mint","inputs":[{"name":"params","type":"tuple","components":[
+        {"name":"token0","type":"address"},{"name":"token1","type":"address"},
+        {"name":"fee","type":"uint24"},{"name":"tickLower","type":"int24"},
+        {"name":"tickUpper","type":"int24"},{"name":"amount0Desired","type":"uint256"},
+        {"name":"amount1Desired","type":"uint256"},{"name":"amount0Min","type":"uint256"},
+        {"name":"amount1Min","type":"uint256"},{"name":"recipient","type":"address"},
+        {"name":"deadline","type":"uint256"}]}],"outputs":[],"stateMutability":"payable"}
+]"#;
+
+fn decode(args: &[&str]) -> serde_json::Value {
+    let output = Command::new(env!("CARGO_BIN_EXE_calldata-decoder"))
+        .arg("decode")
+        .args(args)
+        .output()
+        .unwrap();
+    assert!(
+        output.status.success(),
+        "{}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+    serde_json::from_slice(&output.stdout).unwrap()
+}
+
+#[test]
+fn test_cli_abi_multicall() {
+    let path = std::env::temp_dir().join(format!("cli-abi-{}.json", std::process::id()));
+    std::fs::write(&path, ABI).unwrap();
+    let output = decode(&[MULTICALL, "--json", "--abi", path.to_str().unwrap()]);
+    std::fs::remove_file(&path).unwrap();
+
+    // The multicall decoder claims the call; its calls are typed by the ABI.
+    let calls = output["root"]["calls"].as_array().unwrap();
+    let signatures = calls
+        .iter()
+        .map(|call| call["signatureGuess"].as_str().unwrap())
+        .collect::<Vec<_>>();
+    assert_eq!(
+        signatures,
+        [
+            "mint((address,address,uint24,int24,int24,uint256,uint256,uint256,uint256,address,uint256))",
+            "refundETH()"
+        ]
+    );
+}